@@ -0,0 +1,124 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Benchmarks for pattern matching on the routing hot path
+
+use cim_subject::{
+    Pattern,
+    SubjectRef,
+};
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+
+/// Patterns a typical router subscribes with: full literals, wildcards in
+/// every position and open-ended tails
+const PATTERNS: [&str; 10] = [
+    "orders.order.placed.v1",
+    "orders.order.cancelled.v1",
+    "orders.*.shipped.v1",
+    "orders.order.*.v2",
+    "inventory.>",
+    "billing.invoice.*.v2",
+    "billing.*.*.v1",
+    "*.audit.>",
+    "*.*.deleted.v1",
+    "payments.card.>",
+];
+
+/// Subjects that match none of [`PATTERNS`], including near misses that
+/// share a prefix or differ only in token count
+const MISMATCHING: [&str; 8] = [
+    "shipping.parcel.dispatched.v1",
+    "users.person.registered.v3",
+    "orders.order.refunded.v1",
+    "orders.return.shipped.v3",
+    "billing.invoice.voided.v1",
+    "billing.statement.issued.v3",
+    "identity.session.expired.v1",
+    "payments.bank.settled.v1",
+];
+
+/// Subjects that each match at least one of [`PATTERNS`]
+const MATCHING: [&str; 6] = [
+    "orders.order.placed.v1",
+    "orders.parcel.shipped.v1",
+    "inventory.item.restocked.v1",
+    "billing.invoice.paid.v2",
+    "users.audit.login.v1",
+    "users.person.deleted.v1",
+];
+
+/// The matcher before prefiltering: split the subject into a vector, then
+/// walk the pattern tokens
+struct Tokenized(Vec<String>);
+
+impl Tokenized {
+    fn new(pattern: &str) -> Self {
+        Self(pattern.split('.').map(str::to_string).collect())
+    }
+
+    fn matches(&self, subject: &str) -> bool {
+        let parts: Vec<&str> = subject.split('.').collect();
+        let mut i = 0;
+        while i < self.0.len() && i < parts.len() {
+            match self.0[i].as_str() {
+                ">" => return true,
+                "*" => {},
+                literal if literal != parts[i] => return false,
+                _ => {},
+            }
+            i += 1;
+        }
+        i == self.0.len() && i == parts.len()
+    }
+}
+
+fn bench_matching(c: &mut Criterion) {
+    let patterns: Vec<Pattern> = PATTERNS.iter().map(|p| Pattern::new(*p).unwrap()).collect();
+    let tokenized: Vec<Tokenized> = PATTERNS.iter().map(|p| Tokenized::new(p)).collect();
+
+    for (name, subjects) in [
+        ("mismatching", &MISMATCHING[..]),
+        ("matching", &MATCHING[..]),
+    ] {
+        let mut group = c.benchmark_group(format!("pattern_{name}"));
+        group.bench_function("tokenized", |b| {
+            b.iter(|| {
+                for pattern in &tokenized {
+                    for subject in subjects {
+                        black_box(pattern.matches(black_box(subject)));
+                    }
+                }
+            });
+        });
+        group.bench_function("matches_str", |b| {
+            b.iter(|| {
+                for pattern in &patterns {
+                    for subject in subjects {
+                        black_box(pattern.matches_str(black_box(subject)));
+                    }
+                }
+            });
+        });
+        group.bench_function("matches_ref", |b| {
+            let parsed: Vec<SubjectRef<'_>> = subjects
+                .iter()
+                .map(|s| SubjectRef::new(s).unwrap())
+                .collect();
+            b.iter(|| {
+                for pattern in &patterns {
+                    for subject in &parsed {
+                        black_box(pattern.matches_ref(black_box(subject)));
+                    }
+                }
+            });
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_matching);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Benchmarks for permission evaluation on the publish hot path
+
+use cim_subject::permissions::{
+    Operation,
+    Permissions,
+    PermissionsBuilder,
+};
+use cim_subject::Subject;
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+
+/// Build a policy with a realistic number of overlapping rules
+fn policy() -> Permissions {
+    let mut builder = PermissionsBuilder::new();
+    for context in ["orders", "inventory", "billing", "shipping", "users"] {
+        builder = builder
+            .allow(&format!("{context}.commands.>"), &[Operation::Publish])
+            .unwrap()
+            .allow(&format!("{context}.events.>"), &[Operation::Subscribe])
+            .unwrap()
+            .deny(&format!("{context}.internal.>"), &[
+                Operation::Publish,
+                Operation::Subscribe,
+            ])
+            .unwrap()
+            .allow(&format!("{context}.*.*.v1"), &[Operation::Request])
+            .unwrap();
+    }
+    builder.deny_all("security.>").unwrap().build()
+}
+
+fn bench_publish_checks(c: &mut Criterion) {
+    let permissions = policy();
+    let compiled = permissions.compile();
+    let subjects: Vec<Subject> = [
+        "orders.commands.place.v1",
+        "billing.internal.charge.v1",
+        "users.events.created.v1",
+        "security.keys.rotated.v1",
+    ]
+    .iter()
+    .map(|s| Subject::new(*s).unwrap())
+    .collect();
+
+    let mut group = c.benchmark_group("publish_checks");
+    group.bench_function("interpreted", |b| {
+        b.iter(|| {
+            for subject in &subjects {
+                black_box(permissions.is_allowed(black_box(subject), Operation::Publish));
+            }
+        });
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| {
+            for subject in &subjects {
+                black_box(compiled.is_allowed(black_box(subject), Operation::Publish));
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_publish_checks);
+criterion_main!(benches);
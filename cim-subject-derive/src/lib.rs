@@ -0,0 +1,151 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Derive macros for `cim-subject`
+//!
+//! Use these through `cim-subject` with its `derive` feature, which
+//! re-exports them next to the traits they implement.
+
+#![warn(missing_docs)]
+#![warn(clippy::all)]
+#![warn(clippy::pedantic)]
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input,
+    Data,
+    DeriveInput,
+    Error,
+    Fields,
+    Ident,
+    LitStr,
+};
+
+/// Bind a message type to a subject template and message kind
+///
+/// ```ignore
+/// #[derive(SubjectBound)]
+/// #[subject(template = "orders.{aggregate}.created.v1", kind = "event")]
+/// struct OrderCreated {
+///     aggregate: String,
+///     total: u64,
+/// }
+/// ```
+///
+/// A `{field}` token is filled from the named field's `Display` output.
+/// `kind` is one of `command`, `query` or `event`.
+#[proc_macro_derive(SubjectBound, attributes(subject))]
+pub fn derive_subject_bound(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let (template, kind) = parse_attribute(input)?;
+    let fields = field_names(input)?;
+
+    let mut format = Vec::new();
+    let mut args = Vec::new();
+    let raw = template.value();
+    let tokens: Vec<&str> = raw.split('.').collect();
+    if tokens.len() < 3 {
+        return Err(Error::new(
+            template.span(),
+            "a subject template needs at least context, aggregate and event tokens",
+        ));
+    }
+    for token in tokens {
+        if let Some(name) = token.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+            let Some(field) = fields.iter().find(|field| *field == name) else {
+                return Err(Error::new(
+                    template.span(),
+                    format!("'{{{name}}}' does not name a field of this struct"),
+                ));
+            };
+            format.push("{}");
+            args.push(field.clone());
+        } else if token.is_empty()
+            || token.contains(['{', '}', '*', '>'])
+            || token.chars().any(char::is_whitespace)
+        {
+            return Err(Error::new(
+                template.span(),
+                format!("'{token}' is not a subject token or a '{{field}}' placeholder"),
+            ));
+        } else {
+            format.push(token);
+        }
+    }
+    let format = format.join(".");
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::cim_subject::bound::SubjectBound for #name #type_generics #where_clause {
+            type Kind = ::cim_subject::correlation::kinds::#kind;
+
+            const TEMPLATE: &'static str = #template;
+
+            fn subject(&self) -> ::cim_subject::Result<::cim_subject::Subject> {
+                ::cim_subject::Subject::new(::std::format!(#format, #(self.#args),*))
+            }
+        }
+    })
+}
+
+/// The template and kind from `#[subject(...)]`
+fn parse_attribute(input: &DeriveInput) -> syn::Result<(LitStr, Ident)> {
+    let mut template = None;
+    let mut kind = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("subject")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("template") {
+                template = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("kind") {
+                let value = meta.value()?.parse::<LitStr>()?;
+                let marker = match value.value().as_str() {
+                    "command" => "Command",
+                    "query" => "Query",
+                    "event" => "Event",
+                    _ => return Err(meta.error("kind must be \"command\", \"query\" or \"event\"")),
+                };
+                kind = Some(Ident::new(marker, value.span()));
+            } else {
+                return Err(meta.error("expected `template` or `kind`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let missing = |what: &str| {
+        Error::new(
+            Span::call_site(),
+            format!("SubjectBound needs `#[subject({what} = \"...\")]`"),
+        )
+    };
+    Ok((
+        template.ok_or_else(|| missing("template"))?,
+        kind.ok_or_else(|| missing("kind"))?,
+    ))
+}
+
+/// Names of the struct's fields, empty for tuple and unit structs
+fn field_names(input: &DeriveInput) -> syn::Result<Vec<Ident>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "SubjectBound can only be derived for structs",
+        ));
+    };
+    Ok(match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .filter_map(|f| f.ident.clone())
+            .collect(),
+        Fields::Unnamed(_) | Fields::Unit => Vec::new(),
+    })
+}
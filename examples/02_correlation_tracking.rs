@@ -36,6 +36,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             message_id: IdType::Uuid(order_id),
             correlation_id: CorrelationId(IdType::Uuid(order_id)),
             causation_id: CausationId(IdType::Uuid(order_id)),
+            kind: None,
         },
         caused_by: None,
     };
@@ -59,6 +60,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             message_id: IdType::Uuid(order_created_id),
             correlation_id: order_placed.identity.correlation_id.clone(),
             causation_id: CausationId(IdType::Uuid(order_id)),
+            kind: None,
         },
         caused_by: Some(order_id),
     };
@@ -82,6 +84,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             message_id: IdType::Uuid(stock_reserved_id),
             correlation_id: order_placed.identity.correlation_id.clone(),
             causation_id: CausationId(IdType::Uuid(order_created_id)),
+            kind: None,
         },
         caused_by: Some(order_created_id),
     };
@@ -108,6 +111,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             message_id: IdType::Uuid(payment_processed_id),
             correlation_id: order_placed.identity.correlation_id.clone(),
             causation_id: CausationId(IdType::Uuid(order_created_id)),
+            kind: None,
         },
         caused_by: Some(order_created_id),
     };
@@ -134,6 +138,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             message_id: IdType::Uuid(notification_sent_id),
             correlation_id: order_placed.identity.correlation_id.clone(),
             causation_id: CausationId(IdType::Uuid(payment_processed_id)), // Last in chain
+            kind: None,
         },
         caused_by: Some(payment_processed_id),
     };
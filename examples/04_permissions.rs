@@ -229,6 +229,7 @@ fn test_service_permissions(
                 Operation::Publish => "PUB",
                 Operation::Subscribe => "SUB",
                 Operation::Request => "REQ",
+                Operation::QueueSubscribe => "QSUB",
                 Operation::All => "ALL",
             },
             subject_str,
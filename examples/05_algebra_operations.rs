@@ -12,6 +12,7 @@ use cim_subject::{
         TransformFn,
         Transformation,
     },
+    AlgebraExpr,
     AlgebraOperation,
     CompositionRule,
     Pattern,
@@ -147,33 +148,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let payment_process = Subject::new("payments.commands.payment.process")?;
     let order_confirm = Subject::new("orders.events.order.confirmed")?;
 
-    // First: validate and check inventory in parallel
-    let validation_phase = algebra.compose(
-        &order_validate,
-        &inventory_check,
-        AlgebraOperation::Parallel,
-    )?;
-
-    // Then: process payment
-    let payment_phase = algebra.compose(
-        &validation_phase,
-        &payment_process,
-        AlgebraOperation::Sequence,
-    )?;
+    // Build the workflow as an expression so its structure is kept
+    let workflow = AlgebraExpr::from(order_validate)
+        .parallel(inventory_check.into())
+        .then(payment_process.into())
+        .then(order_confirm.into())
+        .simplify();
 
-    // Finally: confirm order
-    let complete_workflow =
-        algebra.compose(&payment_phase, &order_confirm, AlgebraOperation::Sequence)?;
-
-    println!("  Complete workflow: {}", complete_workflow.as_str());
+    println!("  Workflow structure: {workflow}");
     println!("  Steps:");
-    println!(
-        "    1. ({} ⊗ {})",
-        order_validate.as_str(),
-        inventory_check.as_str()
-    );
-    println!("    2. → {}", payment_process.as_str());
-    println!("    3. → {}", order_confirm.as_str());
+    for (i, subject) in workflow.subjects().iter().enumerate() {
+        println!("    {}. {}", i + 1, subject.as_str());
+    }
+
+    // Evaluate only when a single subject is needed
+    let complete_workflow = algebra.evaluate(&workflow)?;
+    println!("  Evaluated workflow: {}", complete_workflow.as_str());
 
     // Example 8: Lattice operations
     println!("\n8. Lattice operations:\n");
@@ -192,14 +182,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     for i in 0..subjects.len() {
         for j in i + 1..subjects.len() {
-            if let Some(joined) = lattice.join(&subjects[i], &subjects[j]) {
-                println!(
-                    "    {} ⊔ {} = {}",
-                    subjects[i].as_str(),
-                    subjects[j].as_str(),
-                    joined.as_str()
-                );
-            }
+            let joined = lattice.join(&(&subjects[i]).into(), &(&subjects[j]).into());
+            println!(
+                "    {} ⊔ {} = {}",
+                subjects[i].as_str(),
+                subjects[j].as_str(),
+                joined.as_str()
+            );
         }
     }
 
@@ -8,12 +8,16 @@
 use std::sync::Arc;
 
 use cim_subject::{
+    parser::Separator,
     translator::{
         TranslationRule,
         Translator,
     },
+    versioning::VersionPolicy,
+    NormalizationPolicy,
     Pattern,
     Subject,
+    SubjectError,
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -22,21 +26,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 1: Simple version migration
     println!("1. Version Migration Translation\n");
 
-    let version_translator = Translator::new();
-
-    // Translate v1 subjects to v2
-    version_translator.register_rule(
-        "v1_to_v2",
-        TranslationRule::new(
-            "v1_to_v2",
-            Pattern::new("*.*.*.v1")?,
-            Arc::new(|subject| {
-                let mut new_subject = subject.as_str().to_string();
-                new_subject = new_subject.replace(".v1", ".v2");
-                Subject::new(new_subject)
-            }),
-        ),
-    );
+    // Declare the supported versions; the policy generates the rules that
+    // upgrade v1 subjects to v2
+    let version_policy = VersionPolicy::new()
+        .support("orders", "events", "order", [1, 2])
+        .support("inventory", "commands", "stock", [1, 2])
+        .support("payments", "queries", "balance", [1, 2]);
+    let version_translator = version_policy.upgrade_translator()?;
 
     // Test translation
     let v1_subjects = vec![
@@ -54,44 +50,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 2: Legacy system integration
     println!("\n2. Legacy System Translation\n");
 
-    let legacy_translator = Translator::new();
-
-    // Map legacy subjects to modern format
     // Legacy format: SERVICE_TYPE_ACTION
     // Modern format: service.type.entity.action
-    legacy_translator.register_rule(
-        "legacy_to_modern",
-        TranslationRule::new(
-            "legacy_to_modern",
-            Pattern::new("*_*_*")?,
-            Arc::new(|subject| {
-                let parts: Vec<&str> = subject.as_str().split('_').collect();
-                if parts.len() == 3 {
-                    let service = parts[0].to_lowercase();
-                    let msg_type = match parts[1] {
-                        "CMD" => "commands",
-                        "EVT" => "events",
-                        "QRY" => "queries",
-                        _ => "unknown",
-                    };
-                    let action = parts[2].to_lowercase();
-
-                    // Infer entity from service
-                    let entity = match service.as_str() {
-                        "orders" => "order",
-                        "inventory" => "stock",
-                        "payments" => "payment",
-                        _ => "entity",
-                    };
-
-                    let new_subject = format!("{service}.{msg_type}.{entity}.{action}");
-                    Subject::new(new_subject)
-                } else {
-                    Ok(subject.clone())
-                }
-            }),
-        ),
-    );
+    let legacy = Separator::Underscore;
+    let modernize = |raw: &str| -> Result<Subject, SubjectError> {
+        let canonical = legacy.to_canonical(raw)?;
+        let parts: Vec<&str> = canonical.split('.').collect();
+        let [service, msg_type, action] = parts[..] else {
+            return Err(SubjectError::invalid_format(format!(
+                "Legacy subject '{raw}' must be SERVICE_TYPE_ACTION"
+            )));
+        };
+        let service = service.to_lowercase();
+        let msg_type = match msg_type {
+            "CMD" => "commands",
+            "EVT" => "events",
+            "QRY" => "queries",
+            _ => "unknown",
+        };
+
+        // Infer entity from service
+        let entity = match service.as_str() {
+            "orders" => "order",
+            "inventory" => "stock",
+            "payments" => "payment",
+            _ => "entity",
+        };
+
+        Subject::new(format!(
+            "{service}.{msg_type}.{entity}.{}",
+            action.to_lowercase()
+        ))
+    };
 
     // Test legacy translation
     let legacy_subjects = vec![
@@ -100,10 +90,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "PAYMENTS_QRY_STATUS",
     ];
 
-    for legacy in legacy_subjects {
-        let old = Subject::new(legacy)?;
-        let modern = legacy_translator.translate(&old)?;
-        println!("  {} → {}", old.as_str(), modern.as_str());
+    for old in legacy_subjects {
+        let modern = modernize(old)?;
+        println!("  {} → {}", old, modern.as_str());
     }
 
     // Example 3: Bidirectional translation
@@ -182,10 +171,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         TranslationRule::new(
             "normalize",
             Pattern::new("*.*.*.*")?,
-            Arc::new(|subject| {
-                let normalized = subject.as_str().to_lowercase();
-                Subject::new(normalized)
-            }),
+            Arc::new(|subject| subject.canonicalize(&NormalizationPolicy::new().lowercase())),
         ),
     );
 
@@ -11,18 +11,26 @@
 //!   = ["full"] }
 
 use cim_subject::{
+    event_stream::{
+        event_stream_key,
+        StreamKeyStrategy,
+        StreamLayout,
+    },
     permissions::{
         Operation,
         Permissions,
         PermissionsBuilder,
         Policy,
     },
+    request_reply::RequestContext,
     CorrelationId,
+    HeaderConvention,
     IdType,
     MessageIdentity,
     Pattern,
     Subject,
     SubjectBuilder,
+    WorkflowBuilder,
 };
 use uuid::Uuid;
 
@@ -279,17 +287,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             &mut self,
             service: &NatsService,
         ) -> Result<(), Box<dyn std::error::Error>> {
-            let steps = vec![
-                ("validate_order", "orders.commands.order.validate"),
-                ("reserve_stock", "inventory.commands.stock.reserve"),
-                ("process_payment", "payments.commands.payment.process"),
-                ("confirm_order", "orders.commands.order.confirm"),
-            ];
+            let workflow = WorkflowBuilder::new()
+                .step(Subject::new("orders.commands.order.validate")?)
+                .step(Subject::new("inventory.commands.stock.reserve")?)
+                .compensate(Subject::new("inventory.commands.stock.release")?)
+                .step(Subject::new("payments.commands.payment.process")?)
+                .compensate(Subject::new("payments.commands.payment.refund")?)
+                .step(Subject::new("orders.commands.order.confirm")?)
+                .build()?;
+            println!("  Workflow: {}", workflow.expr());
 
-            for (step_name, subject_str) in steps {
-                println!("  Step: {step_name}");
+            for step in workflow.steps() {
+                println!("  Step: {}", step.subject);
+                if let Some(compensation) = &step.compensation {
+                    println!("    (compensated by {compensation})");
+                }
 
-                let subject = Subject::new(subject_str)?;
+                let subject = step.subject.clone();
                 let step_identity = MessageIdentity::caused_by(
                     IdType::Uuid(Uuid::new_v4()),
                     self.correlation_id.clone(),
@@ -304,7 +318,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     )
                     .await?;
 
-                self.current_step = step_name.to_string();
+                self.current_step = step.subject.event_type().to_string();
             }
 
             Ok(())
@@ -321,6 +335,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let stream_pattern = Pattern::new("orders.events.>")?;
     println!("  Creating stream for pattern: {}", stream_pattern.as_str());
 
+    // Check that the stream keeps each aggregate's events in order before
+    // creating it
+    let layout = StreamLayout::new(StreamKeyStrategy::PerAggregate)
+        .stream("ORDER_EVENTS", stream_pattern.clone());
+    layout.validate(&[Subject::new("orders.events.order.created")?])?;
+
     // In real NATS, you'd create a JetStream with subject filter
     // For this example, we'll simulate event storage
 
@@ -341,9 +361,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         fn append(&mut self, subject: Subject, identity: MessageIdentity, payload: Vec<u8>) {
             if self.subject_filter.matches(&subject) {
+                let key = event_stream_key(&subject, StreamKeyStrategy::PerAggregate, None)
+                    .expect("per-aggregate keys need no correlation");
                 self.events.push((subject, identity, payload));
                 println!(
-                    "  Event stored in stream '{}': {} (total: {})",
+                    "  Event stored in stream '{}' under '{key}': {} (total: {})",
                     self.stream_name,
                     self.events.last().unwrap().0.as_str(),
                     self.events.len()
@@ -403,45 +425,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n\n5. Request-Reply pattern:\n");
 
     let query_subject = Subject::new("catalog.queries.product.get_details")?;
-    let reply_inbox = format!("_INBOX.{}", Uuid::new_v4());
-
-    println!("  Request: {}", query_subject.as_str());
-    println!("  Reply-To: {reply_inbox}");
+    let request = RequestContext::new(
+        query_subject,
+        MessageIdentity::root(IdType::Uuid(Uuid::new_v4())),
+    );
 
-    // In real NATS, you'd subscribe to reply_inbox before sending
-    // Then include reply_inbox in the request headers
+    println!("  Request: {}", request.subject.as_str());
+    println!("  Reply-To: {}", request.reply_to);
 
-    let query_identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+    // In real NATS, you'd subscribe to the reply inbox before sending
     let query_payload = b"{'product_id': 'ABC123'}";
 
     // Simulate request with reply
     async fn send_request_reply(
         service: &NatsService,
-        subject: Subject,
-        identity: MessageIdentity,
+        request: &RequestContext,
         payload: Vec<u8>,
-        reply_to: String,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // Check permissions
-        if !service.permissions.is_allowed(&subject, Operation::Request) {
+        if !service
+            .permissions
+            .is_allowed(&request.subject, Operation::Request)
+        {
             return Err("Permission denied for request".into());
         }
 
         let mut headers = mock_nats::HeaderMap::new();
-        headers.insert("X-Correlation-ID".to_string(), vec![identity
-            .correlation_id
-            .to_string()]);
-        headers.insert("X-Causation-ID".to_string(), vec![identity
-            .causation_id
-            .to_string()]);
-        headers.insert("X-Message-ID".to_string(), vec![identity
-            .message_id
-            .to_string()]);
-        headers.insert("Reply-To".to_string(), vec![reply_to]);
+        for (name, value) in request.to_headers(&HeaderConvention::default()) {
+            headers.insert(name, vec![value]);
+        }
 
         service
             .client
-            .publish_with_headers(subject.as_str().to_string(), headers, payload)
+            .publish_with_headers(request.subject.as_str().to_string(), headers, payload)
             .await?;
 
         Ok(())
@@ -456,14 +472,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let query_service =
         NatsService::new("Query Service", nats_url, query_permissions, vec![]).await?;
 
-    send_request_reply(
-        &query_service,
-        query_subject,
-        query_identity,
-        query_payload.to_vec(),
-        reply_inbox,
-    )
-    .await?;
+    send_request_reply(&query_service, &request, query_payload.to_vec()).await?;
+
+    // The responder derives its reply identity from the request, and the
+    // requester checks it before accepting the reply
+    let reply_identity = request.reply_identity();
+    request.validate_reply(&reply_identity)?;
+    println!("  Reply {} accepted", reply_identity.message_id);
 
     // Example 6: Wildcard subscriptions and routing
     println!("\n\n6. Wildcard subscriptions and routing:\n");
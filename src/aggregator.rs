@@ -0,0 +1,391 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Aggregating replies that share a correlation
+//!
+//! An [`Aggregator`] collects the replies to one request, identified by
+//! their correlation ID, until its [`AggregationPolicy`] is met: a number of
+//! replies, a majority of the expected responders, a timeout, or a reply
+//! from every one of a set of patterns. Replies offered after that, or
+//! after the aggregator's own deadline, are kept apart as stragglers, so a
+//! slow lender's quote is still seen without reopening a decided request.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cim_subject::aggregator::{
+//!     AggregationPolicy,
+//!     AggregationStatus,
+//!     Aggregator,
+//! };
+//! use cim_subject::{
+//!     MessageFactory,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let request = MessageFactory::create_root_query(Uuid::new_v4());
+//! let mut quotes = Aggregator::new(request.correlation_id.clone(), AggregationPolicy::FirstN(2))?
+//!     .with_deadline(Duration::from_secs(5));
+//!
+//! for (lender, rate) in [
+//!     ("first_bank", 6.25),
+//!     ("second_bank", 6.5),
+//!     ("third_bank", 6.1),
+//! ] {
+//!     let subject = Subject::new(format!("lenders.prime.{lender}.quoted"))?;
+//!     let reply = MessageFactory::query_from_query(Uuid::new_v4(), &request);
+//!     quotes
+//!         .offer(&subject, &reply, rate, Duration::from_secs(1))
+//!         .unwrap();
+//! }
+//!
+//! let aggregate = quotes.finish(Duration::from_secs(1));
+//! assert_eq!(aggregate.status, AggregationStatus::Met);
+//! assert_eq!(aggregate.responses.len(), 2);
+//! assert_eq!(aggregate.stragglers[0].response, 6.1);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::time::Duration;
+
+use crate::correlation::{
+    CorrelationError,
+    CorrelationId,
+    MessageIdentity,
+    Result,
+};
+use crate::error::SubjectError;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// When an aggregator has collected enough replies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregationPolicy {
+    /// After this many replies
+    FirstN(usize),
+    /// After replies from a strict majority of this many responders
+    Quorum {
+        /// Number of responders asked
+        expected: usize,
+    },
+    /// Once this long has passed, collecting everything until then
+    Timeout(Duration),
+    /// After at least one reply matching each pattern
+    AllOf(Vec<Pattern>),
+}
+
+impl AggregationPolicy {
+    /// Check that the policy needs at least one reply
+    fn validate(&self) -> crate::error::Result<()> {
+        let degenerate = match self {
+            Self::FirstN(0) => "FirstN(0)",
+            Self::Quorum { expected: 0 } => "Quorum { expected: 0 }",
+            Self::AllOf(patterns) if patterns.is_empty() => "AllOf([])",
+            _ => return Ok(()),
+        };
+        Err(SubjectError::validation_error(format!(
+            "aggregation policy {degenerate} would be met without any reply"
+        )))
+    }
+
+    fn is_met(&self, responses: &[Reply<impl Sized>], elapsed: Duration) -> bool {
+        match self {
+            Self::FirstN(count) => responses.len() >= *count,
+            Self::Quorum { expected } => responses.len() * 2 > *expected,
+            Self::Timeout(timeout) => elapsed >= *timeout,
+            Self::AllOf(patterns) => patterns.iter().all(|pattern| {
+                responses
+                    .iter()
+                    .any(|reply| pattern.matches(&reply.subject))
+            }),
+        }
+    }
+}
+
+/// Where an aggregation stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationStatus {
+    /// Still collecting
+    Pending,
+    /// The policy was met
+    Met,
+    /// The deadline passed before the policy was met
+    TimedOut,
+}
+
+/// What became of an offered reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offer {
+    /// Counted towards the policy
+    Collected,
+    /// Arrived once the aggregation was decided
+    Straggler,
+    /// A reply with the same message ID was already offered
+    Duplicate,
+}
+
+/// A reply and when it arrived
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply<R> {
+    /// The subject the reply was published on
+    pub subject: Subject,
+    /// The reply's identity
+    pub identity: MessageIdentity,
+    /// The reply
+    pub response: R,
+    /// When it arrived, from the start of the aggregation
+    pub elapsed: Duration,
+}
+
+/// Collects replies sharing a correlation ID
+#[derive(Debug, Clone)]
+pub struct Aggregator<R> {
+    correlation_id: CorrelationId,
+    policy: AggregationPolicy,
+    deadline: Option<Duration>,
+    responses: Vec<Reply<R>>,
+    stragglers: Vec<Reply<R>>,
+}
+
+impl<R> Aggregator<R> {
+    /// Aggregate replies correlated with `correlation_id`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy would be met without any reply:
+    /// `FirstN(0)`, `Quorum { expected: 0 }` or `AllOf` with no patterns
+    pub fn new(
+        correlation_id: CorrelationId,
+        policy: AggregationPolicy,
+    ) -> crate::error::Result<Self> {
+        policy.validate()?;
+        Ok(Self {
+            correlation_id,
+            policy,
+            deadline: None,
+            responses: Vec::new(),
+            stragglers: Vec::new(),
+        })
+    }
+
+    /// Give up on the policy once `deadline` has passed
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// The policy being applied
+    #[must_use]
+    pub fn policy(&self) -> &AggregationPolicy {
+        &self.policy
+    }
+
+    /// Where the aggregation stands `elapsed` after it started
+    #[must_use]
+    pub fn status(&self, elapsed: Duration) -> AggregationStatus {
+        if self.policy.is_met(&self.responses, elapsed) {
+            AggregationStatus::Met
+        } else if self.deadline.is_some_and(|deadline| elapsed >= deadline) {
+            AggregationStatus::TimedOut
+        } else {
+            AggregationStatus::Pending
+        }
+    }
+
+    /// Offer a reply that arrived `elapsed` after the aggregation started
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reply has a different correlation ID
+    pub fn offer(
+        &mut self,
+        subject: &Subject,
+        identity: &MessageIdentity,
+        response: R,
+        elapsed: Duration,
+    ) -> Result<Offer> {
+        if identity.correlation_id != self.correlation_id {
+            return Err(CorrelationError::InvalidIdentity(format!(
+                "reply {} has {}, expected {}",
+                identity.message_id, identity.correlation_id, self.correlation_id
+            )));
+        }
+        if self
+            .responses
+            .iter()
+            .chain(&self.stragglers)
+            .any(|reply| reply.identity.message_id == identity.message_id)
+        {
+            return Ok(Offer::Duplicate);
+        }
+
+        let pending = self.status(elapsed) == AggregationStatus::Pending;
+        let reply = Reply {
+            subject: subject.clone(),
+            identity: identity.clone(),
+            response,
+            elapsed,
+        };
+        if pending {
+            self.responses.push(reply);
+            Ok(Offer::Collected)
+        } else {
+            self.stragglers.push(reply);
+            Ok(Offer::Straggler)
+        }
+    }
+
+    /// Replies counted towards the policy, in arrival order
+    #[must_use]
+    pub fn responses(&self) -> &[Reply<R>] {
+        &self.responses
+    }
+
+    /// Stop aggregating `elapsed` after the start
+    #[must_use]
+    pub fn finish(self, elapsed: Duration) -> Aggregate<R> {
+        Aggregate {
+            status: self.status(elapsed),
+            correlation_id: self.correlation_id,
+            responses: self.responses,
+            stragglers: self.stragglers,
+        }
+    }
+}
+
+/// The replies collected for one correlation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate<R> {
+    /// The correlation the replies share
+    pub correlation_id: CorrelationId,
+    /// Where the aggregation stood when it finished
+    pub status: AggregationStatus,
+    /// Replies counted towards the policy, in arrival order
+    pub responses: Vec<Reply<R>>,
+    /// Replies that arrived once the aggregation was decided
+    pub stragglers: Vec<Reply<R>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    fn replies(request: &MessageIdentity, subjects: &[&str]) -> Vec<(Subject, MessageIdentity)> {
+        subjects
+            .iter()
+            .map(|s| {
+                (
+                    Subject::new(*s).unwrap(),
+                    MessageFactory::query_from_query(Uuid::new_v4(), request),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quorum_and_stragglers() {
+        let request = MessageFactory::create_root_query(Uuid::new_v4());
+        let mut aggregator =
+            Aggregator::new(request.correlation_id.clone(), AggregationPolicy::Quorum {
+                expected: 4,
+            })
+            .unwrap();
+        let secs = Duration::from_secs;
+        let replies = replies(&request, &[
+            "lenders.prime.a.quoted",
+            "lenders.prime.b.quoted",
+            "lenders.alta.c.quoted",
+            "lenders.nonqm.d.quoted",
+        ]);
+
+        let offer = |aggregator: &mut Aggregator<u32>, index: usize| {
+            let (subject, identity) = &replies[index];
+            aggregator.offer(subject, identity, 0, secs(1)).unwrap()
+        };
+        assert_eq!(offer(&mut aggregator, 0), Offer::Collected);
+        assert_eq!(offer(&mut aggregator, 0), Offer::Duplicate);
+        assert_eq!(offer(&mut aggregator, 1), Offer::Collected);
+        assert_eq!(aggregator.status(secs(1)), AggregationStatus::Pending);
+        assert_eq!(offer(&mut aggregator, 2), Offer::Collected);
+        assert_eq!(aggregator.status(secs(1)), AggregationStatus::Met);
+        assert_eq!(offer(&mut aggregator, 3), Offer::Straggler);
+
+        let other = MessageFactory::create_root_query(Uuid::new_v4());
+        assert!(aggregator.offer(&replies[3].0, &other, 0, secs(1)).is_err());
+
+        let aggregate = aggregator.finish(secs(2));
+        assert_eq!(aggregate.status, AggregationStatus::Met);
+        assert_eq!(aggregate.responses.len(), 3);
+        assert_eq!(aggregate.stragglers[0].subject, replies[3].0);
+    }
+
+    #[test]
+    fn test_all_of_and_timeouts() {
+        let request = MessageFactory::create_root_query(Uuid::new_v4());
+        let secs = Duration::from_secs;
+        let replies = replies(&request, &[
+            "lenders.prime.a.quoted",
+            "lenders.alta.b.quoted",
+        ]);
+        let tiers = AggregationPolicy::AllOf(vec![
+            Pattern::new("lenders.prime.>").unwrap(),
+            Pattern::new("lenders.alta.>").unwrap(),
+        ]);
+
+        let mut all_of = Aggregator::new(request.correlation_id.clone(), tiers)
+            .unwrap()
+            .with_deadline(secs(10));
+        all_of
+            .offer(&replies[0].0, &replies[0].1, (), secs(2))
+            .unwrap();
+        assert_eq!(all_of.status(secs(9)), AggregationStatus::Pending);
+        assert_eq!(all_of.status(secs(10)), AggregationStatus::TimedOut);
+        assert_eq!(
+            all_of
+                .offer(&replies[1].0, &replies[1].1, (), secs(11))
+                .unwrap(),
+            Offer::Straggler
+        );
+        assert_eq!(all_of.finish(secs(11)).status, AggregationStatus::TimedOut);
+
+        let mut timeout = Aggregator::new(
+            request.correlation_id.clone(),
+            AggregationPolicy::Timeout(secs(5)),
+        )
+        .unwrap();
+        assert_eq!(
+            timeout
+                .offer(&replies[0].0, &replies[0].1, (), secs(4))
+                .unwrap(),
+            Offer::Collected
+        );
+        assert_eq!(timeout.status(secs(5)), AggregationStatus::Met);
+        assert_eq!(
+            timeout
+                .offer(&replies[1].0, &replies[1].1, (), secs(6))
+                .unwrap(),
+            Offer::Straggler
+        );
+    }
+
+    #[test]
+    fn test_degenerate_policies_rejected() {
+        let request = MessageFactory::create_root_query(Uuid::new_v4());
+        for policy in [
+            AggregationPolicy::FirstN(0),
+            AggregationPolicy::Quorum { expected: 0 },
+            AggregationPolicy::AllOf(vec![]),
+        ] {
+            assert!(Aggregator::<()>::new(request.correlation_id.clone(), policy).is_err());
+        }
+        assert!(Aggregator::<()>::new(
+            request.correlation_id.clone(),
+            AggregationPolicy::Timeout(Duration::ZERO)
+        )
+        .is_ok());
+    }
+}
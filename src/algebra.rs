@@ -14,12 +14,27 @@ use crate::error::{
     Result,
     SubjectError,
 };
+pub use crate::lattice::{
+    Generalizations,
+    SubjectLattice,
+};
 use crate::pattern::Pattern;
 use crate::subject::{
     Subject,
     SubjectParts,
 };
 
+/// The unit subject: the identity element of sequential and parallel
+/// composition
+pub const UNIT_SUBJECT: &str = "_._._._";
+
+/// The zero subject: the annihilating element of sequential and parallel
+/// composition
+///
+/// Its tokens are not valid subject tokens, so no parsed or built subject
+/// is mistaken for it.
+pub const ZERO_SUBJECT: &str = "∅.∅.∅.∅";
+
 /// Type alias for composition functions
 pub type ComposerFn = Arc<dyn Fn(&Subject, &Subject) -> Result<Subject> + Send + Sync>;
 
@@ -27,10 +42,18 @@ pub type ComposerFn = Arc<dyn Fn(&Subject, &Subject) -> Result<Subject> + Send +
 pub type TransformFn = Arc<dyn Fn(&Subject) -> Result<Subject> + Send + Sync>;
 
 /// The Subject Algebra system for compositional operations
+///
+/// Composition rules are found in two ways. Rules registered under a legacy
+/// key such as `sequence:{left_event}:{right_event}` apply when the operand
+/// event types match the key exactly. All other rules apply when both
+/// operands match the rule's patterns; if several do, the one with the most
+/// specific left pattern (then right pattern) wins.
 #[derive(Clone)]
 pub struct SubjectAlgebra {
-    /// Registered composition rules
+    /// Composition rules keyed by exact event types
     rules: Arc<DashMap<String, CompositionRule>>,
+    /// Composition rules selected by operand patterns
+    pattern_rules: Arc<DashMap<String, (CompositionKind, CompositionRule)>>,
     /// Registered transformations
     transformations: Arc<DashMap<String, Transformation>>,
 }
@@ -47,13 +70,101 @@ impl SubjectAlgebra {
     pub fn new() -> Self {
         Self {
             rules: Arc::new(DashMap::new()),
+            pattern_rules: Arc::new(DashMap::new()),
             transformations: Arc::new(DashMap::new()),
         }
     }
 
+    /// The identity element for [`AlgebraOperation::Sequence`] and
+    /// [`AlgebraOperation::Parallel`]
+    ///
+    /// Composing any subject with the unit yields that subject unchanged,
+    /// which lets pipelines skip a step by substituting the unit for it.
+    #[must_use]
+    pub fn unit() -> Subject {
+        Subject::from_parts(SubjectParts::new("_", "_", "_", "_"))
+    }
+
+    /// Check if a subject is the unit subject
+    #[must_use]
+    pub fn is_unit(subject: &Subject) -> bool {
+        subject.as_str() == UNIT_SUBJECT
+    }
+
+    /// The annihilating element for [`AlgebraOperation::Sequence`] and
+    /// [`AlgebraOperation::Parallel`]
+    ///
+    /// Composing any subject with the zero yields the zero, which lets a
+    /// pipeline mark a step that can never happen and have that carry
+    /// through every composition containing it.
+    #[must_use]
+    pub fn zero() -> Subject {
+        Subject::from_parts(SubjectParts::new("∅", "∅", "∅", "∅"))
+    }
+
+    /// Check if a subject is the zero subject
+    #[must_use]
+    pub fn is_zero(subject: &Subject) -> bool {
+        subject.as_str() == ZERO_SUBJECT
+    }
+
     /// Register a composition rule
+    ///
+    /// Names with an operation prefix (`sequence:`, `parallel:`, `choice:`,
+    /// `project:`, `inject:`) are exact event-type keys. Any other name
+    /// registers a pattern rule for sequential composition; use
+    /// [`register_rule_for`](Self::register_rule_for) for other operations.
     pub fn register_rule(&self, name: impl Into<String>, rule: CompositionRule) {
-        self.rules.insert(name.into(), rule);
+        let name = name.into();
+        if CompositionKind::from_key(&name).is_some() {
+            self.rules.insert(name, rule);
+        } else {
+            self.pattern_rules
+                .insert(name, (CompositionKind::Sequence, rule));
+        }
+    }
+
+    /// Register a pattern rule for a specific kind of composition
+    pub fn register_rule_for(
+        &self,
+        kind: CompositionKind,
+        name: impl Into<String>,
+        rule: CompositionRule,
+    ) {
+        self.pattern_rules.insert(name.into(), (kind, rule));
+    }
+
+    /// Find the rule for a composition: exact key first, then the most
+    /// specific matching pattern rule
+    fn find_rule(
+        &self,
+        key: &str,
+        kind: CompositionKind,
+        left: &Subject,
+        right: &Subject,
+    ) -> Option<ComposerFn> {
+        if let Some(rule) = self.rules.get(key) {
+            return Some(rule.composer.clone());
+        }
+
+        self.pattern_rules
+            .iter()
+            .filter_map(|entry| {
+                let (rule_kind, rule) = entry.value();
+                let applies = *rule_kind == kind
+                    && rule.left_pattern.matches(left)
+                    && rule.right_pattern.matches(right);
+                applies.then(|| {
+                    let rank = (
+                        rule.left_pattern.specificity_key(),
+                        rule.right_pattern.specificity_key(),
+                        entry.key().clone(),
+                    );
+                    (rank, rule.composer.clone())
+                })
+            })
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, composer)| composer)
     }
 
     /// Register a transformation
@@ -69,6 +180,15 @@ impl SubjectAlgebra {
     /// - A named transformation is not found
     /// - A transformation pattern doesn't match the input subject
     /// - A composition rule fails during execution
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, left, right),
+            fields(left = %left, right = %right),
+            err
+        )
+    )]
     pub fn compose(
         &self,
         left: &Subject,
@@ -82,15 +202,34 @@ impl SubjectAlgebra {
             AlgebraOperation::Transform { name } => self.transform(left, &name),
             AlgebraOperation::Project { fields } => self.project(left, &fields),
             AlgebraOperation::Inject { context } => self.inject(left, &context),
+            AlgebraOperation::Identity => Ok(left.clone()),
+        }
+    }
+
+    /// The zero if either operand is the zero, otherwise the other operand
+    /// if either is the unit
+    fn absorb(left: &Subject, right: &Subject) -> Option<Subject> {
+        if Self::is_zero(left) || Self::is_zero(right) {
+            Some(Self::zero())
+        } else if Self::is_unit(left) {
+            Some(right.clone())
+        } else if Self::is_unit(right) {
+            Some(left.clone())
+        } else {
+            None
         }
     }
 
     /// Sequential composition: left happens before right
     fn sequence(&self, left: &Subject, right: &Subject) -> Result<Subject> {
+        if let Some(subject) = Self::absorb(left, right) {
+            return Ok(subject);
+        }
+
         // Check if there's a registered rule for this sequence
         let rule_key = format!("sequence:{}:{}", left.event_type(), right.event_type());
-        if let Some(rule) = self.rules.get(&rule_key) {
-            return (rule.composer)(left, right);
+        if let Some(composer) = self.find_rule(&rule_key, CompositionKind::Sequence, left, right) {
+            return composer(left, right);
         }
 
         // Default sequence behavior
@@ -105,10 +244,14 @@ impl SubjectAlgebra {
 
     /// Parallel composition: left and right happen concurrently
     fn parallel(&self, left: &Subject, right: &Subject) -> Result<Subject> {
+        if let Some(subject) = Self::absorb(left, right) {
+            return Ok(subject);
+        }
+
         // Check if there's a registered rule for this parallel composition
         let rule_key = format!("parallel:{}:{}", left.event_type(), right.event_type());
-        if let Some(rule) = self.rules.get(&rule_key) {
-            return (rule.composer)(left, right);
+        if let Some(composer) = self.find_rule(&rule_key, CompositionKind::Parallel, left, right) {
+            return composer(left, right);
         }
 
         // Default parallel behavior
@@ -130,8 +273,8 @@ impl SubjectAlgebra {
             right.event_type(),
             condition
         );
-        if let Some(rule) = self.rules.get(&rule_key) {
-            return (rule.composer)(left, right);
+        if let Some(composer) = self.find_rule(&rule_key, CompositionKind::Choice, left, right) {
+            return composer(left, right);
         }
 
         // Default choice behavior
@@ -158,9 +301,11 @@ impl SubjectAlgebra {
     fn project(&self, subject: &Subject, fields: &[String]) -> Result<Subject> {
         // Check if there's a registered rule for projection
         let rule_key = format!("project:{}:{}", subject.event_type(), fields.join(","));
-        if let Some(rule) = self.rules.get(&rule_key) {
+        if let Some(composer) =
+            self.find_rule(&rule_key, CompositionKind::Project, subject, subject)
+        {
             // For projection, we pass the subject twice (the rule can ignore the second)
-            return (rule.composer)(subject, subject);
+            return composer(subject, subject);
         }
 
         // Default projection behavior
@@ -177,9 +322,10 @@ impl SubjectAlgebra {
     fn inject(&self, subject: &Subject, new_context: &str) -> Result<Subject> {
         // Check if there's a registered rule for context injection
         let rule_key = format!("inject:{}:{}", subject.context(), new_context);
-        if let Some(rule) = self.rules.get(&rule_key) {
+        if let Some(composer) = self.find_rule(&rule_key, CompositionKind::Inject, subject, subject)
+        {
             // For injection, we pass the subject twice (the rule can ignore the second)
-            return (rule.composer)(subject, subject);
+            return composer(subject, subject);
         }
 
         // Default injection behavior
@@ -207,6 +353,16 @@ impl SubjectAlgebra {
     pub fn create_lattice(&self, subjects: &[Subject]) -> SubjectLattice {
         SubjectLattice::new(subjects)
     }
+
+    /// Create a subject lattice using a token generalization hierarchy
+    #[must_use]
+    pub fn create_lattice_with(
+        &self,
+        subjects: &[Subject],
+        generalizations: Generalizations,
+    ) -> SubjectLattice {
+        SubjectLattice::with_generalizations(subjects, generalizations)
+    }
 }
 
 /// Algebraic operations on subjects
@@ -236,6 +392,37 @@ pub enum AlgebraOperation {
         /// The context to inject into the subject
         context: String,
     },
+    /// Leave the left subject unchanged
+    Identity,
+}
+
+/// Kinds of composition that can have custom rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CompositionKind {
+    /// Sequential composition
+    Sequence,
+    /// Parallel composition
+    Parallel,
+    /// Choice composition
+    Choice,
+    /// Projection
+    Project,
+    /// Context injection
+    Inject,
+}
+
+impl CompositionKind {
+    /// The kind named by a legacy `kind:...` rule key
+    fn from_key(key: &str) -> Option<Self> {
+        match key.split_once(':')?.0 {
+            "sequence" => Some(CompositionKind::Sequence),
+            "parallel" => Some(CompositionKind::Parallel),
+            "choice" => Some(CompositionKind::Choice),
+            "project" => Some(CompositionKind::Project),
+            "inject" => Some(CompositionKind::Inject),
+            _ => None,
+        }
+    }
 }
 
 /// A composition rule defines how subjects can be composed
@@ -281,78 +468,6 @@ impl Transformation {
     }
 }
 
-/// A lattice structure for subjects (partial order)
-#[derive(Debug, Clone)]
-pub struct SubjectLattice {
-    /// Subjects in the lattice
-    subjects: Vec<Subject>,
-    /// Ordering relationships
-    ordering: Vec<(usize, usize)>, // (less_idx, greater_idx)
-}
-
-impl SubjectLattice {
-    /// Create a new subject lattice
-    #[must_use]
-    pub fn new(subjects: &[Subject]) -> Self {
-        let mut lattice = Self {
-            subjects: subjects.to_vec(),
-            ordering: Vec::new(),
-        };
-        lattice.compute_ordering();
-        lattice
-    }
-
-    /// Compute the ordering relationships
-    fn compute_ordering(&mut self) {
-        // Simple ordering based on specificity
-        for i in 0..self.subjects.len() {
-            for j in 0..self.subjects.len() {
-                if i != j && self.is_less_specific(i, j) {
-                    self.ordering.push((i, j));
-                }
-            }
-        }
-    }
-
-    /// Check if subject at index i is less specific than j
-    fn is_less_specific(&self, i: usize, j: usize) -> bool {
-        let si = &self.subjects[i];
-        let sj = &self.subjects[j];
-
-        // Context hierarchy
-        if si.context() != sj.context() {
-            return false;
-        }
-
-        // Event type generalization
-        matches!(
-            (si.event_type(), sj.event_type()),
-            ("*", _) | ("changed", "created" | "updated" | "deleted")
-        )
-    }
-
-    /// Find the join (least upper bound) of two subjects
-    #[must_use]
-    pub fn join(&self, a: &Subject, b: &Subject) -> Option<Subject> {
-        // Find common ancestors
-        let a_idx = self.subjects.iter().position(|s| s == a)?;
-        let b_idx = self.subjects.iter().position(|s| s == b)?;
-
-        // Find minimal common ancestors
-        for (i, subject) in self.subjects.iter().enumerate() {
-            if self.is_ancestor(a_idx, i) && self.is_ancestor(b_idx, i) {
-                return Some(subject.clone());
-            }
-        }
-        None
-    }
-
-    /// Check if a is an ancestor of b in the ordering
-    fn is_ancestor(&self, a: usize, b: usize) -> bool {
-        self.ordering.iter().any(|(x, y)| *x == a && *y == b)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -387,6 +502,68 @@ mod tests {
         assert_eq!(result.event_type(), "parallel");
     }
 
+    #[test]
+    fn test_unit_is_identity() {
+        let algebra = SubjectAlgebra::new();
+        let unit = SubjectAlgebra::unit();
+        let subject = Subject::new("orders.order.created.v1").unwrap();
+
+        for operation in [AlgebraOperation::Sequence, AlgebraOperation::Parallel] {
+            assert_eq!(
+                algebra.compose(&subject, &unit, operation.clone()).unwrap(),
+                subject
+            );
+            assert_eq!(
+                algebra.compose(&unit, &subject, operation).unwrap(),
+                subject
+            );
+        }
+
+        let other = Subject::new("billing.invoice.paid.v1").unwrap();
+        assert_eq!(
+            algebra
+                .compose(&subject, &other, AlgebraOperation::Identity)
+                .unwrap(),
+            subject
+        );
+        assert!(SubjectAlgebra::is_unit(&unit));
+        assert_eq!(unit.as_str(), UNIT_SUBJECT);
+    }
+
+    #[test]
+    fn test_zero_annihilates() {
+        let algebra = SubjectAlgebra::new();
+        let zero = SubjectAlgebra::zero();
+        let subject = Subject::new("orders.order.created.v1").unwrap();
+
+        for operation in [AlgebraOperation::Sequence, AlgebraOperation::Parallel] {
+            for operands in [
+                (&subject, &zero),
+                (&zero, &subject),
+                (&zero, &SubjectAlgebra::unit()),
+                (&SubjectAlgebra::unit(), &zero),
+            ] {
+                assert_eq!(
+                    algebra
+                        .compose(operands.0, operands.1, operation.clone())
+                        .unwrap(),
+                    zero
+                );
+            }
+        }
+        assert!(SubjectAlgebra::is_zero(&zero));
+        assert_eq!(zero.as_str(), ZERO_SUBJECT);
+
+        // User subjects are never the zero
+        assert!(Subject::new(ZERO_SUBJECT).is_err());
+        let zeros = Subject::new("0.0.0.0").unwrap();
+        assert!(!SubjectAlgebra::is_zero(&zeros));
+        let sequenced = algebra
+            .compose(&subject, &zeros, AlgebraOperation::Sequence)
+            .unwrap();
+        assert_eq!(sequenced.as_str(), "orders-0.order-0.sequenced.v1");
+    }
+
     #[test]
     fn test_inject_operation() {
         let algebra = SubjectAlgebra::new();
@@ -437,6 +614,53 @@ mod tests {
         assert_eq!(result.aggregate(), "anonymous");
     }
 
+    #[test]
+    fn test_pattern_rule_selection() {
+        let algebra = SubjectAlgebra::new();
+        let rule = |name: &str, left: &str, right: &str, event: &'static str| CompositionRule {
+            name: name.to_string(),
+            left_pattern: Pattern::new(left).unwrap(),
+            right_pattern: Pattern::new(right).unwrap(),
+            composer: Arc::new(move |left, _| {
+                Ok(Subject::from_parts(SubjectParts::new(
+                    left.context(),
+                    left.aggregate(),
+                    event,
+                    "v1",
+                )))
+            }),
+        };
+
+        algebra.register_rule("broad", rule("broad", "orders.>", "*.*.*.*", "broad"));
+        algebra.register_rule(
+            "narrow",
+            rule("narrow", "orders.order.*.v1", "*.*.*.*", "narrow"),
+        );
+        algebra.register_rule_for(
+            CompositionKind::Parallel,
+            "fork",
+            rule("fork", "*.*.*.*", "*.*.*.*", "forked"),
+        );
+
+        let order = Subject::new("orders.order.placed.v1").unwrap();
+        let invoice = Subject::new("orders.invoice.issued.v1").unwrap();
+        let payment = Subject::new("billing.payment.captured.v1").unwrap();
+
+        let seq = |l: &Subject| {
+            algebra
+                .compose(l, &payment, AlgebraOperation::Sequence)
+                .unwrap()
+        };
+        assert_eq!(seq(&order).event_type(), "narrow");
+        assert_eq!(seq(&invoice).event_type(), "broad");
+        assert_eq!(seq(&payment).event_type(), "sequenced");
+
+        let par = algebra
+            .compose(&order, &payment, AlgebraOperation::Parallel)
+            .unwrap();
+        assert_eq!(par.event_type(), "forked");
+    }
+
     #[test]
     fn test_subject_lattice() {
         let subjects = vec![
@@ -446,9 +670,9 @@ mod tests {
         ];
 
         let algebra = SubjectAlgebra::new();
-        let lattice = algebra.create_lattice(&subjects);
+        let lattice = algebra.create_lattice_with(&subjects, Generalizations::crud());
 
         // The lattice should recognize "changed" as more general
-        assert!(!lattice.ordering.is_empty());
+        assert!(!lattice.ordering().is_empty());
     }
 }
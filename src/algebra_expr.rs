@@ -0,0 +1,459 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Algebra expressions: composition as a tree
+//!
+//! [`SubjectAlgebra::compose`] evaluates eagerly, so a workflow built from
+//! several compositions collapses into a single mangled subject and its
+//! structure is lost. An [`AlgebraExpr`] keeps the structure: it can be built
+//! up, simplified, printed and serialized, and only evaluated when a subject
+//! is actually needed.
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::algebra::{
+    AlgebraOperation,
+    SubjectAlgebra,
+};
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+/// An unevaluated composition of subjects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlgebraExpr {
+    /// A subject leaf
+    Subject(Subject),
+    /// Steps that happen one after another
+    Sequence(Vec<AlgebraExpr>),
+    /// Branches that happen concurrently
+    Parallel(Vec<AlgebraExpr>),
+    /// One of two branches, selected by a condition
+    Choice {
+        /// The condition that selects a branch
+        condition: String,
+        /// Branch taken when the condition holds
+        left: Box<AlgebraExpr>,
+        /// Branch taken otherwise
+        right: Box<AlgebraExpr>,
+    },
+    /// A named transformation
+    Transform {
+        /// Name of the registered transformation
+        name: String,
+        /// Expression to transform
+        expr: Box<AlgebraExpr>,
+    },
+    /// A projection onto fields
+    Project {
+        /// Fields to project
+        fields: Vec<String>,
+        /// Expression to project
+        expr: Box<AlgebraExpr>,
+    },
+    /// An injection into another context
+    Inject {
+        /// Target context
+        context: String,
+        /// Expression to inject
+        expr: Box<AlgebraExpr>,
+    },
+}
+
+impl AlgebraExpr {
+    /// Create a subject leaf
+    #[must_use]
+    pub fn subject(subject: Subject) -> Self {
+        AlgebraExpr::Subject(subject)
+    }
+
+    /// Follow this expression with another (`→`)
+    #[must_use]
+    pub fn then(self, next: AlgebraExpr) -> Self {
+        AlgebraExpr::Sequence(vec![self, next])
+    }
+
+    /// Run this expression alongside another (`⊗`)
+    #[must_use]
+    pub fn parallel(self, other: AlgebraExpr) -> Self {
+        AlgebraExpr::Parallel(vec![self, other])
+    }
+
+    /// Choose between this expression and another
+    #[must_use]
+    pub fn or_else(self, other: AlgebraExpr, condition: impl Into<String>) -> Self {
+        AlgebraExpr::Choice {
+            condition: condition.into(),
+            left: Box::new(self),
+            right: Box::new(other),
+        }
+    }
+
+    /// Apply a named transformation
+    #[must_use]
+    pub fn transform(self, name: impl Into<String>) -> Self {
+        AlgebraExpr::Transform {
+            name: name.into(),
+            expr: Box::new(self),
+        }
+    }
+
+    /// Project onto fields
+    #[must_use]
+    pub fn project(self, fields: Vec<String>) -> Self {
+        AlgebraExpr::Project {
+            fields,
+            expr: Box::new(self),
+        }
+    }
+
+    /// Inject into another context
+    #[must_use]
+    pub fn inject(self, context: impl Into<String>) -> Self {
+        AlgebraExpr::Inject {
+            context: context.into(),
+            expr: Box::new(self),
+        }
+    }
+
+    /// Subject leaves in left-to-right order
+    #[must_use]
+    pub fn subjects(&self) -> Vec<&Subject> {
+        let mut out = Vec::new();
+        self.collect_subjects(&mut out);
+        out
+    }
+
+    fn collect_subjects<'a>(&'a self, out: &mut Vec<&'a Subject>) {
+        match self {
+            AlgebraExpr::Subject(subject) => out.push(subject),
+            AlgebraExpr::Sequence(steps) | AlgebraExpr::Parallel(steps) => {
+                for step in steps {
+                    step.collect_subjects(out);
+                }
+            },
+            AlgebraExpr::Choice { left, right, .. } => {
+                left.collect_subjects(out);
+                right.collect_subjects(out);
+            },
+            AlgebraExpr::Transform { expr, .. }
+            | AlgebraExpr::Project { expr, .. }
+            | AlgebraExpr::Inject { expr, .. } => expr.collect_subjects(out),
+        }
+    }
+
+    /// Rewrite into an equivalent, smaller expression
+    ///
+    /// - Nested sequences and nested parallels are flattened (associativity)
+    /// - Single-step sequences and parallels collapse to their step
+    /// - Unit subjects are dropped from sequences and parallels
+    /// - A sequence or parallel containing the zero subject collapses to it
+    /// - Nested injections keep only the outermost context, and injecting a
+    ///   subject into its own context is dropped
+    ///
+    /// The injection rewrites assume default injection behaviour rather than
+    /// a registered `inject:` rule.
+    #[must_use]
+    pub fn simplify(self) -> Self {
+        match self {
+            AlgebraExpr::Subject(_) => self,
+            AlgebraExpr::Sequence(steps) => Self::flatten(steps, true),
+            AlgebraExpr::Parallel(steps) => Self::flatten(steps, false),
+            AlgebraExpr::Choice {
+                condition,
+                left,
+                right,
+            } => AlgebraExpr::Choice {
+                condition,
+                left: Box::new(left.simplify()),
+                right: Box::new(right.simplify()),
+            },
+            AlgebraExpr::Transform { name, expr } => AlgebraExpr::Transform {
+                name,
+                expr: Box::new(expr.simplify()),
+            },
+            AlgebraExpr::Project { fields, expr } => AlgebraExpr::Project {
+                fields,
+                expr: Box::new(expr.simplify()),
+            },
+            AlgebraExpr::Inject { context, expr } => match expr.simplify() {
+                AlgebraExpr::Inject { expr, .. } => {
+                    AlgebraExpr::Inject { context, expr }.simplify()
+                },
+                AlgebraExpr::Subject(subject) if subject.context() == context => {
+                    AlgebraExpr::Subject(subject)
+                },
+                expr => AlgebraExpr::Inject {
+                    context,
+                    expr: Box::new(expr),
+                },
+            },
+        }
+    }
+
+    fn flatten(steps: Vec<AlgebraExpr>, sequence: bool) -> Self {
+        let mut flat = Vec::with_capacity(steps.len());
+        for step in steps {
+            match (step.simplify(), sequence) {
+                (AlgebraExpr::Sequence(inner), true) | (AlgebraExpr::Parallel(inner), false) => {
+                    flat.extend(inner);
+                },
+                (AlgebraExpr::Subject(subject), _) if SubjectAlgebra::is_unit(&subject) => {},
+                (AlgebraExpr::Subject(subject), _) if SubjectAlgebra::is_zero(&subject) => {
+                    return AlgebraExpr::Subject(subject);
+                },
+                (step, _) => flat.push(step),
+            }
+        }
+
+        match (flat.len(), sequence) {
+            (0, _) => AlgebraExpr::Subject(SubjectAlgebra::unit()),
+            (1, _) => flat.pop().expect("length checked"),
+            (_, true) => AlgebraExpr::Sequence(flat),
+            (_, false) => AlgebraExpr::Parallel(flat),
+        }
+    }
+
+    /// Evaluate the expression with an algebra's rules and transformations
+    ///
+    /// Sequences and parallels fold from the left.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a sequence or parallel is empty, or if any
+    /// composition fails
+    pub fn evaluate(&self, algebra: &SubjectAlgebra) -> Result<Subject> {
+        match self {
+            AlgebraExpr::Subject(subject) => Ok(subject.clone()),
+            AlgebraExpr::Sequence(steps) => Self::fold(steps, algebra, &AlgebraOperation::Sequence),
+            AlgebraExpr::Parallel(steps) => Self::fold(steps, algebra, &AlgebraOperation::Parallel),
+            AlgebraExpr::Choice {
+                condition,
+                left,
+                right,
+            } => algebra.compose(
+                &left.evaluate(algebra)?,
+                &right.evaluate(algebra)?,
+                AlgebraOperation::Choice {
+                    condition: condition.clone(),
+                },
+            ),
+            AlgebraExpr::Transform { name, expr } => {
+                Self::unary(expr, algebra, AlgebraOperation::Transform {
+                    name: name.clone(),
+                })
+            },
+            AlgebraExpr::Project { fields, expr } => {
+                Self::unary(expr, algebra, AlgebraOperation::Project {
+                    fields: fields.clone(),
+                })
+            },
+            AlgebraExpr::Inject { context, expr } => {
+                Self::unary(expr, algebra, AlgebraOperation::Inject {
+                    context: context.clone(),
+                })
+            },
+        }
+    }
+
+    fn fold(
+        steps: &[AlgebraExpr],
+        algebra: &SubjectAlgebra,
+        operation: &AlgebraOperation,
+    ) -> Result<Subject> {
+        let (first, rest) = steps.split_first().ok_or_else(|| {
+            SubjectError::composition_error("Cannot evaluate an empty composition")
+        })?;
+        rest.iter().try_fold(first.evaluate(algebra)?, |acc, step| {
+            algebra.compose(&acc, &step.evaluate(algebra)?, operation.clone())
+        })
+    }
+
+    fn unary(
+        expr: &AlgebraExpr,
+        algebra: &SubjectAlgebra,
+        operation: AlgebraOperation,
+    ) -> Result<Subject> {
+        let subject = expr.evaluate(algebra)?;
+        algebra.compose(&subject, &subject, operation)
+    }
+
+    fn write_joined(
+        f: &mut fmt::Formatter<'_>,
+        steps: &[AlgebraExpr],
+        separator: &str,
+    ) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, step) in steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " {separator} ")?;
+            }
+            write!(f, "{step}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Display for AlgebraExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlgebraExpr::Subject(subject) => write!(f, "{subject}"),
+            AlgebraExpr::Sequence(steps) => Self::write_joined(f, steps, "→"),
+            AlgebraExpr::Parallel(steps) => Self::write_joined(f, steps, "⊗"),
+            AlgebraExpr::Choice {
+                condition,
+                left,
+                right,
+            } => write!(f, "({left} +[{condition}] {right})"),
+            AlgebraExpr::Transform { name, expr } => write!(f, "{name}({expr})"),
+            AlgebraExpr::Project { fields, expr } => {
+                write!(f, "π[{}]({expr})", fields.join(","))
+            },
+            AlgebraExpr::Inject { context, expr } => write!(f, "inject[{context}]({expr})"),
+        }
+    }
+}
+
+impl From<Subject> for AlgebraExpr {
+    fn from(subject: Subject) -> Self {
+        AlgebraExpr::Subject(subject)
+    }
+}
+
+impl SubjectAlgebra {
+    /// Evaluate an algebra expression
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any composition in the expression fails
+    pub fn evaluate(&self, expr: &AlgebraExpr) -> Result<Subject> {
+        expr.evaluate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn leaf(s: &str) -> AlgebraExpr {
+        AlgebraExpr::subject(Subject::new(s).unwrap())
+    }
+
+    #[test]
+    fn test_simplify_and_display() {
+        let expr = leaf("orders.commands.order.validate")
+            .parallel(leaf("inventory.queries.stock.check"))
+            .then(leaf("payments.commands.payment.process"))
+            .then(leaf("orders.events.order.confirmed"));
+
+        let simplified = expr.clone().simplify();
+        assert_eq!(
+            simplified.to_string(),
+            "((orders.commands.order.validate ⊗ inventory.queries.stock.check) → \
+             payments.commands.payment.process → orders.events.order.confirmed)"
+        );
+        assert_eq!(simplified.subjects().len(), 4);
+
+        let redundant = leaf("users.user.created.v1")
+            .inject("tenant")
+            .inject("users");
+        assert_eq!(redundant.simplify(), leaf("users.user.created.v1"));
+
+        // A choice composes its branches even when they are identical
+        let choice = leaf("users.user.created.v1").or_else(leaf("users.user.created.v1"), "always");
+        assert_eq!(choice.clone().simplify(), choice);
+
+        let skipped = leaf("orders.order.placed.v1")
+            .then(AlgebraExpr::subject(SubjectAlgebra::unit()))
+            .parallel(AlgebraExpr::subject(SubjectAlgebra::unit()));
+        assert_eq!(skipped.simplify(), leaf("orders.order.placed.v1"));
+
+        let blocked = leaf("orders.order.placed.v1")
+            .then(AlgebraExpr::subject(SubjectAlgebra::zero()))
+            .parallel(leaf("users.user.created.v1"));
+        assert_eq!(
+            blocked.simplify(),
+            AlgebraExpr::subject(SubjectAlgebra::zero())
+        );
+
+        // Serialization round-trips the structure
+        let json = serde_json::to_string(&simplified).unwrap();
+        let restored: AlgebraExpr = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, simplified);
+    }
+
+    #[test]
+    fn test_evaluate_matches_eager_composition() {
+        let algebra = SubjectAlgebra::new();
+        let a = Subject::new("orders.order.created.v1").unwrap();
+        let b = Subject::new("inventory.stock.reserved.v1").unwrap();
+        let c = Subject::new("billing.invoice.issued.v1").unwrap();
+
+        let eager = algebra
+            .compose(&a, &b, AlgebraOperation::Sequence)
+            .and_then(|ab| algebra.compose(&ab, &c, AlgebraOperation::Sequence))
+            .unwrap();
+        let expr = AlgebraExpr::from(a)
+            .then(b.into())
+            .then(c.into())
+            .simplify();
+        assert_eq!(algebra.evaluate(&expr).unwrap(), eager);
+
+        assert!(AlgebraExpr::Sequence(vec![]).evaluate(&algebra).is_err());
+    }
+
+    fn expr() -> impl Strategy<Value = AlgebraExpr> {
+        let leaf = prop_oneof![
+            Just(AlgebraExpr::subject(SubjectAlgebra::unit())),
+            Just(AlgebraExpr::subject(SubjectAlgebra::zero())),
+            prop::sample::select(vec![
+                "orders.order.created.v1",
+                "users.user.created.v1",
+                "billing.invoice.issued.v2",
+            ])
+            .prop_map(leaf),
+        ];
+        leaf.prop_recursive(4, 32, 3, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 1..4).prop_map(AlgebraExpr::Sequence),
+                prop::collection::vec(inner.clone(), 1..4).prop_map(AlgebraExpr::Parallel),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(left, right)| left.or_else(right, "approved")),
+                inner
+                    .clone()
+                    .prop_map(|expr| expr.project(vec!["total".to_string()])),
+                (
+                    inner,
+                    prop::sample::select(vec!["orders", "users", "tenant"])
+                )
+                    .prop_map(|(expr, context)| expr.inject(context)),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_simplify_preserves_evaluation(expr in expr()) {
+            let algebra = SubjectAlgebra::new();
+            let simplified = expr.clone().simplify();
+            prop_assert_eq!(
+                simplified.evaluate(&algebra).unwrap(),
+                expr.evaluate(&algebra).unwrap(),
+                "{} simplified to {}",
+                expr,
+                simplified
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! AMQP topic exchange binding keys
+//!
+//! AMQP topic exchanges route on dot-separated words like subjects do, but
+//! their `#` matches zero or more words where `>` matches one or more.
+//! Every [`Pattern`] has an exact binding key: `>` becomes `*.#`. The
+//! reverse only exists for keys that `>` can express; since subjects always
+//! have four tokens, a trailing `#` after fewer than four words becomes
+//! `>`, and anything else involving `#` is rejected with an
+//! [`AmqpBindingError`].
+//!
+//! ```
+//! use cim_subject::Pattern;
+//!
+//! let pattern = Pattern::new("orders.*.placed.>").unwrap();
+//! assert_eq!(pattern.to_amqp_binding(), "orders.*.placed.*.#");
+//!
+//! let pattern = Pattern::from_amqp_binding("orders.#").unwrap();
+//! assert_eq!(pattern.as_str(), "orders.>");
+//! assert!(Pattern::from_amqp_binding("orders.#.placed").is_err());
+//! ```
+
+use alloc::string::{
+    String,
+    ToString,
+};
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use crate::error::SubjectError;
+use crate::pattern::{
+    Pattern,
+    Token,
+};
+
+/// Number of tokens in every subject
+const SUBJECT_TOKENS: usize = 4;
+
+/// An AMQP binding key that has no pattern equivalent
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmqpBindingError {
+    /// The binding key is empty
+    #[error("AMQP binding key is empty")]
+    Empty,
+
+    /// A word between dots is empty
+    #[error("AMQP binding key '{key}' has an empty word at position {position}")]
+    EmptyWord {
+        /// The binding key
+        key: String,
+        /// One-based position of the word
+        position: usize,
+    },
+
+    /// A word holds characters that pattern tokens cannot
+    #[error("AMQP binding key '{key}' has word '{word}' with characters patterns cannot hold")]
+    InvalidWord {
+        /// The binding key
+        key: String,
+        /// The offending word
+        word: String,
+    },
+
+    /// `#` is followed by more words, which a pattern cannot express
+    #[error("AMQP binding key '{key}' uses '#' before its last word")]
+    InnerMultiWildcard {
+        /// The binding key
+        key: String,
+    },
+
+    /// `#` follows four or more words, so within subjects it could only
+    /// match zero words, which `>` never does
+    #[error("AMQP binding key '{key}' uses '#' after a complete subject")]
+    MultiWildcardAfterSubject {
+        /// The binding key
+        key: String,
+    },
+}
+
+impl From<AmqpBindingError> for SubjectError {
+    fn from(error: AmqpBindingError) -> Self {
+        SubjectError::translation_error(error.to_string())
+    }
+}
+
+impl Pattern {
+    /// The equivalent AMQP topic exchange binding key
+    #[must_use]
+    pub fn to_amqp_binding(&self) -> String {
+        self.tokens()
+            .iter()
+            .map(|token| match token {
+                Token::Literal(literal) => literal.as_str(),
+                Token::SingleWildcard => "*",
+                Token::MultiWildcard => "*.#",
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Convert an AMQP topic exchange binding key to a pattern
+    ///
+    /// `*.#` at the end becomes `>`, as does a lone trailing `#` after fewer
+    /// than four words.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmqpBindingError`] if the key is empty, has an empty or
+    /// invalid word, or uses `#` anywhere `>` cannot express it
+    pub fn from_amqp_binding(key: &str) -> Result<Self, AmqpBindingError> {
+        if key.is_empty() {
+            return Err(AmqpBindingError::Empty);
+        }
+
+        let words: Vec<&str> = key.split('.').collect();
+        let mut tokens: Vec<&str> = Vec::with_capacity(words.len());
+        for (i, word) in words.iter().enumerate() {
+            match *word {
+                "#" if i + 1 != words.len() => {
+                    return Err(AmqpBindingError::InnerMultiWildcard {
+                        key: key.to_string(),
+                    });
+                },
+                // `*.#` is exactly one or more words
+                "#" if tokens.last() == Some(&"*") => {
+                    tokens.pop();
+                    tokens.push(">");
+                },
+                "#" if i >= SUBJECT_TOKENS => {
+                    return Err(AmqpBindingError::MultiWildcardAfterSubject {
+                        key: key.to_string(),
+                    });
+                },
+                "#" => tokens.push(">"),
+                "*" => tokens.push("*"),
+                "" => {
+                    return Err(AmqpBindingError::EmptyWord {
+                        key: key.to_string(),
+                        position: i + 1,
+                    });
+                },
+                word if word
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+                {
+                    tokens.push(word);
+                },
+                word => {
+                    return Err(AmqpBindingError::InvalidWord {
+                        key: key.to_string(),
+                        word: word.to_string(),
+                    });
+                },
+            }
+        }
+        Ok(Pattern::from_valid_tokens(&tokens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_round_trip() {
+        for raw in [
+            "orders.order.placed.v1",
+            "orders.*.placed.>",
+            ">",
+            "*.*.*.*",
+        ] {
+            let pattern = Pattern::new(raw).unwrap();
+            assert_eq!(
+                Pattern::from_amqp_binding(&pattern.to_amqp_binding()).unwrap(),
+                pattern
+            );
+        }
+        assert_eq!(Pattern::from_amqp_binding("#").unwrap().as_str(), ">");
+        assert_eq!(
+            Pattern::from_amqp_binding("orders.order.#")
+                .unwrap()
+                .as_str(),
+            "orders.order.>"
+        );
+    }
+
+    #[test]
+    fn test_untranslatable_bindings() {
+        assert_eq!(Pattern::from_amqp_binding(""), Err(AmqpBindingError::Empty));
+        assert!(matches!(
+            Pattern::from_amqp_binding("orders..placed"),
+            Err(AmqpBindingError::EmptyWord { position: 2, .. })
+        ));
+        assert!(matches!(
+            Pattern::from_amqp_binding("orders.#.v1"),
+            Err(AmqpBindingError::InnerMultiWildcard { .. })
+        ));
+        assert!(matches!(
+            Pattern::from_amqp_binding("orders.order.placed.v1.#"),
+            Err(AmqpBindingError::MultiWildcardAfterSubject { .. })
+        ));
+        assert!(matches!(
+            Pattern::from_amqp_binding("orders.order placed"),
+            Err(AmqpBindingError::InvalidWord { .. })
+        ));
+    }
+}
@@ -0,0 +1,432 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Per-principal permission resolution
+//!
+//! An [`Authorizer`] holds named role permission sets and explicit grants
+//! for individual principals. The effective permissions of a [`Principal`]
+//! are its roles' rules followed by its own grants, confined to its tenant
+//! through a [`TenantScope`] when it has one. Resolved permissions are
+//! cached per principal until roles or grants change.
+//!
+//! ```
+//! use cim_subject::authorization::{
+//!     Authorizer,
+//!     Principal,
+//! };
+//! use cim_subject::permissions::{
+//!     Operation,
+//!     PermissionsBuilder,
+//! };
+//! use cim_subject::Subject;
+//!
+//! let mut authorizer = Authorizer::new();
+//! authorizer.define_role(
+//!     "clerk",
+//!     PermissionsBuilder::new()
+//!         .allow("orders.>", &[Operation::Publish])?
+//!         .build(),
+//! );
+//!
+//! let alice = Principal::new("alice").with_role("clerk").in_tenant("acme");
+//! let subject = Subject::new("orders.order.place.v1")?.with_tenant("acme")?;
+//! assert!(authorizer.is_allowed(&alice, &subject, Operation::Publish));
+//! assert!(!authorizer.is_allowed(&alice, &subject, Operation::Subscribe));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    ConflictResolution,
+    EvaluationContext,
+    Operation,
+    Permissions,
+    Policy,
+};
+use crate::subject::Subject;
+use crate::tenancy::TenantScope;
+
+/// An identity whose access is being decided
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// Unique principal identifier
+    pub id: String,
+    /// Names of the roles the principal holds
+    pub roles: BTreeSet<String>,
+    /// The tenant the principal belongs to, if any
+    pub tenant: Option<String>,
+    /// Attributes made available to rule conditions
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Principal {
+    /// A principal without roles, tenant or attributes
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            roles: BTreeSet::new(),
+            tenant: None,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Add a role
+    #[must_use]
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.roles.insert(role.into());
+        self
+    }
+
+    /// Place the principal in a tenant
+    #[must_use]
+    pub fn in_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Add an attribute
+    #[must_use]
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// An evaluation context carrying the principal's attributes
+    #[must_use]
+    pub fn evaluation_context(&self) -> EvaluationContext {
+        self.attributes
+            .iter()
+            .fold(EvaluationContext::new(), |context, (name, value)| {
+                context.with_attribute(name, value)
+            })
+    }
+}
+
+/// The permissions in effect for a principal
+#[derive(Debug, Clone)]
+pub enum EffectivePermissions {
+    /// Permissions over all subjects
+    Global(Permissions),
+    /// Permissions confined to one tenant's subjects
+    Tenant(TenantScope),
+}
+
+impl EffectivePermissions {
+    /// The merged, tenant-agnostic permission set
+    #[must_use]
+    pub fn permissions(&self) -> &Permissions {
+        match self {
+            Self::Global(permissions) => permissions,
+            Self::Tenant(scope) => scope.permissions(),
+        }
+    }
+
+    /// Check if an operation is allowed on a subject
+    #[must_use]
+    pub fn is_allowed(&self, subject: &Subject, operation: Operation) -> bool {
+        self.is_allowed_with(subject, operation, &EvaluationContext::new())
+    }
+
+    /// Check if an operation is allowed on a subject in `context`
+    #[must_use]
+    pub fn is_allowed_with(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+    ) -> bool {
+        match self {
+            Self::Global(permissions) => permissions.is_allowed_with(subject, operation, context),
+            Self::Tenant(scope) => scope.is_allowed_with(subject, operation, context),
+        }
+    }
+
+    /// Check if subscribing to a pattern is allowed
+    #[must_use]
+    pub fn is_subscription_allowed(&self, pattern: &Pattern, operation: Operation) -> bool {
+        match self {
+            Self::Global(permissions) => permissions.is_subscription_allowed(pattern, operation),
+            Self::Tenant(scope) => scope.is_subscription_allowed(pattern, operation),
+        }
+    }
+}
+
+/// Resolves and caches effective permissions for principals
+#[derive(Debug, Default)]
+pub struct Authorizer {
+    /// Permission sets by role name
+    roles: HashMap<String, Permissions>,
+    /// Explicit grants by principal id
+    grants: HashMap<String, Permissions>,
+    /// Conflict resolution of resolved permission sets
+    resolution: ConflictResolution,
+    /// Resolved permissions by principal id, roles and tenant
+    cache: Mutex<HashMap<CacheKey, Arc<EffectivePermissions>>>,
+}
+
+type CacheKey = (String, BTreeSet<String>, Option<String>);
+
+impl Authorizer {
+    /// An authorizer without roles or grants
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how conflicts between merged rules are resolved
+    #[must_use]
+    pub fn conflict_resolution(mut self, resolution: ConflictResolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Define or replace a role's permissions
+    pub fn define_role(&mut self, role: impl Into<String>, permissions: Permissions) {
+        self.roles.insert(role.into(), permissions);
+        self.clear_cache();
+    }
+
+    /// Add explicit permissions for one principal, after any already granted
+    pub fn grant(&mut self, principal_id: impl Into<String>, permissions: Permissions) {
+        self.grants
+            .entry(principal_id.into())
+            .or_insert_with(|| Permissions::new(Policy::Deny))
+            .merge(permissions);
+        self.clear_cache();
+    }
+
+    /// Remove a principal's explicit grants
+    pub fn revoke(&mut self, principal_id: &str) {
+        self.grants.remove(principal_id);
+        self.clear_cache();
+    }
+
+    /// The effective permissions of a principal
+    ///
+    /// Role rules come first, in role name order, followed by the
+    /// principal's own grants. Unmatched subjects get the most restrictive
+    /// of the roles' default policies, and are denied for a principal
+    /// without roles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the principal holds an undefined role or its
+    /// tenant is invalid
+    pub fn resolve(&self, principal: &Principal) -> Result<Arc<EffectivePermissions>> {
+        let key = (
+            principal.id.clone(),
+            principal.roles.clone(),
+            principal.tenant.clone(),
+        );
+        if let Some(resolved) = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&key).cloned())
+        {
+            return Ok(resolved);
+        }
+
+        let mut permissions = Permissions::new(Policy::Deny);
+        permissions.set_conflict_resolution(self.resolution);
+        let mut default_policy = None;
+        for role in &principal.roles {
+            let role_permissions = self.roles.get(role).ok_or_else(|| {
+                SubjectError::not_found(format!(
+                    "Role '{role}' of principal '{}' is not defined",
+                    principal.id
+                ))
+            })?;
+            default_policy = match (default_policy, role_permissions.default_policy()) {
+                (Some(Policy::Deny), _) | (_, Policy::Deny) => Some(Policy::Deny),
+                _ => Some(Policy::Allow),
+            };
+            permissions.merge(role_permissions.clone());
+        }
+        permissions.set_default_policy(default_policy.unwrap_or(Policy::Deny));
+        if let Some(grants) = self.grants.get(&principal.id) {
+            permissions.merge(grants.clone());
+        }
+        let resolved = Arc::new(match &principal.tenant {
+            Some(tenant) => EffectivePermissions::Tenant(TenantScope::new(tenant, permissions)?),
+            None => EffectivePermissions::Global(permissions),
+        });
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(key, Arc::clone(&resolved));
+        }
+        Ok(resolved)
+    }
+
+    /// Check if a principal may perform an operation on a subject
+    ///
+    /// The principal's attributes are available to rule conditions.
+    /// Principals that cannot be resolved are denied.
+    #[must_use]
+    pub fn is_allowed(
+        &self,
+        principal: &Principal,
+        subject: &Subject,
+        operation: Operation,
+    ) -> bool {
+        self.resolve(principal).is_ok_and(|resolved| {
+            resolved.is_allowed_with(subject, operation, &principal.evaluation_context())
+        })
+    }
+
+    /// Check if a principal may subscribe to a pattern
+    #[must_use]
+    pub fn is_subscription_allowed(
+        &self,
+        principal: &Principal,
+        pattern: &Pattern,
+        operation: Operation,
+    ) -> bool {
+        self.resolve(principal)
+            .is_ok_and(|resolved| resolved.is_subscription_allowed(pattern, operation))
+    }
+
+    /// Number of principals with cached permissions
+    #[must_use]
+    pub fn cached_principals(&self) -> usize {
+        self.cache.lock().map_or(0, |cache| cache.len())
+    }
+
+    /// Drop all cached permissions
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::PermissionsBuilder;
+
+    fn authorizer() -> Authorizer {
+        let mut authorizer = Authorizer::new();
+        authorizer.define_role(
+            "reader",
+            PermissionsBuilder::new()
+                .allow("orders.>", &[Operation::Subscribe])
+                .unwrap()
+                .build(),
+        );
+        authorizer.define_role(
+            "writer",
+            PermissionsBuilder::new()
+                .allow("orders.order.*.*", &[Operation::Publish])
+                .unwrap()
+                .deny("orders.order.delete.*", &[Operation::Publish])
+                .unwrap()
+                .build(),
+        );
+        authorizer
+    }
+
+    #[test]
+    fn test_roles_and_grants_merge() {
+        let mut authorizer = authorizer();
+        let bob = Principal::new("bob")
+            .with_role("reader")
+            .with_role("writer");
+        let place = Subject::new("orders.order.place.v1").unwrap();
+        let delete = Subject::new("orders.order.delete.v1").unwrap();
+        let audit = Subject::new("audit.log.entry.v1").unwrap();
+
+        assert!(authorizer.is_allowed(&bob, &place, Operation::Publish));
+        assert!(authorizer.is_allowed(&bob, &place, Operation::Subscribe));
+        assert!(!authorizer.is_allowed(&bob, &delete, Operation::Publish));
+        assert!(!authorizer.is_allowed(&bob, &audit, Operation::Subscribe));
+
+        authorizer.grant(
+            "bob",
+            PermissionsBuilder::new()
+                .allow("audit.>", &[Operation::Subscribe])
+                .unwrap()
+                .build(),
+        );
+        assert!(authorizer.is_allowed(&bob, &audit, Operation::Subscribe));
+        // Grants belong to the principal, not its roles
+        let carol = Principal::new("carol").with_role("reader");
+        assert!(!authorizer.is_allowed(&carol, &audit, Operation::Subscribe));
+
+        let unknown = Principal::new("dave").with_role("admin");
+        assert!(authorizer.resolve(&unknown).is_err());
+        assert!(!authorizer.is_allowed(&unknown, &place, Operation::Subscribe));
+    }
+
+    #[test]
+    fn test_role_default_policies() {
+        let mut authorizer = authorizer();
+        authorizer.define_role(
+            "operator",
+            PermissionsBuilder::new()
+                .default_policy(Policy::Allow)
+                .deny("audit.>", &[Operation::Publish])
+                .unwrap()
+                .build(),
+        );
+        let billing = Subject::new("billing.invoice.issued.v1").unwrap();
+        let audit = Subject::new("audit.log.entry.v1").unwrap();
+
+        let operator = Principal::new("erin").with_role("operator");
+        assert!(authorizer.is_allowed(&operator, &billing, Operation::Publish));
+        assert!(!authorizer.is_allowed(&operator, &audit, Operation::Publish));
+
+        // A role that denies by default makes the merged default deny
+        let both = Principal::new("frank")
+            .with_role("operator")
+            .with_role("reader");
+        assert!(!authorizer.is_allowed(&both, &billing, Operation::Publish));
+        assert!(authorizer.is_allowed(
+            &both,
+            &Subject::new("orders.order.placed.v1").unwrap(),
+            Operation::Subscribe
+        ));
+    }
+
+    #[test]
+    fn test_tenant_scoping_and_cache() {
+        let authorizer = authorizer();
+        let alice = Principal::new("alice")
+            .with_role("reader")
+            .in_tenant("acme");
+        let place = Subject::new("orders.order.place.v1").unwrap();
+
+        assert!(authorizer.is_allowed(
+            &alice,
+            &place.with_tenant("acme").unwrap(),
+            Operation::Subscribe
+        ));
+        assert!(!authorizer.is_allowed(
+            &alice,
+            &place.with_tenant("globex").unwrap(),
+            Operation::Subscribe
+        ));
+        assert!(!authorizer.is_allowed(&alice, &place, Operation::Subscribe));
+
+        let first = authorizer.resolve(&alice).unwrap();
+        let second = authorizer.resolve(&alice).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(authorizer.cached_principals(), 1);
+        authorizer.clear_cache();
+        assert_eq!(authorizer.cached_principals(), 0);
+    }
+}
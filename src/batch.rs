@@ -0,0 +1,206 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Batch parsing and validation
+//!
+//! ETL jobs that check millions of archived subjects parse them in bulk
+//! with [`SubjectParser::parse_batch`], or summarise the failures with
+//! [`SubjectParser::validate_batch`]. With the `rayon` feature,
+//! [`SubjectParser::par_parse_batch`] and
+//! [`SubjectParser::par_validate_batch`] spread the work over rayon's
+//! thread pool; results keep the batch order either way.
+//!
+//! ```
+//! use cim_subject::error::ErrorCode;
+//! use cim_subject::SubjectParser;
+//!
+//! let parser = SubjectParser::with_standard_rules();
+//! let subjects = [
+//!     "orders.order.placed.v1",
+//!     "orders.order",
+//!     "orders.order.placed.1",
+//! ];
+//!
+//! let results = parser.parse_batch(&subjects);
+//! assert!(results[0].is_ok());
+//!
+//! let report = parser.validate_batch(&subjects);
+//! assert_eq!((report.total, report.valid()), (3, 1));
+//! assert_eq!(report.failures[0].0, 1);
+//! assert_eq!(report.count(ErrorCode::ValidationError), 1);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{
+    self,
+    Display,
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::{
+    IntoParallelRefIterator,
+    ParallelIterator,
+};
+
+use crate::error::{
+    ErrorCode,
+    Result,
+    SubjectError,
+};
+use crate::parser::SubjectParser;
+use crate::subject::Subject;
+
+/// Failures in a batch of subjects
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    /// Number of subjects checked
+    pub total: usize,
+    /// Index in the batch and error of each invalid subject, in batch order
+    pub failures: Vec<(usize, SubjectError)>,
+}
+
+impl BatchReport {
+    fn from_results(results: Vec<Result<Subject>>) -> Self {
+        let total = results.len();
+        let failures = results
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.err().map(|error| (index, error)))
+            .collect();
+        Self { total, failures }
+    }
+
+    /// Number of valid subjects
+    #[must_use]
+    pub fn valid(&self) -> usize {
+        self.total - self.failures.len()
+    }
+
+    /// Check if every subject is valid
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Number of failures with `code`
+    #[must_use]
+    pub fn count(&self, code: ErrorCode) -> usize {
+        self.failures
+            .iter()
+            .filter(|(_, error)| error.code() == code)
+            .count()
+    }
+
+    /// Number of failures for each error code
+    #[must_use]
+    pub fn counts(&self) -> HashMap<ErrorCode, usize> {
+        let mut counts = HashMap::new();
+        for (_, error) in &self.failures {
+            *counts.entry(error.code()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} subjects invalid",
+            self.failures.len(),
+            self.total
+        )?;
+        let mut counts: Vec<(ErrorCode, usize)> = self.counts().into_iter().collect();
+        counts.sort_by_key(|(code, _)| code.as_str());
+        for (code, count) in counts {
+            write!(f, "\n  {}: {count}", code.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl SubjectParser {
+    /// Parse every subject, in order
+    #[must_use]
+    pub fn parse_batch(&self, subjects: &[&str]) -> Vec<Result<Subject>> {
+        subjects.iter().map(|subject| self.parse(subject)).collect()
+    }
+
+    /// Parse every subject and report the failures
+    #[must_use]
+    pub fn validate_batch(&self, subjects: &[&str]) -> BatchReport {
+        BatchReport::from_results(self.parse_batch(subjects))
+    }
+
+    /// Parse every subject on rayon's thread pool, keeping the batch order
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn par_parse_batch(&self, subjects: &[&str]) -> Vec<Result<Subject>> {
+        subjects
+            .par_iter()
+            .map(|subject| self.parse(subject))
+            .collect()
+    }
+
+    /// Parse every subject on rayon's thread pool and report the failures
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn par_validate_batch(&self, subjects: &[&str]) -> BatchReport {
+        BatchReport::from_results(self.par_parse_batch(subjects))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_batch() {
+        let parser = SubjectParser::with_standard_rules();
+        let subjects = [
+            "orders.order.placed.v1",
+            "orders..placed.v1",
+            "orders.order.placed.1",
+            "orders.order.shipped.v2",
+            "orders",
+        ];
+        let results = parser.parse_batch(&subjects);
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            results[3].as_ref().unwrap().as_str(),
+            "orders.order.shipped.v2"
+        );
+
+        let report = parser.validate_batch(&subjects);
+        assert!(!report.is_ok());
+        assert_eq!(report.valid(), 2);
+        let indices: Vec<usize> = report.failures.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, [1, 2, 4]);
+        assert_eq!(report.count(ErrorCode::ValidationError), 1);
+        assert_eq!(report.counts().values().sum::<usize>(), 3);
+        assert!(report.to_string().starts_with("3 of 5 subjects invalid"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_batch_matches_sequential() {
+        let parser = SubjectParser::with_standard_rules();
+        let subjects: Vec<String> = (0..1_000)
+            .map(|i| {
+                format!(
+                    "orders.order.placed.{}",
+                    if i % 7 == 0 { "x" } else { "v1" }
+                )
+            })
+            .collect();
+        let subjects: Vec<&str> = subjects.iter().map(String::as_str).collect();
+        let sequential = parser.validate_batch(&subjects);
+        let parallel = parser.par_validate_batch(&subjects);
+        assert_eq!(parallel.total, sequential.total);
+        let indices = |report: &BatchReport| -> Vec<usize> {
+            report.failures.iter().map(|(index, _)| *index).collect()
+        };
+        assert_eq!(indices(&parallel), indices(&sequential));
+        assert_eq!(parallel.failures.len(), 143);
+    }
+}
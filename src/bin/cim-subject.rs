@@ -0,0 +1,307 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Command-line checks for subjects, patterns, translations and permissions
+//!
+//! Lets CI pipelines test routing and permission configuration without
+//! writing Rust. Every subcommand exits with status 0 on success, 1 when
+//! the answer is "no" (an invalid subject, a non-match, a denial), and 2 on
+//! usage or configuration errors.
+//!
+//! ```text
+//! cim-subject validate orders.order.placed.v1
+//! cim-subject match 'orders.*.placed.>' orders.order.placed.v1
+//! cim-subject translate --rules rules.yaml orders.order.placed.v1
+//! cim-subject perm check --policy policy.yaml orders.order.placed.v1 Publish
+//! cim-subject chain render --format mermaid chain.json
+//! ```
+
+use std::fs;
+use std::io::{
+    self,
+    BufRead,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::process::ExitCode;
+
+use cim_subject::permissions::Operation;
+use cim_subject::translator::TranslatorBuilder;
+use cim_subject::{
+    CorrelationChain,
+    MessageIdentity,
+    Pattern,
+    Permissions,
+    Subject,
+    Translator,
+};
+use clap::{
+    Parser,
+    Subcommand,
+    ValueEnum,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Parser)]
+#[command(name = "cim-subject", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that subjects are valid, reading lines from stdin if none are
+    /// given
+    Validate { subjects: Vec<String> },
+    /// Check whether a pattern matches a subject
+    Match { pattern: String, subject: String },
+    /// Translate subjects with rules from a YAML or JSON file
+    Translate {
+        #[arg(long)]
+        rules: PathBuf,
+        /// Translate back from target to source subjects
+        #[arg(long)]
+        reverse: bool,
+        subjects: Vec<String>,
+    },
+    /// Permission checks
+    Perm {
+        #[command(subcommand)]
+        command: PermCommand,
+    },
+    /// Correlation chain tools
+    Chain {
+        #[command(subcommand)]
+        command: ChainCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum PermCommand {
+    /// Check whether a policy allows an operation on a subject
+    Check {
+        /// Permission set in its serde form, as YAML or JSON
+        #[arg(long)]
+        policy: PathBuf,
+        subject: String,
+        /// `Publish`, `Subscribe`, `Request`, `QueueSubscribe` or `All`
+        operation: String,
+        /// Print which rules decided, and why
+        #[arg(long)]
+        explain: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChainCommand {
+    /// Render a chain given as a YAML or JSON list of message identities,
+    /// root first
+    Render {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = ChainFormat::Dot)]
+        format: ChainFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ChainFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+/// A rules file for `translate`
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    rules: Vec<RuleSpec>,
+}
+
+/// One translation rule
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RuleSpec {
+    /// Rewrite subjects matching `from` with a template using `{context}`,
+    /// `{aggregate}`, `{event}` and `{version}`
+    Map { from: String, to: String },
+    /// Move subjects from one context to another
+    Context {
+        from_context: String,
+        to_context: String,
+    },
+}
+
+type CliResult<T> = Result<T, String>;
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::from(2)
+        },
+    }
+}
+
+/// Run a command, returning whether its answer was "yes"
+fn run(command: Command) -> CliResult<bool> {
+    match command {
+        Command::Validate { subjects } => validate(subjects),
+        Command::Match { pattern, subject } => {
+            let pattern = Pattern::new(pattern).map_err(|e| e.to_string())?;
+            let subject = Subject::new(subject).map_err(|e| e.to_string())?;
+            let matches = pattern.matches(&subject);
+            println!("{matches}");
+            Ok(matches)
+        },
+        Command::Translate {
+            rules,
+            reverse,
+            subjects,
+        } => {
+            let translator = load_translator(&rules)?;
+            for subject in subjects {
+                let subject = Subject::new(subject).map_err(|e| e.to_string())?;
+                let translated = if reverse {
+                    translator.reverse_translate(&subject)
+                } else {
+                    translator.translate(&subject)
+                }
+                .map_err(|e| e.to_string())?;
+                println!("{subject} -> {translated}");
+            }
+            Ok(true)
+        },
+        Command::Perm {
+            command:
+                PermCommand::Check {
+                    policy,
+                    subject,
+                    operation,
+                    explain,
+                },
+        } => {
+            let permissions: Permissions = load(&policy)?;
+            let subject = Subject::new(subject).map_err(|e| e.to_string())?;
+            let operation: Operation = serde_yaml::from_str(&operation)
+                .map_err(|_| format!("unknown operation '{operation}'"))?;
+            let allowed = permissions.is_allowed(&subject, operation);
+            if explain {
+                println!("{}", permissions.explain(&subject, operation));
+            } else {
+                println!("{}", if allowed { "allow" } else { "deny" });
+            }
+            Ok(allowed)
+        },
+        Command::Chain {
+            command: ChainCommand::Render { file, format },
+        } => {
+            let chain = load_chain(&file)?;
+            let rendered = match format {
+                ChainFormat::Dot => chain.to_dot(),
+                ChainFormat::Mermaid => chain.to_mermaid(),
+                ChainFormat::Json => {
+                    serde_json::to_string_pretty(&chain.graph()).map_err(|e| e.to_string())?
+                },
+            };
+            println!("{rendered}");
+            Ok(true)
+        },
+    }
+}
+
+/// Report invalid subjects, answering whether all were valid
+fn validate(subjects: Vec<String>) -> CliResult<bool> {
+    let subjects = if subjects.is_empty() {
+        io::stdin()
+            .lock()
+            .lines()
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?
+    } else {
+        subjects
+    };
+
+    let mut all_valid = true;
+    for subject in subjects.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        if let Err(error) = Subject::new(subject) {
+            println!("{subject}: {error}");
+            all_valid = false;
+        }
+    }
+    Ok(all_valid)
+}
+
+/// Deserialize a YAML or JSON file
+fn load<T: DeserializeOwned>(path: &Path) -> CliResult<T> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    // YAML is a superset of JSON
+    serde_yaml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn load_translator(path: &Path) -> CliResult<Translator> {
+    let file: RulesFile = load(path)?;
+    file.rules
+        .iter()
+        .try_fold(TranslatorBuilder::new(), |builder, rule| match rule {
+            RuleSpec::Map { from, to } => builder.map(from, to),
+            RuleSpec::Context {
+                from_context,
+                to_context,
+            } => builder.translate_context(from_context, to_context),
+        })
+        .map(TranslatorBuilder::build)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn load_chain(path: &Path) -> CliResult<CorrelationChain> {
+    let identities: Vec<MessageIdentity> = load(path)?;
+    let mut identities = identities.into_iter();
+    let root = identities
+        .next()
+        .ok_or_else(|| format!("{}: a chain needs a root message", path.display()))?;
+    let mut chain = CorrelationChain::new(root).map_err(|e| e.to_string())?;
+    for identity in identities {
+        chain.add_message(identity).map_err(|e| e.to_string())?;
+    }
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_file() {
+        let file: RulesFile = serde_yaml::from_str(
+            r"
+rules:
+  - from: orders.*.*.v1
+    to: legacy.{aggregate}.{event}.v1
+  - from_context: billing
+    to_context: finance
+",
+        )
+        .unwrap();
+        assert!(matches!(file.rules[0], RuleSpec::Map { .. }));
+        assert!(matches!(file.rules[1], RuleSpec::Context { .. }));
+
+        let path =
+            std::env::temp_dir().join(format!("cim-subject-rules-{}.yaml", std::process::id()));
+        fs::write(
+            &path,
+            "rules:\n  - from: orders.*.*.v1\n    to: legacy.{aggregate}.{event}.v1\n",
+        )
+        .unwrap();
+        let translator = load_translator(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let subject = Subject::new("orders.order.placed.v1").unwrap();
+        assert_eq!(
+            translator.translate(&subject).unwrap().as_str(),
+            "legacy.order.placed.v1"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Message types that carry their own addressing
+//!
+//! A [`SubjectBound`] type knows the subject template it is published on
+//! and whether it is a command, query or event, so code handling it never
+//! spells out subjects or picks identity constructors by hand. With the
+//! `derive` feature, `#[derive(SubjectBound)]` implements it from a
+//! `#[subject(template = "...", kind = "...")]` attribute, filling each
+//! `{field}` token from the named field.
+//!
+//! ```
+//! use cim_subject::bound::SubjectBound;
+//! use cim_subject::correlation::kinds::Command;
+//! use cim_subject::{
+//!     Result,
+//!     Subject,
+//! };
+//!
+//! struct PlaceOrder {
+//!     region: String,
+//! }
+//!
+//! impl SubjectBound for PlaceOrder {
+//!     type Kind = Command;
+//!
+//!     const TEMPLATE: &'static str = "orders.order.place.{region}";
+//!
+//!     fn subject(&self) -> Result<Subject> {
+//!         Subject::new(format!("orders.order.place.{}", self.region))
+//!     }
+//! }
+//!
+//! let command = PlaceOrder {
+//!     region: "eu".to_string(),
+//! };
+//! assert!(PlaceOrder::pattern().matches(&command.subject()?));
+//! assert_eq!(PlaceOrder::pattern().as_str(), "orders.order.place.*");
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+#[cfg(feature = "derive")]
+pub use cim_subject_derive::SubjectBound;
+
+use crate::correlation::{
+    Kind,
+    MessageFactory,
+    MessageIdentity,
+};
+use crate::error::Result;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// A message type bound to a subject template and message kind
+pub trait SubjectBound {
+    /// Whether the message is a command, query or event
+    type Kind: Kind;
+
+    /// The subject template, with `{field}` tokens for per-message values
+    const TEMPLATE: &'static str;
+
+    /// The subject this message is published on
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field value is not a valid subject token
+    fn subject(&self) -> Result<Subject>;
+
+    /// The pattern matching every subject of this type, with `*` in place
+    /// of each `{field}` token
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`TEMPLATE`](Self::TEMPLATE) is not a valid pattern once
+    /// its placeholders are replaced, which the derive rules out
+    #[must_use]
+    fn pattern() -> Pattern {
+        template_pattern(Self::TEMPLATE).expect("a subject template is a valid pattern")
+    }
+
+    /// An identity for this message starting a new correlation chain
+    #[must_use]
+    fn root_identity(id: <Self::Kind as Kind>::Id) -> MessageIdentity {
+        MessageFactory::root::<Self::Kind>(id)
+    }
+
+    /// An identity for this message caused by `parent`
+    #[must_use]
+    fn caused_identity(id: <Self::Kind as Kind>::Id, parent: &MessageIdentity) -> MessageIdentity {
+        MessageFactory::caused::<Self::Kind>(id, parent)
+    }
+}
+
+/// The pattern for a subject template, with `*` in place of placeholders
+///
+/// # Errors
+///
+/// Returns an error if the result is not a valid pattern
+pub fn template_pattern(template: &str) -> Result<Pattern> {
+    let tokens: Vec<&str> = template
+        .split('.')
+        .map(|token| {
+            if token.starts_with('{') && token.ends_with('}') {
+                "*"
+            } else {
+                token
+            }
+        })
+        .collect();
+    Pattern::new(tokens.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::kinds::Query;
+    use crate::correlation::MessageKind;
+
+    struct FindOrders;
+
+    impl SubjectBound for FindOrders {
+        type Kind = Query;
+
+        const TEMPLATE: &'static str = "orders.order.find.v1";
+
+        fn subject(&self) -> Result<Subject> {
+            Subject::new(Self::TEMPLATE)
+        }
+    }
+
+    #[test]
+    fn test_identities_carry_kind() {
+        let root = FindOrders::root_identity(Uuid::new_v4());
+        assert!(root.is_root());
+        assert_eq!(root.kind, Some(MessageKind::Query));
+
+        let caused = FindOrders::caused_identity(Uuid::new_v4(), &root);
+        assert_eq!(caused.correlation_id, root.correlation_id);
+        assert!(FindOrders::pattern().matches(&FindOrders.subject().unwrap()));
+        assert!(template_pattern("orders.{id}.>").is_ok());
+        assert!(template_pattern("orders..x").is_err());
+    }
+}
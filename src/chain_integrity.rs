@@ -0,0 +1,306 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Causation-chain integrity checks for live message streams
+//!
+//! [`CorrelationValidator`](crate::correlation::CorrelationValidator)
+//! checks one identity at a time. A [`ChainIntegrityChecker`] consumes
+//! identities in arrival order and reports anomalies that only show up
+//! across messages: causes that were never seen, effects filed under the
+//! wrong correlation, causes fanning out to too many effects, and messages
+//! arriving in a correlation already marked complete.
+//!
+//! ```
+//! use cim_subject::chain_integrity::{
+//!     ChainAnomaly,
+//!     ChainIntegrityChecker,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//! };
+//! use uuid::Uuid;
+//!
+//! let mut checker = ChainIntegrityChecker::new();
+//! let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let placed = MessageIdentity::caused_by(
+//!     IdType::Uuid(Uuid::new_v4()),
+//!     root.correlation_id.clone(),
+//!     root.message_id.clone(),
+//! );
+//!
+//! // The effect arrives before its cause
+//! let anomalies = checker.observe(&placed);
+//! assert!(matches!(anomalies[..], [
+//!     ChainAnomaly::OrphanCausation { .. }
+//! ]));
+//! assert!(checker.observe(&root).is_empty());
+//!
+//! checker.complete(&root.correlation_id);
+//! let late = MessageIdentity::caused_by(
+//!     IdType::Uuid(Uuid::new_v4()),
+//!     root.correlation_id.clone(),
+//!     placed.message_id.clone(),
+//! );
+//! assert!(matches!(checker.observe(&late)[..], [
+//!     ChainAnomaly::Resurrection { .. }
+//! ]));
+//! ```
+
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+use std::fmt::{
+    self,
+    Display,
+};
+
+use crate::correlation::{
+    CorrelationId,
+    IdType,
+    MessageIdentity,
+};
+
+/// An inconsistency found by a [`ChainIntegrityChecker`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainAnomaly {
+    /// A message whose cause has not been seen
+    OrphanCausation {
+        /// The message
+        message_id: IdType,
+        /// Its unseen cause
+        causation_id: IdType,
+    },
+    /// A message whose correlation differs from its cause's
+    CorrelationMismatch {
+        /// The message
+        message_id: IdType,
+        /// The cause's correlation
+        expected: CorrelationId,
+        /// The message's correlation
+        found: CorrelationId,
+    },
+    /// A message caused more messages than the fork limit allows; reported
+    /// once, when the limit is first exceeded
+    ForkLimitExceeded {
+        /// The cause
+        message_id: IdType,
+        /// The limit
+        limit: usize,
+    },
+    /// A message arrived in a correlation already marked complete
+    Resurrection {
+        /// The message
+        message_id: IdType,
+        /// The completed correlation
+        correlation_id: CorrelationId,
+    },
+}
+
+impl Display for ChainAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OrphanCausation {
+                message_id,
+                causation_id,
+            } => write!(f, "{message_id} was caused by unseen {causation_id}"),
+            Self::CorrelationMismatch {
+                message_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{message_id} is in {found} but its cause is in {expected}"
+            ),
+            Self::ForkLimitExceeded { message_id, limit } => {
+                write!(f, "{message_id} caused more than {limit} messages")
+            },
+            Self::Resurrection {
+                message_id,
+                correlation_id,
+            } => write!(f, "{message_id} arrived after {correlation_id} completed"),
+        }
+    }
+}
+
+/// What the checker remembers about a seen message
+#[derive(Debug)]
+struct Seen {
+    correlation_id: CorrelationId,
+    children: usize,
+}
+
+/// Checks identities across a stream, in arrival order
+#[derive(Debug)]
+pub struct ChainIntegrityChecker {
+    max_fork: usize,
+    seen: HashMap<IdType, Seen>,
+    by_correlation: HashMap<CorrelationId, Vec<IdType>>,
+    completed: HashSet<CorrelationId>,
+}
+
+impl Default for ChainIntegrityChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainIntegrityChecker {
+    /// Create a checker allowing each message to cause up to 100 others
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            max_fork: 100,
+            seen: HashMap::new(),
+            by_correlation: HashMap::new(),
+            completed: HashSet::new(),
+        }
+    }
+
+    /// Allow each message to cause at most `max` others
+    #[must_use]
+    pub fn max_fork(mut self, max: usize) -> Self {
+        self.max_fork = max;
+        self
+    }
+
+    /// Check the next identity of the stream and remember it
+    ///
+    /// A message in a completed correlation is only reported as a
+    /// [`ChainAnomaly::Resurrection`], since its causes were forgotten.
+    pub fn observe(&mut self, identity: &MessageIdentity) -> Vec<ChainAnomaly> {
+        let correlation_id = &identity.correlation_id;
+        if self.completed.contains(correlation_id) {
+            return vec![ChainAnomaly::Resurrection {
+                message_id: identity.message_id.clone(),
+                correlation_id: correlation_id.clone(),
+            }];
+        }
+
+        let mut anomalies = Vec::new();
+        if !identity.is_root() {
+            let causation_id = &identity.causation_id.0;
+            match self.seen.get_mut(causation_id) {
+                None => anomalies.push(ChainAnomaly::OrphanCausation {
+                    message_id: identity.message_id.clone(),
+                    causation_id: causation_id.clone(),
+                }),
+                Some(parent) => {
+                    if &parent.correlation_id != correlation_id {
+                        anomalies.push(ChainAnomaly::CorrelationMismatch {
+                            message_id: identity.message_id.clone(),
+                            expected: parent.correlation_id.clone(),
+                            found: correlation_id.clone(),
+                        });
+                    }
+                    parent.children += 1;
+                    if parent.children == self.max_fork + 1 {
+                        anomalies.push(ChainAnomaly::ForkLimitExceeded {
+                            message_id: causation_id.clone(),
+                            limit: self.max_fork,
+                        });
+                    }
+                },
+            }
+        }
+
+        self.seen.insert(identity.message_id.clone(), Seen {
+            correlation_id: correlation_id.clone(),
+            children: 0,
+        });
+        self.by_correlation
+            .entry(correlation_id.clone())
+            .or_default()
+            .push(identity.message_id.clone());
+        anomalies
+    }
+
+    /// Mark a correlation complete: its messages are forgotten, and any
+    /// later message in it is a [`ChainAnomaly::Resurrection`]
+    pub fn complete(&mut self, correlation_id: &CorrelationId) {
+        for message_id in self
+            .by_correlation
+            .remove(correlation_id)
+            .unwrap_or_default()
+        {
+            self.seen.remove(&message_id);
+        }
+        self.completed.insert(correlation_id.clone());
+    }
+
+    /// Number of messages remembered
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Check if no messages are remembered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn root() -> MessageIdentity {
+        MessageIdentity::root(IdType::Uuid(Uuid::new_v4()))
+    }
+
+    fn caused(parent: &MessageIdentity) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            parent.correlation_id.clone(),
+            parent.message_id.clone(),
+        )
+    }
+
+    #[test]
+    fn test_healthy_stream() {
+        let mut checker = ChainIntegrityChecker::new();
+        let root = root();
+        let child = caused(&root);
+        let grandchild = caused(&child);
+        for identity in [&root, &child, &grandchild] {
+            assert_eq!(checker.observe(identity), []);
+        }
+        assert_eq!(checker.len(), 3);
+        checker.complete(&root.correlation_id);
+        assert!(checker.is_empty());
+    }
+
+    #[test]
+    fn test_mismatch_and_fork_limit() {
+        let mut checker = ChainIntegrityChecker::new().max_fork(2);
+        let (a, b) = (root(), root());
+        checker.observe(&a);
+        checker.observe(&b);
+
+        let crossed = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            b.correlation_id.clone(),
+            a.message_id.clone(),
+        );
+        assert_eq!(checker.observe(&crossed), [
+            ChainAnomaly::CorrelationMismatch {
+                message_id: crossed.message_id.clone(),
+                expected: a.correlation_id.clone(),
+                found: b.correlation_id.clone(),
+            }
+        ]);
+
+        assert_eq!(checker.observe(&caused(&a)), []);
+        let fork = checker.observe(&caused(&a));
+        assert_eq!(fork, [ChainAnomaly::ForkLimitExceeded {
+            message_id: a.message_id.clone(),
+            limit: 2,
+        }]);
+        assert!(fork[0].to_string().contains("more than 2"));
+        // Reported only once
+        assert_eq!(checker.observe(&caused(&a)), []);
+    }
+}
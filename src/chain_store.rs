@@ -0,0 +1,374 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Persistence for correlation chains
+//!
+//! A [`ChainStore`] records message identities as they are observed and
+//! rebuilds [`CorrelationChain`]s on demand, so causation graphs survive
+//! process restarts. [`InMemoryChainStore`] is a thread-safe store for a
+//! single process; [`FileChainStore`] appends each identity to a JSON Lines
+//! file and replays it on open.
+
+use std::collections::{
+    HashMap,
+    VecDeque,
+};
+use std::fs::{
+    File,
+    OpenOptions,
+};
+use std::io::{
+    Read,
+    Write,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+
+use crate::correlation::{
+    CorrelationError,
+    CorrelationId,
+    IdType,
+    MessageIdentity,
+    Result,
+};
+use crate::message_algebra::CorrelationChain;
+
+/// Storage for message identities, queryable as correlation chains
+pub trait ChainStore: Send + Sync {
+    /// Record a message identity; recording the same message twice is a
+    /// no-op
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identity cannot be persisted
+    fn insert(&self, identity: MessageIdentity) -> Result<()>;
+
+    /// Rebuild the chain for a correlation ID, if its root has been recorded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored messages do not form a valid chain
+    fn get_chain(&self, correlation_id: &CorrelationId) -> Result<Option<CorrelationChain>>;
+
+    /// Messages directly caused by a message, in insertion order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read
+    fn find_children(&self, message_id: &IdType) -> Result<Vec<MessageIdentity>>;
+}
+
+/// Thread-safe in-memory [`ChainStore`]
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryChainStore {
+    /// All messages by ID
+    messages: Arc<DashMap<IdType, MessageIdentity>>,
+    /// Message IDs per correlation, in insertion order
+    by_correlation: Arc<DashMap<CorrelationId, Vec<IdType>>>,
+    /// Child message IDs per cause, in insertion order
+    children: Arc<DashMap<IdType, Vec<IdType>>>,
+}
+
+impl InMemoryChainStore {
+    /// Create an empty store
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of recorded messages
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Check if no messages are recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Record an identity, returning `false` if it was already present
+    fn record(&self, identity: MessageIdentity) -> bool {
+        let Entry::Vacant(slot) = self.messages.entry(identity.message_id.clone()) else {
+            return false;
+        };
+        let id = identity.message_id.clone();
+        self.by_correlation
+            .entry(identity.correlation_id.clone())
+            .or_default()
+            .push(id.clone());
+        if !identity.is_root() {
+            self.children
+                .entry(identity.causation_id.0.clone())
+                .or_default()
+                .push(id);
+        }
+        slot.insert(identity);
+        true
+    }
+}
+
+impl ChainStore for InMemoryChainStore {
+    fn insert(&self, identity: MessageIdentity) -> Result<()> {
+        self.record(identity);
+        Ok(())
+    }
+
+    fn get_chain(&self, correlation_id: &CorrelationId) -> Result<Option<CorrelationChain>> {
+        let Some(ids) = self
+            .by_correlation
+            .get(correlation_id)
+            .map(|ids| ids.clone())
+        else {
+            return Ok(None);
+        };
+        let messages = ids
+            .iter()
+            .filter_map(|id| self.messages.get(id).map(|m| m.clone()))
+            .collect();
+        assemble_chain(messages)
+    }
+
+    fn find_children(&self, message_id: &IdType) -> Result<Vec<MessageIdentity>> {
+        // Copy the IDs out first: `record` locks `messages` before `children`
+        let ids = self
+            .children
+            .get(message_id)
+            .map(|ids| ids.clone())
+            .unwrap_or_default();
+        Ok(ids
+            .iter()
+            .filter_map(|id| self.messages.get(id).map(|m| m.clone()))
+            .collect())
+    }
+}
+
+/// Append-only JSON Lines [`ChainStore`]
+///
+/// Each recorded identity is written as one line and flushed before the
+/// insert returns. Opening an existing file replays it into memory; a last
+/// line left incomplete by a crash mid-write is truncated away.
+#[derive(Debug)]
+pub struct FileChainStore {
+    path: PathBuf,
+    file: Mutex<File>,
+    index: InMemoryChainStore,
+}
+
+impl FileChainStore {
+    /// Open or create a store at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or a complete line is
+    /// not a message identity
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| storage_error(&path, &e))?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
+            .map_err(|e| storage_error(&path, &e))?;
+
+        let index = InMemoryChainStore::new();
+        let mut complete = 0;
+        for (number, line) in contents.split_inclusive(|&b| b == b'\n').enumerate() {
+            let Some(line) = line.strip_suffix(b"\n") else {
+                // Only the last line can lack its newline: the write of it
+                // was interrupted, so drop it before appending after it
+                file.set_len(complete as u64)
+                    .map_err(|e| storage_error(&path, &e))?;
+                break;
+            };
+            complete += line.len() + 1;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let identity: MessageIdentity = serde_json::from_slice(line).map_err(|e| {
+                CorrelationError::Storage(format!(
+                    "{}:{}: invalid identity: {e}",
+                    path.display(),
+                    number + 1
+                ))
+            })?;
+            index.record(identity);
+        }
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            index,
+        })
+    }
+
+    /// Path of the backing file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ChainStore for FileChainStore {
+    fn insert(&self, identity: MessageIdentity) -> Result<()> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| CorrelationError::Storage("chain store lock poisoned".to_string()))?;
+        if self.index.messages.contains_key(&identity.message_id) {
+            return Ok(());
+        }
+
+        let line = serde_json::to_string(&identity)
+            .map_err(|e| CorrelationError::Storage(e.to_string()))?;
+        writeln!(file, "{line}")
+            .and_then(|()| file.flush())
+            .map_err(|e| storage_error(&self.path, &e))?;
+        self.index.record(identity);
+        Ok(())
+    }
+
+    fn get_chain(&self, correlation_id: &CorrelationId) -> Result<Option<CorrelationChain>> {
+        self.index.get_chain(correlation_id)
+    }
+
+    fn find_children(&self, message_id: &IdType) -> Result<Vec<MessageIdentity>> {
+        self.index.find_children(message_id)
+    }
+}
+
+/// Build a chain from one correlation's messages in any order
+///
+/// Returns `None` if the root is not among the messages.
+///
+/// # Errors
+///
+/// Returns an error if a message's cause is not among the messages, or a
+/// message cannot be added to the chain
+pub fn assemble_chain(messages: Vec<MessageIdentity>) -> Result<Option<CorrelationChain>> {
+    let (roots, pending): (Vec<_>, Vec<_>) =
+        messages.into_iter().partition(MessageIdentity::is_root);
+    let Some(root) = roots.into_iter().next() else {
+        return Ok(None);
+    };
+    let mut chain = CorrelationChain::new(root)?;
+
+    // Messages may have been stored before their causes; add each cause's
+    // effects once the cause is in the chain
+    let mut by_cause: HashMap<IdType, Vec<MessageIdentity>> = HashMap::new();
+    for message in pending {
+        by_cause
+            .entry(message.causation_id.0.clone())
+            .or_default()
+            .push(message);
+    }
+    let mut causes = VecDeque::from([chain.root.message_id.clone()]);
+    while let Some(cause) = causes.pop_front() {
+        for message in by_cause.remove(&cause).unwrap_or_default() {
+            causes.push_back(message.message_id.clone());
+            chain.add_message(message)?;
+        }
+    }
+
+    let orphans: usize = by_cause.values().map(Vec::len).sum();
+    if orphans > 0 {
+        return Err(CorrelationError::InvalidIdentity(format!(
+            "{orphans} stored messages have no recorded cause"
+        )));
+    }
+    Ok(Some(chain))
+}
+
+fn storage_error(path: &Path, error: &std::io::Error) -> CorrelationError {
+    CorrelationError::Storage(format!("{}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    fn sample() -> [MessageIdentity; 3] {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let child = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let grandchild = MessageFactory::command_from_command(Uuid::new_v4(), &child);
+        [root, child, grandchild]
+    }
+
+    #[test]
+    fn test_in_memory_store() {
+        let store = InMemoryChainStore::new();
+        let [root, child, grandchild] = sample();
+
+        // Out-of-order and duplicate inserts are tolerated
+        for message in [&grandchild, &root, &child, &child] {
+            store.insert(message.clone()).unwrap();
+        }
+        assert_eq!(store.len(), 3);
+
+        let chain = store.get_chain(&root.correlation_id).unwrap().unwrap();
+        assert_eq!(chain.depth(), 2);
+        assert_eq!(store.find_children(&root.message_id).unwrap(), vec![
+            child.clone()
+        ]);
+        assert!(store
+            .get_chain(&CorrelationId::from_uuid(Uuid::new_v4()))
+            .unwrap()
+            .is_none());
+
+        // A message whose cause was never recorded cannot be assembled
+        let orphan_store = InMemoryChainStore::new();
+        orphan_store.insert(root.clone()).unwrap();
+        orphan_store.insert(grandchild).unwrap();
+        assert!(orphan_store.get_chain(&root.correlation_id).is_err());
+    }
+
+    #[test]
+    fn test_file_store_replays_on_open() {
+        let path = std::env::temp_dir().join(format!("chain-store-{}.jsonl", Uuid::new_v4()));
+        let [root, child, grandchild] = sample();
+
+        {
+            let store = FileChainStore::open(&path).unwrap();
+            for message in [&root, &child, &grandchild] {
+                store.insert(message.clone()).unwrap();
+            }
+        }
+
+        let store = FileChainStore::open(&path).unwrap();
+        let chain = store.get_chain(&root.correlation_id).unwrap().unwrap();
+        assert_eq!(chain.messages.len(), 3);
+        assert_eq!(store.find_children(&child.message_id).unwrap(), vec![
+            grandchild.clone()
+        ]);
+        drop(store);
+
+        // A crash mid-write leaves a torn last line, which is dropped
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"message_id":{"Uuid""#).unwrap();
+        drop(file);
+        let store = FileChainStore::open(&path).unwrap();
+        assert_eq!(store.index.len(), 3);
+        let late = MessageFactory::command_from_command(Uuid::new_v4(), &grandchild);
+        store.insert(late).unwrap();
+        drop(store);
+        let store = FileChainStore::open(&path).unwrap();
+        assert_eq!(store.index.len(), 4);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,579 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Circuit breakers per subject family
+//!
+//! A [`CircuitBreaker`] tracks handler and translator failures for each
+//! registered pattern; a subject belongs to the most specific matching
+//! family. After [`failure_threshold`] consecutive failures a family's
+//! circuit opens and its subjects are rejected without being handled. Once
+//! [`open_for`] has passed the circuit is half-open: a limited number of
+//! probes go through, and enough successes close it again while any
+//! failure reopens it. Transition hooks see every state change.
+//!
+//! Where retries and dead-lettering deal with a message that keeps failing,
+//! a breaker protects a downstream that keeps failing, by failing fast for
+//! every message headed its way. Wrap a route's handler with
+//! [`wrap`](CircuitBreaker::wrap), and to reject subjects before any other
+//! middleware runs, check them with
+//! [`middleware`](CircuitBreaker::middleware) too.
+//!
+//! [`failure_threshold`]: BreakerConfig::failure_threshold
+//! [`open_for`]: BreakerConfig::open_for
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cim_subject::circuit_breaker::{
+//!     BreakerConfig,
+//!     BreakerState,
+//!     CircuitBreakerBuilder,
+//! };
+//! use cim_subject::router::Router;
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//!     SubjectError,
+//! };
+//!
+//! let breaker = CircuitBreakerBuilder::new()
+//!     .family(
+//!         Pattern::new("payments.>")?,
+//!         BreakerConfig::new(2, Duration::from_secs(30)),
+//!     )
+//!     .build();
+//! let router = Router::<String>::new().route(
+//!     Pattern::new("payments.>")?,
+//!     breaker.wrap(|_, _| Err(SubjectError::validation_error("gateway down"))),
+//! );
+//!
+//! let subject = Subject::new("payments.card.charge.v1")?;
+//! for _ in 0..2 {
+//!     assert!(router.dispatch(&subject, &"charge".to_string()).is_err());
+//! }
+//! assert_eq!(breaker.state(&subject), Some(BreakerState::Open));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+    PoisonError,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Receives every state change of a family's circuit
+pub type TransitionHook = Arc<dyn Fn(&BreakerTransition) + Send + Sync>;
+
+/// State of a family's circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakerState {
+    /// Subjects are handled normally
+    Closed,
+    /// Subjects are rejected without being handled
+    Open,
+    /// A limited number of probes are let through
+    HalfOpen,
+}
+
+impl Display for BreakerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "closed"),
+            Self::Open => write!(f, "open"),
+            Self::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// When a family's circuit opens and closes again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before probing, and how long a
+    /// probe may go unreported before its slot is freed
+    pub open_for: Duration,
+    /// Successful probes needed to close a half-open circuit, and how many
+    /// may be in flight at once
+    pub half_open_probes: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+impl BreakerConfig {
+    /// Open after `failure_threshold` consecutive failures, probing with one
+    /// message after `open_for`
+    #[must_use]
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            half_open_probes: 1,
+        }
+    }
+
+    /// Require `probes` successful probes to close a half-open circuit
+    #[must_use]
+    pub fn with_half_open_probes(mut self, probes: u32) -> Self {
+        self.half_open_probes = probes.max(1);
+        self
+    }
+}
+
+/// A change in a family's circuit state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakerTransition {
+    /// The family's pattern
+    pub pattern: Pattern,
+    /// The subject whose outcome or admission caused the change
+    pub subject: Subject,
+    /// State before
+    pub from: BreakerState,
+    /// State after
+    pub to: BreakerState,
+}
+
+/// Counters behind a family's state
+#[derive(Debug)]
+struct Circuit {
+    state: BreakerState,
+    /// Consecutive failures while closed
+    failures: u32,
+    /// When the circuit last opened
+    opened_at: Option<Instant>,
+    /// Probes admitted while half-open
+    probes: u32,
+    /// When the latest probe was admitted
+    probed_at: Option<Instant>,
+    /// Successful probes while half-open
+    successes: u32,
+}
+
+struct Family {
+    pattern: Pattern,
+    config: BreakerConfig,
+    circuit: Mutex<Circuit>,
+}
+
+/// Circuit breakers for subject families, most specific pattern first
+///
+/// Clones share their circuits, so one breaker can be handed to several
+/// routes and middleware. Build one with [`CircuitBreakerBuilder`].
+#[derive(Clone, Default)]
+pub struct CircuitBreaker {
+    families: Arc<Vec<Family>>,
+    hooks: Arc<Vec<TransitionHook>>,
+}
+
+/// Builder for a [`CircuitBreaker`]
+#[derive(Default)]
+pub struct CircuitBreakerBuilder {
+    families: Vec<Family>,
+    hooks: Vec<TransitionHook>,
+}
+
+impl CircuitBreakerBuilder {
+    /// Create a builder with no families
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a family with its own closed circuit
+    #[must_use]
+    pub fn family(mut self, pattern: Pattern, config: BreakerConfig) -> Self {
+        self.families.push(Family {
+            pattern,
+            config,
+            circuit: Mutex::new(Circuit {
+                state: BreakerState::Closed,
+                failures: 0,
+                opened_at: None,
+                probes: 0,
+                probed_at: None,
+                successes: 0,
+            }),
+        });
+        self
+    }
+
+    /// Call `hook` on every state change
+    #[must_use]
+    pub fn on_transition<F>(mut self, hook: F) -> Self
+    where F: Fn(&BreakerTransition) + Send + Sync + 'static {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Build the breaker
+    #[must_use]
+    pub fn build(mut self) -> CircuitBreaker {
+        // Stable, so equally specific families keep registration order
+        self.families
+            .sort_by_key(|family| family.pattern.specificity_key());
+        CircuitBreaker {
+            families: Arc::new(self.families),
+            hooks: Arc::new(self.hooks),
+        }
+    }
+}
+
+impl fmt::Debug for CircuitBreakerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreakerBuilder")
+            .field(
+                "families",
+                &self
+                    .families
+                    .iter()
+                    .map(|family| &family.pattern)
+                    .collect::<Vec<_>>(),
+            )
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    /// The state of a subject's family; `None` if it has no family
+    #[must_use]
+    pub fn state(&self, subject: &Subject) -> Option<BreakerState> {
+        self.family_of(subject)
+            .map(|family| lock(&family.circuit).state)
+    }
+
+    /// Check whether a subject may be handled now
+    ///
+    /// # Errors
+    ///
+    /// Returns a permission denied error if the family's circuit is open,
+    /// or half-open with all its probes in flight
+    pub fn allow(&self, subject: &Subject) -> Result<()> {
+        self.allow_at(subject, Instant::now())
+    }
+
+    /// Check whether a subject may be handled as of `now`, admitting it as
+    /// a probe if the circuit is half-open
+    ///
+    /// A probe whose outcome is not recorded within
+    /// [`open_for`](BreakerConfig::open_for) gives up its slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns a permission denied error if the family's circuit is open,
+    /// or half-open with all its probes in flight
+    pub fn allow_at(&self, subject: &Subject, now: Instant) -> Result<()> {
+        self.admit(subject, now, true)
+    }
+
+    /// Check whether a subject would be allowed as of `now`, without taking
+    /// a probe slot
+    ///
+    /// # Errors
+    ///
+    /// Returns a permission denied error if the family's circuit is open,
+    /// or half-open with all its probes in flight
+    pub fn check_at(&self, subject: &Subject, now: Instant) -> Result<()> {
+        self.admit(subject, now, false)
+    }
+
+    fn admit(&self, subject: &Subject, now: Instant, take_probe: bool) -> Result<()> {
+        let Some(family) = self.family_of(subject) else {
+            return Ok(());
+        };
+        let mut circuit = lock(&family.circuit);
+        let from = circuit.state;
+        if circuit.state == BreakerState::Open
+            && circuit
+                .opened_at
+                .map_or(true, |at| now.duration_since(at) >= family.config.open_for)
+        {
+            circuit.state = BreakerState::HalfOpen;
+            circuit.probes = 0;
+            circuit.successes = 0;
+        }
+        if circuit.state == BreakerState::HalfOpen
+            && circuit
+                .probed_at
+                .is_some_and(|at| now.duration_since(at) >= family.config.open_for)
+        {
+            // The probes in flight never reported back
+            circuit.probes = 0;
+        }
+        let allowed = match circuit.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => false,
+            BreakerState::HalfOpen => {
+                let admit = circuit.probes < family.config.half_open_probes;
+                if admit && take_probe {
+                    circuit.probes += 1;
+                    circuit.probed_at = Some(now);
+                }
+                admit
+            },
+        };
+        let to = circuit.state;
+        drop(circuit);
+
+        self.notify(family, subject, from, to);
+        if allowed {
+            Ok(())
+        } else {
+            Err(SubjectError::permission_denied(format!(
+                "Circuit for '{}' is {to}; '{subject}' was not handled",
+                family.pattern
+            )))
+        }
+    }
+
+    /// Record that handling a subject succeeded
+    pub fn record_success(&self, subject: &Subject) {
+        self.record_at(subject, true, Instant::now());
+    }
+
+    /// Record that handling a subject failed
+    pub fn record_failure(&self, subject: &Subject) {
+        self.record_at(subject, false, Instant::now());
+    }
+
+    /// Record the outcome of handling a subject as of `now`
+    pub fn record_at(&self, subject: &Subject, succeeded: bool, now: Instant) {
+        let Some(family) = self.family_of(subject) else {
+            return;
+        };
+        let mut circuit = lock(&family.circuit);
+        let from = circuit.state;
+        match (circuit.state, succeeded) {
+            (BreakerState::Closed, true) => circuit.failures = 0,
+            (BreakerState::Closed, false) => {
+                circuit.failures += 1;
+                if circuit.failures >= family.config.failure_threshold {
+                    circuit.state = BreakerState::Open;
+                    circuit.opened_at = Some(now);
+                }
+            },
+            (BreakerState::HalfOpen, true) => {
+                circuit.probes = circuit.probes.saturating_sub(1);
+                circuit.successes += 1;
+                if circuit.successes >= family.config.half_open_probes {
+                    circuit.state = BreakerState::Closed;
+                    circuit.failures = 0;
+                }
+            },
+            (BreakerState::HalfOpen, false) => {
+                circuit.state = BreakerState::Open;
+                circuit.opened_at = Some(now);
+            },
+            // Outcomes of work admitted before the circuit opened
+            (BreakerState::Open, _) => {},
+        }
+        let to = circuit.state;
+        drop(circuit);
+        self.notify(family, subject, from, to);
+    }
+
+    /// Run `f` for a subject if its circuit allows it, recording the outcome
+    ///
+    /// # Errors
+    ///
+    /// Returns the breaker's error if the circuit rejects the subject, or
+    /// the error from `f`
+    pub fn call<T>(&self, subject: &Subject, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.allow(subject)?;
+        let outcome = f();
+        self.record_at(subject, outcome.is_ok(), Instant::now());
+        outcome
+    }
+
+    /// A router handler that runs `handler` through [`call`](Self::call)
+    pub fn wrap<M, F>(&self, handler: F) -> impl Fn(&Subject, &M) -> Result<()> + Send + Sync
+    where F: Fn(&Subject, &M) -> Result<()> + Send + Sync {
+        let breaker = self.clone();
+        move |subject, message| breaker.call(subject, || handler(subject, message))
+    }
+
+    /// Router middleware rejecting subjects whose circuit is open
+    ///
+    /// The check takes no probe slot, so it composes with a
+    /// [wrapped](Self::wrap) handler, which admits the probe and reports
+    /// its outcome.
+    pub fn middleware<M>(&self) -> impl Fn(&Subject, &M) -> Result<()> + Send + Sync {
+        let breaker = self.clone();
+        move |subject, _| breaker.check_at(subject, Instant::now())
+    }
+
+    fn family_of(&self, subject: &Subject) -> Option<&Family> {
+        self.families
+            .iter()
+            .find(|family| family.pattern.matches(subject))
+    }
+
+    fn notify(&self, family: &Family, subject: &Subject, from: BreakerState, to: BreakerState) {
+        if from == to {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(pattern = %family.pattern, %subject, %from, %to, "circuit changed state");
+        let transition = BreakerTransition {
+            pattern: family.pattern.clone(),
+            subject: subject.clone(),
+            from,
+            to,
+        };
+        for hook in self.hooks.iter() {
+            hook(&transition);
+        }
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field(
+                "families",
+                &self
+                    .families
+                    .iter()
+                    .map(|family| (&family.pattern, lock(&family.circuit).state))
+                    .collect::<Vec<_>>(),
+            )
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+fn lock(circuit: &Mutex<Circuit>) -> std::sync::MutexGuard<'_, Circuit> {
+    circuit.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_half_open_and_close() {
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&transitions);
+        let breaker = CircuitBreakerBuilder::new()
+            .family(
+                Pattern::new("payments.>").unwrap(),
+                BreakerConfig::new(2, Duration::from_secs(10)).with_half_open_probes(2),
+            )
+            .on_transition(move |t| seen.lock().unwrap().push((t.from, t.to)))
+            .build();
+        let subject = Subject::new("payments.card.charge.v1").unwrap();
+        let start = Instant::now();
+
+        breaker.record_at(&subject, false, start);
+        breaker.record_at(&subject, true, start);
+        breaker.record_at(&subject, false, start);
+        assert_eq!(breaker.state(&subject), Some(BreakerState::Closed));
+        breaker.record_at(&subject, false, start);
+        assert_eq!(breaker.state(&subject), Some(BreakerState::Open));
+        assert!(breaker
+            .allow_at(&subject, start + Duration::from_secs(5))
+            .is_err());
+
+        // Two probes are admitted, a third waits for their outcomes
+        let later = start + Duration::from_secs(10);
+        assert!(breaker.allow_at(&subject, later).is_ok());
+        assert!(breaker.allow_at(&subject, later).is_ok());
+        assert!(breaker.allow_at(&subject, later).is_err());
+        breaker.record_at(&subject, true, later);
+        assert_eq!(breaker.state(&subject), Some(BreakerState::HalfOpen));
+        breaker.record_at(&subject, true, later);
+        assert_eq!(breaker.state(&subject), Some(BreakerState::Closed));
+
+        assert_eq!(*transitions.lock().unwrap(), [
+            (BreakerState::Closed, BreakerState::Open),
+            (BreakerState::Open, BreakerState::HalfOpen),
+            (BreakerState::HalfOpen, BreakerState::Closed),
+        ]);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens_and_families_are_separate() {
+        let breaker = CircuitBreakerBuilder::new()
+            .family(
+                Pattern::new("payments.>").unwrap(),
+                BreakerConfig::new(1, Duration::from_secs(10)),
+            )
+            .family(
+                Pattern::new("payments.refund.>").unwrap(),
+                BreakerConfig::new(1, Duration::from_secs(10)),
+            )
+            .build();
+        let charge = Subject::new("payments.card.charge.v1").unwrap();
+        let refund = Subject::new("payments.refund.issue.v1").unwrap();
+        let start = Instant::now();
+
+        breaker.record_at(&charge, false, start);
+        assert_eq!(breaker.state(&charge), Some(BreakerState::Open));
+        assert_eq!(breaker.state(&refund), Some(BreakerState::Closed));
+        assert!(breaker.allow_at(&refund, start).is_ok());
+        assert!(breaker
+            .allow(&Subject::new("orders.order.placed.v1").unwrap())
+            .is_ok());
+
+        let later = start + Duration::from_secs(10);
+        assert!(breaker.allow_at(&charge, later).is_ok());
+        breaker.record_at(&charge, false, later);
+        assert_eq!(breaker.state(&charge), Some(BreakerState::Open));
+        assert!(matches!(
+            breaker.call(&charge, || Ok(())),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_middleware_and_abandoned_probes() {
+        let breaker = CircuitBreakerBuilder::new()
+            .family(
+                Pattern::new("payments.>").unwrap(),
+                BreakerConfig::new(1, Duration::from_millis(20)),
+            )
+            .build();
+        let router = crate::router::Router::<()>::new()
+            .middleware(breaker.middleware())
+            .route(
+                Pattern::new("payments.>").unwrap(),
+                breaker.wrap(|_, (): &()| Ok(())),
+            );
+        let subject = Subject::new("payments.card.charge.v1").unwrap();
+
+        breaker.record_failure(&subject);
+        assert!(router.dispatch(&subject, &()).is_err());
+        std::thread::sleep(Duration::from_millis(20));
+        // The middleware leaves the only probe slot to the wrapped handler
+        assert!(router.dispatch(&subject, &()).is_ok());
+        assert_eq!(breaker.state(&subject), Some(BreakerState::Closed));
+
+        // A probe that never reports back frees its slot after open_for
+        breaker.record_failure(&subject);
+        let reopened = Instant::now();
+        let probing = reopened + Duration::from_millis(20);
+        assert!(breaker.allow_at(&subject, probing).is_ok());
+        assert!(breaker.allow_at(&subject, probing).is_err());
+        assert!(breaker
+            .allow_at(&subject, probing + Duration::from_millis(20))
+            .is_ok());
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Data-classification labels on subject families
+//!
+//! A [`ClassificationRegistry`] maps patterns to [`DataClass`] labels; the
+//! most specific matching pattern decides a subject's labels. Restrictions
+//! keep labelled data away from destinations: [`check_flow`] rejects moving
+//! a labelled subject onto a restricted one, [`guard`] applies that check to
+//! every rule of a [`Translator`], and [`permission_rules`] denies
+//! publishing to restricted subjects that are labelled themselves or whose
+//! message lists the label in its [`CLASSIFICATION_HEADER`].
+//!
+//! [`check_flow`]: ClassificationRegistry::check_flow
+//! [`guard`]: ClassificationRegistry::guard
+//! [`permission_rules`]: ClassificationRegistry::permission_rules
+//!
+//! ```
+//! use cim_subject::classification::{
+//!     ClassificationRegistry,
+//!     DataClass,
+//! };
+//! use cim_subject::translator::TranslatorBuilder;
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let mut registry = ClassificationRegistry::new();
+//! registry.classify_pattern(Pattern::new("lending.applicant.>")?, [DataClass::Pii]);
+//! registry.restrict(DataClass::Pii, Pattern::new("external.>")?);
+//!
+//! let subject = Subject::new("lending.applicant.registered.v1")?;
+//! assert_eq!(registry.classify(&subject), [DataClass::Pii]);
+//!
+//! let bridge = registry.guard(
+//!     &TranslatorBuilder::new()
+//!         .translate_context("lending", "external")?
+//!         .build(),
+//! );
+//! assert!(bridge.translate(&subject).is_err());
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    EvaluationContext,
+    Operation,
+    PermissionRule,
+};
+use crate::subject::Subject;
+use crate::translator::{
+    TranslationRule,
+    Translator,
+};
+
+/// Header listing a message's data classes, comma separated
+pub const CLASSIFICATION_HEADER: &str = "X-Data-Classification";
+
+/// A data-classification label, ordered from least to most sensitive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataClass {
+    /// Safe to share outside the organisation
+    Public,
+    /// For use inside the organisation
+    Internal,
+    /// Personally identifiable information
+    Pii,
+    /// Payment card data
+    Pci,
+}
+
+impl DataClass {
+    /// The label's name in headers and policies
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Pii => "pii",
+            Self::Pci => "pci",
+        }
+    }
+}
+
+impl Display for DataClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DataClass {
+    type Err = SubjectError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        [Self::Public, Self::Internal, Self::Pii, Self::Pci]
+            .into_iter()
+            .find(|class| class.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| SubjectError::parse_error(format!("Unknown data class '{s}'")))
+    }
+}
+
+/// Labelled data that must not reach subjects matching a pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Restriction {
+    /// The restricted label
+    pub class: DataClass,
+    /// Subjects the label must not reach
+    pub destination: Pattern,
+}
+
+/// Data classes by subject pattern, most specific first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "ClassificationRegistryRepr")]
+pub struct ClassificationRegistry {
+    entries: Vec<(Pattern, Vec<DataClass>)>,
+    restrictions: Vec<Restriction>,
+}
+
+/// Serialized form of a [`ClassificationRegistry`], in any order
+#[derive(Deserialize)]
+struct ClassificationRegistryRepr {
+    entries: Vec<(Pattern, Vec<DataClass>)>,
+    restrictions: Vec<Restriction>,
+}
+
+impl From<ClassificationRegistryRepr> for ClassificationRegistry {
+    fn from(repr: ClassificationRegistryRepr) -> Self {
+        let mut registry = Self {
+            restrictions: repr.restrictions,
+            ..Self::default()
+        };
+        for (pattern, classes) in repr.entries {
+            registry.classify_pattern(pattern, classes);
+        }
+        registry
+    }
+}
+
+impl ClassificationRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Label the subjects matching a pattern, replacing any earlier labels
+    /// for the same pattern
+    pub fn classify_pattern(
+        &mut self,
+        pattern: Pattern,
+        classes: impl IntoIterator<Item = DataClass>,
+    ) {
+        let mut classes: Vec<DataClass> = classes.into_iter().collect();
+        classes.sort_unstable();
+        classes.dedup();
+        self.entries.retain(|(existing, _)| *existing != pattern);
+        self.entries.push((pattern, classes));
+        // Stable, so equally specific patterns keep registration order
+        self.entries
+            .sort_by_key(|(pattern, _)| pattern.specificity_key());
+    }
+
+    /// Keep a label away from subjects matching `destination`
+    pub fn restrict(&mut self, class: DataClass, destination: Pattern) {
+        self.restrictions.push(Restriction { class, destination });
+    }
+
+    /// Labelled patterns, most specific first
+    pub fn iter(&self) -> impl Iterator<Item = (&Pattern, &[DataClass])> {
+        self.entries
+            .iter()
+            .map(|(pattern, classes)| (pattern, classes.as_slice()))
+    }
+
+    /// Registered restrictions
+    #[must_use]
+    pub fn restrictions(&self) -> &[Restriction] {
+        &self.restrictions
+    }
+
+    /// A subject's labels, least sensitive first; empty if unclassified
+    #[must_use]
+    pub fn classify(&self, subject: &Subject) -> &[DataClass] {
+        self.entries
+            .iter()
+            .find(|(pattern, _)| pattern.matches(subject))
+            .map_or(&[], |(_, classes)| classes.as_slice())
+    }
+
+    /// Check if a subject carries a label
+    #[must_use]
+    pub fn has_class(&self, subject: &Subject, class: DataClass) -> bool {
+        self.classify(subject).contains(&class)
+    }
+
+    /// The labels to record in a message's [`CLASSIFICATION_HEADER`]
+    #[must_use]
+    pub fn header_value(&self, subject: &Subject) -> Option<String> {
+        let classes = self.classify(subject);
+        (!classes.is_empty()).then(|| {
+            classes
+                .iter()
+                .map(|class| class.name())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+    }
+
+    /// Check that data on `source` may move to `destination`
+    ///
+    /// # Errors
+    ///
+    /// Returns a permission denied error naming the first restriction the
+    /// move breaks
+    pub fn check_flow(&self, source: &Subject, destination: &Subject) -> Result<()> {
+        let classes = self.classify(source);
+        match self.restrictions.iter().find(|restriction| {
+            classes.contains(&restriction.class) && restriction.destination.matches(destination)
+        }) {
+            Some(restriction) => Err(SubjectError::permission_denied(format!(
+                "{} data on '{source}' may not flow to '{destination}' ({})",
+                restriction.class, restriction.destination
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// A translator applying [`check_flow`](Self::check_flow) to every
+    /// translation, forward and reverse
+    #[must_use]
+    pub fn guard(&self, translator: &Translator) -> Translator {
+        let registry = Arc::new(self.clone());
+        let guarded = Translator::new();
+        for (name, rule) in translator.named_rules() {
+            let forward = Arc::clone(&rule.translate_fn);
+            let checks = Arc::clone(&registry);
+            let mut guarded_rule = TranslationRule {
+                translate_fn: Arc::new(move |subject| {
+                    let translated = forward(subject)?;
+                    checks.check_flow(subject, &translated)?;
+                    Ok(translated)
+                }),
+                ..rule.clone()
+            };
+            if let Some(reverse) = rule.reverse_fn {
+                let checks = Arc::clone(&registry);
+                guarded_rule.reverse_fn = Some(Arc::new(move |subject| {
+                    let translated = reverse(subject)?;
+                    checks.check_flow(subject, &translated)?;
+                    Ok(translated)
+                }));
+            }
+            guarded.register_rule(name, guarded_rule);
+        }
+        guarded
+    }
+
+    /// Deny rules enforcing the restrictions on publishing
+    ///
+    /// Each denies publishing to a restriction's destination when the
+    /// subject carries the label or the message's [`CLASSIFICATION_HEADER`]
+    /// lists it. Conditions are only evaluated by
+    /// [`Permissions::is_allowed_with`](crate::permissions::Permissions::is_allowed_with).
+    #[must_use]
+    pub fn permission_rules(&self) -> Vec<PermissionRule> {
+        let registry = Arc::new(self.clone());
+        self.restrictions
+            .iter()
+            .map(|restriction| {
+                let class = restriction.class;
+                let registry = Arc::clone(&registry);
+                PermissionRule::deny(
+                    restriction.destination.clone(),
+                    [Operation::Publish].into_iter().collect(),
+                )
+                .with_description(format!("{class} data may not be published here"))
+                .with_condition(
+                    move |subject: &Subject, context: &EvaluationContext| {
+                        registry.has_class(subject, class)
+                            || context.header(CLASSIFICATION_HEADER).is_some_and(|labels| {
+                                labels
+                                    .split(',')
+                                    .any(|label| label.parse::<DataClass>() == Ok(class))
+                            })
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{
+        Permissions,
+        Policy,
+    };
+    use crate::translator::TranslatorBuilder;
+
+    fn registry() -> ClassificationRegistry {
+        let mut registry = ClassificationRegistry::new();
+        registry.classify_pattern(Pattern::new("lending.>").unwrap(), [DataClass::Internal]);
+        registry.classify_pattern(Pattern::new("lending.applicant.>").unwrap(), [
+            DataClass::Pci,
+            DataClass::Pii,
+        ]);
+        registry.classify_pattern(Pattern::new("external.>").unwrap(), [DataClass::Public]);
+        registry.restrict(DataClass::Pii, Pattern::new("external.>").unwrap());
+        registry
+    }
+
+    #[test]
+    fn test_classify_and_check_flow() {
+        let registry = registry();
+        let applicant = Subject::new("lending.applicant.registered.v1").unwrap();
+        let rates = Subject::new("lending.rates.published.v1").unwrap();
+        let external = Subject::new("external.applicant.registered.v1").unwrap();
+
+        assert_eq!(registry.classify(&applicant), [
+            DataClass::Pii,
+            DataClass::Pci
+        ]);
+        assert_eq!(registry.classify(&rates), [DataClass::Internal]);
+        assert!(registry
+            .classify(&Subject::new("orders.order.placed.v1").unwrap())
+            .is_empty());
+        assert_eq!(
+            registry.header_value(&applicant).as_deref(),
+            Some("pii,pci")
+        );
+
+        assert!(matches!(
+            registry.check_flow(&applicant, &external),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+        assert!(registry.check_flow(&rates, &external).is_ok());
+
+        // Loaded entries are ordered by specificity, whatever the config says
+        let mut config = serde_json::to_value(&registry).unwrap();
+        config["entries"].as_array_mut().unwrap().reverse();
+        let loaded: ClassificationRegistry = serde_json::from_value(config).unwrap();
+        assert_eq!(loaded.classify(&applicant), [
+            DataClass::Pii,
+            DataClass::Pci
+        ]);
+        assert!(loaded.check_flow(&applicant, &external).is_err());
+
+        let bridge = registry.guard(
+            &TranslatorBuilder::new()
+                .translate_context("lending", "external")
+                .unwrap()
+                .build(),
+        );
+        assert!(bridge.translate(&applicant).is_err());
+        assert_eq!(
+            bridge.translate(&rates).unwrap().as_str(),
+            "external.rates.published.v1"
+        );
+    }
+
+    #[test]
+    fn test_permission_rules() {
+        let registry = registry();
+        let mut permissions = Permissions::new(Policy::Allow);
+        for rule in registry.permission_rules() {
+            permissions.add_rule(rule);
+        }
+
+        let external = Subject::new("external.rates.published.v1").unwrap();
+        let plain = EvaluationContext::new();
+        let tagged = EvaluationContext::new().with_header(CLASSIFICATION_HEADER, "internal, PII");
+        assert!(permissions.is_allowed_with(&external, Operation::Publish, &plain));
+        assert!(!permissions.is_allowed_with(&external, Operation::Publish, &tagged));
+
+        let mut labelled = registry.clone();
+        labelled.classify_pattern(Pattern::new("external.applicant.>").unwrap(), [
+            DataClass::Pii,
+        ]);
+        let mut permissions = Permissions::new(Policy::Allow);
+        permissions.add_rule(labelled.permission_rules().remove(0));
+        assert!(!permissions.is_allowed_with(
+            &Subject::new("external.applicant.registered.v1").unwrap(),
+            Operation::Publish,
+            &plain
+        ));
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Typed subject constants generated from a manifest
+//!
+//! A subject manifest lists a domain's subjects as nested YAML maps, one
+//! level each for context, aggregate and event type, ending in the list of
+//! versions:
+//!
+//! ```yaml
+//! orders:
+//!   order:
+//!     created: [v1, v2]
+//!     shipped: [v1]
+//! ```
+//!
+//! [`generate`] turns it into a Rust module tree with a constant and a
+//! builder function per version, so application code writes
+//! `orders::order::created::v1()` instead of a string that only fails at
+//! runtime. Call it from `build.rs` and include the output:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     println!("cargo:rerun-if-changed=subjects.yaml");
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     cim_subject::codegen::generate("subjects.yaml", out_dir).unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/subjects.rs"));
+//! ```
+//!
+//! Tokens that are not Rust identifiers are adapted: `-` becomes `_`,
+//! keywords are raw identifiers and a leading digit gains a `_` prefix.
+//!
+//! ```
+//! use cim_subject::codegen::SubjectManifest;
+//!
+//! let manifest = SubjectManifest::from_yaml("orders: {order: {created: [v1]}}")?;
+//! let code = manifest.to_rust()?;
+//! assert!(code.contains(r#"pub const V1: &str = "orders.order.created.v1";"#));
+//! assert!(code.contains("pub fn v1() -> ::cim_subject::Subject"));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+use std::fmt::Write as _;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+/// Name of the file [`generate`] writes into the output directory
+pub const OUTPUT_FILE: &str = "subjects.rs";
+
+/// Names generated in every event module besides the versions
+const RESERVED: [&str; 2] = ["ALL", "pattern"];
+
+/// Rust keywords, which become raw identifiers
+const KEYWORDS: [&str; 50] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where",
+];
+
+/// Subjects by context, aggregate and event type, each with its versions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubjectManifest {
+    contexts: BTreeMap<String, BTreeMap<String, BTreeMap<String, Vec<String>>>>,
+}
+
+impl SubjectManifest {
+    /// Parse a manifest from YAML, or JSON since YAML is a superset of it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a manifest or lists an invalid
+    /// subject
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let manifest: Self = serde_yaml::from_str(text)
+            .map_err(|e| SubjectError::parse_error(format!("Invalid subject manifest: {e}")))?;
+        manifest.subjects()?;
+        Ok(manifest)
+    }
+
+    /// Every subject in the manifest, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first entry that is not a valid subject
+    pub fn subjects(&self) -> Result<Vec<Subject>> {
+        let mut subjects = Vec::new();
+        for (context, aggregates) in &self.contexts {
+            for (aggregate, events) in aggregates {
+                for (event, versions) in events {
+                    for version in versions {
+                        subjects.push(Subject::new(format!(
+                            "{context}.{aggregate}.{event}.{version}"
+                        ))?);
+                    }
+                }
+            }
+        }
+        subjects.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        Ok(subjects)
+    }
+
+    /// Render the manifest as Rust source
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a subject is invalid, or two tokens at the same
+    /// level map to the same Rust identifier
+    pub fn to_rust(&self) -> Result<String> {
+        self.subjects()?;
+        let mut out = String::from(
+            "// @generated by cim_subject::codegen from a subject manifest; do not edit\n",
+        );
+        let mut contexts = Idents::default();
+        for (context, aggregates) in &self.contexts {
+            let context_mod = contexts.claim(context, module_ident(context))?;
+            writeln!(out, "\n/// Subjects in the `{context}` context").ok();
+            writeln!(out, "pub mod {context_mod} {{").ok();
+            let mut aggregate_idents = Idents::default();
+            for (aggregate, events) in aggregates {
+                let aggregate_mod = aggregate_idents.claim(aggregate, module_ident(aggregate))?;
+                writeln!(
+                    out,
+                    "    /// Subjects of the `{context}.{aggregate}` aggregate"
+                )
+                .ok();
+                writeln!(out, "    pub mod {aggregate_mod} {{").ok();
+                let mut event_idents = Idents::default();
+                for (event, versions) in events {
+                    let event_mod = event_idents.claim(event, module_ident(event))?;
+                    let family = format!("{context}.{aggregate}.{event}");
+                    render_event(&mut out, &family, &event_mod, versions)?;
+                }
+                writeln!(out, "    }}").ok();
+            }
+            writeln!(out, "}}").ok();
+        }
+        Ok(out)
+    }
+}
+
+/// Render the module for one event type
+fn render_event(out: &mut String, family: &str, module: &str, versions: &[String]) -> Result<()> {
+    let pad = "        ";
+    writeln!(out, "{pad}/// Versions of `{family}`").ok();
+    writeln!(out, "{pad}pub mod {module} {{").ok();
+    let mut consts = Idents::default();
+    let mut fns = Idents::default();
+    for name in RESERVED {
+        consts.taken.insert(name.to_string());
+        fns.taken.insert(name.to_string());
+    }
+    let mut all = Vec::new();
+    for version in versions {
+        let subject = format!("{family}.{version}");
+        let constant = consts.claim(version, const_ident(version))?;
+        let function = fns.claim(version, module_ident(version))?;
+        writeln!(out, "{pad}    /// `{subject}`").ok();
+        writeln!(out, "{pad}    pub const {constant}: &str = \"{subject}\";").ok();
+        writeln!(out, "{pad}    /// The `{subject}` subject").ok();
+        writeln!(out, "{pad}    ///\n{pad}    /// # Panics\n{pad}    ///").ok();
+        writeln!(
+            out,
+            "{pad}    /// Never: the subject was validated by codegen"
+        )
+        .ok();
+        writeln!(out, "{pad}    #[must_use]").ok();
+        writeln!(
+            out,
+            "{pad}    pub fn {function}() -> ::cim_subject::Subject {{"
+        )
+        .ok();
+        writeln!(
+            out,
+            "{pad}        ::cim_subject::Subject::new({constant}).expect(\"validated by cim_subject::codegen\")"
+        )
+        .ok();
+        writeln!(out, "{pad}    }}").ok();
+        all.push(constant);
+    }
+    writeln!(out, "{pad}    /// Every version of `{family}`").ok();
+    writeln!(
+        out,
+        "{pad}    pub const ALL: &[&str] = &[{}];",
+        all.join(", ")
+    )
+    .ok();
+    writeln!(out, "{pad}    /// `{family}.*`, matching every version").ok();
+    writeln!(out, "{pad}    ///\n{pad}    /// # Panics\n{pad}    ///").ok();
+    writeln!(
+        out,
+        "{pad}    /// Never: the pattern was validated by codegen"
+    )
+    .ok();
+    writeln!(out, "{pad}    #[must_use]").ok();
+    writeln!(
+        out,
+        "{pad}    pub fn pattern() -> ::cim_subject::Pattern {{"
+    )
+    .ok();
+    writeln!(
+        out,
+        "{pad}        ::cim_subject::Pattern::new(\"{family}.*\").expect(\"validated by cim_subject::codegen\")"
+    )
+    .ok();
+    writeln!(out, "{pad}    }}").ok();
+    writeln!(out, "{pad}}}").ok();
+    Ok(())
+}
+
+/// Read a manifest and write its Rust module tree to
+/// [`OUTPUT_FILE`] in `out_dir`, returning the written path
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be read or rendered, or the
+/// output cannot be written
+pub fn generate(manifest: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    let manifest = manifest.as_ref();
+    let text = std::fs::read_to_string(manifest).map_err(|e| io_error(manifest, &e))?;
+    let code = SubjectManifest::from_yaml(&text)?.to_rust()?;
+    let path = out_dir.as_ref().join(OUTPUT_FILE);
+    std::fs::write(&path, code).map_err(|e| io_error(&path, &e))?;
+    Ok(path)
+}
+
+fn io_error(path: &Path, error: &std::io::Error) -> SubjectError {
+    SubjectError::validation_error(format!("{}: {error}", path.display()))
+}
+
+/// Identifiers used at one level of the module tree
+#[derive(Default)]
+struct Idents {
+    taken: BTreeSet<String>,
+}
+
+impl Idents {
+    /// Reserve `ident` for `token`, failing if another token took it
+    fn claim(&mut self, token: &str, ident: String) -> Result<String> {
+        if self.taken.insert(ident.clone()) {
+            Ok(ident)
+        } else {
+            Err(SubjectError::validation_error(format!(
+                "Manifest token '{token}' collides with another as the identifier '{ident}'"
+            )))
+        }
+    }
+}
+
+/// A lowercase token as a module or function name
+fn module_ident(token: &str) -> String {
+    let ident = sanitize(token);
+    match ident.as_str() {
+        // Not allowed as raw identifiers
+        "crate" | "self" | "Self" | "super" | "_" => format!("{ident}_"),
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{ident}"),
+        _ => ident,
+    }
+}
+
+/// A token as a constant name
+fn const_ident(token: &str) -> String {
+    sanitize(token).to_uppercase()
+}
+
+fn sanitize(token: &str) -> String {
+    let ident: String = token
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{ident}")
+    } else {
+        ident
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "
+orders:
+  order:
+    created: [v1, v2]
+    shipped: [v1]
+  line-item:
+    type: [v1]
+";
+
+    #[test]
+    fn test_render_manifest() {
+        let manifest = SubjectManifest::from_yaml(MANIFEST).unwrap();
+        assert_eq!(manifest.subjects().unwrap().len(), 4);
+
+        let code = manifest.to_rust().unwrap();
+        assert!(code.contains("pub mod line_item {"));
+        assert!(code.contains("pub mod r#type {"));
+        assert!(code.contains(r#"pub const V2: &str = "orders.order.created.v2";"#));
+        assert!(code.contains("pub const ALL: &[&str] = &[V1, V2];"));
+        assert!(code.contains(r#"Pattern::new("orders.order.shipped.*")"#));
+
+        // Deterministic, so build scripts do not trigger rebuilds
+        assert_eq!(code, manifest.to_rust().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        assert!(SubjectManifest::from_yaml("orders: {order: {created: [\"v 1\"]}}").is_err());
+        assert!(SubjectManifest::from_yaml("orders: [v1]").is_err());
+        let colliding = SubjectManifest::from_yaml("orders: {order: {created: [v-1, v_1]}}");
+        assert!(colliding.unwrap().to_rust().is_err());
+        let reserved = SubjectManifest::from_yaml("orders: {order: {created: [pattern]}}");
+        assert!(reserved.unwrap().to_rust().is_err());
+
+        let dir = std::env::temp_dir().join(format!("cim-subject-codegen-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join("subjects.yaml");
+        std::fs::write(&manifest, MANIFEST).unwrap();
+        let path = generate(&manifest, &dir).unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("pub mod orders {"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(generate(&manifest, &dir).is_err());
+    }
+}
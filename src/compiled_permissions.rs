@@ -0,0 +1,492 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Compiled permissions for hot-path access checks
+//!
+//! [`Permissions::is_allowed`] filters and sorts every rule on each call.
+//! [`CompiledPermissions`] does that work once: rules are pre-sorted by
+//! specificity and indexed in a token trie, and decisions are memoized in a
+//! bounded LRU cache keyed by `(subject, operation)`. Caching rules out
+//! time- and context-dependent decisions, so
+//! [conditional rules](PermissionRule::is_conditional) are compiled to fail
+//! closed: conditional allow rules are left out and conditional deny rules
+//! always apply. Evaluate those with [`Permissions::is_allowed_with`].
+
+use std::borrow::Borrow;
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::hash::{
+    Hash,
+    Hasher,
+};
+use std::sync::Mutex;
+
+use crate::pattern::Token;
+use crate::permissions::{
+    ConflictResolution,
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+};
+use crate::subject::Subject;
+
+/// Default number of cached decisions
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// A permission set compiled for fast repeated evaluation
+#[derive(Debug)]
+pub struct CompiledPermissions {
+    /// Rules sorted from most to least specific
+    rules: Vec<PermissionRule>,
+    /// Trie over pattern tokens, leaves hold rule indices
+    trie: TrieNode,
+    /// Default policy when no rules match
+    default_policy: Policy,
+    /// Conflict resolution strategy
+    resolution: ConflictResolution,
+    /// Memoized decisions
+    cache: Mutex<DecisionCache>,
+}
+
+impl CompiledPermissions {
+    /// Compile a permission set with the default cache capacity
+    #[must_use]
+    pub fn new(permissions: &Permissions) -> Self {
+        Self::with_cache_capacity(permissions, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Compile a permission set with a specific cache capacity
+    ///
+    /// A capacity of zero disables caching.
+    #[must_use]
+    pub fn with_cache_capacity(permissions: &Permissions, capacity: usize) -> Self {
+        let mut rules: Vec<PermissionRule> = permissions
+            .rules()
+            .iter()
+            .filter(|rule| rule.policy == Policy::Deny || !rule.is_conditional())
+            .cloned()
+            .collect();
+        // Stable sort keeps declaration order for equally specific rules
+        rules.sort_by_key(|rule| rule.pattern.specificity_key());
+
+        let mut trie = TrieNode::default();
+        for (index, rule) in rules.iter().enumerate() {
+            trie.insert(rule.pattern.tokens(), index);
+        }
+
+        Self {
+            rules,
+            trie,
+            default_policy: permissions.default_policy(),
+            resolution: permissions.conflict_resolution(),
+            cache: Mutex::new(DecisionCache::new(capacity)),
+        }
+    }
+
+    /// Check if an operation is allowed on a subject
+    ///
+    /// Returns the same decision as [`Permissions::is_allowed`].
+    #[must_use]
+    pub fn is_allowed(&self, subject: &Subject, operation: Operation) -> bool {
+        self.is_allowed_str(subject.as_str(), operation)
+    }
+
+    /// Check if an operation is allowed on a subject string
+    #[must_use]
+    pub fn is_allowed_str(&self, subject: &str, operation: Operation) -> bool {
+        self.decide(subject, operation, None)
+    }
+
+    /// Check if an operation is allowed on a subject within a queue group
+    ///
+    /// Returns the same decision as [`Permissions::is_allowed_in_queue`].
+    #[must_use]
+    pub fn is_allowed_in_queue(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        queue_group: Option<&str>,
+    ) -> bool {
+        self.decide(subject.as_str(), operation, queue_group)
+    }
+
+    /// Check if publishing to a subject is allowed
+    #[must_use]
+    pub fn can_publish(&self, subject: &Subject) -> bool {
+        self.is_allowed(subject, Operation::Publish)
+    }
+
+    /// Check if subscribing to a subject is allowed
+    #[must_use]
+    pub fn can_subscribe(&self, subject: &Subject) -> bool {
+        self.is_allowed(subject, Operation::Subscribe)
+    }
+
+    /// Check if requesting on a subject is allowed
+    #[must_use]
+    pub fn can_request(&self, subject: &Subject) -> bool {
+        self.is_allowed(subject, Operation::Request)
+    }
+
+    /// Number of decisions currently cached
+    #[must_use]
+    pub fn cached_decisions(&self) -> usize {
+        self.cache.lock().map_or(0, |cache| cache.len())
+    }
+
+    /// Drop all cached decisions
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Look up a decision in the cache, evaluating and recording it on a miss
+    fn decide(&self, subject: &str, operation: Operation, queue_group: Option<&str>) -> bool {
+        let view = (subject, operation, queue_group);
+        if let Some(decision) = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|mut cache| cache.get(&view))
+        {
+            return decision;
+        }
+
+        let decision = self.evaluate(subject, operation, queue_group);
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(
+                DecisionKey {
+                    subject: subject.to_string(),
+                    operation,
+                    queue_group: queue_group.map(str::to_string),
+                },
+                decision,
+            );
+        }
+
+        decision
+    }
+
+    /// Evaluate a decision without consulting the cache
+    fn evaluate(&self, subject: &str, operation: Operation, queue_group: Option<&str>) -> bool {
+        let tokens: Vec<&str> = subject.split('.').collect();
+        let mut candidates = Vec::new();
+        self.trie.collect(&tokens, &mut candidates);
+
+        let matching: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&index| {
+                let rule = &self.rules[index];
+                rule.applies_to_queue_group(queue_group) && rule.operations.contains(&operation)
+            })
+            .collect();
+
+        if self.resolution == ConflictResolution::DenyOverrides
+            && matching
+                .iter()
+                .any(|&index| self.rules[index].policy == Policy::Deny)
+        {
+            return false;
+        }
+
+        // Rules are pre-sorted, so the lowest index is the most specific
+        match matching.into_iter().min() {
+            Some(index) => self.rules[index].policy == Policy::Allow,
+            None => self.default_policy == Policy::Allow,
+        }
+    }
+}
+
+impl From<&Permissions> for CompiledPermissions {
+    fn from(permissions: &Permissions) -> Self {
+        Self::new(permissions)
+    }
+}
+
+impl Permissions {
+    /// Compile this permission set for fast repeated evaluation
+    #[must_use]
+    pub fn compile(&self) -> CompiledPermissions {
+        CompiledPermissions::new(self)
+    }
+}
+
+/// A node in the pattern token trie
+#[derive(Debug, Default)]
+struct TrieNode {
+    /// Children for literal tokens
+    literals: HashMap<String, TrieNode>,
+    /// Child for the `*` wildcard
+    single: Option<Box<TrieNode>>,
+    /// Rules whose pattern ends with `>` at this position
+    multi_rules: Vec<usize>,
+    /// Rules whose pattern ends exactly at this node
+    rules: Vec<usize>,
+}
+
+impl TrieNode {
+    fn insert(&mut self, tokens: &[Token], index: usize) {
+        match tokens.split_first() {
+            None => self.rules.push(index),
+            Some((Token::MultiWildcard, _)) => self.multi_rules.push(index),
+            Some((Token::SingleWildcard, rest)) => {
+                self.single
+                    .get_or_insert_with(Box::default)
+                    .insert(rest, index);
+            },
+            Some((Token::Literal(literal), rest)) => {
+                self.literals
+                    .entry(literal.clone())
+                    .or_default()
+                    .insert(rest, index);
+            },
+        }
+    }
+
+    fn collect(&self, tokens: &[&str], out: &mut Vec<usize>) {
+        match tokens.split_first() {
+            None => out.extend_from_slice(&self.rules),
+            Some((token, rest)) => {
+                out.extend_from_slice(&self.multi_rules);
+                if let Some(child) = self.literals.get(*token) {
+                    child.collect(rest, out);
+                }
+                if let Some(child) = &self.single {
+                    child.collect(rest, out);
+                }
+            },
+        }
+    }
+}
+
+/// Cache key: subject, operation and optional queue group
+#[derive(Debug, Clone)]
+struct DecisionKey {
+    subject: String,
+    operation: Operation,
+    queue_group: Option<String>,
+}
+
+/// A cache key as borrowed parts, so hits are looked up without
+/// allocating an owned key
+trait KeyView {
+    fn view(&self) -> (&str, Operation, Option<&str>);
+}
+
+impl KeyView for DecisionKey {
+    fn view(&self) -> (&str, Operation, Option<&str>) {
+        (&self.subject, self.operation, self.queue_group.as_deref())
+    }
+}
+
+impl KeyView for (&str, Operation, Option<&str>) {
+    fn view(&self) -> (&str, Operation, Option<&str>) {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn KeyView + 'a> for DecisionKey {
+    fn borrow(&self) -> &(dyn KeyView + 'a) {
+        self
+    }
+}
+
+// Owned keys and views hash and compare through the same parts, as
+// `Borrow` requires
+impl Hash for dyn KeyView + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.view().hash(state);
+    }
+}
+
+impl PartialEq for dyn KeyView + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.view() == other.view()
+    }
+}
+
+impl Eq for dyn KeyView + '_ {}
+
+impl Hash for DecisionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.view().hash(state);
+    }
+}
+
+impl PartialEq for DecisionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.view() == other.view()
+    }
+}
+
+impl Eq for DecisionKey {}
+
+/// Bounded least-recently-used cache of decisions
+#[derive(Debug)]
+struct DecisionCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<DecisionKey, (bool, u64)>,
+    recency: BTreeMap<u64, DecisionKey>,
+}
+
+impl DecisionCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    fn get(&mut self, key: &dyn KeyView) -> Option<bool> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (decision, last_used) = self.entries.get_mut(key)?;
+        if let Some(key) = self.recency.remove(last_used) {
+            self.recency.insert(tick, key);
+        }
+        *last_used = tick;
+        Some(*decision)
+    }
+
+    fn insert(&mut self, key: DecisionKey, decision: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.get(&key) {
+            self.recency.remove(last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, evicted)) = self.recency.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, (decision, self.tick));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::permissions::PermissionsBuilder;
+
+    fn sample_permissions(resolution: ConflictResolution) -> Permissions {
+        PermissionsBuilder::new()
+            .conflict_resolution(resolution)
+            .allow("users.>", &[Operation::Subscribe, Operation::Publish])
+            .unwrap()
+            .deny("users.admin.>", &[Operation::Subscribe])
+            .unwrap()
+            .allow("users.admin.audit.*", &[Operation::Subscribe])
+            .unwrap()
+            .allow_queue("users.*.created.*", &["user-workers"])
+            .unwrap()
+            .deny("*.*.deleted.>", &[Operation::Publish])
+            .unwrap()
+            .build()
+    }
+
+    #[test]
+    fn test_compiled_matches_interpreted() {
+        let subjects = [
+            "users.person.created.v1",
+            "users.admin.created.v1",
+            "users.admin.audit.v1",
+            "users.person.deleted.v1",
+            "orders.order.placed.v1",
+        ];
+        let operations = [
+            Operation::Publish,
+            Operation::Subscribe,
+            Operation::Request,
+            Operation::QueueSubscribe,
+        ];
+
+        for resolution in [
+            ConflictResolution::MostSpecific,
+            ConflictResolution::DenyOverrides,
+        ] {
+            let perms = sample_permissions(resolution);
+            let compiled = perms.compile();
+            for subject in subjects {
+                let subject = Subject::new(subject).unwrap();
+                for op in operations {
+                    assert_eq!(
+                        compiled.is_allowed(&subject, op),
+                        perms.is_allowed(&subject, op),
+                        "{subject} {op:?} {resolution:?}"
+                    );
+                    for group in [Some("user-workers"), Some("other"), None] {
+                        assert_eq!(
+                            compiled.is_allowed_in_queue(&subject, op, group),
+                            perms.is_allowed_in_queue(&subject, op, group),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditional_rules_fail_closed() {
+        let mut perms = sample_permissions(ConflictResolution::MostSpecific);
+        let subject = Subject::new("orders.order.placed.v1").unwrap();
+        perms.add_rule(
+            PermissionRule::allow(
+                Pattern::new("orders.>").unwrap(),
+                [Operation::Publish].into_iter().collect(),
+            )
+            .with_condition(|_, _| true),
+        );
+        perms.add_rule(
+            PermissionRule::deny(
+                Pattern::new("users.person.>").unwrap(),
+                [Operation::Publish].into_iter().collect(),
+            )
+            .with_condition(|_, _| false),
+        );
+        assert!(perms.can_publish(&subject));
+
+        let compiled = perms.compile();
+        assert!(!compiled.can_publish(&subject));
+        assert!(!compiled.can_publish(&Subject::new("users.person.created.v1").unwrap()));
+    }
+
+    #[test]
+    fn test_decision_cache_eviction() {
+        let perms = sample_permissions(ConflictResolution::MostSpecific);
+        let compiled = CompiledPermissions::with_cache_capacity(&perms, 2);
+
+        assert!(compiled.is_allowed_str("users.person.created.v1", Operation::Publish));
+        assert!(!compiled.is_allowed_str("users.admin.created.v1", Operation::Subscribe));
+        // Touch the first entry so the second becomes least recently used
+        assert!(compiled.is_allowed_str("users.person.created.v1", Operation::Publish));
+        assert!(!compiled.is_allowed_str("orders.order.placed.v1", Operation::Publish));
+
+        assert_eq!(compiled.cached_decisions(), 2);
+        let cache = compiled.cache.lock().unwrap();
+        let cached = |subject, operation| {
+            let view: &dyn KeyView = &(subject, operation, None);
+            cache.entries.contains_key(view)
+        };
+        assert!(cached("users.person.created.v1", Operation::Publish));
+        assert!(!cached("users.admin.created.v1", Operation::Subscribe));
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Concurrency analysis of correlation chains
+//!
+//! Messages caused by the same message have no ordering constraint
+//! between them, so the branches they start could run concurrently. These
+//! methods find such forks, bound how much work a chain could do in
+//! parallel, and use timestamps to flag sibling branches that ran one after
+//! the other anyway, a common sign of a handler awaiting work it does not
+//! depend on.
+//!
+//! ```
+//! use std::time::{
+//!     Duration,
+//!     SystemTime,
+//! };
+//!
+//! use cim_subject::message_algebra::{
+//!     ChainEntry,
+//!     CorrelationChain,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//! };
+//! use uuid::Uuid;
+//!
+//! let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+//! let caused = |parent: &MessageIdentity| {
+//!     MessageIdentity::caused_by(
+//!         IdType::Uuid(Uuid::new_v4()),
+//!         parent.correlation_id.clone(),
+//!         parent.message_id.clone(),
+//!     )
+//! };
+//!
+//! let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let (reserve, charge) = (caused(&root), caused(&root));
+//! let reserved = caused(&reserve);
+//! let mut chain = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0))).unwrap();
+//! chain
+//!     .add_entry(ChainEntry::new(reserve.clone(), at(1)))
+//!     .unwrap();
+//! chain.add_entry(ChainEntry::new(reserved, at(4))).unwrap();
+//! // Charging only started once the reservation had finished
+//! chain
+//!     .add_entry(ChainEntry::new(charge.clone(), at(5)))
+//!     .unwrap();
+//!
+//! assert_eq!(chain.parallel_forks()[0].branches.len(), 2);
+//! assert_eq!(chain.parallelism_per_level(), [1, 2, 1]);
+//! assert_eq!(chain.max_parallelism(), 2);
+//!
+//! let serialized = chain.serialized_branches();
+//! assert_eq!(serialized[0].first, reserve.message_id);
+//! assert_eq!(serialized[0].second, charge.message_id);
+//! assert_eq!(serialized[0].gap, Duration::from_secs(1));
+//! ```
+
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use crate::correlation::IdType;
+use crate::message_algebra::CorrelationChain;
+
+/// A message that caused several mutually unordered branches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParallelFork {
+    /// The causing message
+    pub fork: IdType,
+    /// The first message of each branch, in the order they were added
+    pub branches: Vec<IdType>,
+}
+
+/// Two sibling branches that could have run concurrently but did not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializedBranches {
+    /// The message that caused both branches
+    pub fork: IdType,
+    /// First message of the branch that finished first
+    pub first: IdType,
+    /// First message of the branch that started after it finished
+    pub second: IdType,
+    /// Time between the end of `first`'s branch and the start of `second`'s
+    pub gap: Duration,
+}
+
+/// Earliest and latest timestamps of a branch
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: SystemTime,
+    end: SystemTime,
+    /// Number of timed messages
+    timed: usize,
+}
+
+impl CorrelationChain {
+    /// Messages that caused two or more branches, in breadth-first order
+    #[must_use]
+    pub fn parallel_forks(&self) -> Vec<ParallelFork> {
+        self.iter_bfs()
+            .filter_map(|message| {
+                let children = self.caused_messages.get(&message.message_id)?;
+                (children.len() > 1).then(|| ParallelFork {
+                    fork: message.message_id.clone(),
+                    branches: children.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Number of messages at each distance from the root, which bounds how
+    /// many of them could be handled at once
+    #[must_use]
+    pub fn parallelism_per_level(&self) -> Vec<usize> {
+        self.stats().width_per_level
+    }
+
+    /// The largest number of messages with no ordering constraint between
+    /// them
+    ///
+    /// In a causation tree no leaf precedes another, and no larger set is
+    /// unordered, so this is the number of leaves.
+    #[must_use]
+    pub fn max_parallelism(&self) -> usize {
+        self.leaves().len()
+    }
+
+    /// Sibling branches where one started only after the other had finished
+    ///
+    /// A branch is considered finished at its latest timed message. Only
+    /// branches of two or more timed messages can show that a sibling
+    /// waited for them; untimed messages are ignored.
+    #[must_use]
+    pub fn serialized_branches(&self) -> Vec<SerializedBranches> {
+        let mut serialized = Vec::new();
+        for fork in self.parallel_forks() {
+            let spans: Vec<(&IdType, Option<Span>)> = fork
+                .branches
+                .iter()
+                .map(|branch| (branch, self.timed_span(branch)))
+                .collect();
+            for (first, first_span) in &spans {
+                let Some(first_span) = first_span.filter(|span| span.timed > 1) else {
+                    continue;
+                };
+                for (second, second_span) in &spans {
+                    let Some(second_span) = second_span else {
+                        continue;
+                    };
+                    if first != second && second_span.start >= first_span.end {
+                        serialized.push(SerializedBranches {
+                            fork: fork.fork.clone(),
+                            first: (*first).clone(),
+                            second: (*second).clone(),
+                            gap: second_span
+                                .start
+                                .duration_since(first_span.end)
+                                .unwrap_or_default(),
+                        });
+                    }
+                }
+            }
+        }
+        serialized
+    }
+
+    /// The span of the timed messages in a subtree
+    fn timed_span(&self, top: &IdType) -> Option<Span> {
+        self.roots_of_subtree(top)
+            .filter_map(|message| self.timestamps.get(&message.message_id))
+            .fold(None, |span, &at| {
+                Some(span.map_or(
+                    Span {
+                        start: at,
+                        end: at,
+                        timed: 1,
+                    },
+                    |span: Span| Span {
+                        start: span.start.min(at),
+                        end: span.end.max(at),
+                        timed: span.timed + 1,
+                    },
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageIdentity;
+    use crate::message_algebra::ChainEntry;
+
+    fn caused(parent: &MessageIdentity) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            parent.correlation_id.clone(),
+            parent.message_id.clone(),
+        )
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_overlapping_branches_are_not_flagged() {
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let (left, right) = (caused(&root), caused(&root));
+        let mut chain = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0))).unwrap();
+        chain
+            .add_entry(ChainEntry::new(left.clone(), at(1)))
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(right.clone(), at(2)))
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(caused(&left), at(3)))
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(caused(&right), at(3)))
+            .unwrap();
+        // An untimed branch has no span
+        chain.add_message(caused(&root)).unwrap();
+
+        assert_eq!(chain.parallel_forks(), [ParallelFork {
+            fork: root.message_id.clone(),
+            branches: chain.caused_messages[&root.message_id].clone(),
+        }]);
+        assert_eq!(chain.parallelism_per_level(), [1, 3, 2]);
+        assert_eq!(chain.max_parallelism(), 3);
+        assert!(chain.serialized_branches().is_empty());
+    }
+
+    #[test]
+    fn test_linear_chain_has_no_forks() {
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let child = caused(&root);
+        let mut chain = CorrelationChain::new(root).unwrap();
+        chain.add_message(child.clone()).unwrap();
+        chain.add_message(caused(&child)).unwrap();
+
+        assert!(chain.parallel_forks().is_empty());
+        assert_eq!(chain.max_parallelism(), 1);
+        assert!(chain.serialized_branches().is_empty());
+    }
+}
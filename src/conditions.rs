@@ -0,0 +1,181 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Attribute-based conditions on permission rules
+//!
+//! A [`Condition`] decides at evaluation time whether a rule applies,
+//! looking at the [`EvaluationContext`]: request attributes, and for
+//! messages their headers, identity, correlation root kind and payload size.
+//! Attach one with [`PermissionRule::with_shared_condition`], or pass a
+//! closure to [`PermissionRule::with_condition`].
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use cim_subject::conditions::{
+//!     HeaderPresent,
+//!     Not,
+//! };
+//! use cim_subject::permissions::{
+//!     EvaluationContext,
+//!     Operation,
+//!     PermissionRule,
+//!     Permissions,
+//!     Policy,
+//! };
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! // Deny publishing events that lack a correlation header
+//! let mut permissions = Permissions::new(Policy::Allow);
+//! permissions.add_rule(
+//!     PermissionRule::deny(
+//!         Pattern::new("*.*.*.*")?,
+//!         [Operation::Publish].into_iter().collect(),
+//!     )
+//!     .with_shared_condition(Arc::new(Not(HeaderPresent::new("X-Correlation-ID")))),
+//! );
+//!
+//! let subject = Subject::new("orders.order.placed.v1")?;
+//! let bare = EvaluationContext::new();
+//! let correlated = EvaluationContext::new().with_header("X-Correlation-ID", "c-1");
+//! assert!(!permissions.is_allowed_with(&subject, Operation::Publish, &bare));
+//! assert!(permissions.is_allowed_with(&subject, Operation::Publish, &correlated));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt;
+
+use crate::correlation::MessageKind;
+use crate::permissions::EvaluationContext;
+#[cfg(doc)]
+use crate::permissions::PermissionRule;
+use crate::subject::Subject;
+
+/// A predicate deciding whether a rule applies to a request
+pub trait Condition: Send + Sync {
+    /// Whether the rule applies to `subject` in `context`
+    fn evaluate(&self, subject: &Subject, context: &EvaluationContext) -> bool;
+
+    /// A short description for debugging output
+    fn describe(&self) -> String {
+        "condition".to_string()
+    }
+}
+
+impl<F> Condition for F
+where F: Fn(&Subject, &EvaluationContext) -> bool + Send + Sync
+{
+    fn evaluate(&self, subject: &Subject, context: &EvaluationContext) -> bool {
+        self(subject, context)
+    }
+}
+
+impl fmt::Debug for dyn Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+/// Holds when the message carries a header, compared case-insensitively
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderPresent(String);
+
+impl HeaderPresent {
+    /// Require the header `name`
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+impl Condition for HeaderPresent {
+    fn evaluate(&self, _subject: &Subject, context: &EvaluationContext) -> bool {
+        context.header(&self.0).is_some()
+    }
+
+    fn describe(&self) -> String {
+        format!("header {} present", self.0)
+    }
+}
+
+/// Holds when the message's correlation chain started with a message of
+/// the given kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootKind(pub MessageKind);
+
+impl Condition for RootKind {
+    fn evaluate(&self, _subject: &Subject, context: &EvaluationContext) -> bool {
+        context.root_kind == Some(self.0)
+    }
+
+    fn describe(&self) -> String {
+        format!("root kind {}", self.0)
+    }
+}
+
+/// Holds when the message's payload is known and at most this many bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxPayloadSize(pub usize);
+
+impl Condition for MaxPayloadSize {
+    fn evaluate(&self, _subject: &Subject, context: &EvaluationContext) -> bool {
+        context.payload_size.is_some_and(|size| size <= self.0)
+    }
+
+    fn describe(&self) -> String {
+        format!("payload at most {} bytes", self.0)
+    }
+}
+
+/// Holds when the wrapped condition does not
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Not<C>(pub C);
+
+impl<C: Condition> Condition for Not<C> {
+    fn evaluate(&self, subject: &Subject, context: &EvaluationContext) -> bool {
+        !self.0.evaluate(subject, context)
+    }
+
+    fn describe(&self) -> String {
+        format!("not {}", self.0.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    #[test]
+    fn test_message_conditions() {
+        let subject = Subject::new("orders.order.place.v1").unwrap();
+        let command = MessageFactory::create_root_command(Uuid::new_v4());
+        let context = EvaluationContext::new()
+            .with_identity(command.clone())
+            .with_header("Nats-Msg-Id", "m-1")
+            .with_payload_size(512);
+
+        assert!(HeaderPresent::new("nats-msg-id").evaluate(&subject, &context));
+        assert!(!Not(HeaderPresent::new("nats-msg-id")).evaluate(&subject, &context));
+        assert!(RootKind(MessageKind::Command).evaluate(&subject, &context));
+        assert!(!RootKind(MessageKind::Event).evaluate(&subject, &context));
+        assert!(MaxPayloadSize(512).evaluate(&subject, &context));
+        assert!(!MaxPayloadSize(511).evaluate(&subject, &context));
+        assert!(!MaxPayloadSize(512).evaluate(&subject, &EvaluationContext::new()));
+
+        // A caused message does not reveal its root's kind by itself
+        let query = MessageFactory::query_from_command(Uuid::new_v4(), &command);
+        let caused = EvaluationContext::new().with_identity(query);
+        assert_eq!(caused.root_kind, None);
+        let caused = caused.with_root_kind(MessageKind::Command);
+        assert!(RootKind(MessageKind::Command).evaluate(&subject, &caused));
+        assert_eq!(
+            Not(MaxPayloadSize(1)).describe(),
+            "not payload at most 1 bytes"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject factories scoped to a bounded context
+//!
+//! Code inside a bounded context only publishes and subscribes within its
+//! own context. A [`ContextScope`] mints subjects and patterns with the
+//! context (and optionally the aggregate) already filled in, rejects
+//! subjects from outside it, and records every subject it mints so the
+//! context's subject catalog can be exported.
+//!
+//! ```
+//! use cim_subject::context_scope::ContextScope;
+//! use cim_subject::Subject;
+//!
+//! let orders = ContextScope::new("orders")?;
+//! let placed = orders.subject("order.placed.v1")?;
+//! assert_eq!(placed.as_str(), "orders.order.placed.v1");
+//! assert!(orders.pattern("*.placed.>")?.matches(&placed));
+//!
+//! let billing = Subject::new("billing.invoice.issued.v1")?;
+//! assert!(orders.check(&billing).is_err());
+//!
+//! let order = orders.with_aggregate("order")?;
+//! order.subject("shipped.v1")?;
+//! let catalog = order.catalog();
+//! let subjects: Vec<&str> = catalog.iter().map(Subject::as_str).collect();
+//! assert_eq!(subjects, ["orders.order.shipped.v1"]);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::sync::Mutex;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::hierarchy::SubjectHierarchy;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Mints subjects and patterns inside one context, and optionally one
+/// aggregate
+#[derive(Debug)]
+pub struct ContextScope {
+    context: String,
+    aggregate: Option<String>,
+    catalog: Mutex<SubjectHierarchy>,
+}
+
+impl ContextScope {
+    /// Create a scope for `context`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `context` is not a valid subject token
+    pub fn new(context: impl Into<String>) -> Result<Self> {
+        let context = context.into();
+        validate_token(&context)?;
+        Ok(Self {
+            context,
+            aggregate: None,
+            catalog: Mutex::new(SubjectHierarchy::new()),
+        })
+    }
+
+    /// Narrow the scope to `aggregate` within the context, with an empty
+    /// catalog
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `aggregate` is not a valid subject token
+    pub fn with_aggregate(&self, aggregate: impl Into<String>) -> Result<Self> {
+        let aggregate = aggregate.into();
+        validate_token(&aggregate)?;
+        Ok(Self {
+            context: self.context.clone(),
+            aggregate: Some(aggregate),
+            catalog: Mutex::new(SubjectHierarchy::new()),
+        })
+    }
+
+    /// The scope's context
+    #[must_use]
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// The scope's aggregate, if narrowed to one
+    #[must_use]
+    pub fn aggregate(&self) -> Option<&str> {
+        self.aggregate.as_deref()
+    }
+
+    /// Mint a subject from the tokens after the scope's prefix, e.g.
+    /// `order.placed.v1`, or `placed.v1` in a scope with an aggregate
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result is not a valid subject
+    pub fn subject(&self, rest: &str) -> Result<Subject> {
+        let subject = Subject::new(format!("{}.{rest}", self.prefix()))?;
+        self.record(subject.clone());
+        Ok(subject)
+    }
+
+    /// Create a pattern from the tokens after the scope's prefix, which may
+    /// include wildcards
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result is not a valid pattern
+    pub fn pattern(&self, rest: &str) -> Result<Pattern> {
+        Pattern::new(format!("{}.{rest}", self.prefix()))
+    }
+
+    /// A pattern matching every subject in the scope
+    #[must_use]
+    pub fn all(&self) -> Pattern {
+        let mut tokens = vec![self.context.as_str()];
+        tokens.extend(self.aggregate.as_deref());
+        tokens.push(">");
+        Pattern::from_valid_tokens(&tokens)
+    }
+
+    /// Whether `subject` lies inside the scope
+    #[must_use]
+    pub fn contains(&self, subject: &Subject) -> bool {
+        subject.context() == self.context
+            && self
+                .aggregate
+                .as_ref()
+                .map_or(true, |aggregate| subject.aggregate() == aggregate)
+    }
+
+    /// Check that `subject` lies inside the scope
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the subject is outside the scope
+    pub fn check(&self, subject: &Subject) -> Result<()> {
+        if self.contains(subject) {
+            Ok(())
+        } else {
+            Err(SubjectError::validation_error(format!(
+                "Subject '{subject}' is outside scope '{}'",
+                self.prefix()
+            )))
+        }
+    }
+
+    /// Add a subject built elsewhere to the scope's catalog
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the subject is outside the scope
+    pub fn register(&self, subject: Subject) -> Result<()> {
+        self.check(&subject)?;
+        self.record(subject);
+        Ok(())
+    }
+
+    /// Every subject minted or registered in the scope
+    #[must_use]
+    pub fn catalog(&self) -> SubjectHierarchy {
+        self.catalog
+            .lock()
+            .map_or_else(|_| SubjectHierarchy::new(), |catalog| catalog.clone())
+    }
+
+    fn prefix(&self) -> String {
+        match &self.aggregate {
+            Some(aggregate) => format!("{}.{aggregate}", self.context),
+            None => self.context.clone(),
+        }
+    }
+
+    fn record(&self, subject: Subject) {
+        if let Ok(mut catalog) = self.catalog.lock() {
+            catalog.insert(subject);
+        }
+    }
+}
+
+fn validate_token(token: &str) -> Result<()> {
+    if token.is_empty()
+        || !token
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(SubjectError::validation_error(format!(
+            "Scope token '{token}' is empty or contains invalid characters"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_stays_inside() {
+        let orders = ContextScope::new("orders").unwrap();
+        assert!(ContextScope::new("orders.order").is_err());
+        // Extra tokens cannot push a subject out of the scope
+        assert!(orders.subject("order.placed").is_err());
+        assert!(orders.subject("order.placed.v1.extra").is_err());
+
+        let order = orders.with_aggregate("order").unwrap();
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let invoice = Subject::new("orders.invoice.issued.v1").unwrap();
+        assert!(order.contains(&placed));
+        assert!(!order.contains(&invoice));
+        assert!(orders.contains(&invoice));
+        assert!(order.register(invoice).is_err());
+
+        assert_eq!(order.all().as_str(), "orders.order.>");
+        assert!(order.all().matches(&placed));
+        assert_eq!(orders.all().as_str(), "orders.>");
+    }
+
+    #[test]
+    fn test_catalog() {
+        let orders = ContextScope::new("orders").unwrap();
+        orders.subject("order.shipped.v1").unwrap();
+        orders.subject("order.placed.v1").unwrap();
+        orders.subject("order.placed.v1").unwrap();
+        orders
+            .register(Subject::new("orders.invoice.issued.v1").unwrap())
+            .unwrap();
+        orders.pattern("order.*.v1").unwrap();
+
+        let catalog = orders.catalog();
+        let subjects: Vec<&str> = catalog.iter().map(Subject::as_str).collect();
+        assert_eq!(subjects, [
+            "orders.invoice.issued.v1",
+            "orders.order.placed.v1",
+            "orders.order.shipped.v1",
+        ]);
+    }
+}
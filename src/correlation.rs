@@ -27,13 +27,25 @@
 //!    - A unique `MessageId`
 //!    - A `CorrelationId` (either self or inherited)
 //!    - A `CausationId` (either self or parent's `MessageId`)
+//!
+//! Without the `std` feature, identities are UUID-only: CIDs, and with them
+//! event identities, need `cim-ipld`.
 
-use std::fmt::{
+use alloc::format;
+use alloc::string::{
+    String,
+    ToString,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{
     self,
     Display,
 };
+use core::str::FromStr;
 
 // Re-export from cim-ipld for CID support
+#[cfg(feature = "std")]
 use cim_ipld::Cid;
 use serde::{
     Deserialize,
@@ -43,19 +55,22 @@ use thiserror::Error;
 use uuid::Uuid;
 
 /// Wrapper for CID that implements Serialize/Deserialize
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SerializableCid(pub Cid);
 
+#[cfg(feature = "std")]
 impl Serialize for SerializableCid {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where S: serde::Serializer {
         // Serialize as string
         self.0.to_string().serialize(serializer)
     }
 }
 
+#[cfg(feature = "std")]
 impl<'de> Deserialize<'de> for SerializableCid {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
     where D: serde::Deserializer<'de> {
         // Deserialize from string
         let s = String::deserialize(deserializer)?;
@@ -64,6 +79,7 @@ impl<'de> Deserialize<'de> for SerializableCid {
     }
 }
 
+#[cfg(feature = "std")]
 impl Display for SerializableCid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -88,10 +104,22 @@ pub enum CorrelationError {
     /// Invalid message identity configuration
     #[error("Invalid message identity: {0}")]
     InvalidIdentity(String),
+
+    /// Saga steps without a registered compensation
+    #[error("No compensation registered for: {}", .0.join(", "))]
+    MissingCompensation(Vec<String>),
+
+    /// A chain store could not read or write its backing storage
+    #[error("Chain storage error: {0}")]
+    Storage(String),
+
+    /// An identity broke one or more configured identity policies
+    #[error("Identity policy violated: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    PolicyViolation(Vec<PolicyViolation>),
 }
 
 /// Result type for correlation operations
-pub type Result<T> = std::result::Result<T, CorrelationError>;
+pub type Result<T> = core::result::Result<T, CorrelationError>;
 
 /// Type of identifier used in the system
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -99,6 +127,7 @@ pub enum IdType {
     /// UUID for commands and queries
     Uuid(Uuid),
     /// Content-addressed ID for events
+    #[cfg(feature = "std")]
     Cid(SerializableCid),
 }
 
@@ -106,11 +135,30 @@ impl Display for IdType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             IdType::Uuid(uuid) => write!(f, "{uuid}"),
+            #[cfg(feature = "std")]
             IdType::Cid(cid) => write!(f, "{cid}"),
         }
     }
 }
 
+impl FromStr for IdType {
+    type Err = CorrelationError;
+
+    /// Parse a UUID, or failing that a CID
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(uuid) = s.parse::<Uuid>() {
+            return Ok(IdType::Uuid(uuid));
+        }
+        #[cfg(feature = "std")]
+        if let Ok(cid) = s.parse::<Cid>() {
+            return Ok(IdType::Cid(SerializableCid(cid)));
+        }
+        Err(CorrelationError::InvalidIdentity(format!(
+            "'{s}' is neither a UUID nor a CID"
+        )))
+    }
+}
+
 /// Unique identifier for correlating related messages
 ///
 /// For the first message in a correlation chain, this is a self-reference.
@@ -126,6 +174,7 @@ impl CorrelationId {
     }
 
     /// Create a correlation ID from a CID
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn from_cid(cid: Cid) -> Self {
         Self(IdType::Cid(SerializableCid(cid)))
@@ -159,6 +208,7 @@ impl CausationId {
     }
 
     /// Create a causation ID from a CID
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn from_cid(cid: Cid) -> Self {
         Self(IdType::Cid(SerializableCid(cid)))
@@ -191,6 +241,103 @@ pub struct MessageIdentity {
 
     /// Identifies what caused this message
     pub causation_id: CausationId,
+
+    /// Whether this is a command, query or event, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<MessageKind>,
+}
+
+/// The kind of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MessageKind {
+    /// A request to change state, identified by a UUID
+    Command,
+    /// A request for information, identified by a UUID
+    Query,
+    /// A record of a state change, identified by a CID
+    Event,
+}
+
+impl Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageKind::Command => write!(f, "command"),
+            MessageKind::Query => write!(f, "query"),
+            MessageKind::Event => write!(f, "event"),
+        }
+    }
+}
+
+/// Type-level message kinds for [`MessageFactory::root`] and
+/// [`MessageFactory::caused`]
+pub trait Kind {
+    /// Identifier type messages of this kind are created with
+    type Id;
+
+    /// The kind recorded in the identity
+    const KIND: MessageKind;
+
+    /// Wrap an identifier
+    fn message_id(id: Self::Id) -> IdType;
+}
+
+/// Marker types implementing [`Kind`]
+pub mod kinds {
+    #[cfg(feature = "std")]
+    use cim_ipld::Cid;
+    use uuid::Uuid;
+
+    #[cfg(feature = "std")]
+    use super::SerializableCid;
+    use super::{
+        IdType,
+        Kind,
+        MessageKind,
+    };
+
+    /// Commands, identified by UUIDs
+    #[derive(Debug, Clone, Copy)]
+    pub struct Command;
+
+    /// Queries, identified by UUIDs
+    #[derive(Debug, Clone, Copy)]
+    pub struct Query;
+
+    /// Events, identified by CIDs
+    #[cfg(feature = "std")]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Event;
+
+    impl Kind for Command {
+        type Id = Uuid;
+
+        const KIND: MessageKind = MessageKind::Command;
+
+        fn message_id(id: Uuid) -> IdType {
+            IdType::Uuid(id)
+        }
+    }
+
+    impl Kind for Query {
+        type Id = Uuid;
+
+        const KIND: MessageKind = MessageKind::Query;
+
+        fn message_id(id: Uuid) -> IdType {
+            IdType::Uuid(id)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Kind for Event {
+        type Id = Cid;
+
+        const KIND: MessageKind = MessageKind::Event;
+
+        fn message_id(id: Cid) -> IdType {
+            IdType::Cid(SerializableCid(id))
+        }
+    }
 }
 
 impl MessageIdentity {
@@ -201,15 +348,10 @@ impl MessageIdentity {
     #[must_use]
     pub fn root(message_id: IdType) -> Self {
         Self {
-            correlation_id: match &message_id {
-                IdType::Uuid(uuid) => CorrelationId::from_uuid(*uuid),
-                IdType::Cid(cid) => CorrelationId(IdType::Cid(cid.clone())),
-            },
-            causation_id: match &message_id {
-                IdType::Uuid(uuid) => CausationId::from_uuid(*uuid),
-                IdType::Cid(cid) => CausationId(IdType::Cid(cid.clone())),
-            },
+            correlation_id: CorrelationId(message_id.clone()),
+            causation_id: CausationId(message_id.clone()),
             message_id,
+            kind: None,
         }
     }
 
@@ -226,32 +368,26 @@ impl MessageIdentity {
         Self {
             message_id,
             correlation_id: parent_correlation,
-            causation_id: match parent_id {
-                IdType::Uuid(uuid) => CausationId::from_uuid(uuid),
-                IdType::Cid(cid) => CausationId(IdType::Cid(cid)),
-            },
+            causation_id: CausationId(parent_id),
+            kind: None,
         }
     }
 
+    /// Record the message kind
+    #[must_use]
+    pub fn with_kind(mut self, kind: MessageKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
     /// Check if this is a root message (self-correlated)
     #[must_use]
     pub fn is_root(&self) -> bool {
-        match (
-            &self.message_id,
-            &self.correlation_id.0,
-            &self.causation_id.0,
-        ) {
-            (IdType::Uuid(msg), IdType::Uuid(corr), IdType::Uuid(caus)) => {
-                msg == corr && msg == caus
-            },
-            (IdType::Cid(msg), IdType::Cid(corr), IdType::Cid(caus)) => {
-                msg.0 == corr.0 && msg.0 == caus.0
-            },
-            _ => false,
-        }
+        self.message_id == self.correlation_id.0 && self.message_id == self.causation_id.0
     }
 
-    /// Convert to NATS headers
+    /// Convert to NATS headers using the default
+    /// [`HeaderConvention`](crate::header_convention::HeaderConvention)
     #[must_use]
     pub fn to_nats_headers(&self) -> Vec<(&'static str, String)> {
         vec![
@@ -262,6 +398,58 @@ impl MessageIdentity {
     }
 }
 
+#[cfg(feature = "cim-ipld")]
+impl MessageIdentity {
+    /// Create an event identity whose CID is derived from its content
+    ///
+    /// The CID is a raw-codec sha2-256 version 1 CID over the payload and, for
+    /// caused events, the parent's correlation and message IDs, so the same
+    /// payload yields the same identity only within the same causal
+    /// position. Without a parent the event starts a new chain.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: the encoded CID is always well-formed
+    #[must_use]
+    pub fn for_event_payload(payload: &[u8], parent: Option<&MessageIdentity>) -> Self {
+        let cid = event_cid(payload, parent);
+        match parent {
+            Some(parent) => MessageFactory::caused::<kinds::Event>(cid, parent),
+            None => MessageFactory::root::<kinds::Event>(cid),
+        }
+    }
+}
+
+/// Hash an event payload and its causal headers into a version 1 CID
+#[cfg(feature = "cim-ipld")]
+fn event_cid(payload: &[u8], parent: Option<&MessageIdentity>) -> Cid {
+    use sha2::{
+        Digest,
+        Sha256,
+    };
+
+    const CID_V1: u8 = 0x01;
+    const RAW_CODEC: u8 = 0x55;
+    const SHA2_256: u8 = 0x12;
+    const DIGEST_LEN: u8 = 0x20;
+
+    // Length-prefix every field so distinct inputs never share an encoding
+    let mut hasher = Sha256::new();
+    let mut field = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    };
+    field(payload);
+    if let Some(parent) = parent {
+        field(parent.correlation_id.to_string().as_bytes());
+        field(parent.message_id.to_string().as_bytes());
+    }
+
+    let mut bytes = vec![CID_V1, RAW_CODEC, SHA2_256, DIGEST_LEN];
+    bytes.extend_from_slice(&hasher.finalize());
+    Cid::try_from(bytes.as_slice()).expect("a sha2-256 version 1 CID is always well-formed")
+}
+
 /// Factory for creating messages with proper correlation/causation
 ///
 /// This is the primary interface for creating messages in the system.
@@ -269,22 +457,57 @@ impl MessageIdentity {
 pub struct MessageFactory;
 
 impl MessageFactory {
+    /// Create a root message of kind `K` (starts new correlation chain)
+    #[must_use]
+    pub fn root<K: Kind>(id: K::Id) -> MessageIdentity {
+        MessageIdentity::root(K::message_id(id)).with_kind(K::KIND)
+    }
+
+    /// Create a message of kind `K` caused by another message
+    ///
+    /// ```
+    /// use cim_subject::correlation::kinds::{
+    ///     Command,
+    ///     Query,
+    /// };
+    /// use cim_subject::correlation::{
+    ///     MessageFactory,
+    ///     MessageKind,
+    /// };
+    /// use uuid::Uuid;
+    ///
+    /// let root = MessageFactory::root::<Command>(Uuid::new_v4());
+    /// let query = MessageFactory::caused::<Query>(Uuid::new_v4(), &root);
+    /// assert_eq!(query.kind, Some(MessageKind::Query));
+    /// assert_eq!(query.causation_id.0, root.message_id);
+    /// ```
+    #[must_use]
+    pub fn caused<K: Kind>(id: K::Id, parent_identity: &MessageIdentity) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            K::message_id(id),
+            parent_identity.correlation_id.clone(),
+            parent_identity.message_id.clone(),
+        )
+        .with_kind(K::KIND)
+    }
+
     /// Create a root command (starts new correlation chain)
     #[must_use]
     pub fn create_root_command(command_id: Uuid) -> MessageIdentity {
-        MessageIdentity::root(IdType::Uuid(command_id))
+        Self::root::<kinds::Command>(command_id)
     }
 
     /// Create a root query (starts new correlation chain)
     #[must_use]
     pub fn create_root_query(query_id: Uuid) -> MessageIdentity {
-        MessageIdentity::root(IdType::Uuid(query_id))
+        Self::root::<kinds::Query>(query_id)
     }
 
     /// Create a root event (starts new correlation chain)
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn create_root_event(event_cid: Cid) -> MessageIdentity {
-        MessageIdentity::root(IdType::Cid(SerializableCid(event_cid)))
+        Self::root::<kinds::Event>(event_cid)
     }
 
     /// Create a command caused by another command
@@ -293,11 +516,7 @@ impl MessageFactory {
         command_id: Uuid,
         parent_identity: &MessageIdentity,
     ) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(command_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Command>(command_id, parent_identity)
     }
 
     /// Create a command caused by a query
@@ -306,11 +525,7 @@ impl MessageFactory {
         command_id: Uuid,
         parent_identity: &MessageIdentity,
     ) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(command_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Command>(command_id, parent_identity)
     }
 
     /// Create a command caused by an event
@@ -319,11 +534,7 @@ impl MessageFactory {
         command_id: Uuid,
         parent_identity: &MessageIdentity,
     ) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(command_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Command>(command_id, parent_identity)
     }
 
     /// Create a query caused by a command
@@ -332,63 +543,89 @@ impl MessageFactory {
         query_id: Uuid,
         parent_identity: &MessageIdentity,
     ) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(query_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Query>(query_id, parent_identity)
     }
 
     /// Create a query caused by another query
     #[must_use]
     pub fn query_from_query(query_id: Uuid, parent_identity: &MessageIdentity) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(query_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Query>(query_id, parent_identity)
     }
 
     /// Create a query caused by an event
     #[must_use]
     pub fn query_from_event(query_id: Uuid, parent_identity: &MessageIdentity) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Uuid(query_id),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Query>(query_id, parent_identity)
     }
 
     /// Create an event caused by a command
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn event_from_command(
         event_cid: Cid,
         parent_identity: &MessageIdentity,
     ) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Cid(SerializableCid(event_cid)),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Event>(event_cid, parent_identity)
     }
 
     /// Create an event caused by a query
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn event_from_query(event_cid: Cid, parent_identity: &MessageIdentity) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Cid(SerializableCid(event_cid)),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
-        )
+        Self::caused::<kinds::Event>(event_cid, parent_identity)
     }
 
     /// Create an event caused by another event
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn event_from_event(event_cid: Cid, parent_identity: &MessageIdentity) -> MessageIdentity {
-        MessageIdentity::caused_by(
-            IdType::Cid(SerializableCid(event_cid)),
-            parent_identity.correlation_id.clone(),
-            parent_identity.message_id.clone(),
+        Self::caused::<kinds::Event>(event_cid, parent_identity)
+    }
+}
+
+/// A convention that message identities can be checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IdentityPolicy {
+    /// Every identity must record its kind
+    KindRequired,
+    /// Events must be identified by CIDs
+    EventsUseCid,
+    /// Commands must be identified by UUIDs
+    CommandsUseUuid,
+    /// Queries must be identified by UUIDs
+    QueriesUseUuid,
+    /// The correlation ID must have the ID type of the root's kind
+    CorrelationMatchesRootKind,
+}
+
+impl IdentityPolicy {
+    /// Every policy
+    pub const ALL: [IdentityPolicy; 5] = [
+        IdentityPolicy::KindRequired,
+        IdentityPolicy::EventsUseCid,
+        IdentityPolicy::CommandsUseUuid,
+        IdentityPolicy::QueriesUseUuid,
+        IdentityPolicy::CorrelationMatchesRootKind,
+    ];
+}
+
+/// A message identity that broke an [`IdentityPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation {
+    /// The broken policy
+    pub policy: IdentityPolicy,
+    /// The offending message
+    pub message_id: IdType,
+    /// What was wrong
+    pub reason: String,
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} on {}: {}",
+            self.policy, self.message_id, self.reason
         )
     }
 }
@@ -397,42 +634,149 @@ impl MessageFactory {
 pub struct CorrelationValidator {
     /// Maximum depth for causation chains to prevent infinite loops
     pub max_chain_depth: usize,
+
+    /// Identity conventions to enforce; none by default
+    pub policies: Vec<IdentityPolicy>,
 }
 
 impl Default for CorrelationValidator {
     fn default() -> Self {
         Self {
             max_chain_depth: 100,
+            policies: Vec::new(),
         }
     }
 }
 
 impl CorrelationValidator {
+    /// A validator enforcing every [`IdentityPolicy`]
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            policies: IdentityPolicy::ALL.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// Enforce an additional policy
+    #[must_use]
+    pub fn with_policy(mut self, policy: IdentityPolicy) -> Self {
+        if !self.policies.contains(&policy) {
+            self.policies.push(policy);
+        }
+        self
+    }
+
+    /// Check an identity against the configured policies
+    ///
+    /// `root_kind` is the kind of the chain's root, needed for
+    /// [`IdentityPolicy::CorrelationMatchesRootKind`] on non-root messages;
+    /// for a root message its own kind is used.
+    #[must_use]
+    pub fn check_policies(
+        &self,
+        identity: &MessageIdentity,
+        root_kind: Option<MessageKind>,
+    ) -> Vec<PolicyViolation> {
+        let is_uuid = |id: &IdType| matches!(id, IdType::Uuid(_));
+        let root_kind = if identity.is_root() {
+            identity.kind
+        } else {
+            root_kind
+        };
+
+        self.policies
+            .iter()
+            .filter_map(|&policy| {
+                let reason = match (policy, identity.kind) {
+                    (IdentityPolicy::KindRequired, None) => {
+                        "message kind is not recorded".to_string()
+                    },
+                    (IdentityPolicy::EventsUseCid, Some(MessageKind::Event))
+                        if is_uuid(&identity.message_id) =>
+                    {
+                        "event is identified by a UUID".to_string()
+                    },
+                    (IdentityPolicy::CommandsUseUuid, Some(MessageKind::Command))
+                    | (IdentityPolicy::QueriesUseUuid, Some(MessageKind::Query))
+                        if !is_uuid(&identity.message_id) =>
+                    {
+                        format!("{} is identified by a CID", identity.kind?)
+                    },
+                    (IdentityPolicy::CorrelationMatchesRootKind, _) => {
+                        let root_kind = root_kind?;
+                        let expects_uuid = root_kind != MessageKind::Event;
+                        if is_uuid(&identity.correlation_id.0) == expects_uuid {
+                            return None;
+                        }
+                        format!(
+                            "correlation ID type does not match root {root_kind}, which uses {}",
+                            if expects_uuid { "UUIDs" } else { "CIDs" }
+                        )
+                    },
+                    _ => return None,
+                };
+                Some(PolicyViolation {
+                    policy,
+                    message_id: identity.message_id.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    /// Check every identity of one correlation against the configured
+    /// policies, taking the root kind from the root among them
+    #[must_use]
+    pub fn check_chain_policies(&self, identities: &[MessageIdentity]) -> Vec<PolicyViolation> {
+        let root_kind = identities
+            .iter()
+            .find(|identity| identity.is_root())
+            .and_then(|root| root.kind);
+        identities
+            .iter()
+            .flat_map(|identity| self.check_policies(identity, root_kind))
+            .collect()
+    }
+
     /// Validate a message identity
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The identity breaks a configured policy (the root kind is only known
+    ///   for root messages here; use
+    ///   [`check_chain_policies`](Self::check_chain_policies) for chains)
     /// - A non-root message has self-causation (message ID equals causation ID)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                message_id = %identity.message_id,
+                correlation_id = %identity.correlation_id.0,
+                causation_id = %identity.causation_id.0,
+            ),
+            err
+        )
+    )]
     pub fn validate(&self, identity: &MessageIdentity) -> Result<()> {
+        let violations = self.check_policies(identity, None);
+        if !violations.is_empty() {
+            return Err(CorrelationError::PolicyViolation(violations));
+        }
+
         // Root messages must have self-correlation
         if identity.is_root() {
             return Ok(());
         }
 
         // Non-root messages must have different message ID and causation ID
-        match (&identity.message_id, &identity.causation_id.0) {
-            (IdType::Uuid(msg), IdType::Uuid(caus)) if msg == caus => {
-                return Err(CorrelationError::InvalidIdentity(
-                    "Non-root message cannot be self-caused".to_string(),
-                ));
-            },
-            (IdType::Cid(msg), IdType::Cid(caus)) if msg.0 == caus.0 => {
-                return Err(CorrelationError::InvalidIdentity(
-                    "Non-root message cannot be self-caused".to_string(),
-                ));
-            },
-            _ => {},
+        if identity.message_id == identity.causation_id.0 {
+            return Err(CorrelationError::InvalidIdentity(
+                "Non-root message cannot be self-caused".to_string(),
+            ));
         }
 
         Ok(())
@@ -450,11 +794,12 @@ impl CorrelationValidator {
             return Err(CorrelationError::CyclicCausation);
         }
 
-        // Build a map of message IDs to check for cycles
-        let mut seen = std::collections::HashSet::new();
-
-        for identity in chain {
-            if !seen.insert(&identity.message_id) {
+        // The depth bound keeps this pairwise scan short
+        for (i, identity) in chain.iter().enumerate() {
+            if chain[..i]
+                .iter()
+                .any(|earlier| earlier.message_id == identity.message_id)
+            {
                 return Err(CorrelationError::CyclicCausation);
             }
         }
@@ -492,6 +837,32 @@ mod tests {
         assert_eq!(caused_identity.message_id, IdType::Uuid(caused_id));
         assert_eq!(caused_identity.correlation_id, root_identity.correlation_id);
         assert_eq!(caused_identity.causation_id.0, root_identity.message_id);
+        assert_eq!(caused_identity.kind, Some(MessageKind::Command));
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        let query_id = Uuid::new_v4();
+        let root = MessageFactory::root::<kinds::Query>(query_id);
+        assert_eq!(root, MessageFactory::create_root_query(query_id));
+
+        let json = serde_json::to_string(&root).unwrap();
+        assert!(json.contains("\"kind\":\"Query\""));
+        assert_eq!(
+            serde_json::from_str::<MessageIdentity>(&json).unwrap(),
+            root
+        );
+
+        // Identities serialized before kinds existed still load
+        let untyped = MessageIdentity::root(root.message_id.clone());
+        let legacy = serde_json::to_string(&untyped).unwrap();
+        assert!(!legacy.contains("kind"));
+        assert_eq!(
+            serde_json::from_str::<MessageIdentity>(&legacy)
+                .unwrap()
+                .kind,
+            None
+        );
     }
 
     #[test]
@@ -520,4 +891,70 @@ mod tests {
         let caused_identity = MessageFactory::command_from_command(caused_id, &root_identity);
         assert!(validator.validate(&caused_identity).is_ok());
     }
+
+    #[cfg(feature = "cim-ipld")]
+    #[test]
+    fn test_event_payload_cids_are_deterministic() {
+        let root = MessageIdentity::for_event_payload(b"order placed", None);
+        assert!(root.is_root());
+        assert_eq!(root.kind, Some(MessageKind::Event));
+        assert_eq!(
+            root,
+            MessageIdentity::for_event_payload(b"order placed", None)
+        );
+
+        let caused = MessageIdentity::for_event_payload(b"order placed", Some(&root));
+        assert_eq!(caused.causation_id.0, root.message_id);
+        assert_eq!(caused.correlation_id, root.correlation_id);
+        // The same payload in another causal position gets another CID
+        assert_ne!(caused.message_id, root.message_id);
+        assert!(CorrelationValidator::strict()
+            .check_chain_policies(&[root, caused])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_identity_policies() {
+        let validator = CorrelationValidator::strict();
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let query = MessageFactory::query_from_command(Uuid::new_v4(), &root);
+        assert!(validator
+            .check_chain_policies(&[root.clone(), query])
+            .is_empty());
+
+        // An event carrying a UUID, with no kind on a sibling
+        let bad_event = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            root.correlation_id.clone(),
+            root.message_id.clone(),
+        )
+        .with_kind(MessageKind::Event);
+        let untyped = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            root.correlation_id.clone(),
+            root.message_id.clone(),
+        );
+        let violations =
+            validator.check_chain_policies(&[root.clone(), bad_event.clone(), untyped]);
+        let policies: Vec<IdentityPolicy> = violations.iter().map(|v| v.policy).collect();
+        assert_eq!(policies, vec![
+            IdentityPolicy::EventsUseCid,
+            IdentityPolicy::KindRequired
+        ]);
+        assert_eq!(violations[0].message_id, bad_event.message_id);
+
+        // An event-rooted chain must be correlated by a CID
+        let child = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let violations = validator.check_policies(&child, Some(MessageKind::Event));
+        assert_eq!(
+            violations[0].policy,
+            IdentityPolicy::CorrelationMatchesRootKind
+        );
+
+        assert!(matches!(
+            validator.validate(&bad_event),
+            Err(CorrelationError::PolicyViolation(_))
+        ));
+        assert!(CorrelationValidator::default().validate(&bad_event).is_ok());
+    }
 }
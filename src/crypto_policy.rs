@@ -0,0 +1,549 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Payload encryption required by subject pattern
+//!
+//! A [`CryptoPolicy`] maps patterns to an [`EncryptionRequirement`]; the
+//! most specific matching pattern decides, so a whole context can require
+//! encryption while one subject family pins an algorithm. Publishers call
+//! [`seal`](CryptoPolicy::seal) and consumers [`open`](CryptoPolicy::open)
+//! with an [`EncryptionKey`], so sensitive subjects are encrypted the same
+//! way everywhere and a consumer rejects plaintext where the policy demands
+//! ciphertext.
+//!
+//! An encrypted payload is the random nonce followed by the AEAD
+//! ciphertext, with the subject as associated data so it cannot be replayed
+//! on another subject. The [`ENCRYPTION_HEADER`] names the algorithm and the
+//! [`ENCRYPTION_KEY_HEADER`] the key; `Content-Type` keeps describing the
+//! plaintext.
+//!
+//! ```
+//! use cim_subject::crypto_policy::{
+//!     CryptoPolicy,
+//!     EncryptionKey,
+//!     EncryptionRequirement,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     NatsMessage,
+//!     Pattern,
+//! };
+//! use uuid::Uuid;
+//!
+//! let mut policy = CryptoPolicy::new();
+//! policy.require(
+//!     Pattern::new("lending.documents.identity.>")?,
+//!     EncryptionRequirement::Required,
+//! );
+//! let key = EncryptionKey::new("documents-2025", [42; 32]);
+//!
+//! let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let mut message = NatsMessage::with_correlation(
+//!     "lending.documents.identity.uploaded".to_string(),
+//!     serde_json::json!({"passport": "X1234567"}),
+//!     &identity,
+//! );
+//! assert!(policy.seal(&mut message, &key)?);
+//! assert!(message.payload_json::<serde_json::Value>().is_err());
+//!
+//! policy.open(&mut message, &key)?;
+//! let payload: serde_json::Value = message.payload_json()?;
+//! assert_eq!(payload["passport"], "X1234567");
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use aes_gcm::aead::{
+    Aead,
+    AeadCore,
+    KeyInit,
+    Nonce,
+    OsRng,
+    Payload as AeadPayload,
+};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::ChaCha20Poly1305;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::translator::{
+    NatsMessage,
+    Payload,
+};
+
+/// Header naming the algorithm an encrypted payload uses
+pub const ENCRYPTION_HEADER: &str = "X-Encryption";
+
+/// Header naming the key an encrypted payload was sealed with
+pub const ENCRYPTION_KEY_HEADER: &str = "X-Encryption-Key-ID";
+
+/// An AEAD cipher for payloads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EncryptionAlgorithm {
+    /// AES-256 in Galois/Counter Mode
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    /// `ChaCha20` with a `Poly1305` authenticator
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl EncryptionAlgorithm {
+    /// The algorithm's name in the [`ENCRYPTION_HEADER`]
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
+    /// The algorithm with the given header name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Aes256Gcm, Self::ChaCha20Poly1305]
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    fn seal(self, key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm => seal_with::<Aes256Gcm>(key, aad, plaintext),
+            Self::ChaCha20Poly1305 => seal_with::<ChaCha20Poly1305>(key, aad, plaintext),
+        }
+    }
+
+    fn open(self, key: &[u8; 32], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm => open_with::<Aes256Gcm>(key, aad, sealed),
+            Self::ChaCha20Poly1305 => open_with::<ChaCha20Poly1305>(key, aad, sealed),
+        }
+    }
+}
+
+impl Display for EncryptionAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether subjects must carry encrypted payloads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionRequirement {
+    /// Payloads are sent in the clear
+    #[default]
+    None,
+    /// Payloads must be encrypted, with any algorithm
+    Required,
+    /// Payloads must be encrypted with this algorithm
+    Algorithm(EncryptionAlgorithm),
+}
+
+impl EncryptionRequirement {
+    /// Check if a payload encrypted with `algorithm`, or sent in the clear
+    /// for `None`, meets the requirement
+    #[must_use]
+    pub fn is_satisfied_by(self, algorithm: Option<EncryptionAlgorithm>) -> bool {
+        match self {
+            Self::None => true,
+            Self::Required => algorithm.is_some(),
+            Self::Algorithm(required) => algorithm == Some(required),
+        }
+    }
+}
+
+/// A named symmetric key
+#[derive(Clone)]
+pub struct EncryptionKey {
+    id: String,
+    bytes: [u8; 32],
+}
+
+impl EncryptionKey {
+    /// Create a key from its ID and 256 bits of key material
+    #[must_use]
+    pub fn new(id: impl Into<String>, bytes: [u8; 32]) -> Self {
+        Self {
+            id: id.into(),
+            bytes,
+        }
+    }
+
+    /// The key's ID, recorded in the [`ENCRYPTION_KEY_HEADER`]
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Encrypt a message's payload with `algorithm` and mark its headers
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the payload is already encrypted, or a
+    /// translation error if it cannot be encoded or encrypted
+    pub fn encrypt(&self, message: &mut NatsMessage, algorithm: EncryptionAlgorithm) -> Result<()> {
+        if message.headers.contains(ENCRYPTION_HEADER) {
+            return Err(SubjectError::validation_error(format!(
+                "Payload on '{}' is already encrypted",
+                message.subject
+            )));
+        }
+        let sealed = algorithm.seal(
+            &self.bytes,
+            message.subject.as_bytes(),
+            &message.payload_raw()?,
+        )?;
+        message.payload = Payload::Raw(sealed.into());
+        message
+            .headers
+            .insert(ENCRYPTION_HEADER, algorithm.name())?;
+        message
+            .headers
+            .insert(ENCRYPTION_KEY_HEADER, self.id.clone())
+    }
+
+    /// Decrypt a message's payload and remove its encryption headers,
+    /// returning the algorithm it was encrypted with
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the payload is not encrypted or names
+    /// another key or an unknown algorithm, or a permission denied error if
+    /// the ciphertext does not authenticate under this key
+    pub fn decrypt(&self, message: &mut NatsMessage) -> Result<EncryptionAlgorithm> {
+        let algorithm = encryption_of(message)?.ok_or_else(|| {
+            SubjectError::validation_error(format!(
+                "Payload on '{}' is not encrypted",
+                message.subject
+            ))
+        })?;
+        if let Some(id) = message.headers.get(ENCRYPTION_KEY_HEADER) {
+            if id != self.id {
+                return Err(SubjectError::validation_error(format!(
+                    "Payload on '{}' was encrypted with key '{id}', not '{}'",
+                    message.subject, self.id
+                )));
+            }
+        }
+        let plaintext = algorithm
+            .open(
+                &self.bytes,
+                message.subject.as_bytes(),
+                &message.payload_raw()?,
+            )
+            .ok_or_else(|| {
+                SubjectError::permission_denied(format!(
+                    "Payload on '{}' does not decrypt with key '{}'",
+                    message.subject, self.id
+                ))
+            })?;
+        message.payload = Payload::Raw(plaintext.into());
+        message.headers.remove(ENCRYPTION_HEADER)?;
+        message.headers.remove(ENCRYPTION_KEY_HEADER)?;
+        Ok(algorithm)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encryption requirements by subject pattern, most specific first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "CryptoPolicyRepr")]
+pub struct CryptoPolicy {
+    entries: Vec<(Pattern, EncryptionRequirement)>,
+    /// Algorithm used where encryption is [`Required`] without naming one
+    ///
+    /// [`Required`]: EncryptionRequirement::Required
+    pub default_algorithm: EncryptionAlgorithm,
+}
+
+/// Serialized form of a [`CryptoPolicy`], in any order
+#[derive(Deserialize)]
+struct CryptoPolicyRepr {
+    entries: Vec<(Pattern, EncryptionRequirement)>,
+    default_algorithm: EncryptionAlgorithm,
+}
+
+impl From<CryptoPolicyRepr> for CryptoPolicy {
+    fn from(repr: CryptoPolicyRepr) -> Self {
+        let mut policy = Self::new().with_default_algorithm(repr.default_algorithm);
+        for (pattern, requirement) in repr.entries {
+            policy.require(pattern, requirement);
+        }
+        policy
+    }
+}
+
+impl CryptoPolicy {
+    /// Create a policy that requires nothing, encrypting with AES-256-GCM
+    /// when asked to
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the algorithm for subjects that require encryption without
+    /// naming one
+    #[must_use]
+    pub fn with_default_algorithm(mut self, algorithm: EncryptionAlgorithm) -> Self {
+        self.default_algorithm = algorithm;
+        self
+    }
+
+    /// Set the requirement for subjects matching a pattern, replacing any
+    /// earlier entry for the same pattern
+    pub fn require(&mut self, pattern: Pattern, requirement: EncryptionRequirement) {
+        self.entries.retain(|(existing, _)| *existing != pattern);
+        self.entries.push((pattern, requirement));
+        // Stable, so equally specific patterns keep registration order
+        self.entries
+            .sort_by_key(|(pattern, _)| pattern.specificity_key());
+    }
+
+    /// Registered patterns and their requirements, most specific first
+    pub fn iter(&self) -> impl Iterator<Item = (&Pattern, EncryptionRequirement)> {
+        self.entries
+            .iter()
+            .map(|(pattern, requirement)| (pattern, *requirement))
+    }
+
+    /// The requirement for a subject; `None` if no pattern matches
+    #[must_use]
+    pub fn requirement(&self, subject: &Subject) -> EncryptionRequirement {
+        self.entries
+            .iter()
+            .find(|(pattern, _)| pattern.matches(subject))
+            .map(|(_, requirement)| *requirement)
+            .unwrap_or_default()
+    }
+
+    /// The algorithm a subject's payloads are encrypted with, if any
+    #[must_use]
+    pub fn algorithm_for(&self, subject: &Subject) -> Option<EncryptionAlgorithm> {
+        match self.requirement(subject) {
+            EncryptionRequirement::None => None,
+            EncryptionRequirement::Required => Some(self.default_algorithm),
+            EncryptionRequirement::Algorithm(algorithm) => Some(algorithm),
+        }
+    }
+
+    /// Encrypt a message's payload if its subject requires it, returning
+    /// whether it did
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subject is invalid or the payload is already
+    /// encrypted or cannot be encrypted
+    pub fn seal(&self, message: &mut NatsMessage, key: &EncryptionKey) -> Result<bool> {
+        let subject = Subject::new(&message.subject)?;
+        let Some(algorithm) = self.algorithm_for(&subject) else {
+            return Ok(false);
+        };
+        key.encrypt(message, algorithm)?;
+        Ok(true)
+    }
+
+    /// Check a received message against its subject's requirement and
+    /// decrypt its payload if it is encrypted
+    ///
+    /// # Errors
+    ///
+    /// Returns a permission denied error if the payload is not encrypted as
+    /// the policy requires or does not authenticate, or a validation error
+    /// if its encryption headers are invalid
+    pub fn open(&self, message: &mut NatsMessage, key: &EncryptionKey) -> Result<()> {
+        let subject = Subject::new(&message.subject)?;
+        let algorithm = encryption_of(message)?;
+        let requirement = self.requirement(&subject);
+        if !requirement.is_satisfied_by(algorithm) {
+            return Err(SubjectError::permission_denied(format!(
+                "Payload on '{subject}' is {} but the policy requires {}",
+                algorithm.map_or_else(|| "unencrypted".to_string(), |a| a.to_string()),
+                match requirement {
+                    EncryptionRequirement::Algorithm(required) => required.to_string(),
+                    _ => "encryption".to_string(),
+                }
+            )));
+        }
+        if algorithm.is_some() {
+            key.decrypt(message)?;
+        }
+        Ok(())
+    }
+}
+
+/// The algorithm named by a message's [`ENCRYPTION_HEADER`], if it has one
+fn encryption_of(message: &NatsMessage) -> Result<Option<EncryptionAlgorithm>> {
+    message
+        .headers
+        .get(ENCRYPTION_HEADER)
+        .map(|name| {
+            EncryptionAlgorithm::from_name(name).ok_or_else(|| {
+                SubjectError::validation_error(format!("Unknown encryption algorithm '{name}'"))
+            })
+        })
+        .transpose()
+}
+
+fn seal_with<C: Aead + AeadCore + KeyInit>(
+    key: &[u8; 32],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    let cipher = C::new_from_slice(key)
+        .map_err(|e| SubjectError::translation_error(format!("Invalid key: {e}")))?;
+    let nonce = C::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, AeadPayload {
+            msg: plaintext,
+            aad,
+        })
+        .map_err(|e| SubjectError::translation_error(format!("Cannot encrypt payload: {e}")))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open_with<C: Aead + AeadCore + KeyInit>(
+    key: &[u8; 32],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    let cipher = C::new_from_slice(key).ok()?;
+    let nonce_len = Nonce::<C>::default().len();
+    if sealed.len() < nonce_len {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(nonce_len);
+    cipher
+        .decrypt(Nonce::<C>::from_slice(nonce), AeadPayload {
+            msg: ciphertext,
+            aad,
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::{
+        IdType,
+        MessageIdentity,
+    };
+
+    fn message(subject: &str) -> NatsMessage {
+        NatsMessage::with_correlation(
+            subject.to_string(),
+            serde_json::json!({"ssn": "123-45-6789"}),
+            &MessageIdentity::root(IdType::Uuid(Uuid::new_v4())),
+        )
+    }
+
+    fn policy() -> CryptoPolicy {
+        let mut policy = CryptoPolicy::new();
+        policy.require(
+            Pattern::new("lending.documents.>").unwrap(),
+            EncryptionRequirement::Required,
+        );
+        policy.require(
+            Pattern::new("lending.documents.identity.>").unwrap(),
+            EncryptionRequirement::Algorithm(EncryptionAlgorithm::ChaCha20Poly1305),
+        );
+        policy.require(
+            Pattern::new("lending.documents.public.>").unwrap(),
+            EncryptionRequirement::None,
+        );
+        policy
+    }
+
+    #[test]
+    fn test_most_specific_requirement_wins() {
+        let policy = policy();
+        let algorithm = |subject: &str| policy.algorithm_for(&Subject::new(subject).unwrap());
+        assert_eq!(
+            algorithm("lending.documents.identity.uploaded"),
+            Some(EncryptionAlgorithm::ChaCha20Poly1305)
+        );
+        assert_eq!(
+            algorithm("lending.documents.income.uploaded"),
+            Some(EncryptionAlgorithm::Aes256Gcm)
+        );
+        assert_eq!(algorithm("lending.documents.public.uploaded"), None);
+        assert_eq!(algorithm("orders.order.placed.v1"), None);
+
+        // Loaded entries are ordered by specificity, whatever the config says
+        let mut config = serde_json::to_value(&policy).unwrap();
+        config["entries"].as_array_mut().unwrap().rotate_right(1);
+        let loaded: CryptoPolicy = serde_json::from_value(config).unwrap();
+        assert_eq!(
+            loaded.iter().collect::<Vec<_>>(),
+            policy.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            loaded.algorithm_for(&Subject::new("lending.documents.identity.uploaded").unwrap()),
+            Some(EncryptionAlgorithm::ChaCha20Poly1305)
+        );
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let policy = policy();
+        let key = EncryptionKey::new("k1", [1; 32]);
+
+        let mut sealed = message("lending.documents.identity.uploaded");
+        assert!(policy.seal(&mut sealed, &key).unwrap());
+        assert_eq!(
+            sealed.headers.get(ENCRYPTION_HEADER),
+            Some("chacha20-poly1305")
+        );
+        assert_eq!(sealed.headers.get(ENCRYPTION_KEY_HEADER), Some("k1"));
+        assert!(key
+            .encrypt(&mut sealed.clone(), EncryptionAlgorithm::Aes256Gcm)
+            .is_err());
+
+        // Wrong key, tampered subject, or plaintext where ciphertext is due
+        let other = EncryptionKey::new("k1", [2; 32]);
+        assert!(matches!(
+            policy.open(&mut sealed.clone(), &other),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+        let mut moved = sealed.clone();
+        moved.subject = "lending.documents.identity.deleted".to_string();
+        assert!(policy.open(&mut moved, &key).is_err());
+        assert!(matches!(
+            policy.open(&mut message("lending.documents.income.uploaded"), &key),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+
+        policy.open(&mut sealed, &key).unwrap();
+        assert!(!sealed.headers.contains(ENCRYPTION_HEADER));
+        let payload: serde_json::Value = sealed.payload_json().unwrap();
+        assert_eq!(payload["ssn"], "123-45-6789");
+
+        let mut public = message("lending.documents.public.uploaded");
+        assert!(!policy.seal(&mut public, &key).unwrap());
+        policy.open(&mut public, &key).unwrap();
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Correlation-aware message deduplication
+//!
+//! At-least-once delivery redelivers messages after timeouts and restarts.
+//! A [`DeduplicationGuard`] remembers the message IDs seen recently in each
+//! correlation, so a consumer can skip a message it has already handled.
+//! Memory is bounded three ways: IDs expire after the window, each
+//! correlation keeps a limited number of IDs, and the least recently active
+//! correlations are dropped once too many are tracked.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cim_subject::deduplication::{
+//!     DeduplicationGuard,
+//!     DuplicateStatus,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//! };
+//! use uuid::Uuid;
+//!
+//! let guard = DeduplicationGuard::new(Duration::from_secs(300));
+//! let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//!
+//! assert_eq!(guard.check_and_record(&identity), DuplicateStatus::New);
+//! assert!(guard.check_and_record(&identity).is_duplicate());
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+    VecDeque,
+};
+use std::sync::Mutex;
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use crate::correlation::{
+    CorrelationId,
+    IdType,
+    MessageIdentity,
+};
+
+/// Whether a message was seen before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateStatus {
+    /// Not seen within the window; it is now recorded
+    New,
+    /// Already seen within the window
+    Duplicate {
+        /// When the message was first recorded
+        first_seen: SystemTime,
+    },
+}
+
+impl DuplicateStatus {
+    /// Check if the message was seen before
+    #[must_use]
+    pub fn is_duplicate(self) -> bool {
+        matches!(self, Self::Duplicate { .. })
+    }
+}
+
+/// Message IDs recently seen in one correlation
+#[derive(Debug, Default)]
+struct Seen {
+    ids: HashMap<IdType, SystemTime>,
+    /// IDs in the order they were recorded, oldest first
+    order: VecDeque<(IdType, SystemTime)>,
+    /// Key of this correlation in [`Correlations::by_activity`]
+    activity: Option<ActivityKey>,
+}
+
+impl Seen {
+    fn expire(&mut self, now: SystemTime, window: Duration) {
+        while let Some((id, seen)) = self.order.front() {
+            if elapsed(*seen, now) < window {
+                break;
+            }
+            self.ids.remove(id);
+            self.order.pop_front();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((id, _)) = self.order.pop_front() {
+            self.ids.remove(&id);
+        }
+    }
+}
+
+/// When a correlation was last active, with a sequence number to keep
+/// keys unique
+type ActivityKey = (SystemTime, u64);
+
+/// Tracked correlations, indexed by last activity so the least recently
+/// active can be found without a scan
+#[derive(Debug, Default)]
+struct Correlations {
+    seen: HashMap<CorrelationId, Seen>,
+    by_activity: BTreeMap<ActivityKey, CorrelationId>,
+    next: u64,
+}
+
+impl Correlations {
+    /// The IDs seen in a correlation, marking it active at `now`
+    fn touch(&mut self, correlation: &CorrelationId, now: SystemTime) -> &mut Seen {
+        let key = (now, self.next);
+        self.next += 1;
+        let seen = self.seen.entry(correlation.clone()).or_default();
+        if let Some(previous) = seen.activity.replace(key) {
+            self.by_activity.remove(&previous);
+        }
+        self.by_activity.insert(key, correlation.clone());
+        seen
+    }
+
+    fn remove(&mut self, correlation: &CorrelationId) {
+        if let Some(key) = self.seen.remove(correlation).and_then(|seen| seen.activity) {
+            self.by_activity.remove(&key);
+        }
+    }
+}
+
+/// Remembers recent message IDs per correlation
+#[derive(Debug)]
+pub struct DeduplicationGuard {
+    window: Duration,
+    max_per_correlation: usize,
+    max_correlations: usize,
+    seen: Mutex<Correlations>,
+}
+
+impl DeduplicationGuard {
+    /// Create a guard remembering IDs for `window`, with at most 1024 IDs
+    /// per correlation and 10,000 correlations
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            max_per_correlation: 1024,
+            max_correlations: 10_000,
+            seen: Mutex::new(Correlations::default()),
+        }
+    }
+
+    /// Keep at most `max` IDs per correlation, forgetting the oldest first
+    #[must_use]
+    pub fn max_per_correlation(mut self, max: usize) -> Self {
+        self.max_per_correlation = max.max(1);
+        self
+    }
+
+    /// Track at most `max` correlations, forgetting the least recently
+    /// active first
+    #[must_use]
+    pub fn max_correlations(mut self, max: usize) -> Self {
+        self.max_correlations = max.max(1);
+        self
+    }
+
+    /// Check whether the message was seen within the window, recording it
+    /// if not
+    pub fn check_and_record(&self, identity: &MessageIdentity) -> DuplicateStatus {
+        self.check_and_record_at(identity, SystemTime::now())
+    }
+
+    /// [`check_and_record`](Self::check_and_record) at `now`
+    ///
+    /// A poisoned guard reports every message as new, so messages are
+    /// handled again rather than dropped.
+    pub fn check_and_record_at(
+        &self,
+        identity: &MessageIdentity,
+        now: SystemTime,
+    ) -> DuplicateStatus {
+        let Ok(mut correlations) = self.seen.lock() else {
+            return DuplicateStatus::New;
+        };
+        if !correlations.seen.contains_key(&identity.correlation_id) {
+            self.make_room(&mut correlations, now);
+        }
+        let seen = correlations.touch(&identity.correlation_id, now);
+        seen.expire(now, self.window);
+        if let Some(&first_seen) = seen.ids.get(&identity.message_id) {
+            return DuplicateStatus::Duplicate { first_seen };
+        }
+        if seen.order.len() >= self.max_per_correlation {
+            seen.evict_oldest();
+        }
+        seen.ids.insert(identity.message_id.clone(), now);
+        seen.order.push_back((identity.message_id.clone(), now));
+        DuplicateStatus::New
+    }
+
+    /// Forget a correlation, e.g. once its saga has completed
+    pub fn forget(&self, correlation: &CorrelationId) {
+        if let Ok(mut correlations) = self.seen.lock() {
+            correlations.remove(correlation);
+        }
+    }
+
+    /// Number of correlations tracked
+    #[must_use]
+    pub fn correlations(&self) -> usize {
+        self.seen
+            .lock()
+            .map_or(0, |correlations| correlations.seen.len())
+    }
+
+    /// Drop expired correlations, then the least recently active ones,
+    /// until a new correlation fits
+    fn make_room(&self, correlations: &mut Correlations, now: SystemTime) {
+        if correlations.seen.len() < self.max_correlations {
+            return;
+        }
+        // Expired correlations are the least recently active, so both kinds
+        // come off the front of the index
+        while let Some((&(active, _), correlation)) = correlations.by_activity.first_key_value() {
+            if correlations.seen.len() < self.max_correlations && elapsed(active, now) < self.window
+            {
+                break;
+            }
+            let correlation = correlation.clone();
+            correlations.remove(&correlation);
+        }
+    }
+}
+
+/// Time from `then` to `now`, or zero if the clock went backwards
+fn elapsed(then: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(then).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn caused(parent: &MessageIdentity) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            parent.correlation_id.clone(),
+            parent.message_id.clone(),
+        )
+    }
+
+    #[test]
+    fn test_window_and_per_correlation_bound() {
+        let start = SystemTime::UNIX_EPOCH;
+        let guard = DeduplicationGuard::new(Duration::from_secs(60)).max_per_correlation(2);
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        assert_eq!(
+            guard.check_and_record_at(&root, start),
+            DuplicateStatus::New
+        );
+        assert_eq!(
+            guard.check_and_record_at(&root, start + Duration::from_secs(30)),
+            DuplicateStatus::Duplicate { first_seen: start }
+        );
+        // Expired after the window
+        let later = start + Duration::from_secs(61);
+        assert_eq!(
+            guard.check_and_record_at(&root, later),
+            DuplicateStatus::New
+        );
+
+        // The oldest ID is forgotten once the correlation is full
+        let (a, b) = (caused(&root), caused(&root));
+        guard.check_and_record_at(&a, later);
+        guard.check_and_record_at(&b, later);
+        assert!(!guard.check_and_record_at(&root, later).is_duplicate());
+        assert!(guard.check_and_record_at(&b, later).is_duplicate());
+    }
+
+    #[test]
+    fn test_correlation_bound() {
+        let now = SystemTime::UNIX_EPOCH;
+        let guard = DeduplicationGuard::new(Duration::from_secs(60)).max_correlations(2);
+        let roots: Vec<MessageIdentity> = (0..3)
+            .map(|_| MessageIdentity::root(IdType::Uuid(Uuid::new_v4())))
+            .collect();
+        for (offset, root) in (0..).zip(&roots) {
+            guard.check_and_record_at(root, now + Duration::from_secs(offset));
+        }
+        assert_eq!(guard.correlations(), 2);
+        // The least recently active correlation was dropped
+        let at = now + Duration::from_secs(5);
+        assert!(!guard.check_and_record_at(&roots[0], at).is_duplicate());
+        assert!(guard.check_and_record_at(&roots[2], at).is_duplicate());
+
+        guard.forget(&roots[2].correlation_id);
+        assert_eq!(guard.correlations(), 1);
+    }
+
+    #[test]
+    fn test_activity_order() {
+        let now = SystemTime::UNIX_EPOCH;
+        let guard = DeduplicationGuard::new(Duration::from_secs(60)).max_correlations(2);
+        let roots: Vec<MessageIdentity> = (0..4)
+            .map(|_| MessageIdentity::root(IdType::Uuid(Uuid::new_v4())))
+            .collect();
+        guard.check_and_record_at(&roots[0], now);
+        guard.check_and_record_at(&roots[1], now);
+        // Activity on the first correlation makes the second the oldest
+        guard.check_and_record_at(&caused(&roots[0]), now + Duration::from_secs(1));
+        guard.check_and_record_at(&roots[2], now + Duration::from_secs(2));
+        assert!(guard
+            .check_and_record_at(&roots[0], now + Duration::from_secs(3))
+            .is_duplicate());
+        assert_eq!(guard.correlations(), 2);
+
+        // Once both have expired, a new correlation replaces them
+        let later = now + Duration::from_secs(120);
+        guard.check_and_record_at(&roots[3], later);
+        assert_eq!(guard.correlations(), 1);
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Deprecation of subject families with replacements and sunset dates
+//!
+//! A [`DeprecationRegistry`] maps patterns to [`Deprecation`] metadata. The
+//! most specific matching pattern decides, so a family can be deprecated as
+//! a whole while one event type gets its own replacement. During a staged
+//! migration, publishers check subjects before sending, and bridges run the
+//! registry's [`translator`](DeprecationRegistry::translator) to rewrite
+//! deprecated subjects onto their replacements while reporting each one.
+//!
+//! ```
+//! use cim_subject::deprecation::{
+//!     Deprecation,
+//!     DeprecationRegistry,
+//!     DeprecationSeverity,
+//! };
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let mut registry = DeprecationRegistry::new();
+//! registry.deprecate(
+//!     Pattern::new("orders.*.*.v1")?,
+//!     Deprecation::new(DeprecationSeverity::Warning)
+//!         .replaced_by("orders.{aggregate}.{event}.v2")
+//!         .reason("v1 payloads lack currency codes"),
+//! );
+//!
+//! let subject = Subject::new("orders.order.created.v1")?;
+//! let notice = registry.check(&subject).unwrap();
+//! assert_eq!(
+//!     notice.replacement.unwrap().as_str(),
+//!     "orders.order.created.v2"
+//! );
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::Result;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::translator::{
+    render_template,
+    TranslationRule,
+    Translator,
+};
+
+/// How urgently publishers should move off a deprecated subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DeprecationSeverity {
+    /// Still fully supported; migrate when convenient
+    Info,
+    /// Migrate before the sunset date
+    Warning,
+    /// Removal is imminent or has happened
+    Critical,
+}
+
+/// Deprecation metadata for a subject family
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// How urgent migration is
+    pub severity: DeprecationSeverity,
+    /// Template for the replacement subject, using `{context}`,
+    /// `{aggregate}`, `{event}` and `{version}`
+    pub replacement: Option<String>,
+    /// When the subjects stop being supported
+    pub sunset: Option<SystemTime>,
+    /// Why the subjects are deprecated
+    pub reason: Option<String>,
+}
+
+impl Deprecation {
+    /// Create deprecation metadata with no replacement or sunset
+    #[must_use]
+    pub fn new(severity: DeprecationSeverity) -> Self {
+        Self {
+            severity,
+            replacement: None,
+            sunset: None,
+            reason: None,
+        }
+    }
+
+    /// Set the replacement subject template
+    #[must_use]
+    pub fn replaced_by(mut self, template: impl Into<String>) -> Self {
+        self.replacement = Some(template.into());
+        self
+    }
+
+    /// Set the sunset time
+    #[must_use]
+    pub fn sunset(mut self, sunset: SystemTime) -> Self {
+        self.sunset = Some(sunset);
+        self
+    }
+
+    /// Set the reason
+    #[must_use]
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// A deprecated subject and what to do about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeprecationNotice {
+    /// The deprecated subject
+    pub subject: Subject,
+    /// The registered pattern that matched
+    pub pattern: Pattern,
+    /// How urgent migration is
+    pub severity: DeprecationSeverity,
+    /// The subject to publish instead, if there is one
+    pub replacement: Option<Subject>,
+    /// When the subject stops being supported
+    pub sunset: Option<SystemTime>,
+    /// Whether the sunset has passed
+    pub past_sunset: bool,
+    /// Why the subject is deprecated
+    pub reason: Option<String>,
+}
+
+impl Display for DeprecationNotice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is deprecated ({:?}, matched '{}')",
+            self.subject, self.severity, self.pattern
+        )?;
+        if let Some(replacement) = &self.replacement {
+            write!(f, "; use '{replacement}'")?;
+        }
+        if self.past_sunset {
+            write!(f, "; past its sunset")?;
+        }
+        if let Some(reason) = &self.reason {
+            write!(f, ": {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Deprecated subject families, most specific pattern first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeprecationRegistry {
+    entries: Vec<(Pattern, Deprecation)>,
+}
+
+impl DeprecationRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deprecate the subjects matching a pattern, replacing any earlier
+    /// entry for the same pattern
+    pub fn deprecate(&mut self, pattern: Pattern, deprecation: Deprecation) {
+        self.entries.retain(|(existing, _)| *existing != pattern);
+        self.entries.push((pattern, deprecation));
+        // Stable, so equally specific patterns keep registration order
+        self.entries
+            .sort_by_key(|(pattern, _)| pattern.specificity_key());
+    }
+
+    /// Remove the entry for a pattern, returning its metadata
+    pub fn undeprecate(&mut self, pattern: &Pattern) -> Option<Deprecation> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(existing, _)| existing == pattern)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Registered patterns and their metadata, most specific first
+    pub fn iter(&self) -> impl Iterator<Item = (&Pattern, &Deprecation)> {
+        self.entries
+            .iter()
+            .map(|(pattern, deprecation)| (pattern, deprecation))
+    }
+
+    /// Check whether a subject is deprecated
+    #[must_use]
+    pub fn check(&self, subject: &Subject) -> Option<DeprecationNotice> {
+        self.check_at(subject, SystemTime::now())
+    }
+
+    /// Check whether a subject is deprecated as of `now`
+    ///
+    /// A replacement template that does not produce a valid subject is
+    /// reported as no replacement.
+    #[must_use]
+    pub fn check_at(&self, subject: &Subject, now: SystemTime) -> Option<DeprecationNotice> {
+        let (pattern, deprecation) = self
+            .entries
+            .iter()
+            .find(|(pattern, _)| pattern.matches(subject))?;
+        Some(DeprecationNotice {
+            subject: subject.clone(),
+            pattern: pattern.clone(),
+            severity: deprecation.severity,
+            replacement: deprecation
+                .replacement
+                .as_deref()
+                .and_then(|template| render_template(template, subject).ok()),
+            sunset: deprecation.sunset,
+            past_sunset: deprecation.sunset.is_some_and(|sunset| sunset <= now),
+            reason: deprecation.reason.clone(),
+        })
+    }
+
+    /// A translator rewriting deprecated subjects onto their replacements
+    ///
+    /// `on_notice` receives every deprecated subject translated, including
+    /// those without a replacement, which pass through unchanged. With the
+    /// `tracing` feature each notice is also logged as a warning.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: the catch-all pattern `>` is valid
+    #[must_use]
+    pub fn translator<F>(&self, on_notice: F) -> Translator
+    where F: Fn(&DeprecationNotice) + Send + Sync + 'static {
+        let registry = self.clone();
+        let rule = TranslationRule::new(
+            "deprecations",
+            Pattern::new(">").expect("'>' is a valid pattern"),
+            Arc::new(move |subject: &Subject| {
+                let Some(notice) = registry.check(subject) else {
+                    return Ok(subject.clone());
+                };
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%notice, "translating deprecated subject");
+                on_notice(&notice);
+                Ok(notice.replacement.unwrap_or_else(|| subject.clone()))
+            }),
+        );
+
+        let translator = Translator::new();
+        translator.register_rule(rule.name.clone(), rule);
+        translator
+    }
+
+    /// Check that every replacement template produces a valid subject for
+    /// an example subject of its pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns the first template error
+    pub fn validate_templates(&self, examples: &[Subject]) -> Result<()> {
+        for subject in examples {
+            if let Some((_, deprecation)) = self
+                .entries
+                .iter()
+                .find(|(pattern, _)| pattern.matches(subject))
+            {
+                if let Some(template) = &deprecation.replacement {
+                    render_template(template, subject)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn registry() -> DeprecationRegistry {
+        let mut registry = DeprecationRegistry::new();
+        registry.deprecate(
+            Pattern::new("orders.>").unwrap(),
+            Deprecation::new(DeprecationSeverity::Info).reason("moving to sales"),
+        );
+        registry.deprecate(
+            Pattern::new("orders.*.*.v1").unwrap(),
+            Deprecation::new(DeprecationSeverity::Critical)
+                .replaced_by("sales.{aggregate}.{event}.v2")
+                .sunset(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000)),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_most_specific_deprecation_wins() {
+        let registry = registry();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000);
+
+        let v1 = Subject::new("orders.order.created.v1").unwrap();
+        let notice = registry.check_at(&v1, now).unwrap();
+        assert_eq!(notice.severity, DeprecationSeverity::Critical);
+        assert_eq!(
+            notice.replacement.as_ref().unwrap().as_str(),
+            "sales.order.created.v2"
+        );
+        assert!(notice.past_sunset);
+        assert!(notice.to_string().contains("use 'sales.order.created.v2'"));
+
+        let v2 = Subject::new("orders.order.created.v2").unwrap();
+        let notice = registry.check_at(&v2, now).unwrap();
+        assert_eq!(notice.severity, DeprecationSeverity::Info);
+        assert!(notice.replacement.is_none());
+        assert!(!notice.past_sunset);
+
+        assert!(registry
+            .check(&Subject::new("sales.order.created.v2").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn test_translator_rewrites_and_reports() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let translator = registry().translator(move |notice| {
+            sink.lock().unwrap().push(notice.subject.to_string());
+        });
+
+        let translate = |s: &str| {
+            translator
+                .translate(&Subject::new(s).unwrap())
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            translate("orders.order.created.v1"),
+            "sales.order.created.v2"
+        );
+        assert_eq!(
+            translate("orders.order.created.v2"),
+            "orders.order.created.v2"
+        );
+        assert_eq!(
+            translate("sales.order.created.v2"),
+            "sales.order.created.v2"
+        );
+        assert_eq!(*seen.lock().unwrap(), vec![
+            "orders.order.created.v1",
+            "orders.order.created.v2"
+        ]);
+
+        let json = serde_json::to_string(&registry()).unwrap();
+        let restored: DeprecationRegistry = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.iter().count(), 2);
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject catalog discovery from live NATS traffic
+//!
+//! Available with the `nats` feature. Adopting subject algebra on an
+//! existing system starts with knowing which subjects are in use.
+//! [`SubjectDiscovery`] subscribes to `>` for a sampling period, counts the
+//! subjects it sees, and clusters subjects that differ in a single token
+//! into a `*` template once enough variants appear. The resulting
+//! [`DiscoveredCatalog`] can seed a [`SubjectHierarchy`], a registry or a
+//! starting [`Permissions`] set.
+//!
+//! ```
+//! use cim_subject::discovery::SubjectDiscovery;
+//!
+//! let mut discovery = SubjectDiscovery::new().cluster_threshold(3);
+//! for event in ["placed", "shipped", "cancelled", "placed"] {
+//!     discovery.observe(&format!("orders.order.{event}.v1"));
+//! }
+//! discovery.observe("billing.invoice.issued.v1");
+//! discovery.observe("_INBOX.abc123");
+//!
+//! let catalog = discovery.catalog();
+//! let templates: Vec<(&str, u64)> = catalog
+//!     .entries()
+//!     .iter()
+//!     .map(|entry| (entry.template.as_str(), entry.count))
+//!     .collect();
+//! assert_eq!(templates, [
+//!     ("billing.invoice.issued.v1", 1),
+//!     ("orders.order.*.v1", 4),
+//! ]);
+//! assert_eq!(catalog.unparsed(), 1);
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+    HashSet,
+};
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::hierarchy::SubjectHierarchy;
+use crate::pattern::Pattern;
+use crate::permissions::{
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+};
+use crate::subject::Subject;
+
+/// Token positions tried for clustering, most variable first: event type,
+/// aggregate, then version. Contexts are never merged.
+const CLUSTER_POSITIONS: [usize; 3] = [2, 1, 3];
+
+/// Samples subjects and clusters them into templates
+#[derive(Debug, Clone)]
+pub struct SubjectDiscovery {
+    cluster_threshold: usize,
+    max_messages: Option<usize>,
+    counts: HashMap<Subject, u64>,
+    unparsed: u64,
+}
+
+impl Default for SubjectDiscovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubjectDiscovery {
+    /// Discovery that merges subjects once five variants of a token appear
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cluster_threshold: 5,
+            max_messages: None,
+            counts: HashMap::new(),
+            unparsed: 0,
+        }
+    }
+
+    /// Merge subjects differing in one token once `threshold` distinct
+    /// values of it appear; a threshold of zero or one never merges
+    #[must_use]
+    pub fn cluster_threshold(mut self, threshold: usize) -> Self {
+        self.cluster_threshold = threshold;
+        self
+    }
+
+    /// Stop sampling after `max` messages, even if the period has not
+    /// elapsed
+    #[must_use]
+    pub fn max_messages(mut self, max: usize) -> Self {
+        self.max_messages = Some(max);
+        self
+    }
+
+    /// Count one observed subject; subjects that do not parse, such as
+    /// `_INBOX` replies, are only counted as unparsed
+    pub fn observe(&mut self, subject: &str) {
+        match Subject::new(subject) {
+            Ok(subject) => *self.counts.entry(subject).or_default() += 1,
+            Err(_) => self.unparsed += 1,
+        }
+    }
+
+    /// Subscribe to `>` and observe every message for `period`
+    ///
+    /// Returns the number of messages observed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subscription fails
+    pub async fn sample(&mut self, client: &async_nats::Client, period: Duration) -> Result<usize> {
+        let mut subscriber = client
+            .subscribe(">")
+            .await
+            .map_err(|e| SubjectError::validation_error(format!("Cannot subscribe to '>': {e}")))?;
+        let deadline = tokio::time::Instant::now() + period;
+        let mut observed = 0;
+        while self.max_messages.map_or(true, |max| observed < max) {
+            match tokio::time::timeout_at(deadline, subscriber.next()).await {
+                Ok(Some(message)) => {
+                    self.observe(message.subject.as_str());
+                    observed += 1;
+                },
+                Ok(None) | Err(_) => break,
+            }
+        }
+        // The subscription is dropped either way; a failed unsubscribe only
+        // means the connection is already gone
+        let _ = subscriber.unsubscribe().await;
+        Ok(observed)
+    }
+
+    /// Cluster the subjects observed so far into a catalog
+    #[must_use]
+    pub fn catalog(&self) -> DiscoveredCatalog {
+        let mut entries: Vec<CatalogEntry> = self
+            .counts
+            .iter()
+            .map(|(subject, &count)| CatalogEntry {
+                template: Pattern::from_valid_tokens(
+                    &subject.as_str().split('.').collect::<Vec<_>>(),
+                ),
+                count,
+                subjects: vec![(subject.clone(), count)],
+            })
+            .collect();
+        if self.cluster_threshold > 1 {
+            for position in CLUSTER_POSITIONS {
+                entries = cluster(entries, position, self.cluster_threshold);
+            }
+        }
+        entries.sort_by(|a, b| a.template.as_str().cmp(b.template.as_str()));
+        DiscoveredCatalog {
+            entries,
+            unparsed: self.unparsed,
+        }
+    }
+}
+
+/// Merge entries that agree on every token but `position` into one
+/// template when at least `threshold` of them do
+fn cluster(entries: Vec<CatalogEntry>, position: usize, threshold: usize) -> Vec<CatalogEntry> {
+    let mut groups: BTreeMap<Vec<String>, Vec<CatalogEntry>> = BTreeMap::new();
+    for entry in entries {
+        let mut key: Vec<String> = entry
+            .template
+            .as_str()
+            .split('.')
+            .map(str::to_string)
+            .collect();
+        key[position] = "*".to_string();
+        groups.entry(key).or_default().push(entry);
+    }
+
+    let mut clustered = Vec::new();
+    for (key, group) in groups {
+        let variants: BTreeSet<&str> = group
+            .iter()
+            .map(|entry| {
+                entry
+                    .template
+                    .as_str()
+                    .split('.')
+                    .nth(position)
+                    .unwrap_or_default()
+            })
+            .collect();
+        if variants.len() < threshold {
+            clustered.extend(group);
+            continue;
+        }
+        let mut subjects: Vec<(Subject, u64)> =
+            group.into_iter().flat_map(|entry| entry.subjects).collect();
+        subjects.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        clustered.push(CatalogEntry {
+            template: Pattern::from_valid_tokens(&key),
+            count: subjects.iter().map(|(_, count)| count).sum(),
+            subjects,
+        });
+    }
+    clustered
+}
+
+/// One template in a [`DiscoveredCatalog`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    /// The subject, or a `*` template covering several subjects
+    pub template: Pattern,
+    /// Messages observed under the template
+    pub count: u64,
+    /// The concrete subjects behind the template, with their counts
+    pub subjects: Vec<(Subject, u64)>,
+}
+
+/// Subjects observed by a [`SubjectDiscovery`], clustered into templates
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredCatalog {
+    entries: Vec<CatalogEntry>,
+    unparsed: u64,
+}
+
+impl DiscoveredCatalog {
+    /// Entries sorted by template
+    #[must_use]
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Messages whose subject was not a valid subject
+    #[must_use]
+    pub fn unparsed(&self) -> u64 {
+        self.unparsed
+    }
+
+    /// Total messages observed under a template
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    /// The templates, as patterns
+    #[must_use]
+    pub fn patterns(&self) -> Vec<Pattern> {
+        self.entries
+            .iter()
+            .map(|entry| entry.template.clone())
+            .collect()
+    }
+
+    /// Every concrete subject observed, as a hierarchy
+    #[must_use]
+    pub fn hierarchy(&self) -> SubjectHierarchy {
+        let mut hierarchy = SubjectHierarchy::new();
+        for (subject, _) in self.entries.iter().flat_map(|entry| &entry.subjects) {
+            hierarchy.insert(subject.clone());
+        }
+        hierarchy
+    }
+
+    /// A deny-by-default permission set allowing `operations` on every
+    /// template
+    #[must_use]
+    pub fn permissions(&self, operations: &[Operation]) -> Permissions {
+        let operations: HashSet<Operation> = operations.iter().copied().collect();
+        let mut permissions = Permissions::new(Policy::Deny);
+        for entry in &self.entries {
+            permissions.add_rule(PermissionRule::allow(
+                entry.template.clone(),
+                operations.clone(),
+            ));
+        }
+        permissions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clustering() {
+        let mut discovery = SubjectDiscovery::new().cluster_threshold(2);
+        discovery.observe("orders.order.placed.v1");
+        discovery.observe("orders.order.shipped.v1");
+        discovery.observe("orders.order.placed.v2");
+        discovery.observe("orders.order.shipped.v2");
+        discovery.observe("orders.cart.emptied.v1");
+
+        // Event types merge first, then the resulting templates by version
+        let catalog = discovery.catalog();
+        let patterns = catalog.patterns();
+        let templates: Vec<&str> = patterns.iter().map(Pattern::as_str).collect();
+        assert_eq!(templates, ["orders.cart.emptied.v1", "orders.order.*.*"]);
+        assert_eq!(catalog.entries()[1].subjects.len(), 4);
+        assert_eq!(catalog.total(), 5);
+        assert_eq!(catalog.hierarchy().len(), 5);
+
+        let permissions = catalog.permissions(&[Operation::Subscribe]);
+        let shipped = Subject::new("orders.order.shipped.v3").unwrap();
+        assert!(permissions.is_allowed(&shipped, Operation::Subscribe));
+        let other = Subject::new("orders.cart.filled.v1").unwrap();
+        assert!(!permissions.is_allowed(&other, Operation::Subscribe));
+    }
+
+    #[test]
+    fn test_no_clustering_below_threshold() {
+        let mut discovery = SubjectDiscovery::new();
+        discovery.observe("orders.order.placed.v1");
+        discovery.observe("orders.order.shipped.v1");
+        let catalog = discovery.catalog();
+        assert_eq!(catalog.entries().len(), 2);
+        assert_eq!(catalog.unparsed(), 0);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Messages bundled with their subject and identity
+//!
+//! An [`Envelope`] carries a typed payload together with the [`Subject`] it
+//! is published on, its [`MessageIdentity`] and any extra headers, so the
+//! three travel and serialize as one value. Follow-up messages are built
+//! from the envelope that caused them, which keeps correlation and
+//! causation right without touching identities directly.
+//!
+//! ```
+//! use cim_subject::correlation::kinds::Command;
+//! use cim_subject::envelope::Envelope;
+//! use cim_subject::Subject;
+//! use uuid::Uuid;
+//!
+//! let request = Envelope::root::<Command>(
+//!     Subject::new("orders.order.place.v1")?,
+//!     Uuid::new_v4(),
+//!     "order 42".to_string(),
+//! )
+//! .with_header("Tenant", "acme");
+//!
+//! let reply = request.caused_reply(true);
+//! assert_eq!(
+//!     reply.identity.correlation_id,
+//!     request.identity.correlation_id
+//! );
+//! assert_eq!(reply.identity.causation_id.0, request.identity.message_id);
+//! assert_eq!(request.header("Tenant"), Some("acme"));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use cim_ipld::Cid;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use uuid::Uuid;
+
+use crate::correlation::{
+    kinds,
+    IdType,
+    Kind,
+    MessageFactory,
+    MessageIdentity,
+};
+use crate::header_convention::HeaderConvention;
+use crate::subject::Subject;
+
+/// A payload with its subject, identity and headers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    /// The subject the message is published on
+    pub subject: Subject,
+    /// The message's identity
+    pub identity: MessageIdentity,
+    /// Headers beyond the identity, in insertion order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<(String, String)>,
+    /// The message body
+    pub payload: T,
+}
+
+impl<T> Envelope<T> {
+    /// Wrap a payload with an existing identity
+    #[must_use]
+    pub fn new(subject: Subject, identity: MessageIdentity, payload: T) -> Self {
+        Self {
+            subject,
+            identity,
+            headers: Vec::new(),
+            payload,
+        }
+    }
+
+    /// Wrap a payload as a message of kind `K` starting a new correlation
+    /// chain
+    #[must_use]
+    pub fn root<K: Kind>(subject: Subject, id: K::Id, payload: T) -> Self {
+        Self::new(subject, MessageFactory::root::<K>(id), payload)
+    }
+
+    /// Wrap a payload as a message of kind `K` caused by this one, on
+    /// `subject`
+    #[must_use]
+    pub fn caused<K: Kind, U>(&self, subject: Subject, id: K::Id, payload: U) -> Envelope<U> {
+        Envelope::new(
+            subject,
+            MessageFactory::caused::<K>(id, &self.identity),
+            payload,
+        )
+    }
+
+    /// A reply to this message with a fresh UUID, on the same subject
+    ///
+    /// Replies have no [`MessageKind`](crate::correlation::MessageKind);
+    /// use [`with_subject`](Envelope::with_subject) to readdress one.
+    #[must_use]
+    pub fn caused_reply<U>(&self, payload: U) -> Envelope<U> {
+        let identity = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            self.identity.correlation_id.clone(),
+            self.identity.message_id.clone(),
+        );
+        Envelope::new(self.subject.clone(), identity, payload)
+    }
+
+    /// An event caused by this message, on the same subject
+    #[must_use]
+    pub fn caused_event<U>(&self, cid: Cid, payload: U) -> Envelope<U> {
+        self.caused::<kinds::Event, U>(self.subject.clone(), cid, payload)
+    }
+
+    /// Readdress the message
+    #[must_use]
+    pub fn with_subject(mut self, subject: Subject) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    /// Add a header
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The first value of a header, compared case-insensitively
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replace the payload, keeping subject, identity and headers
+    #[must_use]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Envelope<U> {
+        Envelope {
+            subject: self.subject,
+            identity: self.identity,
+            headers: self.headers,
+            payload: f(self.payload),
+        }
+    }
+
+    /// Identity headers under `convention` followed by the extra headers,
+    /// ready for a transport
+    #[must_use]
+    pub fn to_headers(&self, convention: &HeaderConvention) -> Vec<(String, String)> {
+        let mut headers = convention.to_headers(&self.identity);
+        headers.extend(self.headers.iter().cloned());
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correlation::MessageKind;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct OrderPlaced {
+        order_id: String,
+        total: u64,
+    }
+
+    fn cid(seed: u8) -> Cid {
+        let mut bytes = vec![0x01, 0x55, 0x12, 0x20];
+        bytes.extend([seed; 32]);
+        Cid::try_from(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_caused_envelopes() {
+        let command = Envelope::root::<kinds::Command>(
+            Subject::new("orders.order.place.v1").unwrap(),
+            Uuid::new_v4(),
+            "o-1",
+        );
+        assert!(command.identity.is_root());
+
+        let event = command
+            .caused_event(cid(1), OrderPlaced {
+                order_id: "o-1".to_string(),
+                total: 10,
+            })
+            .with_subject(Subject::new("orders.order.placed.v1").unwrap());
+        assert_eq!(event.identity.kind, Some(MessageKind::Event));
+        assert_eq!(event.identity.causation_id.0, command.identity.message_id);
+        assert_eq!(event.subject.as_str(), "orders.order.placed.v1");
+
+        let reply = command.caused_reply(());
+        assert_eq!(reply.identity.kind, None);
+        assert_eq!(
+            reply.identity.correlation_id,
+            command.identity.correlation_id
+        );
+        assert_eq!(reply.subject, command.subject);
+
+        let total = event.clone().map(|placed| placed.total);
+        assert_eq!(total.payload, 10);
+        assert_eq!(total.identity, event.identity);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let envelope = Envelope::root::<kinds::Event>(
+            Subject::new("orders.order.placed.v1").unwrap(),
+            cid(2),
+            OrderPlaced {
+                order_id: "o-2".to_string(),
+                total: 5,
+            },
+        )
+        .with_header("Tenant", "acme");
+
+        let json = serde_json::to_string(&envelope).unwrap();
+        let restored: Envelope<OrderPlaced> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, envelope);
+        assert_eq!(restored.header("tenant"), Some("acme"));
+
+        let headers = envelope.to_headers(&HeaderConvention::default());
+        assert_eq!(headers.len(), 4);
+        assert_eq!(headers[3], ("Tenant".to_string(), "acme".to_string()));
+    }
+}
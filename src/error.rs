@@ -1,11 +1,42 @@
 // Copyright 2025 Cowboy AI, LLC.
 
 //! Error types for subject operations
+//!
+//! Every [`SubjectError`] has a stable, machine-readable [`ErrorCode`].
+//! Subjects and patterns that fail to parse produce
+//! [`SubjectError::InvalidToken`], which also carries the offending input
+//! and the byte span of the problem, e.g. "character '$' at position 12".
+//! With the `miette` feature, `SubjectError` implements
+//! `miette::Diagnostic`, so CLI tools can underline the span.
+//!
+//! ```
+//! use cim_subject::error::ErrorCode;
+//! use cim_subject::Subject;
+//!
+//! let err = Subject::new("orders.order.pl$ce.v1").unwrap_err();
+//! assert_eq!(err.code(), ErrorCode::InvalidFormat);
+//! assert_eq!(err.code().as_str(), "cim_subject::invalid_format");
+//! assert_eq!(err.span(), Some(15..16));
+//! assert_eq!(
+//!     err.to_string(),
+//!     "Invalid subject format: character '$' at position 15 in 'orders.order.pl$ce.v1'"
+//! );
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt::{
+    self,
+    Display,
+};
+use core::ops::Range;
 
 use thiserror::Error;
 
+use crate::limits::LimitExceeded;
+
 /// Result type alias for subject operations
-pub type Result<T> = std::result::Result<T, SubjectError>;
+pub type Result<T> = core::result::Result<T, SubjectError>;
 
 /// Errors that can occur during subject operations
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -41,6 +72,103 @@ pub enum SubjectError {
     /// Not found
     #[error("Not found: {0}")]
     NotFound(String),
+
+    /// A subject or pattern token that failed to parse, with its location
+    #[error("{0}")]
+    InvalidToken(Box<TokenError>),
+
+    /// A subject or pattern over its size limits
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(LimitExceeded),
+}
+
+/// Machine-readable category of a [`SubjectError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// The input is not a valid subject
+    InvalidFormat,
+    /// The input is not a valid pattern
+    InvalidPattern,
+    /// Input in another format failed to parse
+    ParseError,
+    /// An operation was not permitted
+    PermissionDenied,
+    /// A subject could not be translated
+    TranslationError,
+    /// Subjects could not be composed
+    CompositionError,
+    /// A value failed validation
+    ValidationError,
+    /// Something looked up does not exist
+    NotFound,
+    /// A subject or pattern is over its size limits
+    LimitExceeded,
+}
+
+impl ErrorCode {
+    /// The code as a stable string, e.g. `cim_subject::invalid_format`
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidFormat => "cim_subject::invalid_format",
+            Self::InvalidPattern => "cim_subject::invalid_pattern",
+            Self::ParseError => "cim_subject::parse_error",
+            Self::PermissionDenied => "cim_subject::permission_denied",
+            Self::TranslationError => "cim_subject::translation_error",
+            Self::CompositionError => "cim_subject::composition_error",
+            Self::ValidationError => "cim_subject::validation_error",
+            Self::NotFound => "cim_subject::not_found",
+            Self::LimitExceeded => "cim_subject::limit_exceeded",
+        }
+    }
+
+    /// The prefix of the error's message
+    fn title(self) -> &'static str {
+        match self {
+            Self::InvalidFormat => "Invalid subject format",
+            Self::InvalidPattern => "Invalid pattern",
+            Self::ParseError => "Parse error",
+            Self::PermissionDenied => "Permission denied",
+            Self::TranslationError => "Translation error",
+            Self::CompositionError => "Composition error",
+            Self::ValidationError => "Validation error",
+            Self::NotFound => "Not found",
+            Self::LimitExceeded => "Limit exceeded",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem at a specific place in a subject or pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenError {
+    /// [`ErrorCode::InvalidFormat`] for subjects,
+    /// [`ErrorCode::InvalidPattern`] for patterns
+    pub code: ErrorCode,
+    /// The whole input that failed to parse
+    pub input: String,
+    /// Byte range of the problem within the input
+    pub span: Range<usize>,
+    /// What is wrong, e.g. `character '$'`
+    pub reason: String,
+}
+
+impl Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at position {} in '{}'",
+            self.code.title(),
+            self.reason,
+            self.span.start,
+            self.input
+        )
+    }
 }
 
 impl SubjectError {
@@ -83,6 +211,79 @@ impl SubjectError {
     pub fn not_found(msg: impl Into<String>) -> Self {
         Self::NotFound(msg.into())
     }
+
+    /// Create an error locating a problem within a subject or pattern
+    pub fn invalid_token(
+        code: ErrorCode,
+        input: impl Into<String>,
+        span: Range<usize>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self::InvalidToken(Box::new(TokenError {
+            code,
+            input: input.into(),
+            span,
+            reason: reason.into(),
+        }))
+    }
+
+    /// The error's machine-readable code
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidFormat(_) => ErrorCode::InvalidFormat,
+            Self::InvalidPattern(_) => ErrorCode::InvalidPattern,
+            Self::ParseError(_) => ErrorCode::ParseError,
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Self::TranslationError(_) => ErrorCode::TranslationError,
+            Self::CompositionError(_) => ErrorCode::CompositionError,
+            Self::ValidationError(_) => ErrorCode::ValidationError,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::InvalidToken(token) => token.code,
+            Self::LimitExceeded(_) => ErrorCode::LimitExceeded,
+        }
+    }
+
+    /// The input that failed to parse, for errors that locate a problem
+    #[must_use]
+    pub fn input(&self) -> Option<&str> {
+        match self {
+            Self::InvalidToken(token) => Some(&token.input),
+            _ => None,
+        }
+    }
+
+    /// Byte range of the problem within [`input`](Self::input)
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::InvalidToken(token) => Some(token.span.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for SubjectError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(SubjectError::code(self)))
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        match self {
+            Self::InvalidToken(token) => Some(&token.input),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        match self {
+            Self::InvalidToken(token) => Some(Box::new(core::iter::once(
+                miette::LabeledSpan::new_with_span(Some(token.reason.clone()), token.span.clone()),
+            ))),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +359,47 @@ mod tests {
         assert_eq!(err1, err2);
     }
 
+    #[test]
+    fn test_codes_and_spans() {
+        let err = SubjectError::invalid_token(
+            ErrorCode::InvalidPattern,
+            "a.>.b",
+            2..3,
+            "'>' before the last token",
+        );
+        assert_eq!(err.code(), ErrorCode::InvalidPattern);
+        assert_eq!(err.input(), Some("a.>.b"));
+        assert_eq!(err.span(), Some(2..3));
+        assert_eq!(
+            err.to_string(),
+            "Invalid pattern: '>' before the last token at position 2 in 'a.>.b'"
+        );
+
+        let err = SubjectError::not_found("route");
+        assert_eq!(err.code().to_string(), "cim_subject::not_found");
+        assert_eq!(err.span(), None);
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn test_diagnostic() {
+        use miette::Diagnostic;
+
+        let err = SubjectError::invalid_token(
+            ErrorCode::InvalidFormat,
+            "a.b$.c.d",
+            3..4,
+            "character '$'",
+        );
+        assert_eq!(
+            Diagnostic::code(&err).unwrap().to_string(),
+            "cim_subject::invalid_format"
+        );
+        let label = err.labels().unwrap().next().unwrap();
+        assert_eq!((label.offset(), label.len()), (3, 1));
+        assert!(err.source_code().is_some());
+    }
+
     #[test]
     fn test_result_type_alias() {
         fn test_function() -> Result<String> {
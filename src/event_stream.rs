@@ -0,0 +1,245 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Event-sourcing stream keys
+//!
+//! An event store appends each event to a stream chosen from its subject.
+//! [`event_stream_key`] derives the stream key for a [`StreamKeyStrategy`],
+//! and a [`StreamLayout`] checks that a set of `JetStream` streams, each with
+//! a subject filter, keeps every aggregate's events in one stream so they
+//! can be replayed in order.
+//!
+//! ```
+//! use cim_subject::event_stream::{
+//!     event_stream_key,
+//!     StreamKeyStrategy,
+//!     StreamLayout,
+//! };
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let placed = Subject::new("orders.order.placed.v1")?;
+//! let key = event_stream_key(&placed, StreamKeyStrategy::PerAggregate, None)?;
+//! assert_eq!(key, "orders.order");
+//!
+//! let layout = StreamLayout::new(StreamKeyStrategy::PerAggregate)
+//!     .stream("ORDERS", Pattern::new("orders.order.>")?)
+//!     .stream("CARTS", Pattern::new("orders.cart.>")?);
+//! layout.validate(std::slice::from_ref(&placed))?;
+//! assert_eq!(layout.stream_for(&placed), Some("ORDERS"));
+//!
+//! let split = StreamLayout::new(StreamKeyStrategy::PerAggregate)
+//!     .stream("PLACED", Pattern::new("orders.*.placed.>")?)
+//!     .stream("ORDERS", Pattern::new("orders.>")?);
+//! assert!(split.validate(&[placed]).is_err());
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use crate::correlation::CorrelationId;
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Which events share a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamKeyStrategy {
+    /// One stream per context and aggregate, e.g. `orders.order`
+    PerAggregate,
+    /// One stream per context, e.g. `orders`
+    PerContext,
+    /// One stream per correlation, spanning contexts
+    PerCorrelation,
+}
+
+impl StreamKeyStrategy {
+    /// Whether every aggregate's events land in a single stream, so
+    /// replaying the stream yields them in order
+    ///
+    /// A correlation covers only part of an aggregate's history, so
+    /// [`PerCorrelation`](Self::PerCorrelation) does not.
+    #[must_use]
+    pub fn preserves_aggregate_order(self) -> bool {
+        !matches!(self, Self::PerCorrelation)
+    }
+}
+
+impl Display for StreamKeyStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PerAggregate => write!(f, "per-aggregate"),
+            Self::PerContext => write!(f, "per-context"),
+            Self::PerCorrelation => write!(f, "per-correlation"),
+        }
+    }
+}
+
+/// The key of the stream an event with `subject` belongs to
+///
+/// # Errors
+///
+/// Returns a validation error if `strategy` is
+/// [`PerCorrelation`](StreamKeyStrategy::PerCorrelation) and `correlation`
+/// is `None`
+pub fn event_stream_key(
+    subject: &Subject,
+    strategy: StreamKeyStrategy,
+    correlation: Option<&CorrelationId>,
+) -> Result<String> {
+    match strategy {
+        StreamKeyStrategy::PerAggregate => {
+            Ok(format!("{}.{}", subject.context(), subject.aggregate()))
+        },
+        StreamKeyStrategy::PerContext => Ok(subject.context().to_string()),
+        StreamKeyStrategy::PerCorrelation => correlation
+            .map(|correlation| correlation.0.to_string())
+            .ok_or_else(|| {
+                SubjectError::validation_error(format!(
+                    "A per-correlation stream key for '{subject}' needs a correlation ID"
+                ))
+            }),
+    }
+}
+
+/// Named streams, each capturing the subjects matching its filter
+#[derive(Debug, Clone)]
+pub struct StreamLayout {
+    strategy: StreamKeyStrategy,
+    streams: Vec<(String, Pattern)>,
+}
+
+impl StreamLayout {
+    /// Create a layout without streams
+    #[must_use]
+    pub fn new(strategy: StreamKeyStrategy) -> Self {
+        Self {
+            strategy,
+            streams: Vec::new(),
+        }
+    }
+
+    /// Add a stream capturing subjects that match `filter`
+    #[must_use]
+    pub fn stream(mut self, name: impl Into<String>, filter: Pattern) -> Self {
+        self.streams.push((name.into(), filter));
+        self
+    }
+
+    /// The layout's key strategy
+    #[must_use]
+    pub fn strategy(&self) -> StreamKeyStrategy {
+        self.strategy
+    }
+
+    /// The first stream whose filter matches `subject`
+    #[must_use]
+    pub fn stream_for(&self, subject: &Subject) -> Option<&str> {
+        self.streams
+            .iter()
+            .find(|(_, filter)| filter.matches(subject))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Check that the aggregate of each event in `events` keeps its order:
+    /// the strategy must preserve aggregate order, and exactly one stream
+    /// must capture all of the aggregate's subjects
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error naming the first aggregate whose events
+    /// would be lost or split across streams
+    pub fn validate(&self, events: &[Subject]) -> Result<()> {
+        if !self.strategy.preserves_aggregate_order() {
+            return Err(SubjectError::validation_error(format!(
+                "A {} layout cannot reconstruct per-aggregate ordering",
+                self.strategy
+            )));
+        }
+        for event in events {
+            let aggregate = Pattern::new(format!("{}.{}.>", event.context(), event.aggregate()))?;
+            let capturing: Vec<&str> = self
+                .streams
+                .iter()
+                .filter(|(_, filter)| filter.overlaps(&aggregate))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            match capturing.as_slice() {
+                [name] if self.captures_all(name, &aggregate) => {},
+                [] | [_] => {
+                    return Err(SubjectError::validation_error(format!(
+                        "No stream captures every event of aggregate '{aggregate}'"
+                    )));
+                },
+                names => {
+                    return Err(SubjectError::validation_error(format!(
+                        "Events of aggregate '{aggregate}' are split across streams {}",
+                        names.join(", ")
+                    )));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn captures_all(&self, name: &str, aggregate: &Pattern) -> bool {
+        self.streams
+            .iter()
+            .any(|(stream, filter)| stream == name && filter.subsumes(aggregate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::IdType;
+
+    #[test]
+    fn test_stream_keys() {
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        assert_eq!(
+            event_stream_key(&placed, StreamKeyStrategy::PerContext, None).unwrap(),
+            "orders"
+        );
+        assert!(event_stream_key(&placed, StreamKeyStrategy::PerCorrelation, None).is_err());
+
+        let uuid = Uuid::new_v4();
+        let correlation = CorrelationId(IdType::Uuid(uuid));
+        let key = event_stream_key(
+            &placed,
+            StreamKeyStrategy::PerCorrelation,
+            Some(&correlation),
+        )
+        .unwrap();
+        assert_eq!(key, uuid.to_string());
+    }
+
+    #[test]
+    fn test_layout_validation() {
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let issued = Subject::new("billing.invoice.issued.v1").unwrap();
+        let contexts = StreamLayout::new(StreamKeyStrategy::PerContext)
+            .stream("ORDERS", Pattern::new("orders.>").unwrap());
+        assert!(contexts.validate(std::slice::from_ref(&placed)).is_ok());
+        // Events no stream captures would be lost
+        assert!(contexts.validate(&[issued]).is_err());
+
+        // A filter covering only some of an aggregate's events
+        let partial = StreamLayout::new(StreamKeyStrategy::PerAggregate)
+            .stream("PLACED", Pattern::new("orders.order.placed.>").unwrap());
+        assert!(partial.validate(std::slice::from_ref(&placed)).is_err());
+
+        let correlated = StreamLayout::new(StreamKeyStrategy::PerCorrelation)
+            .stream("ALL", Pattern::new(">").unwrap());
+        assert!(correlated.validate(&[placed]).is_err());
+    }
+}
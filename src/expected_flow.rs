@@ -0,0 +1,432 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Expected message flows and conformance checking
+//!
+//! An [`ExpectedFlow`] declares the steps a correlation chain should go
+//! through as a graph of subject patterns, e.g. `order.created` →
+//! `stock.reserved` → `payment.processed` → `order.confirmed`.
+//! [`ExpectedFlow::check`] compares a concrete [`CorrelationChain`] with
+//! subjects attached against it and reports each [`FlowDeviation`]: steps
+//! that never happened, messages no step expects, and steps that were not
+//! caused by any of their predecessors.
+//!
+//! ```
+//! use cim_subject::expected_flow::{
+//!     ExpectedFlow,
+//!     FlowDeviation,
+//! };
+//! use cim_subject::message_algebra::{
+//!     ChainEntry,
+//!     CorrelationChain,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     Pattern,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let flow = ExpectedFlow::sequence([
+//!     Pattern::new("orders.events.order.created")?,
+//!     Pattern::new("inventory.events.stock.reserved")?,
+//!     Pattern::new("orders.events.order.confirmed")?,
+//! ]);
+//!
+//! let now = std::time::SystemTime::now();
+//! let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let confirmed = MessageIdentity::caused_by(
+//!     IdType::Uuid(Uuid::new_v4()),
+//!     root.correlation_id.clone(),
+//!     root.message_id.clone(),
+//! );
+//! let mut chain = CorrelationChain::from_entry(
+//!     ChainEntry::new(root, now).with_subject(Subject::new("orders.events.order.created")?),
+//! )
+//! .unwrap();
+//! chain
+//!     .add_entry(
+//!         ChainEntry::new(confirmed, now)
+//!             .with_subject(Subject::new("orders.events.order.confirmed")?),
+//!     )
+//!     .unwrap();
+//!
+//! let report = flow.check(&chain).unwrap();
+//! assert!(!report.is_conformant());
+//! assert!(matches!(report.deviations()[..], [
+//!     FlowDeviation::Missing { .. },
+//!     FlowDeviation::Misordered { .. },
+//! ]));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::{
+    self,
+    Display,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::correlation::{
+    IdType,
+    Result,
+};
+use crate::message_algebra::CorrelationChain;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// A difference between a chain and its expected flow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowDeviation {
+    /// No message matched the step
+    Missing {
+        /// The step's pattern
+        step: Pattern,
+    },
+    /// A message matched no step
+    Extra {
+        /// The message
+        message_id: IdType,
+        /// Its subject
+        subject: Subject,
+    },
+    /// A message matched a step, but none of the step's predecessors
+    /// appears among its causes
+    Misordered {
+        /// The message
+        message_id: IdType,
+        /// The step it matched
+        step: Pattern,
+        /// The steps expected to precede it
+        expected_after: Vec<Pattern>,
+    },
+}
+
+impl Display for FlowDeviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { step } => write!(f, "no message matched step '{step}'"),
+            Self::Extra {
+                message_id,
+                subject,
+            } => write!(f, "{message_id} on '{subject}' matched no step"),
+            Self::Misordered {
+                message_id,
+                step,
+                expected_after,
+            } => {
+                write!(f, "{message_id} matched '{step}' but was not caused by ")?;
+                for (i, predecessor) in expected_after.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "'{predecessor}'")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// The outcome of checking a chain against an [`ExpectedFlow`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    deviations: Vec<FlowDeviation>,
+}
+
+impl ConformanceReport {
+    /// Check if the chain followed the flow exactly
+    #[must_use]
+    pub fn is_conformant(&self) -> bool {
+        self.deviations.is_empty()
+    }
+
+    /// Missing steps in declaration order, then extra and misordered
+    /// messages in causal order
+    #[must_use]
+    pub fn deviations(&self) -> &[FlowDeviation] {
+        &self.deviations
+    }
+}
+
+/// Expected steps of a chain, as a graph of subject patterns
+///
+/// A message matches the first step whose pattern matches its subject. A
+/// step with predecessors must be caused, directly or transitively, by a
+/// message matching at least one of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedFlow {
+    steps: Vec<Pattern>,
+    /// `(from, to)` indices into `steps`
+    edges: Vec<(usize, usize)>,
+}
+
+impl ExpectedFlow {
+    /// Create a flow without steps
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a linear flow where each step follows the previous one
+    #[must_use]
+    pub fn sequence(steps: impl IntoIterator<Item = Pattern>) -> Self {
+        let mut flow = Self::new();
+        let mut previous = None;
+        for step in steps {
+            let index = flow.index_of(step);
+            if let Some(from) = previous {
+                flow.link(from, index);
+            }
+            previous = Some(index);
+        }
+        flow
+    }
+
+    /// Add a step, if not already declared
+    #[must_use]
+    pub fn step(mut self, step: Pattern) -> Self {
+        self.index_of(step);
+        self
+    }
+
+    /// Declare that `to` follows `from`, adding either step if needed
+    #[must_use]
+    pub fn edge(mut self, from: Pattern, to: Pattern) -> Self {
+        let from = self.index_of(from);
+        let to = self.index_of(to);
+        self.link(from, to);
+        self
+    }
+
+    /// Steps in declaration order
+    #[must_use]
+    pub fn steps(&self) -> &[Pattern] {
+        &self.steps
+    }
+
+    /// The steps `step` directly follows
+    #[must_use]
+    pub fn predecessors(&self, step: &Pattern) -> Vec<&Pattern> {
+        self.steps
+            .iter()
+            .position(|declared| declared == step)
+            .map(|to| {
+                self.predecessor_indices(to)
+                    .map(|from| &self.steps[from])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check `chain` against the flow
+    ///
+    /// Messages without a subject are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CorrelationError::CyclicCausation`] if the chain's
+    /// causation graph contains a cycle
+    ///
+    /// [`CorrelationError::CyclicCausation`]: crate::correlation::CorrelationError::CyclicCausation
+    pub fn check(&self, chain: &CorrelationChain) -> Result<ConformanceReport> {
+        let order = chain.topo_order()?;
+        let step_of = |message_id: &IdType| {
+            chain
+                .subjects
+                .get(message_id)
+                .and_then(|subject| self.steps.iter().position(|step| step.matches(subject)))
+        };
+
+        let mut seen_steps = HashSet::new();
+        let mut out_of_order = Vec::new();
+        for message in &order {
+            let message_id = &message.message_id;
+            let Some(subject) = chain.subjects.get(message_id) else {
+                continue;
+            };
+            let Some(step) = step_of(message_id) else {
+                out_of_order.push(FlowDeviation::Extra {
+                    message_id: message_id.clone(),
+                    subject: subject.clone(),
+                });
+                continue;
+            };
+            seen_steps.insert(step);
+
+            let predecessors: HashSet<usize> = self.predecessor_indices(step).collect();
+            if predecessors.is_empty() {
+                continue;
+            }
+            let caused_by_predecessor = ancestors(chain, message_id)
+                .any(|ancestor| step_of(ancestor).is_some_and(|s| predecessors.contains(&s)));
+            if !caused_by_predecessor {
+                out_of_order.push(FlowDeviation::Misordered {
+                    message_id: message_id.clone(),
+                    step: self.steps[step].clone(),
+                    expected_after: self
+                        .predecessor_indices(step)
+                        .map(|from| self.steps[from].clone())
+                        .collect(),
+                });
+            }
+        }
+
+        let mut deviations: Vec<FlowDeviation> = (0..self.steps.len())
+            .filter(|step| !seen_steps.contains(step))
+            .map(|step| FlowDeviation::Missing {
+                step: self.steps[step].clone(),
+            })
+            .collect();
+        deviations.extend(out_of_order);
+        Ok(ConformanceReport { deviations })
+    }
+
+    fn index_of(&mut self, step: Pattern) -> usize {
+        if let Some(index) = self.steps.iter().position(|declared| *declared == step) {
+            index
+        } else {
+            self.steps.push(step);
+            self.steps.len() - 1
+        }
+    }
+
+    fn link(&mut self, from: usize, to: usize) {
+        if !self.edges.contains(&(from, to)) {
+            self.edges.push((from, to));
+        }
+    }
+
+    fn predecessor_indices(&self, to: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(_, target)| *target == to)
+            .map(|(from, _)| *from)
+    }
+}
+
+/// The causes of a message, nearest first, stopping at a repeat
+fn ancestors<'a>(
+    chain: &'a CorrelationChain,
+    message_id: &'a IdType,
+) -> impl Iterator<Item = &'a IdType> {
+    let mut visited = HashSet::from([message_id]);
+    let mut current = message_id;
+    std::iter::from_fn(move || {
+        let parent = chain.causation_graph.get(current)?;
+        if !visited.insert(parent) {
+            return None;
+        }
+        current = parent;
+        Some(parent)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageIdentity;
+    use crate::message_algebra::ChainEntry;
+
+    fn pattern(pattern: &str) -> Pattern {
+        Pattern::new(pattern).unwrap()
+    }
+
+    fn add(
+        chain: &mut CorrelationChain,
+        parent: &MessageIdentity,
+        subject: &str,
+    ) -> MessageIdentity {
+        let identity = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            parent.correlation_id.clone(),
+            parent.message_id.clone(),
+        );
+        chain
+            .add_entry(
+                ChainEntry::new(identity.clone(), SystemTime::now())
+                    .with_subject(Subject::new(subject).unwrap()),
+            )
+            .unwrap();
+        identity
+    }
+
+    fn order_flow() -> ExpectedFlow {
+        ExpectedFlow::sequence([
+            pattern("orders.events.order.created"),
+            pattern("inventory.events.stock.reserved"),
+            pattern("payments.events.payment.processed"),
+            pattern("orders.events.order.confirmed"),
+        ])
+    }
+
+    #[test]
+    fn test_conformant_chain_allows_intermediate_steps() {
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let mut chain = CorrelationChain::from_entry(
+            ChainEntry::new(root.clone(), SystemTime::now())
+                .with_subject(Subject::new("orders.events.order.created").unwrap()),
+        )
+        .unwrap();
+        let reserved = add(&mut chain, &root, "inventory.events.stock.reserved");
+        let processed = add(&mut chain, &reserved, "payments.events.payment.processed");
+        add(&mut chain, &processed, "orders.events.order.confirmed");
+
+        let flow = order_flow();
+        assert!(flow.check(&chain).unwrap().is_conformant());
+        assert_eq!(
+            flow.predecessors(&pattern("orders.events.order.confirmed")),
+            [&pattern("payments.events.payment.processed")]
+        );
+
+        // Branches: confirmation may follow either payment or a voucher
+        let branching = flow.edge(
+            pattern("orders.events.voucher.redeemed"),
+            pattern("orders.events.order.confirmed"),
+        );
+        let voucher = add(&mut chain, &reserved, "orders.events.voucher.redeemed");
+        add(&mut chain, &voucher, "orders.events.order.confirmed");
+        assert!(branching.check(&chain).unwrap().is_conformant());
+    }
+
+    #[test]
+    fn test_deviations() {
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let mut chain = CorrelationChain::from_entry(
+            ChainEntry::new(root.clone(), SystemTime::now())
+                .with_subject(Subject::new("orders.events.order.created").unwrap()),
+        )
+        .unwrap();
+        let audit = add(&mut chain, &root, "audit.events.log.written");
+        // Payment happened without a stock reservation
+        let processed = add(&mut chain, &root, "payments.events.payment.processed");
+        add(&mut chain, &processed, "orders.events.order.confirmed");
+
+        let report = order_flow().check(&chain).unwrap();
+        assert_eq!(report.deviations(), [
+            FlowDeviation::Missing {
+                step: pattern("inventory.events.stock.reserved"),
+            },
+            FlowDeviation::Extra {
+                message_id: audit.message_id.clone(),
+                subject: Subject::new("audit.events.log.written").unwrap(),
+            },
+            FlowDeviation::Misordered {
+                message_id: processed.message_id.clone(),
+                step: pattern("payments.events.payment.processed"),
+                expected_after: vec![pattern("inventory.events.stock.reserved")],
+            },
+        ]);
+        assert!(report.deviations()[2]
+            .to_string()
+            .contains("not caused by 'inventory.events.stock.reserved'"));
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Extended patterns with negation, alternation and version range tokens
+//!
+//! [`Pattern`] follows NATS syntax exactly so it can be handed to the server.
+//! [`ExtendedPattern`] adds tokens that only this crate understands:
+//!
+//! - `{a,b,c}` matches any one of the listed literals
+//! - `!{a,b}` matches any single token except the listed literals
+//! - `v{>=2,<4}` matches `vN` version tokens within the bounds, which may use
+//!   `>=`, `>`, `<=`, `<` and `=`
+//!
+//! For example `orders.!{internal}.>` matches every orders subject except
+//! internal ones. Use [`ExtendedPattern::subscription_pattern`] to obtain the
+//! plain NATS pattern to subscribe with, then filter with
+//! [`ExtendedPattern::matches`].
+
+use std::fmt::{
+    self,
+    Display,
+};
+use std::str::FromStr;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::versioning::{
+    parse_version,
+    version_token,
+};
+
+/// Most NATS patterns [`ExtendedPattern::expand`] produces
+pub const MAX_EXPANSION: usize = 1024;
+
+/// A pattern supporting negation and alternation in addition to NATS wildcards
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExtendedPattern {
+    /// The raw pattern string
+    raw: String,
+    /// Parsed tokens
+    tokens: Vec<ExtendedToken>,
+}
+
+/// A token in an extended pattern
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExtendedToken {
+    /// Literal token that must match exactly
+    Literal(String),
+    /// Single wildcard (`*`)
+    SingleWildcard,
+    /// Multi wildcard (`>`)
+    MultiWildcard,
+    /// Any of the listed literals (`{a,b}`)
+    AnyOf(Vec<String>),
+    /// Any single token except the listed literals (`!{a,b}`)
+    NoneOf(Vec<String>),
+    /// A `vN` version within inclusive bounds (`v{>=2,<4}`)
+    VersionRange {
+        /// Lowest matching version, if bounded below
+        min: Option<u32>,
+        /// Highest matching version, if bounded above
+        max: Option<u32>,
+    },
+}
+
+impl ExtendedToken {
+    fn matches(&self, token: &str) -> bool {
+        match self {
+            ExtendedToken::Literal(literal) => literal == token,
+            ExtendedToken::SingleWildcard | ExtendedToken::MultiWildcard => true,
+            ExtendedToken::AnyOf(options) => options.iter().any(|o| o == token),
+            ExtendedToken::NoneOf(excluded) => excluded.iter().all(|e| e != token),
+            ExtendedToken::VersionRange { min, max } => parse_version(token).is_some_and(|v| {
+                min.map_or(true, |min| v >= min) && max.map_or(true, |max| v <= max)
+            }),
+        }
+    }
+}
+
+impl ExtendedPattern {
+    /// Create a new extended pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid
+    pub fn new(pattern: impl Into<String>) -> Result<Self> {
+        let raw = pattern.into();
+        let tokens = Self::parse_tokens(&raw)?;
+        Ok(Self { raw, tokens })
+    }
+
+    fn parse_tokens(pattern: &str) -> Result<Vec<ExtendedToken>> {
+        if pattern.is_empty() {
+            return Err(SubjectError::invalid_pattern("Pattern cannot be empty"));
+        }
+
+        let parts = split_tokens(pattern);
+        let mut tokens = Vec::with_capacity(parts.len());
+
+        for (i, part) in parts.iter().enumerate() {
+            let token = match *part {
+                "" => {
+                    return Err(SubjectError::invalid_pattern(format!(
+                        "Empty token at position {} in pattern '{}'",
+                        i + 1,
+                        pattern
+                    )));
+                },
+                "*" => ExtendedToken::SingleWildcard,
+                ">" => {
+                    if i != parts.len() - 1 {
+                        return Err(SubjectError::invalid_pattern(
+                            "Multi-wildcard '>' can only appear at the end of a pattern",
+                        ));
+                    }
+                    ExtendedToken::MultiWildcard
+                },
+                part => {
+                    if let Some(set) = part.strip_prefix('!') {
+                        ExtendedToken::NoneOf(parse_set(set, pattern)?)
+                    } else if let Some(range) = part.strip_prefix("v{") {
+                        parse_version_range(range, pattern)?
+                    } else if part.starts_with('{') {
+                        ExtendedToken::AnyOf(parse_set(part, pattern)?)
+                    } else {
+                        // Only the first token may be a system subject's
+                        // `$`-prefixed name
+                        let name = match part.strip_prefix('$') {
+                            Some(name) if i == 0 && !name.is_empty() => name,
+                            _ => part,
+                        };
+                        parse_literal(name)?;
+                        ExtendedToken::Literal(part.to_string())
+                    }
+                },
+            };
+            tokens.push(token);
+        }
+
+        Ok(tokens)
+    }
+
+    /// Check if a subject matches this pattern
+    #[must_use]
+    pub fn matches(&self, subject: &Subject) -> bool {
+        self.matches_str(subject.as_str())
+    }
+
+    /// Check if a subject string matches this pattern
+    #[must_use]
+    pub fn matches_str(&self, subject: &str) -> bool {
+        let mut parts = subject.split('.');
+
+        for token in &self.tokens {
+            match (token, parts.next()) {
+                (ExtendedToken::MultiWildcard, next) => return next.is_some(),
+                (token, Some(part)) if token.matches(part) => {},
+                _ => return false,
+            }
+        }
+
+        parts.next().is_none()
+    }
+
+    /// Get the raw pattern string
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Get the parsed tokens
+    #[must_use]
+    pub fn tokens(&self) -> &[ExtendedToken] {
+        &self.tokens
+    }
+
+    /// Check if this pattern only uses NATS syntax
+    #[must_use]
+    pub fn is_plain(&self) -> bool {
+        !self.tokens.iter().any(|t| {
+            matches!(
+                t,
+                ExtendedToken::AnyOf(_)
+                    | ExtendedToken::NoneOf(_)
+                    | ExtendedToken::VersionRange { .. }
+            )
+        })
+    }
+
+    /// The narrowest NATS pattern matching every subject this pattern matches
+    ///
+    /// Extended tokens become `*`, so subscribers must still filter with
+    /// [`ExtendedPattern::matches`] unless [`is_plain`](Self::is_plain).
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: literals were validated when the pattern was parsed
+    #[must_use]
+    pub fn subscription_pattern(&self) -> Pattern {
+        let raw: Vec<&str> = self
+            .tokens
+            .iter()
+            .map(|token| match token {
+                ExtendedToken::Literal(literal) => literal.as_str(),
+                ExtendedToken::MultiWildcard => ">",
+                _ => "*",
+            })
+            .collect();
+        Pattern::new(raw.join(".")).expect("extended tokens map to valid NATS tokens")
+    }
+
+    /// Expand alternations and version ranges into the equivalent set of
+    /// NATS patterns
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern contains a negation or a version
+    /// range without an upper bound, which NATS cannot express, or would
+    /// expand to more than [`MAX_EXPANSION`] patterns
+    pub fn expand(&self) -> Result<Vec<Pattern>> {
+        let inexpressible = || {
+            SubjectError::invalid_pattern(format!(
+                "Pattern '{}' cannot be expanded into NATS patterns",
+                self.raw
+            ))
+        };
+        let mut count: usize = 1;
+        for token in &self.tokens {
+            let options = match token {
+                ExtendedToken::AnyOf(options) => options.len(),
+                ExtendedToken::NoneOf(_) | ExtendedToken::VersionRange { max: None, .. } => {
+                    return Err(inexpressible());
+                },
+                ExtendedToken::VersionRange {
+                    min,
+                    max: Some(max),
+                } => usize::try_from(max - min.unwrap_or(0))
+                    .map_or(usize::MAX, |span| span.saturating_add(1)),
+                _ => 1,
+            };
+            count = count.saturating_mul(options);
+        }
+        if count > MAX_EXPANSION {
+            return Err(SubjectError::invalid_pattern(format!(
+                "Pattern '{}' expands to more than {MAX_EXPANSION} NATS patterns",
+                self.raw
+            )));
+        }
+
+        let mut expanded = vec![String::new()];
+        for token in &self.tokens {
+            let options: Vec<String> = match token {
+                ExtendedToken::Literal(literal) => vec![literal.clone()],
+                ExtendedToken::SingleWildcard => vec!["*".to_string()],
+                ExtendedToken::MultiWildcard => vec![">".to_string()],
+                ExtendedToken::AnyOf(options) => options.clone(),
+                ExtendedToken::VersionRange { min, max } => {
+                    let max = max.ok_or_else(inexpressible)?;
+                    (min.unwrap_or(0)..=max).map(version_token).collect()
+                },
+                ExtendedToken::NoneOf(_) => return Err(inexpressible()),
+            };
+            expanded = expanded
+                .iter()
+                .flat_map(|prefix| {
+                    options.iter().map(move |option| {
+                        if prefix.is_empty() {
+                            option.clone()
+                        } else {
+                            format!("{prefix}.{option}")
+                        }
+                    })
+                })
+                .collect();
+        }
+
+        expanded.into_iter().map(Pattern::new).collect()
+    }
+}
+
+/// Split on `.` outside of braces
+fn split_tokens(pattern: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '.' if depth == 0 => {
+                parts.push(&pattern[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(&pattern[start..]);
+    parts
+}
+
+/// Parse a `{a,b}` literal set
+fn parse_set(set: &str, pattern: &str) -> Result<Vec<String>> {
+    let inner = set
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| {
+            SubjectError::invalid_pattern(format!(
+                "Expected '{{a,b}}' set but found '{set}' in pattern '{pattern}'"
+            ))
+        })?;
+
+    let options = inner
+        .split(',')
+        .map(|option| parse_literal(option.trim()))
+        .collect::<Result<Vec<_>>>()?;
+
+    if options.is_empty() {
+        return Err(SubjectError::invalid_pattern(format!(
+            "Empty set in pattern '{pattern}'"
+        )));
+    }
+    Ok(options)
+}
+
+/// Parse the bounds of a `v{>=2,<4}` range, given what follows `v{`
+fn parse_version_range(range: &str, pattern: &str) -> Result<ExtendedToken> {
+    let invalid = |reason: &str| {
+        SubjectError::invalid_pattern(format!(
+            "Invalid version range 'v{{{range}' in pattern '{pattern}': {reason}"
+        ))
+    };
+    let inner = range
+        .strip_suffix('}')
+        .ok_or_else(|| invalid("missing '}'"))?;
+
+    let (mut min, mut max): (Option<u32>, Option<u32>) = (None, None);
+    for bound in inner.split(',').map(str::trim) {
+        let (operator, number) = ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|op| bound.strip_prefix(op).map(|rest| (*op, rest.trim())))
+            .ok_or_else(|| invalid("bounds start with >=, >, <=, < or ="))?;
+        let number: u32 = number
+            .strip_prefix('v')
+            .unwrap_or(number)
+            .parse()
+            .map_err(|_| invalid("bounds must be version numbers"))?;
+
+        let below = |n: u32| number.checked_sub(n).ok_or_else(|| invalid("empty range"));
+        let (lower, upper) = match operator {
+            ">=" => (Some(number), None),
+            ">" => (
+                Some(
+                    number
+                        .checked_add(1)
+                        .ok_or_else(|| invalid("empty range"))?,
+                ),
+                None,
+            ),
+            "<=" => (None, Some(number)),
+            "<" => (None, Some(below(1)?)),
+            _ => (Some(number), Some(number)),
+        };
+        min = min.max(lower);
+        max = match (max, upper) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(invalid("empty range"));
+        }
+    }
+    Ok(ExtendedToken::VersionRange { min, max })
+}
+
+fn parse_literal(literal: &str) -> Result<String> {
+    if literal.is_empty()
+        || !literal
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(SubjectError::invalid_pattern(format!(
+            "Token '{literal}' contains invalid characters"
+        )));
+    }
+    Ok(literal.to_string())
+}
+
+impl Display for ExtendedPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl FromStr for ExtendedPattern {
+    type Err = SubjectError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl From<Pattern> for ExtendedPattern {
+    fn from(pattern: Pattern) -> Self {
+        Self::new(pattern.as_str()).expect("NATS patterns are valid extended patterns")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negation_and_alternation() {
+        let pattern = ExtendedPattern::new("orders.!{internal,debug}.>").unwrap();
+        assert!(pattern.matches_str("orders.order.placed.v1"));
+        assert!(!pattern.matches_str("orders.internal.audit.v1"));
+        assert!(!pattern.matches_str("orders.debug.trace.v1"));
+        assert!(!pattern.is_plain());
+        assert_eq!(pattern.subscription_pattern().as_str(), "orders.*.>");
+        assert!(pattern.expand().is_err());
+
+        let pattern = ExtendedPattern::new("{orders,billing}.*.{created,updated}.v1").unwrap();
+        assert!(pattern.matches_str("billing.invoice.updated.v1"));
+        assert!(!pattern.matches_str("billing.invoice.deleted.v1"));
+        assert!(!pattern.matches_str("shipping.parcel.created.v1"));
+
+        let expanded = pattern.expand().unwrap();
+        assert_eq!(expanded.len(), 4);
+        assert_eq!(expanded[0].as_str(), "orders.*.created.v1");
+
+        // 33^2 combinations are too many
+        let options: Vec<String> = (0..33).map(|i| format!("t{i}")).collect();
+        let wide = format!("{{{0}}}.{{{0}}}.placed.v1", options.join(","));
+        assert!(ExtendedPattern::new(wide).unwrap().expand().is_err());
+    }
+
+    #[test]
+    fn test_plain_patterns_behave_like_nats() {
+        for raw in ["orders.*.placed.v1", "orders.>", "*", "$JS.API.>"] {
+            let extended = ExtendedPattern::from(Pattern::new(raw).unwrap());
+            let plain = Pattern::new(raw).unwrap();
+            assert!(extended.is_plain());
+            assert_eq!(extended.subscription_pattern(), plain);
+            for subject in ["orders.order.placed.v1", "orders", "billing.x"] {
+                assert_eq!(extended.matches_str(subject), plain.matches_str(subject));
+            }
+        }
+
+        assert!(ExtendedPattern::new("orders.{}.v1").is_err());
+        assert!(ExtendedPattern::new("orders.{a,b.v1").is_err());
+        assert!(ExtendedPattern::new("orders.!internal.v1").is_err());
+        assert!(ExtendedPattern::new("orders.>.v1").is_err());
+        assert!(ExtendedPattern::new("orders.$JS.>").is_err());
+        assert!(ExtendedPattern::new("$.API.>").is_err());
+
+        let api = ExtendedPattern::from(Pattern::new("$JS.API.>").unwrap());
+        assert!(api.matches_str("$JS.API.STREAM.INFO"));
+        assert!(!api.matches_str("JS.API.STREAM.INFO"));
+    }
+
+    #[test]
+    fn test_version_ranges() {
+        let pattern = ExtendedPattern::new("orders.order.created.v{>=2,<4}").unwrap();
+        assert!(!pattern.matches_str("orders.order.created.v1"));
+        assert!(pattern.matches_str("orders.order.created.v2"));
+        assert!(pattern.matches_str("orders.order.created.v3"));
+        assert!(!pattern.matches_str("orders.order.created.v4"));
+        assert!(!pattern.matches_str("orders.order.created.beta"));
+        assert_eq!(pattern.tokens()[3], ExtendedToken::VersionRange {
+            min: Some(2),
+            max: Some(3)
+        });
+        assert_eq!(
+            pattern.subscription_pattern().as_str(),
+            "orders.order.created.*"
+        );
+        let expanded: Vec<String> = pattern
+            .expand()
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(expanded, vec![
+            "orders.order.created.v2",
+            "orders.order.created.v3"
+        ]);
+
+        let open = ExtendedPattern::new("orders.*.*.v{>1}").unwrap();
+        assert!(open.matches_str("orders.order.created.v10"));
+        assert!(open.expand().is_err());
+        assert!(ExtendedPattern::new("orders.*.*.v{<=4000000000}")
+            .unwrap()
+            .expand()
+            .is_err());
+        assert!(ExtendedPattern::new("*.*.*.v{=3}")
+            .unwrap()
+            .matches_str("a.b.c.v3"));
+
+        for invalid in [
+            "a.b.c.v{>=4,<4}",
+            "a.b.c.v{<0}",
+            "a.b.c.v{~2}",
+            "a.b.c.v{>=x}",
+            "a.b.c.v{>=2",
+            "a.b.c.v{>4294967295}",
+        ] {
+            assert!(ExtendedPattern::new(invalid).is_err(), "{invalid}");
+        }
+    }
+}
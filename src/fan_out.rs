@@ -0,0 +1,503 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Planning broadcast requests in tiers and waves
+//!
+//! A [`FanOut`] sends one request to many targets, such as asking every
+//! lender for a rate quote. Targets are grouped into tiers by pattern, in
+//! the order the tiers were added; each tier has its own timeout and a
+//! limit on how many of its requests may be outstanding at once. The
+//! resulting [`FanOutPlan`] splits every tier into waves of at most that
+//! many targets, run one after another, each with a deadline relative to
+//! the start of the fan-out, so a slow tier cannot flood responders or
+//! hold up the tiers before it.
+//!
+//! A [`FanOutAggregator`] then collects the responses: replies must belong
+//! to the request, on-time responses go to the `on_response` hooks, and
+//! [`finish`](FanOutAggregator::finish) reports what was late or missing.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cim_subject::fan_out::FanOut;
+//! use cim_subject::request_reply::RequestContext;
+//! use cim_subject::{
+//!     MessageFactory,
+//!     Pattern,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let fan_out = FanOut::new()
+//!     .tier(
+//!         "prime",
+//!         Pattern::new("lenders.prime.>")?,
+//!         Duration::from_secs(5),
+//!         2,
+//!     )
+//!     .tier(
+//!         "alt-a",
+//!         Pattern::new("lenders.alta.>")?,
+//!         Duration::from_secs(10),
+//!         4,
+//!     );
+//!
+//! let targets = [
+//!     "lenders.prime.first_bank.quote",
+//!     "lenders.prime.second_bank.quote",
+//!     "lenders.prime.third_bank.quote",
+//!     "lenders.alta.credit_union.quote",
+//! ]
+//! .map(|s| Subject::new(s).unwrap());
+//! let plan = fan_out.plan(&targets);
+//! assert_eq!(plan.waves.len(), 3);
+//! assert_eq!(plan.waves[2].deadline, Duration::from_secs(20));
+//!
+//! let request = RequestContext::new(
+//!     Subject::new("lending.rates.shop.v1")?,
+//!     MessageFactory::create_root_query(Uuid::new_v4()),
+//! );
+//! let mut quotes = plan.aggregator::<f64>(request.clone());
+//! quotes
+//!     .collect(
+//!         &targets[0],
+//!         &request.reply_identity(),
+//!         6.25,
+//!         Duration::from_secs(1),
+//!     )
+//!     .unwrap();
+//! let result = quotes.finish();
+//! assert_eq!(result.responses.len(), 1);
+//! assert_eq!(result.missing.len(), 3);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use crate::correlation::{
+    self,
+    MessageIdentity,
+};
+use crate::pattern::Pattern;
+use crate::request_reply::RequestContext;
+use crate::subject::Subject;
+
+/// Receives each on-time response
+pub type ResponseHook<R> = Box<dyn FnMut(&FanOutResponse<R>) + Send>;
+
+/// Targets sharing a timeout and concurrency limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanOutTier {
+    /// Name of the tier
+    pub name: String,
+    /// Targets in the tier
+    pub pattern: Pattern,
+    /// How long each wave of the tier may take
+    pub timeout: Duration,
+    /// Most requests of the tier outstanding at once
+    pub max_concurrency: usize,
+}
+
+/// Tiers of targets for a broadcast request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FanOut {
+    tiers: Vec<FanOutTier>,
+}
+
+impl FanOut {
+    /// Create a fan-out with no tiers
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tier, after those already added
+    ///
+    /// A `max_concurrency` of zero is treated as one.
+    #[must_use]
+    pub fn tier(
+        mut self,
+        name: impl Into<String>,
+        pattern: Pattern,
+        timeout: Duration,
+        max_concurrency: usize,
+    ) -> Self {
+        self.tiers.push(FanOutTier {
+            name: name.into(),
+            pattern,
+            timeout,
+            max_concurrency: max_concurrency.max(1),
+        });
+        self
+    }
+
+    /// Tiers in the order they run
+    #[must_use]
+    pub fn tiers(&self) -> &[FanOutTier] {
+        &self.tiers
+    }
+
+    /// Split targets into waves
+    ///
+    /// Each target joins the first tier whose pattern matches it; duplicate
+    /// targets are planned once.
+    #[must_use]
+    pub fn plan(&self, targets: &[Subject]) -> FanOutPlan {
+        let mut seen = HashSet::new();
+        let mut by_tier: Vec<Vec<Subject>> = vec![Vec::new(); self.tiers.len()];
+        let mut unmatched = Vec::new();
+        for target in targets {
+            if !seen.insert(target) {
+                continue;
+            }
+            match self
+                .tiers
+                .iter()
+                .position(|tier| tier.pattern.matches(target))
+            {
+                Some(index) => by_tier[index].push(target.clone()),
+                None => unmatched.push(target.clone()),
+            }
+        }
+
+        let mut waves = Vec::new();
+        let mut elapsed = Duration::ZERO;
+        for (tier, targets) in self.tiers.iter().zip(by_tier) {
+            for chunk in targets.chunks(tier.max_concurrency) {
+                let starts_after = elapsed;
+                elapsed = elapsed.saturating_add(tier.timeout);
+                waves.push(Wave {
+                    tier: tier.name.clone(),
+                    targets: chunk.to_vec(),
+                    starts_after,
+                    deadline: elapsed,
+                });
+            }
+        }
+        FanOutPlan { waves, unmatched }
+    }
+}
+
+/// A batch of requests sent together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wave {
+    /// The tier the targets belong to
+    pub tier: String,
+    /// Subjects to send the request to
+    pub targets: Vec<Subject>,
+    /// When to send, from the start of the fan-out
+    pub starts_after: Duration,
+    /// When responses are due, from the start of the fan-out
+    pub deadline: Duration,
+}
+
+/// Waves to run in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanOutPlan {
+    /// Waves, in the order they are sent
+    pub waves: Vec<Wave>,
+    /// Targets no tier matched, which are not sent the request
+    pub unmatched: Vec<Subject>,
+}
+
+impl FanOutPlan {
+    /// How long the whole fan-out may take
+    #[must_use]
+    pub fn total_timeout(&self) -> Duration {
+        self.waves
+            .last()
+            .map_or(Duration::ZERO, |wave| wave.deadline)
+    }
+
+    /// Number of targets sent the request
+    #[must_use]
+    pub fn target_count(&self) -> usize {
+        self.waves.iter().map(|wave| wave.targets.len()).sum()
+    }
+
+    /// The wave a target is in
+    #[must_use]
+    pub fn wave_of(&self, target: &Subject) -> Option<&Wave> {
+        self.waves.iter().find(|wave| wave.targets.contains(target))
+    }
+
+    /// An aggregator for responses to `request` under this plan
+    #[must_use]
+    pub fn aggregator<R>(&self, request: RequestContext) -> FanOutAggregator<R> {
+        FanOutAggregator {
+            plan: self.clone(),
+            request,
+            responses: Vec::new(),
+            late: Vec::new(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// A response from one target
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanOutResponse<R> {
+    /// The target that responded
+    pub target: Subject,
+    /// The tier the target belongs to
+    pub tier: String,
+    /// The reply's identity
+    pub identity: MessageIdentity,
+    /// The response
+    pub response: R,
+    /// When it arrived, from the start of the fan-out
+    pub elapsed: Duration,
+}
+
+/// What became of a collected response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collected {
+    /// On time, and passed to the hooks
+    Accepted,
+    /// After its wave's deadline
+    Late,
+    /// The target had already responded
+    Duplicate,
+}
+
+/// Collects responses to a fan-out request
+pub struct FanOutAggregator<R> {
+    plan: FanOutPlan,
+    request: RequestContext,
+    responses: Vec<FanOutResponse<R>>,
+    late: Vec<Subject>,
+    hooks: Vec<ResponseHook<R>>,
+}
+
+impl<R> FanOutAggregator<R> {
+    /// Call `hook` with every on-time response
+    #[must_use]
+    pub fn on_response<F>(mut self, hook: F) -> Self
+    where F: FnMut(&FanOutResponse<R>) + Send + 'static {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Collect a target's response that arrived `elapsed` after the start
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reply does not belong to the request or the
+    /// target was not sent it
+    pub fn collect(
+        &mut self,
+        target: &Subject,
+        reply: &MessageIdentity,
+        response: R,
+        elapsed: Duration,
+    ) -> correlation::Result<Collected> {
+        self.request.validate_reply(reply)?;
+        let wave = self.plan.wave_of(target).ok_or_else(|| {
+            correlation::CorrelationError::InvalidIdentity(format!(
+                "reply {} is from '{target}', which was not sent the request",
+                reply.message_id
+            ))
+        })?;
+        if self.has_responded(target) {
+            return Ok(Collected::Duplicate);
+        }
+        if elapsed > wave.deadline {
+            self.late.push(target.clone());
+            return Ok(Collected::Late);
+        }
+
+        let response = FanOutResponse {
+            target: target.clone(),
+            tier: wave.tier.clone(),
+            identity: reply.clone(),
+            response,
+            elapsed,
+        };
+        for hook in &mut self.hooks {
+            hook(&response);
+        }
+        self.responses.push(response);
+        Ok(Collected::Accepted)
+    }
+
+    /// On-time responses so far, in arrival order
+    #[must_use]
+    pub fn responses(&self) -> &[FanOutResponse<R>] {
+        &self.responses
+    }
+
+    /// Check if every target has responded, on time or late
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.responses.len() + self.late.len() == self.plan.target_count()
+    }
+
+    /// Stop collecting, reporting late and missing targets
+    #[must_use]
+    pub fn finish(self) -> FanOutResult<R> {
+        let missing = self
+            .plan
+            .waves
+            .iter()
+            .flat_map(|wave| &wave.targets)
+            .filter(|target| !self.has_responded(target))
+            .cloned()
+            .collect();
+        FanOutResult {
+            responses: self.responses,
+            late: self.late,
+            missing,
+        }
+    }
+
+    fn has_responded(&self, target: &Subject) -> bool {
+        self.late.contains(target)
+            || self
+                .responses
+                .iter()
+                .any(|response| &response.target == target)
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for FanOutAggregator<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FanOutAggregator")
+            .field("plan", &self.plan)
+            .field("request", &self.request)
+            .field("responses", &self.responses)
+            .field("late", &self.late)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+/// The outcome of a fan-out
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanOutResult<R> {
+    /// On-time responses, in arrival order
+    pub responses: Vec<FanOutResponse<R>>,
+    /// Targets that responded after their wave's deadline
+    pub late: Vec<Subject>,
+    /// Targets that never responded, in plan order
+    pub missing: Vec<Subject>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    fn subjects(names: &[&str]) -> Vec<Subject> {
+        names.iter().map(|s| Subject::new(*s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_plan_waves() {
+        let fan_out = FanOut::new()
+            .tier(
+                "prime",
+                Pattern::new("lenders.prime.*.quote").unwrap(),
+                Duration::from_secs(5),
+                2,
+            )
+            .tier(
+                "any",
+                Pattern::new("lenders.>").unwrap(),
+                Duration::from_secs(10),
+                0,
+            );
+        let targets = subjects(&[
+            "lenders.prime.a.quote",
+            "lenders.nonqm.b.quote",
+            "lenders.prime.c.quote",
+            "lenders.prime.a.quote",
+            "lenders.prime.d.quote",
+            "brokers.x.y.quote",
+        ]);
+        let plan = fan_out.plan(&targets);
+
+        let waves: Vec<(&str, usize, u64, u64)> = plan
+            .waves
+            .iter()
+            .map(|w| {
+                (
+                    w.tier.as_str(),
+                    w.targets.len(),
+                    w.starts_after.as_secs(),
+                    w.deadline.as_secs(),
+                )
+            })
+            .collect();
+        assert_eq!(waves, [
+            ("prime", 2, 0, 5),
+            ("prime", 1, 5, 10),
+            ("any", 1, 10, 20)
+        ]);
+        assert_eq!(plan.unmatched, subjects(&["brokers.x.y.quote"]));
+        assert_eq!(plan.target_count(), 4);
+        assert_eq!(plan.total_timeout(), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_aggregate_responses() {
+        let fan_out = FanOut::new().tier(
+            "prime",
+            Pattern::new("lenders.prime.>").unwrap(),
+            Duration::from_secs(5),
+            1,
+        );
+        let targets = subjects(&["lenders.prime.a.quote", "lenders.prime.b.quote"]);
+        let plan = fan_out.plan(&targets);
+        let request = RequestContext::new(
+            Subject::new("lending.rates.shop.v1").unwrap(),
+            MessageFactory::create_root_query(Uuid::new_v4()),
+        );
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let mut aggregator =
+            plan.aggregator(request.clone())
+                .on_response(move |response: &FanOutResponse<u32>| {
+                    hook_seen.lock().unwrap().push(response.response);
+                });
+
+        let reply = request.reply_identity();
+        let secs = Duration::from_secs;
+        assert_eq!(
+            aggregator.collect(&targets[0], &reply, 1, secs(4)).unwrap(),
+            Collected::Accepted
+        );
+        assert_eq!(
+            aggregator.collect(&targets[0], &reply, 2, secs(4)).unwrap(),
+            Collected::Duplicate
+        );
+        // The second wave is due 10s in
+        assert_eq!(
+            aggregator
+                .collect(&targets[1], &reply, 3, secs(11))
+                .unwrap(),
+            Collected::Late
+        );
+        assert!(aggregator.is_complete());
+
+        let stranger = MessageFactory::create_root_query(Uuid::new_v4());
+        assert!(aggregator
+            .collect(&targets[1], &stranger, 4, secs(1))
+            .is_err());
+        let other = Subject::new("lenders.prime.z.quote").unwrap();
+        assert!(aggregator.collect(&other, &reply, 5, secs(1)).is_err());
+
+        assert_eq!(*seen.lock().unwrap(), [1]);
+        let result = aggregator.finish();
+        assert_eq!(result.late, targets[1..]);
+        assert!(result.missing.is_empty());
+    }
+}
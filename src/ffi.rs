@@ -0,0 +1,336 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! C API for subject validation, pattern matching and permission checks
+//!
+//! With the `ffi` feature, these `extern "C"` functions let services
+//! written in Go, Python or C enforce the same subject rules as Rust ones.
+//! Build a shared library with
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Strings are NUL-terminated UTF-8. Functions answering a question return
+//! `1` for yes and `0` for no; functions that can fail return
+//! [`CIM_SUBJECT_OK`] or a negative error code, and
+//! [`cim_subject_last_error`] describes the most recent failure on the
+//! calling thread. Objects created by the library are released with the
+//! matching `_free` function.
+//!
+//! ```c
+//! if (cim_subject_pattern_matches("orders.*.placed.>", "orders.order.placed.v1") == 1) {
+//!     /* route it */
+//! }
+//!
+//! CimPermissions *permissions = cim_permissions_from_json(json);
+//! int allowed = cim_permissions_check(permissions, subject, "Publish");
+//! cim_permissions_free(permissions);
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{
+    c_char,
+    c_int,
+    CStr,
+    CString,
+};
+use std::ptr;
+
+use crate::pattern::Pattern;
+use crate::permissions::{
+    Operation,
+    Permissions,
+};
+use crate::subject::Subject;
+
+/// The call succeeded
+pub const CIM_SUBJECT_OK: c_int = 0;
+
+/// A pointer argument was null or a string was not UTF-8
+pub const CIM_SUBJECT_ERR_ARGUMENT: c_int = -1;
+
+/// A subject, pattern, operation or permission set was invalid
+pub const CIM_SUBJECT_ERR_INVALID: c_int = -2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An opaque permission set
+pub struct CimPermissions(Permissions);
+
+/// The four tokens of a parsed subject, owned by the library
+#[repr(C)]
+#[derive(Debug)]
+pub struct CimSubjectParts {
+    /// The bounded context token
+    pub context: *mut c_char,
+    /// The aggregate token
+    pub aggregate: *mut c_char,
+    /// The event type token
+    pub event_type: *mut c_char,
+    /// The version token
+    pub version: *mut c_char,
+}
+
+/// The most recent error on the calling thread, or null if there was none
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn cim_subject_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Whether a string is a valid subject: `1`, `0`, or an error code
+///
+/// # Safety
+///
+/// `subject` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cim_subject_validate(subject: *const c_char) -> c_int {
+    status(str_arg(subject).map(|subject| Subject::new(subject).is_ok()))
+}
+
+/// Split a subject into its tokens, to be released with
+/// [`cim_subject_parts_free`]
+///
+/// # Safety
+///
+/// `subject` must be null or point to a NUL-terminated string, and `out`
+/// must be null or point to writable memory for a [`CimSubjectParts`].
+#[no_mangle]
+pub unsafe extern "C" fn cim_subject_parse(
+    subject: *const c_char,
+    out: *mut CimSubjectParts,
+) -> c_int {
+    if out.is_null() {
+        return fail(CIM_SUBJECT_ERR_ARGUMENT, "output pointer is null");
+    }
+    let subject = match str_arg(subject).and_then(|s| invalid(Subject::new(s))) {
+        Ok(subject) => subject,
+        Err((code, message)) => return fail(code, message),
+    };
+    let owned = |token: &str| {
+        // Subject tokens never contain NUL
+        CString::new(token).map_or(ptr::null_mut(), CString::into_raw)
+    };
+    out.write(CimSubjectParts {
+        context: owned(subject.context()),
+        aggregate: owned(subject.aggregate()),
+        event_type: owned(subject.event_type()),
+        version: owned(subject.version()),
+    });
+    CIM_SUBJECT_OK
+}
+
+/// Release the tokens written by [`cim_subject_parse`]
+///
+/// # Safety
+///
+/// `parts` must be null or point to tokens written by
+/// [`cim_subject_parse`] that have not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cim_subject_parts_free(parts: *mut CimSubjectParts) {
+    if let Some(parts) = parts.as_mut() {
+        for token in [
+            &mut parts.context,
+            &mut parts.aggregate,
+            &mut parts.event_type,
+            &mut parts.version,
+        ] {
+            if !token.is_null() {
+                drop(CString::from_raw(*token));
+                *token = ptr::null_mut();
+            }
+        }
+    }
+}
+
+/// Whether a pattern matches a subject: `1`, `0`, or an error code
+///
+/// # Safety
+///
+/// `pattern` and `subject` must each be null or point to a NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn cim_subject_pattern_matches(
+    pattern: *const c_char,
+    subject: *const c_char,
+) -> c_int {
+    status((|| {
+        let pattern = invalid(Pattern::new(str_arg(pattern)?))?;
+        let subject = invalid(Subject::new(str_arg(subject)?))?;
+        Ok(pattern.matches(&subject))
+    })())
+}
+
+/// Load a permission set from its JSON form, returning null on failure
+///
+/// Release it with [`cim_permissions_free`].
+///
+/// # Safety
+///
+/// `json` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cim_permissions_from_json(json: *const c_char) -> *mut CimPermissions {
+    let parsed = str_arg(json).and_then(|json| invalid(serde_json::from_str::<Permissions>(json)));
+    match parsed {
+        Ok(permissions) => Box::into_raw(Box::new(CimPermissions(permissions))),
+        Err((code, message)) => {
+            fail(code, message);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Release a permission set
+///
+/// # Safety
+///
+/// `permissions` must be null or a pointer returned by
+/// [`cim_permissions_from_json`] that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cim_permissions_free(permissions: *mut CimPermissions) {
+    if !permissions.is_null() {
+        drop(Box::from_raw(permissions));
+    }
+}
+
+/// Whether an operation is allowed on a subject: `1`, `0`, or an error code
+///
+/// Operations are named like their variants: `Publish`, `Subscribe`,
+/// `Request`, `QueueSubscribe` or `All`.
+///
+/// # Safety
+///
+/// `permissions` must be null or a live pointer from
+/// [`cim_permissions_from_json`], and `subject` and `operation` must each
+/// be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cim_permissions_check(
+    permissions: *const CimPermissions,
+    subject: *const c_char,
+    operation: *const c_char,
+) -> c_int {
+    status((|| {
+        let permissions = permissions.as_ref().ok_or((
+            CIM_SUBJECT_ERR_ARGUMENT,
+            "permissions pointer is null".to_string(),
+        ))?;
+        let subject = invalid(Subject::new(str_arg(subject)?))?;
+        let operation = str_arg(operation)?;
+        let operation: Operation = invalid(serde_json::from_value(serde_json::Value::String(
+            operation.to_string(),
+        )))?;
+        Ok(permissions.0.is_allowed(&subject, operation))
+    })())
+}
+
+type FfiResult<T> = Result<T, (c_int, String)>;
+
+/// Borrow a string argument
+unsafe fn str_arg<'a>(value: *const c_char) -> FfiResult<&'a str> {
+    if value.is_null() {
+        return Err((
+            CIM_SUBJECT_ERR_ARGUMENT,
+            "string argument is null".to_string(),
+        ));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|e| (CIM_SUBJECT_ERR_ARGUMENT, e.to_string()))
+}
+
+/// Report a rejected value as [`CIM_SUBJECT_ERR_INVALID`]
+fn invalid<T, E: std::fmt::Display>(result: Result<T, E>) -> FfiResult<T> {
+    result.map_err(|e| (CIM_SUBJECT_ERR_INVALID, e.to_string()))
+}
+
+/// Convert an answer to `1` or `0`, recording any error
+fn status(result: FfiResult<bool>) -> c_int {
+    match result {
+        Ok(answer) => c_int::from(answer),
+        Err((code, message)) => fail(code, message),
+    }
+}
+
+/// Record an error for [`cim_subject_last_error`] and return its code
+fn fail(code: c_int, message: impl Into<String>) -> c_int {
+    // Error messages come from Display impls, which never contain NUL
+    let message = CString::new(message.into()).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{
+        PermissionRule,
+        Policy,
+    };
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_validate_parse_and_match() {
+        let subject = c("orders.order.placed.v1");
+        unsafe {
+            assert_eq!(cim_subject_validate(subject.as_ptr()), 1);
+            assert_eq!(cim_subject_validate(c("orders").as_ptr()), 0);
+            assert_eq!(cim_subject_validate(ptr::null()), CIM_SUBJECT_ERR_ARGUMENT);
+
+            let mut parts = CimSubjectParts {
+                context: ptr::null_mut(),
+                aggregate: ptr::null_mut(),
+                event_type: ptr::null_mut(),
+                version: ptr::null_mut(),
+            };
+            assert_eq!(
+                cim_subject_parse(subject.as_ptr(), &mut parts),
+                CIM_SUBJECT_OK
+            );
+            assert_eq!(CStr::from_ptr(parts.event_type).to_str(), Ok("placed"));
+            cim_subject_parts_free(&mut parts);
+            assert!(parts.context.is_null());
+
+            assert_eq!(
+                cim_subject_pattern_matches(c("orders.*.placed.>").as_ptr(), subject.as_ptr()),
+                1
+            );
+            assert_eq!(
+                cim_subject_pattern_matches(c("orders..>").as_ptr(), subject.as_ptr()),
+                CIM_SUBJECT_ERR_INVALID
+            );
+            let error = CStr::from_ptr(cim_subject_last_error()).to_str().unwrap();
+            assert!(error.starts_with("Invalid pattern"), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_permission_checks() {
+        let mut rules = Permissions::new(Policy::Deny);
+        rules.add_rule(PermissionRule::new(
+            Pattern::new("orders.>").unwrap(),
+            [Operation::Publish].into_iter().collect(),
+            Policy::Allow,
+        ));
+        let json = serde_json::to_string(&rules).unwrap();
+        let subject = c("orders.order.placed.v1");
+        unsafe {
+            let permissions = cim_permissions_from_json(c(&json).as_ptr());
+            assert!(!permissions.is_null());
+            let check = |operation: &str| {
+                cim_permissions_check(permissions, subject.as_ptr(), c(operation).as_ptr())
+            };
+            assert_eq!(check("Publish"), 1);
+            assert_eq!(check("Subscribe"), 0);
+            assert_eq!(check("Delete"), CIM_SUBJECT_ERR_INVALID);
+            cim_permissions_free(permissions);
+
+            assert!(cim_permissions_from_json(c("{}").as_ptr()).is_null());
+        }
+    }
+}
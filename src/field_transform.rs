@@ -0,0 +1,551 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Field transform expressions for schema mappings
+//!
+//! [`FieldMapping::transform`](crate::translator::FieldMapping) holds a small
+//! expression describing how a value changes while it is moved between
+//! schemas. Expressions are pipelines of built-in steps separated by `|`:
+//!
+//! | Step | Effect |
+//! |------|--------|
+//! | `lowercase`, `uppercase`, `trim` | String case and whitespace |
+//! | `to_string` | Render numbers and booleans as strings |
+//! | `rename(PENDING=pending, DONE=done)` | Rename enum variants |
+//! | `timestamp(seconds->millis)` | Convert between `seconds`, `millis` and `rfc3339` |
+//! | `const(<json>)` | Inject a constant, ignoring the input |
+//!
+//! For example `trim | lowercase | rename(active=enabled)`.
+
+use std::fmt::{
+    self,
+    Display,
+};
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+
+/// Units for timestamp conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampUnit {
+    /// Unix epoch seconds
+    Seconds,
+    /// Unix epoch milliseconds
+    Millis,
+    /// RFC 3339 UTC string (`2025-01-22T10:00:00Z`)
+    Rfc3339,
+}
+
+impl TimestampUnit {
+    fn name(self) -> &'static str {
+        match self {
+            TimestampUnit::Seconds => "seconds",
+            TimestampUnit::Millis => "millis",
+            TimestampUnit::Rfc3339 => "rfc3339",
+        }
+    }
+}
+
+impl FromStr for TimestampUnit {
+    type Err = SubjectError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "seconds" => Ok(TimestampUnit::Seconds),
+            "millis" => Ok(TimestampUnit::Millis),
+            "rfc3339" => Ok(TimestampUnit::Rfc3339),
+            other => Err(SubjectError::parse_error(format!(
+                "Unknown timestamp unit '{other}'"
+            ))),
+        }
+    }
+}
+
+/// A single built-in transform step
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformStep {
+    /// Lowercase a string
+    Lowercase,
+    /// Uppercase a string
+    Uppercase,
+    /// Trim surrounding whitespace from a string
+    Trim,
+    /// Render a scalar as a string
+    ToString,
+    /// Rename enum variants (string values), leaving unknown values as-is
+    Rename(Vec<(String, String)>),
+    /// Convert a timestamp between units
+    Timestamp {
+        /// Unit of the input value
+        from: TimestampUnit,
+        /// Unit of the output value
+        to: TimestampUnit,
+    },
+    /// Replace the value with a constant
+    Const(Value),
+}
+
+impl TransformStep {
+    /// Apply this step to a value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value has the wrong type for the step
+    pub fn apply(&self, value: Value) -> Result<Value> {
+        match self {
+            TransformStep::Lowercase => map_string(value, "lowercase", str::to_lowercase),
+            TransformStep::Uppercase => map_string(value, "uppercase", str::to_uppercase),
+            TransformStep::Trim => map_string(value, "trim", |s| s.trim().to_string()),
+            TransformStep::ToString => Ok(match value {
+                Value::String(s) => Value::String(s),
+                other => Value::String(other.to_string()),
+            }),
+            TransformStep::Rename(pairs) => Ok(match value {
+                Value::String(s) => Value::String(
+                    pairs
+                        .iter()
+                        .find(|(from, _)| *from == s)
+                        .map_or(s, |(_, to)| to.clone()),
+                ),
+                other => other,
+            }),
+            TransformStep::Timestamp { from, to } => convert_timestamp(&value, *from, *to),
+            TransformStep::Const(constant) => Ok(constant.clone()),
+        }
+    }
+
+    /// The step that undoes this one, if it is lossless
+    ///
+    /// `rename` is treated as invertible when its targets are distinct, which
+    /// assumes values outside the declared variants do not occur.
+    #[must_use]
+    pub fn inverse(&self) -> Option<TransformStep> {
+        match self {
+            TransformStep::Rename(pairs) => {
+                let mut targets: Vec<&String> = pairs.iter().map(|(_, to)| to).collect();
+                targets.sort();
+                targets.dedup();
+                (targets.len() == pairs.len()).then(|| {
+                    TransformStep::Rename(
+                        pairs
+                            .iter()
+                            .map(|(from, to)| (to.clone(), from.clone()))
+                            .collect(),
+                    )
+                })
+            },
+            // Dropping milliseconds loses information
+            TransformStep::Timestamp { from, to }
+                if *from != TimestampUnit::Millis || *to == TimestampUnit::Millis =>
+            {
+                Some(TransformStep::Timestamp {
+                    from: *to,
+                    to: *from,
+                })
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Display for TransformStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformStep::Lowercase => write!(f, "lowercase"),
+            TransformStep::Uppercase => write!(f, "uppercase"),
+            TransformStep::Trim => write!(f, "trim"),
+            TransformStep::ToString => write!(f, "to_string"),
+            TransformStep::Rename(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(from, to)| format!("{from}={to}"))
+                    .collect();
+                write!(f, "rename({})", pairs.join(", "))
+            },
+            TransformStep::Timestamp { from, to } => {
+                write!(f, "timestamp({}->{})", from.name(), to.name())
+            },
+            TransformStep::Const(value) => write!(f, "const({value})"),
+        }
+    }
+}
+
+/// A pipeline of transform steps
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldTransform {
+    /// Steps applied in order
+    pub steps: Vec<TransformStep>,
+}
+
+impl FieldTransform {
+    /// Parse a transform expression
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a step is unknown or has malformed arguments
+    pub fn parse(expression: &str) -> Result<Self> {
+        let steps = split_top_level(expression, '|')
+            .into_iter()
+            .map(parse_step)
+            .collect::<Result<Vec<_>>>()?;
+
+        if steps.is_empty() {
+            return Err(SubjectError::parse_error("Empty transform expression"));
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Apply the pipeline to a value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any step fails
+    pub fn apply(&self, value: Value) -> Result<Value> {
+        self.steps
+            .iter()
+            .try_fold(value, |value, step| step.apply(value))
+    }
+
+    /// The pipeline that undoes this one, if every step is invertible
+    #[must_use]
+    pub fn inverse(&self) -> Option<FieldTransform> {
+        let steps = self
+            .steps
+            .iter()
+            .rev()
+            .map(TransformStep::inverse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { steps })
+    }
+
+    /// The first step that cannot be inverted, if any
+    #[must_use]
+    pub fn non_invertible_step(&self) -> Option<&TransformStep> {
+        self.steps.iter().find(|step| step.inverse().is_none())
+    }
+
+    /// Check if the pipeline produces a constant regardless of input
+    #[must_use]
+    pub fn is_constant(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| matches!(step, TransformStep::Const(_)))
+    }
+}
+
+impl Display for FieldTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self.steps.iter().map(ToString::to_string).collect();
+        write!(f, "{}", steps.join(" | "))
+    }
+}
+
+impl FromStr for FieldTransform {
+    type Err = SubjectError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Parse one step such as `lowercase` or `rename(a=b)`
+fn parse_step(step: &str) -> Result<TransformStep> {
+    let step = step.trim();
+    let (name, args) = match step.split_once('(') {
+        Some((name, rest)) => {
+            let args = rest.strip_suffix(')').ok_or_else(|| {
+                SubjectError::parse_error(format!("Unclosed arguments in transform '{step}'"))
+            })?;
+            (name.trim(), Some(args.trim()))
+        },
+        None => (step, None),
+    };
+
+    match (name, args) {
+        ("lowercase", None) => Ok(TransformStep::Lowercase),
+        ("uppercase", None) => Ok(TransformStep::Uppercase),
+        ("trim", None) => Ok(TransformStep::Trim),
+        ("to_string", None) => Ok(TransformStep::ToString),
+        ("rename", Some(args)) => {
+            let pairs = split_top_level(args, ',')
+                .into_iter()
+                .map(|pair| {
+                    pair.split_once('=')
+                        .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+                        .ok_or_else(|| {
+                            SubjectError::parse_error(format!(
+                                "Rename pair '{pair}' must be 'from=to'"
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(TransformStep::Rename(pairs))
+        },
+        ("timestamp", Some(args)) => {
+            let (from, to) = args.split_once("->").ok_or_else(|| {
+                SubjectError::parse_error(format!(
+                    "Timestamp transform '{args}' must be 'from->to'"
+                ))
+            })?;
+            Ok(TransformStep::Timestamp {
+                from: from.parse()?,
+                to: to.parse()?,
+            })
+        },
+        ("const", Some(args)) => serde_json::from_str(args)
+            .map(TransformStep::Const)
+            .map_err(|e| SubjectError::parse_error(format!("Invalid constant '{args}': {e}"))),
+        _ => Err(SubjectError::parse_error(format!(
+            "Unknown transform step '{step}'"
+        ))),
+    }
+}
+
+/// Split on a separator outside of parentheses and quotes
+fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' | '[' | '{' if !in_quotes => depth += 1,
+            ')' | ']' | '}' if !in_quotes => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 && !in_quotes => {
+                parts.push(input[start..i].trim());
+                start = i + c.len_utf8();
+            },
+            _ => {},
+        }
+    }
+    parts.push(input[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn map_string(value: Value, step: &str, f: impl Fn(&str) -> String) -> Result<Value> {
+    match value {
+        Value::String(s) => Ok(Value::String(f(&s))),
+        other => Err(SubjectError::translation_error(format!(
+            "Transform '{step}' expects a string, got {other}"
+        ))),
+    }
+}
+
+fn convert_timestamp(value: &Value, from: TimestampUnit, to: TimestampUnit) -> Result<Value> {
+    let invalid = || {
+        SubjectError::translation_error(format!(
+            "Value {value} is not a valid {} timestamp",
+            from.name()
+        ))
+    };
+
+    let overflow = || {
+        SubjectError::translation_error(format!(
+            "Timestamp {value} is out of range for {}",
+            to.name()
+        ))
+    };
+
+    let millis = match from {
+        TimestampUnit::Seconds => value
+            .as_i64()
+            .ok_or_else(invalid)?
+            .checked_mul(1000)
+            .ok_or_else(overflow)?,
+        TimestampUnit::Millis => value.as_i64().ok_or_else(invalid)?,
+        TimestampUnit::Rfc3339 => {
+            parse_rfc3339(value.as_str().ok_or_else(invalid)?).ok_or_else(invalid)? * 1000
+        },
+    };
+
+    Ok(match to {
+        TimestampUnit::Seconds => Value::from(millis.div_euclid(1000)),
+        TimestampUnit::Millis => Value::from(millis),
+        TimestampUnit::Rfc3339 => {
+            Value::String(format_rfc3339(millis.div_euclid(1000)).ok_or_else(overflow)?)
+        },
+    })
+}
+
+/// Format epoch seconds as an RFC 3339 UTC timestamp
+///
+/// `None` if the year falls outside `0000..=9999`.
+fn format_rfc3339(seconds: i64) -> Option<String> {
+    let days = seconds.div_euclid(86_400);
+    let secs_of_day = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+    Some(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    ))
+}
+
+/// Parse an RFC 3339 UTC timestamp (`Z` suffix, no fractional seconds)
+fn parse_rfc3339(input: &str) -> Option<i64> {
+    let input = input.strip_suffix('Z')?;
+    let (date, time) = input.split_once('T')?;
+    let field = |part: &str| {
+        part.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| part.parse::<i64>().ok())
+            .flatten()
+    };
+    let mut date = date.split('-').map(field);
+    let mut time = time.split(':').map(field);
+
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+
+    if date.next().is_some()
+        || time.next().is_some()
+        || year > 9999
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Number of days in a month of the proleptic Gregorian calendar
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date for days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_apply_pipeline() {
+        let transform = FieldTransform::parse("trim | lowercase | rename(active=enabled)").unwrap();
+        assert_eq!(transform.steps.len(), 3);
+        assert_eq!(
+            transform.apply(json!("  ACTIVE ")).unwrap(),
+            json!("enabled")
+        );
+        assert_eq!(transform.apply(json!("Paused")).unwrap(), json!("paused"));
+        assert!(transform.apply(json!(42)).is_err());
+
+        // Display round-trips through the parser
+        let reparsed = FieldTransform::parse(&transform.to_string()).unwrap();
+        assert_eq!(reparsed, transform);
+    }
+
+    #[test]
+    fn test_timestamp_and_constant_steps() {
+        let to_rfc = FieldTransform::parse("timestamp(seconds->rfc3339)").unwrap();
+        assert_eq!(
+            to_rfc.apply(json!(1_737_540_000)).unwrap(),
+            json!("2025-01-22T10:00:00Z")
+        );
+
+        let to_secs = FieldTransform::parse("timestamp(rfc3339->seconds)").unwrap();
+        assert_eq!(
+            to_secs.apply(json!("2025-01-22T10:00:00Z")).unwrap(),
+            json!(1_737_540_000)
+        );
+
+        let to_millis = FieldTransform::parse("timestamp(seconds->millis)").unwrap();
+        assert_eq!(to_millis.apply(json!(2)).unwrap(), json!(2000));
+
+        let constant = FieldTransform::parse(r#"const({"source": "a|b"})"#).unwrap();
+        assert!(constant.is_constant());
+        assert_eq!(
+            constant.apply(Value::Null).unwrap(),
+            json!({"source": "a|b"})
+        );
+
+        for invalid in [
+            "2025-13-01T00:00:00Z",
+            "2025-02-29T00:00:00Z",
+            "2025-04-31T00:00:00Z",
+            "2025-01-22T24:00:00Z",
+            "2025-01-22T10:60:00Z",
+            "2025-01-22T10:00:60Z",
+            "2025-01-22T10:00:-1Z",
+            "2025-01-22-01T10:00:00Z",
+        ] {
+            assert!(to_secs.apply(json!(invalid)).is_err(), "{invalid}");
+        }
+        assert_eq!(
+            to_secs.apply(json!("2024-02-29T00:00:00Z")).unwrap(),
+            json!(1_709_164_800)
+        );
+        assert!(to_millis.apply(json!(i64::MAX)).is_err());
+        assert!(to_rfc.apply(json!(i64::MAX / 1000)).is_err());
+
+        assert!(FieldTransform::parse("reverse").is_err());
+        assert!(FieldTransform::parse("timestamp(days->seconds)").is_err());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let transform =
+            FieldTransform::parse("timestamp(seconds->millis) | rename(a=x, b=y)").unwrap();
+        let inverse = transform.inverse().unwrap();
+        assert_eq!(
+            inverse.to_string(),
+            "rename(x=a, y=b) | timestamp(millis->seconds)"
+        );
+
+        let lossy = FieldTransform::parse("rename(a=x, b=x)").unwrap();
+        assert!(lossy.inverse().is_none());
+        let lossy = FieldTransform::parse("trim | timestamp(millis->seconds)").unwrap();
+        assert_eq!(lossy.non_invertible_step(), Some(&TransformStep::Trim));
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Flow discovery from observed correlation chains
+//!
+//! Where an [`ExpectedFlow`](crate::expected_flow::ExpectedFlow) states how
+//! messages should flow, a [`FlowDiscovery`] shows how they actually do.
+//! It folds many subject-annotated chains into one graph of subjects: each
+//! edge links a message's subject to that of its nearest cause with a
+//! subject, and counts how often, and in how many chains, it occurred. The
+//! resulting [`DiscoveredFlow`] points out loops and rarely taken branches
+//! and renders as Mermaid or DOT.
+//!
+//! ```
+//! use std::time::SystemTime;
+//!
+//! use cim_subject::flow_discovery::FlowDiscovery;
+//! use cim_subject::message_algebra::{
+//!     ChainEntry,
+//!     CorrelationChain,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let mut discovery = FlowDiscovery::new();
+//! for next in [
+//!     "orders.events.order.paid",
+//!     "orders.events.order.paid",
+//!     "orders.events.order.voided",
+//! ] {
+//!     let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//!     let effect = MessageIdentity::caused_by(
+//!         IdType::Uuid(Uuid::new_v4()),
+//!         root.correlation_id.clone(),
+//!         root.message_id.clone(),
+//!     );
+//!     let mut chain = CorrelationChain::from_entry(
+//!         ChainEntry::new(root, SystemTime::now())
+//!             .with_subject(Subject::new("orders.events.order.placed")?),
+//!     )
+//!     .unwrap();
+//!     chain
+//!         .add_entry(ChainEntry::new(effect, SystemTime::now()).with_subject(Subject::new(next)?))
+//!         .unwrap();
+//!     discovery.observe(&chain);
+//! }
+//!
+//! let flow = discovery.discover();
+//! assert_eq!(flow.chains(), 3);
+//! assert_eq!(flow.edges().len(), 2);
+//! let rare: Vec<&str> = flow
+//!     .rare_edges(0.5)
+//!     .iter()
+//!     .map(|edge| edge.to.as_str())
+//!     .collect();
+//! assert_eq!(rare, ["orders.events.order.voided"]);
+//! assert!(flow.to_mermaid().contains("-->|2|"));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    HashSet,
+};
+use std::fmt::Write as _;
+
+use crate::correlation::IdType;
+use crate::message_algebra::CorrelationChain;
+
+/// A subject seen in the observed chains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowNode {
+    /// The subject
+    pub subject: String,
+    /// Messages seen on the subject
+    pub count: u64,
+    /// Messages on the subject with no annotated cause
+    pub starts: u64,
+}
+
+/// A cause-to-effect step between two subjects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowEdge {
+    /// The cause's subject
+    pub from: String,
+    /// The effect's subject
+    pub to: String,
+    /// Times the step occurred
+    pub count: u64,
+    /// Chains in which the step occurred at least once
+    pub chains: u64,
+}
+
+/// Folds subject-annotated chains into a flow graph
+#[derive(Debug, Clone, Default)]
+pub struct FlowDiscovery {
+    chains: u64,
+    nodes: BTreeMap<String, (u64, u64)>,
+    edges: BTreeMap<(String, String), (u64, u64)>,
+}
+
+impl FlowDiscovery {
+    /// Create a discovery that has observed nothing
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chain to the graph
+    ///
+    /// Messages without a subject are skipped: their effects are linked to
+    /// the nearest cause that has one.
+    pub fn observe(&mut self, chain: &CorrelationChain) {
+        self.chains += 1;
+        let mut in_chain = HashSet::new();
+        for (message_id, subject) in &chain.subjects {
+            let node = self.nodes.entry(subject.as_str().to_string()).or_default();
+            node.0 += 1;
+            let Some(cause) = annotated_cause(chain, message_id) else {
+                node.1 += 1;
+                continue;
+            };
+            let key = (cause.to_string(), subject.as_str().to_string());
+            let edge = self.edges.entry(key.clone()).or_default();
+            edge.0 += 1;
+            if in_chain.insert(key) {
+                edge.1 += 1;
+            }
+        }
+    }
+
+    /// The flow graph of all chains observed so far
+    #[must_use]
+    pub fn discover(&self) -> DiscoveredFlow {
+        DiscoveredFlow {
+            chains: self.chains,
+            nodes: self
+                .nodes
+                .iter()
+                .map(|(subject, &(count, starts))| FlowNode {
+                    subject: subject.clone(),
+                    count,
+                    starts,
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|((from, to), &(count, chains))| FlowEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    count,
+                    chains,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The subject of the nearest cause that has one
+fn annotated_cause<'a>(chain: &'a CorrelationChain, message_id: &'a IdType) -> Option<&'a str> {
+    let mut visited = HashSet::from([message_id]);
+    let mut current = message_id;
+    while let Some(parent) = chain.causation_graph.get(current) {
+        if !visited.insert(parent) {
+            return None;
+        }
+        if let Some(subject) = chain.subjects.get(parent) {
+            return Some(subject.as_str());
+        }
+        current = parent;
+    }
+    None
+}
+
+/// The flow graph discovered from a set of chains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredFlow {
+    chains: u64,
+    nodes: Vec<FlowNode>,
+    edges: Vec<FlowEdge>,
+}
+
+impl DiscoveredFlow {
+    /// Number of chains observed
+    #[must_use]
+    pub fn chains(&self) -> u64 {
+        self.chains
+    }
+
+    /// Subjects, sorted
+    #[must_use]
+    pub fn nodes(&self) -> &[FlowNode] {
+        &self.nodes
+    }
+
+    /// Steps, sorted by cause then effect
+    #[must_use]
+    pub fn edges(&self) -> &[FlowEdge] {
+        &self.edges
+    }
+
+    /// Fraction of chains in which `edge` occurred
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn support(&self, edge: &FlowEdge) -> f64 {
+        if self.chains == 0 {
+            0.0
+        } else {
+            edge.chains as f64 / self.chains as f64
+        }
+    }
+
+    /// Steps occurring in less than `min_support` of the chains
+    #[must_use]
+    pub fn rare_edges(&self, min_support: f64) -> Vec<&FlowEdge> {
+        self.edges
+            .iter()
+            .filter(|edge| self.support(edge) < min_support)
+            .collect()
+    }
+
+    /// Steps that lie on a loop, i.e. whose effect's subject can lead back
+    /// to their cause's subject
+    #[must_use]
+    pub fn loops(&self) -> Vec<&FlowEdge> {
+        self.edges
+            .iter()
+            .filter(|edge| self.reaches(&edge.to, &edge.from))
+            .collect()
+    }
+
+    fn reaches(&self, from: &str, to: &str) -> bool {
+        let mut visited = HashSet::from([from]);
+        let mut stack = vec![from];
+        while let Some(subject) = stack.pop() {
+            if subject == to {
+                return true;
+            }
+            for edge in self.edges.iter().filter(|edge| edge.from == subject) {
+                if visited.insert(&edge.to) {
+                    stack.push(&edge.to);
+                }
+            }
+        }
+        false
+    }
+
+    /// Render the flow as a Mermaid flowchart, edges labelled with their
+    /// counts and subjects that start chains drawn round
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let index = self.index();
+        let mut out = String::from("graph TD\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.starts > 0 {
+                let _ = writeln!(out, "    n{i}((\"{}\"))", node.subject);
+            } else {
+                let _ = writeln!(out, "    n{i}[\"{}\"]", node.subject);
+            }
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "    n{} -->|{}| n{}",
+                index[edge.from.as_str()],
+                edge.count,
+                index[edge.to.as_str()]
+            );
+        }
+        out
+    }
+
+    /// Render the flow as a Graphviz DOT digraph, edges labelled with their
+    /// counts and subjects that start chains drawn as double circles
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph flow {\n");
+        for node in &self.nodes {
+            let shape = if node.starts > 0 {
+                "doublecircle"
+            } else {
+                "box"
+            };
+            let _ = writeln!(out, "  \"{}\" [shape={shape}];", node.subject);
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                edge.from, edge.to, edge.count
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn index(&self) -> BTreeMap<&str, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.subject.as_str(), i))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageIdentity;
+    use crate::message_algebra::ChainEntry;
+    use crate::subject::Subject;
+
+    /// A linear chain through `subjects`; `None` leaves a message unannotated
+    fn chain(subjects: &[Option<&str>]) -> CorrelationChain {
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let entry = |identity: MessageIdentity, subject: Option<&str>| {
+            let entry = ChainEntry::new(identity, SystemTime::now());
+            match subject {
+                Some(subject) => entry.with_subject(Subject::new(subject).unwrap()),
+                None => entry,
+            }
+        };
+        let mut chain = CorrelationChain::from_entry(entry(root.clone(), subjects[0])).unwrap();
+        let mut parent = root;
+        for subject in &subjects[1..] {
+            let identity = MessageIdentity::caused_by(
+                IdType::Uuid(Uuid::new_v4()),
+                parent.correlation_id.clone(),
+                parent.message_id.clone(),
+            );
+            chain.add_entry(entry(identity.clone(), *subject)).unwrap();
+            parent = identity;
+        }
+        chain
+    }
+
+    #[test]
+    fn test_loops_and_contracted_edges() {
+        let mut discovery = FlowDiscovery::new();
+        discovery.observe(&chain(&[
+            Some("payments.commands.payment.request"),
+            Some("payments.events.payment.failed"),
+            Some("payments.commands.payment.request"),
+            None,
+            Some("payments.events.payment.succeeded"),
+        ]));
+        let flow = discovery.discover();
+
+        let request = flow
+            .nodes()
+            .iter()
+            .find(|node| node.subject == "payments.commands.payment.request")
+            .unwrap();
+        assert_eq!((request.count, request.starts), (2, 1));
+        // The unannotated message is contracted away
+        assert!(flow
+            .edges()
+            .iter()
+            .any(|edge| edge.from == "payments.commands.payment.request"
+                && edge.to == "payments.events.payment.succeeded"));
+
+        let loops: Vec<(&str, &str)> = flow
+            .loops()
+            .into_iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(loops, [
+            (
+                "payments.commands.payment.request",
+                "payments.events.payment.failed"
+            ),
+            (
+                "payments.events.payment.failed",
+                "payments.commands.payment.request"
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_counts_and_rendering() {
+        let mut discovery = FlowDiscovery::new();
+        let placed = Some("orders.events.order.placed");
+        let shipped = Some("orders.events.order.shipped");
+        discovery.observe(&chain(&[placed, shipped]));
+        discovery.observe(&chain(&[placed, shipped, placed, shipped]));
+        let flow = discovery.discover();
+
+        let forward = &flow.edges()[0];
+        assert_eq!((forward.count, forward.chains), (3, 2));
+        assert!((flow.support(forward) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(flow.rare_edges(0.6).len(), 1);
+
+        let dot = flow.to_dot();
+        assert!(dot.contains(
+            "\"orders.events.order.placed\" -> \"orders.events.order.shipped\" [label=\"3\"];"
+        ));
+        assert!(dot.contains("\"orders.events.order.placed\" [shape=doublecircle];"));
+        assert!(flow.to_mermaid().contains("n0 -->|3| n1"));
+    }
+}
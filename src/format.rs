@@ -0,0 +1,236 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Wire formats for payloads, messages, identities and chain exports
+//!
+//! JSON is always available. CBOR and `MessagePack` need the `cbor` and
+//! `msgpack` features; encoding or decoding with a format whose feature is
+//! disabled is an error rather than a missing variant, so a
+//! [`PayloadFormat`] read from a header or a config file always parses.
+//! Any serializable type can be encoded, including [`NatsMessage`],
+//! [`MessageIdentity`] and [`ChainGraph`].
+//!
+//! [`NatsMessage`]: crate::translator::NatsMessage
+//! [`MessageIdentity`]: crate::correlation::MessageIdentity
+//! [`ChainGraph`]: crate::message_algebra::ChainGraph
+//!
+//! ```
+//! use cim_subject::format::PayloadFormat;
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//! };
+//! use uuid::Uuid;
+//!
+//! let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let bytes = PayloadFormat::Json.encode(&identity)?;
+//! let decoded: MessageIdentity = PayloadFormat::Json.decode(&bytes)?;
+//! assert_eq!(decoded, identity);
+//!
+//! let format = PayloadFormat::from_content_type("application/cbor; charset=binary");
+//! assert_eq!(format, Some(PayloadFormat::Cbor));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use serde::de::DeserializeOwned;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+
+/// How a payload or value is encoded on the wire
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    /// JSON
+    #[default]
+    Json,
+    /// CBOR (RFC 8949); needs the `cbor` feature
+    Cbor,
+    /// `MessagePack`, with struct fields as map keys; needs the `msgpack`
+    /// feature
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+impl PayloadFormat {
+    /// The format's MIME type, for a `Content-Type` header
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Cbor => "application/cbor",
+            Self::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// The format named by a `Content-Type` value, ignoring parameters and
+    /// case
+    #[must_use]
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        [
+            ("application/json", Self::Json),
+            ("application/cbor", Self::Cbor),
+            ("application/msgpack", Self::MessagePack),
+            ("application/x-msgpack", Self::MessagePack),
+            ("application/vnd.msgpack", Self::MessagePack),
+        ]
+        .into_iter()
+        .find(|(name, _)| essence.eq_ignore_ascii_case(name))
+        .map(|(_, format)| format)
+    }
+
+    /// Check if this build can encode and decode the format
+    #[must_use]
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Json => true,
+            Self::Cbor => cfg!(feature = "cbor"),
+            Self::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+
+    /// Encode `value` in this format
+    ///
+    /// # Errors
+    ///
+    /// Returns a translation error if the format is not available or the
+    /// value cannot be encoded
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>> {
+        self.ensure_available()?;
+        let encoded = match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes)
+                    .map(|()| bytes)
+                    .map_err(|e| e.to_string())
+            },
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("checked by ensure_available"),
+        };
+        encoded.map_err(|e| SubjectError::translation_error(format!("Cannot encode {self}: {e}")))
+    }
+
+    /// Decode a value from bytes in this format
+    ///
+    /// # Errors
+    ///
+    /// Returns a translation error if the format is not available, or a
+    /// parse error if the bytes are not a valid encoding of `T`
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        self.ensure_available()?;
+        let decoded = match self {
+            Self::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("checked by ensure_available"),
+        };
+        decoded.map_err(|e| SubjectError::parse_error(format!("Invalid {self}: {e}")))
+    }
+
+    fn ensure_available(self) -> Result<()> {
+        if self.is_available() {
+            return Ok(());
+        }
+        let feature = if self == Self::Cbor {
+            "cbor"
+        } else {
+            "msgpack"
+        };
+        Err(SubjectError::translation_error(format!(
+            "{self} support requires the `{feature}` feature"
+        )))
+    }
+}
+
+impl Display for PayloadFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "JSON"),
+            Self::Cbor => write!(f, "CBOR"),
+            Self::MessagePack => write!(f, "MessagePack"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::{
+        IdType,
+        MessageIdentity,
+    };
+    use crate::translator::NatsMessage;
+
+    #[test]
+    fn test_round_trips() {
+        let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let message = NatsMessage::with_correlation(
+            "orders.order.placed.v1".to_string(),
+            serde_json::json!({"order_id": 42, "lines": [{"sku": "A-1"}]}),
+            &identity,
+        )
+        .with_format(PayloadFormat::Cbor);
+
+        for format in [
+            PayloadFormat::Json,
+            PayloadFormat::Cbor,
+            PayloadFormat::MessagePack,
+        ] {
+            if !format.is_available() {
+                assert!(format.encode(&identity).is_err());
+                continue;
+            }
+            let bytes = format.encode(&identity).unwrap();
+            assert_eq!(format.decode::<MessageIdentity>(&bytes).unwrap(), identity);
+
+            let bytes = format.encode(&message).unwrap();
+            let decoded: NatsMessage = format.decode(&bytes).unwrap();
+            assert_eq!(decoded.payload, message.payload);
+            assert_eq!(decoded.payload_format, PayloadFormat::Cbor);
+            assert!(format.decode::<MessageIdentity>(b"\xff\x00").is_err());
+        }
+    }
+
+    #[test]
+    fn test_content_types() {
+        for format in [
+            PayloadFormat::Json,
+            PayloadFormat::Cbor,
+            PayloadFormat::MessagePack,
+        ] {
+            assert_eq!(
+                PayloadFormat::from_content_type(format.content_type()),
+                Some(format)
+            );
+        }
+        assert_eq!(
+            PayloadFormat::from_content_type("Application/X-MsgPack"),
+            Some(PayloadFormat::MessagePack)
+        );
+        assert_eq!(PayloadFormat::from_content_type("text/plain"), None);
+        assert_eq!(
+            serde_json::to_string(&PayloadFormat::MessagePack).unwrap(),
+            "\"msgpack\""
+        );
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Header names for carrying message identities
+//!
+//! A [`HeaderConvention`] names the headers that hold a message's ID,
+//! correlation ID and causation ID, and is used both to write an identity
+//! into headers and to read it back. The default is the `X-Message-ID` /
+//! `X-Correlation-ID` / `X-Causation-ID` set; presets cover NATS
+//! deduplication and `CloudEvents` binary mode, and any convention can be
+//! lowercased for transports that normalise header case.
+//!
+//! ```
+//! use cim_subject::{
+//!     HeaderConvention,
+//!     MessageFactory,
+//! };
+//! use uuid::Uuid;
+//!
+//! let convention = HeaderConvention::cloud_events("urn:service:orders");
+//! let identity = MessageFactory::create_root_command(Uuid::new_v4());
+//! let headers = convention.to_headers(&identity);
+//! assert!(headers.contains(&("ce-source".to_string(), "urn:service:orders".to_string())));
+//!
+//! let parsed = convention
+//!     .parse(headers.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+//!     .unwrap();
+//! assert_eq!(parsed.message_id, identity.message_id);
+//! ```
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::correlation::{
+    CausationId,
+    CorrelationError,
+    CorrelationId,
+    IdType,
+    MessageIdentity,
+    Result,
+};
+
+/// Header names used to carry a [`MessageIdentity`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderConvention {
+    /// Header holding the message ID
+    pub message_id: String,
+    /// Header holding the correlation ID
+    pub correlation_id: String,
+    /// Header holding the causation ID
+    pub causation_id: String,
+    /// Fixed headers written alongside the identity and ignored when parsing
+    pub static_headers: Vec<(String, String)>,
+}
+
+impl Default for HeaderConvention {
+    fn default() -> Self {
+        Self::custom("X-Message-ID", "X-Correlation-ID", "X-Causation-ID")
+    }
+}
+
+impl HeaderConvention {
+    /// A convention with the given header names
+    #[must_use]
+    pub fn custom(
+        message_id: impl Into<String>,
+        correlation_id: impl Into<String>,
+        causation_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            message_id: message_id.into(),
+            correlation_id: correlation_id.into(),
+            causation_id: causation_id.into(),
+            static_headers: Vec::new(),
+        }
+    }
+
+    /// The message ID in `Nats-Msg-Id`, so `JetStream` deduplicates on it
+    #[must_use]
+    pub fn nats() -> Self {
+        Self::custom("Nats-Msg-Id", "X-Correlation-ID", "X-Causation-ID")
+    }
+
+    /// `CloudEvents` binary mode: `ce-id` plus the correlation extension's
+    /// `ce-correlationid` and `ce-causationid`, with `ce-specversion` and
+    /// `ce-source` as fixed headers
+    #[must_use]
+    pub fn cloud_events(source: impl Into<String>) -> Self {
+        Self::custom("ce-id", "ce-correlationid", "ce-causationid")
+            .with_static_header("ce-specversion", "1.0")
+            .with_static_header("ce-source", source)
+    }
+
+    /// Lowercase every header name
+    #[must_use]
+    pub fn lowercase(mut self) -> Self {
+        for name in [
+            &mut self.message_id,
+            &mut self.correlation_id,
+            &mut self.causation_id,
+        ] {
+            *name = name.to_lowercase();
+        }
+        for (name, _) in &mut self.static_headers {
+            *name = name.to_lowercase();
+        }
+        self
+    }
+
+    /// Add a fixed header written with every identity
+    #[must_use]
+    pub fn with_static_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.static_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Write an identity as headers, followed by the fixed headers
+    ///
+    /// Values are the bare IDs, without the `correlation:` and `causation:`
+    /// prefixes their `Display` adds.
+    #[must_use]
+    pub fn to_headers(&self, identity: &MessageIdentity) -> Vec<(String, String)> {
+        let mut headers = vec![
+            (self.message_id.clone(), identity.message_id.to_string()),
+            (
+                self.correlation_id.clone(),
+                identity.correlation_id.0.to_string(),
+            ),
+            (
+                self.causation_id.clone(),
+                identity.causation_id.0.to_string(),
+            ),
+        ];
+        headers.extend(self.static_headers.iter().cloned());
+        headers
+    }
+
+    /// Read an identity from headers
+    ///
+    /// Header names must match exactly; unrelated headers are ignored. The
+    /// `correlation:` and `causation:` prefixes written by
+    /// [`MessageIdentity::to_nats_headers`] are accepted. The parsed
+    /// identity's kind is unknown.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the identity headers is missing or does not
+    /// hold a UUID or CID
+    pub fn parse<'a>(
+        &self,
+        headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<MessageIdentity> {
+        let (mut message_id, mut correlation_id, mut causation_id) = (None, None, None);
+        for (name, value) in headers {
+            let (slot, prefix) = if name == self.message_id {
+                (&mut message_id, "")
+            } else if name == self.correlation_id {
+                (&mut correlation_id, "correlation:")
+            } else if name == self.causation_id {
+                (&mut causation_id, "causation:")
+            } else {
+                continue;
+            };
+            *slot = Some(
+                value
+                    .strip_prefix(prefix)
+                    .unwrap_or(value)
+                    .parse::<IdType>()?,
+            );
+        }
+
+        let message_id = message_id.ok_or_else(|| {
+            CorrelationError::InvalidIdentity(format!("missing '{}' header", self.message_id))
+        })?;
+        let correlation_id = correlation_id.ok_or(CorrelationError::MissingCorrelation)?;
+        let causation_id = causation_id.ok_or(CorrelationError::MissingCausation)?;
+        Ok(MessageIdentity {
+            message_id,
+            correlation_id: CorrelationId(correlation_id),
+            causation_id: CausationId(causation_id),
+            kind: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    fn round_trip(convention: &HeaderConvention, identity: &MessageIdentity) -> MessageIdentity {
+        let headers = convention.to_headers(identity);
+        convention
+            .parse(headers.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_conventions_round_trip() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let caused = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let expected = MessageIdentity {
+            kind: None,
+            ..caused.clone()
+        };
+
+        for convention in [
+            HeaderConvention::default(),
+            HeaderConvention::nats(),
+            HeaderConvention::cloud_events("urn:test"),
+            HeaderConvention::default().lowercase(),
+        ] {
+            assert_eq!(round_trip(&convention, &caused), expected);
+        }
+
+        let lower = HeaderConvention::nats().lowercase();
+        assert_eq!(lower.to_headers(&caused)[0].0, "nats-msg-id");
+
+        // The default convention reads what `to_nats_headers` writes
+        let headers = caused.to_nats_headers();
+        let parsed = HeaderConvention::default()
+            .parse(headers.iter().map(|(k, v)| (*k, v.as_str())))
+            .unwrap();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let convention = HeaderConvention::default();
+        let id = Uuid::new_v4().to_string();
+        assert!(matches!(
+            convention.parse([
+                ("X-Message-ID", id.as_str()),
+                ("X-Causation-ID", id.as_str())
+            ]),
+            Err(CorrelationError::MissingCorrelation)
+        ));
+        // Names are matched exactly
+        assert!(convention
+            .parse([
+                ("x-message-id", id.as_str()),
+                ("X-Correlation-ID", id.as_str()),
+                ("X-Causation-ID", id.as_str()),
+            ])
+            .is_err());
+        assert!(convention
+            .parse([
+                ("X-Message-ID", "not-an-id"),
+                ("X-Correlation-ID", id.as_str()),
+                ("X-Causation-ID", id.as_str()),
+            ])
+            .is_err());
+    }
+}
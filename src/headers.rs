@@ -0,0 +1,358 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Message headers
+//!
+//! [`Headers`] keeps header names as written but looks them up
+//! case-insensitively, and holds any number of values per name. The
+//! identity headers in [`IDENTITY_HEADERS`] are reserved: [`Headers::insert`]
+//! and friends refuse them, so application code cannot overwrite a
+//! message's identity by accident, and only [`Headers::set_identity`] writes
+//! them. Headers collected from received messages keep whatever they hold.
+//!
+//! ```
+//! use cim_subject::headers::Headers;
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//! };
+//! use uuid::Uuid;
+//!
+//! let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let mut headers = Headers::new();
+//! headers.set_identity(&identity);
+//! headers.append("Accept", "application/json")?;
+//! headers.append("accept", "application/cbor")?;
+//!
+//! assert_eq!(headers.get("ACCEPT"), Some("application/json"));
+//! assert_eq!(headers.get_all("Accept").count(), 2);
+//! assert!(headers.insert("x-message-id", "forged").is_err());
+//! assert_eq!(headers.identity().unwrap(), identity);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{
+    MapAccess,
+    Visitor,
+};
+use serde::ser::SerializeMap;
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+use crate::correlation::{
+    self,
+    MessageIdentity,
+};
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::header_convention::HeaderConvention;
+
+/// Headers carrying a message's identity, reserved for
+/// [`Headers::set_identity`]
+pub const IDENTITY_HEADERS: [&str; 3] = ["X-Message-ID", "X-Correlation-ID", "X-Causation-ID"];
+
+/// Case-insensitive, multi-valued message headers
+///
+/// Serialized as a map from name to value, or to a list of values for
+/// names with several, so single-valued headers keep the shape of a plain
+/// string map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Headers {
+    /// Names as first written, each with its values in order
+    entries: Vec<(String, Vec<String>)>,
+}
+
+impl Headers {
+    /// Create empty headers
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The first value of `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_all(name).next()
+    }
+
+    /// Every value of `name`, in the order they were added
+    pub fn get_all<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> {
+        self.position(name)
+            .into_iter()
+            .flat_map(|i| self.entries[i].1.iter().map(String::as_str))
+    }
+
+    /// Check if `name` has a value
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Set `name` to `value`, replacing its previous values
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `name` is an identity header
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        check_unreserved(&name)?;
+        self.put(name, value.into());
+        Ok(())
+    }
+
+    /// Add `value` to the values of `name`
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `name` is an identity header
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        check_unreserved(&name)?;
+        self.push(name, value.into());
+        Ok(())
+    }
+
+    /// Remove `name`, returning its values
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `name` is an identity header
+    pub fn remove(&mut self, name: &str) -> Result<Vec<String>> {
+        check_unreserved(name)?;
+        Ok(self
+            .position(name)
+            .map(|i| self.entries.remove(i).1)
+            .unwrap_or_default())
+    }
+
+    /// Write `identity` into the identity headers
+    pub fn set_identity(&mut self, identity: &MessageIdentity) {
+        for (name, value) in identity.to_nats_headers() {
+            self.put(name.to_string(), value);
+        }
+    }
+
+    /// Read the identity from the identity headers, whatever their case
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an identity header is missing or malformed
+    pub fn identity(&self) -> correlation::Result<MessageIdentity> {
+        HeaderConvention::default().parse(
+            IDENTITY_HEADERS
+                .iter()
+                .filter_map(|name| self.get(name).map(|value| (*name, value))),
+        )
+    }
+
+    /// Every name and value, names in the order first written
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().flat_map(|(name, values)| {
+            values
+                .iter()
+                .map(move |value| (name.as_str(), value.as_str()))
+        })
+    }
+
+    /// Number of values across all names
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|(_, values)| values.len()).sum()
+    }
+
+    /// Check if there are no headers
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(existing, _)| existing.eq_ignore_ascii_case(name))
+    }
+
+    /// Set `name` to `value` even if it is reserved
+    pub(crate) fn put(&mut self, name: String, value: String) {
+        match self.position(&name) {
+            Some(i) => self.entries[i].1 = vec![value],
+            None => self.entries.push((name, vec![value])),
+        }
+    }
+
+    fn push(&mut self, name: String, value: String) {
+        match self.position(&name) {
+            Some(i) => self.entries[i].1.push(value),
+            None => self.entries.push((name, vec![value])),
+        }
+    }
+}
+
+fn check_unreserved(name: &str) -> Result<()> {
+    if IDENTITY_HEADERS
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    {
+        return Err(SubjectError::validation_error(format!(
+            "Header '{name}' is reserved for the message identity"
+        )));
+    }
+    Ok(())
+}
+
+/// Collect headers as received, identity headers included
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Headers {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = Self::new();
+        for (name, value) in iter {
+            headers.push(name.into(), value.into());
+        }
+        headers
+    }
+}
+
+impl From<HashMap<String, String>> for Headers {
+    fn from(map: HashMap<String, String>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<&async_nats::HeaderMap> for Headers {
+    fn from(map: &async_nats::HeaderMap) -> Self {
+        map.iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .map(move |value| (name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<&Headers> for async_nats::HeaderMap {
+    fn from(headers: &Headers) -> Self {
+        let mut map = async_nats::HeaderMap::new();
+        for (name, value) in headers.iter() {
+            map.append(name, value);
+        }
+        map
+    }
+}
+
+impl Serialize for Headers {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (name, values) in &self.entries {
+            match values.as_slice() {
+                [value] => map.serialize_entry(name, value)?,
+                values => map.serialize_entry(name, values)?,
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Headers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Values {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        struct HeadersVisitor;
+
+        impl<'de> Visitor<'de> for HeadersVisitor {
+            type Value = Headers;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a map of header names to a value or a list of values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> std::result::Result<Headers, A::Error> {
+                let mut headers = Headers::new();
+                while let Some((name, values)) = access.next_entry::<String, Values>()? {
+                    match values {
+                        Values::One(value) => headers.push(name, value),
+                        Values::Many(values) => {
+                            for value in values {
+                                headers.push(name.clone(), value);
+                            }
+                        },
+                    }
+                }
+                Ok(headers)
+            }
+        }
+
+        deserializer.deserialize_map(HeadersVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::IdType;
+
+    #[test]
+    fn test_case_insensitive_multi_values() {
+        let mut headers = Headers::new();
+        headers.append("Accept", "application/json").unwrap();
+        headers.append("ACCEPT", "application/cbor").unwrap();
+        headers.insert("Content-Type", "text/plain").unwrap();
+        headers.insert("content-type", "application/json").unwrap();
+
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers.get("content-TYPE"), Some("application/json"));
+        let pairs: Vec<(&str, &str)> = headers.iter().collect();
+        assert_eq!(pairs, [
+            ("Accept", "application/json"),
+            ("Accept", "application/cbor"),
+            ("Content-Type", "application/json"),
+        ]);
+
+        let json = serde_json::to_value(&headers).unwrap();
+        assert_eq!(json["Accept"][1], "application/cbor");
+        assert_eq!(json["Content-Type"], "application/json");
+        assert_eq!(serde_json::from_value::<Headers>(json).unwrap(), headers);
+
+        assert_eq!(headers.remove("accept").unwrap().len(), 2);
+        assert!(!headers.contains("Accept"));
+    }
+
+    #[test]
+    fn test_identity_headers_are_reserved() {
+        let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let mut headers = Headers::new();
+        headers.set_identity(&identity);
+        for name in ["X-Message-ID", "x-correlation-id"] {
+            assert!(headers.insert(name, "forged").is_err());
+            assert!(headers.append(name, "forged").is_err());
+            assert!(headers.remove(name).is_err());
+        }
+        assert_eq!(headers.identity().unwrap(), identity);
+
+        // Received headers keep their identity, whatever the case
+        let received: Headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+            .collect();
+        assert_eq!(received.identity().unwrap(), identity);
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject hierarchy as a token tree
+//!
+//! Subjects share prefixes: `orders.order.placed.v1` and
+//! `orders.order.shipped.v1` both live under `orders.order`. A
+//! [`SubjectHierarchy`] stores subjects in a tree keyed by token so that
+//! prefixes can be browsed, subjects can be listed by ancestry, and patterns
+//! can be evaluated by pruning whole branches.
+
+use std::collections::BTreeMap;
+use std::fmt::{
+    self,
+    Display,
+};
+
+use crate::pattern::{
+    Pattern,
+    Token,
+};
+use crate::subject::Subject;
+
+/// A tree of subjects keyed by token
+#[derive(Debug, Clone, Default)]
+pub struct SubjectHierarchy {
+    root: HierarchyNode,
+    len: usize,
+}
+
+/// A node in the hierarchy; nodes at full subject depth hold the subject
+#[derive(Debug, Clone, Default)]
+struct HierarchyNode {
+    children: BTreeMap<String, HierarchyNode>,
+    subject: Option<Subject>,
+}
+
+impl SubjectHierarchy {
+    /// Create an empty hierarchy
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a subject, returning `false` if it was already present
+    pub fn insert(&mut self, subject: Subject) -> bool {
+        let mut node = &mut self.root;
+        for token in subject.as_str().split('.') {
+            node = node.children.entry(token.to_string()).or_default();
+        }
+        if node.subject.is_some() {
+            return false;
+        }
+        node.subject = Some(subject);
+        self.len += 1;
+        true
+    }
+
+    /// Remove a subject, pruning branches left empty
+    pub fn remove(&mut self, subject: &Subject) -> bool {
+        let tokens: Vec<&str> = subject.as_str().split('.').collect();
+        let removed = self.root.remove(&tokens);
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Check if a subject is present
+    #[must_use]
+    pub fn contains(&self, subject: &Subject) -> bool {
+        self.node(subject.as_str())
+            .is_some_and(|node| node.subject.is_some())
+    }
+
+    /// Number of subjects in the hierarchy
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the hierarchy has no subjects
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tokens directly below a dot-separated prefix, in sorted order
+    ///
+    /// An empty prefix lists the top-level contexts.
+    #[must_use]
+    pub fn children_of(&self, prefix: &str) -> Vec<&str> {
+        self.node(prefix)
+            .map(|node| node.children.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Proper prefixes of a subject, from the context down
+    ///
+    /// For `orders.order.placed.v1` this is `orders`, `orders.order` and
+    /// `orders.order.placed`.
+    #[must_use]
+    pub fn ancestors_of(&self, subject: &Subject) -> Vec<String> {
+        let tokens: Vec<&str> = subject.as_str().split('.').collect();
+        (1..tokens.len())
+            .map(|depth| tokens[..depth].join("."))
+            .filter(|prefix| self.node(prefix).is_some())
+            .collect()
+    }
+
+    /// All subjects below a dot-separated prefix
+    #[must_use]
+    pub fn descendants_of(&self, prefix: &str) -> Vec<&Subject> {
+        let mut out = Vec::new();
+        if let Some(node) = self.node(prefix) {
+            node.collect_all(&mut out);
+        }
+        out
+    }
+
+    /// All subjects matching a pattern, skipping branches that cannot match
+    #[must_use]
+    pub fn subtree_matching(&self, pattern: &Pattern) -> Vec<&Subject> {
+        let mut out = Vec::new();
+        self.root.collect_matching(pattern.tokens(), &mut out);
+        out
+    }
+
+    /// Iterate over all subjects in sorted order
+    pub fn iter(&self) -> impl Iterator<Item = &Subject> {
+        self.descendants_of("").into_iter()
+    }
+
+    fn node(&self, prefix: &str) -> Option<&HierarchyNode> {
+        if prefix.is_empty() {
+            return Some(&self.root);
+        }
+        prefix
+            .split('.')
+            .try_fold(&self.root, |node, token| node.children.get(token))
+    }
+}
+
+impl HierarchyNode {
+    fn remove(&mut self, tokens: &[&str]) -> bool {
+        let Some((first, rest)) = tokens.split_first() else {
+            return self.subject.take().is_some();
+        };
+        let Some(child) = self.children.get_mut(*first) else {
+            return false;
+        };
+        let removed = child.remove(rest);
+        if child.children.is_empty() && child.subject.is_none() {
+            self.children.remove(*first);
+        }
+        removed
+    }
+
+    fn collect_all<'a>(&'a self, out: &mut Vec<&'a Subject>) {
+        out.extend(self.subject.as_ref());
+        for child in self.children.values() {
+            child.collect_all(out);
+        }
+    }
+
+    fn collect_matching<'a>(&'a self, tokens: &[Token], out: &mut Vec<&'a Subject>) {
+        match tokens.split_first() {
+            None => out.extend(self.subject.as_ref()),
+            Some((Token::MultiWildcard, _)) => {
+                for child in self.children.values() {
+                    child.collect_all(out);
+                }
+            },
+            Some((Token::SingleWildcard, rest)) => {
+                for child in self.children.values() {
+                    child.collect_matching(rest, out);
+                }
+            },
+            Some((Token::Literal(literal), rest)) => {
+                if let Some(child) = self.children.get(literal) {
+                    child.collect_matching(rest, out);
+                }
+            },
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        for (token, child) in &self.children {
+            writeln!(f, "{:indent$}{token}", "", indent = depth * 2)?;
+            child.render(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SubjectHierarchy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.render(f, 0)
+    }
+}
+
+impl FromIterator<Subject> for SubjectHierarchy {
+    fn from_iter<I: IntoIterator<Item = Subject>>(iter: I) -> Self {
+        let mut hierarchy = Self::new();
+        for subject in iter {
+            hierarchy.insert(subject);
+        }
+        hierarchy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SubjectHierarchy {
+        [
+            "orders.order.placed.v1",
+            "orders.order.shipped.v1",
+            "orders.invoice.issued.v1",
+            "billing.payment.captured.v2",
+        ]
+        .into_iter()
+        .map(|s| Subject::new(s).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_hierarchy_queries() {
+        let hierarchy = sample();
+        assert_eq!(hierarchy.len(), 4);
+        assert_eq!(hierarchy.children_of(""), vec!["billing", "orders"]);
+        assert_eq!(hierarchy.children_of("orders"), vec!["invoice", "order"]);
+        assert!(hierarchy.children_of("shipping").is_empty());
+
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        assert_eq!(hierarchy.ancestors_of(&placed), vec![
+            "orders",
+            "orders.order",
+            "orders.order.placed"
+        ]);
+        assert_eq!(hierarchy.descendants_of("orders.order").len(), 2);
+
+        let matched: Vec<&str> = hierarchy
+            .subtree_matching(&Pattern::new("*.*.*.v1").unwrap())
+            .into_iter()
+            .map(Subject::as_str)
+            .collect();
+        assert_eq!(matched, vec![
+            "orders.invoice.issued.v1",
+            "orders.order.placed.v1",
+            "orders.order.shipped.v1"
+        ]);
+        assert_eq!(
+            hierarchy
+                .subtree_matching(&Pattern::new("orders.>").unwrap())
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_insert_remove_and_render() {
+        let mut hierarchy = sample();
+        let captured = Subject::new("billing.payment.captured.v2").unwrap();
+
+        assert!(!hierarchy.insert(captured.clone()));
+        assert!(hierarchy.remove(&captured));
+        assert!(!hierarchy.contains(&captured));
+        assert_eq!(hierarchy.children_of(""), vec!["orders"]);
+        assert_eq!(hierarchy.iter().count(), 3);
+
+        let rendered = hierarchy.to_string();
+        assert!(rendered.starts_with("orders\n  invoice\n    issued\n      v1\n"));
+    }
+}
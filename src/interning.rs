@@ -0,0 +1,319 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Interned subjects
+//!
+//! A long-running router sees the same few thousand subjects millions of
+//! times. Interning them through a [`SubjectInterner`] stores each distinct
+//! subject once; every [`InternedSubject`] is a pointer into the pool with
+//! its hash precomputed, so cloning, hashing and comparing are cheap. A
+//! router can own its interner, or share [`SubjectInterner::global`].
+//!
+//! ```
+//! use cim_subject::interning::{
+//!     InternedSubject,
+//!     SubjectInterner,
+//! };
+//! use cim_subject::Subject;
+//!
+//! let interner = SubjectInterner::new();
+//! let a = interner.intern_str("orders.order.placed.v1")?;
+//! let b = interner.intern(&Subject::new("orders.order.placed.v1")?);
+//! assert_eq!(a, b);
+//! assert!(InternedSubject::ptr_eq(&a, &b));
+//! assert_eq!(a.aggregate(), "order");
+//!
+//! let stats = interner.stats();
+//! assert_eq!((stats.subjects, stats.hits, stats.misses), (1, 1, 1));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt::{
+    self,
+    Display,
+};
+use std::hash::{
+    Hash,
+    Hasher,
+};
+use std::ops::Deref;
+use std::sync::{
+    Arc,
+    Mutex,
+    OnceLock,
+};
+
+use crate::error::Result;
+use crate::subject::Subject;
+
+/// A pooled subject with its hash
+#[derive(Debug)]
+struct Entry {
+    subject: Subject,
+    hash: u64,
+}
+
+/// A subject shared through a [`SubjectInterner`]
+///
+/// Equality matches [`Subject`]'s, also between subjects interned in
+/// different pools; within one pool it is a pointer comparison.
+#[derive(Debug, Clone)]
+pub struct InternedSubject(Arc<Entry>);
+
+impl InternedSubject {
+    /// Intern `subject` in the [global](SubjectInterner::global) pool
+    #[must_use]
+    pub fn intern(subject: &Subject) -> Self {
+        SubjectInterner::global().intern(subject)
+    }
+
+    /// The interned subject
+    #[must_use]
+    pub fn as_subject(&self) -> &Subject {
+        &self.0.subject
+    }
+
+    /// Whether both share the same storage
+    #[must_use]
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl Deref for InternedSubject {
+    type Target = Subject;
+
+    fn deref(&self) -> &Subject {
+        &self.0.subject
+    }
+}
+
+impl PartialEq for InternedSubject {
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other)
+            || (self.0.hash == other.0.hash && self.0.subject == other.0.subject)
+    }
+}
+
+impl Eq for InternedSubject {}
+
+impl Hash for InternedSubject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash);
+    }
+}
+
+impl Display for InternedSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.subject.fmt(f)
+    }
+}
+
+impl From<Subject> for InternedSubject {
+    /// Intern in the [global](SubjectInterner::global) pool
+    fn from(subject: Subject) -> Self {
+        SubjectInterner::global().intern_owned(subject)
+    }
+}
+
+impl From<InternedSubject> for Subject {
+    fn from(interned: InternedSubject) -> Self {
+        Arc::try_unwrap(interned.0)
+            .map_or_else(|entry| entry.subject.clone(), |entry| entry.subject)
+    }
+}
+
+/// Pool key looked up by the subject string
+#[derive(Debug)]
+struct PoolKey(Arc<Entry>);
+
+impl Borrow<str> for PoolKey {
+    fn borrow(&self) -> &str {
+        self.0.subject.as_str()
+    }
+}
+
+impl PartialEq for PoolKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.subject == other.0.subject
+    }
+}
+
+impl Eq for PoolKey {}
+
+impl Hash for PoolKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.subject.as_str().hash(state);
+    }
+}
+
+/// Size and effectiveness of a [`SubjectInterner`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternerStats {
+    /// Distinct subjects in the pool
+    pub subjects: usize,
+    /// Bytes of subject text in the pool
+    pub bytes: usize,
+    /// Lookups that found the subject already pooled
+    pub hits: u64,
+    /// Lookups that added a subject
+    pub misses: u64,
+}
+
+#[derive(Debug, Default)]
+struct Pool {
+    keys: HashSet<PoolKey>,
+    stats: InternerStats,
+}
+
+/// A pool of interned subjects
+#[derive(Debug, Default)]
+pub struct SubjectInterner {
+    pool: Mutex<Pool>,
+}
+
+impl SubjectInterner {
+    /// Create an empty pool
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide pool
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<SubjectInterner> = OnceLock::new();
+        GLOBAL.get_or_init(Self::new)
+    }
+
+    /// Intern a subject, cloning it only if it is not pooled yet
+    pub fn intern(&self, subject: &Subject) -> InternedSubject {
+        self.lookup(subject.as_str())
+            .unwrap_or_else(|| self.insert(subject.clone()))
+    }
+
+    /// Intern a subject, taking ownership of it if it is not pooled yet
+    pub fn intern_owned(&self, subject: Subject) -> InternedSubject {
+        self.lookup(subject.as_str())
+            .unwrap_or_else(|| self.insert(subject))
+    }
+
+    /// Intern a subject string, parsing it only if it is not pooled yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not pooled and is not a valid
+    /// subject
+    pub fn intern_str(&self, subject: &str) -> Result<InternedSubject> {
+        match self.lookup(subject) {
+            Some(interned) => Ok(interned),
+            None => Ok(self.insert(Subject::new(subject)?)),
+        }
+    }
+
+    /// Drop pooled subjects no [`InternedSubject`] refers to any more,
+    /// returning how many were dropped
+    pub fn purge(&self) -> usize {
+        let Ok(mut pool) = self.pool.lock() else {
+            return 0;
+        };
+        let before = pool.keys.len();
+        pool.keys.retain(|key| Arc::strong_count(&key.0) > 1);
+        let removed = before - pool.keys.len();
+        pool.stats.subjects = pool.keys.len();
+        pool.stats.bytes = pool
+            .keys
+            .iter()
+            .map(|key| key.0.subject.as_str().len())
+            .sum();
+        removed
+    }
+
+    /// Current pool statistics
+    #[must_use]
+    pub fn stats(&self) -> InternerStats {
+        self.pool
+            .lock()
+            .map_or_else(|_| InternerStats::default(), |pool| pool.stats)
+    }
+
+    fn lookup(&self, subject: &str) -> Option<InternedSubject> {
+        let mut pool = self.pool.lock().ok()?;
+        let entry = Arc::clone(&pool.keys.get(subject)?.0);
+        pool.stats.hits += 1;
+        Some(InternedSubject(entry))
+    }
+
+    fn insert(&self, subject: Subject) -> InternedSubject {
+        let mut hasher = DefaultHasher::new();
+        subject.hash(&mut hasher);
+        let entry = Arc::new(Entry {
+            hash: hasher.finish(),
+            subject,
+        });
+        let Ok(mut pool) = self.pool.lock() else {
+            // A poisoned pool still hands out working, unshared subjects
+            return InternedSubject(entry);
+        };
+        // Another thread may have pooled the subject since the lookup
+        if let Some(existing) = pool.keys.get(entry.subject.as_str()) {
+            let existing = Arc::clone(&existing.0);
+            pool.stats.hits += 1;
+            return InternedSubject(existing);
+        }
+        pool.stats.misses += 1;
+        pool.stats.subjects += 1;
+        pool.stats.bytes += entry.subject.as_str().len();
+        pool.keys.insert(PoolKey(Arc::clone(&entry)));
+        InternedSubject(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_interned_subjects_share_storage() {
+        let interner = SubjectInterner::new();
+        let subject = Subject::new("orders.order.placed.v1").unwrap();
+        let a = interner.intern(&subject);
+        let b = interner.intern_owned(subject.clone());
+        assert!(InternedSubject::ptr_eq(&a, &b));
+        assert!(interner.intern_str("orders..placed.v1").is_err());
+
+        // Equal across pools, and usable as a map key
+        let other = SubjectInterner::new().intern(&subject);
+        assert!(!InternedSubject::ptr_eq(&a, &other));
+        assert_eq!(a, other);
+        let mut counts = HashMap::new();
+        *counts.entry(a.clone()).or_insert(0) += 1;
+        *counts.entry(other).or_insert(0) += 1;
+        assert_eq!(counts[&b], 2);
+
+        assert_eq!(Subject::from(b), subject);
+        assert_eq!(interner.stats(), InternerStats {
+            subjects: 1,
+            bytes: 22,
+            hits: 1,
+            misses: 1,
+        });
+    }
+
+    #[test]
+    fn test_purge() {
+        let interner = SubjectInterner::new();
+        let kept = interner.intern_str("orders.order.placed.v1").unwrap();
+        drop(interner.intern_str("orders.order.shipped.v1").unwrap());
+        assert_eq!(interner.stats().subjects, 2);
+        assert_eq!(interner.purge(), 1);
+        assert_eq!(interner.stats().subjects, 1);
+        assert!(InternedSubject::ptr_eq(
+            &kept,
+            &interner.intern_str("orders.order.placed.v1").unwrap()
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Correlation chain storage in a NATS key-value bucket
+//!
+//! Available with the `nats` feature. Every message identity is written
+//! once under `chain.{correlation_id}.{message_id}`, and once more under
+//! `cause.{causation_id}.{message_id}` unless it is a root, so an append is
+//! a single create no matter how long the chain is and several services can
+//! record into the same chain. Reading a chain or a message's children
+//! replays the keys under its prefix in insertion order. Watching a chain
+//! streams the rebuilt chain after every append.
+
+use async_nats::jetstream::consumer::pull::OrderedConfig;
+use async_nats::jetstream::consumer::{
+    DeliverPolicy,
+    ReplayPolicy,
+};
+use async_nats::jetstream::context::KeyValueErrorKind;
+use async_nats::jetstream::kv::{
+    self,
+    CreateErrorKind,
+    Operation,
+};
+use async_nats::jetstream::Context;
+use futures::{
+    Stream,
+    StreamExt,
+};
+
+use crate::chain_store::assemble_chain;
+use crate::correlation::{
+    CorrelationError,
+    CorrelationId,
+    IdType,
+    MessageIdentity,
+    Result,
+};
+use crate::message_algebra::CorrelationChain;
+
+/// Async [`ChainStore`](crate::chain_store::ChainStore) counterpart backed
+/// by a NATS key-value bucket
+#[derive(Debug, Clone)]
+pub struct JetStreamChainStore {
+    kv: kv::Store,
+}
+
+impl JetStreamChainStore {
+    /// Open a bucket, creating it if it does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bucket name is invalid, or the bucket can
+    /// neither be opened nor created
+    pub async fn open(context: &Context, bucket: &str) -> Result<Self> {
+        let kv = match context.get_key_value(bucket).await {
+            Ok(kv) => kv,
+            Err(e) if e.kind() != KeyValueErrorKind::GetBucket => return Err(storage_error(e)),
+            Err(_) => context
+                .create_key_value(kv::Config {
+                    bucket: bucket.to_string(),
+                    history: 1,
+                    ..Default::default()
+                })
+                .await
+                .map_err(storage_error)?,
+        };
+        Ok(Self { kv })
+    }
+
+    /// Use an already opened bucket
+    #[must_use]
+    pub fn from_store(kv: kv::Store) -> Self {
+        Self { kv }
+    }
+
+    /// Record a message identity; recording the same message twice is a
+    /// no-op
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bucket cannot be read or written
+    pub async fn insert(&self, identity: MessageIdentity) -> Result<()> {
+        let message_id = &identity.message_id;
+        self.append(
+            &format!("{}.{message_id}", chain_prefix(&identity.correlation_id)),
+            &identity,
+        )
+        .await?;
+        if !identity.is_root() {
+            self.append(
+                &format!("{}.{message_id}", cause_prefix(&identity.causation_id.0)),
+                &identity,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the chain for a correlation ID, if its root has been recorded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bucket cannot be read or the stored messages
+    /// do not form a valid chain
+    pub async fn get_chain(
+        &self,
+        correlation_id: &CorrelationId,
+    ) -> Result<Option<CorrelationChain>> {
+        assemble_chain(self.load(&chain_prefix(correlation_id)).await?)
+    }
+
+    /// Messages directly caused by a message, in insertion order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bucket cannot be read
+    pub async fn find_children(&self, message_id: &IdType) -> Result<Vec<MessageIdentity>> {
+        self.load(&cause_prefix(message_id)).await
+    }
+
+    /// Stream the chain for a correlation ID: its current state, if any,
+    /// then the rebuilt chain after every update
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watch cannot be started; errors while
+    /// watching are yielded by the stream
+    pub async fn watch(
+        &self,
+        correlation_id: &CorrelationId,
+    ) -> Result<impl Stream<Item = Result<CorrelationChain>>> {
+        let watch = self
+            .kv
+            .watch_with_history(format!("{}.>", chain_prefix(correlation_id)))
+            .await
+            .map_err(storage_error)?;
+
+        // Replayed entries are collected silently until the watch catches
+        // up, so the current state is yielded once rather than once per
+        // stored message
+        Ok(watch
+            .scan(Vec::new(), |messages, entry| {
+                let item = match entry {
+                    Ok(entry) if entry.operation != Operation::Put => None,
+                    Ok(entry) => match decode(&entry.value) {
+                        Ok(identity) => {
+                            messages.push(identity);
+                            (entry.delta == 0).then(|| assemble_chain(messages.clone()))
+                        },
+                        Err(e) => Some(Err(e)),
+                    },
+                    Err(e) => Some(Err(storage_error(e))),
+                };
+                async move { Some(item) }
+            })
+            .filter_map(|item| async move { item.and_then(Result::transpose) }))
+    }
+
+    /// Every identity stored under `prefix`, in insertion order
+    async fn load(&self, prefix: &str) -> Result<Vec<MessageIdentity>> {
+        let consumer = self
+            .kv
+            .stream
+            .create_consumer(OrderedConfig {
+                filter_subject: format!("{}{prefix}.>", self.kv.prefix),
+                deliver_policy: DeliverPolicy::All,
+                replay_policy: ReplayPolicy::Instant,
+                ..Default::default()
+            })
+            .await
+            .map_err(storage_error)?;
+        if consumer.cached_info().num_pending == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut messages = consumer.messages().await.map_err(storage_error)?;
+        let mut identities = Vec::new();
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(storage_error)?;
+            let pending = message.info().map_err(storage_error)?.pending;
+            // Deleted and purged keys carry an operation header and no value
+            if message
+                .headers
+                .as_ref()
+                .map_or(true, |headers| headers.get("KV-Operation").is_none())
+            {
+                identities.push(decode(&message.payload)?);
+            }
+            if pending == 0 {
+                break;
+            }
+        }
+        Ok(identities)
+    }
+
+    /// Write an identity under `key`; a key that already exists holds the
+    /// same message and is left alone
+    async fn append(&self, key: &str, identity: &MessageIdentity) -> Result<()> {
+        let value = serde_json::to_vec(identity).map_err(storage_error)?;
+        match self.kv.create(key, value.into()).await {
+            Err(e) if e.kind() != CreateErrorKind::AlreadyExists => Err(storage_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn chain_prefix(correlation_id: &CorrelationId) -> String {
+    format!("chain.{}", correlation_id.0)
+}
+
+fn cause_prefix(message_id: &IdType) -> String {
+    format!("cause.{message_id}")
+}
+
+fn decode(value: &[u8]) -> Result<MessageIdentity> {
+    serde_json::from_slice(value).map_err(storage_error)
+}
+
+fn storage_error(error: impl std::fmt::Display) -> CorrelationError {
+    CorrelationError::Storage(error.to_string())
+}
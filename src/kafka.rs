@@ -0,0 +1,341 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Kafka topic naming for subjects
+//!
+//! Kafka topic names are limited to ASCII letters, digits, `.`, `_` and `-`
+//! and to 249 characters. [`KafkaTopicNaming`] converts subjects to legal
+//! topic names and back, so NATS and Kafka sides of a hybrid deployment can
+//! address the same streams. Patterns have no Kafka counterpart; they become
+//! either a regex for pattern subscriptions or the list of matching topics.
+//!
+//! Kafka treats `.` and `_` as colliding in metric names, so a deployment
+//! should settle on one separator for all of its topics.
+//!
+//! ```
+//! use cim_subject::kafka::KafkaTopicNaming;
+//! use cim_subject::Subject;
+//!
+//! let naming = KafkaTopicNaming::new().with_separator('_').unwrap();
+//! let subject = Subject::new("orders.order.placed.v1").unwrap();
+//!
+//! let topic = naming.to_topic(&subject).unwrap();
+//! assert_eq!(topic, "orders_order_placed_v1");
+//! assert_eq!(naming.to_subject(&topic).unwrap(), subject);
+//! ```
+
+use std::sync::Arc;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::{
+    Pattern,
+    Token,
+};
+use crate::subject::{
+    Subject,
+    SubjectParts,
+};
+use crate::translator::TranslationRule;
+
+/// Longest topic name Kafka accepts
+pub const MAX_TOPIC_LENGTH: usize = 249;
+
+/// Conversion between subjects and Kafka topic names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaTopicNaming {
+    separator: char,
+    context_prefix: String,
+    max_length: usize,
+}
+
+impl Default for KafkaTopicNaming {
+    fn default() -> Self {
+        Self {
+            separator: '.',
+            context_prefix: String::new(),
+            max_length: MAX_TOPIC_LENGTH,
+        }
+    }
+}
+
+impl KafkaTopicNaming {
+    /// Dot-separated topics without a prefix
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Join subject tokens with `separator` instead of `.`
+    ///
+    /// Subjects whose tokens contain the separator cannot be converted, so
+    /// every topic converts back unambiguously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the separator is not `.`, `_` or `-`
+    pub fn with_separator(mut self, separator: char) -> Result<Self> {
+        if !matches!(separator, '.' | '_' | '-') {
+            return Err(SubjectError::validation_error(format!(
+                "Kafka topic separator must be '.', '_' or '-', got '{separator}'"
+            )));
+        }
+        self.separator = separator;
+        Ok(self)
+    }
+
+    /// Prepend `prefix` to the context token, e.g. `prod-` for
+    /// `prod-orders.order.placed.v1`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefix contains characters other than ASCII
+    /// letters, digits, `_` and `-`, or contains the separator
+    pub fn with_context_prefix(mut self, prefix: impl Into<String>) -> Result<Self> {
+        let prefix = prefix.into();
+        if !prefix
+            .chars()
+            .all(|c| is_topic_token_char(c) && c != self.separator)
+        {
+            return Err(SubjectError::validation_error(format!(
+                "Kafka topic prefix '{prefix}' contains invalid characters"
+            )));
+        }
+        self.context_prefix = prefix;
+        Ok(self)
+    }
+
+    /// Reject topics longer than `max_length`, capped at
+    /// [`MAX_TOPIC_LENGTH`]
+    #[must_use]
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length.min(MAX_TOPIC_LENGTH);
+        self
+    }
+
+    /// The topic name for a subject
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A token contains non-ASCII characters or the separator
+    /// - The topic would exceed the maximum length
+    pub fn to_topic(&self, subject: &Subject) -> Result<String> {
+        let tokens = subject.parts().as_parts_ref().tokens();
+        if let Some(token) = tokens.iter().find(|token| {
+            !token
+                .chars()
+                .all(|c| is_topic_token_char(c) && c != self.separator)
+        }) {
+            return Err(SubjectError::translation_error(format!(
+                "Token '{token}' of '{subject}' cannot be used in a Kafka topic with separator '{}'",
+                self.separator
+            )));
+        }
+
+        let topic = format!(
+            "{}{}",
+            self.context_prefix,
+            tokens.join(&self.separator.to_string())
+        );
+        if topic.len() > self.max_length {
+            return Err(SubjectError::translation_error(format!(
+                "Kafka topic for '{subject}' is {} characters, over the limit of {}",
+                topic.len(),
+                self.max_length
+            )));
+        }
+        Ok(topic)
+    }
+
+    /// The subject a topic name was produced from
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the topic lacks the context prefix or does not
+    /// split into a valid subject
+    pub fn to_subject(&self, topic: &str) -> Result<Subject> {
+        let unprefixed = topic.strip_prefix(&self.context_prefix).ok_or_else(|| {
+            SubjectError::translation_error(format!(
+                "Kafka topic '{topic}' does not start with '{}'",
+                self.context_prefix
+            ))
+        })?;
+        let tokens: Vec<&str> = unprefixed.split(self.separator).collect();
+        let [context, aggregate, event_type, version] = tokens[..] else {
+            return Err(SubjectError::translation_error(format!(
+                "Kafka topic '{topic}' does not have 4 '{}'-separated tokens",
+                self.separator
+            )));
+        };
+        Subject::new(SubjectParts::new(context, aggregate, event_type, version).to_subject())
+    }
+
+    /// An anchored regex matching the topics of every subject the pattern
+    /// matches, for Kafka pattern subscriptions
+    #[must_use]
+    pub fn topic_regex(&self, pattern: &Pattern) -> String {
+        let separator = escape(&self.separator.to_string());
+        let token = format!("[^{separator}]+");
+        let parts: Vec<String> = pattern
+            .tokens()
+            .iter()
+            .map(|t| match t {
+                Token::Literal(literal) => escape(literal),
+                Token::SingleWildcard => token.clone(),
+                Token::MultiWildcard => format!("{token}({separator}{token})*"),
+            })
+            .collect();
+        format!(
+            "^{}{}$",
+            escape(&self.context_prefix),
+            parts.join(&separator)
+        )
+    }
+
+    /// The topics among `topics` whose subjects match the pattern
+    ///
+    /// Topics that do not convert back to a subject are skipped.
+    pub fn expand<'a>(
+        &self,
+        pattern: &Pattern,
+        topics: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<&'a str> {
+        topics
+            .into_iter()
+            .filter(|topic| {
+                self.to_subject(topic)
+                    .is_ok_and(|subject| pattern.matches(&subject))
+            })
+            .collect()
+    }
+
+    /// A [`TranslationRule`] from subjects matching `source_pattern` to the
+    /// subjects spelled like their topics, with the reverse
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the separator is not `.`, since only then are
+    /// topic names themselves subjects
+    pub fn translation_rule(
+        &self,
+        name: impl Into<String>,
+        source_pattern: Pattern,
+    ) -> Result<TranslationRule> {
+        if self.separator != '.' {
+            return Err(SubjectError::translation_error(format!(
+                "Kafka topics separated by '{}' are not subjects",
+                self.separator
+            )));
+        }
+
+        // Topics match the source pattern with the prefix on its context
+        let mut target_tokens: Vec<String> = source_pattern
+            .as_str()
+            .split('.')
+            .map(str::to_string)
+            .collect();
+        if !matches!(
+            source_pattern.tokens()[0],
+            Token::SingleWildcard | Token::MultiWildcard
+        ) {
+            target_tokens[0].insert_str(0, &self.context_prefix);
+        }
+        let target_pattern = Pattern::from_valid_tokens(&target_tokens);
+
+        let forward = self.clone();
+        let reverse = self.clone();
+        Ok(TranslationRule::new(
+            name,
+            source_pattern,
+            Arc::new(move |subject| Subject::new(forward.to_topic(subject)?)),
+        )
+        .with_target_pattern(target_pattern)
+        .with_reverse(Arc::new(move |subject| {
+            reverse.to_subject(subject.as_str())
+        })))
+    }
+}
+
+/// Characters Kafka allows in a topic, other than `.`
+fn is_topic_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Escape regex metacharacters that can occur in topic names
+fn escape(literal: &str) -> String {
+    literal.replace('.', "\\.").replace('-', "\\-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translator::Translator;
+
+    fn subject(s: &str) -> Subject {
+        Subject::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_topic_round_trip() {
+        let naming = KafkaTopicNaming::new()
+            .with_separator('-')
+            .unwrap()
+            .with_context_prefix("prod_")
+            .unwrap();
+        let placed = subject("orders.order.placed.v1");
+        assert_eq!(
+            naming.to_topic(&placed).unwrap(),
+            "prod_orders-order-placed-v1"
+        );
+        assert_eq!(
+            naming.to_subject("prod_orders-order-placed-v1").unwrap(),
+            placed
+        );
+
+        // Tokens holding the separator or non-ASCII characters are rejected
+        assert!(naming
+            .to_topic(&subject("orders.line-item.added.v1"))
+            .is_err());
+        assert!(naming
+            .to_topic(&subject("orders.größe.changed.v1"))
+            .is_err());
+        assert!(naming.to_subject("orders-order-placed-v1").is_err());
+        assert!(KafkaTopicNaming::new()
+            .with_max_length(10)
+            .to_topic(&placed)
+            .is_err());
+        assert!(KafkaTopicNaming::new().with_separator('/').is_err());
+    }
+
+    #[test]
+    fn test_patterns_and_rules() {
+        let naming = KafkaTopicNaming::new().with_separator('_').unwrap();
+        let pattern = Pattern::new("orders.*.placed.>").unwrap();
+        assert_eq!(
+            naming.topic_regex(&pattern),
+            "^orders_[^_]+_placed_[^_]+(_[^_]+)*$"
+        );
+        let topics = [
+            "orders_order_placed_v1",
+            "orders_order_cancelled_v1",
+            "legacy-topic",
+        ];
+        assert_eq!(naming.expand(&pattern, topics), vec![
+            "orders_order_placed_v1"
+        ]);
+        assert!(naming.translation_rule("kafka", pattern.clone()).is_err());
+
+        let prefixed = KafkaTopicNaming::new().with_context_prefix("dc1-").unwrap();
+        let translator = Translator::new();
+        translator.register_rule(
+            "kafka",
+            prefixed.translation_rule("kafka", pattern).unwrap(),
+        );
+        let placed = subject("orders.order.placed.v1");
+        let topic = translator.translate(&placed).unwrap();
+        assert_eq!(topic.as_str(), "dc1-orders.order.placed.v1");
+        assert_eq!(translator.reverse_translate(&topic).unwrap(), placed);
+    }
+}
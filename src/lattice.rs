@@ -0,0 +1,487 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject lattice: generalization order with meets and joins
+//!
+//! Patterns are ordered token by token: a token is below itself, below the
+//! `*` wildcard, and below any token it generalizes to in a
+//! [`Generalizations`] hierarchy (e.g. `created` below `changed`). A
+//! trailing `>` stands for one or more tokens, so it is above any non-empty
+//! suffix, as in [`Pattern::subsumes`]. The join of two patterns
+//! generalizes each differing position to the nearest common ancestor,
+//! falling back to `*`, or to `>` where their lengths differ; the meet
+//! keeps the more specific token and does not exist when two tokens are
+//! unrelated.
+
+use std::collections::HashMap;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Weight of a wildcard when ranking patterns by generality
+const WILDCARD_WEIGHT: usize = 1 << 20;
+
+/// A user-defined hierarchy of token generalizations
+///
+/// Each token has at most one more general parent, so the hierarchy is a
+/// forest and every pair of related tokens has a unique nearest ancestor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Generalizations {
+    /// Specific token to its more general parent
+    parents: HashMap<String, String>,
+}
+
+impl Generalizations {
+    /// Create an empty hierarchy, leaving only structural generalization
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The classic CRUD hierarchy: `changed` generalizes `created`,
+    /// `updated` and `deleted`
+    #[must_use]
+    pub fn crud() -> Self {
+        let mut generalizations = Self::new();
+        for specific in ["created", "updated", "deleted"] {
+            generalizations
+                .parents
+                .insert(specific.to_string(), "changed".to_string());
+        }
+        generalizations
+    }
+
+    /// Declare `general` as the parent of each of `specifics`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token is not a valid literal, already has a
+    /// different parent, or the declaration would create a cycle
+    pub fn generalize(mut self, general: &str, specifics: &[&str]) -> Result<Self> {
+        validate_token(general)?;
+        for specific in specifics {
+            validate_token(specific)?;
+            if let Some(existing) = self.parents.get(*specific) {
+                if existing != general {
+                    return Err(SubjectError::composition_error(format!(
+                        "Token '{specific}' already generalizes to '{existing}'"
+                    )));
+                }
+            }
+            if *specific == general || self.ancestors(general).any(|a| a == *specific) {
+                return Err(SubjectError::composition_error(format!(
+                    "Generalizing '{specific}' to '{general}' would create a cycle"
+                )));
+            }
+            self.parents
+                .insert((*specific).to_string(), general.to_string());
+        }
+        Ok(self)
+    }
+
+    /// The direct parent of a token
+    #[must_use]
+    pub fn parent(&self, token: &str) -> Option<&str> {
+        self.parents.get(token).map(String::as_str)
+    }
+
+    /// Strict ancestors of a token, nearest first
+    pub fn ancestors<'a>(&'a self, token: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        std::iter::successors(self.parent(token), |t| self.parent(t))
+    }
+
+    /// Check if `specific` is below or equal to `general`
+    ///
+    /// `>` is only below itself, since it can stand for several tokens.
+    #[must_use]
+    pub fn token_leq(&self, specific: &str, general: &str) -> bool {
+        general == ">"
+            || (general == "*" && specific != ">")
+            || specific == general
+            || self.ancestors(specific).any(|a| a == general)
+    }
+
+    /// Nearest common generalization of two tokens, or `*`
+    #[must_use]
+    pub fn token_join<'a>(&'a self, a: &'a str, b: &'a str) -> &'a str {
+        if a == b {
+            return a;
+        }
+        std::iter::once(a)
+            .chain(self.ancestors(a))
+            .find(|candidate| self.token_leq(b, candidate))
+            .unwrap_or("*")
+    }
+
+    /// The more specific of two related tokens
+    #[must_use]
+    pub fn token_meet<'a>(&self, a: &'a str, b: &'a str) -> Option<&'a str> {
+        if self.token_leq(a, b) {
+            Some(a)
+        } else if self.token_leq(b, a) {
+            Some(b)
+        } else {
+            None
+        }
+    }
+
+    /// Number of tokens covered by a token, used to rank generality
+    fn weight(&self, token: &str) -> usize {
+        if is_wildcard(token) {
+            return WILDCARD_WEIGHT;
+        }
+        1 + self
+            .parents
+            .keys()
+            .filter(|specific| self.ancestors(specific).any(|a| a == token))
+            .count()
+    }
+}
+
+/// A lattice of subjects ordered by generality
+#[derive(Debug, Clone)]
+pub struct SubjectLattice {
+    /// Elements of the lattice
+    elements: Vec<Pattern>,
+    /// Token generalization hierarchy
+    generalizations: Generalizations,
+    /// Strict ordering relationships as `(less_idx, greater_idx)`
+    ordering: Vec<(usize, usize)>,
+}
+
+impl SubjectLattice {
+    /// Create a lattice with purely structural generalization
+    #[must_use]
+    pub fn new(subjects: &[Subject]) -> Self {
+        Self::with_generalizations(subjects, Generalizations::new())
+    }
+
+    /// Create a lattice using a token generalization hierarchy
+    #[must_use]
+    pub fn with_generalizations(subjects: &[Subject], generalizations: Generalizations) -> Self {
+        Self::from_patterns(
+            subjects.iter().map(Pattern::from).collect(),
+            generalizations,
+        )
+    }
+
+    /// Create a lattice whose elements may include wildcards
+    #[must_use]
+    pub fn from_patterns(elements: Vec<Pattern>, generalizations: Generalizations) -> Self {
+        let mut lattice = Self {
+            elements,
+            generalizations,
+            ordering: Vec::new(),
+        };
+        lattice.compute_ordering();
+        lattice
+    }
+
+    fn compute_ordering(&mut self) {
+        for i in 0..self.elements.len() {
+            for j in 0..self.elements.len() {
+                if i != j
+                    && self.elements[i] != self.elements[j]
+                    && self.leq(&self.elements[i], &self.elements[j])
+                {
+                    self.ordering.push((i, j));
+                }
+            }
+        }
+    }
+
+    /// Elements of the lattice
+    #[must_use]
+    pub fn elements(&self) -> &[Pattern] {
+        &self.elements
+    }
+
+    /// The generalization hierarchy in use
+    #[must_use]
+    pub fn generalizations(&self) -> &Generalizations {
+        &self.generalizations
+    }
+
+    /// Check if `a` is at most as general as `b`
+    ///
+    /// Without generalizations this agrees with [`Pattern::subsumes`]: a
+    /// `>` in `b` is above any non-empty rest of `a`, while a `>` in `a` is
+    /// only below a `>` in `b` at the same or an earlier position.
+    #[must_use]
+    pub fn leq(&self, a: &Pattern, b: &Pattern) -> bool {
+        let (a, b) = (tokens(a), tokens(b));
+        for (i, general) in b.iter().enumerate() {
+            match a.get(i) {
+                None => return false,
+                Some(_) if *general == ">" => return true,
+                Some(specific) if !self.generalizations.token_leq(specific, general) => {
+                    return false;
+                },
+                Some(_) => {},
+            }
+        }
+        a.len() == b.len()
+    }
+
+    /// Least upper bound: the most specific pattern above both
+    #[must_use]
+    pub fn join(&self, a: &Pattern, b: &Pattern) -> Pattern {
+        let (a_tokens, b_tokens) = (tokens(a), tokens(b));
+        let shared = a_tokens.len().min(b_tokens.len());
+        // From the first `>`, or the last shared position if the lengths
+        // differ, the join must cover suffixes of any length
+        let tail = a_tokens
+            .iter()
+            .zip(&b_tokens)
+            .position(|(a, b)| *a == ">" || *b == ">")
+            .or_else(|| (a_tokens.len() != b_tokens.len()).then_some(shared - 1));
+        let mut joined: Vec<&str> = a_tokens
+            .iter()
+            .zip(&b_tokens)
+            .take(tail.unwrap_or(shared))
+            .map(|(a, b)| self.generalizations.token_join(a, b))
+            .collect();
+        if tail.is_some() {
+            joined.push(">");
+        }
+        Pattern::from_valid_tokens(&joined)
+    }
+
+    /// Greatest lower bound: the most general pattern below both
+    ///
+    /// Returns `None` when no subject lies below both patterns.
+    #[must_use]
+    pub fn meet(&self, a: &Pattern, b: &Pattern) -> Option<Pattern> {
+        let (a_tokens, b_tokens) = (tokens(a), tokens(b));
+        let mut met = Vec::new();
+        for i in 0.. {
+            match (a_tokens.get(i), b_tokens.get(i)) {
+                (None, None) => break,
+                // The other pattern's rest, which must not be empty
+                (Some(&">"), Some(_)) => {
+                    met.extend_from_slice(&b_tokens[i..]);
+                    break;
+                },
+                (Some(_), Some(&">")) => {
+                    met.extend_from_slice(&a_tokens[i..]);
+                    break;
+                },
+                (Some(a), Some(b)) => met.push(self.generalizations.token_meet(a, b)?),
+                _ => return None,
+            }
+        }
+        Some(Pattern::from_valid_tokens(&met))
+    }
+
+    /// Join of all elements, or `None` for an empty lattice
+    #[must_use]
+    pub fn top(&self) -> Option<Pattern> {
+        let (first, rest) = self.elements.split_first()?;
+        Some(rest.iter().fold(first.clone(), |acc, p| self.join(&acc, p)))
+    }
+
+    /// Meet of all elements, or `None` if the elements share no lower bound
+    #[must_use]
+    pub fn bottom(&self) -> Option<Pattern> {
+        let (first, rest) = self.elements.split_first()?;
+        rest.iter()
+            .try_fold(first.clone(), |acc, p| self.meet(&acc, p))
+    }
+
+    /// Elements strictly more general than `pattern`
+    pub fn above<'a>(&'a self, pattern: &'a Pattern) -> impl Iterator<Item = &'a Pattern> + 'a {
+        self.elements
+            .iter()
+            .filter(move |e| *e != pattern && self.leq(pattern, e))
+    }
+
+    /// Elements strictly more specific than `pattern`
+    pub fn below<'a>(&'a self, pattern: &'a Pattern) -> impl Iterator<Item = &'a Pattern> + 'a {
+        self.elements
+            .iter()
+            .filter(move |e| *e != pattern && self.leq(e, pattern))
+    }
+
+    /// Elements from most specific to most general
+    ///
+    /// Every element comes before all elements above it.
+    #[must_use]
+    pub fn iter_ascending(&self) -> Vec<&Pattern> {
+        let mut sorted: Vec<&Pattern> = self.elements.iter().collect();
+        sorted.sort_by_key(|p| self.generality(p));
+        sorted
+    }
+
+    /// Strict ordering relationships as `(less_idx, greater_idx)` pairs
+    #[must_use]
+    pub fn ordering(&self) -> &[(usize, usize)] {
+        &self.ordering
+    }
+
+    fn generality(&self, pattern: &Pattern) -> usize {
+        tokens(pattern)
+            .iter()
+            .map(|t| self.generalizations.weight(t))
+            .sum()
+    }
+}
+
+fn tokens(pattern: &Pattern) -> Vec<&str> {
+    pattern.as_str().split('.').collect()
+}
+
+fn is_wildcard(token: &str) -> bool {
+    token == "*" || token == ">"
+}
+
+fn validate_token(token: &str) -> Result<()> {
+    if token.is_empty()
+        || !token
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(SubjectError::invalid_pattern(format!(
+            "Token '{token}' contains invalid characters"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> Pattern {
+        Pattern::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_structural_join_and_meet() {
+        let lattice = SubjectLattice::new(&[]);
+
+        assert_eq!(
+            lattice
+                .join(&p("orders.order.placed.v1"), &p("orders.order.placed.v2"))
+                .as_str(),
+            "orders.order.placed.*"
+        );
+        assert_eq!(
+            lattice
+                .join(
+                    &p("orders.order.placed.v1"),
+                    &p("billing.invoice.placed.v1")
+                )
+                .as_str(),
+            "*.*.placed.v1"
+        );
+        assert_eq!(
+            lattice
+                .meet(&p("orders.*.placed.*"), &p("*.order.*.v1"))
+                .unwrap()
+                .as_str(),
+            "orders.order.placed.v1"
+        );
+        assert!(lattice
+            .meet(&p("orders.order.placed.v1"), &p("orders.order.shipped.v1"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_multi_wildcard_agrees_with_subsumes() {
+        let lattice = SubjectLattice::new(&[]);
+        let patterns = [
+            ">",
+            "orders.>",
+            "orders.*",
+            "orders.*.>",
+            "orders.order",
+            "orders.order.>",
+            "orders.order.placed",
+            "orders.*.placed",
+            "*.order.>",
+            "billing.>",
+        ]
+        .map(p);
+
+        for a in &patterns {
+            for b in &patterns {
+                assert_eq!(lattice.leq(a, b), b.subsumes(a), "{a} <= {b}");
+                let join = lattice.join(a, b);
+                assert!(join.subsumes(a) && join.subsumes(b), "{a} v {b} = {join}");
+                match lattice.meet(a, b) {
+                    Some(meet) => {
+                        assert!(a.subsumes(&meet) && b.subsumes(&meet), "{a} ^ {b} = {meet}");
+                    },
+                    None => assert!(!a.overlaps(b), "{a} ^ {b}"),
+                }
+            }
+        }
+        assert_eq!(
+            lattice.join(&p("orders.order"), &p("orders.order.placed")),
+            p("orders.>")
+        );
+        assert_eq!(
+            lattice.join(&p("orders.*.placed"), &p("orders.order.>")),
+            p("orders.*.>")
+        );
+        assert_eq!(
+            lattice.meet(&p("orders.>"), &p("*.order.>")),
+            Some(p("orders.order.>"))
+        );
+        assert_eq!(
+            lattice.meet(&p("orders.>"), &p("orders.*")),
+            Some(p("orders.*"))
+        );
+    }
+
+    #[test]
+    fn test_generalization_hierarchy() {
+        let generalizations = Generalizations::crud()
+            .generalize("lifecycle", &["changed", "archived"])
+            .unwrap();
+        assert!(generalizations
+            .clone()
+            .generalize("created", &["lifecycle"])
+            .is_err());
+
+        let subjects: Vec<Subject> = [
+            "events.base.changed.v1",
+            "events.base.created.v1",
+            "events.base.updated.v1",
+            "events.base.archived.v1",
+        ]
+        .iter()
+        .map(|s| Subject::new(*s).unwrap())
+        .collect();
+        let lattice = SubjectLattice::with_generalizations(&subjects, generalizations);
+
+        let created = Pattern::from(&subjects[1]);
+        let updated = Pattern::from(&subjects[2]);
+        let archived = Pattern::from(&subjects[3]);
+        assert_eq!(
+            lattice.join(&created, &updated).as_str(),
+            "events.base.changed.v1"
+        );
+        assert_eq!(
+            lattice.join(&created, &archived).as_str(),
+            "events.base.lifecycle.v1"
+        );
+        assert_eq!(
+            lattice.meet(&created, &p("events.base.changed.v1")),
+            Some(created.clone())
+        );
+
+        assert_eq!(lattice.top().unwrap().as_str(), "events.base.lifecycle.v1");
+        assert!(lattice.bottom().is_none());
+        assert_eq!(lattice.above(&created).count(), 1);
+        assert_eq!(lattice.below(&p("events.base.changed.v1")).count(), 2);
+        assert_eq!(
+            lattice.iter_ascending().last().unwrap().as_str(),
+            "events.base.changed.v1"
+        );
+        assert_eq!(lattice.ordering().len(), 2);
+    }
+}
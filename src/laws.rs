@@ -0,0 +1,325 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Algebraic law checks for composition rules
+//!
+//! Registered [`CompositionRule`](crate::algebra::CompositionRule)s can make
+//! an operation behave very differently from the defaults. These checks
+//! evaluate a law over every combination of sample subjects and report the
+//! first counterexample, so users can verify claims such as "our sequence
+//! rule is associative" in their own test suites.
+//!
+//! ```
+//! use cim_subject::laws::{
+//!     check_associative,
+//!     sample_subjects,
+//! };
+//! use cim_subject::{
+//!     AlgebraOperation,
+//!     SubjectAlgebra,
+//! };
+//!
+//! let algebra = SubjectAlgebra::new();
+//! let samples = sample_subjects(6, 42);
+//! assert!(check_associative(&algebra, &AlgebraOperation::Sequence, &samples).is_ok());
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use crate::algebra::{
+    AlgebraOperation,
+    SubjectAlgebra,
+};
+use crate::error::Result;
+use crate::subject::{
+    Subject,
+    SubjectParts,
+};
+
+/// An algebraic law
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Law {
+    /// `(a ∘ b) ∘ c == a ∘ (b ∘ c)`
+    Associative,
+    /// `a ∘ b == b ∘ a`
+    Commutative,
+    /// `a ∘ e == a` and `e ∘ a == a`
+    Identity,
+    /// `a ∘ z == z` and `z ∘ a == z`
+    Annihilator,
+}
+
+impl Display for Law {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Law::Associative => write!(f, "associativity"),
+            Law::Commutative => write!(f, "commutativity"),
+            Law::Identity => write!(f, "identity"),
+            Law::Annihilator => write!(f, "annihilation"),
+        }
+    }
+}
+
+/// A counterexample to a law
+#[derive(Debug, Clone, PartialEq)]
+pub struct LawViolation {
+    /// The law that failed
+    pub law: Law,
+    /// Name of the operation that was checked
+    pub operation: String,
+    /// Operands that produced the counterexample
+    pub operands: Vec<Subject>,
+    /// Result of the left-hand side of the law
+    pub left: Result<Subject>,
+    /// Result of the right-hand side of the law
+    pub right: Result<Subject>,
+}
+
+impl Display for LawViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operands: Vec<&str> = self.operands.iter().map(Subject::as_str).collect();
+        let show = |result: &Result<Subject>| match result {
+            Ok(subject) => subject.to_string(),
+            Err(e) => format!("error: {e}"),
+        };
+        write!(
+            f,
+            "{} of {} fails for [{}]: {} != {}",
+            self.law,
+            self.operation,
+            operands.join(", "),
+            show(&self.left),
+            show(&self.right)
+        )
+    }
+}
+
+impl std::error::Error for LawViolation {}
+
+/// Check `(a ∘ b) ∘ c == a ∘ (b ∘ c)` for all sample triples
+///
+/// # Errors
+///
+/// Returns the first counterexample found, boxed to keep `Ok` cheap
+pub fn check_associative(
+    algebra: &SubjectAlgebra,
+    operation: &AlgebraOperation,
+    samples: &[Subject],
+) -> std::result::Result<(), Box<LawViolation>> {
+    let compose = |a: &Subject, b: &Subject| algebra.compose(a, b, operation.clone());
+
+    for a in samples {
+        for b in samples {
+            for c in samples {
+                let left = compose(a, b).and_then(|ab| compose(&ab, c));
+                let right = compose(b, c).and_then(|bc| compose(a, &bc));
+                if left != right {
+                    return Err(violation(
+                        Law::Associative,
+                        operation,
+                        vec![a.clone(), b.clone(), c.clone()],
+                        left,
+                        right,
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check `a ∘ b == b ∘ a` for all sample pairs
+///
+/// # Errors
+///
+/// Returns the first counterexample found
+pub fn check_commutative(
+    algebra: &SubjectAlgebra,
+    operation: &AlgebraOperation,
+    samples: &[Subject],
+) -> std::result::Result<(), Box<LawViolation>> {
+    for (i, a) in samples.iter().enumerate() {
+        for b in &samples[i + 1..] {
+            let left = algebra.compose(a, b, operation.clone());
+            let right = algebra.compose(b, a, operation.clone());
+            if left != right {
+                return Err(violation(
+                    Law::Commutative,
+                    operation,
+                    vec![a.clone(), b.clone()],
+                    left,
+                    right,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check `a ∘ e == a` and `e ∘ a == a` for every sample
+///
+/// # Errors
+///
+/// Returns the first counterexample found
+pub fn check_identity(
+    algebra: &SubjectAlgebra,
+    operation: &AlgebraOperation,
+    identity: &Subject,
+    samples: &[Subject],
+) -> std::result::Result<(), Box<LawViolation>> {
+    for a in samples {
+        for (left, operands) in [
+            (algebra.compose(a, identity, operation.clone()), vec![
+                a.clone(),
+                identity.clone(),
+            ]),
+            (algebra.compose(identity, a, operation.clone()), vec![
+                identity.clone(),
+                a.clone(),
+            ]),
+        ] {
+            if left.as_ref() != Ok(a) {
+                return Err(violation(
+                    Law::Identity,
+                    operation,
+                    operands,
+                    left,
+                    Ok(a.clone()),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check `a ∘ z == z` and `z ∘ a == z` for every sample
+///
+/// # Errors
+///
+/// Returns the first counterexample found
+pub fn check_annihilator(
+    algebra: &SubjectAlgebra,
+    operation: &AlgebraOperation,
+    zero: &Subject,
+    samples: &[Subject],
+) -> std::result::Result<(), Box<LawViolation>> {
+    for a in samples {
+        for (left, operands) in [
+            (algebra.compose(a, zero, operation.clone()), vec![
+                a.clone(),
+                zero.clone(),
+            ]),
+            (algebra.compose(zero, a, operation.clone()), vec![
+                zero.clone(),
+                a.clone(),
+            ]),
+        ] {
+            if left.as_ref() != Ok(zero) {
+                return Err(violation(
+                    Law::Annihilator,
+                    operation,
+                    operands,
+                    left,
+                    Ok(zero.clone()),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Generate deterministic pseudo-random sample subjects
+///
+/// The same `count` and `seed` always produce the same subjects, so failing
+/// checks are reproducible.
+#[must_use]
+pub fn sample_subjects(count: usize, seed: u64) -> Vec<Subject> {
+    const CONTEXTS: [&str; 5] = ["orders", "billing", "users", "inventory", "shipping"];
+    const AGGREGATES: [&str; 5] = ["order", "invoice", "user", "stock", "parcel"];
+    const EVENTS: [&str; 5] = ["created", "updated", "deleted", "shipped", "paid"];
+    const VERSIONS: [&str; 3] = ["v1", "v2", "v3"];
+
+    // xorshift64*; a zero state would stay zero
+    let mut state = seed.max(1);
+    let mut next = |len: usize| {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let value = state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32;
+        usize::try_from(value).unwrap_or_default() % len
+    };
+
+    (0..count)
+        .map(|_| {
+            Subject::from_parts(SubjectParts::new(
+                CONTEXTS[next(CONTEXTS.len())],
+                AGGREGATES[next(AGGREGATES.len())],
+                EVENTS[next(EVENTS.len())],
+                VERSIONS[next(VERSIONS.len())],
+            ))
+        })
+        .collect()
+}
+
+fn violation(
+    law: Law,
+    operation: &AlgebraOperation,
+    operands: Vec<Subject>,
+    left: Result<Subject>,
+    right: Result<Subject>,
+) -> Box<LawViolation> {
+    Box::new(LawViolation {
+        law,
+        operation: format!("{operation:?}"),
+        operands,
+        left,
+        right,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_operation_laws() {
+        let algebra = SubjectAlgebra::new();
+        let samples = sample_subjects(5, 7);
+        assert_eq!(samples, sample_subjects(5, 7));
+
+        assert!(check_associative(&algebra, &AlgebraOperation::Sequence, &samples).is_ok());
+        assert!(check_associative(&algebra, &AlgebraOperation::Parallel, &samples).is_ok());
+
+        // Default parallel composition keeps operand order in the subject
+        let distinct = [
+            Subject::new("orders.order.created.v1").unwrap(),
+            Subject::new("billing.invoice.paid.v1").unwrap(),
+        ];
+        let violation =
+            check_commutative(&algebra, &AlgebraOperation::Parallel, &distinct).unwrap_err();
+        assert_eq!(violation.law, Law::Commutative);
+        assert!(violation
+            .to_string()
+            .starts_with("commutativity of Parallel fails"));
+
+        let unit = SubjectAlgebra::unit();
+        assert!(check_identity(&algebra, &AlgebraOperation::Sequence, &unit, &samples).is_ok());
+        assert!(check_identity(&algebra, &AlgebraOperation::Parallel, &unit, &samples).is_ok());
+        let not_unit = Subject::new("unit.unit.unit.v1").unwrap();
+        assert!(
+            check_identity(&algebra, &AlgebraOperation::Sequence, &not_unit, &samples).is_err()
+        );
+
+        let zero = SubjectAlgebra::zero();
+        assert!(check_annihilator(&algebra, &AlgebraOperation::Sequence, &zero, &samples).is_ok());
+        assert!(check_annihilator(&algebra, &AlgebraOperation::Parallel, &zero, &samples).is_ok());
+        let violation =
+            check_annihilator(&algebra, &AlgebraOperation::Sequence, &unit, &samples).unwrap_err();
+        assert_eq!(violation.law, Law::Annihilator);
+        assert!(violation
+            .to_string()
+            .starts_with("annihilation of Sequence fails"));
+    }
+}
@@ -41,72 +41,302 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## `no_std`
+//!
+//! With default features off, the crate builds without `std` on any target
+//! with `alloc`, keeping [`Subject`], [`SubjectParts`], [`Pattern`] and
+//! matching, the AMQP and MQTT conversions, the subscription planner, and
+//! UUID message identities built with [`MessageFactory`] from caller-supplied
+//! UUIDs. Everything else, including CID event identities and random UUIDs,
+//! needs the default `std` feature.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod aggregator;
+#[cfg(feature = "std")]
 pub mod algebra;
+#[cfg(feature = "std")]
+pub mod algebra_expr;
+pub mod amqp;
+#[cfg(feature = "std")]
+pub mod authorization;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bound;
+#[cfg(feature = "std")]
+pub mod chain_integrity;
+#[cfg(feature = "std")]
+pub mod chain_store;
+#[cfg(feature = "std")]
+pub mod circuit_breaker;
+#[cfg(feature = "std")]
+pub mod classification;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod compiled_permissions;
+#[cfg(feature = "std")]
+pub mod concurrency;
+#[cfg(feature = "std")]
+pub mod conditions;
+#[cfg(feature = "std")]
+pub mod context_scope;
 pub mod correlation;
+#[cfg(feature = "encryption")]
+pub mod crypto_policy;
+#[cfg(feature = "std")]
+pub mod deduplication;
+#[cfg(feature = "std")]
+pub mod deprecation;
+#[cfg(feature = "nats")]
+pub mod discovery;
+#[cfg(feature = "std")]
+pub mod envelope;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod event_stream;
+#[cfg(feature = "std")]
+pub mod expected_flow;
+#[cfg(feature = "std")]
+pub mod extended_pattern;
+#[cfg(feature = "std")]
+pub mod fan_out;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod field_transform;
+#[cfg(feature = "std")]
+pub mod flow_discovery;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod header_convention;
+#[cfg(feature = "std")]
+pub mod headers;
+#[cfg(feature = "std")]
+pub mod hierarchy;
+#[cfg(feature = "std")]
+pub mod interning;
+#[cfg(feature = "nats")]
+pub mod jetstream_chain_store;
+#[cfg(feature = "std")]
+pub mod kafka;
+#[cfg(feature = "std")]
+pub mod lattice;
+#[cfg(feature = "std")]
+pub mod laws;
+#[cfg(feature = "std")]
+pub mod lifecycle;
+pub mod limits;
+#[cfg(feature = "std")]
 pub mod message_algebra;
+pub mod mqtt;
+#[cfg(feature = "std")]
+pub mod nats_auth;
+#[cfg(feature = "std")]
+pub mod normalization;
+#[cfg(feature = "std")]
 pub mod parser;
+pub mod partition;
+#[cfg(feature = "std")]
+pub mod paths;
 pub mod pattern;
+#[cfg(feature = "std")]
+pub mod permission_audit;
+#[cfg(feature = "std")]
 pub mod permissions;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "std")]
+pub mod presets;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod redaction;
+#[cfg(feature = "std")]
+pub mod repair;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod request_reply;
+#[cfg(feature = "std")]
+pub mod router;
+#[cfg(feature = "std")]
+pub mod schema_registry;
+#[cfg(feature = "json-schema")]
+pub mod schema_validation;
+#[cfg(feature = "std")]
+pub mod scope;
+#[cfg(feature = "signing")]
+pub mod signing;
+#[cfg(feature = "std")]
+pub mod snapshot;
 pub mod subject;
+pub mod subscription_planner;
+#[cfg(feature = "std")]
+pub mod telemetry;
+#[cfg(feature = "std")]
+pub mod tenancy;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "std")]
 pub mod translator;
+#[cfg(feature = "std")]
+pub mod versioning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod workflow;
 
 // Re-export main types
+#[cfg(feature = "std")]
 pub use algebra::{
     AlgebraOperation,
+    CompositionKind,
     CompositionRule,
     SubjectAlgebra,
+    UNIT_SUBJECT,
+    ZERO_SUBJECT,
 };
+#[cfg(feature = "std")]
+pub use algebra_expr::AlgebraExpr;
+pub use amqp::AmqpBindingError;
+#[cfg(feature = "std")]
+pub use chain_store::{
+    ChainStore,
+    FileChainStore,
+    InMemoryChainStore,
+};
+#[cfg(feature = "std")]
+pub use compiled_permissions::CompiledPermissions;
+#[cfg(feature = "std")]
+pub use correlation::SerializableCid;
 pub use correlation::{
     CausationId,
     CorrelationError,
     CorrelationId,
     CorrelationValidator,
     IdType,
+    IdentityPolicy,
     MessageFactory,
     MessageIdentity,
-    SerializableCid,
+    MessageKind,
+    PolicyViolation,
 };
+#[cfg(feature = "std")]
+pub use envelope::Envelope;
 pub use error::{
+    ErrorCode,
     Result,
     SubjectError,
 };
+#[cfg(feature = "std")]
+pub use extended_pattern::ExtendedPattern;
+#[cfg(feature = "std")]
+pub use field_transform::FieldTransform;
+#[cfg(feature = "std")]
+pub use format::PayloadFormat;
+#[cfg(feature = "std")]
+pub use header_convention::HeaderConvention;
+#[cfg(feature = "std")]
+pub use headers::Headers;
+#[cfg(feature = "std")]
+pub use hierarchy::SubjectHierarchy;
+#[cfg(feature = "nats")]
+pub use jetstream_chain_store::JetStreamChainStore;
+#[cfg(feature = "std")]
+pub use lattice::{
+    Generalizations,
+    SubjectLattice,
+};
+pub use limits::SubjectLimits;
+#[cfg(feature = "std")]
 pub use message_algebra::{
+    ChainDiff,
+    ChainEntry,
+    ChainGraph,
+    ChainStats,
     CorrelationChain,
     MessageAlgebra,
+    Saga,
+    SagaStep,
 };
+#[cfg(feature = "std")]
+pub use nats_auth::NatsAuthorization;
+#[cfg(feature = "std")]
+pub use normalization::NormalizationPolicy;
+#[cfg(feature = "std")]
 pub use parser::{
+    AggregateIdentity,
     ParseRule,
+    Separator,
     SubjectParser,
 };
 pub use pattern::{
     Pattern,
     PatternMatcher,
 };
+#[cfg(feature = "std")]
+pub use permission_audit::{
+    Explanation,
+    InactiveReason,
+    InactiveRule,
+    PermissionsDiff,
+    SimulationReport,
+    TrafficRecord,
+};
+#[cfg(feature = "std")]
 pub use permissions::{
     PermissionRule,
     Permissions,
 };
+#[cfg(feature = "std")]
+pub use router::{
+    DeadLetter,
+    DispatchMode,
+    QueueBalancing,
+    RetryPolicy,
+    RouteOptions,
+    Router,
+};
 pub use subject::{
     Subject,
     SubjectBuilder,
+    SubjectMut,
     SubjectParts,
+    SubjectPartsRef,
+    SubjectRef,
 };
+pub use subscription_planner::SubscriptionPlanner;
+#[cfg(feature = "std")]
 pub use translator::{
+    JsonMessageTranslator,
     MessageTranslator,
     NatsMessage,
+    NonInvertibleMapping,
+    Payload,
     TranslationRule,
     Translator,
 };
+#[cfg(feature = "std")]
+pub use workflow::{
+    Workflow,
+    WorkflowBuilder,
+    WorkflowStep,
+};
 
 /// Prelude module for convenient imports
 pub mod prelude {
+    #[cfg(feature = "std")]
     pub use crate::{
         AlgebraOperation,
         CausationId,
@@ -119,18 +349,20 @@ pub mod prelude {
         MessageFactory,
         MessageIdentity,
         NatsMessage,
-        Pattern,
-        PatternMatcher,
         PermissionRule,
         Permissions,
-        Result,
         SerializableCid,
-        Subject,
         SubjectAlgebra,
+        TranslationRule,
+        Translator,
+    };
+    pub use crate::{
+        Pattern,
+        PatternMatcher,
+        Result,
+        Subject,
         SubjectBuilder,
         SubjectError,
         SubjectParts,
-        TranslationRule,
-        Translator,
     };
 }
@@ -0,0 +1,325 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Correlation chain lifecycle
+//!
+//! A correlation chain opens with its first message and ends when the
+//! workflow it tracks finishes. A [`ChainLifecycle`] names the terminal
+//! subjects: a completion subject moves a chain to
+//! [`ChainState::Completing`] until it is confirmed with
+//! [`ChainTracker::mark_completed`], and a failure subject moves it to
+//! [`ChainState::Failed`]. A [`ChainTracker`] follows the state of every
+//! chain it sees, so chains still open long after they started can be
+//! flagged as stuck.
+//!
+//! ```
+//! use std::time::{
+//!     Duration,
+//!     SystemTime,
+//! };
+//!
+//! use cim_subject::lifecycle::{
+//!     ChainLifecycle,
+//!     ChainState,
+//!     ChainTracker,
+//! };
+//! use cim_subject::message_algebra::ChainEntry;
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     Pattern,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let lifecycle = ChainLifecycle::new()
+//!     .completes_on(Pattern::new("orders.events.order.shipped")?)
+//!     .fails_on(Pattern::new("orders.events.order.cancelled")?);
+//! let mut tracker = ChainTracker::new(lifecycle);
+//!
+//! let start = SystemTime::UNIX_EPOCH;
+//! let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let entry = ChainEntry::new(root.clone(), start)
+//!     .with_subject(Subject::new("orders.commands.order.place")?);
+//! assert_eq!(tracker.observe(&entry), ChainState::Open);
+//!
+//! let an_hour_later = start + Duration::from_secs(3_600);
+//! let stuck = tracker.open_older_than(Duration::from_secs(600), an_hour_later);
+//! assert_eq!(stuck, [&root.correlation_id]);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{
+    self,
+    Display,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use crate::correlation::CorrelationId;
+use crate::message_algebra::ChainEntry;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Where a correlation chain is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChainState {
+    /// Messages are still expected
+    Open,
+    /// A completion subject was seen; awaiting confirmation
+    Completing,
+    /// The chain finished successfully
+    Completed,
+    /// The chain finished unsuccessfully
+    Failed,
+}
+
+impl ChainState {
+    /// Check if the chain has finished, successfully or not
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+impl Display for ChainState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Completing => write!(f, "completing"),
+            Self::Completed => write!(f, "completed"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// The subjects that end a chain
+#[derive(Debug, Clone, Default)]
+pub struct ChainLifecycle {
+    completes_on: Vec<Pattern>,
+    fails_on: Vec<Pattern>,
+}
+
+impl ChainLifecycle {
+    /// Create a lifecycle without terminal subjects
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subjects matching `pattern` complete the chain
+    #[must_use]
+    pub fn completes_on(mut self, pattern: Pattern) -> Self {
+        self.completes_on.push(pattern);
+        self
+    }
+
+    /// Subjects matching `pattern` fail the chain
+    #[must_use]
+    pub fn fails_on(mut self, pattern: Pattern) -> Self {
+        self.fails_on.push(pattern);
+        self
+    }
+
+    /// The state a message on `subject` moves an open chain to; failure
+    /// subjects win over completion subjects
+    #[must_use]
+    pub fn transition(&self, subject: &Subject) -> ChainState {
+        if self.fails_on.iter().any(|pattern| pattern.matches(subject)) {
+            ChainState::Failed
+        } else if self
+            .completes_on
+            .iter()
+            .any(|pattern| pattern.matches(subject))
+        {
+            ChainState::Completing
+        } else {
+            ChainState::Open
+        }
+    }
+}
+
+/// A tracked chain's state and timing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainStatus {
+    /// Current state
+    pub state: ChainState,
+    /// When the chain's first message was observed
+    pub opened_at: SystemTime,
+    /// When the chain's latest message was observed
+    pub last_activity: SystemTime,
+}
+
+/// Follows the lifecycle state of every chain it observes
+#[derive(Debug, Clone, Default)]
+pub struct ChainTracker {
+    lifecycle: ChainLifecycle,
+    chains: HashMap<CorrelationId, ChainStatus>,
+}
+
+impl ChainTracker {
+    /// Create a tracker for `lifecycle`
+    #[must_use]
+    pub fn new(lifecycle: ChainLifecycle) -> Self {
+        Self {
+            lifecycle,
+            chains: HashMap::new(),
+        }
+    }
+
+    /// Observe a message, returning its chain's new state
+    ///
+    /// Finished chains keep their state; entries without a subject only
+    /// record activity.
+    pub fn observe(&mut self, entry: &ChainEntry) -> ChainState {
+        let status = self
+            .chains
+            .entry(entry.identity.correlation_id.clone())
+            .or_insert(ChainStatus {
+                state: ChainState::Open,
+                opened_at: entry.timestamp,
+                last_activity: entry.timestamp,
+            });
+        status.last_activity = status.last_activity.max(entry.timestamp);
+        if !status.state.is_terminal() {
+            let next = entry.subject.as_ref().map_or(ChainState::Open, |subject| {
+                self.lifecycle.transition(subject)
+            });
+            // A completing chain does not reopen
+            if next != ChainState::Open {
+                status.state = next;
+            }
+        }
+        status.state
+    }
+
+    /// Confirm that a chain completed, whatever its current state
+    pub fn mark_completed(&mut self, correlation_id: &CorrelationId) {
+        self.set_state(correlation_id, ChainState::Completed);
+    }
+
+    /// Record that a chain failed, whatever its current state
+    pub fn mark_failed(&mut self, correlation_id: &CorrelationId) {
+        self.set_state(correlation_id, ChainState::Failed);
+    }
+
+    fn set_state(&mut self, correlation_id: &CorrelationId, state: ChainState) {
+        if let Some(status) = self.chains.get_mut(correlation_id) {
+            status.state = state;
+        }
+    }
+
+    /// The state of a chain, if it has been observed
+    #[must_use]
+    pub fn state(&self, correlation_id: &CorrelationId) -> Option<ChainState> {
+        self.status(correlation_id).map(|status| status.state)
+    }
+
+    /// The status of a chain, if it has been observed
+    #[must_use]
+    pub fn status(&self, correlation_id: &CorrelationId) -> Option<&ChainStatus> {
+        self.chains.get(correlation_id)
+    }
+
+    /// Chains not yet finished that opened more than `threshold` before
+    /// `now`, oldest first
+    #[must_use]
+    pub fn open_older_than(&self, threshold: Duration, now: SystemTime) -> Vec<&CorrelationId> {
+        let mut stuck: Vec<(&CorrelationId, &ChainStatus)> = self
+            .chains
+            .iter()
+            .filter(|(_, status)| {
+                !status.state.is_terminal()
+                    && now
+                        .duration_since(status.opened_at)
+                        .is_ok_and(|age| age > threshold)
+            })
+            .collect();
+        stuck.sort_by_key(|(_, status)| status.opened_at);
+        stuck
+            .into_iter()
+            .map(|(correlation_id, _)| correlation_id)
+            .collect()
+    }
+
+    /// Stop tracking finished chains, returning how many were dropped
+    pub fn prune_finished(&mut self) -> usize {
+        let before = self.chains.len();
+        self.chains.retain(|_, status| !status.state.is_terminal());
+        before - self.chains.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::{
+        IdType,
+        MessageIdentity,
+    };
+
+    fn entry(identity: &MessageIdentity, subject: &str, secs: u64) -> ChainEntry {
+        ChainEntry::new(
+            identity.clone(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        )
+        .with_subject(Subject::new(subject).unwrap())
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let lifecycle = ChainLifecycle::new()
+            .completes_on(Pattern::new("orders.events.order.shipped").unwrap())
+            .fails_on(Pattern::new("orders.events.*.cancelled").unwrap());
+        let mut tracker = ChainTracker::new(lifecycle);
+        let shipped = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let cancelled = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+
+        tracker.observe(&entry(&shipped, "orders.commands.order.place", 0));
+        let state = tracker.observe(&entry(&shipped, "orders.events.order.shipped", 5));
+        assert_eq!(state, ChainState::Completing);
+        // Later non-terminal messages do not reopen the chain
+        let state = tracker.observe(&entry(&shipped, "orders.events.order.noted", 6));
+        assert_eq!(state, ChainState::Completing);
+        tracker.mark_completed(&shipped.correlation_id);
+        assert_eq!(
+            tracker.state(&shipped.correlation_id),
+            Some(ChainState::Completed)
+        );
+
+        tracker.observe(&entry(&cancelled, "orders.events.order.cancelled", 1));
+        let state = tracker.observe(&entry(&cancelled, "orders.events.order.shipped", 2));
+        assert_eq!(state, ChainState::Failed);
+        let status = tracker.status(&cancelled.correlation_id).unwrap();
+        assert_eq!(
+            status.last_activity,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(2)
+        );
+        assert_eq!(tracker.prune_finished(), 2);
+    }
+
+    #[test]
+    fn test_stuck_chains() {
+        let mut tracker = ChainTracker::new(
+            ChainLifecycle::new().completes_on(Pattern::new("*.events.*.done").unwrap()),
+        );
+        let ids: Vec<MessageIdentity> = (0..3)
+            .map(|_| MessageIdentity::root(IdType::Uuid(Uuid::new_v4())))
+            .collect();
+        tracker.observe(&entry(&ids[0], "orders.commands.order.place", 100));
+        tracker.observe(&entry(&ids[1], "orders.commands.order.place", 10));
+        tracker.observe(&entry(&ids[2], "orders.commands.order.place", 0));
+        tracker.mark_failed(&ids[2].correlation_id);
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(200);
+        let stuck = tracker.open_older_than(Duration::from_secs(60), now);
+        assert_eq!(stuck, [&ids[1].correlation_id, &ids[0].correlation_id]);
+        assert_eq!(tracker.open_older_than(Duration::from_secs(150), now), [
+            &ids[1].correlation_id
+        ]);
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Size limits on subjects and patterns
+//!
+//! NATS servers and clients reject overly long subjects, and unbounded
+//! input from outside a system is a resource risk. [`SubjectLimits`] bounds
+//! the total length, the number of tokens and the length of each token.
+//! [`Subject::new`] and [`Pattern::new`] enforce
+//! [`SubjectLimits::DEFAULT`]; [`Subject::new_with_limits`] and
+//! [`Pattern::new_with_limits`] take other limits, and
+//! [`SubjectLimits::check_limits`] vets a raw string before it is parsed.
+//!
+//! ```
+//! use cim_subject::limits::SubjectLimits;
+//! use cim_subject::{
+//!     ErrorCode,
+//!     Subject,
+//! };
+//!
+//! let limits = SubjectLimits::DEFAULT.max_bytes(16);
+//! assert!(limits.check_limits("orders.order.placed.v1").is_err());
+//!
+//! let err = Subject::new_with_limits("orders.order.placed.v1", &limits).unwrap_err();
+//! assert_eq!(err.code(), ErrorCode::LimitExceeded);
+//! assert_eq!(
+//!     err.to_string(),
+//!     "Limit exceeded: 22 bytes, at most 16 allowed"
+//! );
+//! ```
+
+use core::fmt::{
+    self,
+    Display,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+#[cfg(doc)]
+use crate::pattern::Pattern;
+#[cfg(doc)]
+use crate::subject::Subject;
+
+/// Which limit was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// Total length in bytes
+    Bytes,
+    /// Number of tokens
+    Tokens,
+    /// Length of a single token in bytes
+    TokenBytes,
+}
+
+/// A subject or pattern over one of its limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// The limit exceeded
+    pub limit: Limit,
+    /// The largest value allowed
+    pub max: usize,
+    /// The value found
+    pub actual: usize,
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.limit {
+            Limit::Bytes => "bytes",
+            Limit::Tokens => "tokens",
+            Limit::TokenBytes => "bytes in a token",
+        };
+        write!(f, "{} {what}, at most {} allowed", self.actual, self.max)
+    }
+}
+
+/// Bounds on the size of subjects and patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubjectLimits {
+    /// Maximum total length in bytes
+    pub max_bytes: usize,
+    /// Maximum number of tokens
+    pub max_tokens: usize,
+    /// Maximum length of a token in bytes
+    pub max_token_bytes: usize,
+}
+
+impl Default for SubjectLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl SubjectLimits {
+    /// Limits enforced by [`Subject::new`] and [`Pattern::new`], well above
+    /// what real deployments use
+    pub const DEFAULT: Self = Self {
+        max_bytes: 1024,
+        max_tokens: 32,
+        max_token_bytes: 256,
+    };
+
+    /// No limits at all
+    pub const UNLIMITED: Self = Self {
+        max_bytes: usize::MAX,
+        max_tokens: usize::MAX,
+        max_token_bytes: usize::MAX,
+    };
+
+    /// Set the maximum total length
+    #[must_use]
+    pub const fn max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = max;
+        self
+    }
+
+    /// Set the maximum number of tokens
+    #[must_use]
+    pub const fn max_tokens(mut self, max: usize) -> Self {
+        self.max_tokens = max;
+        self
+    }
+
+    /// Set the maximum length of a token
+    #[must_use]
+    pub const fn max_token_bytes(mut self, max: usize) -> Self {
+        self.max_token_bytes = max;
+        self
+    }
+
+    /// Check a raw subject or pattern against the limits without parsing it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubjectError::LimitExceeded`] for the first limit exceeded,
+    /// checking total length, then token count, then token length
+    pub fn check_limits(&self, subject: &str) -> Result<()> {
+        let exceeded = |limit, max, actual| {
+            Err(SubjectError::LimitExceeded(LimitExceeded {
+                limit,
+                max,
+                actual,
+            }))
+        };
+        if subject.len() > self.max_bytes {
+            return exceeded(Limit::Bytes, self.max_bytes, subject.len());
+        }
+        let tokens = subject.split('.').count();
+        if tokens > self.max_tokens {
+            return exceeded(Limit::Tokens, self.max_tokens, tokens);
+        }
+        if let Some(longest) = subject
+            .split('.')
+            .map(str::len)
+            .find(|&len| len > self.max_token_bytes)
+        {
+            return exceeded(Limit::TokenBytes, self.max_token_bytes, longest);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::subject::Subject;
+
+    #[test]
+    fn test_limits() {
+        let limits = SubjectLimits::DEFAULT.max_tokens(3).max_token_bytes(5);
+        assert!(limits.check_limits("a.b.c").is_ok());
+        assert_eq!(
+            limits.check_limits("a.b.c.d"),
+            Err(SubjectError::LimitExceeded(LimitExceeded {
+                limit: Limit::Tokens,
+                max: 3,
+                actual: 4,
+            }))
+        );
+        let err = limits.check_limits("a.orders.c").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Limit exceeded: 6 bytes in a token, at most 5 allowed"
+        );
+
+        // Construction enforces the defaults
+        let long = "x".repeat(300);
+        assert!(Subject::new(format!("orders.{long}.placed.v1")).is_err());
+        assert!(Subject::new_with_limits(
+            format!("orders.{long}.placed.v1"),
+            &SubjectLimits::UNLIMITED
+        )
+        .is_ok());
+        assert!(Pattern::new_with_limits("orders.>", &limits.max_bytes(4)).is_err());
+    }
+}
@@ -3,20 +3,37 @@
 //! # Message Algebra for Correlation Chains
 //!
 //! This module implements algebraic operations on message correlation chains,
-//! enabling complex message flow analysis and validation.
+//! enabling complex message flow analysis and validation. A [`Saga`] builds
+//! on a chain to track forward steps and the subjects that compensate them.
 
 use std::collections::{
+    BTreeMap,
     HashMap,
     HashSet,
     VecDeque,
 };
+use std::fmt::Write as _;
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
 
 use crate::correlation::{
+    CausationId,
     CorrelationError,
     IdType,
     MessageIdentity,
     Result,
 };
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::telemetry;
+use crate::workflow::Workflow;
 
 /// Represents a correlation chain - a sequence of related messages
 #[derive(Debug, Clone)]
@@ -32,6 +49,99 @@ pub struct CorrelationChain {
 
     /// Reverse causation: parent -> children
     pub caused_messages: HashMap<IdType, Vec<IdType>>,
+
+    /// When each timed message was observed
+    pub timestamps: HashMap<IdType, SystemTime>,
+
+    /// Subjects messages were published on, where known
+    pub subjects: HashMap<IdType, Subject>,
+
+    /// Messages whose handling has finished
+    pub completed: HashSet<IdType>,
+
+    /// Upper bound on the number of messages kept; see
+    /// [`with_max_messages`](Self::with_max_messages)
+    pub max_messages: Option<usize>,
+
+    /// Leaves in eviction order
+    eviction: EvictionIndex,
+}
+
+/// Key ordering leaves for eviction: untimed before timed, then by
+/// timestamp, then by insertion
+type EvictionKey = (Option<SystemTime>, u64);
+
+/// The non-root messages of a chain, with the current leaves ordered
+/// oldest first so eviction does not have to search for them
+#[derive(Debug, Clone, Default)]
+struct EvictionIndex {
+    keys: HashMap<IdType, EvictionKey>,
+    leaves: BTreeMap<EvictionKey, IdType>,
+    next: u64,
+}
+
+impl EvictionIndex {
+    /// Track a newly added message, which is a leaf
+    fn insert(&mut self, id: IdType, timestamp: Option<SystemTime>) {
+        let key = (timestamp, self.next);
+        self.next += 1;
+        self.keys.insert(id.clone(), key);
+        self.leaves.insert(key, id);
+    }
+
+    /// Record whether a tracked message is a leaf; untracked ones, such as
+    /// the root, are ignored
+    fn set_leaf(&mut self, id: &IdType, leaf: bool) {
+        if let Some(key) = self.keys.get(id) {
+            if leaf {
+                self.leaves.insert(*key, id.clone());
+            } else {
+                self.leaves.remove(key);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: &IdType) {
+        if let Some(key) = self.keys.remove(id) {
+            self.leaves.remove(&key);
+        }
+    }
+
+    /// The oldest leaf other than `keep`
+    fn oldest(&self, keep: &IdType) -> Option<&IdType> {
+        self.leaves.values().find(|id| *id != keep)
+    }
+}
+
+/// A message identity with the time it was observed and, optionally, its
+/// subject
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainEntry {
+    /// The message identity
+    pub identity: MessageIdentity,
+    /// When the message was observed
+    pub timestamp: SystemTime,
+    /// Subject the message was published on
+    pub subject: Option<Subject>,
+}
+
+impl ChainEntry {
+    /// Create an entry observed at `timestamp`
+    #[must_use]
+    pub fn new(identity: MessageIdentity, timestamp: SystemTime) -> Self {
+        Self {
+            identity,
+            timestamp,
+            subject: None,
+        }
+    }
+
+    /// Attach the message's subject
+    #[must_use]
+    pub fn with_subject(mut self, subject: Subject) -> Self {
+        self.subject = Some(subject);
+        self
+    }
 }
 
 impl CorrelationChain {
@@ -55,9 +165,113 @@ impl CorrelationChain {
             messages,
             causation_graph: HashMap::new(),
             caused_messages: HashMap::new(),
+            timestamps: HashMap::new(),
+            subjects: HashMap::new(),
+            completed: HashSet::new(),
+            max_messages: None,
+            eviction: EvictionIndex::default(),
+        })
+    }
+
+    /// Bound the chain to `max` messages
+    ///
+    /// When an added message takes the chain over the bound, the oldest
+    /// leaves are evicted: untimed leaves first, then by timestamp. The root
+    /// and the message just added are never evicted, so the bound is best
+    /// effort for chains that are a single long path.
+    #[must_use]
+    pub fn with_max_messages(mut self, max: usize) -> Self {
+        self.max_messages = Some(max);
+        self
+    }
+
+    /// Create a new chain from a timed root entry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry's message is not a root message
+    pub fn from_entry(root: ChainEntry) -> Result<Self> {
+        let mut chain = Self::new(root.identity.clone())?;
+        chain.record_entry_details(root);
+        Ok(chain)
+    }
+
+    /// Add a timed message to the chain
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as
+    /// [`add_message`](Self::add_message)
+    pub fn add_entry(&mut self, entry: ChainEntry) -> Result<()> {
+        self.insert_message(entry.identity.clone(), Some(entry.timestamp))?;
+        self.record_entry_details(entry);
+        Ok(())
+    }
+
+    /// The timed entry for a message, if it has a timestamp
+    #[must_use]
+    pub fn entry(&self, message_id: &IdType) -> Option<ChainEntry> {
+        Some(ChainEntry {
+            identity: self.messages.get(message_id)?.clone(),
+            timestamp: *self.timestamps.get(message_id)?,
+            subject: self.subjects.get(message_id).cloned(),
         })
     }
 
+    fn record_entry_details(&mut self, entry: ChainEntry) {
+        let id = entry.identity.message_id;
+        if let Some(subject) = entry.subject {
+            self.subjects.insert(id.clone(), subject);
+        }
+        self.timestamps.insert(id, entry.timestamp);
+    }
+
+    /// Time between a message's cause and the message itself
+    ///
+    /// `None` if either timestamp is missing. A message observed before its
+    /// cause (clock skew) has zero latency.
+    #[must_use]
+    pub fn hop_latency(&self, message_id: &IdType) -> Option<Duration> {
+        let parent = self.causation_graph.get(message_id)?;
+        let at = self.timestamps.get(message_id)?;
+        let parent_at = self.timestamps.get(parent)?;
+        Some(at.duration_since(*parent_at).unwrap_or(Duration::ZERO))
+    }
+
+    /// Latency of every timed hop, in breadth-first order of the effect
+    #[must_use]
+    pub fn hop_latencies(&self) -> Vec<(IdType, Duration)> {
+        self.iter_bfs()
+            .filter_map(|m| Some((m.message_id.clone(), self.hop_latency(&m.message_id)?)))
+            .collect()
+    }
+
+    /// Time from the root to the latest timed message
+    #[must_use]
+    pub fn end_to_end_latency(&self) -> Option<Duration> {
+        let (_, latest) = self.latest_timed()?;
+        latest
+            .duration_since(*self.timestamps.get(&self.root.message_id)?)
+            .ok()
+    }
+
+    /// The root-to-message path ending at the latest timed message
+    ///
+    /// Hop latencies along a path sum to the time between its ends, so this
+    /// is the path that accounts for the end-to-end latency.
+    #[must_use]
+    pub fn slowest_path(&self) -> Vec<&MessageIdentity> {
+        self.latest_timed()
+            .and_then(|(id, _)| self.get_path_to(id).ok())
+            .unwrap_or_default()
+    }
+
+    fn latest_timed(&self) -> Option<(&IdType, SystemTime)> {
+        self.iter_bfs()
+            .filter_map(|m| Some((&m.message_id, *self.timestamps.get(&m.message_id)?)))
+            .reduce(|latest, next| if next.1 > latest.1 { next } else { latest })
+    }
+
     /// Add a message to the chain
     ///
     /// # Errors
@@ -65,7 +279,20 @@ impl CorrelationChain {
     /// Returns an error if:
     /// - The message's correlation ID doesn't match the chain
     /// - The message's parent is not found in the chain
+    /// - A different message with the same ID is already in the chain
+    ///
+    /// Adding a message that is already in the chain does nothing.
     pub fn add_message(&mut self, message: MessageIdentity) -> Result<()> {
+        self.insert_message(message, None)
+    }
+
+    /// Add a message observed at `timestamp`, if known, then evict down to
+    /// the bound
+    fn insert_message(
+        &mut self,
+        message: MessageIdentity,
+        timestamp: Option<SystemTime>,
+    ) -> Result<()> {
         // Verify correlation matches
         if message.correlation_id != self.root.correlation_id {
             return Err(CorrelationError::InvalidIdentity(
@@ -73,6 +300,17 @@ impl CorrelationChain {
             ));
         }
 
+        if let Some(existing) = self.messages.get(&message.message_id) {
+            return if *existing == message {
+                Ok(())
+            } else {
+                Err(CorrelationError::InvalidIdentity(format!(
+                    "a different message with ID {} is already in the chain",
+                    message.message_id
+                )))
+            };
+        }
+
         // For non-root messages, verify parent exists
         if !message.is_root() {
             let parent_id = &message.causation_id.0;
@@ -91,14 +329,190 @@ impl CorrelationChain {
                 .entry(parent_id.clone())
                 .or_default()
                 .push(message.message_id.clone());
+
+            self.eviction.set_leaf(parent_id, false);
+            self.eviction.insert(message.message_id.clone(), timestamp);
         }
 
         // Add message
-        self.messages.insert(message.message_id.clone(), message);
+        let message_id = message.message_id.clone();
+        self.messages.insert(message_id.clone(), message);
+        telemetry::record_chain_depth(|| {
+            self.get_path_to(&message_id)
+                .map_or(0, |path| path.len().saturating_sub(1))
+        });
+        self.evict_over_limit(&message_id);
 
         Ok(())
     }
 
+    /// Mark a message as completed, making it eligible for
+    /// [`prune_completed_subtrees`](Self::prune_completed_subtrees)
+    pub fn mark_completed(&mut self, message_id: &IdType) {
+        if self.messages.contains_key(message_id) {
+            self.completed.insert(message_id.clone());
+        }
+    }
+
+    /// Remove subtrees whose messages were all observed more than `max_age`
+    /// ago, returning the number of messages removed
+    ///
+    /// See [`prune_before`](Self::prune_before).
+    pub fn prune_older_than(&mut self, max_age: Duration) -> usize {
+        let cutoff = SystemTime::now()
+            .checked_sub(max_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        self.prune_before(cutoff)
+    }
+
+    /// Remove subtrees whose messages were all observed before `cutoff`,
+    /// returning the number of messages removed
+    ///
+    /// Untimed messages are never considered old, and the root is always
+    /// kept so the chain stays connected.
+    pub fn prune_before(&mut self, cutoff: SystemTime) -> usize {
+        self.prune_subtrees(|chain, id| chain.timestamps.get(id).is_some_and(|at| *at < cutoff))
+    }
+
+    /// Remove subtrees in which every message is completed, returning the
+    /// number of messages removed
+    ///
+    /// The root is always kept.
+    pub fn prune_completed_subtrees(&mut self) -> usize {
+        self.prune_subtrees(|chain, id| chain.completed.contains(id))
+    }
+
+    /// A reduced chain of the messages whose subjects match `pattern`
+    ///
+    /// Causation is contracted across removed messages: each kept message's
+    /// causation ID is rewritten to its nearest kept ancestor. The root is
+    /// always kept so the projection stays connected. Timestamps, subjects
+    /// and completion marks carry over; the message bound does not.
+    #[must_use]
+    pub fn project(&self, pattern: &Pattern) -> Self {
+        let mut projection = Self {
+            root: self.root.clone(),
+            messages: HashMap::from([(self.root.message_id.clone(), self.root.clone())]),
+            causation_graph: HashMap::new(),
+            caused_messages: HashMap::new(),
+            timestamps: HashMap::new(),
+            subjects: HashMap::new(),
+            completed: HashSet::new(),
+            max_messages: None,
+            eviction: EvictionIndex::default(),
+        };
+
+        // Breadth-first, so every kept ancestor is in place before its
+        // descendants
+        for message in self.iter_bfs().skip(1) {
+            let id = &message.message_id;
+            if !self
+                .subjects
+                .get(id)
+                .is_some_and(|subject| pattern.matches(subject))
+            {
+                continue;
+            }
+            let mut cause = &message.causation_id.0;
+            while !projection.messages.contains_key(cause) {
+                match self.causation_graph.get(cause) {
+                    Some(parent) => cause = parent,
+                    None => break,
+                }
+            }
+            let mut kept = message.clone();
+            kept.causation_id = CausationId(cause.clone());
+            projection.causation_graph.insert(id.clone(), cause.clone());
+            projection
+                .caused_messages
+                .entry(cause.clone())
+                .or_default()
+                .push(id.clone());
+            projection.eviction.set_leaf(cause, false);
+            projection
+                .eviction
+                .insert(id.clone(), self.timestamps.get(id).copied());
+            projection.messages.insert(id.clone(), kept);
+        }
+
+        for id in projection.messages.keys() {
+            if let Some(timestamp) = self.timestamps.get(id) {
+                projection.timestamps.insert(id.clone(), *timestamp);
+            }
+            if let Some(subject) = self.subjects.get(id) {
+                projection.subjects.insert(id.clone(), subject.clone());
+            }
+            if self.completed.contains(id) {
+                projection.completed.insert(id.clone());
+            }
+        }
+        projection
+    }
+
+    /// Remove every maximal non-root subtree whose messages all satisfy
+    /// `prunable`
+    fn prune_subtrees(&mut self, prunable: impl Fn(&Self, &IdType) -> bool) -> usize {
+        // Post-order: a message is prunable if it and all its children are
+        let order: Vec<IdType> = self.iter_dfs().map(|m| m.message_id.clone()).collect();
+        let mut whole = HashSet::new();
+        for id in order.iter().rev() {
+            if prunable(self, id) && self.children_ids(id).all(|child| whole.contains(child)) {
+                whole.insert(id.clone());
+            }
+        }
+
+        let tops: Vec<IdType> = order
+            .into_iter()
+            .filter(|id| {
+                whole.contains(id)
+                    && self
+                        .causation_graph
+                        .get(id)
+                        .is_some_and(|parent| !whole.contains(parent))
+            })
+            .collect();
+        tops.iter().map(|id| self.remove_subtree(id)).sum()
+    }
+
+    fn evict_over_limit(&mut self, keep: &IdType) {
+        let Some(max) = self.max_messages else {
+            return;
+        };
+        while self.messages.len() > max {
+            let Some(oldest) = self.eviction.oldest(keep).cloned() else {
+                return;
+            };
+            self.remove_subtree(&oldest);
+        }
+    }
+
+    /// Remove a message and everything it caused, returning the count
+    fn remove_subtree(&mut self, message_id: &IdType) -> usize {
+        let ids: Vec<IdType> = self
+            .roots_of_subtree(message_id)
+            .map(|m| m.message_id.clone())
+            .collect();
+        if let Some(parent) = self.causation_graph.get(message_id) {
+            if let Some(siblings) = self.caused_messages.get_mut(parent) {
+                siblings.retain(|id| id != message_id);
+                if siblings.is_empty() {
+                    self.caused_messages.remove(parent);
+                    self.eviction.set_leaf(parent, true);
+                }
+            }
+        }
+        for id in &ids {
+            self.messages.remove(id);
+            self.causation_graph.remove(id);
+            self.caused_messages.remove(id);
+            self.timestamps.remove(id);
+            self.subjects.remove(id);
+            self.completed.remove(id);
+            self.eviction.remove(id);
+        }
+        ids.len()
+    }
+
     /// Get all messages caused by a specific message
     #[must_use]
     pub fn get_caused_by(&self, message_id: &IdType) -> Vec<&MessageIdentity> {
@@ -157,6 +571,233 @@ impl CorrelationChain {
         Ok(path)
     }
 
+    /// Iterate over messages breadth-first from the root
+    ///
+    /// Children are visited in the order they were added. Each message is
+    /// visited once, even if the causation graph has a cycle.
+    pub fn iter_bfs(&self) -> impl Iterator<Item = &MessageIdentity> {
+        let mut queue = VecDeque::from([&self.root.message_id]);
+        let mut visited = HashSet::from([&self.root.message_id]);
+        std::iter::from_fn(move || {
+            let id = queue.pop_front()?;
+            queue.extend(self.children_ids(id).filter(|child| visited.insert(*child)));
+            self.messages.get(id)
+        })
+    }
+
+    /// Iterate over messages depth-first (pre-order) from the root
+    pub fn iter_dfs(&self) -> impl Iterator<Item = &MessageIdentity> {
+        self.roots_of_subtree(&self.root.message_id)
+    }
+
+    /// Iterate depth-first over the subtree rooted at a message, starting
+    /// with the message itself
+    ///
+    /// Yields nothing if the message is not in the chain. Each message is
+    /// visited once, even if the causation graph has a cycle.
+    pub fn roots_of_subtree<'a>(
+        &'a self,
+        message_id: &'a IdType,
+    ) -> impl Iterator<Item = &'a MessageIdentity> {
+        let mut stack = vec![message_id];
+        let mut visited = HashSet::new();
+        std::iter::from_fn(move || loop {
+            let id = stack.pop()?;
+            if visited.insert(id) {
+                stack.extend(self.children_ids(id).rev());
+                return self.messages.get(id);
+            }
+        })
+    }
+
+    /// Messages ordered so that every message comes after its cause
+    ///
+    /// Unlike [`iter_bfs`](Self::iter_bfs), this also covers messages that
+    /// are not reachable from the root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CorrelationError::CyclicCausation`] if the causation graph
+    /// contains a cycle
+    pub fn topo_order(&self) -> Result<Vec<&MessageIdentity>> {
+        let starts =
+            std::iter::once(&self.root.message_id).chain(self.messages.keys().filter(|id| {
+                **id != self.root.message_id && !self.causation_graph.contains_key(*id)
+            }));
+        let mut queue: VecDeque<&IdType> = starts.collect();
+        let mut visited: HashSet<&IdType> = queue.iter().copied().collect();
+        let mut order = Vec::with_capacity(self.messages.len());
+
+        while let Some(id) = queue.pop_front() {
+            if let Some(message) = self.messages.get(id) {
+                order.push(message);
+            }
+            queue.extend(self.children_ids(id).filter(|child| {
+                self.causation_graph.get(*child) == Some(id) && visited.insert(*child)
+            }));
+        }
+
+        if order.len() == self.messages.len() {
+            Ok(order)
+        } else {
+            Err(CorrelationError::CyclicCausation)
+        }
+    }
+
+    /// Messages that caused no further messages, in breadth-first order
+    #[must_use]
+    pub fn leaves(&self) -> Vec<&MessageIdentity> {
+        self.iter_bfs()
+            .filter(|message| self.children_ids(&message.message_id).next().is_none())
+            .collect()
+    }
+
+    /// A serializable node and edge list of the chain, in breadth-first order
+    #[must_use]
+    pub fn graph(&self) -> ChainGraph {
+        let mut depths: HashMap<&IdType, usize> = HashMap::new();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        for message in self.iter_bfs() {
+            let parent = self.causation_graph.get(&message.message_id);
+            let depth = parent.and_then(|p| depths.get(p)).map_or(0, |d| d + 1);
+            depths.insert(&message.message_id, depth);
+
+            nodes.push(ChainGraphNode {
+                id: message.message_id.to_string(),
+                depth,
+                root: message.is_root(),
+            });
+            if let Some(parent) = parent {
+                edges.push(ChainGraphEdge {
+                    cause: parent.to_string(),
+                    effect: message.message_id.to_string(),
+                });
+            }
+        }
+
+        ChainGraph {
+            correlation_id: self.root.correlation_id.0.to_string(),
+            nodes,
+            edges,
+        }
+    }
+
+    /// Shape statistics for spotting runaway cascades
+    #[must_use]
+    pub fn stats(&self) -> ChainStats {
+        let mut depths: HashMap<&IdType, usize> = HashMap::new();
+        let mut width_per_level: Vec<usize> = Vec::new();
+        let mut max_fan_out: Option<(IdType, usize)> = None;
+        let mut deepest = &self.root.message_id;
+        let (mut edges, mut internal) = (0_usize, 0_usize);
+
+        for message in self.iter_bfs() {
+            let id = &message.message_id;
+            let depth = self
+                .causation_graph
+                .get(id)
+                .and_then(|parent| depths.get(parent))
+                .map_or(0, |d| d + 1);
+            depths.insert(id, depth);
+
+            if width_per_level.len() <= depth {
+                width_per_level.push(0);
+                deepest = id;
+            }
+            width_per_level[depth] += 1;
+
+            let fan_out = self.children_ids(id).count();
+            if fan_out > 0 {
+                edges += fan_out;
+                internal += 1;
+            }
+            if max_fan_out.as_ref().map_or(true, |(_, max)| fan_out > *max) {
+                max_fan_out = Some((id.clone(), fan_out));
+            }
+        }
+
+        let critical_path = self
+            .get_path_to(deepest)
+            .map(|path| path.into_iter().map(|m| m.message_id.clone()).collect())
+            .unwrap_or_default();
+
+        #[allow(clippy::cast_precision_loss)]
+        let average_branching_factor = if internal == 0 {
+            0.0
+        } else {
+            edges as f64 / internal as f64
+        };
+
+        ChainStats {
+            depth: width_per_level.len().saturating_sub(1),
+            width_per_level,
+            max_fan_out,
+            average_branching_factor,
+            critical_path,
+        }
+    }
+
+    /// Render the chain as a Graphviz DOT digraph, edges pointing from cause
+    /// to effect
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let graph = self.graph();
+        let mut out = format!("digraph \"{}\" {{\n", graph.correlation_id);
+        for node in &graph.nodes {
+            let shape = if node.root { "doublecircle" } else { "box" };
+            let _ = writeln!(out, "  \"{}\" [shape={shape}];", node.id);
+        }
+        for edge in &graph.edges {
+            let _ = writeln!(out, "  \"{}\" -> \"{}\";", edge.cause, edge.effect);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the chain as a Mermaid flowchart, edges pointing from cause to
+    /// effect
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let graph = self.graph();
+        let index: HashMap<&str, usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+
+        let mut out = String::from("graph TD\n");
+        for (i, node) in graph.nodes.iter().enumerate() {
+            if node.root {
+                let _ = writeln!(out, "    n{i}((\"{}\"))", node.id);
+            } else {
+                let _ = writeln!(out, "    n{i}[\"{}\"]", node.id);
+            }
+        }
+        for edge in &graph.edges {
+            let _ = writeln!(
+                out,
+                "    n{} --> n{}",
+                index[edge.cause.as_str()],
+                index[edge.effect.as_str()]
+            );
+        }
+        out
+    }
+
+    fn children_ids<'a>(
+        &'a self,
+        message_id: &IdType,
+    ) -> impl DoubleEndedIterator<Item = &'a IdType> {
+        self.caused_messages
+            .get(message_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+    }
+
     /// Check if the chain contains cycles
     #[must_use]
     pub fn has_cycles(&self) -> bool {
@@ -207,6 +848,7 @@ impl CorrelationChain {
     pub fn depth(&self) -> usize {
         let mut max_depth = 0;
         let mut queue = VecDeque::new();
+        let mut visited = HashSet::from([&self.root.message_id]);
         queue.push_back((&self.root.message_id, 0));
 
         while let Some((node_id, depth)) = queue.pop_front() {
@@ -214,7 +856,9 @@ impl CorrelationChain {
 
             if let Some(children) = self.caused_messages.get(node_id) {
                 for child in children {
-                    queue.push_back((child, depth + 1));
+                    if visited.insert(child) {
+                        queue.push_back((child, depth + 1));
+                    }
                 }
             }
         }
@@ -223,6 +867,93 @@ impl CorrelationChain {
     }
 }
 
+/// Shape statistics of a [`CorrelationChain`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainStats {
+    /// Longest distance from the root
+    pub depth: usize,
+    /// Number of messages at each distance from the root
+    pub width_per_level: Vec<usize>,
+    /// The message that caused the most messages, with that count
+    pub max_fan_out: Option<(IdType, usize)>,
+    /// Mean number of caused messages over messages that caused any
+    pub average_branching_factor: f64,
+    /// Message IDs from the root to the first deepest message
+    pub critical_path: Vec<IdType>,
+}
+
+/// Graph export of a [`CorrelationChain`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainGraph {
+    /// Correlation ID shared by all messages
+    pub correlation_id: String,
+    /// Messages in breadth-first order
+    pub nodes: Vec<ChainGraphNode>,
+    /// Causation edges
+    pub edges: Vec<ChainGraphEdge>,
+}
+
+/// A message in a [`ChainGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainGraphNode {
+    /// Message ID
+    pub id: String,
+    /// Distance from the root
+    pub depth: usize,
+    /// Whether this is the root message
+    pub root: bool,
+}
+
+/// A causation edge in a [`ChainGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainGraphEdge {
+    /// ID of the causing message
+    pub cause: String,
+    /// ID of the caused message
+    pub effect: String,
+}
+
+/// A message whose subject differs between two chains
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubjectChange {
+    /// Message ID
+    pub message_id: IdType,
+    /// Subject in the first chain
+    pub before: Option<Subject>,
+    /// Subject in the second chain
+    pub after: Option<Subject>,
+}
+
+/// Differences between two chains, matched by message ID
+///
+/// Messages and edges are listed in breadth-first order of the chain they
+/// belong to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainDiff {
+    /// Messages only in the first chain
+    pub only_in_first: Vec<IdType>,
+    /// Messages only in the second chain
+    pub only_in_second: Vec<IdType>,
+    /// Causation edges, as `(cause, effect)`, only in the first chain
+    pub edges_only_in_first: Vec<(IdType, IdType)>,
+    /// Causation edges, as `(cause, effect)`, only in the second chain
+    pub edges_only_in_second: Vec<(IdType, IdType)>,
+    /// Messages in both chains whose subjects differ
+    pub subject_changes: Vec<SubjectChange>,
+}
+
+impl ChainDiff {
+    /// Check if the chains hold the same messages, edges and subjects
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.edges_only_in_first.is_empty()
+            && self.edges_only_in_second.is_empty()
+            && self.subject_changes.is_empty()
+    }
+}
+
 /// Algebra operations on correlation chains
 pub struct MessageAlgebra;
 
@@ -252,6 +983,15 @@ impl MessageAlgebra {
                 merged.add_message(message.clone())?;
             }
         }
+        for (id, timestamp) in &chain2.timestamps {
+            merged.timestamps.entry(id.clone()).or_insert(*timestamp);
+        }
+        for (id, subject) in &chain2.subjects {
+            merged
+                .subjects
+                .entry(id.clone())
+                .or_insert_with(|| subject.clone());
+        }
 
         Ok(merged)
     }
@@ -305,6 +1045,186 @@ impl MessageAlgebra {
 
         Ok(dist1 + dist2)
     }
+
+    /// Describe the messages, causation edges and subjects that differ
+    /// between two chains, e.g. a replayed chain and its original
+    ///
+    /// Messages are matched by ID, so the chains may have different
+    /// correlation IDs.
+    #[must_use]
+    pub fn diff(first: &CorrelationChain, second: &CorrelationChain) -> ChainDiff {
+        let only = |chain: &CorrelationChain, other: &CorrelationChain| -> Vec<IdType> {
+            chain
+                .iter_bfs()
+                .map(|m| &m.message_id)
+                .filter(|id| !other.messages.contains_key(*id))
+                .cloned()
+                .collect()
+        };
+        let edges_only = |chain: &CorrelationChain, other: &CorrelationChain| {
+            chain
+                .iter_bfs()
+                .filter_map(|m| {
+                    let cause = chain.causation_graph.get(&m.message_id)?;
+                    (other.causation_graph.get(&m.message_id) != Some(cause))
+                        .then(|| (cause.clone(), m.message_id.clone()))
+                })
+                .collect()
+        };
+        let subject_changes = first
+            .iter_bfs()
+            .map(|m| &m.message_id)
+            .filter(|id| second.messages.contains_key(*id))
+            .filter_map(|id| {
+                let before = first.subjects.get(id);
+                let after = second.subjects.get(id);
+                (before != after).then(|| SubjectChange {
+                    message_id: id.clone(),
+                    before: before.cloned(),
+                    after: after.cloned(),
+                })
+            })
+            .collect();
+
+        ChainDiff {
+            only_in_first: only(first, second),
+            only_in_second: only(second, first),
+            edges_only_in_first: edges_only(first, second),
+            edges_only_in_second: edges_only(second, first),
+            subject_changes,
+        }
+    }
+
+    /// The part of `first` not covered by `second`
+    ///
+    /// Every subtree of `first` whose messages all appear in `second` is
+    /// removed. What remains is the messages missing from `second` plus
+    /// the ancestors connecting them to the root, which is always kept.
+    #[must_use]
+    pub fn subtract(first: &CorrelationChain, second: &CorrelationChain) -> CorrelationChain {
+        let mut residual = first.clone();
+        residual.prune_subtrees(|chain, id| {
+            *id != chain.root.message_id && second.messages.contains_key(id)
+        });
+        residual
+    }
+}
+
+/// A forward step recorded in a saga
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SagaStep {
+    /// Subject the step was published on
+    pub subject: Subject,
+    /// Identity of the step's message in the correlation chain
+    pub message_id: IdType,
+}
+
+/// A saga: forward steps tracked on a correlation chain, plus the subjects
+/// that undo them
+#[derive(Debug, Clone)]
+pub struct Saga {
+    chain: CorrelationChain,
+    steps: Vec<SagaStep>,
+    compensations: HashMap<Subject, Subject>,
+}
+
+impl Saga {
+    /// Start a saga from a root message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provided message is not a root message
+    pub fn new(root: MessageIdentity) -> Result<Self> {
+        Ok(Self {
+            chain: CorrelationChain::new(root)?,
+            steps: Vec::new(),
+            compensations: HashMap::new(),
+        })
+    }
+
+    /// Register the subject that compensates a forward subject
+    pub fn register_compensation(&mut self, forward: Subject, compensation: Subject) {
+        self.compensations.insert(forward, compensation);
+    }
+
+    /// Register every compensation declared in a workflow
+    pub fn register_workflow(&mut self, workflow: &Workflow) {
+        for step in workflow.steps() {
+            if let Some(compensation) = &step.compensation {
+                self.register_compensation(step.subject.clone(), compensation.clone());
+            }
+        }
+    }
+
+    /// Record a completed forward step
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message cannot be added to the saga's chain
+    pub fn record_step(&mut self, subject: Subject, message: MessageIdentity) -> Result<()> {
+        let message_id = message.message_id.clone();
+        self.chain.add_message(message)?;
+        self.steps.push(SagaStep {
+            subject,
+            message_id,
+        });
+        Ok(())
+    }
+
+    /// The correlation chain of the saga's messages
+    #[must_use]
+    pub fn chain(&self) -> &CorrelationChain {
+        &self.chain
+    }
+
+    /// Recorded forward steps in order
+    #[must_use]
+    pub fn steps(&self) -> &[SagaStep] {
+        &self.steps
+    }
+
+    /// The compensation registered for a forward subject
+    #[must_use]
+    pub fn compensation_for(&self, forward: &Subject) -> Option<&Subject> {
+        self.compensations.get(forward)
+    }
+
+    /// Compensations to emit when step `failed_step` fails
+    ///
+    /// Steps before `failed_step` completed and are undone in reverse order;
+    /// the failed step itself never completed. Steps without a registered
+    /// compensation are skipped.
+    #[must_use]
+    pub fn compensations_after_failure(&self, failed_step: usize) -> Vec<&Subject> {
+        self.steps[..failed_step.min(self.steps.len())]
+            .iter()
+            .rev()
+            .filter_map(|step| self.compensation_for(&step.subject))
+            .collect()
+    }
+
+    /// Check that every recorded forward subject has a compensation
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CorrelationError::MissingCompensation`] listing each
+    /// uncompensated subject once
+    pub fn validate(&self) -> Result<()> {
+        let mut seen = HashSet::new();
+        let missing: Vec<String> = self
+            .steps
+            .iter()
+            .filter(|step| !self.compensations.contains_key(&step.subject))
+            .filter(|step| seen.insert(&step.subject))
+            .map(|step| step.subject.to_string())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(CorrelationError::MissingCompensation(missing))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -388,6 +1308,360 @@ mod tests {
         assert_eq!(chain.depth(), 2);
     }
 
+    #[test]
+    fn test_chain_traversal() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let mut chain = CorrelationChain::new(root.clone()).unwrap();
+
+        //     root
+        //    /    \
+        //   a      b
+        //   |
+        //   c
+        let a = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let b = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let c = MessageFactory::command_from_command(Uuid::new_v4(), &a);
+        for message in [&a, &b, &c] {
+            chain.add_message(message.clone()).unwrap();
+        }
+
+        let ids = |messages: Vec<&MessageIdentity>| -> Vec<IdType> {
+            messages.into_iter().map(|m| m.message_id.clone()).collect()
+        };
+        let [root, a, b, c] = [root, a, b, c].map(|m| m.message_id);
+
+        assert_eq!(ids(chain.iter_bfs().collect()), vec![
+            root.clone(),
+            a.clone(),
+            b.clone(),
+            c.clone()
+        ]);
+        assert_eq!(ids(chain.iter_dfs().collect()), vec![
+            root.clone(),
+            a.clone(),
+            c.clone(),
+            b.clone()
+        ]);
+        assert_eq!(
+            ids(chain.topo_order().unwrap()),
+            ids(chain.iter_bfs().collect())
+        );
+        assert_eq!(ids(chain.leaves()), vec![b.clone(), c.clone()]);
+        assert_eq!(ids(chain.roots_of_subtree(&a).collect()), vec![a, c]);
+    }
+
+    #[test]
+    fn test_chain_stats() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let mut chain = CorrelationChain::new(root.clone()).unwrap();
+
+        // root -> validate, reserve, notify; reserve -> allocate -> pick
+        let validate = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let reserve = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let notify = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let allocate = MessageFactory::command_from_command(Uuid::new_v4(), &reserve);
+        let pick = MessageFactory::command_from_command(Uuid::new_v4(), &allocate);
+        for message in [&validate, &reserve, &notify, &allocate, &pick] {
+            chain.add_message(message.clone()).unwrap();
+        }
+
+        let stats = chain.stats();
+        assert_eq!(stats.depth, chain.depth());
+        assert_eq!(stats.width_per_level, vec![1, 3, 1, 1]);
+        assert_eq!(stats.max_fan_out, Some((root.message_id.clone(), 3)));
+        assert!((stats.average_branching_factor - 5.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(stats.critical_path, vec![
+            root.message_id,
+            reserve.message_id,
+            allocate.message_id,
+            pick.message_id
+        ]);
+    }
+
+    #[test]
+    fn test_chain_latency() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let at = |millis| start + Duration::from_millis(millis);
+
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let fast = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let slow = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let done = MessageFactory::command_from_command(Uuid::new_v4(), &slow);
+
+        let mut chain = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0))).unwrap();
+        chain
+            .add_entry(ChainEntry::new(fast.clone(), at(20)))
+            .unwrap();
+        chain
+            .add_entry(
+                ChainEntry::new(slow.clone(), at(1_500))
+                    .with_subject(Subject::new("payments.payment.captured.v1").unwrap()),
+            )
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(done.clone(), at(2_000)))
+            .unwrap();
+
+        assert_eq!(
+            chain.hop_latency(&slow.message_id),
+            Some(Duration::from_millis(1_500))
+        );
+        assert_eq!(chain.hop_latency(&root.message_id), None);
+        assert_eq!(chain.hop_latencies().len(), 3);
+        assert_eq!(chain.end_to_end_latency(), Some(Duration::from_secs(2)));
+
+        let path: Vec<&IdType> = chain.slowest_path().iter().map(|m| &m.message_id).collect();
+        assert_eq!(path, vec![
+            &root.message_id,
+            &slow.message_id,
+            &done.message_id
+        ]);
+        assert_eq!(
+            chain
+                .entry(&slow.message_id)
+                .unwrap()
+                .subject
+                .unwrap()
+                .as_str(),
+            "payments.payment.captured.v1"
+        );
+    }
+
+    #[test]
+    fn test_chain_pruning() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // root -> old -> old_child; root -> recent
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let old = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let old_child = MessageFactory::command_from_command(Uuid::new_v4(), &old);
+        let recent = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+
+        let mut chain = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0))).unwrap();
+        chain
+            .add_entry(ChainEntry::new(old.clone(), at(1)))
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(old_child.clone(), at(2)))
+            .unwrap();
+        chain
+            .add_entry(ChainEntry::new(recent.clone(), at(60)))
+            .unwrap();
+
+        let mut by_age = chain.clone();
+        assert_eq!(by_age.prune_before(at(30)), 2);
+        assert_eq!(by_age.messages.len(), 2);
+        assert_eq!(by_age.get_caused_by(&root.message_id).len(), 1);
+        assert!(!by_age.has_cycles());
+
+        // A subtree is only pruned once all of it is completed
+        chain.mark_completed(&old.message_id);
+        assert_eq!(chain.prune_completed_subtrees(), 0);
+        chain.mark_completed(&old_child.message_id);
+        assert_eq!(chain.prune_completed_subtrees(), 2);
+        assert!(!chain.timestamps.contains_key(&old_child.message_id));
+
+        // Max-size eviction drops the oldest leaf, never the new message
+        let mut bounded = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0)))
+            .unwrap()
+            .with_max_messages(2);
+        bounded
+            .add_entry(ChainEntry::new(old.clone(), at(1)))
+            .unwrap();
+        bounded
+            .add_entry(ChainEntry::new(recent.clone(), at(60)))
+            .unwrap();
+        assert_eq!(bounded.messages.len(), 2);
+        assert!(bounded.messages.contains_key(&recent.message_id));
+        assert!(!bounded.messages.contains_key(&old.message_id));
+
+        // A message becomes evictable again once its last child is evicted
+        let mut bounded = CorrelationChain::from_entry(ChainEntry::new(root.clone(), at(0)))
+            .unwrap()
+            .with_max_messages(3);
+        for (message, secs) in [(&old, 1), (&old_child, 2), (&recent, 60)] {
+            bounded
+                .add_entry(ChainEntry::new(message.clone(), at(secs)))
+                .unwrap();
+        }
+        assert!(!bounded.messages.contains_key(&old_child.message_id));
+        assert!(bounded.messages.contains_key(&old.message_id));
+        let newest = MessageFactory::command_from_command(Uuid::new_v4(), &recent);
+        bounded.add_entry(ChainEntry::new(newest, at(61))).unwrap();
+        assert!(!bounded.messages.contains_key(&old.message_id));
+        assert_eq!(bounded.messages.len(), 3);
+    }
+
+    #[test]
+    fn test_chain_export() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let child = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let mut chain = CorrelationChain::new(root.clone()).unwrap();
+        chain.add_message(child.clone()).unwrap();
+        let (root_id, child_id) = (root.message_id.to_string(), child.message_id.to_string());
+
+        let graph = chain.graph();
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.nodes[0].root);
+        assert_eq!(graph.nodes[1].depth, 1);
+        assert_eq!(graph.edges, vec![ChainGraphEdge {
+            cause: root_id.clone(),
+            effect: child_id.clone(),
+        }]);
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(serde_json::from_str::<ChainGraph>(&json).unwrap(), graph);
+
+        assert!(chain
+            .to_dot()
+            .contains(&format!("  \"{root_id}\" -> \"{child_id}\";\n")));
+        assert_eq!(
+            chain.to_mermaid(),
+            format!("graph TD\n    n0((\"{root_id}\"))\n    n1[\"{child_id}\"]\n    n0 --> n1\n")
+        );
+    }
+
+    #[test]
+    fn test_saga_compensations() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let mut saga = Saga::new(root.clone()).unwrap();
+        let subject = |s: &str| Subject::new(s).unwrap();
+
+        let mut parent = root;
+        for step in [
+            "orders.commands.order.validate",
+            "inventory.commands.stock.reserve",
+            "payments.commands.payment.process",
+        ] {
+            let message = MessageFactory::command_from_command(Uuid::new_v4(), &parent);
+            saga.record_step(subject(step), message.clone()).unwrap();
+            parent = message;
+        }
+        assert_eq!(saga.chain().messages.len(), 4);
+
+        saga.register_compensation(
+            subject("inventory.commands.stock.reserve"),
+            subject("inventory.commands.stock.release"),
+        );
+        saga.register_compensation(
+            subject("payments.commands.payment.process"),
+            subject("payments.commands.payment.refund"),
+        );
+
+        // Payment failed: only the reservation needs undoing
+        assert_eq!(saga.compensations_after_failure(2), vec![&subject(
+            "inventory.commands.stock.release"
+        )]);
+        assert_eq!(saga.compensations_after_failure(10).len(), 2);
+        assert!(saga.compensations_after_failure(0).is_empty());
+
+        let Err(CorrelationError::MissingCompensation(missing)) = saga.validate() else {
+            panic!("validation should fail");
+        };
+        assert_eq!(missing, vec!["orders.commands.order.validate"]);
+
+        saga.register_compensation(
+            subject("orders.commands.order.validate"),
+            subject("orders.commands.order.cancel"),
+        );
+        assert!(saga.validate().is_ok());
+    }
+
+    #[test]
+    fn test_diff_and_subtract() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let shared = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let original_only = MessageFactory::command_from_command(Uuid::new_v4(), &shared);
+        let replay_only = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+
+        let mut original = CorrelationChain::new(root.clone()).unwrap();
+        original.add_message(shared.clone()).unwrap();
+        original.add_message(original_only.clone()).unwrap();
+        original.subjects.insert(
+            shared.message_id.clone(),
+            Subject::new("orders.order.placed.v1").unwrap(),
+        );
+
+        let mut replayed = CorrelationChain::new(root.clone()).unwrap();
+        replayed.add_message(shared.clone()).unwrap();
+        replayed.add_message(replay_only.clone()).unwrap();
+        replayed.subjects.insert(
+            shared.message_id.clone(),
+            Subject::new("orders.order.placed.v2").unwrap(),
+        );
+
+        let diff = MessageAlgebra::diff(&original, &replayed);
+        assert_eq!(diff.only_in_first, vec![original_only.message_id.clone()]);
+        assert_eq!(diff.only_in_second, vec![replay_only.message_id.clone()]);
+        assert_eq!(diff.edges_only_in_first, [(
+            shared.message_id.clone(),
+            original_only.message_id.clone()
+        )]);
+        assert_eq!(diff.subject_changes.len(), 1);
+        assert_eq!(
+            diff.subject_changes[0].after.as_ref().map(Subject::as_str),
+            Some("orders.order.placed.v2")
+        );
+        assert!(MessageAlgebra::diff(&original, &original).is_empty());
+
+        // The residual keeps the missing message and the path to it
+        let residual = MessageAlgebra::subtract(&original, &replayed);
+        assert_eq!(residual.messages.len(), 3);
+        let residual = MessageAlgebra::subtract(&replayed, &original);
+        assert_eq!(residual.messages.len(), 2);
+        assert!(residual.messages.contains_key(&replay_only.message_id));
+        assert_eq!(
+            MessageAlgebra::subtract(&original, &original)
+                .messages
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_project_contracts_causation() {
+        let subject = |s: &str| Subject::new(s).unwrap();
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let placed = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let reserve = MessageFactory::command_from_command(Uuid::new_v4(), &placed);
+        let confirmed = MessageFactory::command_from_command(Uuid::new_v4(), &reserve);
+
+        let mut chain = CorrelationChain::new(root.clone()).unwrap();
+        for (message, on) in [
+            (&placed, "orders.events.order.placed"),
+            (&reserve, "inventory.commands.stock.reserve"),
+            (&confirmed, "orders.events.order.confirmed"),
+        ] {
+            chain
+                .add_entry(
+                    ChainEntry::new(message.clone(), SystemTime::now()).with_subject(subject(on)),
+                )
+                .unwrap();
+        }
+        chain.mark_completed(&confirmed.message_id);
+
+        let orders = chain.project(&Pattern::new("orders.>").unwrap());
+        assert_eq!(orders.messages.len(), 3);
+        assert!(!orders.messages.contains_key(&reserve.message_id));
+        let path: Vec<&IdType> = orders
+            .get_path_to(&confirmed.message_id)
+            .unwrap()
+            .into_iter()
+            .map(|m| &m.message_id)
+            .collect();
+        assert_eq!(path, [
+            &root.message_id,
+            &placed.message_id,
+            &confirmed.message_id
+        ]);
+        assert!(orders.completed.contains(&confirmed.message_id));
+        assert_eq!(orders.subjects.len(), 2);
+
+        // A pattern matching nothing leaves only the root
+        let none = chain.project(&Pattern::new("billing.>").unwrap());
+        assert_eq!(none.messages.len(), 1);
+    }
+
     #[test]
     fn test_cycle_detection() {
         let root_id = Uuid::new_v4();
@@ -401,4 +1675,42 @@ mod tests {
         // Note: Creating actual cycles would require bypassing the
         // MessageFactory which enforces proper causation rules
     }
+
+    #[test]
+    fn test_duplicate_adds_and_cyclic_traversal() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let child = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let grandchild = MessageFactory::command_from_command(Uuid::new_v4(), &child);
+
+        let mut chain = CorrelationChain::new(root.clone()).unwrap();
+        chain.add_message(child.clone()).unwrap();
+        chain.add_message(child.clone()).unwrap();
+        chain.add_message(root.clone()).unwrap();
+        assert_eq!(chain.iter_bfs().count(), 2);
+        assert_eq!(chain.topo_order().unwrap().len(), 2);
+
+        let mut impostor = MessageFactory::command_from_command(Uuid::new_v4(), &child);
+        impostor.message_id = child.message_id.clone();
+        assert!(chain.add_message(impostor).is_err());
+
+        // Tamper with the public graph to close child -> grandchild -> child
+        chain.add_message(grandchild.clone()).unwrap();
+        chain
+            .caused_messages
+            .entry(grandchild.message_id.clone())
+            .or_default()
+            .push(child.message_id.clone());
+        chain
+            .causation_graph
+            .insert(child.message_id.clone(), grandchild.message_id.clone());
+
+        assert!(chain.has_cycles());
+        assert_eq!(chain.iter_bfs().count(), 3);
+        assert_eq!(chain.iter_dfs().count(), 3);
+        assert_eq!(chain.depth(), 2);
+        assert!(matches!(
+            chain.topo_order(),
+            Err(CorrelationError::CyclicCausation)
+        ));
+    }
 }
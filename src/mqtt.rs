@@ -0,0 +1,200 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! MQTT topic and filter compatibility
+//!
+//! MQTT separates levels with `/` and uses `+` for one level and `#` for
+//! the remaining levels, where NATS uses `.`, `*` and `>`. These conversions
+//! let gateways bridging MQTT devices reuse [`Pattern`]-based permissions
+//! and routing: an MQTT filter is converted to a pattern, or an MQTT topic
+//! to a subject, and checked as usual.
+//!
+//! The wildcards differ in one respect: MQTT's `#` also matches the parent
+//! level itself, so `a/b/c/#` matches `a/b/c`, while `>` needs at least one
+//! token. Since subjects always have four tokens this only matters for
+//! filters with four or more levels before `#`, which are rejected.
+//!
+//! ```
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let pattern = Pattern::from_mqtt_filter("sensors/+/reading/#").unwrap();
+//! assert_eq!(pattern.as_str(), "sensors.*.reading.>");
+//! assert_eq!(pattern.to_mqtt_filter(), "sensors/+/reading/#");
+//!
+//! let subject = Subject::from_mqtt_topic("sensors/boiler/reading/v1").unwrap();
+//! assert!(pattern.matches(&subject));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::{
+    Pattern,
+    Token,
+};
+use crate::subject::Subject;
+
+/// Number of tokens in every subject
+const SUBJECT_TOKENS: usize = 4;
+
+impl Pattern {
+    /// The equivalent MQTT topic filter
+    #[must_use]
+    pub fn to_mqtt_filter(&self) -> String {
+        self.tokens()
+            .iter()
+            .map(|token| match token {
+                Token::Literal(literal) => literal.as_str(),
+                Token::SingleWildcard => "+",
+                Token::MultiWildcard => "#",
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Convert an MQTT topic filter to a pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the filter:
+    /// - Is empty or has an empty level
+    /// - Starts with `$`, reserved for broker system topics
+    /// - Uses `+` or `#` as part of a level, or `#` before the last level
+    /// - Has a level with characters subject tokens cannot hold
+    /// - Has `#` after four or more levels, where it could only match the
+    ///   parent level that `>` never matches
+    pub fn from_mqtt_filter(filter: &str) -> Result<Self> {
+        if filter.starts_with('$') {
+            return Err(incompatible(
+                filter,
+                "'$' topics are reserved for the broker",
+            ));
+        }
+
+        let levels: Vec<&str> = filter.split('/').collect();
+        let mut tokens = Vec::with_capacity(levels.len());
+        for (i, level) in levels.iter().enumerate() {
+            let token = match *level {
+                "+" => "*",
+                "#" if i + 1 != levels.len() => {
+                    return Err(incompatible(filter, "'#' must be the last level"));
+                },
+                "#" if i >= SUBJECT_TOKENS => {
+                    return Err(incompatible(
+                        filter,
+                        "'#' after four levels only matches the parent level, which '>' cannot",
+                    ));
+                },
+                "#" => ">",
+                level if level.contains(['+', '#']) => {
+                    return Err(incompatible(
+                        filter,
+                        format!("wildcards must fill a whole level, got '{level}'"),
+                    ));
+                },
+                level => check_level(filter, level)?,
+            };
+            tokens.push(token);
+        }
+        Ok(Pattern::from_valid_tokens(&tokens))
+    }
+}
+
+impl Subject {
+    /// The equivalent MQTT topic
+    #[must_use]
+    pub fn to_mqtt_topic(&self) -> String {
+        self.as_str().replace('.', "/")
+    }
+
+    /// Convert an MQTT topic to a subject
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the topic contains wildcards, does not have four
+    /// levels, or has a level with characters subject tokens cannot hold
+    pub fn from_mqtt_topic(topic: &str) -> Result<Self> {
+        if topic.contains(['+', '#']) {
+            return Err(incompatible(topic, "topics cannot contain wildcards"));
+        }
+        let levels = topic
+            .split('/')
+            .map(|level| check_level(topic, level))
+            .collect::<Result<Vec<_>>>()?;
+        Subject::new(levels.join("."))
+    }
+}
+
+/// Check that a literal level is a valid subject token
+fn check_level<'a>(topic: &str, level: &'a str) -> Result<&'a str> {
+    if level.is_empty() {
+        return Err(incompatible(
+            topic,
+            "empty levels have no subject equivalent",
+        ));
+    }
+    if !level
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(incompatible(
+            topic,
+            format!("level '{level}' contains characters subjects cannot hold"),
+        ));
+    }
+    Ok(level)
+}
+
+fn incompatible(topic: &str, reason: impl core::fmt::Display) -> SubjectError {
+    SubjectError::invalid_pattern(format!("MQTT topic '{topic}' is incompatible: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_round_trip() {
+        for (pattern, filter) in [
+            ("home.*.temperature.v1", "home/+/temperature/v1"),
+            ("home.>", "home/#"),
+            ("*.*.*.*", "+/+/+/+"),
+        ] {
+            let pattern = Pattern::new(pattern).unwrap();
+            assert_eq!(pattern.to_mqtt_filter(), filter);
+            assert_eq!(Pattern::from_mqtt_filter(filter).unwrap(), pattern);
+        }
+
+        let subject = Subject::new("home.kitchen.temperature.v1").unwrap();
+        assert_eq!(subject.to_mqtt_topic(), "home/kitchen/temperature/v1");
+        assert_eq!(
+            Subject::from_mqtt_topic("home/kitchen/temperature/v1").unwrap(),
+            subject
+        );
+    }
+
+    #[test]
+    fn test_incompatible_filters() {
+        for filter in [
+            "",
+            "home//temperature/v1",
+            "/home/+/temperature",
+            "$SYS/broker/load/#",
+            "home/#/v1",
+            "home/kitchen+/temperature/v1",
+            "home/kitchen/temperature/v1/#",
+            "home/kitchen.north/temperature/v1",
+        ] {
+            assert!(Pattern::from_mqtt_filter(filter).is_err(), "{filter}");
+        }
+        assert!(Subject::from_mqtt_topic("home/+/temperature/v1").is_err());
+        assert!(Subject::from_mqtt_topic("home/kitchen/temperature").is_err());
+    }
+}
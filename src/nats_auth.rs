@@ -0,0 +1,501 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Import of NATS server authorization configuration
+//!
+//! Parses the `authorization` block of a NATS server configuration (either
+//! the `.conf` syntax or its JSON equivalent) and account/user JWT permission
+//! claims into [`Permissions`], so services can evaluate the broker policy
+//! locally.
+//!
+//! The NATS server semantics are preserved:
+//!
+//! - Publishing covers both [`Operation::Publish`] and [`Operation::Request`]
+//! - Subscribing covers plain and queue subscriptions; `"subject queue"`
+//!   entries only grant queue subscriptions in that queue group
+//! - A section with only a deny list allows everything else
+//! - A missing section allows everything
+//! - A matching deny always wins ([`ConflictResolution::DenyOverrides`])
+
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use serde_json::{
+    Map,
+    Value,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    ConflictResolution,
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+};
+
+/// Authorization settings imported from a NATS server configuration
+#[derive(Debug, Clone)]
+pub struct NatsAuthorization {
+    /// Permissions applied to users without explicit permissions
+    pub default_permissions: Option<Permissions>,
+    /// Permissions by user name (or nkey)
+    pub users: HashMap<String, Permissions>,
+}
+
+impl NatsAuthorization {
+    /// Parse a NATS server configuration containing an `authorization` block
+    ///
+    /// The input may be a full server configuration, a bare `authorization`
+    /// block body, or the equivalent JSON. Top-level variables (e.g.
+    /// `ADMIN = { publish: ">" }`) referenced as `$ADMIN` are resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The configuration cannot be parsed
+    /// - A permission subject is not a valid pattern
+    pub fn parse(input: &str) -> Result<Self> {
+        let document = parse_config(input)?;
+        let authorization = document.get("authorization").unwrap_or(&document);
+
+        let default_permissions = authorization
+            .get("default_permissions")
+            .map(|value| Permissions::from_nats_permissions(resolve(value, &document)))
+            .transpose()?;
+
+        let mut users = HashMap::new();
+        if let Some(Value::Array(entries)) = authorization.get("users") {
+            for entry in entries {
+                let name = entry
+                    .get("user")
+                    .or_else(|| entry.get("nkey"))
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        SubjectError::parse_error("User entry without 'user' or 'nkey'")
+                    })?;
+
+                let permissions = match entry.get("permissions") {
+                    Some(value) => Permissions::from_nats_permissions(resolve(value, &document))?,
+                    None => match &default_permissions {
+                        Some(defaults) => defaults.clone(),
+                        None => Permissions::from_nats_permissions(&Value::Object(Map::new()))?,
+                    },
+                };
+
+                users.insert(name.to_string(), permissions);
+            }
+        }
+
+        Ok(Self {
+            default_permissions,
+            users,
+        })
+    }
+
+    /// Get the permissions for a user, falling back to the defaults
+    #[must_use]
+    pub fn permissions_for(&self, user: &str) -> Option<&Permissions> {
+        self.users.get(user).or(self.default_permissions.as_ref())
+    }
+}
+
+impl Permissions {
+    /// Build permissions from a NATS `permissions` block
+    ///
+    /// Accepts the server configuration keys (`publish`, `subscribe`) and
+    /// the JWT claim keys (`pub`, `sub`). Each section may be a single
+    /// subject, a list of subjects, or an object with `allow`/`deny` lists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a section is malformed or a subject is not a
+    /// valid pattern
+    pub fn from_nats_permissions(value: &Value) -> Result<Self> {
+        if !value.is_object() {
+            return Err(SubjectError::parse_error(
+                "NATS permissions must be an object",
+            ));
+        }
+
+        let mut permissions = Permissions::new(Policy::Deny);
+        permissions.set_conflict_resolution(ConflictResolution::DenyOverrides);
+
+        let sections = [
+            (
+                value.get("publish").or_else(|| value.get("pub")),
+                [Operation::Publish, Operation::Request].as_slice(),
+            ),
+            (
+                value.get("subscribe").or_else(|| value.get("sub")),
+                [Operation::Subscribe, Operation::QueueSubscribe].as_slice(),
+            ),
+        ];
+
+        for (section, operations) in sections {
+            let (allow, deny) = match section {
+                Some(section) => subject_lists(section)?,
+                None => (Vec::new(), Vec::new()),
+            };
+            let operations: HashSet<Operation> = operations.iter().copied().collect();
+
+            // NATS allows everything when no allow list is given
+            if allow.is_empty() {
+                permissions.add_rule(PermissionRule::allow(
+                    Pattern::new(">")?,
+                    operations.clone(),
+                ));
+            }
+            for subject in allow {
+                permissions.add_rule(nats_rule(subject, &operations, Policy::Allow)?);
+            }
+            for subject in deny {
+                permissions.add_rule(nats_rule(subject, &operations, Policy::Deny)?);
+            }
+        }
+
+        Ok(permissions)
+    }
+
+    /// Parse a NATS `permissions` block in `.conf` or JSON syntax
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block cannot be parsed or contains invalid
+    /// subjects
+    pub fn from_nats_config(input: &str) -> Result<Self> {
+        let document = parse_config(input)?;
+        let block = document.get("permissions").unwrap_or(&document);
+        Self::from_nats_permissions(resolve(block, &document))
+    }
+}
+
+/// Build a rule from a NATS permission entry
+///
+/// Subscribe entries of the form `"orders.> workers"` restrict the rule to
+/// queue subscriptions in the named queue group.
+fn nats_rule(
+    entry: &str,
+    operations: &HashSet<Operation>,
+    policy: Policy,
+) -> Result<PermissionRule> {
+    match entry.split_once(' ') {
+        Some((subject, queue)) if operations.contains(&Operation::QueueSubscribe) => {
+            let queue_only = [Operation::QueueSubscribe].into_iter().collect();
+            Ok(
+                PermissionRule::new(Pattern::new(subject)?, queue_only, policy)
+                    .with_queue_groups([queue.trim()]),
+            )
+        },
+        _ => Ok(PermissionRule::new(
+            Pattern::new(entry)?,
+            operations.clone(),
+            policy,
+        )),
+    }
+}
+
+/// Split a permission section into allow and deny subject lists
+fn subject_lists(section: &Value) -> Result<(Vec<&str>, Vec<&str>)> {
+    match section {
+        Value::Object(map) => {
+            let allow = map
+                .get("allow")
+                .map(subjects)
+                .transpose()?
+                .unwrap_or_default();
+            let deny = map
+                .get("deny")
+                .map(subjects)
+                .transpose()?
+                .unwrap_or_default();
+            Ok((allow, deny))
+        },
+        other => Ok((subjects(other)?, Vec::new())),
+    }
+}
+
+/// Read a single subject or a list of subjects
+fn subjects(value: &Value) -> Result<Vec<&str>> {
+    match value {
+        Value::String(subject) => Ok(vec![subject.as_str()]),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .ok_or_else(|| SubjectError::parse_error("Permission subjects must be strings"))
+            })
+            .collect(),
+        _ => Err(SubjectError::parse_error(
+            "Permission section must be a subject, a list, or an allow/deny object",
+        )),
+    }
+}
+
+/// Resolve a `$VARIABLE` reference against the top-level document
+fn resolve<'a>(value: &'a Value, document: &'a Value) -> &'a Value {
+    value
+        .as_str()
+        .and_then(|s| s.strip_prefix('$'))
+        .and_then(|name| document.get(name))
+        .unwrap_or(value)
+}
+
+/// Parse NATS configuration syntax into a JSON value
+///
+/// JSON input is accepted as-is since it is a subset of the syntax.
+fn parse_config(input: &str) -> Result<Value> {
+    let trimmed = input.trim_start();
+    if trimmed.starts_with('{') {
+        if let Ok(value) = serde_json::from_str(input) {
+            return Ok(value);
+        }
+    }
+
+    let mut parser = ConfigParser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    let map = parser.parse_map_body(None)?;
+    Ok(Value::Object(map))
+}
+
+/// Recursive descent parser for the NATS configuration format
+struct ConfigParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ConfigParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Skip whitespace, separators and comments
+    fn skip_trivia(&mut self, skip_separators: bool) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || (skip_separators && (c == ',' || c == ';')) {
+                self.pos += 1;
+            } else if c == '#' || (c == '/' && self.chars.get(self.pos + 1) == Some(&'/')) {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, msg: &str) -> SubjectError {
+        SubjectError::parse_error(format!("{msg} at offset {} in NATS config", self.pos))
+    }
+
+    fn parse_map_body(&mut self, close: Option<char>) -> Result<Map<String, Value>> {
+        let mut map = Map::new();
+        loop {
+            self.skip_trivia(true);
+            match self.peek() {
+                None if close.is_none() => return Ok(map),
+                None => return Err(self.error("Unterminated block")),
+                Some(c) if Some(c) == close => {
+                    self.pos += 1;
+                    return Ok(map);
+                },
+                Some(_) => {
+                    let key = self.parse_key()?;
+                    if key == "include" {
+                        return Err(self.error("'include' directives are not supported"));
+                    }
+                    self.skip_trivia(false);
+                    if matches!(self.peek(), Some(':' | '=')) {
+                        self.pos += 1;
+                        self.skip_trivia(false);
+                    }
+                    let value = self.parse_value()?;
+                    map.insert(key, value);
+                },
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.parse_quoted();
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| {
+            !c.is_whitespace()
+                && !matches!(
+                    c,
+                    ':' | '=' | '{' | '}' | '[' | ']' | ',' | '#' | '"' | '\''
+                )
+        }) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("Expected key"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                Ok(Value::Object(self.parse_map_body(Some('}'))?))
+            },
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_trivia(true);
+                    match self.peek() {
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(items));
+                        },
+                        None => return Err(self.error("Unterminated array")),
+                        Some(_) => items.push(self.parse_value()?),
+                    }
+                }
+            },
+            Some('"' | '\'') => Ok(Value::String(self.parse_quoted()?)),
+            Some(_) => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| !matches!(c, '\n' | '\r' | ',' | ';' | ']' | '}' | '#'))
+                {
+                    self.pos += 1;
+                }
+                let token: String = self.chars[start..self.pos].iter().collect();
+                let token = token.trim();
+                if token.is_empty() {
+                    return Err(self.error("Expected value"));
+                }
+                Ok(match token {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    _ => token
+                        .parse::<i64>()
+                        .map_or_else(|_| Value::String(token.to_string()), Value::from),
+                })
+            },
+            None => Err(self.error("Expected value")),
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.peek().ok_or_else(|| self.error("Expected string"))?;
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("Unterminated string")),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(value);
+                },
+                Some('\\') => {
+                    self.pos += 1;
+                    if let Some(escaped) = self.peek() {
+                        value.push(escaped);
+                        self.pos += 1;
+                    }
+                },
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subject::Subject;
+
+    #[test]
+    fn test_parse_conf_authorization() {
+        let config = r#"
+            # Shared permission sets
+            ORDERS = {
+                publish: { allow: ["orders.>"], deny: "orders.internal.>" }
+                subscribe = ["inventory.events.>", "orders.> order-workers"]
+            }
+
+            authorization {
+                default_permissions: { publish: "public.>" }
+                users = [
+                    { user: order_service, password: secret, permissions: $ORDERS }
+                    { user: guest, password: guest }
+                ]
+            }
+        "#;
+
+        let auth = NatsAuthorization::parse(config).unwrap();
+        let orders = auth.permissions_for("order_service").unwrap();
+
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let internal = Subject::new("orders.internal.audit.v1").unwrap();
+        let stock = Subject::new("inventory.events.reserved.v1").unwrap();
+
+        assert!(orders.can_publish(&placed));
+        assert!(orders.can_request(&placed));
+        assert!(!orders.can_publish(&internal)); // Deny wins over broader allow
+        assert!(orders.can_subscribe(&stock));
+        assert!(!orders.can_publish(&stock));
+        assert!(orders.can_queue_subscribe(&placed, "order-workers"));
+        assert!(!orders.can_queue_subscribe(&placed, "other-workers"));
+        assert!(!orders.can_subscribe(&placed));
+
+        // Users without permissions inherit the defaults
+        let guest = auth.permissions_for("guest").unwrap();
+        assert!(guest.can_publish(&Subject::new("public.news.posted.v1").unwrap()));
+        assert!(!guest.can_publish(&placed));
+        // No subscribe section allows all
+        assert!(guest.can_subscribe(&placed));
+    }
+
+    #[test]
+    fn test_parse_jwt_permissions() {
+        let claims = serde_json::json!({
+            "pub": { "deny": ["security.>"] },
+            "sub": { "allow": ["events.>"] }
+        });
+
+        let perms = Permissions::from_nats_permissions(&claims).unwrap();
+        let keys = Subject::new("security.keys.rotated.v1").unwrap();
+        let event = Subject::new("events.user.created.v1").unwrap();
+
+        assert!(!perms.can_publish(&keys));
+        assert!(perms.can_publish(&event)); // Deny-only section allows the rest
+        assert!(perms.can_subscribe(&event));
+        assert!(!perms.can_subscribe(&keys));
+    }
+
+    #[test]
+    fn test_system_subjects() {
+        let perms = Permissions::from_nats_config(
+            r#"publish: { allow: ["$JS.API.>", "orders.>"], deny: "$SYS.>" }"#,
+        )
+        .unwrap();
+        let patterns: Vec<&str> = perms.rules().iter().map(|r| r.pattern.as_str()).collect();
+        assert!(patterns.contains(&"$JS.API.>"));
+        assert!(patterns.contains(&"$SYS.>"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Permissions::from_nats_config("publish: { allow: [\"a.>\"").is_err());
+        assert!(Permissions::from_nats_config("publish: 42").is_err());
+        assert!(NatsAuthorization::parse("include ./auth.conf").is_err());
+    }
+}
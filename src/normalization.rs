@@ -0,0 +1,213 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject canonicalization
+//!
+//! Producers rarely agree on spelling: `Orders.Order.Placed.v1`,
+//! `orders/order/placed/v1` and `orders.order.evt_placed.v1` may all mean the
+//! same thing. A [`NormalizationPolicy`] describes how to fold such variants
+//! into one canonical subject before routing or permission checks.
+
+use std::collections::HashMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::error::Result;
+use crate::subject::Subject;
+
+/// Case folding applied to each token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CaseFolding {
+    /// Leave case unchanged
+    #[default]
+    Preserve,
+    /// Fold to lowercase
+    Lower,
+    /// Fold to uppercase
+    Upper,
+}
+
+/// Rules for turning subject spellings into a canonical form
+///
+/// Steps run in a fixed order: token separators are replaced with `.`,
+/// tokens are trimmed, case is folded, word separators inside tokens are
+/// unified, and finally whole-token aliases are resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizationPolicy {
+    /// Case folding for every token
+    pub case: CaseFolding,
+    /// Trim whitespace around the subject and each token
+    pub trim: bool,
+    /// Characters accepted as token separators in addition to `.`
+    pub separators: Vec<char>,
+    /// Replace `-` and `_` inside tokens with this character
+    pub word_separator: Option<char>,
+    /// Whole-token aliases, e.g. `evt` -> `events`
+    pub aliases: HashMap<String, String>,
+}
+
+impl NormalizationPolicy {
+    /// Create a policy that changes nothing
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A common policy: trimmed, lowercase, `/` and `:` as separators, `_`
+    /// between words
+    #[must_use]
+    pub fn standard() -> Self {
+        Self::new()
+            .lowercase()
+            .trim()
+            .separator('/')
+            .separator(':')
+            .word_separator('_')
+    }
+
+    /// Fold tokens to lowercase
+    #[must_use]
+    pub fn lowercase(mut self) -> Self {
+        self.case = CaseFolding::Lower;
+        self
+    }
+
+    /// Set the case folding
+    #[must_use]
+    pub fn case(mut self, case: CaseFolding) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Trim whitespace around the subject and its tokens
+    #[must_use]
+    pub fn trim(mut self) -> Self {
+        self.trim = true;
+        self
+    }
+
+    /// Accept an additional token separator
+    #[must_use]
+    pub fn separator(mut self, separator: char) -> Self {
+        if !self.separators.contains(&separator) {
+            self.separators.push(separator);
+        }
+        self
+    }
+
+    /// Unify `-` and `_` inside tokens to one character
+    #[must_use]
+    pub fn word_separator(mut self, separator: char) -> Self {
+        self.word_separator = Some(separator);
+        self
+    }
+
+    /// Resolve a token alias
+    ///
+    /// Aliases are matched after case folding, so register them in canonical
+    /// case.
+    #[must_use]
+    pub fn alias(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.aliases.insert(from.into(), to.into());
+        self
+    }
+
+    /// Normalize a raw subject string without validating it
+    #[must_use]
+    pub fn normalize(&self, subject: &str) -> String {
+        let subject = if self.trim { subject.trim() } else { subject };
+        let subject: String = subject
+            .chars()
+            .map(|c| if self.separators.contains(&c) { '.' } else { c })
+            .collect();
+
+        subject
+            .split('.')
+            .map(|token| self.normalize_token(token))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Normalize and parse a raw subject string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the normalized string is not a valid subject
+    pub fn canonicalize(&self, subject: &str) -> Result<Subject> {
+        Subject::new(self.normalize(subject))
+    }
+
+    fn normalize_token(&self, token: &str) -> String {
+        let token = if self.trim { token.trim() } else { token };
+        let token = match self.case {
+            CaseFolding::Preserve => token.to_string(),
+            CaseFolding::Lower => token.to_lowercase(),
+            CaseFolding::Upper => token.to_uppercase(),
+        };
+        let token = match self.word_separator {
+            Some(separator) => token
+                .chars()
+                .map(|c| if c == '-' || c == '_' { separator } else { c })
+                .collect(),
+            None => token,
+        };
+        self.aliases.get(&token).cloned().unwrap_or(token)
+    }
+}
+
+impl Subject {
+    /// Rewrite this subject into the canonical form described by a policy
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an alias or word separator produces an invalid
+    /// subject
+    pub fn canonicalize(&self, policy: &NormalizationPolicy) -> Result<Subject> {
+        policy.canonicalize(self.as_str())
+    }
+
+    /// Check if this subject is already canonical under a policy
+    #[must_use]
+    pub fn is_canonical(&self, policy: &NormalizationPolicy) -> bool {
+        policy.normalize(self.as_str()) == self.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_policy() {
+        let policy = NormalizationPolicy::standard().alias("evt", "events");
+
+        let subject = policy
+            .canonicalize("  Orders/EVT:Order-Placed.v1 ")
+            .unwrap();
+        assert_eq!(subject.as_str(), "orders.events.order_placed.v1");
+        assert!(subject.is_canonical(&policy));
+
+        let mixed = Subject::new("Orders.Order.Placed.v1").unwrap();
+        assert!(!mixed.is_canonical(&policy));
+        assert_eq!(
+            mixed.canonicalize(&policy).unwrap().as_str(),
+            "orders.order.placed.v1"
+        );
+    }
+
+    #[test]
+    fn test_default_policy_is_identity() {
+        let policy = NormalizationPolicy::new();
+        let subject = Subject::new("Orders.Order.Placed.v1").unwrap();
+        assert!(subject.is_canonical(&policy));
+
+        // Separators are not accepted unless configured
+        assert!(policy.canonicalize("orders/order/placed/v1").is_err());
+        // Aliases must still yield a valid subject
+        let policy = policy.alias("placed", "placed!");
+        assert!(subject.canonicalize(&policy).is_ok());
+        assert!(policy.canonicalize("orders.order.placed.v1").is_err());
+    }
+}
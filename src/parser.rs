@@ -1,8 +1,56 @@
 // Copyright 2025 Cowboy AI, LLC.
 
 //! Subject parser with custom parsing rules
-
-use std::sync::Arc;
+//!
+//! Deployments that embed entity IDs in the aggregate token, such as
+//! `orders.order-123.created.v1`, can register
+//! [`ParseRule::aggregate_identity`] for a context and read the
+//! [`AggregateIdentity`] back from parsed subjects.
+//!
+//! ```
+//! use cim_subject::parser::ParserBuilder;
+//!
+//! let parser = ParserBuilder::new()
+//!     .with_aggregate_identity("orders", '-')
+//!     .build();
+//!
+//! let (subject, identity) = parser.parse_identified("orders.order-123.created.v1")?;
+//! assert_eq!(subject.aggregate(), "order-123");
+//! let identity = identity.unwrap();
+//! assert_eq!(identity.aggregate_type, "order");
+//! assert_eq!(identity.aggregate_id, "123");
+//!
+//! assert!(parser.parse("orders.order.created.v1").is_err());
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+//!
+//! Legacy systems that separate tokens with `_`, `/` or `:` can be ingested
+//! by giving the parser a [`Separator`]; rules and validators then see the
+//! canonical dot form.
+//!
+//! ```
+//! use cim_subject::parser::{
+//!     ParserBuilder,
+//!     Separator,
+//! };
+//!
+//! let parser = ParserBuilder::new()
+//!     .with_separator(Separator::Colon)
+//!     .build();
+//! let subject = parser.parse("orders:order:placed:v1")?;
+//! assert_eq!(subject.as_str(), "orders.order.placed.v1");
+//! assert_eq!(parser.render(&subject)?, "orders:order:placed:v1");
+//!
+//! let legacy = Separator::Underscore.to_canonical("ORDERS_CMD_CREATE")?;
+//! assert_eq!(legacy, "ORDERS.CMD.CREATE");
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::borrow::Cow;
+use std::sync::{
+    Arc,
+    RwLock,
+};
 
 use dashmap::DashMap;
 
@@ -10,6 +58,7 @@ use crate::error::{
     Result,
     SubjectError,
 };
+use crate::pattern::Pattern;
 use crate::subject::{
     Subject,
     SubjectParts,
@@ -21,13 +70,32 @@ pub type ParserFn = Arc<dyn Fn(&str) -> Result<SubjectParts> + Send + Sync>;
 /// Type alias for validator functions
 pub type ValidatorFn = Arc<dyn Fn(&SubjectParts) -> Result<()> + Send + Sync>;
 
+/// Priority of rules keyed by context; pattern-keyed rules above it are
+/// tried first, and rules at or below it only when no context rule applies
+pub const CONTEXT_RULE_PRIORITY: i32 = 0;
+
 /// Parser for subjects with custom rules
+///
+/// For each subject the parser tries, in order: pattern-keyed rules with a
+/// priority above [`CONTEXT_RULE_PRIORITY`], the rule for the subject's
+/// context, the remaining pattern-keyed rules, the fallback rule, and
+/// finally standard parsing. Pattern-keyed rules of equal priority are
+/// tried in registration order. The first rule that applies parses the
+/// subject; its errors are not retried with later rules.
 #[derive(Clone)]
 pub struct SubjectParser {
     /// Custom parsing rules by context
     rules: Arc<DashMap<String, ParseRule>>,
+    /// Custom parsing rules by pattern, highest priority first
+    pattern_rules: Arc<RwLock<Vec<PatternRule>>>,
+    /// Rule for subjects no other rule applies to
+    fallback: Arc<RwLock<Option<ParseRule>>>,
     /// Validation rules
     validators: Arc<DashMap<String, ValidationRule>>,
+    /// Aggregate identity separators by context
+    identities: Arc<DashMap<String, char>>,
+    /// Token separator of the input
+    separator: Separator,
 }
 
 impl Default for SubjectParser {
@@ -42,15 +110,76 @@ impl SubjectParser {
     pub fn new() -> Self {
         Self {
             rules: Arc::new(DashMap::new()),
+            pattern_rules: Arc::new(RwLock::new(Vec::new())),
+            fallback: Arc::new(RwLock::new(None)),
             validators: Arc::new(DashMap::new()),
+            identities: Arc::new(DashMap::new()),
+            separator: Separator::Dot,
         }
     }
 
+    /// Parse input whose tokens are separated by `separator` instead of `.`
+    #[must_use]
+    pub fn with_separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// The token separator of the input
+    #[must_use]
+    pub fn separator(&self) -> Separator {
+        self.separator
+    }
+
+    /// Write a subject with the parser's separator, reversing
+    /// [`parse`](Self::parse)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token contains the separator
+    pub fn render(&self, subject: &Subject) -> Result<String> {
+        self.separator.from_canonical(subject.as_str())
+    }
+
     /// Register a custom parsing rule for a context
     pub fn register_rule(&self, context: impl Into<String>, rule: ParseRule) {
         self.rules.insert(context.into(), rule);
     }
 
+    /// Register a custom parsing rule for subjects matching `pattern`
+    ///
+    /// Higher priorities are tried first; see [`SubjectParser`] for how
+    /// they interleave with context rules.
+    pub fn register_pattern_rule(&self, pattern: Pattern, priority: i32, rule: ParseRule) {
+        if let Ok(mut rules) = self.pattern_rules.write() {
+            // After every rule of equal or higher priority, so ties keep
+            // registration order
+            let index = rules.partition_point(|existing| existing.priority >= priority);
+            rules.insert(index, PatternRule {
+                pattern,
+                priority,
+                rule,
+            });
+        }
+    }
+
+    /// Set the rule for subjects no context or pattern rule applies to,
+    /// replacing standard parsing for them
+    pub fn set_fallback_rule(&self, rule: ParseRule) {
+        if let Ok(mut fallback) = self.fallback.write() {
+            *fallback = Some(rule);
+        }
+    }
+
+    /// Register [`ParseRule::aggregate_identity`] for a context, so
+    /// [`aggregate_identity`](Self::aggregate_identity) can split its
+    /// subjects' aggregates
+    pub fn register_aggregate_identity(&self, context: impl Into<String>, separator: char) {
+        let context = context.into();
+        self.register_rule(context.clone(), ParseRule::aggregate_identity(separator));
+        self.identities.insert(context, separator);
+    }
+
     /// Register a validation rule
     pub fn register_validator(&self, name: impl Into<String>, validator: ValidationRule) {
         self.validators.insert(name.into(), validator);
@@ -62,20 +191,20 @@ impl SubjectParser {
     ///
     /// Returns an error if:
     /// - The subject string is empty
+    /// - The subject contains `.` but the parser uses another separator
     /// - The subject format is invalid
     /// - Validation rules fail
     pub fn parse(&self, subject: &str) -> Result<Subject> {
-        // Extract the context (first part) to check for custom rules
-        let parts: Vec<&str> = subject.split('.').collect();
-        if parts.is_empty() {
+        let canonical = self.separator.to_canonical(subject)?;
+        let subject = canonical.as_ref();
+
+        if subject.is_empty() {
             return Err(SubjectError::invalid_format("Empty subject"));
         }
 
-        let context = parts[0];
-
-        // Check for custom parsing rules for this context
-        if let Some(rule) = self.rules.get(context) {
-            let custom_parts = rule.parse(subject)?;
+        // Check for a custom parsing rule for this subject
+        if let Some(parser) = self.select_rule(subject) {
+            let custom_parts = parser(subject)?;
             // Validate the parsed subject
             self.validate(&custom_parts)?;
             return Ok(Subject::from_parts(custom_parts));
@@ -90,6 +219,45 @@ impl SubjectParser {
         Ok(Subject::from_parts(standard_parts))
     }
 
+    /// Parse a subject string, also splitting its aggregate if its context
+    /// has a registered aggregate identity
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`](Self::parse)
+    pub fn parse_identified(&self, subject: &str) -> Result<(Subject, Option<AggregateIdentity>)> {
+        let subject = self.parse(subject)?;
+        let identity = self.aggregate_identity(&subject);
+        Ok((subject, identity))
+    }
+
+    /// The aggregate identity of a subject whose context has one registered
+    #[must_use]
+    pub fn aggregate_identity(&self, subject: &Subject) -> Option<AggregateIdentity> {
+        let separator = *self.identities.get(subject.context())?;
+        AggregateIdentity::split(subject.aggregate(), separator)
+    }
+
+    /// The parser function of the first rule that applies to `subject`
+    fn select_rule(&self, subject: &str) -> Option<ParserFn> {
+        let context = subject.split('.').next().unwrap_or_default();
+        let context_rule = self.rules.get(context).map(|rule| Arc::clone(&rule.parser));
+        if let Ok(rules) = self.pattern_rules.read() {
+            for pattern_rule in rules.iter() {
+                if context_rule.is_some() && pattern_rule.priority <= CONTEXT_RULE_PRIORITY {
+                    break;
+                }
+                if pattern_rule.pattern.matches_str(subject) {
+                    return Some(Arc::clone(&pattern_rule.rule.parser));
+                }
+            }
+        }
+        context_rule.or_else(|| {
+            let fallback = self.fallback.read().ok()?;
+            fallback.as_ref().map(|rule| Arc::clone(&rule.parser))
+        })
+    }
+
     /// Validate subject parts
     fn validate(&self, parts: &SubjectParts) -> Result<()> {
         // Run all validators
@@ -139,6 +307,71 @@ impl SubjectParser {
     }
 }
 
+/// Token separator of a subject naming convention
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Separator {
+    /// `orders.order.placed.v1`, the canonical form
+    #[default]
+    Dot,
+    /// `ORDERS_CMD_CREATE`
+    Underscore,
+    /// `orders/order/placed/v1`
+    Slash,
+    /// `orders:order:placed:v1`
+    Colon,
+}
+
+impl Separator {
+    /// The separator character
+    #[must_use]
+    pub const fn as_char(self) -> char {
+        match self {
+            Self::Dot => '.',
+            Self::Underscore => '_',
+            Self::Slash => '/',
+            Self::Colon => ':',
+        }
+    }
+
+    /// Convert `raw` to canonical dot form
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the separator is not `.` and `raw` already
+    /// contains a `.`, which would make the token boundaries ambiguous
+    pub fn to_canonical(self, raw: &str) -> Result<Cow<'_, str>> {
+        if self == Self::Dot {
+            return Ok(Cow::Borrowed(raw));
+        }
+        if raw.contains('.') {
+            return Err(SubjectError::invalid_format(format!(
+                "'{raw}' contains '.' but tokens are separated by '{}'",
+                self.as_char()
+            )));
+        }
+        Ok(Cow::Owned(raw.replace(self.as_char(), ".")))
+    }
+
+    /// Convert a canonical dot-form subject or pattern to this separator
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token contains the separator, so the result
+    /// would not convert back to the same tokens
+    pub fn from_canonical(self, canonical: &str) -> Result<String> {
+        if self == Self::Dot {
+            return Ok(canonical.to_string());
+        }
+        if canonical.contains(self.as_char()) {
+            return Err(SubjectError::invalid_format(format!(
+                "'{canonical}' has a token containing the separator '{}'",
+                self.as_char()
+            )));
+        }
+        Ok(canonical.replace('.', &self.as_char().to_string()))
+    }
+}
+
 /// A custom parsing rule
 #[derive(Clone)]
 pub struct ParseRule {
@@ -160,6 +393,29 @@ impl ParseRule {
         }
     }
 
+    /// A rule accepting standard subjects whose aggregate embeds an ID
+    /// after `separator`, such as `order-123`
+    ///
+    /// The subject is kept as is; [`AggregateIdentity::split`] recovers the
+    /// aggregate type and ID.
+    #[must_use]
+    pub fn aggregate_identity(separator: char) -> Self {
+        Self::new(
+            "aggregate_identity",
+            format!("Aggregate type and ID separated by '{separator}'"),
+            Arc::new(move |subject| {
+                let parts = SubjectParts::parse(subject)?;
+                if AggregateIdentity::split(&parts.aggregate, separator).is_none() {
+                    return Err(SubjectError::validation_error(format!(
+                        "Aggregate '{}' has no '{separator}'-separated ID",
+                        parts.aggregate
+                    )));
+                }
+                Ok(parts)
+            }),
+        )
+    }
+
     /// Parse a subject using this rule
     ///
     /// # Errors
@@ -170,6 +426,41 @@ impl ParseRule {
     }
 }
 
+/// A [`ParseRule`] keyed by pattern
+#[derive(Clone)]
+struct PatternRule {
+    pattern: Pattern,
+    priority: i32,
+    rule: ParseRule,
+}
+
+/// The aggregate type and entity ID embedded in an aggregate token
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AggregateIdentity {
+    /// The aggregate type, before the separator
+    pub aggregate_type: String,
+    /// The entity ID, after the separator
+    pub aggregate_id: String,
+}
+
+impl AggregateIdentity {
+    /// Split an aggregate token at the first `separator`, so the aggregate
+    /// type cannot contain the separator but the ID can
+    ///
+    /// Returns `None` unless both sides are non-empty.
+    #[must_use]
+    pub fn split(aggregate: &str, separator: char) -> Option<Self> {
+        let (aggregate_type, aggregate_id) = aggregate.split_once(separator)?;
+        if aggregate_type.is_empty() || aggregate_id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            aggregate_type: aggregate_type.to_string(),
+            aggregate_id: aggregate_id.to_string(),
+        })
+    }
+}
+
 /// A validation rule
 #[derive(Clone)]
 pub struct ValidationRule {
@@ -202,7 +493,11 @@ impl ValidationRule {
 #[derive(Default)]
 pub struct ParserBuilder {
     rules: Vec<(String, ParseRule)>,
+    pattern_rules: Vec<(Pattern, i32, ParseRule)>,
+    fallback: Option<ParseRule>,
     validators: Vec<(String, ValidationRule)>,
+    identities: Vec<(String, char)>,
+    separator: Separator,
 }
 
 impl ParserBuilder {
@@ -219,6 +514,21 @@ impl ParserBuilder {
         self
     }
 
+    /// Add a parsing rule for subjects matching `pattern`; see
+    /// [`SubjectParser::register_pattern_rule`]
+    #[must_use]
+    pub fn with_pattern_rule(mut self, pattern: Pattern, priority: i32, rule: ParseRule) -> Self {
+        self.pattern_rules.push((pattern, priority, rule));
+        self
+    }
+
+    /// Set the rule for subjects no other rule applies to
+    #[must_use]
+    pub fn with_fallback_rule(mut self, rule: ParseRule) -> Self {
+        self.fallback = Some(rule);
+        self
+    }
+
     /// Add a validation rule
     #[must_use]
     pub fn with_validator(mut self, name: impl Into<String>, validator: ValidationRule) -> Self {
@@ -255,15 +565,43 @@ impl ParserBuilder {
         self
     }
 
+    /// Split aggregate identities out of a context's subjects; see
+    /// [`ParseRule::aggregate_identity`]
+    #[must_use]
+    pub fn with_aggregate_identity(mut self, context: impl Into<String>, separator: char) -> Self {
+        self.identities.push((context.into(), separator));
+        self
+    }
+
+    /// Parse input whose tokens are separated by `separator`; rules and
+    /// validators see the canonical dot form
+    #[must_use]
+    pub fn with_separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
+    }
+
     /// Build the parser
     #[must_use]
     pub fn build(self) -> SubjectParser {
-        let parser = SubjectParser::new();
+        let parser = SubjectParser::new().with_separator(self.separator);
 
         for (context, rule) in self.rules {
             parser.register_rule(context, rule);
         }
 
+        for (pattern, priority, rule) in self.pattern_rules {
+            parser.register_pattern_rule(pattern, priority, rule);
+        }
+
+        if let Some(rule) = self.fallback {
+            parser.set_fallback_rule(rule);
+        }
+
+        for (context, separator) in self.identities {
+            parser.register_aggregate_identity(context, separator);
+        }
+
         for (name, validator) in self.validators {
             parser.register_validator(name, validator);
         }
@@ -337,6 +675,89 @@ mod tests {
         assert_eq!(s2.version(), "v2");
     }
 
+    #[test]
+    fn test_aggregate_identity() {
+        let parser = ParserBuilder::new()
+            .with_aggregate_identity("orders", '-')
+            .build();
+
+        // IDs may contain the separator, aggregate types may not
+        let (_, identity) = parser
+            .parse_identified("orders.order-7f3a-11ee.created.v1")
+            .unwrap();
+        assert_eq!(
+            identity,
+            Some(AggregateIdentity {
+                aggregate_type: "order".to_string(),
+                aggregate_id: "7f3a-11ee".to_string(),
+            })
+        );
+        assert!(parser.parse("orders.order-.created.v1").is_err());
+
+        // Other contexts parse as usual, without an identity
+        let (subject, identity) = parser.parse_identified("users.person.created.v1").unwrap();
+        assert_eq!(subject.aggregate(), "person");
+        assert_eq!(identity, None);
+    }
+
+    #[test]
+    fn test_separators() {
+        let parser = ParserBuilder::new()
+            .with_separator(Separator::Slash)
+            .with_flexible_context("graph")
+            .build();
+        let subject = parser.parse("orders/order/placed/v1").unwrap();
+        assert_eq!(subject.as_str(), "orders.order.placed.v1");
+        assert_eq!(parser.render(&subject).unwrap(), "orders/order/placed/v1");
+        // Rules see the canonical form
+        let nested = parser.parse("graph/workflow/node/updated/v2").unwrap();
+        assert_eq!(nested.aggregate(), "workflow.node");
+        assert!(parser.parse("orders.order.placed.v1").is_err());
+
+        let underscore = Separator::Underscore;
+        assert_eq!(
+            underscore.to_canonical("ORDERS_CMD_CREATE").unwrap(),
+            "ORDERS.CMD.CREATE"
+        );
+        assert!(underscore
+            .from_canonical("user_events.person.created.v1")
+            .is_err());
+        assert_eq!(
+            Separator::Dot.to_canonical("a.b.c.v1").unwrap(),
+            Cow::Borrowed("a.b.c.v1")
+        );
+    }
+
+    #[test]
+    fn test_rule_priorities() {
+        let fixed = |event: &'static str| {
+            ParseRule::new(
+                event,
+                format!("Parses every subject as a {event} event"),
+                Arc::new(move |subject| {
+                    let context = subject.split('.').next().unwrap_or_default();
+                    Ok(SubjectParts::new(context, "any", event, "v1"))
+                }),
+            )
+        };
+        let parser = ParserBuilder::new()
+            .with_rule("orders", fixed("context"))
+            .with_pattern_rule(Pattern::new("*.*.placed.>").unwrap(), 10, fixed("high"))
+            .with_pattern_rule(Pattern::new("*.*.placed.>").unwrap(), 10, fixed("tie"))
+            .with_pattern_rule(Pattern::new("*.*.shipped.>").unwrap(), 0, fixed("low"))
+            .with_fallback_rule(fixed("fallback"))
+            .build();
+        let event = |subject: &str| parser.parse(subject).unwrap().event_type().to_string();
+
+        // Higher priorities beat context rules; ties keep registration order
+        assert_eq!(event("orders.order.placed.v1"), "high");
+        // Context rules beat patterns at or below their priority
+        assert_eq!(event("orders.order.shipped.v1"), "context");
+        assert_eq!(event("billing.order.shipped.v1"), "low");
+        // Anything else, even input standard parsing rejects
+        assert_eq!(event("billing.invoice"), "fallback");
+    }
+
     #[test]
     fn test_validation_rules() {
         let parser = ParserBuilder::new()
@@ -0,0 +1,196 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Partition keys derived from subjects
+//!
+//! Sharded consumers must agree on which partition a subject belongs to,
+//! whatever language or service computes it. A [`PartitionStrategy`]
+//! selects the tokens that decide the partition, usually the aggregate so
+//! that each aggregate's events stay in order on one partition, and
+//! [`Subject::partition_key`] hashes them with 64-bit FNV-1a, joined by
+//! `.`. [`Subject::partition`] maps the key to one of `n` partitions with
+//! jump consistent hashing, so growing from `n` to `n + 1` partitions moves
+//! only about `1 / (n + 1)` of the keys.
+//!
+//! ```
+//! use cim_subject::partition::PartitionStrategy;
+//! use cim_subject::Subject;
+//!
+//! let placed = Subject::new("orders.order.placed.v1")?;
+//! let shipped = Subject::new("orders.order.shipped.v2")?;
+//! let strategy = PartitionStrategy::context_aggregate();
+//!
+//! assert_eq!(
+//!     placed.partition_key(&strategy),
+//!     shipped.partition_key(&strategy)
+//! );
+//! assert_eq!(placed.partition_key(&strategy), 0x67d4_0a57_60ea_b45e);
+//! assert!(placed.partition(&strategy, 16) < 16);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use alloc::format;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Which subject tokens decide the partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartitionStrategy {
+    tokens: [bool; 4],
+}
+
+impl PartitionStrategy {
+    /// Partition by aggregate alone
+    #[must_use]
+    pub const fn aggregate() -> Self {
+        Self {
+            tokens: [false, true, false, false],
+        }
+    }
+
+    /// Partition by context and aggregate, so equal aggregate names in
+    /// different contexts spread independently
+    #[must_use]
+    pub const fn context_aggregate() -> Self {
+        Self {
+            tokens: [true, true, false, false],
+        }
+    }
+
+    /// Partition by the whole subject
+    #[must_use]
+    pub const fn subject() -> Self {
+        Self { tokens: [true; 4] }
+    }
+
+    /// Partition by the tokens at `positions`, counted from zero for the
+    /// context; order and repeats do not matter
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `positions` is empty or a position is past the
+    /// fourth token
+    pub fn tokens(positions: &[usize]) -> Result<Self> {
+        let mut tokens = [false; 4];
+        for &position in positions {
+            let token = tokens.get_mut(position).ok_or_else(|| {
+                SubjectError::validation_error(format!(
+                    "Token position {position} is out of range; subjects have 4 tokens"
+                ))
+            })?;
+            *token = true;
+        }
+        if positions.is_empty() {
+            return Err(SubjectError::validation_error(
+                "A partition strategy needs at least one token",
+            ));
+        }
+        Ok(Self { tokens })
+    }
+}
+
+impl Subject {
+    /// A stable 64-bit key: FNV-1a of the tokens selected by `strategy`,
+    /// joined by `.`
+    #[must_use]
+    pub fn partition_key(&self, strategy: &PartitionStrategy) -> u64 {
+        let selected = self
+            .as_str()
+            .split('.')
+            .zip(strategy.tokens)
+            .filter_map(|(token, selected)| selected.then_some(token));
+        let mut hash = FNV_OFFSET_BASIS;
+        for (index, token) in selected.enumerate() {
+            let separator: &[u8] = if index == 0 { b"" } else { b"." };
+            for &byte in separator.iter().chain(token.as_bytes()) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    /// The partition, out of `partitions`, for this subject's
+    /// [`partition_key`](Self::partition_key)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `partitions` is zero
+    #[must_use]
+    pub fn partition(&self, strategy: &PartitionStrategy, partitions: u32) -> u32 {
+        jump_hash(self.partition_key(strategy), partitions)
+    }
+}
+
+/// Jump consistent hash (Lamping and Veach, 2014)
+///
+/// # Panics
+///
+/// Panics if `buckets` is zero
+#[must_use]
+pub fn jump_hash(mut key: u64, buckets: u32) -> u32 {
+    assert!(buckets > 0, "jump_hash needs at least one bucket");
+    let mut bucket: i64 = -1;
+    let mut next: i64 = 0;
+    while next < i64::from(buckets) {
+        bucket = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        {
+            next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+        }
+    }
+    // The loop only exits once `bucket` is a valid index below `buckets`
+    u32::try_from(bucket).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies() {
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let other = Subject::new("billing.order.placed.v1").unwrap();
+        let aggregate = PartitionStrategy::aggregate();
+        assert_eq!(
+            placed.partition_key(&aggregate),
+            other.partition_key(&aggregate)
+        );
+        let scoped = PartitionStrategy::context_aggregate();
+        assert_ne!(placed.partition_key(&scoped), other.partition_key(&scoped));
+        assert_eq!(PartitionStrategy::tokens(&[1, 0, 1]).unwrap(), scoped);
+        assert!(PartitionStrategy::tokens(&[4]).is_err());
+        assert!(PartitionStrategy::tokens(&[]).is_err());
+
+        // FNV-1a test vector for "a"
+        let event = PartitionStrategy::tokens(&[2]).unwrap();
+        let a = Subject::new("x.y.a.v1").unwrap();
+        assert_eq!(a.partition_key(&event), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_jump_hash_is_consistent() {
+        assert_eq!(jump_hash(42, 1), 0);
+        let keys = 0..10_000u64;
+        let moved = keys
+            .clone()
+            .filter(|&key| {
+                let key = key.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                jump_hash(key, 10) != jump_hash(key, 11)
+            })
+            .count();
+        // About a tenth of the keys move, and only to the new bucket
+        assert!((700..1_100).contains(&moved), "{moved} keys moved");
+        for key in keys.map(|key| key.wrapping_mul(0x9e37_79b9_7f4a_7c15)) {
+            let (before, after) = (jump_hash(key, 10), jump_hash(key, 11));
+            assert!(before == after || after == 10);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! File-system paths and URLs for subjects
+//!
+//! Event archives on disk or in object storage, and webhook endpoints, are
+//! often laid out by subject. [`Subject::to_path`] and [`Subject::to_url`]
+//! give each token its own path segment, and [`Subject::from_path`] and
+//! [`Subject::from_url`] reverse them. Characters outside ASCII letters,
+//! digits, `_` and `-` are percent-encoded as UTF-8, so paths and URLs are
+//! plain ASCII and map back to exactly one subject.
+//!
+//! ```
+//! use std::path::Path;
+//!
+//! use cim_subject::Subject;
+//!
+//! let subject = Subject::new("orders.order.placed.v1")?;
+//! assert_eq!(subject.to_path(), Path::new("orders/order/placed/v1"));
+//! assert_eq!(
+//!     subject.to_url("https://hooks.example.com/events/"),
+//!     "https://hooks.example.com/events/orders/order/placed/v1"
+//! );
+//!
+//! let café = Subject::new("menu.café.opened.v1")?;
+//! let url = café.to_url("https://hooks.example.com");
+//! assert_eq!(url, "https://hooks.example.com/menu/caf%C3%A9/opened/v1");
+//! assert_eq!(Subject::from_url("https://hooks.example.com", &url)?, café);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::Write;
+use std::path::{
+    Component,
+    Path,
+    PathBuf,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+impl Subject {
+    /// The relative path with one directory level per token
+    #[must_use]
+    pub fn to_path(&self) -> PathBuf {
+        self.as_str().split('.').map(encode).collect()
+    }
+
+    /// Parse a relative path of four segments, as produced by
+    /// [`to_path`](Self::to_path)
+    ///
+    /// Strip an archive root with [`Path::strip_prefix`] first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is absolute, has a `.` or `..`
+    /// component, a segment that is not valid UTF-8 or percent-encoding, or
+    /// does not decode to a valid subject
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let segments = path
+            .components()
+            .map(|component| match component {
+                Component::Normal(segment) => segment.to_str().ok_or_else(|| {
+                    SubjectError::parse_error(format!(
+                        "Path '{}' is not valid UTF-8",
+                        path.display()
+                    ))
+                }),
+                _ => Err(SubjectError::parse_error(format!(
+                    "Path '{}' must be relative, without '.' or '..'",
+                    path.display()
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        from_segments(&segments)
+    }
+
+    /// The URL under `base` with one path segment per token
+    ///
+    /// A trailing `/` on `base` is ignored.
+    #[must_use]
+    pub fn to_url(&self, base: &str) -> String {
+        let mut url = base.trim_end_matches('/').to_string();
+        for token in self.as_str().split('.') {
+            url.push('/');
+            url.push_str(&encode(token));
+        }
+        url
+    }
+
+    /// Parse a URL produced by [`to_url`](Self::to_url) with the same
+    /// `base`, ignoring any query string or fragment
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not under `base`, or its path below
+    /// `base` does not decode to a valid subject
+    pub fn from_url(base: &str, url: &str) -> Result<Self> {
+        let base = base.trim_end_matches('/');
+        let path = url
+            .strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(|| {
+                SubjectError::parse_error(format!("URL '{url}' is not under '{base}'"))
+            })?;
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        from_segments(&path.split('/').collect::<Vec<_>>())
+    }
+}
+
+/// Decode path segments and join them into a subject
+fn from_segments(segments: &[&str]) -> Result<Subject> {
+    let tokens = segments
+        .iter()
+        .map(|segment| decode(segment))
+        .collect::<Result<Vec<_>>>()?;
+    // A decoded `.` would split a segment into several tokens
+    if let Some(token) = tokens.iter().find(|token| token.contains('.')) {
+        return Err(SubjectError::parse_error(format!(
+            "Segment '{token}' decodes to more than one token"
+        )));
+    }
+    Subject::new(tokens.join("."))
+}
+
+/// Percent-encode everything but ASCII letters, digits, `_` and `-`
+fn encode(token: &str) -> String {
+    let mut encoded = String::with_capacity(token.len());
+    for c in token.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+            encoded.push(c);
+        } else {
+            let mut utf8 = [0; 4];
+            for byte in c.encode_utf8(&mut utf8).bytes() {
+                // Writing to a String cannot fail
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+/// Reverse [`encode`]
+fn decode(segment: &str) -> Result<String> {
+    let invalid = || SubjectError::parse_error(format!("Invalid percent-encoding in '{segment}'"));
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_round_trip() {
+        for raw in [
+            "orders.order.placed.v1",
+            "menu.café.opened.v1",
+            "a-b.c_d.日本.v2",
+        ] {
+            let subject = Subject::new(raw).unwrap();
+            let path = subject.to_path();
+            assert!(path.to_str().unwrap().is_ascii());
+            assert_eq!(Subject::from_path(&path).unwrap(), subject);
+            let url = subject.to_url("s3://archive/events");
+            assert_eq!(
+                Subject::from_url("s3://archive/events/", &url).unwrap(),
+                subject
+            );
+        }
+
+        let archived = Path::new("/var/archive/orders/order/placed/v1");
+        let relative = archived.strip_prefix("/var/archive").unwrap();
+        assert_eq!(
+            Subject::from_path(relative).unwrap().as_str(),
+            "orders.order.placed.v1"
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        assert!(Subject::from_path(Path::new("/orders/order/placed/v1")).is_err());
+        assert!(Subject::from_path(Path::new("orders/../placed/v1")).is_err());
+        assert!(Subject::from_path(Path::new("orders/order/placed")).is_err());
+        // Encoded separators do not smuggle in extra tokens
+        assert!(Subject::from_path(Path::new("orders/order%2Eplaced/v1")).is_err());
+        assert!(Subject::from_url("https://a.example", "https://a.example/x/y/z%2").is_err());
+        assert!(Subject::from_url("https://a.example", "https://b.example/x/y/z/v1").is_err());
+
+        let subject = Subject::from_url(
+            "https://a.example",
+            "https://a.example/orders/order/placed/v1?attempt=2",
+        )
+        .unwrap();
+        assert_eq!(subject.as_str(), "orders.order.placed.v1");
+    }
+}
@@ -2,11 +2,17 @@
 
 //! Pattern matching for subjects with wildcard support
 
-use std::fmt::{
+use alloc::format;
+use alloc::string::{
+    String,
+    ToString,
+};
+use alloc::vec::Vec;
+use core::fmt::{
     self,
     Display,
 };
-use std::str::FromStr;
+use core::str::FromStr;
 
 use serde::{
     Deserialize,
@@ -14,10 +20,15 @@ use serde::{
 };
 
 use crate::error::{
+    ErrorCode,
     Result,
     SubjectError,
 };
-use crate::subject::Subject;
+use crate::limits::SubjectLimits;
+use crate::subject::{
+    Subject,
+    SubjectRef,
+};
 
 /// A pattern for matching subjects with wildcards
 ///
@@ -25,16 +36,83 @@ use crate::subject::Subject;
 /// - `*` matches exactly one token
 /// - `>` matches one or more tokens (must be at the end)
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "PatternRepr")]
 pub struct Pattern {
     /// The raw pattern string
     raw: String,
     /// Parsed tokens
     tokens: Vec<Token>,
+    /// Precomputed data for fast rejection, derived from `tokens`
+    #[serde(skip)]
+    prefilter: Prefilter,
+}
+
+/// Serialized form of a [`Pattern`]
+#[derive(Deserialize)]
+struct PatternRepr {
+    raw: String,
+    tokens: Vec<Token>,
+}
+
+impl From<PatternRepr> for Pattern {
+    fn from(repr: PatternRepr) -> Self {
+        let prefilter = Prefilter::new(&repr.tokens);
+        Self {
+            raw: repr.raw,
+            tokens: repr.tokens,
+            prefilter,
+        }
+    }
+}
+
+/// Literal ends of a pattern, which reject most non-matching subjects with
+/// one comparison each before any wildcard is matched
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct Prefilter {
+    /// Leading literal tokens joined with `.`, including the trailing `.`
+    /// when more tokens follow
+    prefix: String,
+    /// Number of tokens covered by `prefix`
+    prefix_tokens: usize,
+    /// Trailing literal tokens after the last wildcard, joined with `.` and
+    /// starting with one; empty if the pattern ends with a wildcard or is
+    /// all literals
+    suffix: String,
+    /// Number of tokens covered by `suffix`
+    suffix_tokens: usize,
+}
+
+impl Prefilter {
+    fn new(tokens: &[Token]) -> Self {
+        let literals: Vec<&str> = tokens.iter().map_while(Token::literal).collect();
+
+        let mut prefix = literals.join(".");
+        if literals.len() < tokens.len() && !literals.is_empty() {
+            prefix.push('.');
+        }
+
+        let mut suffix: Vec<&str> = Vec::new();
+        if literals.len() < tokens.len() {
+            suffix = tokens.iter().rev().map_while(Token::literal).collect();
+            suffix.reverse();
+        }
+
+        Self {
+            prefix,
+            prefix_tokens: literals.len(),
+            suffix: if suffix.is_empty() {
+                String::new()
+            } else {
+                format!(".{}", suffix.join("."))
+            },
+            suffix_tokens: suffix.len(),
+        }
+    }
 }
 
 /// A token in a pattern
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-enum Token {
+pub(crate) enum Token {
     /// Literal token that must match exactly
     Literal(String),
     /// Single wildcard (*)
@@ -43,58 +121,116 @@ enum Token {
     MultiWildcard,
 }
 
+impl Token {
+    fn literal(&self) -> Option<&str> {
+        match self {
+            Token::Literal(literal) => Some(literal),
+            _ => None,
+        }
+    }
+}
+
 impl Pattern {
     /// Create a new pattern
     ///
+    /// The first token may start with `$`, as NATS system subjects such as
+    /// `$JS.API.>` and `$SYS.>` do.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the pattern is invalid
+    /// Returns an error if the pattern is invalid or over
+    /// [`SubjectLimits::DEFAULT`]
     pub fn new(pattern: impl Into<String>) -> Result<Self> {
+        Self::new_with_limits(pattern, &SubjectLimits::DEFAULT)
+    }
+
+    /// Create a new pattern, enforcing `limits` instead of the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubjectError::LimitExceeded`] if the pattern is over
+    /// `limits`, and otherwise the same errors as [`Pattern::new`]
+    pub fn new_with_limits(pattern: impl Into<String>, limits: &SubjectLimits) -> Result<Self> {
         let raw = pattern.into();
+        limits.check_limits(&raw)?;
         let tokens = Self::parse_tokens(&raw)?;
-        Ok(Self { raw, tokens })
+        let prefilter = Prefilter::new(&tokens);
+        Ok(Self {
+            raw,
+            tokens,
+            prefilter,
+        })
+    }
+
+    /// Build a pattern from tokens that are already known to be valid
+    pub(crate) fn from_valid_tokens<S: AsRef<str>>(tokens: &[S]) -> Self {
+        let raw = tokens
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<_>>()
+            .join(".");
+        let tokens: Vec<Token> = tokens
+            .iter()
+            .map(|token| match token.as_ref() {
+                "*" => Token::SingleWildcard,
+                ">" => Token::MultiWildcard,
+                literal => Token::Literal(literal.to_string()),
+            })
+            .collect();
+        let prefilter = Prefilter::new(&tokens);
+        Self {
+            raw,
+            tokens,
+            prefilter,
+        }
     }
 
     /// Parse pattern tokens
     fn parse_tokens(pattern: &str) -> Result<Vec<Token>> {
+        let invalid = |span, reason| {
+            SubjectError::invalid_token(ErrorCode::InvalidPattern, pattern, span, reason)
+        };
         if pattern.is_empty() {
-            return Err(SubjectError::invalid_pattern("Pattern cannot be empty"));
+            return Err(invalid(0..0, "pattern is empty".to_string()));
         }
 
         let parts: Vec<&str> = pattern.split('.').collect();
         let mut tokens = Vec::with_capacity(parts.len());
+        let mut start = 0;
 
         for (i, part) in parts.iter().enumerate() {
             match *part {
                 "" => {
-                    return Err(SubjectError::invalid_pattern(format!(
-                        "Empty token at position {} in pattern '{}'",
-                        i + 1,
-                        pattern
-                    )));
+                    return Err(invalid(start..start, format!("token {} is empty", i + 1)));
                 },
                 "*" => tokens.push(Token::SingleWildcard),
                 ">" => {
                     if i != parts.len() - 1 {
-                        return Err(SubjectError::invalid_pattern(
-                            "Multi-wildcard '>' can only appear at the end of a pattern",
+                        return Err(invalid(
+                            start..start + 1,
+                            "multi-wildcard '>' before the last token".to_string(),
                         ));
                     }
                     tokens.push(Token::MultiWildcard);
                 },
                 literal => {
-                    // Validate literal token
-                    if !literal
-                        .chars()
-                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                    // Validate literal token, allowing a system subject's
+                    // leading '$'
+                    let name = match literal.strip_prefix('$') {
+                        Some(name) if i == 0 && !name.is_empty() => name,
+                        _ => literal,
+                    };
+                    if let Some((offset, c)) = name
+                        .char_indices()
+                        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '-'))
                     {
-                        return Err(SubjectError::invalid_pattern(format!(
-                            "Token '{literal}' contains invalid characters"
-                        )));
+                        let at = start + literal.len() - name.len() + offset;
+                        return Err(invalid(at..at + c.len_utf8(), format!("character '{c}'")));
                     }
                     tokens.push(Token::Literal(literal.to_string()));
                 },
             }
+            start += part.len() + 1;
         }
 
         Ok(tokens)
@@ -107,40 +243,88 @@ impl Pattern {
     }
 
     /// Check if a subject string matches this pattern
+    ///
+    /// The pattern's literal prefix and suffix are compared first, then the
+    /// tokens between them are matched in a single pass over the bytes,
+    /// stopping at the first mismatch; the subject is never split or copied.
     #[must_use]
     pub fn matches_str(&self, subject: &str) -> bool {
-        let subject_parts: Vec<&str> = subject.split('.').collect();
-        self.matches_parts(&subject_parts)
-    }
+        let prefilter = &self.prefilter;
+        let mut rest = subject.as_bytes();
+        if prefilter.prefix_tokens > 0 {
+            let Some(tail) = rest.strip_prefix(prefilter.prefix.as_bytes()) else {
+                return false;
+            };
+            rest = tail;
+        }
+        if prefilter.suffix_tokens > 0 {
+            let Some(head) = rest.strip_suffix(prefilter.suffix.as_bytes()) else {
+                return false;
+            };
+            rest = head;
+        }
 
-    /// Check if subject parts match this pattern
-    fn matches_parts(&self, subject_parts: &[&str]) -> bool {
-        let mut pattern_idx = 0;
-        let mut subject_idx = 0;
+        let middle = prefilter.prefix_tokens..self.tokens.len() - prefilter.suffix_tokens;
+        Self::matches_bytes(&self.tokens[middle], rest)
+    }
 
-        while pattern_idx < self.tokens.len() && subject_idx < subject_parts.len() {
-            match &self.tokens[pattern_idx] {
-                Token::MultiWildcard => {
-                    // > matches everything remaining
-                    return true;
+    /// Match pattern tokens against subject bytes that start and end on
+    /// token boundaries
+    fn matches_bytes(tokens: &[Token], mut rest: &[u8]) -> bool {
+        for (i, token) in tokens.iter().enumerate() {
+            if i > 0 {
+                match rest.split_first() {
+                    Some((b'.', tail)) => rest = tail,
+                    _ => return false,
+                }
+            }
+            match token {
+                // At a token boundary at least one (possibly empty) token
+                // remains
+                Token::MultiWildcard => return true,
+                Token::SingleWildcard => {
+                    let end = rest.iter().position(|&b| b == b'.').unwrap_or(rest.len());
+                    rest = &rest[end..];
+                },
+                Token::Literal(literal) => match rest.strip_prefix(literal.as_bytes()) {
+                    Some(tail) => rest = tail,
+                    None => return false,
                 },
+            }
+        }
+
+        // Both must be exhausted for a match
+        rest.is_empty()
+    }
+
+    /// Check if a borrowed subject matches this pattern without allocating
+    #[must_use]
+    pub fn matches_ref(&self, subject: &SubjectRef<'_>) -> bool {
+        Self::matches_tokens(&self.tokens, subject.parts().tokens().into_iter())
+    }
+
+    /// Check if subject tokens match pattern tokens
+    fn matches_tokens<'s>(tokens: &[Token], mut subject: impl Iterator<Item = &'s str>) -> bool {
+        for token in tokens {
+            match token {
+                // > matches one or more remaining tokens
+                Token::MultiWildcard => return subject.next().is_some(),
+                // * matches exactly one token
                 Token::SingleWildcard => {
-                    // * matches exactly one token
-                    pattern_idx += 1;
-                    subject_idx += 1;
+                    if subject.next().is_none() {
+                        return false;
+                    }
                 },
                 Token::Literal(literal) => {
-                    if literal != subject_parts[subject_idx] {
+                    if subject.next() != Some(literal.as_str()) {
                         return false;
                     }
-                    pattern_idx += 1;
-                    subject_idx += 1;
                 },
             }
         }
 
         // Both must be exhausted for a match
-        pattern_idx == self.tokens.len() && subject_idx == subject_parts.len()
+        subject.next().is_none()
     }
 
     /// Get the raw pattern string
@@ -149,6 +333,38 @@ impl Pattern {
         &self.raw
     }
 
+    /// Get the parsed tokens
+    pub(crate) fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Sort key ordering patterns from most to least specific
+    ///
+    /// Consistent with [`Pattern::is_more_specific_than`]: a pattern is more
+    /// specific than another exactly when its key is smaller.
+    #[cfg(feature = "std")]
+    pub(crate) fn specificity_key(&self) -> (bool, usize, core::cmp::Reverse<usize>) {
+        let has_multi = self
+            .tokens
+            .iter()
+            .any(|t| matches!(t, Token::MultiWildcard));
+        let single_wildcards = self
+            .tokens
+            .iter()
+            .filter(|t| matches!(t, Token::SingleWildcard))
+            .count();
+        let first_wildcard = self
+            .tokens
+            .iter()
+            .position(|t| matches!(t, Token::SingleWildcard | Token::MultiWildcard))
+            .unwrap_or(usize::MAX);
+        (
+            has_multi,
+            single_wildcards,
+            core::cmp::Reverse(first_wildcard),
+        )
+    }
+
     /// Check if this pattern is more specific than another
     ///
     /// A pattern is more specific if it has fewer wildcards or
@@ -204,6 +420,68 @@ impl Pattern {
                                            * specific */
         }
     }
+
+    /// Check if every subject matched by `other` is also matched by this
+    /// pattern
+    ///
+    /// For example `orders.>` subsumes `orders.*.created.v1`, and every
+    /// pattern subsumes itself.
+    #[must_use]
+    pub fn subsumes(&self, other: &Pattern) -> bool {
+        Self::tokens_subsume(&self.tokens, &other.tokens)
+    }
+
+    /// Check if some subject matches both this pattern and `other`
+    ///
+    /// For example `orders.*.created.v1` overlaps `orders.order.>`, but
+    /// not `orders.*.deleted.v1`.
+    #[must_use]
+    pub fn overlaps(&self, other: &Pattern) -> bool {
+        Self::tokens_overlap(&self.tokens, &other.tokens)
+    }
+
+    /// Drop patterns already covered by another pattern in the set
+    ///
+    /// Keeps the first of any duplicates and preserves the input order.
+    #[must_use]
+    pub fn remove_subsumed(patterns: &[Pattern]) -> Vec<Pattern> {
+        patterns
+            .iter()
+            .enumerate()
+            .filter(|(i, pattern)| {
+                !patterns.iter().enumerate().any(|(j, other)| {
+                    j != *i && other.subsumes(pattern) && (!pattern.subsumes(other) || j < *i)
+                })
+            })
+            .map(|(_, pattern)| pattern.clone())
+            .collect()
+    }
+
+    fn tokens_overlap(a: &[Token], b: &[Token]) -> bool {
+        match (a.first(), b.first()) {
+            (None, None)
+            | (Some(Token::MultiWildcard), Some(_))
+            | (Some(_), Some(Token::MultiWildcard)) => true,
+            (None, _) | (_, None) => false,
+            (Some(Token::Literal(x)), Some(Token::Literal(y))) if x != y => false,
+            _ => Self::tokens_overlap(&a[1..], &b[1..]),
+        }
+    }
+
+    fn tokens_subsume(general: &[Token], specific: &[Token]) -> bool {
+        match (general.split_first(), specific.split_first()) {
+            // > needs at least one token, which every non-empty pattern has
+            (None, None) | (Some((Token::MultiWildcard, _)), Some(_)) => true,
+            // * covers exactly one token, so not another >
+            (Some((Token::SingleWildcard, rest)), Some((token, other_rest))) => {
+                !matches!(token, Token::MultiWildcard) && Self::tokens_subsume(rest, other_rest)
+            },
+            (Some((Token::Literal(a), rest)), Some((Token::Literal(b), other_rest))) => {
+                a == b && Self::tokens_subsume(rest, other_rest)
+            },
+            _ => false,
+        }
+    }
 }
 
 impl Display for Pattern {
@@ -220,6 +498,13 @@ impl FromStr for Pattern {
     }
 }
 
+impl From<&Subject> for Pattern {
+    fn from(subject: &Subject) -> Self {
+        let tokens: Vec<&str> = subject.as_str().split('.').collect();
+        Self::from_valid_tokens(&tokens)
+    }
+}
+
 /// A trait for types that can match patterns
 pub trait PatternMatcher {
     /// Check if this matches the given pattern
@@ -232,6 +517,12 @@ impl PatternMatcher for Subject {
     }
 }
 
+impl PatternMatcher for SubjectRef<'_> {
+    fn matches_pattern(&self, pattern: &Pattern) -> bool {
+        pattern.matches_ref(self)
+    }
+}
+
 impl PatternMatcher for str {
     fn matches_pattern(&self, pattern: &Pattern) -> bool {
         pattern.matches_str(self)
@@ -299,6 +590,32 @@ mod tests {
 
         // Invalid characters
         assert!(Pattern::new("people.per$on.*.v1").is_err());
+        assert!(Pattern::new("people.$on.*.v1").is_err());
+        assert!(Pattern::new("$.API.>").is_err());
+        assert!(Pattern::new("$$JS.API.>").is_err());
+    }
+
+    #[test]
+    fn test_system_subject_patterns() {
+        let api = Pattern::new("$JS.API.>").unwrap();
+        assert!(api.matches_str("$JS.API.STREAM.INFO.orders"));
+        assert!(!api.matches_str("JS.API.STREAM.INFO.orders"));
+        assert!(Pattern::new("$SYS.ACCOUNT.*.CONNECT").is_ok());
+        assert!(Pattern::new(">").unwrap().matches_str("$SYS.SERVER.PING"));
+    }
+
+    #[test]
+    fn test_error_spans() {
+        let err = Pattern::new("people.>.created.v1").unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidPattern);
+        assert_eq!(err.span(), Some(7..8));
+
+        let err = Pattern::new("people.per$on.*.v1").unwrap_err();
+        assert_eq!(err.span(), Some(10..11));
+        assert_eq!(err.input(), Some("people.per$on.*.v1"));
+
+        let err = Pattern::new("people..created").unwrap_err();
+        assert_eq!(err.span(), Some(7..7));
     }
 
     #[test]
@@ -314,6 +631,85 @@ mod tests {
         assert!(!p4.is_more_specific_than(&p1));
     }
 
+    #[test]
+    fn test_prefilter_agrees_with_tokenized_matching() {
+        let patterns = [
+            "orders.*.placed.v1",
+            "orders.>",
+            "*.order.>",
+            "orders.order",
+            ">",
+            "*",
+            "orders.*.v1",
+            "*.*.placed.v1",
+            "*.v1",
+        ];
+        let subjects = [
+            "orders.order.placed.v1",
+            "orders.order.placed.v1.extra",
+            "orders.order",
+            "orders",
+            "ordersx.order.placed.v1",
+            "billing.order.placed.v1",
+            "orders..placed.v1",
+            "orders.order.placed.",
+            "orders.",
+            ".order.x",
+            "",
+            "orders.v1",
+            "orders.x.v1",
+            ".v1",
+        ];
+
+        for pattern in patterns {
+            let pattern = Pattern::new(pattern).unwrap();
+            for subject in subjects {
+                let parts: Vec<&str> = subject.split('.').collect();
+                assert_eq!(
+                    pattern.matches_str(subject),
+                    Pattern::matches_tokens(&pattern.tokens, parts.into_iter()),
+                    "{pattern} vs {subject}"
+                );
+            }
+        }
+
+        // Deserialized patterns rebuild the prefilter
+        let pattern = Pattern::new("orders.*.placed.>").unwrap();
+        let json = serde_json::to_string(&pattern).unwrap();
+        let restored: Pattern = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, pattern);
+        assert!(restored.matches_str("orders.order.placed.v1"));
+    }
+
+    #[test]
+    fn test_subsumption() {
+        let p = |s: &str| Pattern::new(s).unwrap();
+
+        assert!(p("orders.>").subsumes(&p("orders.*.created.v1")));
+        assert!(p("orders.>").subsumes(&p("orders.order.>")));
+        assert!(p("orders.*.*.v1").subsumes(&p("orders.order.created.v1")));
+        assert!(p(">").subsumes(&p("*")));
+        assert!(p("orders.*").subsumes(&p("orders.*")));
+
+        assert!(!p("orders.*.created.v1").subsumes(&p("orders.>")));
+        assert!(!p("orders.*").subsumes(&p("orders.>")));
+        assert!(!p("orders.order.>").subsumes(&p("orders.*.created.v1")));
+        assert!(!p("orders.>").subsumes(&p("orders")));
+
+        assert!(p("orders.*.created.v1").overlaps(&p("orders.order.>")));
+        assert!(p("orders.*.created.v1").overlaps(&p("*.order.*.v1")));
+        assert!(!p("orders.*.created.v1").overlaps(&p("orders.*.deleted.v1")));
+        assert!(!p("orders.>").overlaps(&p("orders")));
+
+        let minimal = Pattern::remove_subsumed(&[
+            p("orders.order.created.v1"),
+            p("orders.>"),
+            p("billing.*.paid.v1"),
+            p("orders.>"),
+        ]);
+        assert_eq!(minimal, vec![p("orders.>"), p("billing.*.paid.v1")]);
+    }
+
     #[test]
     fn test_pattern_matcher_trait() {
         let pattern = Pattern::new("events.*.completed.>").unwrap();
@@ -322,5 +718,11 @@ mod tests {
         assert!(subject.matches_pattern(&pattern));
         assert!("events.task.completed.v2".matches_pattern(&pattern));
         assert!(String::from("events.job.completed.v1.final").matches_pattern(&pattern));
+        assert!(SubjectRef::new("events.saga.completed.v3")
+            .unwrap()
+            .matches_pattern(&pattern));
+        assert!(!SubjectRef::new("events.saga.failed.v3")
+            .unwrap()
+            .matches_pattern(&pattern));
     }
 }
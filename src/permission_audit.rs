@@ -0,0 +1,850 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Permission auditing: decision explanations and policy diffs
+//!
+//! [`Permissions::explain`] reports which rule decided a request and which
+//! rules were considered. [`Permissions::diff`] compares two policy versions
+//! and lists the subject/operation pairs whose decision changes, which is the
+//! question a security reviewer asks when a policy is edited.
+//! [`Translator::verify_permissions`] checks that a subject translation
+//! bridging two clusters never maps a subject allowed on the source side
+//! onto one denied on the target side. [`Permissions::simulate`] replays
+//! recorded traffic against a candidate policy and reports the requests
+//! whose decision would flip, before the change reaches production.
+
+use std::collections::{
+    BTreeSet,
+    HashMap,
+};
+use std::fmt::{
+    self,
+    Display,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::pattern::Pattern;
+use crate::permissions::{
+    meet_tokens,
+    subject_shaped,
+    EvaluationContext,
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+};
+use crate::subject::Subject;
+use crate::translator::Translator;
+
+/// Placeholder token used when instantiating wildcards into probe subjects
+const PROBE_TOKEN: &str = "_probe";
+
+/// Operations compared by [`Permissions::diff`]
+const AUDITED_OPERATIONS: [Operation; 4] = [
+    Operation::Publish,
+    Operation::Subscribe,
+    Operation::Request,
+    Operation::QueueSubscribe,
+];
+
+/// Explanation of a single permission decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    /// The subject that was checked
+    pub subject: Subject,
+    /// The operation that was checked
+    pub operation: Operation,
+    /// The final decision
+    pub allowed: bool,
+    /// The rule that decided, or `None` if the default policy applied
+    pub winning_rule: Option<PermissionRule>,
+    /// All matching rules that applied, most specific first
+    pub considered: Vec<PermissionRule>,
+    /// Matching rules that did not apply, and why
+    #[serde(default)]
+    pub inactive: Vec<InactiveRule>,
+    /// Whether the winning allow rule had exhausted its rate limit, so the
+    /// request was denied
+    #[serde(default)]
+    pub rate_limited: bool,
+}
+
+/// A rule whose pattern, operation and queue group match a request but
+/// which did not apply to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InactiveRule {
+    /// The rule
+    pub rule: PermissionRule,
+    /// Why it did not apply
+    pub reason: InactiveReason,
+}
+
+/// Why a matching rule did not apply to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InactiveReason {
+    /// The request came before the rule's validity window
+    NotYetValid,
+    /// The request came after the rule's validity window
+    Expired,
+    /// The rule's condition rejected the request
+    ConditionFailed,
+}
+
+impl Display for InactiveReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotYetValid => "not yet valid",
+            Self::Expired => "expired",
+            Self::ConditionFailed => "condition failed",
+        })
+    }
+}
+
+impl Explanation {
+    /// Check if the default policy decided
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.winning_rule.is_none()
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.allowed { "ALLOW" } else { "DENY" };
+        writeln!(f, "{verdict} {:?} {}", self.operation, self.subject)?;
+        match &self.winning_rule {
+            Some(rule) => writeln!(f, "  decided by: {}", describe_rule(rule))?,
+            None => writeln!(f, "  decided by: default policy")?,
+        }
+        if self.rate_limited {
+            writeln!(f, "  rate limit exhausted")?;
+        }
+        for rule in &self.considered {
+            writeln!(f, "  considered: {}", describe_rule(rule))?;
+        }
+        for inactive in &self.inactive {
+            writeln!(
+                f,
+                "  inactive ({}): {}",
+                inactive.reason,
+                describe_rule(&inactive.rule)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A subject/operation pair whose decision changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionChange {
+    /// The affected subject
+    pub subject: Subject,
+    /// The affected operation
+    pub operation: Operation,
+}
+
+/// Differences in decisions between two permission sets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionsDiff {
+    /// Requests denied before and allowed after
+    pub newly_allowed: Vec<PermissionChange>,
+    /// Requests allowed before and denied after
+    pub newly_denied: Vec<PermissionChange>,
+}
+
+impl PermissionsDiff {
+    /// Check if no decision changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.newly_allowed.is_empty() && self.newly_denied.is_empty()
+    }
+}
+
+impl Display for PermissionsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.newly_allowed {
+            writeln!(f, "+ {:?} {}", change.operation, change.subject)?;
+        }
+        for change in &self.newly_denied {
+            writeln!(f, "- {:?} {}", change.operation, change.subject)?;
+        }
+        Ok(())
+    }
+}
+
+/// One request from a traffic log
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TrafficRecord {
+    /// The requested subject
+    pub subject: Subject,
+    /// The requested operation
+    pub operation: Operation,
+    /// Who made the request
+    pub principal: String,
+    /// The queue group subscribed in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_group: Option<String>,
+}
+
+impl TrafficRecord {
+    /// A request outside any queue group
+    #[must_use]
+    pub fn new(subject: Subject, operation: Operation, principal: impl Into<String>) -> Self {
+        Self {
+            subject,
+            operation,
+            principal: principal.into(),
+            queue_group: None,
+        }
+    }
+}
+
+/// A distinct request whose decision flips, with how often it was seen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlippedRequest {
+    /// The request
+    pub request: TrafficRecord,
+    /// Number of times it occurs in the traffic
+    pub occurrences: usize,
+}
+
+/// Requests whose decision a candidate policy would change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// Number of requests replayed
+    pub requests: usize,
+    /// Requests denied today and allowed by the candidate, in first-seen
+    /// order
+    pub newly_allowed: Vec<FlippedRequest>,
+    /// Requests allowed today and denied by the candidate, in first-seen
+    /// order
+    pub newly_denied: Vec<FlippedRequest>,
+}
+
+impl SimulationReport {
+    /// Check if no replayed decision changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.newly_allowed.is_empty() && self.newly_denied.is_empty()
+    }
+
+    fn flips_mut(&mut self, newly_allowed: bool) -> &mut Vec<FlippedRequest> {
+        if newly_allowed {
+            &mut self.newly_allowed
+        } else {
+            &mut self.newly_denied
+        }
+    }
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (sign, flipped) in [("+", &self.newly_allowed), ("-", &self.newly_denied)] {
+            for flip in flipped {
+                let request = &flip.request;
+                writeln!(
+                    f,
+                    "{sign} {:?} {} by {} x{}",
+                    request.operation, request.subject, request.principal, flip.occurrences
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replay traffic through two decision functions and report the flips
+///
+/// Use this when decisions depend on the principal, e.g. with two
+/// [`Authorizer`](crate::authorization::Authorizer)s and a lookup from
+/// principal IDs to principals.
+pub fn simulate_with<'a>(
+    traffic: impl IntoIterator<Item = &'a TrafficRecord>,
+    current: impl Fn(&TrafficRecord) -> bool,
+    candidate: impl Fn(&TrafficRecord) -> bool,
+) -> SimulationReport {
+    let mut report = SimulationReport::default();
+    // Direction and list index of each distinct flipped request
+    let mut seen: HashMap<&TrafficRecord, (bool, usize)> = HashMap::new();
+    for record in traffic {
+        report.requests += 1;
+        let (allowed, index) = if let Some(&flip) = seen.get(record) {
+            flip
+        } else {
+            let now_allowed = match (current(record), candidate(record)) {
+                (false, true) => true,
+                (true, false) => false,
+                _ => continue,
+            };
+            let list = report.flips_mut(now_allowed);
+            list.push(FlippedRequest {
+                request: record.clone(),
+                occurrences: 0,
+            });
+            let flip = (now_allowed, list.len() - 1);
+            seen.insert(record, flip);
+            flip
+        };
+        report.flips_mut(allowed)[index].occurrences += 1;
+    }
+    report
+}
+
+/// Why a translated subject violates the target permissions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ViolationKind {
+    /// The translator failed on an allowed source subject
+    TranslationFailed(String),
+    /// The translated subject is denied on the target side
+    TargetDenied {
+        /// The translated subject
+        target: Subject,
+    },
+}
+
+/// A source subject whose translation breaks the target permissions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationViolation {
+    /// Subject allowed on the source side
+    pub source: Subject,
+    /// Operation being bridged
+    pub operation: Operation,
+    /// What went wrong
+    pub kind: ViolationKind,
+}
+
+/// Result of verifying a translator against two permission sets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationReport {
+    /// Number of allowed source subject/operation pairs checked
+    pub checked: usize,
+    /// Violations found
+    pub violations: Vec<TranslationViolation>,
+}
+
+impl TranslationReport {
+    /// Check if the translation preserves permissions
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Translator {
+    /// Verify that translation preserves permissions across a bridge
+    ///
+    /// Every subject allowed on the source side must translate to a
+    /// subject allowed on the target side. Subjects are probed from the
+    /// source rules and the translation rules' source patterns, with their
+    /// wildcard positions also filled from each target rule's tokens, and
+    /// from the target rules translated back where the translator can; so
+    /// translations landing on a target rule's boundary are checked too.
+    #[must_use]
+    pub fn verify_permissions(
+        &self,
+        source: &Permissions,
+        target: &Permissions,
+        operations: &[Operation],
+    ) -> TranslationReport {
+        let target_probes = probe_subjects(target.rules().iter());
+        let source_probes: Vec<String> = probe_subjects(source.rules().iter())
+            .iter()
+            .map(|subject| subject.as_str().to_string())
+            .chain(
+                self.named_rules()
+                    .iter()
+                    .filter_map(|(_, rule)| probe_subject(&rule.source_pattern)),
+            )
+            .collect();
+
+        let mut probes: BTreeSet<String> = source_probes.iter().cloned().collect();
+        for probe in &source_probes {
+            for target_probe in &target_probes {
+                let filled: Vec<&str> = probe
+                    .split('.')
+                    .zip(target_probe.as_str().split('.'))
+                    .map(|(own, other)| if own == PROBE_TOKEN { other } else { own })
+                    .collect();
+                probes.insert(filled.join("."));
+            }
+        }
+        probes.extend(
+            target_probes
+                .iter()
+                .filter_map(|translated| self.reverse_translate(translated).ok())
+                .map(|subject| subject.as_str().to_string()),
+        );
+        let subjects: Vec<Subject> = probes
+            .into_iter()
+            .filter_map(|probe| Subject::new(probe).ok())
+            .collect();
+        self.verify_permissions_on(source, target, operations, &subjects)
+    }
+
+    /// Verify that translation preserves permissions for given subjects
+    #[must_use]
+    pub fn verify_permissions_on(
+        &self,
+        source: &Permissions,
+        target: &Permissions,
+        operations: &[Operation],
+        subjects: &[Subject],
+    ) -> TranslationReport {
+        let mut report = TranslationReport::default();
+
+        for subject in subjects {
+            for &operation in operations {
+                if !source.is_allowed(subject, operation) {
+                    continue;
+                }
+                report.checked += 1;
+
+                let kind = match self.translate(subject) {
+                    Err(err) => ViolationKind::TranslationFailed(err.to_string()),
+                    Ok(translated) if !target.is_allowed(&translated, operation) => {
+                        ViolationKind::TargetDenied { target: translated }
+                    },
+                    Ok(_) => continue,
+                };
+
+                report.violations.push(TranslationViolation {
+                    source: subject.clone(),
+                    operation,
+                    kind,
+                });
+            }
+        }
+
+        report
+    }
+}
+
+impl Permissions {
+    /// Explain the decision for an operation on a subject made now
+    #[must_use]
+    pub fn explain(&self, subject: &Subject, operation: Operation) -> Explanation {
+        self.explain_with(subject, operation, &EvaluationContext::new())
+    }
+
+    /// Explain the decision [`is_allowed_with`](Self::is_allowed_with)
+    /// makes in an evaluation context
+    ///
+    /// Matching rules outside their validity window or whose condition
+    /// rejects the request are listed as inactive rather than considered.
+    /// Rate limits are checked but not charged.
+    #[must_use]
+    pub fn explain_with(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+    ) -> Explanation {
+        let (considered, inactive): (Vec<&PermissionRule>, Vec<&PermissionRule>) = self
+            .matching_rules(subject, operation, context.queue_group.as_deref())
+            .into_iter()
+            .partition(|rule| rule.is_active(subject, context));
+        let winner = self.winning_rule(&considered);
+        let rate_limited = winner.is_some_and(|rule| {
+            rule.policy == Policy::Allow && !rule.rate_permit(context.now, false)
+        });
+        let allowed = winner.map_or(self.default_policy() == Policy::Allow, |rule| {
+            rule.policy == Policy::Allow
+        }) && !rate_limited;
+
+        Explanation {
+            subject: subject.clone(),
+            operation,
+            allowed,
+            winning_rule: winner.cloned(),
+            considered: considered.into_iter().cloned().collect(),
+            inactive: inactive
+                .into_iter()
+                .map(|rule| InactiveRule {
+                    rule: rule.clone(),
+                    reason: inactive_reason(rule, context),
+                })
+                .collect(),
+            rate_limited,
+        }
+    }
+
+    /// Compare decisions against a newer permission set
+    ///
+    /// Probe subjects are derived from the patterns of both sets and from
+    /// the intersection of every pair of them, by instantiating wildcards,
+    /// so every rule boundary and every overlap of two rules is exercised.
+    #[must_use]
+    pub fn diff(&self, other: &Permissions) -> PermissionsDiff {
+        let subjects = probe_subjects(self.rules().iter().chain(other.rules()));
+        self.diff_subjects(other, &subjects)
+    }
+
+    /// Replay traffic against a candidate permission set and report the
+    /// requests whose decision would flip
+    ///
+    /// Every principal is judged by the same set. Validity windows and
+    /// conditions are evaluated as of now; rate limits are neither applied
+    /// nor charged, so replaying a log does not use up live grants.
+    #[must_use]
+    pub fn simulate<'a>(
+        &self,
+        candidate: &Permissions,
+        traffic: impl IntoIterator<Item = &'a TrafficRecord>,
+    ) -> SimulationReport {
+        simulate_with(
+            traffic,
+            |record| self.dry_run(record),
+            |record| candidate.dry_run(record),
+        )
+    }
+
+    /// The decision for a recorded request, without charging rate limits
+    fn dry_run(&self, record: &TrafficRecord) -> bool {
+        let context = EvaluationContext {
+            queue_group: record.queue_group.clone(),
+            ..EvaluationContext::new()
+        };
+        let mut matching = self.matching_rules(
+            &record.subject,
+            record.operation,
+            record.queue_group.as_deref(),
+        );
+        matching.retain(|rule| rule.is_active(&record.subject, &context));
+        self.winning_rule(&matching)
+            .map_or(self.default_policy() == Policy::Allow, |rule| {
+                rule.policy == Policy::Allow
+            })
+    }
+
+    /// Compare decisions against a newer permission set on given subjects
+    #[must_use]
+    pub fn diff_subjects(&self, other: &Permissions, subjects: &[Subject]) -> PermissionsDiff {
+        let mut diff = PermissionsDiff::default();
+
+        for subject in subjects {
+            for operation in AUDITED_OPERATIONS {
+                let before = self.is_allowed(subject, operation);
+                let after = other.is_allowed(subject, operation);
+                let change = PermissionChange {
+                    subject: subject.clone(),
+                    operation,
+                };
+                match (before, after) {
+                    (false, true) => diff.newly_allowed.push(change),
+                    (true, false) => diff.newly_denied.push(change),
+                    _ => {},
+                }
+            }
+        }
+
+        diff
+    }
+}
+
+/// Derive sorted, deduplicated probe subjects from rule patterns and the
+/// pairwise intersections of those patterns
+fn probe_subjects<'a>(rules: impl Iterator<Item = &'a PermissionRule>) -> Vec<Subject> {
+    let shapes: Vec<Vec<&str>> = rules
+        .filter_map(|rule| subject_shaped(&rule.pattern))
+        .collect();
+
+    let mut probes: BTreeSet<String> = shapes.iter().map(|shape| instantiate(shape)).collect();
+    for (i, a) in shapes.iter().enumerate() {
+        probes.extend(
+            shapes[i + 1..]
+                .iter()
+                .filter_map(|b| meet_tokens(a, b))
+                .map(|meet| instantiate(&meet)),
+        );
+    }
+
+    probes
+        .into_iter()
+        .filter_map(|probe| Subject::new(probe).ok())
+        .collect()
+}
+
+/// Instantiate a pattern into a four-token subject it matches
+fn probe_subject(pattern: &Pattern) -> Option<String> {
+    subject_shaped(pattern).map(|shape| instantiate(&shape))
+}
+
+/// Join subject-shaped tokens, filling wildcards with [`PROBE_TOKEN`]
+fn instantiate(shape: &[&str]) -> String {
+    shape
+        .iter()
+        .map(|token| if *token == "*" { PROBE_TOKEN } else { token })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Why a matching rule is not active for a request
+fn inactive_reason(rule: &PermissionRule, context: &EvaluationContext) -> InactiveReason {
+    if rule.not_before.is_some_and(|start| context.now < start) {
+        InactiveReason::NotYetValid
+    } else if rule.not_after.is_some_and(|end| context.now > end) {
+        InactiveReason::Expired
+    } else {
+        InactiveReason::ConditionFailed
+    }
+}
+
+/// Describe a rule on one line
+pub(crate) fn describe_rule(rule: &PermissionRule) -> String {
+    let mut operations: Vec<String> = rule.operations.iter().map(|op| format!("{op:?}")).collect();
+    operations.sort();
+
+    let line = format!(
+        "{:?} {} [{}]",
+        rule.policy,
+        rule.pattern,
+        operations.join(", ")
+    );
+    match &rule.description {
+        Some(description) => format!("{line} ({description})"),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{
+        Duration,
+        SystemTime,
+    };
+
+    use super::*;
+    use crate::permissions::PermissionsBuilder;
+    use crate::translator::TranslatorBuilder;
+
+    #[test]
+    fn test_explain_decision() {
+        let perms = PermissionsBuilder::new()
+            .allow("users.>", &[Operation::Subscribe])
+            .unwrap()
+            .deny("users.admin.>", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+
+        let admin = Subject::new("users.admin.created.v1").unwrap();
+        let explanation = perms.explain(&admin, Operation::Subscribe);
+
+        assert!(!explanation.allowed);
+        assert_eq!(explanation.considered.len(), 2);
+        assert_eq!(
+            explanation.winning_rule.unwrap().pattern.as_str(),
+            "users.admin.>"
+        );
+
+        let publish = perms.explain(&admin, Operation::Publish);
+        assert!(publish.is_default());
+        assert!(!publish.allowed);
+    }
+
+    #[test]
+    fn test_explain_inactive_rules() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let operations = || [Operation::Publish].into_iter().collect();
+        let mut perms = Permissions::new(Policy::Deny);
+        perms.add_rule(PermissionRule::allow(
+            Pattern::new("orders.>").unwrap(),
+            operations(),
+        ));
+        perms.add_rule(
+            PermissionRule::deny(Pattern::new("orders.order.>").unwrap(), operations())
+                .valid_from(start),
+        );
+        perms.add_rule(
+            PermissionRule::deny(Pattern::new("orders.order.placed.>").unwrap(), operations())
+                .with_condition(|_, ctx| ctx.attribute("tier") == Some("bronze")),
+        );
+        perms.add_rule(
+            PermissionRule::allow(
+                Pattern::new("orders.order.placed.v1").unwrap(),
+                operations(),
+            )
+            .valid_until(start)
+            .with_rate_limit(1, Duration::from_secs(60)),
+        );
+
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let before = EvaluationContext::new().at(start - Duration::from_secs(1));
+        let explanation = perms.explain_with(&placed, Operation::Publish, &before);
+        assert_eq!(
+            explanation.allowed,
+            perms.is_allowed_with(&placed, Operation::Publish, &before)
+        );
+        assert!(explanation.allowed);
+        assert_eq!(explanation.considered.len(), 2);
+        let reasons: Vec<InactiveReason> = explanation.inactive.iter().map(|i| i.reason).collect();
+        assert_eq!(reasons, [
+            InactiveReason::ConditionFailed,
+            InactiveReason::NotYetValid
+        ]);
+
+        // Past its window the narrow allow is inactive and the broad deny
+        // decides
+        let after = EvaluationContext::new().at(start + Duration::from_secs(1));
+        let explanation = perms.explain_with(&placed, Operation::Publish, &after);
+        assert!(!explanation.allowed);
+        assert_eq!(
+            explanation.winning_rule.unwrap().pattern.as_str(),
+            "orders.order.>"
+        );
+        assert!(explanation
+            .inactive
+            .iter()
+            .any(|i| i.reason == InactiveReason::Expired));
+
+        // An exhausted rate limit denies
+        assert!(perms.authorize(&placed, Operation::Publish, &before));
+        let explanation = perms.explain_with(&placed, Operation::Publish, &before);
+        assert!(!explanation.allowed);
+        assert!(explanation.rate_limited);
+        assert!(explanation.to_string().contains("inactive (not yet valid)"));
+    }
+
+    #[test]
+    fn test_policy_diff() {
+        let before = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        let after = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe])
+            .unwrap()
+            .deny("orders.internal.>", &[Operation::Subscribe])
+            .unwrap()
+            .allow("billing.invoice.*.v1", &[Operation::Publish])
+            .unwrap()
+            .build();
+
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.newly_denied.len(), 1);
+        assert_eq!(diff.newly_denied[0].operation, Operation::Subscribe);
+        assert_eq!(diff.newly_denied[0].subject.context(), "orders");
+        assert_eq!(diff.newly_denied[0].subject.aggregate(), "internal");
+
+        assert_eq!(diff.newly_allowed.len(), 1);
+        assert_eq!(diff.newly_allowed[0].operation, Operation::Publish);
+        assert!(before.diff(&before).is_empty());
+
+        // Only the overlap of two rules flips
+        let before = PermissionsBuilder::new()
+            .allow("orders.*.created.v1", &[Operation::Publish])
+            .unwrap()
+            .build();
+        let after = PermissionsBuilder::new()
+            .allow("orders.*.created.v1", &[Operation::Publish])
+            .unwrap()
+            .deny("orders.order.*.v1", &[Operation::Publish])
+            .unwrap()
+            .build();
+
+        let diff = before.diff(&after);
+        assert!(diff.newly_allowed.is_empty());
+        assert_eq!(diff.newly_denied.len(), 1);
+        assert_eq!(
+            diff.newly_denied[0].subject.as_str(),
+            "orders.order.created.v1"
+        );
+    }
+
+    #[test]
+    fn test_traffic_simulation() {
+        let current = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Publish, Operation::Subscribe])
+            .unwrap()
+            .build();
+        let candidate = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe])
+            .unwrap()
+            .allow("orders.order.*.v2", &[Operation::Publish])
+            .unwrap()
+            .allow("billing.>", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+
+        let record = |subject: &str, operation, principal| {
+            TrafficRecord::new(Subject::new(subject).unwrap(), operation, principal)
+        };
+        let traffic = vec![
+            record("orders.order.placed.v1", Operation::Publish, "alice"),
+            record("orders.order.placed.v2", Operation::Publish, "alice"),
+            record("orders.order.placed.v1", Operation::Publish, "alice"),
+            record("orders.order.placed.v1", Operation::Publish, "bob"),
+            record("billing.invoice.sent.v1", Operation::Subscribe, "carol"),
+            record("orders.order.placed.v1", Operation::Subscribe, "carol"),
+        ];
+
+        let report = current.simulate(&candidate, &traffic);
+        assert_eq!(report.requests, 6);
+        assert_eq!(report.newly_denied.len(), 2);
+        assert_eq!(report.newly_denied[0].request.principal, "alice");
+        assert_eq!(report.newly_denied[0].occurrences, 2);
+        assert_eq!(report.newly_denied[1].request.principal, "bob");
+        assert_eq!(report.newly_allowed.len(), 1);
+        assert_eq!(
+            report.to_string(),
+            "+ Subscribe billing.invoice.sent.v1 by carol x1\n\
+             - Publish orders.order.placed.v1 by alice x2\n\
+             - Publish orders.order.placed.v1 by bob x1\n"
+        );
+        assert!(current.simulate(&current, &traffic).is_empty());
+
+        // Per-principal decisions, e.g. from two authorizers
+        let report = simulate_with(&traffic, |_| true, |r| r.principal != "bob");
+        assert_eq!(report.newly_denied.len(), 1);
+        assert!(report.newly_allowed.is_empty());
+    }
+
+    #[test]
+    fn test_translation_guardrails() {
+        let internal = PermissionsBuilder::new()
+            .allow("internal.>", &[Operation::Publish])
+            .unwrap()
+            .build();
+        let external = PermissionsBuilder::new()
+            .allow("public.>", &[Operation::Publish])
+            .unwrap()
+            .deny("public.secrets.>", &[Operation::Publish])
+            .unwrap()
+            .build();
+        let translator = TranslatorBuilder::new()
+            .translate_context("internal", "public")
+            .unwrap()
+            .build();
+
+        let subjects = vec![
+            Subject::new("internal.orders.placed.v1").unwrap(),
+            Subject::new("internal.secrets.rotated.v1").unwrap(),
+            Subject::new("billing.invoice.sent.v1").unwrap(),
+        ];
+        let report = translator.verify_permissions_on(
+            &internal,
+            &external,
+            &[Operation::Publish],
+            &subjects,
+        );
+
+        assert_eq!(report.checked, 2); // billing is not allowed on the source side
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].source.aggregate(), "secrets");
+        assert!(matches!(
+            &report.violations[0].kind,
+            ViolationKind::TargetDenied { target } if target.context() == "public"
+        ));
+
+        // The denied target family is probed back through the translator
+        let report = translator.verify_permissions(&internal, &external, &[Operation::Publish]);
+        assert!(!report.is_ok());
+        assert!(report
+            .violations
+            .iter()
+            .all(|violation| violation.source.aggregate() == "secrets"));
+    }
+}
@@ -2,16 +2,36 @@
 
 //! Subject-based permissions and access control
 
-use std::collections::HashSet;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashSet,
+    VecDeque,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+};
 
 use serde::{
     Deserialize,
     Serialize,
 };
 
+use crate::conditions::Condition;
+use crate::correlation::{
+    MessageIdentity,
+    MessageKind,
+};
 use crate::error::Result;
+use crate::extended_pattern::ExtendedPattern;
 use crate::pattern::Pattern;
 use crate::subject::Subject;
+use crate::telemetry;
 
 /// Permissions for subject-based operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +40,12 @@ pub struct Permissions {
     rules: Vec<PermissionRule>,
     /// Default policy when no rules match
     default_policy: Policy,
+    /// How conflicts between matching rules are resolved
+    #[serde(default)]
+    resolution: ConflictResolution,
+    /// How subscriptions to patterns are judged
+    #[serde(default)]
+    subscription_check: SubscriptionCheck,
 }
 
 impl Default for Permissions {
@@ -35,9 +61,50 @@ impl Permissions {
         Self {
             rules: Vec::new(),
             default_policy,
+            resolution: ConflictResolution::default(),
+            subscription_check: SubscriptionCheck::default(),
         }
     }
 
+    /// Set how conflicts between matching rules are resolved
+    pub fn set_conflict_resolution(&mut self, resolution: ConflictResolution) {
+        self.resolution = resolution;
+    }
+
+    /// Get the conflict resolution strategy
+    #[must_use]
+    pub fn conflict_resolution(&self) -> ConflictResolution {
+        self.resolution
+    }
+
+    /// Set how subscriptions to patterns are judged
+    pub fn set_subscription_check(&mut self, check: SubscriptionCheck) {
+        self.subscription_check = check;
+    }
+
+    /// Get how subscriptions to patterns are judged
+    #[must_use]
+    pub fn subscription_check(&self) -> SubscriptionCheck {
+        self.subscription_check
+    }
+
+    /// Set the policy for requests no rule matches
+    pub fn set_default_policy(&mut self, policy: Policy) {
+        self.default_policy = policy;
+    }
+
+    /// Get the default policy
+    #[must_use]
+    pub fn default_policy(&self) -> Policy {
+        self.default_policy
+    }
+
+    /// Get the rules in this permission set
+    #[must_use]
+    pub fn rules(&self) -> &[PermissionRule] {
+        &self.rules
+    }
+
     /// Add a permission rule
     pub fn add_rule(&mut self, rule: PermissionRule) {
         self.rules.push(rule);
@@ -46,11 +113,95 @@ impl Permissions {
     /// Check if an operation is allowed on a subject
     #[must_use]
     pub fn is_allowed(&self, subject: &Subject, operation: Operation) -> bool {
-        // Collect all matching rules
+        self.is_allowed_in_queue(subject, operation, None)
+    }
+
+    /// Check if an operation is allowed on a subject within a queue group
+    ///
+    /// Rules restricted to queue groups only apply when `queue_group` is one
+    /// of their groups; unrestricted rules apply regardless of the group.
+    #[must_use]
+    pub fn is_allowed_in_queue(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        queue_group: Option<&str>,
+    ) -> bool {
+        let context = EvaluationContext {
+            queue_group: queue_group.map(str::to_string),
+            ..EvaluationContext::new()
+        };
+        self.is_allowed_with(subject, operation, &context)
+    }
+
+    /// Check if an operation is allowed on a subject in an evaluation
+    /// context
+    ///
+    /// Rules outside their validity window or whose condition rejects the
+    /// request do not match. An allow rule that wins after exhausting its
+    /// rate limit denies. The check is read-only: use
+    /// [`authorize`](Self::authorize) to count a grant against the limit.
+    #[must_use]
+    pub fn is_allowed_with(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+    ) -> bool {
+        self.decide(subject, operation, context, false)
+    }
+
+    /// Decide as [`is_allowed_with`](Self::is_allowed_with) and, if a
+    /// rate-limited rule grants, spend one of its permits
+    #[must_use]
+    pub fn authorize(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+    ) -> bool {
+        self.decide(subject, operation, context, true)
+    }
+
+    fn decide(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+        spend_permit: bool,
+    ) -> bool {
+        let matching = telemetry::time_pattern_match("permissions", || {
+            let mut matching =
+                self.matching_rules(subject, operation, context.queue_group.as_deref());
+            matching.retain(|rule| rule.is_active(subject, context));
+            matching
+        });
+        let winner = self.winning_rule(&matching);
+        let allowed = winner.map_or(self.default_policy == Policy::Allow, |rule| {
+            rule.policy == Policy::Allow && rule.rate_permit(context.now, spend_permit)
+        });
+        telemetry::record_permission_decision(
+            allowed,
+            winner.map(|rule| {
+                rule.description
+                    .as_deref()
+                    .unwrap_or_else(|| rule.pattern.as_str())
+            }),
+        );
+        allowed
+    }
+
+    /// Collect the rules matching a request, most specific first
+    pub(crate) fn matching_rules(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        queue_group: Option<&str>,
+    ) -> Vec<&PermissionRule> {
         let mut matching_rules: Vec<&PermissionRule> = self
             .rules
             .iter()
-            .filter(|rule| rule.matches(subject, operation))
+            .filter(|rule| rule.matches_in_queue(subject, operation, queue_group))
             .collect();
 
         // Sort by specificity (most specific first)
@@ -64,13 +215,100 @@ impl Permissions {
             }
         });
 
-        // Apply the most specific rule
-        if let Some(rule) = matching_rules.first() {
-            return rule.policy == Policy::Allow;
+        matching_rules
+    }
+
+    /// Select the deciding rule among sorted matching rules
+    pub(crate) fn winning_rule<'a>(
+        &self,
+        matching_rules: &[&'a PermissionRule],
+    ) -> Option<&'a PermissionRule> {
+        // A matching deny always wins under deny-overrides resolution
+        if self.resolution == ConflictResolution::DenyOverrides {
+            if let Some(deny) = matching_rules
+                .iter()
+                .find(|rule| rule.policy == Policy::Deny)
+            {
+                return Some(deny);
+            }
+        }
+
+        // Otherwise the most specific rule applies
+        matching_rules.first().copied()
+    }
+
+    /// Check if an operation is allowed on every subject a pattern can
+    /// match or, under [`SubscriptionCheck::Partial`], on at least one
+    ///
+    /// The decision is exact: the pattern is split into regions on which
+    /// every rule either always or never matches, and one subject from each
+    /// region is checked. A pattern that can match no four-token subject is
+    /// never allowed.
+    ///
+    /// Rules outside their validity window now do not match. Conditions
+    /// cannot be evaluated for a whole pattern, so they fail closed:
+    /// conditional allow rules never match and conditional deny rules
+    /// always do.
+    #[must_use]
+    pub fn is_subscription_allowed(&self, pattern: &Pattern, operation: Operation) -> bool {
+        let regions = self.subscription_regions(pattern, operation);
+        if regions.is_empty() {
+            return false;
+        }
+        let now = SystemTime::now();
+        let mut decisions = regions.into_iter().map(|subject| {
+            let mut matching = self.matching_rules(&subject, operation, None);
+            matching.retain(|rule| {
+                rule.is_valid_at(now) && (rule.condition.is_none() || rule.policy == Policy::Deny)
+            });
+            self.winning_rule(&matching)
+                .map_or(self.default_policy == Policy::Allow, |rule| {
+                    rule.policy == Policy::Allow && rule.rate_permit(now, false)
+                })
+        });
+        match self.subscription_check {
+            SubscriptionCheck::Strict => decisions.all(|allowed| allowed),
+            SubscriptionCheck::Partial => decisions.any(|allowed| allowed),
+        }
+    }
+
+    /// One subject from each region of `pattern` that the rules for
+    /// `operation` treat alike
+    fn subscription_regions(&self, pattern: &Pattern, operation: Operation) -> Vec<Subject> {
+        let Some(pattern) = subject_shaped(pattern) else {
+            return Vec::new();
+        };
+        let rules: Vec<Vec<&str>> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.operations.contains(&operation))
+            .filter_map(|rule| subject_shaped(&rule.pattern))
+            .collect();
+
+        // Intersect with each rule in turn, so every combination of rules
+        // matching some subject of the pattern gets its own region
+        let mut regions = BTreeSet::from([pattern]);
+        for rule in &rules {
+            let met: Vec<Vec<&str>> = regions
+                .iter()
+                .filter_map(|region| meet_tokens(region, rule))
+                .collect();
+            regions.extend(met);
         }
 
-        // No rule matched, use default policy
-        self.default_policy == Policy::Allow
+        // A token no rule names stands for every token the rules ignore
+        let longest = rules.iter().flatten().map(|t| t.len()).max().unwrap_or(0);
+        let fresh = "_".repeat(longest + 1);
+        regions
+            .iter()
+            .filter_map(|region| {
+                let tokens: Vec<&str> = region
+                    .iter()
+                    .map(|token| if *token == "*" { fresh.as_str() } else { token })
+                    .collect();
+                Subject::new(tokens.join(".")).ok()
+            })
+            .collect()
     }
 
     /// Check if publishing to a subject is allowed
@@ -91,6 +329,34 @@ impl Permissions {
         self.is_allowed(subject, Operation::Request)
     }
 
+    /// Check if subscribing to a subject as a member of a queue group is
+    /// allowed
+    #[must_use]
+    pub fn can_queue_subscribe(&self, subject: &Subject, queue_group: &str) -> bool {
+        self.is_allowed_in_queue(subject, Operation::QueueSubscribe, Some(queue_group))
+    }
+
+    /// Check if an operation is allowed for a principal, honoring subject
+    /// ownership
+    ///
+    /// Publishing or requesting on a subject owned by another principal is
+    /// denied regardless of the rules, as a request publishes to it.
+    #[must_use]
+    pub fn is_allowed_as(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        principal: &str,
+        ownership: &SubjectOwnership,
+    ) -> bool {
+        if matches!(operation, Operation::Publish | Operation::Request)
+            && !ownership.may_publish(subject, principal)
+        {
+            return false;
+        }
+        self.is_allowed(subject, operation)
+    }
+
     /// Get all allowed subjects for an operation from a list
     #[must_use]
     pub fn filter_allowed(&self, subjects: &[Subject], operation: Operation) -> Vec<Subject> {
@@ -164,6 +430,33 @@ pub struct PermissionRule {
     pub policy: Policy,
     /// Optional description
     pub description: Option<String>,
+    /// Queue groups this rule is restricted to (`None` applies to all)
+    #[serde(default)]
+    pub queue_groups: Option<HashSet<String>>,
+    /// The rule does not apply before this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<SystemTime>,
+    /// The rule does not apply after this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<SystemTime>,
+    /// Limit on how often an allow rule grants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Predicate the request must satisfy
+    ///
+    /// Only its presence is serialized. A restored allow rule never applies
+    /// and a restored deny rule always does, until a condition is attached
+    /// again.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "condition_marker"
+    )]
+    pub condition: Option<RuleCondition>,
+    /// Recent grants counted against the rate limit; each clone counts its
+    /// own
+    #[serde(skip)]
+    grants: RateUsage,
 }
 
 impl PermissionRule {
@@ -175,6 +468,12 @@ impl PermissionRule {
             operations,
             policy,
             description: None,
+            queue_groups: None,
+            not_before: None,
+            not_after: None,
+            rate_limit: None,
+            condition: None,
+            grants: RateUsage::default(),
         }
     }
 
@@ -197,10 +496,299 @@ impl PermissionRule {
         self
     }
 
+    /// Restrict this rule to specific queue groups
+    #[must_use]
+    pub fn with_queue_groups<I, S>(mut self, groups: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.queue_groups = Some(groups.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only apply the rule from `time` on
+    #[must_use]
+    pub fn valid_from(mut self, time: SystemTime) -> Self {
+        self.not_before = Some(time);
+        self
+    }
+
+    /// Only apply the rule up to `time`
+    #[must_use]
+    pub fn valid_until(mut self, time: SystemTime) -> Self {
+        self.not_after = Some(time);
+        self
+    }
+
+    /// Grant at most `max` requests per `window`; only affects allow rules
+    #[must_use]
+    pub fn with_rate_limit(mut self, max: u32, window: Duration) -> Self {
+        self.rate_limit = Some(RateLimit { max, window });
+        self
+    }
+
+    /// Only apply the rule to requests satisfying `condition`
+    #[must_use]
+    pub fn with_condition<F>(mut self, condition: F) -> Self
+    where F: Fn(&Subject, &EvaluationContext) -> bool + Send + Sync + 'static {
+        self.condition = Some(RuleCondition(Some(Arc::new(condition))));
+        self
+    }
+
+    /// Only apply the rule to requests satisfying a [`Condition`] object
+    #[must_use]
+    pub fn with_shared_condition(mut self, condition: Arc<dyn Condition>) -> Self {
+        self.condition = Some(RuleCondition(Some(condition)));
+        self
+    }
+
+    /// Whether the rule has a validity window, rate limit or condition,
+    /// which only [`Permissions::is_allowed_with`] and the checks built on
+    /// it evaluate
+    #[must_use]
+    pub fn is_conditional(&self) -> bool {
+        self.not_before.is_some()
+            || self.not_after.is_some()
+            || self.rate_limit.is_some()
+            || self.condition.is_some()
+    }
+
+    /// Whether the validity window and condition admit a request
+    #[must_use]
+    pub fn is_active(&self, subject: &Subject, context: &EvaluationContext) -> bool {
+        self.is_valid_at(context.now)
+            && self
+                .condition
+                .as_ref()
+                .map_or(true, |condition| match &condition.0 {
+                    Some(condition) => condition.evaluate(subject, context),
+                    // Lost in serialization, so fail closed
+                    None => self.policy == Policy::Deny,
+                })
+    }
+
+    /// Whether `now` is within the validity window
+    fn is_valid_at(&self, now: SystemTime) -> bool {
+        self.not_before.map_or(true, |start| now >= start)
+            && self.not_after.map_or(true, |end| now <= end)
+    }
+
+    /// Check if the rate limit has a permit left at `now`, recording a grant
+    /// if `spend` is set
+    pub(crate) fn rate_permit(&self, now: SystemTime, spend: bool) -> bool {
+        let Some(limit) = self.rate_limit else {
+            return true;
+        };
+        let Ok(mut grants) = self.grants.0.lock() else {
+            return false;
+        };
+        if let Some(window_start) = now.checked_sub(limit.window) {
+            while grants.front().is_some_and(|grant| *grant <= window_start) {
+                grants.pop_front();
+            }
+        }
+        if grants.len() >= limit.max as usize {
+            return false;
+        }
+        if spend {
+            grants.push_back(now);
+        }
+        true
+    }
+
     /// Check if this rule matches a subject and operation
     #[must_use]
     pub fn matches(&self, subject: &Subject, operation: Operation) -> bool {
-        self.pattern.matches(subject) && self.operations.contains(&operation)
+        self.matches_in_queue(subject, operation, None)
+    }
+
+    /// Check if this rule matches a subject and operation within a queue
+    /// group
+    #[must_use]
+    pub fn matches_in_queue(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        queue_group: Option<&str>,
+    ) -> bool {
+        self.applies_to_queue_group(queue_group)
+            && self.pattern.matches(subject)
+            && self.operations.contains(&operation)
+    }
+
+    /// Check if this rule applies to a subscription in the given queue group
+    pub(crate) fn applies_to_queue_group(&self, queue_group: Option<&str>) -> bool {
+        match (&self.queue_groups, queue_group) {
+            (None, _) => true,
+            (Some(groups), Some(group)) => groups.contains(group),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// At most `max` grants per sliding `window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Grants allowed per window
+    pub max: u32,
+    /// Length of the sliding window
+    pub window: Duration,
+}
+
+/// A predicate deciding whether a rule applies to a request
+///
+/// Empty when the rule was deserialized, as conditions are code.
+#[derive(Debug, Clone)]
+pub struct RuleCondition(Option<Arc<dyn Condition>>);
+
+/// Serializes only whether a rule has a condition
+mod condition_marker {
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+
+    use super::RuleCondition;
+
+    #[allow(clippy::ref_option)]
+    pub(super) fn serialize<S: Serializer>(
+        condition: &Option<RuleCondition>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(condition.is_some())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<RuleCondition>, D::Error> {
+        Ok(bool::deserialize(deserializer)?.then_some(RuleCondition(None)))
+    }
+}
+
+/// Times of recent grants under a rate limit
+#[derive(Debug, Default)]
+struct RateUsage(Mutex<VecDeque<SystemTime>>);
+
+impl Clone for RateUsage {
+    fn clone(&self) -> Self {
+        let grants = self
+            .0
+            .lock()
+            .map(|grants| grants.clone())
+            .unwrap_or_default();
+        Self(Mutex::new(grants))
+    }
+}
+
+/// What a request is evaluated against besides its subject and operation
+#[derive(Debug, Clone)]
+pub struct EvaluationContext {
+    /// When the request is made
+    pub now: SystemTime,
+    /// The queue group subscribed in, if any
+    pub queue_group: Option<String>,
+    /// Facts about the request for rule conditions, such as a principal's
+    /// tier
+    pub attributes: BTreeMap<String, String>,
+    /// Headers of the message being published, if any
+    pub headers: Vec<(String, String)>,
+    /// Identity of the message being published, if known
+    pub identity: Option<MessageIdentity>,
+    /// Kind of the message that started the correlation chain, if known
+    pub root_kind: Option<MessageKind>,
+    /// Payload size in bytes, if known
+    pub payload_size: Option<usize>,
+}
+
+impl Default for EvaluationContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvaluationContext {
+    /// A context for a request made now, outside any queue group
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            now: SystemTime::now(),
+            queue_group: None,
+            attributes: BTreeMap::new(),
+            headers: Vec::new(),
+            identity: None,
+            root_kind: None,
+            payload_size: None,
+        }
+    }
+
+    /// Evaluate as of `time`
+    #[must_use]
+    pub fn at(mut self, time: SystemTime) -> Self {
+        self.now = time;
+        self
+    }
+
+    /// Evaluate within a queue group
+    #[must_use]
+    pub fn in_queue(mut self, group: impl Into<String>) -> Self {
+        self.queue_group = Some(group.into());
+        self
+    }
+
+    /// Add an attribute
+    #[must_use]
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// The value of an attribute
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Add a message header
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The first value of a message header, compared case-insensitively
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Record the message's identity, and its kind as the root kind when the
+    /// message starts its correlation chain
+    #[must_use]
+    pub fn with_identity(mut self, identity: MessageIdentity) -> Self {
+        if identity.is_root() {
+            self.root_kind = self.root_kind.or(identity.kind);
+        }
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Record the kind of the message that started the correlation chain
+    #[must_use]
+    pub fn with_root_kind(mut self, kind: MessageKind) -> Self {
+        self.root_kind = Some(kind);
+        self
+    }
+
+    /// Record the payload size in bytes
+    #[must_use]
+    pub fn with_payload_size(mut self, size: usize) -> Self {
+        self.payload_size = Some(size);
+        self
     }
 }
 
@@ -213,6 +801,8 @@ pub enum Operation {
     Subscribe,
     /// Make request-reply calls on a subject
     Request,
+    /// Subscribe as a member of a queue group
+    QueueSubscribe,
     /// All operations
     All,
 }
@@ -225,10 +815,63 @@ impl Operation {
         ops.insert(Operation::Publish);
         ops.insert(Operation::Subscribe);
         ops.insert(Operation::Request);
+        ops.insert(Operation::QueueSubscribe);
         ops
     }
 }
 
+/// Ownership of subject families by principals
+///
+/// Only the owner of a subject may publish or send requests to it, e.g. a
+/// bounded context's service owns the events of that context.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubjectOwnership {
+    /// Owned patterns and their owners
+    owners: Vec<(Pattern, String)>,
+}
+
+impl SubjectOwnership {
+    /// Create an empty ownership registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign ownership of a pattern to a principal
+    pub fn assign(&mut self, pattern: Pattern, owner: impl Into<String>) {
+        self.owners.push((pattern, owner.into()));
+    }
+
+    /// Assign ownership of a whole bounded context to a principal
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the context is not a valid pattern token
+    pub fn assign_context(&mut self, context: &str, owner: impl Into<String>) -> Result<()> {
+        self.assign(Pattern::new(format!("{context}.>"))?, owner);
+        Ok(())
+    }
+
+    /// Get the owner of a subject (the most specific owning pattern wins)
+    #[must_use]
+    pub fn owner_of(&self, subject: &Subject) -> Option<&str> {
+        self.owners
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(subject))
+            .min_by_key(|(pattern, _)| pattern.specificity_key())
+            .map(|(_, owner)| owner.as_str())
+    }
+
+    /// Check if a principal may publish to a subject
+    ///
+    /// Unowned subjects may be published by anyone.
+    #[must_use]
+    pub fn may_publish(&self, subject: &Subject, principal: &str) -> bool {
+        self.owner_of(subject)
+            .map_or(true, |owner| owner == principal)
+    }
+}
+
 /// Permission policy
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Policy {
@@ -238,11 +881,61 @@ pub enum Policy {
     Deny,
 }
 
+/// Strategy for resolving conflicts when several rules match a subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// The most specific matching rule decides
+    #[default]
+    MostSpecific,
+    /// Any matching deny rule wins, as in the NATS server
+    DenyOverrides,
+}
+
+/// How [`Permissions::is_subscription_allowed`] judges a pattern that
+/// matches both allowed and denied subjects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SubscriptionCheck {
+    /// Every subject the pattern can match must be allowed
+    #[default]
+    Strict,
+    /// Some subject the pattern can match must be allowed, leaving the
+    /// rest to be filtered on delivery
+    Partial,
+}
+
+/// The four-token form of a pattern as tokens of literals and `*`, or
+/// `None` if it can match no subject
+pub(crate) fn subject_shaped(pattern: &Pattern) -> Option<Vec<&str>> {
+    const SUBJECT_TOKENS: usize = 4;
+    let mut tokens: Vec<&str> = pattern.as_str().split('.').collect();
+    if tokens.last() == Some(&">") {
+        tokens.pop();
+        if tokens.len() >= SUBJECT_TOKENS {
+            return None;
+        }
+        tokens.resize(SUBJECT_TOKENS, "*");
+    }
+    (tokens.len() == SUBJECT_TOKENS).then_some(tokens)
+}
+
+/// Intersect two subject-shaped patterns
+pub(crate) fn meet_tokens<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<&'a str>> {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| match (*x, *y) {
+            ("*", token) | (token, "*") => Some(token),
+            (x, y) => (x == y).then_some(x),
+        })
+        .collect()
+}
+
 /// Builder for permissions
 #[derive(Debug, Default)]
 pub struct PermissionsBuilder {
     rules: Vec<PermissionRule>,
     default_policy: Option<Policy>,
+    resolution: ConflictResolution,
+    subscription_check: SubscriptionCheck,
 }
 
 impl PermissionsBuilder {
@@ -259,6 +952,20 @@ impl PermissionsBuilder {
         self
     }
 
+    /// Set the conflict resolution strategy
+    #[must_use]
+    pub fn conflict_resolution(mut self, resolution: ConflictResolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Set how subscriptions to patterns are judged
+    #[must_use]
+    pub fn subscription_check(mut self, check: SubscriptionCheck) -> Self {
+        self.subscription_check = check;
+        self
+    }
+
     /// Allow a pattern for specific operations
     ///
     /// # Errors
@@ -283,6 +990,36 @@ impl PermissionsBuilder {
         Ok(self)
     }
 
+    /// Allow an [`ExtendedPattern`] such as `orders.*.created.v{>=2,<4}`
+    /// for specific operations
+    ///
+    /// The pattern becomes one rule per NATS pattern it expands to. A
+    /// pattern that cannot be expanded, such as `v{>=2}`, becomes a single
+    /// [conditional](PermissionRule::is_conditional) rule on its
+    /// [subscription pattern](ExtendedPattern::subscription_pattern) that
+    /// matches the pattern directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid
+    pub fn allow_extended(mut self, pattern: &str, operations: &[Operation]) -> Result<Self> {
+        self.rules
+            .extend(extended_rules(pattern, operations, Policy::Allow)?);
+        Ok(self)
+    }
+
+    /// Deny an [`ExtendedPattern`] for specific operations, as
+    /// [`allow_extended`](Self::allow_extended) allows one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid
+    pub fn deny_extended(mut self, pattern: &str, operations: &[Operation]) -> Result<Self> {
+        self.rules
+            .extend(extended_rules(pattern, operations, Policy::Deny)?);
+        Ok(self)
+    }
+
     /// Allow all operations on a pattern
     ///
     /// # Errors
@@ -293,6 +1030,7 @@ impl PermissionsBuilder {
             Operation::Publish,
             Operation::Subscribe,
             Operation::Request,
+            Operation::QueueSubscribe,
         ])
     }
 
@@ -306,19 +1044,58 @@ impl PermissionsBuilder {
             Operation::Publish,
             Operation::Subscribe,
             Operation::Request,
+            Operation::QueueSubscribe,
         ])
     }
 
+    /// Allow queue subscriptions on a pattern only within the given groups
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid
+    pub fn allow_queue(mut self, pattern: &str, queue_groups: &[&str]) -> Result<Self> {
+        let pattern = Pattern::new(pattern)?;
+        let ops: HashSet<_> = [Operation::QueueSubscribe].into_iter().collect();
+        self.rules.push(
+            PermissionRule::allow(pattern, ops).with_queue_groups(queue_groups.iter().copied()),
+        );
+        Ok(self)
+    }
+
     /// Build the permissions
     #[must_use]
     pub fn build(self) -> Permissions {
         let default_policy = self.default_policy.unwrap_or(Policy::Deny);
         let mut perms = Permissions::new(default_policy);
         perms.rules = self.rules;
+        perms.resolution = self.resolution;
+        perms.subscription_check = self.subscription_check;
         perms
     }
 }
 
+/// Rules equivalent to an extended pattern: one per NATS pattern it
+/// expands to, or one matching it directly
+fn extended_rules(
+    pattern: &str,
+    operations: &[Operation],
+    policy: Policy,
+) -> Result<Vec<PermissionRule>> {
+    let extended = ExtendedPattern::new(pattern)?;
+    let ops: HashSet<_> = operations.iter().copied().collect();
+    if let Ok(patterns) = extended.expand() {
+        return Ok(patterns
+            .into_iter()
+            .map(|pattern| PermissionRule::new(pattern, ops.clone(), policy))
+            .collect());
+    }
+    let broad = extended.subscription_pattern();
+    Ok(vec![PermissionRule::new(broad, ops, policy)
+        .with_condition(
+            move |subject: &Subject, _: &EvaluationContext| extended.matches(subject),
+        )])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,6 +1131,86 @@ mod tests {
         assert!(perms.can_subscribe(&subject)); // Default allow
     }
 
+    #[test]
+    fn test_deny_overrides_resolution() {
+        let perms = PermissionsBuilder::new()
+            .conflict_resolution(ConflictResolution::DenyOverrides)
+            .deny("users.>", &[Operation::Subscribe])
+            .unwrap()
+            .allow("users.person.>", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+
+        let subject = Subject::new("users.person.created.v1").unwrap();
+        assert!(!perms.can_subscribe(&subject)); // Broader deny still wins
+    }
+
+    #[test]
+    fn test_queue_group_restrictions() {
+        let perms = PermissionsBuilder::new()
+            .allow_queue("orders.>", &["orders-workers"])
+            .unwrap()
+            .build();
+
+        let subject = Subject::new("orders.order.placed.v1").unwrap();
+
+        assert!(perms.can_queue_subscribe(&subject, "orders-workers"));
+        assert!(!perms.can_queue_subscribe(&subject, "rogue-group"));
+        assert!(!perms.can_subscribe(&subject)); // Plain subscription not granted
+        assert!(!perms.is_allowed(&subject, Operation::QueueSubscribe)); // Group required
+    }
+
+    #[test]
+    fn test_version_range_rules() {
+        let perms = PermissionsBuilder::new()
+            .allow_extended("orders.order.created.v{>=2,<4}", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        assert_eq!(perms.rules().len(), 2);
+        for (version, allowed) in [("v1", false), ("v2", true), ("v3", true), ("v4", false)] {
+            let subject = Subject::new(format!("orders.order.created.{version}")).unwrap();
+            assert_eq!(perms.can_subscribe(&subject), allowed, "{version}");
+        }
+
+        // Open ranges are matched directly
+        let perms = PermissionsBuilder::new()
+            .default_policy(Policy::Allow)
+            .deny_extended("orders.*.created.v{>=2}", &[Operation::Publish])
+            .unwrap()
+            .build();
+        assert_eq!(perms.rules().len(), 1);
+        for (version, allowed) in [("v1", true), ("v2", false), ("v40", false)] {
+            let subject = Subject::new(format!("orders.order.created.{version}")).unwrap();
+            assert_eq!(perms.can_publish(&subject), allowed, "{version}");
+        }
+        assert!(PermissionsBuilder::new()
+            .allow_extended("orders.*.created.v{>=2", &[Operation::Publish])
+            .is_err());
+    }
+
+    #[test]
+    fn test_subject_ownership() {
+        let perms = PermissionsBuilder::new()
+            .default_policy(Policy::Allow)
+            .build();
+        let mut ownership = SubjectOwnership::new();
+        ownership.assign_context("orders", "order-service").unwrap();
+        ownership.assign(Pattern::new("orders.audit.>").unwrap(), "audit-service");
+
+        let placed = Subject::new("orders.order.placed.v1").unwrap();
+        let audit = Subject::new("orders.audit.recorded.v1").unwrap();
+
+        assert_eq!(ownership.owner_of(&audit), Some("audit-service"));
+        assert!(perms.is_allowed_as(&placed, Operation::Publish, "order-service", &ownership));
+        assert!(!perms.is_allowed_as(&placed, Operation::Publish, "billing", &ownership));
+        assert!(!perms.is_allowed_as(&audit, Operation::Publish, "order-service", &ownership));
+        // A request publishes, so it needs ownership too
+        assert!(perms.is_allowed_as(&placed, Operation::Request, "order-service", &ownership));
+        assert!(!perms.is_allowed_as(&placed, Operation::Request, "billing", &ownership));
+        // Ownership does not restrict subscriptions
+        assert!(perms.is_allowed_as(&placed, Operation::Subscribe, "billing", &ownership));
+    }
+
     #[test]
     fn test_permission_ordering() {
         let perms = PermissionsBuilder::new()
@@ -417,4 +1274,149 @@ mod tests {
         assert!(!intersection.can_subscribe(&user_admin)); // Only in perms1
         assert!(!intersection.can_subscribe(&order)); // Only in perms1
     }
+
+    #[test]
+    fn test_subscription_patterns() {
+        let perms = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe])
+            .unwrap()
+            .deny("orders.*.deleted.*", &[Operation::Subscribe])
+            .unwrap()
+            .allow("orders.audit.deleted.v1", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        let allowed = |p: &str| {
+            perms.is_subscription_allowed(&Pattern::new(p).unwrap(), Operation::Subscribe)
+        };
+
+        assert!(allowed("orders.*.created.>"));
+        assert!(allowed("orders.audit.deleted.v1"));
+        // Some subject under these is denied
+        assert!(!allowed("orders.>"));
+        assert!(!allowed("orders.audit.deleted.*"));
+        assert!(!allowed(">"));
+        assert!(!allowed("billing.>"));
+        // Matches no four-token subject
+        assert!(!allowed("orders.a.b.c.>"));
+        assert!(!allowed("orders.*"));
+
+        let mut partial = perms.clone();
+        partial.set_subscription_check(SubscriptionCheck::Partial);
+        let allowed = |p: &str| {
+            partial.is_subscription_allowed(&Pattern::new(p).unwrap(), Operation::Subscribe)
+        };
+        assert!(allowed("orders.>"));
+        assert!(allowed(">"));
+        assert!(!allowed("orders.*.deleted.v2"));
+        assert!(!allowed("billing.>"));
+    }
+
+    #[test]
+    fn test_subscription_patterns_with_conditional_rules() {
+        let allowed = |perms: &Permissions, p: &str| {
+            perms.is_subscription_allowed(&Pattern::new(p).unwrap(), Operation::Subscribe)
+        };
+
+        // An expired allow no longer grants
+        let mut expired = Permissions::new(Policy::Deny);
+        expired.add_rule(
+            PermissionRule::allow(
+                Pattern::new("orders.>").unwrap(),
+                [Operation::Subscribe].into_iter().collect(),
+            )
+            .valid_until(SystemTime::UNIX_EPOCH + Duration::from_secs(1)),
+        );
+        assert!(!allowed(&expired, "orders.*.created.*"));
+
+        // A conditional allow cannot vouch for the whole pattern
+        let ranged = PermissionsBuilder::new()
+            .allow_extended("orders.*.created.v{>=2}", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        assert!(!allowed(&ranged, "orders.*.created.*"));
+        assert!(!allowed(&ranged, "orders.*.created.v2"));
+
+        // A conditional deny always applies
+        let denied = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe])
+            .unwrap()
+            .deny_extended("orders.*.created.v{>=2}", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        assert!(!allowed(&denied, "orders.*.created.v1"));
+        assert!(allowed(&denied, "orders.*.shipped.v1"));
+    }
+
+    #[test]
+    fn test_conditional_rules() {
+        let hour = Duration::from_secs(3600);
+        let monday = SystemTime::UNIX_EPOCH + Duration::from_secs(4 * 24 * 3600);
+        let business_hours = |_: &Subject, ctx: &EvaluationContext| {
+            let secs = ctx
+                .now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            (9..17).contains(&(secs / 3600 % 24))
+        };
+
+        let mut perms = Permissions::new(Policy::Deny);
+        perms.add_rule(
+            PermissionRule::allow(
+                Pattern::new("loans.application.submit.>").unwrap(),
+                [Operation::Publish].into_iter().collect(),
+            )
+            .with_condition(move |subject, ctx| {
+                ctx.attribute("tier") != Some("bronze") || business_hours(subject, ctx)
+            })
+            .with_rate_limit(2, hour)
+            .valid_until(monday + 30 * 24 * hour),
+        );
+
+        let submit = Subject::new("loans.application.submit.v1").unwrap();
+        let bronze = |time| {
+            EvaluationContext::new()
+                .at(time)
+                .with_attribute("tier", "bronze")
+        };
+        // Bronze brokers only during business hours
+        assert!(!perms.is_allowed_with(&submit, Operation::Publish, &bronze(monday + 20 * hour)));
+        assert!(perms.is_allowed_with(&submit, Operation::Publish, &bronze(monday + 10 * hour)));
+        assert!(perms.is_allowed_with(&submit, Operation::Publish, &bronze(monday + 11 * hour)));
+        // Two grants per sliding hour, spent only by authorize
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let at = |m| bronze(monday + 11 * hour + minutes(m));
+        assert!(perms.authorize(&submit, Operation::Publish, &at(0)));
+        assert!(perms.authorize(&submit, Operation::Publish, &at(30)));
+        assert!(!perms.authorize(&submit, Operation::Publish, &at(40)));
+        assert!(!perms.is_allowed_with(&submit, Operation::Publish, &at(40)));
+        assert!(perms.is_allowed_with(&submit, Operation::Publish, &at(61)));
+        // A clone counts its own grants
+        let copy = perms.clone();
+        assert!(copy.authorize(&submit, Operation::Publish, &at(61)));
+        assert!(!copy.authorize(&submit, Operation::Publish, &at(62)));
+        assert!(perms.authorize(&submit, Operation::Publish, &at(62)));
+        // Past the validity window
+        let gold = EvaluationContext::new()
+            .at(monday + 31 * 24 * hour)
+            .with_attribute("tier", "gold");
+        assert!(!perms.is_allowed_with(&submit, Operation::Publish, &gold));
+
+        let rule = &perms.rules()[0];
+        assert!(rule.is_conditional());
+        let json = serde_json::to_string(rule).unwrap();
+        let restored: PermissionRule = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.rate_limit, rule.rate_limit);
+        // The condition is lost, so the allow no longer applies
+        assert!(restored.condition.is_some());
+        assert!(!restored.is_active(&submit, &bronze(monday + 10 * hour)));
+        let deny = PermissionRule::deny(
+            Pattern::new("loans.>").unwrap(),
+            [Operation::Publish].into_iter().collect(),
+        )
+        .with_condition(|_, _| false);
+        let restored: PermissionRule =
+            serde_json::from_str(&serde_json::to_string(&deny).unwrap()).unwrap();
+        assert!(restored.is_active(&submit, &bronze(monday)));
+    }
 }
@@ -0,0 +1,460 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! A text format for permission sets
+//!
+//! Policies are statements separated by newlines or `;`, with `#` starting
+//! a comment:
+//!
+//! ```text
+//! default deny
+//! resolution deny-overrides        # or most-specific
+//! subscriptions partial            # or strict
+//! allow pub,sub orders.commands.>
+//! allow qsub work.> queues billing,shipping
+//! allow req pricing.quote.*.* rate 10/60s "quotes are expensive"
+//! deny all security.keys.> ; deny sub audit.*.*.* from 1700000000 until 1800000000
+//! ```
+//!
+//! Operations are `pub`, `sub`, `req`, `qsub` or `all`. After the pattern
+//! a rule may list queue groups, a rate limit of grants per window in `s`
+//! or `ms`, a validity window in Unix seconds, and a quoted description.
+//! [`render`] writes a permission set back out; rules with a
+//! [condition](crate::permissions::PermissionRule::condition) have no text
+//! form.
+//!
+//! ```
+//! use cim_subject::permissions::Operation;
+//! use cim_subject::{
+//!     policy,
+//!     Subject,
+//! };
+//!
+//! let permissions = policy::parse("allow pub orders.commands.> ; deny sub security.keys.>")?;
+//! let subject = Subject::new("orders.commands.order.place")?;
+//! assert!(permissions.is_allowed(&subject, Operation::Publish));
+//! assert_eq!(
+//!     policy::parse(&policy::render(&permissions)?)?.rules().len(),
+//!     2
+//! );
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    ConflictResolution,
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+    SubscriptionCheck,
+};
+
+/// Operation keywords in the order they are rendered
+const OPERATIONS: [(&str, Operation); 4] = [
+    ("pub", Operation::Publish),
+    ("sub", Operation::Subscribe),
+    ("req", Operation::Request),
+    ("qsub", Operation::QueueSubscribe),
+];
+
+/// Parse a policy into a permission set
+///
+/// Without a `default` statement, unmatched requests are denied.
+///
+/// # Errors
+///
+/// Returns a parse error naming the line of the first invalid statement
+pub fn parse(source: &str) -> Result<Permissions> {
+    let mut permissions = Permissions::new(Policy::Deny);
+    for (index, line) in source.lines().enumerate() {
+        for statement in statements(line) {
+            parse_statement(&statement, &mut permissions)
+                .map_err(|e| located(&format!("line {}", index + 1), e))?;
+        }
+    }
+    Ok(permissions)
+}
+
+/// Read and parse a policy file
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or does not parse
+pub fn load(path: impl AsRef<Path>) -> Result<Permissions> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| SubjectError::validation_error(format!("{}: {e}", path.display())))?;
+    parse(&source).map_err(|e| located(&path.display().to_string(), e))
+}
+
+/// Render a permission set as a policy that parses back to the same rules
+///
+/// # Errors
+///
+/// Returns an error if a rule has a condition or its description contains
+/// a `"`, neither of which the format can express
+pub fn render(permissions: &Permissions) -> Result<String> {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "default {}",
+        policy_keyword(permissions.default_policy())
+    );
+    if permissions.conflict_resolution() == ConflictResolution::DenyOverrides {
+        out.push_str("resolution deny-overrides\n");
+    }
+    if permissions.subscription_check() == SubscriptionCheck::Partial {
+        out.push_str("subscriptions partial\n");
+    }
+    for rule in permissions.rules() {
+        out.push_str(&render_rule(rule)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn render_rule(rule: &PermissionRule) -> Result<String> {
+    if rule.condition.is_some() {
+        return Err(SubjectError::validation_error(format!(
+            "Rule for '{}' has a condition, which a policy cannot express",
+            rule.pattern
+        )));
+    }
+    let mut out = format!(
+        "{} {} {}",
+        policy_keyword(rule.policy),
+        render_operations(&rule.operations)?,
+        rule.pattern
+    );
+    if let Some(groups) = &rule.queue_groups {
+        let mut groups: Vec<&str> = groups.iter().map(String::as_str).collect();
+        groups.sort_unstable();
+        let _ = write!(out, " queues {}", groups.join(","));
+    }
+    if let Some(limit) = rule.rate_limit {
+        let window = if limit.window.subsec_millis() == 0 {
+            format!("{}s", limit.window.as_secs())
+        } else {
+            format!("{}ms", limit.window.as_millis())
+        };
+        let _ = write!(out, " rate {}/{window}", limit.max);
+    }
+    for (keyword, time) in [("from", rule.not_before), ("until", rule.not_after)] {
+        if let Some(time) = time {
+            let _ = write!(out, " {keyword} {}", unix_seconds(time, &rule.pattern)?);
+        }
+    }
+    if let Some(description) = &rule.description {
+        if description.contains('"') {
+            return Err(SubjectError::validation_error(format!(
+                "Description of rule for '{}' contains '\"'",
+                rule.pattern
+            )));
+        }
+        let _ = write!(out, " \"{description}\"");
+    }
+    Ok(out)
+}
+
+fn render_operations(operations: &HashSet<Operation>) -> Result<String> {
+    if operations.contains(&Operation::All) {
+        return Err(SubjectError::validation_error(
+            "Operation::All has no policy keyword; use all four operations",
+        ));
+    }
+    if *operations == Operation::all_operations() {
+        return Ok("all".to_string());
+    }
+    let keywords: Vec<&str> = OPERATIONS
+        .iter()
+        .filter(|(_, op)| operations.contains(op))
+        .map(|(keyword, _)| *keyword)
+        .collect();
+    Ok(keywords.join(","))
+}
+
+fn unix_seconds(time: SystemTime, pattern: &Pattern) -> Result<u64> {
+    let since = time.duration_since(UNIX_EPOCH).map_err(|_| {
+        SubjectError::validation_error(format!("Validity of rule for '{pattern}' predates 1970"))
+    })?;
+    if since.subsec_nanos() != 0 {
+        return Err(SubjectError::validation_error(format!(
+            "Validity of rule for '{pattern}' is not in whole seconds"
+        )));
+    }
+    Ok(since.as_secs())
+}
+
+fn policy_keyword(policy: Policy) -> &'static str {
+    match policy {
+        Policy::Allow => "allow",
+        Policy::Deny => "deny",
+    }
+}
+
+/// Split a line into statements at `;`, dropping a trailing `#` comment and
+/// leaving quoted text intact
+fn statements(line: &str) -> Vec<String> {
+    let mut statements = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => break,
+            ';' if !quoted => {
+                statements.push(String::new());
+                continue;
+            },
+            _ => {},
+        }
+        statements.last_mut().expect("never empty").push(c);
+    }
+    statements
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn parse_statement(statement: &str, permissions: &mut Permissions) -> Result<()> {
+    let (body, description) = match statement.split_once('"') {
+        Some((body, rest)) => {
+            let description = rest
+                .strip_suffix('"')
+                .filter(|d| !d.contains('"'))
+                .ok_or_else(|| {
+                    SubjectError::parse_error("a description must be one quoted string at the end")
+                })?;
+            (body, Some(description.to_string()))
+        },
+        None => (statement, None),
+    };
+    let words: Vec<&str> = body.split_whitespace().collect();
+
+    match words.first().copied() {
+        Some("default") if description.is_none() => {
+            let policy = match setting(&words)? {
+                "allow" => Policy::Allow,
+                "deny" => Policy::Deny,
+                other => return Err(unknown("default policy", other)),
+            };
+            permissions.set_default_policy(policy);
+        },
+        Some("resolution") if description.is_none() => {
+            permissions.set_conflict_resolution(match setting(&words)? {
+                "most-specific" => ConflictResolution::MostSpecific,
+                "deny-overrides" => ConflictResolution::DenyOverrides,
+                other => return Err(unknown("resolution", other)),
+            });
+        },
+        Some("subscriptions") if description.is_none() => {
+            permissions.set_subscription_check(match setting(&words)? {
+                "strict" => SubscriptionCheck::Strict,
+                "partial" => SubscriptionCheck::Partial,
+                other => return Err(unknown("subscription check", other)),
+            });
+        },
+        Some("allow") => permissions.add_rule(parse_rule(Policy::Allow, &words[1..], description)?),
+        Some("deny") => permissions.add_rule(parse_rule(Policy::Deny, &words[1..], description)?),
+        _ => {
+            return Err(SubjectError::parse_error(format!(
+                "unknown statement '{body}'"
+            )))
+        },
+    }
+    Ok(())
+}
+
+/// The value of a `<setting> <value>` statement
+fn setting<'a>(words: &[&'a str]) -> Result<&'a str> {
+    match words {
+        [_, value] => Ok(value),
+        _ => Err(SubjectError::parse_error(format!(
+            "'{}' takes one value",
+            words[0]
+        ))),
+    }
+}
+
+fn parse_rule(
+    policy: Policy,
+    words: &[&str],
+    description: Option<String>,
+) -> Result<PermissionRule> {
+    let [operations, pattern, options @ ..] = words else {
+        return Err(SubjectError::parse_error(
+            "a rule needs operations and a pattern",
+        ));
+    };
+    let mut rule = PermissionRule::new(
+        Pattern::new(*pattern)?,
+        parse_operations(operations)?,
+        policy,
+    );
+    rule.description = description;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| SubjectError::parse_error(format!("'{option}' needs a value")))?;
+        match *option {
+            "queues" => rule = rule.with_queue_groups(value.split(',')),
+            "rate" => {
+                let (max, window) = value
+                    .split_once('/')
+                    .and_then(|(max, window)| Some((max.parse().ok()?, parse_window(window)?)))
+                    .ok_or_else(|| {
+                        SubjectError::parse_error(format!(
+                            "rate '{value}' is not <max>/<window>, e.g. 10/60s"
+                        ))
+                    })?;
+                rule = rule.with_rate_limit(max, window);
+            },
+            "from" | "until" => {
+                let time = value
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| UNIX_EPOCH.checked_add(Duration::from_secs(seconds)))
+                    .ok_or_else(|| {
+                        SubjectError::parse_error(format!(
+                            "'{value}' is not a Unix time in seconds"
+                        ))
+                    })?;
+                rule = if *option == "from" {
+                    rule.valid_from(time)
+                } else {
+                    rule.valid_until(time)
+                };
+            },
+            other => return Err(unknown("rule option", other)),
+        }
+    }
+    Ok(rule)
+}
+
+fn parse_operations(list: &str) -> Result<HashSet<Operation>> {
+    let mut operations = HashSet::new();
+    for keyword in list.split(',') {
+        if keyword == "all" {
+            operations.extend(Operation::all_operations());
+            continue;
+        }
+        let (_, operation) = OPERATIONS
+            .iter()
+            .find(|(name, _)| *name == keyword)
+            .ok_or_else(|| unknown("operation", keyword))?;
+        operations.insert(*operation);
+    }
+    Ok(operations)
+}
+
+fn parse_window(window: &str) -> Option<Duration> {
+    if let Some(millis) = window.strip_suffix("ms") {
+        millis.parse().ok().map(Duration::from_millis)
+    } else {
+        window
+            .strip_suffix('s')?
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+    }
+}
+
+/// A parse error prefixed with where it occurred
+fn located(location: &str, error: SubjectError) -> SubjectError {
+    match error {
+        SubjectError::ParseError(message) => {
+            SubjectError::parse_error(format!("{location}: {message}"))
+        },
+        other => SubjectError::parse_error(format!("{location}: {other}")),
+    }
+}
+
+fn unknown(what: &str, value: &str) -> SubjectError {
+    SubjectError::parse_error(format!("unknown {what} '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subject::Subject;
+
+    const POLICY: &str = r#"
+        # Order service
+        default deny
+        resolution deny-overrides
+        allow pub,sub orders.commands.>
+        allow qsub work.> queues shipping,billing
+        allow req pricing.quote.*.* rate 10/1500ms "quotes; expensive"
+        deny all security.keys.> ; deny sub audit.*.*.* from 1700000000 until 1800000000
+    "#;
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let permissions = parse(POLICY).unwrap();
+        assert_eq!(permissions.rules().len(), 5);
+        assert_eq!(
+            permissions.conflict_resolution(),
+            ConflictResolution::DenyOverrides
+        );
+        let place = Subject::new("orders.commands.order.place").unwrap();
+        assert!(permissions.is_allowed(&place, Operation::Subscribe));
+        assert!(!permissions.is_allowed(&place, Operation::Request));
+        let quote = &permissions.rules()[2];
+        assert_eq!(quote.description.as_deref(), Some("quotes; expensive"));
+        assert_eq!(
+            quote.rate_limit.unwrap().window,
+            Duration::from_millis(1500)
+        );
+
+        let rendered = render(&permissions).unwrap();
+        assert_eq!(
+            rendered,
+            "default deny\n\
+             resolution deny-overrides\n\
+             allow pub,sub orders.commands.>\n\
+             allow qsub work.> queues billing,shipping\n\
+             allow req pricing.quote.*.* rate 10/1500ms \"quotes; expensive\"\n\
+             deny all security.keys.>\n\
+             deny sub audit.*.*.* from 1700000000 until 1800000000\n"
+        );
+        assert_eq!(render(&parse(&rendered).unwrap()).unwrap(), rendered);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("allow pub orders.>\nallow publish orders.>").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("line 2: unknown operation 'publish'"));
+        assert!(parse("allow pub").is_err());
+        assert!(parse("allow pub orders.> rate 10").is_err());
+        assert!(parse("default maybe").is_err());
+        assert!(parse("allow pub orders.> \"unterminated").is_err());
+        assert!(parse(&format!("allow pub orders.> until {}", u64::MAX)).is_err());
+
+        let mut permissions = Permissions::new(Policy::Deny);
+        permissions.add_rule(
+            PermissionRule::allow(
+                Pattern::new("orders.>").unwrap(),
+                Operation::all_operations(),
+            )
+            .with_condition(|_, _| true),
+        );
+        assert!(render(&permissions).is_err());
+    }
+}
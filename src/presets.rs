@@ -0,0 +1,290 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Well-known subject grammars for commands, events and queries
+//!
+//! Most services name subjects after the kind of message they carry, in one
+//! of two conventions: `orders.commands.order.create`, with the message
+//! type after the context, or `cmd.order.create.v1`, with it first. A
+//! [`MessageTypePreset`] bundles a parser that enforces the convention,
+//! the patterns for each message type, and permission templates for the
+//! service handling a context and for its clients.
+//!
+//! ```
+//! use cim_subject::permissions::Operation;
+//! use cim_subject::presets::{
+//!     MessageType,
+//!     MessageTypePreset,
+//! };
+//! use cim_subject::Subject;
+//!
+//! let cqrs = MessageTypePreset::cqrs();
+//! let create = cqrs.parser().parse("orders.commands.order.create")?;
+//! assert_eq!(cqrs.message_type(&create), Some(MessageType::Command));
+//! assert!(cqrs.parser().parse("orders.order.created.v1").is_err());
+//! assert_eq!(
+//!     cqrs.pattern(MessageType::Event, None).as_str(),
+//!     "*.events.>"
+//! );
+//!
+//! let service = cqrs.handler_permissions("orders")?;
+//! assert!(service.is_allowed(&create, Operation::Subscribe));
+//! let created = Subject::new("orders.events.order.created")?;
+//! assert!(service.is_allowed(&created, Operation::Publish));
+//! assert!(!service.is_allowed(&create, Operation::Publish));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::parser::{
+    SubjectParser,
+    ValidationRule,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    Operation,
+    PermissionRule,
+    Permissions,
+    Policy,
+};
+use crate::subject::{
+    Subject,
+    SubjectParts,
+};
+
+/// The kind of message a subject carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageType {
+    /// A request to change state, handled by one service
+    Command,
+    /// A fact that state changed, published by its owner
+    Event,
+    /// A request to read state, answered by one service
+    Query,
+}
+
+impl MessageType {
+    /// Every message type
+    pub const ALL: [Self; 3] = [Self::Command, Self::Event, Self::Query];
+}
+
+/// A subject convention naming the message type in a fixed token
+#[derive(Clone)]
+pub struct MessageTypePreset {
+    position: usize,
+    tokens: [String; 3],
+    parser: SubjectParser,
+}
+
+impl MessageTypePreset {
+    /// `<context>.commands|events|queries.<aggregate>.<name>`, e.g.
+    /// `orders.commands.order.create`
+    #[must_use]
+    pub fn cqrs() -> Self {
+        Self::new(1, ["commands", "events", "queries"])
+    }
+
+    /// `cmd|evt|qry.<aggregate>.<name>.<version>`, e.g.
+    /// `cmd.order.create.v1`
+    #[must_use]
+    pub fn prefixed() -> Self {
+        Self::new(0, ["cmd", "evt", "qry"])
+    }
+
+    fn new(position: usize, tokens: [&str; 3]) -> Self {
+        let tokens = tokens.map(str::to_string);
+        let allowed = tokens.clone();
+        let parser = SubjectParser::new();
+        parser.register_validator(
+            "message_type",
+            ValidationRule::new(
+                "Message Type",
+                Arc::new(move |parts| {
+                    let token = token_at(parts, position);
+                    if allowed.iter().any(|allowed| allowed == token) {
+                        return Ok(());
+                    }
+                    Err(SubjectError::validation_error(format!(
+                        "Message type '{token}' is not one of {}",
+                        allowed.join(", ")
+                    )))
+                }),
+            ),
+        );
+        Self {
+            position,
+            tokens,
+            parser,
+        }
+    }
+
+    /// A parser that rejects subjects without a known message type
+    #[must_use]
+    pub fn parser(&self) -> &SubjectParser {
+        &self.parser
+    }
+
+    /// The token naming `message_type`
+    #[must_use]
+    pub fn token(&self, message_type: MessageType) -> &str {
+        &self.tokens[message_type as usize]
+    }
+
+    /// The message type of a subject, if it follows the convention
+    #[must_use]
+    pub fn message_type(&self, subject: &Subject) -> Option<MessageType> {
+        let token = subject.as_str().split('.').nth(self.position)?;
+        MessageType::ALL
+            .into_iter()
+            .find(|&message_type| self.token(message_type) == token)
+    }
+
+    /// The pattern for `message_type`, within `owner` if given
+    ///
+    /// The owner is the other of the first two tokens: the context for
+    /// [`cqrs`](Self::cqrs), the aggregate for [`prefixed`](Self::prefixed).
+    /// It must be a valid subject token.
+    #[must_use]
+    pub fn pattern(&self, message_type: MessageType, owner: Option<&str>) -> Pattern {
+        let owner = owner.unwrap_or("*");
+        let message_type = self.token(message_type);
+        let tokens = if self.position == 0 {
+            [message_type, owner, ">"]
+        } else {
+            [owner, message_type, ">"]
+        };
+        Pattern::from_valid_tokens(&tokens)
+    }
+
+    /// The patterns for every message type
+    #[must_use]
+    pub fn patterns(&self) -> Vec<Pattern> {
+        MessageType::ALL
+            .into_iter()
+            .map(|message_type| self.pattern(message_type, None))
+            .collect()
+    }
+
+    /// Deny-by-default permissions for the service owning `owner`: handle
+    /// its commands and queries, publish its events, and follow every
+    /// event
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `owner` is not a valid subject token
+    pub fn handler_permissions(&self, owner: &str) -> Result<Permissions> {
+        validate_owner(owner)?;
+        let handle = [Operation::Subscribe, Operation::QueueSubscribe];
+        Ok(permissions([
+            (self.pattern(MessageType::Command, Some(owner)), &handle[..]),
+            (self.pattern(MessageType::Query, Some(owner)), &handle),
+            (self.pattern(MessageType::Event, Some(owner)), &[
+                Operation::Publish,
+            ]),
+            (self.pattern(MessageType::Event, None), &[
+                Operation::Subscribe,
+            ]),
+        ]))
+    }
+
+    /// Deny-by-default permissions for a client of the service owning
+    /// `owner`: send it commands and queries, and follow its events
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `owner` is not a valid subject token
+    pub fn client_permissions(&self, owner: &str) -> Result<Permissions> {
+        validate_owner(owner)?;
+        let send = [Operation::Publish, Operation::Request];
+        Ok(permissions([
+            (self.pattern(MessageType::Command, Some(owner)), &send[..]),
+            (self.pattern(MessageType::Query, Some(owner)), &send),
+            (self.pattern(MessageType::Event, Some(owner)), &[
+                Operation::Subscribe,
+            ]),
+        ]))
+    }
+}
+
+fn token_at(parts: &SubjectParts, position: usize) -> &str {
+    if position == 0 {
+        &parts.context
+    } else {
+        &parts.aggregate
+    }
+}
+
+fn permissions<const N: usize>(rules: [(Pattern, &[Operation]); N]) -> Permissions {
+    let mut permissions = Permissions::new(Policy::Deny);
+    for (pattern, operations) in rules {
+        let operations: HashSet<Operation> = operations.iter().copied().collect();
+        permissions.add_rule(PermissionRule::allow(pattern, operations));
+    }
+    permissions
+}
+
+fn validate_owner(owner: &str) -> Result<()> {
+    if owner.is_empty()
+        || !owner
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(SubjectError::validation_error(format!(
+            "Owner '{owner}' is empty or contains invalid characters"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let prefixed = MessageTypePreset::prefixed();
+        let get = prefixed.parser().parse("qry.order.get.v1").unwrap();
+        assert_eq!(prefixed.message_type(&get), Some(MessageType::Query));
+        assert!(prefixed.parser().parse("orders.order.get.v1").is_err());
+        assert_eq!(
+            prefixed
+                .pattern(MessageType::Command, Some("order"))
+                .as_str(),
+            "cmd.order.>"
+        );
+
+        let cqrs = MessageTypePreset::cqrs();
+        let patterns = cqrs.patterns();
+        let patterns: Vec<&str> = patterns.iter().map(Pattern::as_str).collect();
+        assert_eq!(patterns, ["*.commands.>", "*.events.>", "*.queries.>"]);
+        let created = Subject::new("orders.events.order.created").unwrap();
+        assert_eq!(cqrs.message_type(&created), Some(MessageType::Event));
+        assert_eq!(prefixed.message_type(&created), None);
+    }
+
+    #[test]
+    fn test_permission_templates() {
+        let cqrs = MessageTypePreset::cqrs();
+        assert!(cqrs.handler_permissions("orders.x").is_err());
+
+        let client = cqrs.client_permissions("orders").unwrap();
+        let create = Subject::new("orders.commands.order.create").unwrap();
+        let check = Subject::new("inventory.queries.stock.check").unwrap();
+        let created = Subject::new("orders.events.order.created").unwrap();
+        assert!(client.is_allowed(&create, Operation::Publish));
+        assert!(!client.is_allowed(&check, Operation::Request));
+        assert!(client.is_allowed(&created, Operation::Subscribe));
+        assert!(!client.is_allowed(&created, Operation::Publish));
+
+        // Handlers follow every context's events but only publish their own
+        let inventory = cqrs.handler_permissions("inventory").unwrap();
+        assert!(inventory.is_allowed(&check, Operation::QueueSubscribe));
+        assert!(inventory.is_allowed(&created, Operation::Subscribe));
+        assert!(!inventory.is_allowed(&created, Operation::Publish));
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Python bindings for data and operations tooling
+//!
+//! With the `python` feature, `PyO3` exports a `cim_subject` module with
+//! `Subject`, `Pattern`, `Permissions` and `CorrelationChain` classes, so
+//! notebooks analyzing causation chains or auditing permissions apply the
+//! exact rules the services do. Build a wheel with maturin, adding
+//! `pyo3/extension-module` to the features.
+//!
+//! Permission sets and message identities cross the boundary as the JSON
+//! their serde form produces, and operations are named like their variants:
+//! `"Publish"`, `"Subscribe"`, `"Request"`, `"QueueSubscribe"` or `"All"`.
+//!
+//! ```python
+//! from cim_subject import Pattern, Permissions, Subject
+//!
+//! subject = Subject("orders.order.placed.v1")
+//! assert Pattern("orders.*.placed.>").matches(subject)
+//!
+//! permissions = Permissions.from_json(open("permissions.json").read())
+//! print(permissions.explain(subject, "Publish"))
+//! ```
+
+use std::fmt::Display;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::{
+    pyclass,
+    pymethods,
+    pymodule,
+    Bound,
+    FromPyObject,
+    PyModule,
+    PyModuleMethods,
+    PyResult,
+};
+use pyo3::PyErr;
+
+use crate::correlation::{
+    IdType,
+    MessageIdentity,
+};
+use crate::message_algebra::CorrelationChain;
+use crate::pattern::Pattern;
+use crate::permissions::{
+    Operation,
+    Permissions,
+};
+use crate::subject::Subject;
+
+/// A validated subject
+#[pyclass(name = "Subject", module = "cim_subject", frozen, eq, hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PySubject {
+    inner: Subject,
+}
+
+#[pymethods]
+impl PySubject {
+    /// Parse and validate a subject, raising `ValueError` if it is invalid
+    #[new]
+    fn new(subject: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: Subject::new(subject).map_err(value_error)?,
+        })
+    }
+
+    /// Whether a string is a valid subject
+    #[staticmethod]
+    fn is_valid(subject: &str) -> bool {
+        Subject::new(subject).is_ok()
+    }
+
+    #[getter]
+    fn context(&self) -> &str {
+        self.inner.context()
+    }
+
+    #[getter]
+    fn aggregate(&self) -> &str {
+        self.inner.aggregate()
+    }
+
+    #[getter]
+    fn event_type(&self) -> &str {
+        self.inner.event_type()
+    }
+
+    #[getter]
+    fn version(&self) -> &str {
+        self.inner.version()
+    }
+
+    fn __str__(&self) -> &str {
+        self.inner.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Subject('{}')", self.inner)
+    }
+}
+
+/// A subject or subject string argument
+#[derive(FromPyObject)]
+enum SubjectArg {
+    Subject(PySubject),
+    Str(String),
+}
+
+impl SubjectArg {
+    fn into_subject(self) -> PyResult<Subject> {
+        match self {
+            SubjectArg::Subject(subject) => Ok(subject.inner),
+            SubjectArg::Str(subject) => Subject::new(subject).map_err(value_error),
+        }
+    }
+}
+
+/// A validated subject pattern
+#[pyclass(name = "Pattern", module = "cim_subject", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyPattern {
+    inner: Pattern,
+}
+
+#[pymethods]
+impl PyPattern {
+    /// Parse and validate a pattern, raising `ValueError` if it is invalid
+    #[new]
+    fn new(pattern: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: Pattern::new(pattern).map_err(value_error)?,
+        })
+    }
+
+    /// Whether the pattern matches a `Subject` or subject string
+    fn matches(&self, subject: SubjectArg) -> PyResult<bool> {
+        Ok(self.inner.matches(&subject.into_subject()?))
+    }
+
+    fn __str__(&self) -> &str {
+        self.inner.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pattern('{}')", self.inner)
+    }
+}
+
+/// A permission set
+#[pyclass(name = "Permissions", module = "cim_subject", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyPermissions {
+    inner: Permissions,
+}
+
+#[pymethods]
+impl PyPermissions {
+    /// Load a permission set from its JSON form
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: serde_json::from_str(json).map_err(value_error)?,
+        })
+    }
+
+    /// The permission set as JSON
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(value_error)
+    }
+
+    /// Whether an operation is allowed on a subject
+    fn is_allowed(&self, subject: SubjectArg, operation: &str) -> PyResult<bool> {
+        Ok(self
+            .inner
+            .is_allowed(&subject.into_subject()?, parse_operation(operation)?))
+    }
+
+    /// Which rules decided a request, and why
+    fn explain(&self, subject: SubjectArg, operation: &str) -> PyResult<String> {
+        Ok(self
+            .inner
+            .explain(&subject.into_subject()?, parse_operation(operation)?)
+            .to_string())
+    }
+}
+
+/// A causation chain of messages sharing a correlation ID
+#[pyclass(name = "CorrelationChain", module = "cim_subject")]
+#[derive(Debug, Clone)]
+pub struct PyCorrelationChain {
+    inner: CorrelationChain,
+}
+
+#[pymethods]
+impl PyCorrelationChain {
+    /// Build a chain from a JSON array of message identities, root first
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let identities: Vec<MessageIdentity> = serde_json::from_str(json).map_err(value_error)?;
+        let mut identities = identities.into_iter();
+        let root = identities
+            .next()
+            .ok_or_else(|| PyValueError::new_err("a chain needs a root message"))?;
+        let mut inner = CorrelationChain::new(root).map_err(value_error)?;
+        for identity in identities {
+            inner.add_message(identity).map_err(value_error)?;
+        }
+        Ok(Self { inner })
+    }
+
+    /// Add a message given as a JSON message identity
+    fn add_json(&mut self, json: &str) -> PyResult<()> {
+        let identity = serde_json::from_str(json).map_err(value_error)?;
+        self.inner.add_message(identity).map_err(value_error)
+    }
+
+    /// Longest distance from the root
+    fn depth(&self) -> usize {
+        self.inner.depth()
+    }
+
+    /// IDs of messages that caused nothing
+    fn leaves(&self) -> Vec<String> {
+        self.inner
+            .leaves()
+            .iter()
+            .map(|identity| identity.message_id.to_string())
+            .collect()
+    }
+
+    /// IDs from the root to a message
+    fn path_to(&self, message_id: &str) -> PyResult<Vec<String>> {
+        let message_id: IdType = message_id.parse().map_err(value_error)?;
+        Ok(self
+            .inner
+            .get_path_to(&message_id)
+            .map_err(value_error)?
+            .iter()
+            .map(|identity| identity.message_id.to_string())
+            .collect())
+    }
+
+    /// Shape statistics as JSON
+    fn stats_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner.stats()).map_err(value_error)
+    }
+
+    /// Nodes and causation edges as JSON
+    fn graph_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner.graph()).map_err(value_error)
+    }
+
+    /// Graphviz DOT rendering
+    fn to_dot(&self) -> String {
+        self.inner.to_dot()
+    }
+
+    /// Mermaid flowchart rendering
+    fn to_mermaid(&self) -> String {
+        self.inner.to_mermaid()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.iter_bfs().count()
+    }
+}
+
+/// The `cim_subject` Python module
+///
+/// # Errors
+///
+/// Returns an error if a class cannot be added to the module
+#[pymodule]
+#[pyo3(name = "cim_subject")]
+pub fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySubject>()?;
+    module.add_class::<PyPattern>()?;
+    module.add_class::<PyPermissions>()?;
+    module.add_class::<PyCorrelationChain>()?;
+    Ok(())
+}
+
+/// Parse an operation by its variant name
+fn parse_operation(operation: &str) -> PyResult<Operation> {
+    serde_json::from_value(serde_json::Value::String(operation.to_string())).map_err(value_error)
+}
+
+fn value_error(error: impl Display) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    #[test]
+    fn test_chain_from_json() {
+        let root = MessageFactory::create_root_command(Uuid::new_v4());
+        let child = MessageFactory::command_from_command(Uuid::new_v4(), &root);
+        let json = serde_json::to_string(&[&root, &child]).unwrap();
+
+        let chain = PyCorrelationChain::from_json(&json).unwrap();
+        assert_eq!(chain.depth(), 1);
+        assert_eq!(chain.__len__(), 2);
+        assert_eq!(chain.leaves(), vec![child.message_id.to_string()]);
+        assert_eq!(chain.path_to(&child.message_id.to_string()).unwrap(), vec![
+            root.message_id.to_string(),
+            child.message_id.to_string()
+        ]);
+    }
+
+    #[test]
+    fn test_subject_arguments() {
+        let pattern = PyPattern::new("orders.*.placed.>").unwrap();
+        let subject = PySubject::new("orders.order.placed.v1").unwrap();
+        assert!(pattern.matches(SubjectArg::Subject(subject)).unwrap());
+        assert!(!pattern
+            .matches(SubjectArg::Str("orders.order.shipped.v1".to_string()))
+            .unwrap());
+        assert!(PySubject::is_valid("orders.order.placed.v1"));
+        assert!(parse_operation("Publish").is_ok());
+    }
+}
@@ -0,0 +1,451 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Redacting subjects and payload fields by pattern
+//!
+//! A [`RedactionPolicy`] holds [`RedactionRule`]s. Every rule whose pattern
+//! matches a message scrubs its payload fields, addressed by JSON pointers
+//! in which a `*` segment stands for every array element or object member.
+//! The most specific matching rule with a subject template also rewrites
+//! the subject, using the `{context}`, `{aggregate}`, `{event}` and
+//! `{version}` placeholders. Each pass returns a [`RedactionRecord`] of what
+//! was changed, for the audit log.
+//!
+//! ```
+//! use cim_subject::redaction::{
+//!     RedactionAction,
+//!     RedactionPolicy,
+//!     RedactionRule,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     NatsMessage,
+//!     Pattern,
+//! };
+//! use uuid::Uuid;
+//!
+//! let mut policy = RedactionPolicy::new();
+//! policy.add_rule(
+//!     RedactionRule::new("anonymize-users", Pattern::new("users.*.*.v1")?)
+//!         .rewrite_subject("public.anonymous.{event}.{version}")
+//!         .redact("/email", RedactionAction::Remove)?
+//!         .redact("/addresses/*/street", RedactionAction::Mask)?,
+//! );
+//!
+//! let mut message = NatsMessage::with_correlation(
+//!     "users.ada.updated.v1".to_string(),
+//!     serde_json::json!({
+//!         "email": "ada@example.com",
+//!         "addresses": [{"street": "1 Analytical Way", "city": "London"}]
+//!     }),
+//!     &MessageIdentity::root(IdType::Uuid(Uuid::new_v4())),
+//! );
+//! let record = policy.redact(&mut message)?;
+//!
+//! assert_eq!(message.subject, "public.anonymous.updated.v1");
+//! let payload: serde_json::Value = message.payload_json()?;
+//! assert_eq!(
+//!     payload,
+//!     serde_json::json!({"addresses": [{"street": "[REDACTED]", "city": "London"}]})
+//! );
+//! assert_eq!(record.fields.len(), 2);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value;
+
+use crate::correlation::IdType;
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::translator::{
+    render_template,
+    NatsMessage,
+    Payload,
+};
+
+/// Value written in place of masked fields
+pub const REDACTED: &str = "[REDACTED]";
+
+/// What happens to a redacted field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionAction {
+    /// Remove the field, or the element from its array
+    Remove,
+    /// Replace the value with [`REDACTED`]
+    Mask,
+    /// Replace the value with another
+    Replace(Value),
+}
+
+/// Subjects to redact and how
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// Name of the rule, recorded in audit records
+    pub name: String,
+    /// Subjects the rule applies to
+    pub pattern: Pattern,
+    /// Template for the redacted subject, if the subject is rewritten
+    pub subject_template: Option<String>,
+    /// JSON pointers to payload fields and what to do with them
+    pub fields: Vec<(String, RedactionAction)>,
+}
+
+impl RedactionRule {
+    /// Create a rule that changes nothing yet
+    #[must_use]
+    pub fn new(name: impl Into<String>, pattern: Pattern) -> Self {
+        Self {
+            name: name.into(),
+            pattern,
+            subject_template: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Rewrite matching subjects with a template
+    #[must_use]
+    pub fn rewrite_subject(mut self, template: impl Into<String>) -> Self {
+        self.subject_template = Some(template.into());
+        self
+    }
+
+    /// Redact the payload fields a JSON pointer addresses
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if the pointer is empty or does not start
+    /// with `/`
+    pub fn redact(mut self, pointer: impl Into<String>, action: RedactionAction) -> Result<Self> {
+        let pointer = pointer.into();
+        if !pointer.starts_with('/') {
+            return Err(SubjectError::parse_error(format!(
+                "Redaction pointer '{pointer}' must start with '/'"
+            )));
+        }
+        self.fields.push((pointer, action));
+        Ok(self)
+    }
+}
+
+/// One field a redaction changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactedField {
+    /// The rule that redacted it
+    pub rule: String,
+    /// JSON pointer to the field, with wildcards resolved
+    pub pointer: String,
+    /// What was done to it
+    pub action: RedactionAction,
+}
+
+/// What a redaction pass changed, without the redacted values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRecord {
+    /// The message's ID, if its identity headers are readable
+    pub message_id: Option<IdType>,
+    /// The subject before redaction
+    pub original_subject: String,
+    /// The subject after redaction
+    pub subject: String,
+    /// Names of the rules that matched
+    pub rules: Vec<String>,
+    /// Fields that were redacted, in rule order
+    pub fields: Vec<RedactedField>,
+}
+
+impl RedactionRecord {
+    /// Check if nothing was redacted
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.original_subject == self.subject && self.fields.is_empty()
+    }
+}
+
+/// Redaction rules, most specific pattern first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RedactionPolicyRepr")]
+pub struct RedactionPolicy {
+    rules: Vec<RedactionRule>,
+}
+
+/// Serialized form of a [`RedactionPolicy`], in any order
+#[derive(Deserialize)]
+struct RedactionPolicyRepr {
+    rules: Vec<RedactionRule>,
+}
+
+impl From<RedactionPolicyRepr> for RedactionPolicy {
+    fn from(repr: RedactionPolicyRepr) -> Self {
+        let mut policy = Self::new();
+        for rule in repr.rules {
+            policy.add_rule(rule);
+        }
+        policy
+    }
+}
+
+impl RedactionPolicy {
+    /// Create an empty policy
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, replacing any earlier rule with the same name
+    pub fn add_rule(&mut self, rule: RedactionRule) {
+        self.rules.retain(|existing| existing.name != rule.name);
+        self.rules.push(rule);
+        // Stable, so equally specific patterns keep registration order
+        self.rules
+            .sort_by_key(|rule| rule.pattern.specificity_key());
+    }
+
+    /// Rules, most specific first
+    #[must_use]
+    pub fn rules(&self) -> &[RedactionRule] {
+        &self.rules
+    }
+
+    /// Redact a subject and JSON payload in place
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a subject template does not produce a valid
+    /// subject
+    pub fn redact_parts(
+        &self,
+        subject: &mut Subject,
+        payload: &mut Value,
+    ) -> Result<RedactionRecord> {
+        let matching: Vec<&RedactionRule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.pattern.matches(subject))
+            .collect();
+        let mut record = RedactionRecord {
+            message_id: None,
+            original_subject: subject.as_str().to_string(),
+            subject: subject.as_str().to_string(),
+            rules: matching.iter().map(|rule| rule.name.clone()).collect(),
+            fields: Vec::new(),
+        };
+
+        for rule in &matching {
+            for (pointer, action) in &rule.fields {
+                for pointer in redact_pointer(payload, pointer, action) {
+                    record.fields.push(RedactedField {
+                        rule: rule.name.clone(),
+                        pointer,
+                        action: action.clone(),
+                    });
+                }
+            }
+        }
+        if let Some(template) = matching
+            .iter()
+            .find_map(|rule| rule.subject_template.as_deref())
+        {
+            *subject = render_template(template, subject)?;
+            record.subject = subject.as_str().to_string();
+        }
+        Ok(record)
+    }
+
+    /// Redact a message's subject and payload in one pass
+    ///
+    /// A payload is only decoded, in the message's format, if a matching
+    /// rule redacts fields; it is left as JSON afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subject is invalid, the payload cannot be
+    /// decoded, or a subject template does not produce a valid subject
+    pub fn redact(&self, message: &mut NatsMessage) -> Result<RedactionRecord> {
+        let mut subject = Subject::new(&message.subject)?;
+        let touches_payload = self
+            .rules
+            .iter()
+            .any(|rule| !rule.fields.is_empty() && rule.pattern.matches(&subject));
+        let mut payload = if touches_payload {
+            message.payload_json()?
+        } else {
+            Value::Null
+        };
+
+        let mut record = self.redact_parts(&mut subject, &mut payload)?;
+        record.message_id = message
+            .headers
+            .identity()
+            .ok()
+            .map(|identity| identity.message_id);
+        message.subject.clone_from(&record.subject);
+        if touches_payload {
+            message.payload = Payload::Json(payload);
+        }
+        Ok(record)
+    }
+}
+
+/// Apply `action` to every value `pointer` addresses, returning the
+/// resolved pointers
+fn redact_pointer(payload: &mut Value, pointer: &str, action: &RedactionAction) -> Vec<String> {
+    let segments: Vec<String> = pointer
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let mut redacted = Vec::new();
+    redact_at(payload, &segments, "", action, &mut redacted);
+    redacted
+}
+
+fn redact_at(
+    value: &mut Value,
+    segments: &[String],
+    path: &str,
+    action: &RedactionAction,
+    redacted: &mut Vec<String>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+    let keys: Vec<String> = match (segment.as_str(), &*value) {
+        ("*", Value::Object(map)) => map.keys().cloned().collect(),
+        ("*", Value::Array(items)) => (0..items.len()).map(|i| i.to_string()).collect(),
+        _ => vec![segment.clone()],
+    };
+
+    // Remove from the back so earlier array indices stay valid
+    for key in keys.into_iter().rev() {
+        let child_path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+        if !rest.is_empty() {
+            if let Some(child) = child_mut(value, &key) {
+                redact_at(child, rest, &child_path, action, redacted);
+            }
+            continue;
+        }
+        let done = match action {
+            RedactionAction::Remove => match value {
+                Value::Object(map) => map.remove(&key).is_some(),
+                Value::Array(items) => key
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&i| i < items.len())
+                    .map(|i| items.remove(i))
+                    .is_some(),
+                _ => false,
+            },
+            RedactionAction::Mask => child_mut(value, &key)
+                .map(|child| *child = Value::String(REDACTED.to_string()))
+                .is_some(),
+            RedactionAction::Replace(replacement) => child_mut(value, &key)
+                .map(|child| *child = replacement.clone())
+                .is_some(),
+        };
+        if done {
+            redacted.push(child_path);
+        }
+    }
+}
+
+fn child_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(map) => map.get_mut(key),
+        Value::Array(items) => items.get_mut(key.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_rules_combine_and_most_specific_rewrites() {
+        let mut policy = RedactionPolicy::new();
+        policy.add_rule(
+            RedactionRule::new("pii", Pattern::new("lending.>").unwrap())
+                .rewrite_subject("redacted.{aggregate}.{event}.{version}")
+                .redact("/applicants/*/ssn", RedactionAction::Remove)
+                .unwrap(),
+        );
+        policy.add_rule(
+            RedactionRule::new("income", Pattern::new("lending.loan.*.v1").unwrap())
+                .rewrite_subject("public.loan.{event}.{version}")
+                .redact("/income", RedactionAction::Replace(json!(0)))
+                .unwrap()
+                .redact("/a~1b", RedactionAction::Mask)
+                .unwrap(),
+        );
+        assert_eq!(policy.rules()[0].name, "income");
+
+        let mut subject = Subject::new("lending.loan.applied.v1").unwrap();
+        let mut payload = json!({
+            "applicants": [{"ssn": "1"}, {"name": "bo"}, {"ssn": "3"}],
+            "income": 90_000,
+            "a/b": true
+        });
+        let record = policy.redact_parts(&mut subject, &mut payload).unwrap();
+
+        assert_eq!(subject.as_str(), "public.loan.applied.v1");
+        assert_eq!(
+            payload,
+            json!({
+                "applicants": [{}, {"name": "bo"}, {}],
+                "income": 0,
+                "a/b": REDACTED
+            })
+        );
+        assert_eq!(record.rules, ["income", "pii"]);
+        let pointers: Vec<&str> = record.fields.iter().map(|f| f.pointer.as_str()).collect();
+        assert_eq!(pointers, [
+            "/income",
+            "/a~1b",
+            "/applicants/2/ssn",
+            "/applicants/0/ssn"
+        ]);
+
+        // Loaded rules are deduplicated by name and ordered by specificity
+        let mut config = serde_json::to_value(&policy).unwrap();
+        let rules = config["rules"].as_array_mut().unwrap();
+        rules.reverse();
+        rules.insert(0, rules[1].clone());
+        let loaded: RedactionPolicy = serde_json::from_value(config).unwrap();
+        assert_eq!(loaded, policy);
+
+        let mut other = Subject::new("orders.order.placed.v1").unwrap();
+        assert!(policy
+            .redact_parts(&mut other, &mut payload)
+            .unwrap()
+            .is_empty());
+        assert!(RedactionRule::new("bad", Pattern::new(">").unwrap())
+            .redact("ssn", RedactionAction::Remove)
+            .is_err());
+    }
+
+    #[test]
+    fn test_remove_array_elements() {
+        let mut policy = RedactionPolicy::new();
+        policy.add_rule(
+            RedactionRule::new("drop-notes", Pattern::new(">").unwrap())
+                .redact("/notes/*", RedactionAction::Remove)
+                .unwrap(),
+        );
+        let mut subject = Subject::new("crm.customer.updated.v1").unwrap();
+        let mut payload = json!({"notes": ["a", "b", "c"], "id": 1});
+        let record = policy.redact_parts(&mut subject, &mut payload).unwrap();
+        assert_eq!(payload, json!({"notes": [], "id": 1}));
+        assert_eq!(record.fields.len(), 3);
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Repairing invalid subjects
+//!
+//! Subjects from external and legacy systems often break the rules in
+//! predictable ways: `/` between tokens, spaces or punctuation inside them,
+//! two tokens run together as `order_placed` or `OrderPlaced`. A
+//! [`RepairPolicy`] says which of these to fix, and
+//! [`Subject::new_lossy`] applies it. [`SubjectError::suggestions`] offers
+//! the candidate fixes for a subject that failed to parse.
+//!
+//! ```
+//! use cim_subject::repair::RepairPolicy;
+//! use cim_subject::Subject;
+//!
+//! let policy = RepairPolicy::default().lowercase();
+//! let subject = Subject::new_lossy("Orders/Order Placed/v1", &policy)?;
+//! assert_eq!(subject.as_str(), "orders.order.placed.v1");
+//!
+//! let err = Subject::new("orders.order.pl$ced.v1").unwrap_err();
+//! let fixes: Vec<String> = err
+//!     .suggestions()
+//!     .into_iter()
+//!     .map(|s| s.subject.to_string())
+//!     .collect();
+//! assert_eq!(fixes, ["orders.order.plced.v1", "orders.order.pl_ced.v1"]);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use crate::error::{
+    ErrorCode,
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+/// Number of tokens in a subject
+const SUBJECT_TOKENS: usize = 4;
+
+/// What to do with characters not allowed in a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidChars {
+    /// Leave them, so the subject fails to parse
+    Keep,
+    /// Remove them
+    Strip,
+    /// Replace each run of them with a character, trimmed at token ends
+    Replace(char),
+}
+
+/// Which repairs [`Subject::new_lossy`] applies
+///
+/// Repairs run in a fixed order: separators become `.`, invalid characters
+/// are handled, empty tokens are dropped, merged tokens are split, and
+/// finally tokens are lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairPolicy {
+    /// Characters treated as token separators in addition to `.`
+    pub separators: Vec<char>,
+    /// Handling of characters not allowed in a token
+    pub invalid_chars: InvalidChars,
+    /// Split tokens at `_`, `-` or a lower-to-upper case change while the
+    /// subject has fewer than four tokens
+    pub split_merged: bool,
+    /// Fold tokens to lowercase
+    pub lowercase: bool,
+}
+
+impl Default for RepairPolicy {
+    /// `/` and `:` as separators, invalid characters replaced with `_`, and
+    /// merged tokens split
+    fn default() -> Self {
+        Self {
+            separators: vec!['/', ':'],
+            invalid_chars: InvalidChars::Replace('_'),
+            split_merged: true,
+            lowercase: false,
+        }
+    }
+}
+
+impl RepairPolicy {
+    /// A policy that repairs nothing
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            separators: Vec::new(),
+            invalid_chars: InvalidChars::Keep,
+            split_merged: false,
+            lowercase: false,
+        }
+    }
+
+    /// Accept an additional token separator
+    #[must_use]
+    pub fn separator(mut self, separator: char) -> Self {
+        if !self.separators.contains(&separator) {
+            self.separators.push(separator);
+        }
+        self
+    }
+
+    /// Set the handling of invalid characters
+    #[must_use]
+    pub fn invalid_chars(mut self, handling: InvalidChars) -> Self {
+        self.invalid_chars = handling;
+        self
+    }
+
+    /// Split merged tokens
+    #[must_use]
+    pub fn split_merged(mut self) -> Self {
+        self.split_merged = true;
+        self
+    }
+
+    /// Fold tokens to lowercase
+    #[must_use]
+    pub fn lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Apply the repairs to a raw subject string without validating it
+    #[must_use]
+    pub fn repair(&self, subject: &str) -> String {
+        let subject: String = subject
+            .chars()
+            .map(|c| if self.separators.contains(&c) { '.' } else { c })
+            .collect();
+        let mut tokens: Vec<String> = subject
+            .split('.')
+            .map(|token| self.repair_chars(token))
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        if self.split_merged {
+            while tokens.len() < SUBJECT_TOKENS {
+                let Some((index, at)) = tokens
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, token)| word_boundary(token).map(|at| (index, at)))
+                else {
+                    break;
+                };
+                let token = tokens.remove(index);
+                let (head, tail) = token.split_at(at);
+                let tail = tail.trim_start_matches(['_', '-']);
+                tokens.insert(index, tail.to_string());
+                tokens.insert(index, head.to_string());
+            }
+        }
+
+        let subject = tokens.join(".");
+        if self.lowercase {
+            subject.to_lowercase()
+        } else {
+            subject
+        }
+    }
+
+    fn repair_chars(&self, token: &str) -> String {
+        let valid = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        match self.invalid_chars {
+            InvalidChars::Keep => token.to_string(),
+            InvalidChars::Strip => token.chars().filter(|&c| valid(c)).collect(),
+            InvalidChars::Replace(replacement) => {
+                let mut out = String::with_capacity(token.len());
+                let mut in_run = false;
+                for c in token.chars() {
+                    if valid(c) {
+                        out.push(c);
+                        in_run = false;
+                    } else if !in_run {
+                        out.push(replacement);
+                        in_run = true;
+                    }
+                }
+                out.trim_matches(replacement).to_string()
+            },
+        }
+    }
+}
+
+/// Byte offset to split a merged token at: the first `_` or `-` between
+/// two non-empty words, otherwise the first lower-to-upper case change
+fn word_boundary(token: &str) -> Option<usize> {
+    let separator = token
+        .char_indices()
+        .find(|&(at, c)| (c == '_' || c == '-') && at > 0 && at + 1 < token.len())
+        .map(|(at, _)| at);
+    separator.or_else(|| {
+        token
+            .char_indices()
+            .zip(token.chars().skip(1))
+            .find(|&((_, c), next)| c.is_lowercase() && next.is_uppercase())
+            .map(|((at, c), _)| at + c.len_utf8())
+    })
+}
+
+impl Subject {
+    /// Create a subject, repairing the input under `policy` if it does not
+    /// parse as is
+    ///
+    /// # Errors
+    ///
+    /// Returns the original parse error if the repaired input is still not
+    /// a valid subject
+    pub fn new_lossy(subject: &str, policy: &RepairPolicy) -> Result<Self> {
+        Subject::new(subject).or_else(|err| Subject::new(policy.repair(subject)).map_err(|_| err))
+    }
+}
+
+/// A candidate fix for an invalid subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// What the fix does
+    pub repair: &'static str,
+    /// The repaired subject
+    pub subject: Subject,
+}
+
+impl SubjectError {
+    /// Candidate fixes for a subject that failed to parse, least invasive
+    /// first
+    ///
+    /// Errors other than an invalid subject have no suggestions.
+    #[must_use]
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        let Some(input) = self
+            .input()
+            .filter(|_| self.code() == ErrorCode::InvalidFormat)
+        else {
+            return Vec::new();
+        };
+        let candidates = [
+            (
+                "strip invalid characters",
+                RepairPolicy::none().invalid_chars(InvalidChars::Strip),
+            ),
+            (
+                "replace invalid characters with '_'",
+                RepairPolicy::none().invalid_chars(InvalidChars::Replace('_')),
+            ),
+            ("split merged tokens", RepairPolicy::default()),
+            ("lowercase tokens", RepairPolicy::none().lowercase()),
+            (
+                "split merged tokens and lowercase",
+                RepairPolicy::default().lowercase(),
+            ),
+        ];
+
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for (repair, policy) in candidates {
+            let Ok(subject) = Subject::new(policy.repair(input)) else {
+                continue;
+            };
+            if suggestions.iter().all(|s| s.subject != subject) {
+                suggestions.push(Suggestion { repair, subject });
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repairs() {
+        let policy = RepairPolicy::default();
+        assert_eq!(
+            policy.repair("orders.order_placed.v1"),
+            "orders.order.placed.v1"
+        );
+        assert_eq!(
+            policy.repair("orders.OrderPlaced.v1"),
+            "orders.Order.Placed.v1"
+        );
+        assert_eq!(
+            policy.repair("orders:order/placed//v1"),
+            "orders.order.placed.v1"
+        );
+        assert_eq!(
+            policy.repair("orders.order.(placed).v1"),
+            "orders.order.placed.v1"
+        );
+        // Tokens are only split while the subject is short
+        assert_eq!(
+            policy.repair("my_orders.order.placed.v1"),
+            "my_orders.order.placed.v1"
+        );
+
+        let strip = RepairPolicy::none().invalid_chars(InvalidChars::Strip);
+        assert_eq!(
+            strip.repair("orders.or der.placed.v1"),
+            "orders.order.placed.v1"
+        );
+
+        // Valid subjects are never rewritten
+        let subject = Subject::new_lossy("Orders.Order.Placed.v1", &policy.clone().lowercase());
+        assert_eq!(subject.unwrap().as_str(), "Orders.Order.Placed.v1");
+        assert!(Subject::new_lossy("a.b.c.d.e", &policy).is_err());
+        assert!(Subject::new_lossy("orders.order placed.v1", &RepairPolicy::none()).is_err());
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let err = Subject::new("Orders.Order Placed.v1").unwrap_err();
+        let suggestions = err.suggestions();
+        let subjects: Vec<&str> = suggestions.iter().map(|s| s.subject.as_str()).collect();
+        assert_eq!(subjects, [
+            "Orders.Order.Placed.v1",
+            "orders.order.placed.v1"
+        ]);
+        assert_eq!(suggestions[0].repair, "split merged tokens");
+
+        assert!(SubjectError::not_found("x").suggestions().is_empty());
+        assert!(crate::pattern::Pattern::new("a.$")
+            .unwrap_err()
+            .suggestions()
+            .is_empty());
+    }
+}
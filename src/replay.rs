@@ -0,0 +1,245 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Filters for replaying stored events
+//!
+//! Replay tooling selects events by subject, correlation, time and
+//! causation at once. A [`ReplayFilter`] states those conditions, all of
+//! which must hold, and [`compile`](ReplayFilter::compile)s them into a
+//! [`ReplayPredicate`] that checks [`ChainEntry`] records in replay order,
+//! cheapest condition first.
+//!
+//! ```
+//! use std::time::{
+//!     Duration,
+//!     SystemTime,
+//! };
+//!
+//! use cim_subject::message_algebra::ChainEntry;
+//! use cim_subject::replay::ReplayFilter;
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     Pattern,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+//! let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let placed = MessageIdentity::caused_by(
+//!     IdType::Uuid(Uuid::new_v4()),
+//!     root.correlation_id.clone(),
+//!     root.message_id.clone(),
+//! );
+//! let entries = [
+//!     ChainEntry::new(root.clone(), start)
+//!         .with_subject(Subject::new("orders.commands.order.place")?),
+//!     ChainEntry::new(placed, start + Duration::from_secs(1))
+//!         .with_subject(Subject::new("orders.events.order.placed")?),
+//! ];
+//!
+//! let filter = ReplayFilter::new()
+//!     .pattern(Pattern::new("orders.events.>")?)
+//!     .correlation(root.correlation_id.clone())
+//!     .descended_from(root.message_id.clone())
+//!     .since(start);
+//! let replayed = filter.apply(&entries);
+//! assert_eq!(replayed.len(), 1);
+//! assert_eq!(
+//!     replayed[0].subject.as_ref().map(Subject::as_str),
+//!     Some("orders.events.order.placed")
+//! );
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::time::SystemTime;
+
+use crate::correlation::{
+    CorrelationId,
+    IdType,
+};
+use crate::message_algebra::ChainEntry;
+use crate::pattern::Pattern;
+
+/// Conditions an event must meet to be replayed
+///
+/// Unset conditions always hold, so an empty filter replays everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayFilter {
+    pattern: Option<Pattern>,
+    correlation: Option<CorrelationId>,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+    ancestor: Option<IdType>,
+}
+
+impl ReplayFilter {
+    /// Create a filter that replays everything
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only events whose subject matches `pattern`; events without a
+    /// subject never match
+    #[must_use]
+    pub fn pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Only events in `correlation`
+    #[must_use]
+    pub fn correlation(mut self, correlation: CorrelationId) -> Self {
+        self.correlation = Some(correlation);
+        self
+    }
+
+    /// Only events observed at or after `time`
+    #[must_use]
+    pub fn since(mut self, time: SystemTime) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Only events observed before `time`
+    #[must_use]
+    pub fn until(mut self, time: SystemTime) -> Self {
+        self.until = Some(time);
+        self
+    }
+
+    /// Only the message `ancestor` and the messages it caused, directly or
+    /// transitively
+    #[must_use]
+    pub fn descended_from(mut self, ancestor: IdType) -> Self {
+        self.ancestor = Some(ancestor);
+        self
+    }
+
+    /// Compile the filter into a predicate for one replay
+    #[must_use]
+    pub fn compile(&self) -> ReplayPredicate<'_> {
+        ReplayPredicate {
+            filter: self,
+            descendants: self.ancestor.iter().cloned().collect(),
+        }
+    }
+
+    /// The entries, in replay order, that pass the filter
+    #[must_use]
+    pub fn apply<'a>(&self, entries: &'a [ChainEntry]) -> Vec<&'a ChainEntry> {
+        let mut predicate = self.compile();
+        entries
+            .iter()
+            .filter(|entry| predicate.matches(entry))
+            .collect()
+    }
+}
+
+/// A [`ReplayFilter`] applied to one replay
+///
+/// Descent from the ancestor is tracked as entries are checked, so entries
+/// must be passed in causal order, as an event stream replays them, and
+/// each entry exactly once.
+#[derive(Debug, Clone)]
+pub struct ReplayPredicate<'a> {
+    filter: &'a ReplayFilter,
+    descendants: HashSet<IdType>,
+}
+
+impl ReplayPredicate<'_> {
+    /// Check the next entry of the replay
+    pub fn matches(&mut self, entry: &ChainEntry) -> bool {
+        let filter = self.filter;
+        let identity = &entry.identity;
+        // Record descent first: an entry outside the other conditions can
+        // still cause entries inside them
+        if filter.ancestor.is_some() {
+            if self.descendants.contains(&identity.causation_id.0) {
+                self.descendants.insert(identity.message_id.clone());
+            }
+            if !self.descendants.contains(&identity.message_id) {
+                return false;
+            }
+        }
+        filter.since.map_or(true, |since| entry.timestamp >= since)
+            && filter.until.map_or(true, |until| entry.timestamp < until)
+            && filter
+                .correlation
+                .as_ref()
+                .map_or(true, |correlation| &identity.correlation_id == correlation)
+            && filter.pattern.as_ref().map_or(true, |pattern| {
+                entry
+                    .subject
+                    .as_ref()
+                    .is_some_and(|subject| pattern.matches(subject))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageIdentity;
+    use crate::subject::Subject;
+
+    fn caused(parent: &MessageIdentity) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            parent.correlation_id.clone(),
+            parent.message_id.clone(),
+        )
+    }
+
+    #[test]
+    fn test_descent_is_transitive() {
+        let at = SystemTime::UNIX_EPOCH;
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let left = caused(&root);
+        let right = caused(&root);
+        let grandchild = caused(&left);
+        let entries: Vec<ChainEntry> = [&root, &left, &right, &grandchild]
+            .into_iter()
+            .map(|identity| ChainEntry::new(identity.clone(), at))
+            .collect();
+
+        let filter = ReplayFilter::new().descended_from(left.message_id.clone());
+        let ids: Vec<&IdType> = filter
+            .apply(&entries)
+            .into_iter()
+            .map(|entry| &entry.identity.message_id)
+            .collect();
+        assert_eq!(ids, [&left.message_id, &grandchild.message_id]);
+        assert_eq!(ReplayFilter::new().apply(&entries).len(), 4);
+    }
+
+    #[test]
+    fn test_conditions_combine() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let other = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let placed = Subject::new("orders.events.order.placed").unwrap();
+        let entries = [
+            ChainEntry::new(caused(&root), start).with_subject(placed.clone()),
+            ChainEntry::new(caused(&root), start + Duration::from_secs(10))
+                .with_subject(placed.clone()),
+            ChainEntry::new(caused(&other), start).with_subject(placed),
+            ChainEntry::new(caused(&root), start),
+        ];
+
+        let filter = ReplayFilter::new()
+            .pattern(Pattern::new("orders.events.>").unwrap())
+            .correlation(root.correlation_id.clone())
+            .since(start)
+            .until(start + Duration::from_secs(10));
+        let replayed = filter.apply(&entries);
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0], &entries[0]);
+    }
+}
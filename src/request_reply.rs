@@ -0,0 +1,215 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Request/response correlation
+//!
+//! A [`RequestContext`] is created by the requester with a fresh inbox and
+//! travels in the request's headers, the inbox in `Reply-To` next to the
+//! identity headers. The responder rebuilds it from those headers and
+//! derives the reply's identity from it, so the reply is caused by the
+//! request and shares its correlation. Back at the requester,
+//! [`validate_reply`](RequestContext::validate_reply) rejects replies that
+//! do not belong to the request.
+//!
+//! ```
+//! use cim_subject::request_reply::RequestContext;
+//! use cim_subject::{
+//!     HeaderConvention,
+//!     MessageFactory,
+//!     Subject,
+//! };
+//! use uuid::Uuid;
+//!
+//! let convention = HeaderConvention::default();
+//! let request = RequestContext::new(
+//!     Subject::new("catalog.product.get_details.v1").unwrap(),
+//!     MessageFactory::create_root_query(Uuid::new_v4()),
+//! );
+//! let headers = request.to_headers(&convention);
+//!
+//! // On the responder
+//! let received = RequestContext::from_headers(
+//!     request.subject.clone(),
+//!     &convention,
+//!     headers.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+//! )
+//! .unwrap();
+//! assert_eq!(received.reply_to, request.reply_to);
+//! let reply = received.reply_identity();
+//!
+//! // Back on the requester
+//! assert!(request.validate_reply(&reply).is_ok());
+//! ```
+
+use uuid::Uuid;
+
+use crate::correlation::{
+    CorrelationError,
+    IdType,
+    MessageIdentity,
+    Result,
+};
+use crate::header_convention::HeaderConvention;
+use crate::subject::Subject;
+
+/// Header carrying the subject replies are published on
+pub const REPLY_TO_HEADER: &str = "Reply-To";
+
+/// Prefix of inboxes created by [`new_inbox`], as used by NATS clients
+pub const DEFAULT_INBOX_PREFIX: &str = "_INBOX";
+
+/// A unique inbox subject under `prefix`
+#[must_use]
+pub fn new_inbox(prefix: &str) -> String {
+    format!("{prefix}.{}", Uuid::new_v4().simple())
+}
+
+/// A request's subject, identity and reply inbox
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// The subject the request is sent on
+    pub subject: Subject,
+    /// The request's identity
+    pub identity: MessageIdentity,
+    /// Where replies are published
+    pub reply_to: String,
+}
+
+impl RequestContext {
+    /// A request with a fresh inbox under [`DEFAULT_INBOX_PREFIX`]
+    #[must_use]
+    pub fn new(subject: Subject, identity: MessageIdentity) -> Self {
+        Self::with_inbox_prefix(subject, identity, DEFAULT_INBOX_PREFIX)
+    }
+
+    /// A request with a fresh inbox under `prefix`, e.g. a per-client
+    /// prefix the requester already subscribes to with `prefix.*`
+    #[must_use]
+    pub fn with_inbox_prefix(subject: Subject, identity: MessageIdentity, prefix: &str) -> Self {
+        Self {
+            subject,
+            identity,
+            reply_to: new_inbox(prefix),
+        }
+    }
+
+    /// Rebuild a received request from its headers
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identity headers cannot be parsed or
+    /// `Reply-To` is missing
+    pub fn from_headers<'a>(
+        subject: Subject,
+        convention: &HeaderConvention,
+        headers: impl IntoIterator<Item = (&'a str, &'a str)> + Clone,
+    ) -> Result<Self> {
+        let identity = convention.parse(headers.clone())?;
+        let reply_to = headers
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(REPLY_TO_HEADER))
+            .map(|(_, value)| value.to_string())
+            .ok_or_else(|| {
+                CorrelationError::InvalidIdentity(format!("missing '{REPLY_TO_HEADER}' header"))
+            })?;
+        Ok(Self {
+            subject,
+            identity,
+            reply_to,
+        })
+    }
+
+    /// Identity headers under `convention` plus `Reply-To`
+    #[must_use]
+    pub fn to_headers(&self, convention: &HeaderConvention) -> Vec<(String, String)> {
+        let mut headers = convention.to_headers(&self.identity);
+        headers.push((REPLY_TO_HEADER.to_string(), self.reply_to.clone()));
+        headers
+    }
+
+    /// A fresh identity for a reply, caused by the request
+    #[must_use]
+    pub fn reply_identity(&self) -> MessageIdentity {
+        MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            self.identity.correlation_id.clone(),
+            self.identity.message_id.clone(),
+        )
+    }
+
+    /// Check that a reply shares the request's correlation and was caused by
+    /// the request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first mismatch
+    pub fn validate_reply(&self, reply: &MessageIdentity) -> Result<()> {
+        if reply.correlation_id != self.identity.correlation_id {
+            return Err(CorrelationError::InvalidIdentity(format!(
+                "reply {} has {}, expected {}",
+                reply.message_id, reply.correlation_id, self.identity.correlation_id
+            )));
+        }
+        if reply.causation_id.0 != self.identity.message_id {
+            return Err(CorrelationError::InvalidIdentity(format!(
+                "reply {} was caused by {}, not request {}",
+                reply.message_id, reply.causation_id, self.identity.message_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correlation::MessageFactory;
+
+    fn request() -> RequestContext {
+        RequestContext::with_inbox_prefix(
+            Subject::new("catalog.product.get_details.v1").unwrap(),
+            MessageFactory::create_root_query(Uuid::new_v4()),
+            "_INBOX.client7",
+        )
+    }
+
+    #[test]
+    fn test_reply_validation() {
+        let request = request();
+        assert!(request.reply_to.starts_with("_INBOX.client7."));
+        // Every request gets its own inbox
+        assert_ne!(request.reply_to, self::request().reply_to);
+
+        assert!(request.validate_reply(&request.reply_identity()).is_ok());
+        // A reply to another request in the same conversation
+        let follow_up = MessageFactory::query_from_query(Uuid::new_v4(), &request.identity);
+        let other = RequestContext::new(request.subject.clone(), follow_up);
+        let err = request.validate_reply(&other.reply_identity()).unwrap_err();
+        assert!(err.to_string().contains("was caused by"));
+        let unrelated = RequestContext::new(
+            request.subject.clone(),
+            MessageFactory::create_root_query(Uuid::new_v4()),
+        );
+        assert!(request.validate_reply(&unrelated.reply_identity()).is_err());
+    }
+
+    #[test]
+    fn test_headers_round_trip() {
+        let request = request();
+        let convention = HeaderConvention::nats();
+        let headers = request.to_headers(&convention);
+        let pairs = || headers.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+
+        let received =
+            RequestContext::from_headers(request.subject.clone(), &convention, pairs()).unwrap();
+        assert_eq!(received.reply_to, request.reply_to);
+        assert_eq!(received.identity.message_id, request.identity.message_id);
+
+        let without_reply_to = pairs().filter(|(k, _)| *k != REPLY_TO_HEADER);
+        assert!(RequestContext::from_headers(
+            request.subject.clone(),
+            &convention,
+            without_reply_to
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,1076 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! In-process message routing by subject
+//!
+//! A [`Router`] dispatches a message to handlers registered against
+//! [`Pattern`]s. In [`DispatchMode::MostSpecific`] only the most specific
+//! matching route runs; in [`DispatchMode::All`] every matching route does,
+//! most specific first. Middleware runs before any handler and can reject a
+//! message, and a route can carry [`Permissions`] that must allow
+//! subscribing to the subject.
+//!
+//! Like NATS queue subscriptions, handlers registered under the same queue
+//! group for the same pattern act as one route: each message goes to just
+//! one of them, chosen by the router's [`QueueBalancing`].
+//!
+//! [`RouteOptions`] add a [`RetryPolicy`] and a dead-letter pattern to a
+//! route. A message whose handler still fails after its last attempt is
+//! handed, unchanged, to the router's dead-letter sink together with a
+//! [`DeadLetter`] naming its dead-letter subject, so republishing it there
+//! keeps its identity and causation.
+//!
+//! With the `tokio` feature, `AsyncRouter` does the same for async
+//! handlers, running all matching routes concurrently.
+//!
+//! ```
+//! use std::sync::atomic::{
+//!     AtomicUsize,
+//!     Ordering,
+//! };
+//! use std::sync::Arc;
+//!
+//! use cim_subject::router::Router;
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let placed = Arc::new(AtomicUsize::new(0));
+//! let counter = placed.clone();
+//! let router = Router::<String>::new()
+//!     .route(Pattern::new("orders.>").unwrap(), |_, _| Ok(()))
+//!     .route(
+//!         Pattern::new("orders.order.placed.*").unwrap(),
+//!         move |_, _| {
+//!             counter.fetch_add(1, Ordering::SeqCst);
+//!             Ok(())
+//!         },
+//!     );
+//!
+//! let subject = Subject::new("orders.order.placed.v1").unwrap();
+//! router.dispatch(&subject, &"order 42".to_string()).unwrap();
+//! assert_eq!(placed.load(Ordering::SeqCst), 1);
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::{
+    Pattern,
+    Token,
+};
+use crate::permissions::{
+    Operation,
+    Permissions,
+};
+use crate::subject::Subject;
+use crate::telemetry;
+
+/// A synchronous route handler
+pub type Handler<M> = Arc<dyn Fn(&Subject, &M) -> Result<()> + Send + Sync>;
+
+/// A check run on every message before any handler; an error stops dispatch
+pub type Middleware<M> = Arc<dyn Fn(&Subject, &M) -> Result<()> + Send + Sync>;
+
+/// Receives messages whose handler failed on every attempt
+pub type DeadLetterSink<M> = Arc<dyn Fn(&DeadLetter, &M) -> Result<()> + Send + Sync>;
+
+/// Which matching routes a message is dispatched to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Only the most specific matching route
+    #[default]
+    MostSpecific,
+    /// Every matching route, most specific first
+    All,
+}
+
+/// How a queue group picks the member that receives a message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueueBalancing {
+    /// Members take turns in registration order
+    #[default]
+    RoundRobin,
+    /// A member is picked at random
+    Random,
+}
+
+/// How often a failing handler is retried, and how long to wait between
+/// attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for every further retry
+    pub initial_backoff: Duration,
+    /// Longest wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Make up to `max_attempts` attempts without waiting in between
+    #[must_use]
+    pub fn attempts(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Wait `initial` before the first retry, doubling up to `max`
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// The wait before retry number `retry`, counting from 1
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// A message that failed on every attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadLetter {
+    /// Subject to republish the message on
+    pub subject: Subject,
+    /// Subject the message was dispatched on
+    pub original_subject: Subject,
+    /// Error from the last attempt
+    pub error: SubjectError,
+    /// Attempts made
+    pub attempts: u32,
+}
+
+/// Per-route settings
+#[derive(Debug, Clone, Default)]
+pub struct RouteOptions {
+    /// Permissions that must allow subscribing to a subject, or for a
+    /// queue group queue subscribing in that group, for the route to
+    /// receive it
+    pub permissions: Option<Permissions>,
+    /// Retries for a failing handler
+    pub retry: RetryPolicy,
+    /// Where messages go after the last failed attempt: literal tokens
+    /// replace the subject's, wildcards keep them, so `orders.dlq.>` maps
+    /// `orders.commands.order.place` to `orders.dlq.order.place`
+    pub dead_letter: Option<Pattern>,
+}
+
+impl RouteOptions {
+    /// Options with no permissions, one attempt and no dead-lettering
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require permissions to allow subscribing to a subject
+    #[must_use]
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Retry failing handlers
+    #[must_use]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Dead-letter messages that fail every attempt
+    #[must_use]
+    pub fn dead_letter(mut self, pattern: Pattern) -> Self {
+        self.dead_letter = Some(pattern);
+        self
+    }
+}
+
+/// Map a subject onto a dead-letter pattern
+///
+/// # Errors
+///
+/// Returns an error if the pattern's token count cannot line up with the
+/// subject, or the result is not a valid subject
+pub fn dead_letter_subject(pattern: &Pattern, subject: &Subject) -> Result<Subject> {
+    let source = subject.parts().as_parts_ref().tokens();
+    let mut tokens: Vec<&str> = Vec::with_capacity(source.len());
+    for (i, token) in pattern.tokens().iter().enumerate() {
+        match token {
+            Token::Literal(literal) => tokens.push(literal),
+            Token::SingleWildcard if i < source.len() => tokens.push(source[i]),
+            Token::MultiWildcard if i < source.len() => tokens.extend(&source[i..]),
+            _ => tokens.push(""),
+        }
+    }
+    if tokens.len() != source.len() || tokens.contains(&"") {
+        return Err(SubjectError::translation_error(format!(
+            "Dead-letter pattern '{pattern}' does not line up with '{subject}'"
+        )));
+    }
+    Subject::new(tokens.join("."))
+}
+
+/// A registered route; queue groups have several members, other routes one
+struct Route<H> {
+    pattern: Pattern,
+    queue_group: Option<String>,
+    options: RouteOptions,
+    members: Vec<H>,
+    /// Deliveries so far, for picking the next member
+    deliveries: AtomicUsize,
+}
+
+/// Routes ordered by specificity, with the middleware and dispatch mode
+/// shared by [`Router`] and `AsyncRouter`
+struct RouteTable<H, M> {
+    routes: Vec<Route<H>>,
+    middleware: Vec<Middleware<M>>,
+    mode: DispatchMode,
+    balancing: QueueBalancing,
+    random: RandomState,
+    dead_letters: Option<DeadLetterSink<M>>,
+}
+
+impl<H, M> Default for RouteTable<H, M> {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            middleware: Vec::new(),
+            mode: DispatchMode::default(),
+            balancing: QueueBalancing::default(),
+            random: RandomState::new(),
+            dead_letters: None,
+        }
+    }
+}
+
+impl<H, M> RouteTable<H, M> {
+    fn add(&mut self, pattern: Pattern, options: RouteOptions, handler: H) {
+        self.push(pattern, None, options, handler);
+    }
+
+    /// Join the queue group for the pattern, creating it with `options` if
+    /// needed; members joining an existing group share its options
+    fn add_to_queue(&mut self, pattern: Pattern, group: String, options: RouteOptions, handler: H) {
+        let existing = self
+            .routes
+            .iter_mut()
+            .find(|route| route.pattern == pattern && route.queue_group.as_ref() == Some(&group));
+        match existing {
+            Some(route) => route.members.push(handler),
+            None => self.push(pattern, Some(group), options, handler),
+        }
+    }
+
+    fn push(
+        &mut self,
+        pattern: Pattern,
+        queue_group: Option<String>,
+        options: RouteOptions,
+        handler: H,
+    ) {
+        self.routes.push(Route {
+            pattern,
+            queue_group,
+            options,
+            members: vec![handler],
+            deliveries: AtomicUsize::new(0),
+        });
+        // Stable, so equally specific routes keep registration order
+        self.routes
+            .sort_by_key(|route| route.pattern.specificity_key());
+    }
+
+    fn patterns(&self) -> Vec<&Pattern> {
+        self.routes.iter().map(|route| &route.pattern).collect()
+    }
+
+    /// Run the middleware and pick the handlers for a message, with their
+    /// route's options
+    fn select(&self, subject: &Subject, message: &M) -> Result<Vec<(&RouteOptions, &H)>> {
+        for middleware in &self.middleware {
+            middleware(subject, message)?;
+        }
+
+        let matching: Vec<&Route<H>> = telemetry::time_pattern_match("router", || {
+            self.routes
+                .iter()
+                .filter(|route| route.pattern.matches(subject))
+                .collect()
+        });
+        if matching.is_empty() {
+            return Err(SubjectError::not_found(format!(
+                "No route matches '{subject}'"
+            )));
+        }
+
+        // Queue groups are checked as queue subscriptions in their group
+        let allowed = |route: &&Route<H>| {
+            route
+                .options
+                .permissions
+                .as_ref()
+                .map_or(true, |p| match &route.queue_group {
+                    Some(group) => p.can_queue_subscribe(subject, group),
+                    None => p.is_allowed(subject, Operation::Subscribe),
+                })
+        };
+        let selected: Vec<&Route<H>> = match self.mode {
+            DispatchMode::MostSpecific => matching.into_iter().take(1).filter(allowed).collect(),
+            DispatchMode::All => matching.into_iter().filter(allowed).collect(),
+        };
+        let handlers: Vec<(&RouteOptions, &H)> = selected
+            .into_iter()
+            .map(|route| (&route.options, self.member(route)))
+            .collect();
+
+        if handlers.is_empty() {
+            return Err(SubjectError::permission_denied(format!(
+                "Route permissions deny '{subject}'"
+            )));
+        }
+        Ok(handlers)
+    }
+
+    /// The member of a route that receives the next message
+    fn member<'a>(&self, route: &'a Route<H>) -> &'a H {
+        let delivery = route.deliveries.fetch_add(1, Ordering::Relaxed);
+        let index = match self.balancing {
+            QueueBalancing::RoundRobin => delivery,
+            // Truncation is fine: only the remainder is used
+            #[allow(clippy::cast_possible_truncation)]
+            QueueBalancing::Random => self.random.hash_one(delivery) as usize,
+        };
+        &route.members[index % route.members.len()]
+    }
+
+    /// Hand a message that failed every attempt to the dead-letter sink, or
+    /// return its error if the route has no dead-letter pattern
+    fn dead_letter(
+        &self,
+        options: &RouteOptions,
+        subject: &Subject,
+        message: &M,
+        error: SubjectError,
+    ) -> Result<()> {
+        let (Some(pattern), Some(sink)) = (&options.dead_letter, &self.dead_letters) else {
+            return Err(error);
+        };
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            %subject,
+            %error,
+            attempts = options.retry.max_attempts,
+            "dead-lettering message"
+        );
+        let dead_letter = DeadLetter {
+            subject: dead_letter_subject(pattern, subject)?,
+            original_subject: subject.clone(),
+            error,
+            attempts: options.retry.max_attempts,
+        };
+        sink(&dead_letter, message)
+    }
+}
+
+/// Dispatches messages to synchronous handlers by subject
+pub struct Router<M> {
+    table: RouteTable<Handler<M>, M>,
+}
+
+impl<M> Default for Router<M> {
+    fn default() -> Self {
+        Self {
+            table: RouteTable::default(),
+        }
+    }
+}
+
+impl<M> Router<M> {
+    /// Create a router with no routes that dispatches to the most specific
+    /// match
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set which matching routes receive a message
+    #[must_use]
+    pub fn dispatch_mode(mut self, mode: DispatchMode) -> Self {
+        self.table.mode = mode;
+        self
+    }
+
+    /// Add a handler for subjects matching `pattern`
+    #[must_use]
+    pub fn route<F>(mut self, pattern: Pattern, handler: F) -> Self
+    where F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static {
+        self.table
+            .add(pattern, RouteOptions::default(), Arc::new(handler));
+        self
+    }
+
+    /// Add a handler to the queue group `group` for `pattern`; each message
+    /// the group receives goes to one of its members
+    #[must_use]
+    pub fn queue_route<F>(self, pattern: Pattern, group: impl Into<String>, handler: F) -> Self
+    where F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static {
+        self.queue_route_with_options(pattern, group, RouteOptions::default(), handler)
+    }
+
+    /// Add a handler to a queue group with permissions, retries or
+    /// dead-lettering
+    ///
+    /// The options apply to the whole group and are taken from the member
+    /// that creates it; later members share them.
+    #[must_use]
+    pub fn queue_route_with_options<F>(
+        mut self,
+        pattern: Pattern,
+        group: impl Into<String>,
+        options: RouteOptions,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static,
+    {
+        self.table
+            .add_to_queue(pattern, group.into(), options, Arc::new(handler));
+        self
+    }
+
+    /// Set how queue groups pick a member
+    #[must_use]
+    pub fn queue_balancing(mut self, balancing: QueueBalancing) -> Self {
+        self.table.balancing = balancing;
+        self
+    }
+
+    /// Add a handler that only receives subjects `permissions` allow
+    /// subscribing to
+    #[must_use]
+    pub fn route_with_permissions<F>(
+        self,
+        pattern: Pattern,
+        permissions: Permissions,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static,
+    {
+        self.route_with_options(
+            pattern,
+            RouteOptions::new().permissions(permissions),
+            handler,
+        )
+    }
+
+    /// Add a handler with permissions, retries or dead-lettering
+    #[must_use]
+    pub fn route_with_options<F>(
+        mut self,
+        pattern: Pattern,
+        options: RouteOptions,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static,
+    {
+        self.table.add(pattern, options, Arc::new(handler));
+        self
+    }
+
+    /// Receive messages that failed every attempt on a route with a
+    /// dead-letter pattern
+    #[must_use]
+    pub fn dead_letter_sink<F>(mut self, sink: F) -> Self
+    where F: Fn(&DeadLetter, &M) -> Result<()> + Send + Sync + 'static {
+        self.table.dead_letters = Some(Arc::new(sink));
+        self
+    }
+
+    /// Add middleware, run in registration order before any handler
+    #[must_use]
+    pub fn middleware<F>(mut self, middleware: F) -> Self
+    where F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static {
+        self.table.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Route patterns, most specific first
+    #[must_use]
+    pub fn patterns(&self) -> Vec<&Pattern> {
+        self.table.patterns()
+    }
+
+    /// Dispatch a message, returning how many handlers ran
+    ///
+    /// Retry backoff blocks the calling thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Middleware rejects the message
+    /// - No route matches the subject
+    /// - The permissions of the selected routes deny the subject
+    /// - A handler fails every attempt and its route has no dead-letter
+    ///   pattern, or dead-lettering fails; later handlers do not run
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(subject = %subject), err)
+    )]
+    pub fn dispatch(&self, subject: &Subject, message: &M) -> Result<usize> {
+        let handlers = self.table.select(subject, message)?;
+        for (options, handler) in &handlers {
+            let mut attempt = 1;
+            let outcome = loop {
+                match handler(subject, message) {
+                    Err(_) if attempt < options.retry.max_attempts => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(attempt, "handler failed, retrying");
+                        std::thread::sleep(options.retry.backoff(attempt));
+                        attempt += 1;
+                    },
+                    outcome => break outcome,
+                }
+            };
+            if let Err(error) = outcome {
+                self.table.dead_letter(options, subject, message, error)?;
+            }
+        }
+        Ok(handlers.len())
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::async_router::{
+    AsyncHandler,
+    AsyncRouter,
+};
+
+#[cfg(feature = "tokio")]
+mod async_router {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+
+    use tokio::task::JoinSet;
+
+    use super::{
+        DeadLetter,
+        DispatchMode,
+        Permissions,
+        QueueBalancing,
+        RouteOptions,
+        RouteTable,
+    };
+    use crate::error::{
+        Result,
+        SubjectError,
+    };
+    use crate::pattern::Pattern;
+    use crate::subject::Subject;
+
+    /// An async route handler
+    pub type AsyncHandler<M> = Arc<
+        dyn Fn(Subject, Arc<M>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync,
+    >;
+
+    /// Dispatches messages to async handlers by subject
+    ///
+    /// Middleware stays synchronous and runs before any handler.
+    pub struct AsyncRouter<M> {
+        table: RouteTable<AsyncHandler<M>, M>,
+    }
+
+    impl<M> Default for AsyncRouter<M> {
+        fn default() -> Self {
+            Self {
+                table: RouteTable::default(),
+            }
+        }
+    }
+
+    impl<M: Send + Sync + 'static> AsyncRouter<M> {
+        /// Create a router with no routes that dispatches to the most
+        /// specific match
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set which matching routes receive a message
+        #[must_use]
+        pub fn dispatch_mode(mut self, mode: DispatchMode) -> Self {
+            self.table.mode = mode;
+            self
+        }
+
+        /// Add an async handler for subjects matching `pattern`
+        #[must_use]
+        pub fn route<F, Fut>(mut self, pattern: Pattern, handler: F) -> Self
+        where
+            F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+        {
+            self.table
+                .add(pattern, RouteOptions::default(), boxed(handler));
+            self
+        }
+
+        /// Add an async handler to the queue group `group` for `pattern`;
+        /// each message the group receives goes to one of its members
+        #[must_use]
+        pub fn queue_route<F, Fut>(
+            self,
+            pattern: Pattern,
+            group: impl Into<String>,
+            handler: F,
+        ) -> Self
+        where
+            F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+        {
+            self.queue_route_with_options(pattern, group, RouteOptions::default(), handler)
+        }
+
+        /// Add an async handler to a queue group with permissions, retries
+        /// or dead-lettering
+        ///
+        /// The options apply to the whole group and are taken from the
+        /// member that creates it; later members share them.
+        #[must_use]
+        pub fn queue_route_with_options<F, Fut>(
+            mut self,
+            pattern: Pattern,
+            group: impl Into<String>,
+            options: RouteOptions,
+            handler: F,
+        ) -> Self
+        where
+            F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+        {
+            self.table
+                .add_to_queue(pattern, group.into(), options, boxed(handler));
+            self
+        }
+
+        /// Set how queue groups pick a member
+        #[must_use]
+        pub fn queue_balancing(mut self, balancing: QueueBalancing) -> Self {
+            self.table.balancing = balancing;
+            self
+        }
+
+        /// Add an async handler that only receives subjects `permissions`
+        /// allow subscribing to
+        #[must_use]
+        pub fn route_with_permissions<F, Fut>(
+            self,
+            pattern: Pattern,
+            permissions: Permissions,
+            handler: F,
+        ) -> Self
+        where
+            F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+        {
+            self.route_with_options(
+                pattern,
+                RouteOptions::new().permissions(permissions),
+                handler,
+            )
+        }
+
+        /// Add an async handler with permissions, retries or dead-lettering
+        #[must_use]
+        pub fn route_with_options<F, Fut>(
+            mut self,
+            pattern: Pattern,
+            options: RouteOptions,
+            handler: F,
+        ) -> Self
+        where
+            F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+        {
+            self.table.add(pattern, options, boxed(handler));
+            self
+        }
+
+        /// Receive messages that failed every attempt on a route with a
+        /// dead-letter pattern
+        #[must_use]
+        pub fn dead_letter_sink<F>(mut self, sink: F) -> Self
+        where F: Fn(&DeadLetter, &M) -> Result<()> + Send + Sync + 'static {
+            self.table.dead_letters = Some(Arc::new(sink));
+            self
+        }
+
+        /// Add middleware, run in registration order before any handler
+        #[must_use]
+        pub fn middleware<F>(mut self, middleware: F) -> Self
+        where F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static {
+            self.table.middleware.push(Arc::new(middleware));
+            self
+        }
+
+        /// Route patterns, most specific first
+        #[must_use]
+        pub fn patterns(&self) -> Vec<&Pattern> {
+            self.table.patterns()
+        }
+
+        /// Dispatch a message, returning how many handlers ran
+        ///
+        /// Matching handlers run concurrently on the current Tokio runtime,
+        /// and all of them run to completion, including retries, even if one
+        /// fails.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - Middleware rejects the message
+        /// - No route matches the subject
+        /// - The permissions of the selected routes deny the subject
+        /// - A handler panics, or fails every attempt and its route has no
+        ///   dead-letter pattern, or dead-lettering fails; the first failure is
+        ///   returned
+        #[cfg_attr(
+            feature = "tracing",
+            tracing::instrument(level = "debug", skip_all, fields(subject = %subject), err)
+        )]
+        pub async fn dispatch(&self, subject: &Subject, message: M) -> Result<usize> {
+            let handlers = self.table.select(subject, &message)?;
+            let message = Arc::new(message);
+
+            let mut tasks = JoinSet::new();
+            for (index, (options, handler)) in handlers.iter().enumerate() {
+                let (handler, retry) = (Arc::clone(handler), options.retry);
+                let (subject, message) = (subject.clone(), message.clone());
+                let task = async move {
+                    let mut attempt = 1;
+                    loop {
+                        match handler(subject.clone(), message.clone()).await {
+                            Err(_) if attempt < retry.max_attempts => {
+                                #[cfg(feature = "tracing")]
+                                tracing::debug!(attempt, "handler failed, retrying");
+                                tokio::time::sleep(retry.backoff(attempt)).await;
+                                attempt += 1;
+                            },
+                            outcome => return (index, outcome),
+                        }
+                    }
+                };
+                // Keep handler events inside the dispatch span
+                #[cfg(feature = "tracing")]
+                let task = tracing::Instrument::in_current_span(task);
+                tasks.spawn(task);
+            }
+
+            let mut first_error = None;
+            while let Some(joined) = tasks.join_next().await {
+                let outcome = match joined {
+                    Ok((_, Ok(()))) => Ok(()),
+                    Ok((index, Err(error))) => {
+                        self.table
+                            .dead_letter(handlers[index].0, subject, &message, error)
+                    },
+                    Err(e) => Err(SubjectError::validation_error(format!(
+                        "Handler for '{subject}' panicked: {e}"
+                    ))),
+                };
+                if let Err(e) = outcome {
+                    first_error.get_or_insert(e);
+                }
+            }
+            first_error.map_or(Ok(handlers.len()), Err)
+        }
+    }
+
+    fn boxed<M, F, Fut>(handler: F) -> AsyncHandler<M>
+    where
+        F: Fn(Subject, Arc<M>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Arc::new(move |subject, message| Box::pin(handler(subject, message)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::permissions::{
+        PermissionsBuilder,
+        Policy,
+    };
+
+    fn pattern(s: &str) -> Pattern {
+        Pattern::new(s).unwrap()
+    }
+
+    fn subject(s: &str) -> Subject {
+        Subject::new(s).unwrap()
+    }
+
+    fn recording_router(log: &Arc<Mutex<Vec<&'static str>>>, mode: DispatchMode) -> Router<()> {
+        let record = |name: &'static str| {
+            let log = log.clone();
+            move |_: &Subject, (): &()| {
+                log.lock().unwrap().push(name);
+                Ok(())
+            }
+        };
+        Router::new()
+            .dispatch_mode(mode)
+            .route(pattern("orders.>"), record("all orders"))
+            .route(pattern("orders.order.placed.v1"), record("placed"))
+            .route(pattern("orders.*.placed.*"), record("any placed"))
+    }
+
+    #[test]
+    fn test_dispatch_modes() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let placed = subject("orders.order.placed.v1");
+
+        let router = recording_router(&log, DispatchMode::MostSpecific);
+        assert_eq!(router.dispatch(&placed, &()).unwrap(), 1);
+        assert_eq!(*log.lock().unwrap(), vec!["placed"]);
+
+        log.lock().unwrap().clear();
+        let router = recording_router(&log, DispatchMode::All);
+        assert_eq!(router.dispatch(&placed, &()).unwrap(), 3);
+        assert_eq!(*log.lock().unwrap(), vec![
+            "placed",
+            "any placed",
+            "all orders"
+        ]);
+
+        assert!(matches!(
+            router.dispatch(&subject("billing.invoice.sent.v1"), &()),
+            Err(SubjectError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_middleware_and_permissions() {
+        let deny_placed = PermissionsBuilder::new()
+            .default_policy(Policy::Allow)
+            .deny("orders.order.placed.*", &[Operation::Subscribe])
+            .unwrap()
+            .build();
+        let router = Router::<u32>::new()
+            .middleware(|_, amount| {
+                if *amount == 0 {
+                    Err(SubjectError::validation_error("empty order"))
+                } else {
+                    Ok(())
+                }
+            })
+            .route_with_permissions(pattern("orders.order.*.v1"), deny_placed, |_, _| Ok(()));
+
+        assert!(router
+            .dispatch(&subject("orders.order.shipped.v1"), &5)
+            .is_ok());
+        assert!(matches!(
+            router.dispatch(&subject("orders.order.placed.v1"), &5),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            router.dispatch(&subject("orders.order.shipped.v1"), &0),
+            Err(SubjectError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_queue_groups() {
+        let counts = Arc::new(Mutex::new([0_usize; 4]));
+        let count = |index: usize| {
+            let counts = counts.clone();
+            move |_: &Subject, (): &()| {
+                counts.lock().unwrap()[index] += 1;
+                Ok(())
+            }
+        };
+        let router = Router::new()
+            .dispatch_mode(DispatchMode::All)
+            .queue_route(pattern("orders.>"), "workers", count(0))
+            .queue_route(pattern("orders.>"), "workers", count(1))
+            .queue_route(pattern("orders.>"), "workers", count(2))
+            .route(pattern("orders.>"), count(3));
+        assert_eq!(router.patterns().len(), 2);
+
+        let placed = subject("orders.order.placed.v1");
+        for _ in 0..6 {
+            assert_eq!(router.dispatch(&placed, &()).unwrap(), 2);
+        }
+        assert_eq!(*counts.lock().unwrap(), [2, 2, 2, 6]);
+
+        // Random balancing still delivers each message to one member
+        *counts.lock().unwrap() = [0; 4];
+        let router = Router::new()
+            .queue_balancing(QueueBalancing::Random)
+            .queue_route(pattern("orders.>"), "workers", count(0))
+            .queue_route(pattern("orders.>"), "workers", count(1));
+        for _ in 0..10 {
+            router.dispatch(&placed, &()).unwrap();
+        }
+        assert_eq!(counts.lock().unwrap().iter().sum::<usize>(), 10);
+
+        // Options set when the group is created apply to every member
+        *counts.lock().unwrap() = [0; 4];
+        let permissions = PermissionsBuilder::new()
+            .allow("orders.order.>", &[Operation::QueueSubscribe])
+            .unwrap()
+            .build();
+        let router = Router::new()
+            .queue_route_with_options(
+                pattern("orders.>"),
+                "workers",
+                RouteOptions::new().permissions(permissions),
+                count(0),
+            )
+            .queue_route(pattern("orders.>"), "workers", count(1));
+        for _ in 0..4 {
+            router.dispatch(&placed, &()).unwrap();
+        }
+        assert!(router
+            .dispatch(&subject("orders.return.received.v1"), &())
+            .is_err());
+        assert_eq!(*counts.lock().unwrap(), [2, 2, 0, 0]);
+
+        // A queue-only rule admits the group it names
+        let queue_only = |group: &str| {
+            Router::new().queue_route_with_options(
+                pattern("orders.>"),
+                group,
+                RouteOptions::new().permissions(
+                    Permissions::from_nats_config(r#"subscribe: ["orders.> workers"]"#).unwrap(),
+                ),
+                count(0),
+            )
+        };
+        assert!(queue_only("workers").dispatch(&placed, &()).is_ok());
+        assert!(queue_only("others").dispatch(&placed, &()).is_err());
+    }
+
+    #[test]
+    fn test_retry_and_dead_letter() {
+        let attempts = Arc::new(Mutex::new(0));
+        let dead = Arc::new(Mutex::new(Vec::new()));
+        let failing = {
+            let attempts = attempts.clone();
+            move |_: &Subject, _: &String| {
+                *attempts.lock().unwrap() += 1;
+                Err(SubjectError::validation_error("stock service down"))
+            }
+        };
+        let router = Router::new()
+            .route_with_options(
+                pattern("orders.commands.>"),
+                RouteOptions::new()
+                    .retry(
+                        RetryPolicy::attempts(3)
+                            .with_backoff(Duration::from_millis(1), Duration::from_millis(2)),
+                    )
+                    .dead_letter(pattern("orders.dlq.>")),
+                failing,
+            )
+            .dead_letter_sink({
+                let dead = dead.clone();
+                move |letter: &DeadLetter, message: &String| {
+                    dead.lock().unwrap().push((letter.clone(), message.clone()));
+                    Ok(())
+                }
+            });
+
+        let place = subject("orders.commands.order.place");
+        assert_eq!(router.dispatch(&place, &"order 7".to_string()).unwrap(), 1);
+        assert_eq!(*attempts.lock().unwrap(), 3);
+        let dead = dead.lock().unwrap();
+        let (letter, message) = &dead[0];
+        assert_eq!(letter.subject.as_str(), "orders.dlq.order.place");
+        assert_eq!(letter.original_subject, place);
+        assert_eq!(letter.attempts, 3);
+        assert_eq!(message, "order 7");
+
+        let policy = RetryPolicy::attempts(5)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(25));
+        assert_eq!(policy.backoff(1), Duration::from_millis(10));
+        assert_eq!(policy.backoff(2), Duration::from_millis(20));
+        assert_eq!(policy.backoff(3), Duration::from_millis(25));
+        assert!(dead_letter_subject(&pattern("orders.dlq"), &place).is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_router_runs_all_matches() {
+        use std::sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        };
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let count = |calls: &Arc<AtomicUsize>| {
+            let calls = calls.clone();
+            move |_: Subject, amount: Arc<usize>| {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(*amount, Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+        };
+        let router = AsyncRouter::new()
+            .dispatch_mode(DispatchMode::All)
+            .route(pattern("orders.>"), count(&calls))
+            .route(pattern("orders.order.*.v1"), count(&calls))
+            .route(pattern("orders.order.shipped.v1"), |_, _| async {
+                Err(SubjectError::validation_error("carrier offline"))
+            });
+
+        assert_eq!(
+            router
+                .dispatch(&subject("orders.order.placed.v1"), 2)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(router
+            .dispatch(&subject("orders.order.shipped.v1"), 1)
+            .await
+            .is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+}
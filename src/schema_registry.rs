@@ -0,0 +1,326 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Payload schemas registered per subject pattern
+//!
+//! A [`SubjectRegistry`] answers "what payload shape travels on
+//! `orders.order.created.v2`" by mapping patterns to [`SchemaRef`]s, JSON
+//! Schema or Avro references owned by a schema store. The most specific
+//! matching pattern decides, so a family-wide schema can be overridden for
+//! one event type. Registering a pattern that overlaps another without
+//! either covering the other is a [`SchemaConflict`] when their schemas
+//! differ, since neither can be said to override the other.
+//!
+//! The registry serializes with serde, so it can be kept in a file or a
+//! key-value bucket next to the services that consult it.
+//!
+//! ```
+//! use cim_subject::schema_registry::{
+//!     SchemaRef,
+//!     SubjectRegistry,
+//! };
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//!
+//! let mut registry = SubjectRegistry::new();
+//! registry
+//!     .register(
+//!         Pattern::new("orders.order.created.v2")?,
+//!         SchemaRef::json_schema("https://schemas.example.com/order-created-v2.json"),
+//!     )
+//!     .unwrap();
+//!
+//! let subject = Subject::new("orders.order.created.v2")?;
+//! assert_eq!(
+//!     registry.lookup(&subject).unwrap().id,
+//!     "https://schemas.example.com/order-created-v2.json"
+//! );
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// The kind of schema a reference points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SchemaFormat {
+    /// A JSON Schema document, usually referenced by URI
+    JsonSchema,
+    /// An Avro schema, usually referenced by full name or registry ID
+    Avro,
+}
+
+/// A reference to a payload schema
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SchemaRef {
+    /// The schema language
+    pub format: SchemaFormat,
+    /// The schema's identifier in its store
+    pub id: String,
+}
+
+impl SchemaRef {
+    /// Reference a JSON Schema
+    #[must_use]
+    pub fn json_schema(id: impl Into<String>) -> Self {
+        Self {
+            format: SchemaFormat::JsonSchema,
+            id: id.into(),
+        }
+    }
+
+    /// Reference an Avro schema
+    #[must_use]
+    pub fn avro(id: impl Into<String>) -> Self {
+        Self {
+            format: SchemaFormat::Avro,
+            id: id.into(),
+        }
+    }
+}
+
+impl Display for SchemaRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self.format {
+            SchemaFormat::JsonSchema => "json-schema",
+            SchemaFormat::Avro => "avro",
+        };
+        write!(f, "{format}:{}", self.id)
+    }
+}
+
+/// A pattern and the schema of payloads on its subjects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaEntry {
+    /// Subjects the schema applies to
+    pub pattern: Pattern,
+    /// The payload schema
+    pub schema: SchemaRef,
+}
+
+/// Two overlapping patterns with different schemas, neither covering the
+/// other
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaConflict {
+    /// The entry being registered, or the later of two registered entries
+    pub entry: SchemaEntry,
+    /// The entry it conflicts with
+    pub existing: SchemaEntry,
+}
+
+impl Display for SchemaConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' ({}) overlaps '{}' ({})",
+            self.entry.pattern, self.entry.schema, self.existing.pattern, self.existing.schema
+        )
+    }
+}
+
+impl std::error::Error for SchemaConflict {}
+
+/// Payload schemas by subject pattern
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubjectRegistry {
+    entries: Vec<SchemaEntry>,
+}
+
+impl SubjectRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the schema for a pattern
+    ///
+    /// Registering the same pattern and schema again does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first conflict if the pattern is already registered with
+    /// another schema, or overlaps a pattern with another schema without
+    /// either covering the other
+    pub fn register(
+        &mut self,
+        pattern: Pattern,
+        schema: SchemaRef,
+    ) -> std::result::Result<(), Box<SchemaConflict>> {
+        let entry = SchemaEntry { pattern, schema };
+        if let Some(existing) = self.entries.iter().find(|e| conflicting(&entry, e)) {
+            return Err(Box::new(SchemaConflict {
+                entry,
+                existing: existing.clone(),
+            }));
+        }
+        if !self.entries.contains(&entry) {
+            self.entries.push(entry);
+        }
+        Ok(())
+    }
+
+    /// Remove a pattern, returning its schema
+    pub fn unregister(&mut self, pattern: &Pattern) -> Option<SchemaRef> {
+        let index = self.entries.iter().position(|e| e.pattern == *pattern)?;
+        Some(self.entries.remove(index).schema)
+    }
+
+    /// The schema of payloads on a subject, from the most specific matching
+    /// pattern
+    #[must_use]
+    pub fn lookup(&self, subject: &Subject) -> Option<&SchemaRef> {
+        self.lookup_entry(subject).map(|entry| &entry.schema)
+    }
+
+    /// The most specific entry matching a subject
+    #[must_use]
+    pub fn lookup_entry(&self, subject: &Subject) -> Option<&SchemaEntry> {
+        // `min_by_key` keeps the first of equally specific entries
+        self.entries
+            .iter()
+            .filter(|entry| entry.pattern.matches(subject))
+            .min_by_key(|entry| entry.pattern.specificity_key())
+    }
+
+    /// Patterns registered for a schema
+    pub fn patterns_for<'a>(&'a self, schema: &'a SchemaRef) -> impl Iterator<Item = &'a Pattern> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.schema == *schema)
+            .map(|entry| &entry.pattern)
+    }
+
+    /// Registered entries in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &SchemaEntry> {
+        self.entries.iter()
+    }
+
+    /// Every conflict between registered entries, e.g. in a registry that
+    /// was deserialized from a hand-edited file
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<SchemaConflict> {
+        self.entries
+            .iter()
+            .enumerate()
+            .flat_map(|(i, entry)| {
+                self.entries[..i]
+                    .iter()
+                    .filter(|existing| conflicting(entry, existing))
+                    .map(|existing| SchemaConflict {
+                        entry: entry.clone(),
+                        existing: existing.clone(),
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Whether two entries disagree on some subject with no override between
+/// them
+fn conflicting(a: &SchemaEntry, b: &SchemaEntry) -> bool {
+    if a.schema == b.schema || !a.pattern.overlaps(&b.pattern) {
+        return false;
+    }
+    let a_covers = a.pattern.subsumes(&b.pattern);
+    let b_covers = b.pattern.subsumes(&a.pattern);
+    // Equal patterns cover each other and genuinely conflict
+    a_covers == b_covers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> Pattern {
+        Pattern::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_lookup_most_specific() {
+        let mut registry = SubjectRegistry::new();
+        registry
+            .register(p("orders.>"), SchemaRef::avro("com.example.OrderEvent"))
+            .unwrap();
+        registry
+            .register(
+                p("orders.order.created.v2"),
+                SchemaRef::json_schema("created-v2"),
+            )
+            .unwrap();
+        // Re-registering is idempotent
+        registry
+            .register(p("orders.>"), SchemaRef::avro("com.example.OrderEvent"))
+            .unwrap();
+        assert_eq!(registry.iter().count(), 2);
+
+        let lookup = |s: &str| {
+            registry
+                .lookup(&Subject::new(s).unwrap())
+                .map(ToString::to_string)
+        };
+        assert_eq!(
+            lookup("orders.order.created.v2").unwrap(),
+            "json-schema:created-v2"
+        );
+        assert_eq!(
+            lookup("orders.order.created.v1").unwrap(),
+            "avro:com.example.OrderEvent"
+        );
+        assert!(lookup("billing.invoice.paid.v1").is_none());
+
+        let json = serde_json::to_string(&registry).unwrap();
+        let restored: SubjectRegistry = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, registry);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut registry = SubjectRegistry::new();
+        registry
+            .register(p("orders.*.created.v2"), SchemaRef::json_schema("created"))
+            .unwrap();
+
+        let conflict = registry
+            .register(p("orders.order.*.v2"), SchemaRef::json_schema("order"))
+            .unwrap_err();
+        assert_eq!(conflict.existing.pattern, p("orders.*.created.v2"));
+        assert!(registry
+            .register(p("orders.*.created.v2"), SchemaRef::avro("created"))
+            .is_err());
+        // Same schema, or a pattern covering the other, is fine
+        registry
+            .register(p("orders.order.*.v2"), SchemaRef::json_schema("created"))
+            .unwrap();
+        registry
+            .register(p("orders.>"), SchemaRef::json_schema("any"))
+            .unwrap();
+        assert!(registry.conflicts().is_empty());
+
+        // As if deserialized from a hand-edited file
+        let edited = SubjectRegistry {
+            entries: vec![
+                SchemaEntry {
+                    pattern: p("a.*.c.v1"),
+                    schema: SchemaRef::avro("x"),
+                },
+                SchemaEntry {
+                    pattern: p("a.b.*.v1"),
+                    schema: SchemaRef::avro("y"),
+                },
+            ],
+        };
+        assert_eq!(edited.conflicts().len(), 1);
+    }
+}
@@ -0,0 +1,371 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Validating payloads against registered JSON Schemas
+//!
+//! A [`RegistryValidator`] pairs a [`SubjectRegistry`] with compiled JSON
+//! Schema documents keyed by their [`SchemaRef`] ID. Publishers call
+//! [`validate`](RegistryValidator::validate) before sending, or install
+//! [`middleware`](RegistryValidator::middleware) on a `Router<Value>`, and
+//! get a [`PayloadValidationError`] listing every violation with its JSON
+//! pointer into the payload.
+//!
+//! ```
+//! use cim_subject::schema_registry::{
+//!     SchemaRef,
+//!     SubjectRegistry,
+//! };
+//! use cim_subject::schema_validation::{
+//!     PayloadValidationError,
+//!     RegistryValidator,
+//! };
+//! use cim_subject::{
+//!     Pattern,
+//!     Subject,
+//! };
+//! use serde_json::json;
+//!
+//! let mut registry = SubjectRegistry::new();
+//! registry
+//!     .register(Pattern::new("orders.order.created.v2")?, SchemaRef::json_schema("order-created"))
+//!     .unwrap();
+//!
+//! let mut validator = RegistryValidator::new(registry);
+//! validator.add_schema(
+//!     "order-created",
+//!     &json!({"type": "object", "required": ["order_id"]}),
+//! )?;
+//!
+//! let subject = Subject::new("orders.order.created.v2")?;
+//! assert!(validator.validate(&subject, &json!({"order_id": "o-1"})).is_ok());
+//! let err = validator.validate(&subject, &json!({})).unwrap_err();
+//! let PayloadValidationError::Invalid { violations, .. } = *err else {
+//!     panic!("expected violations");
+//! };
+//! assert_eq!(violations.len(), 1);
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{
+    self,
+    Display,
+};
+use std::sync::Arc;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::schema_registry::{
+    SchemaFormat,
+    SchemaRef,
+    SubjectRegistry,
+};
+use crate::subject::Subject;
+
+/// One way a payload breaks its schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value in the payload
+    pub instance_path: String,
+    /// JSON pointer to the schema keyword that failed
+    pub schema_path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.instance_path.is_empty() {
+            "/"
+        } else {
+            &self.instance_path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+/// Why a payload was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadValidationError {
+    /// No schema is registered for the subject
+    Unregistered {
+        /// The subject published on
+        subject: Subject,
+    },
+    /// The registered schema is not a JSON Schema
+    UnsupportedFormat {
+        /// The subject published on
+        subject: Subject,
+        /// The registered schema
+        schema: SchemaRef,
+    },
+    /// The registered schema was never added to the validator
+    UnknownSchema {
+        /// The subject published on
+        subject: Subject,
+        /// The registered schema
+        schema: SchemaRef,
+    },
+    /// The payload does not conform to its schema
+    Invalid {
+        /// The subject published on
+        subject: Subject,
+        /// The schema checked against
+        schema: SchemaRef,
+        /// Every violation, in the order the schema reports them
+        violations: Vec<SchemaViolation>,
+    },
+}
+
+impl Display for PayloadValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unregistered { subject } => write!(f, "no schema registered for '{subject}'"),
+            Self::UnsupportedFormat { subject, schema } => {
+                write!(f, "'{subject}' uses {schema}, which is not a JSON Schema")
+            },
+            Self::UnknownSchema { subject, schema } => {
+                write!(f, "'{subject}' uses {schema}, which was not loaded")
+            },
+            Self::Invalid {
+                subject,
+                schema,
+                violations,
+            } => {
+                write!(f, "payload on '{subject}' violates {schema}")?;
+                for violation in violations {
+                    write!(f, "; {violation}")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for PayloadValidationError {}
+
+impl From<Box<PayloadValidationError>> for SubjectError {
+    fn from(err: Box<PayloadValidationError>) -> Self {
+        SubjectError::validation_error(err.to_string())
+    }
+}
+
+/// Checks payloads against the JSON Schema registered for their subject
+#[derive(Debug, Clone)]
+pub struct RegistryValidator {
+    registry: SubjectRegistry,
+    schemas: HashMap<String, Arc<jsonschema::Validator>>,
+    allow_unregistered: bool,
+}
+
+impl RegistryValidator {
+    /// Create a validator with no schema documents loaded
+    #[must_use]
+    pub fn new(registry: SubjectRegistry) -> Self {
+        Self {
+            registry,
+            schemas: HashMap::new(),
+            allow_unregistered: false,
+        }
+    }
+
+    /// Accept any payload on subjects with no registered schema instead of
+    /// rejecting it
+    #[must_use]
+    pub fn allow_unregistered(mut self) -> Self {
+        self.allow_unregistered = true;
+        self
+    }
+
+    /// The registry consulted for each subject
+    #[must_use]
+    pub fn registry(&self) -> &SubjectRegistry {
+        &self.registry
+    }
+
+    /// Compile and load the JSON Schema document with the given ID,
+    /// replacing any earlier document with that ID
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a valid JSON Schema
+    pub fn add_schema(&mut self, id: impl Into<String>, document: &Value) -> Result<()> {
+        let id = id.into();
+        let compiled = jsonschema::validator_for(document).map_err(|e| {
+            SubjectError::validation_error(format!("Invalid JSON Schema '{id}': {e}"))
+        })?;
+        self.schemas.insert(id, Arc::new(compiled));
+        Ok(())
+    }
+
+    /// Check a payload against the schema registered for its subject
+    ///
+    /// # Errors
+    ///
+    /// Returns every violation if the payload does not conform, or why it
+    /// could not be checked
+    pub fn validate(
+        &self,
+        subject: &Subject,
+        payload: &Value,
+    ) -> std::result::Result<(), Box<PayloadValidationError>> {
+        let Some(schema) = self.registry.lookup(subject) else {
+            if self.allow_unregistered {
+                return Ok(());
+            }
+            return Err(Box::new(PayloadValidationError::Unregistered {
+                subject: subject.clone(),
+            }));
+        };
+        if schema.format != SchemaFormat::JsonSchema {
+            return Err(Box::new(PayloadValidationError::UnsupportedFormat {
+                subject: subject.clone(),
+                schema: schema.clone(),
+            }));
+        }
+        let Some(compiled) = self.schemas.get(&schema.id) else {
+            return Err(Box::new(PayloadValidationError::UnknownSchema {
+                subject: subject.clone(),
+                schema: schema.clone(),
+            }));
+        };
+
+        let violations: Vec<SchemaViolation> = compiled
+            .iter_errors(payload)
+            .map(|error| SchemaViolation {
+                instance_path: error.instance_path.to_string(),
+                schema_path: error.schema_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Box::new(PayloadValidationError::Invalid {
+                subject: subject.clone(),
+                schema: schema.clone(),
+                violations,
+            }))
+        }
+    }
+
+    /// Router middleware rejecting messages whose payload fails
+    /// [`validate`](Self::validate)
+    pub fn middleware(self) -> impl Fn(&Subject, &Value) -> Result<()> + Send + Sync + 'static {
+        move |subject, payload| Ok(self.validate(subject, payload)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::router::Router;
+
+    fn validator() -> RegistryValidator {
+        let mut registry = SubjectRegistry::new();
+        registry
+            .register(
+                Pattern::new("orders.order.*.v1").unwrap(),
+                SchemaRef::json_schema("order"),
+            )
+            .unwrap();
+        registry
+            .register(
+                Pattern::new("orders.payment.*.v1").unwrap(),
+                SchemaRef::avro("com.example.Payment"),
+            )
+            .unwrap();
+        registry
+            .register(
+                Pattern::new("orders.refund.*.v1").unwrap(),
+                SchemaRef::json_schema("refund"),
+            )
+            .unwrap();
+
+        let mut validator = RegistryValidator::new(registry);
+        validator
+            .add_schema(
+                "order",
+                &json!({
+                    "type": "object",
+                    "required": ["order_id", "total"],
+                    "properties": {"total": {"type": "number", "minimum": 0}}
+                }),
+            )
+            .unwrap();
+        validator
+    }
+
+    fn subject(s: &str) -> Subject {
+        Subject::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_validate_reports_violations() {
+        let validator = validator();
+        let placed = subject("orders.order.placed.v1");
+        assert!(validator
+            .validate(&placed, &json!({"order_id": "o-1", "total": 10}))
+            .is_ok());
+
+        let err = validator
+            .validate(&placed, &json!({"total": -1}))
+            .unwrap_err();
+        let PayloadValidationError::Invalid {
+            schema, violations, ..
+        } = &*err
+        else {
+            panic!("expected violations, got {err}");
+        };
+        assert_eq!(schema.id, "order");
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|v| v.instance_path == "/total"));
+
+        assert!(matches!(
+            validator.validate(&subject("orders.payment.captured.v1"), &json!({})),
+            Err(e) if matches!(*e, PayloadValidationError::UnsupportedFormat { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&subject("orders.refund.issued.v1"), &json!({})),
+            Err(e) if matches!(*e, PayloadValidationError::UnknownSchema { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&subject("billing.invoice.paid.v1"), &json!({})),
+            Err(e) if matches!(*e, PayloadValidationError::Unregistered { .. })
+        ));
+        assert!(validator
+            .clone()
+            .allow_unregistered()
+            .validate(&subject("billing.invoice.paid.v1"), &json!({}))
+            .is_ok());
+        assert!(RegistryValidator::new(SubjectRegistry::new())
+            .add_schema("bad", &json!({"type": 12}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_middleware_rejects_invalid_payloads() {
+        let router = Router::<Value>::new()
+            .middleware(validator().middleware())
+            .route(Pattern::new("orders.>").unwrap(), |_, _| Ok(()));
+
+        let placed = subject("orders.order.placed.v1");
+        assert_eq!(
+            router
+                .dispatch(&placed, &json!({"order_id": "o-1", "total": 1}))
+                .unwrap(),
+            1
+        );
+        assert!(router.dispatch(&placed, &json!({"order_id": 7})).is_err());
+    }
+}
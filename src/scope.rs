@@ -0,0 +1,119 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Ambient subject context
+//!
+//! Handler code for one bounded context builds many subjects in the same
+//! context. Entering a [`SubjectScope`] sets that context for the current
+//! thread, and [`SubjectBuilder`](crate::SubjectBuilder) uses it when no
+//! context is given explicitly. Scopes nest; leaving one restores the
+//! context outside it, even if scopes are left out of order.
+//!
+//! ```
+//! use cim_subject::scope::SubjectScope;
+//! use cim_subject::SubjectBuilder;
+//!
+//! let _scope = SubjectScope::enter("orders");
+//! let subject = SubjectBuilder::new()
+//!     .aggregate("order")
+//!     .event_type("placed")
+//!     .version("v1")
+//!     .build()?;
+//! assert_eq!(subject.as_str(), "orders.order.placed.v1");
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::cell::{
+    Cell,
+    RefCell,
+};
+use std::marker::PhantomData;
+
+thread_local! {
+    /// Contexts of the scopes entered on this thread with their scope IDs,
+    /// innermost last
+    static CONTEXTS: RefCell<Vec<(u64, String)>> = const { RefCell::new(Vec::new()) };
+    /// ID for the next scope entered on this thread
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Guard for an ambient context, left when dropped
+///
+/// The guard is tied to the thread that entered it. Dropping it removes its
+/// own context, so a guard dropped while an inner scope is still entered
+/// leaves the inner context in place.
+#[derive(Debug)]
+pub struct SubjectScope {
+    id: u64,
+    _thread: PhantomData<*const ()>,
+}
+
+impl SubjectScope {
+    /// Make `context` the ambient context on this thread until the returned
+    /// guard is dropped
+    #[must_use = "the scope is left as soon as the guard is dropped"]
+    pub fn enter(context: impl Into<String>) -> Self {
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        CONTEXTS.with(|contexts| contexts.borrow_mut().push((id, context.into())));
+        Self {
+            id,
+            _thread: PhantomData,
+        }
+    }
+
+    /// The context of the innermost scope entered on this thread
+    #[must_use]
+    pub fn current_context() -> Option<String> {
+        CONTEXTS.with(|contexts| contexts.borrow().last().map(|(_, context)| context.clone()))
+    }
+}
+
+impl Drop for SubjectScope {
+    fn drop(&mut self) {
+        CONTEXTS.with(|contexts| {
+            let mut contexts = contexts.borrow_mut();
+            if let Some(index) = contexts.iter().rposition(|(id, _)| *id == self.id) {
+                contexts.remove(index);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subject::SubjectBuilder;
+
+    #[test]
+    fn test_nested_scopes() {
+        assert_eq!(SubjectScope::current_context(), None);
+        let outer = SubjectScope::enter("orders");
+        {
+            let _inner = SubjectScope::enter("billing");
+            assert_eq!(SubjectScope::current_context().as_deref(), Some("billing"));
+        }
+        assert_eq!(SubjectScope::current_context().as_deref(), Some("orders"));
+
+        // An explicit context wins over the ambient one
+        let subject = SubjectBuilder::new()
+            .context("shipping")
+            .aggregate("parcel")
+            .event_type("sent")
+            .version("v1")
+            .build()
+            .unwrap();
+        assert_eq!(subject.context(), "shipping");
+
+        drop(outer);
+        assert_eq!(SubjectScope::current_context(), None);
+    }
+
+    #[test]
+    fn test_out_of_order_drop() {
+        let outer = SubjectScope::enter("orders");
+        let inner = SubjectScope::enter("billing");
+        drop(outer);
+        assert_eq!(SubjectScope::current_context().as_deref(), Some("billing"));
+        drop(inner);
+        assert_eq!(SubjectScope::current_context(), None);
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Signed identity headers
+//!
+//! An [`IdentitySigner`] signs a message's subject together with its
+//! message, correlation and causation IDs, and stores the signature in the
+//! [`SIGNATURE_HEADER`]. An [`IdentityVerifier`] holding the matching key
+//! checks it on receipt, so a service can trust that no intermediary
+//! rewrote the causation chain or replayed the identity on another subject.
+//!
+//! Signatures are HMAC-SHA256 with a shared secret or Ed25519 with a key
+//! pair, written as `<algorithm>:<base64 signature>`. The payload is not
+//! covered.
+//!
+//! ```
+//! use cim_subject::signing::{
+//!     IdentitySigner,
+//!     IdentityVerifier,
+//! };
+//! use cim_subject::{
+//!     IdType,
+//!     MessageIdentity,
+//!     NatsMessage,
+//! };
+//! use uuid::Uuid;
+//!
+//! let signer = IdentitySigner::hmac_sha256(b"shared secret".to_vec());
+//! let verifier = IdentityVerifier::hmac_sha256(b"shared secret".to_vec());
+//!
+//! let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+//! let mut message = NatsMessage::with_correlation(
+//!     "orders.order.placed.v1".to_string(),
+//!     serde_json::json!({"order_id": 7}),
+//!     &identity,
+//! );
+//! signer.sign_message(&mut message)?;
+//! assert_eq!(verifier.verify_message(&message)?, identity);
+//!
+//! // Moving the message to another subject breaks the signature
+//! message.subject = "orders.order.cancelled.v1".to_string();
+//! assert!(verifier.verify_message(&message).is_err());
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{
+    Signer,
+    SigningKey,
+    Verifier,
+    VerifyingKey,
+};
+use hmac::{
+    Hmac,
+    Mac,
+};
+use sha2::Sha256;
+
+use crate::correlation::MessageIdentity;
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::translator::NatsMessage;
+
+/// Header carrying the identity signature
+pub const SIGNATURE_HEADER: &str = "X-Identity-Signature";
+
+/// How an identity signature is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureAlgorithm {
+    /// HMAC-SHA256 with a shared secret
+    HmacSha256,
+    /// Ed25519 with a key pair
+    Ed25519,
+}
+
+impl SignatureAlgorithm {
+    /// The algorithm's name in signature headers
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HmacSha256 => "hmac-sha256",
+            Self::Ed25519 => "ed25519",
+        }
+    }
+}
+
+impl Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The bytes a signature covers: the subject and the three identity IDs,
+/// one per line after a version tag
+#[must_use]
+pub fn signing_input(subject: &str, identity: &MessageIdentity) -> Vec<u8> {
+    format!(
+        "cim-identity-v1\n{subject}\n{}\n{}\n{}",
+        identity.message_id, identity.correlation_id.0, identity.causation_id.0
+    )
+    .into_bytes()
+}
+
+/// Signs message identities
+#[derive(Clone)]
+pub struct IdentitySigner {
+    key: SignerKey,
+}
+
+#[derive(Clone)]
+enum SignerKey {
+    Hmac(Vec<u8>),
+    Ed25519(Box<SigningKey>),
+}
+
+impl IdentitySigner {
+    /// Sign with HMAC-SHA256 under a shared secret
+    #[must_use]
+    pub fn hmac_sha256(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: SignerKey::Hmac(secret.into()),
+        }
+    }
+
+    /// Sign with an Ed25519 private key
+    #[must_use]
+    pub fn ed25519(key: SigningKey) -> Self {
+        Self {
+            key: SignerKey::Ed25519(Box::new(key)),
+        }
+    }
+
+    /// The algorithm this signer uses
+    #[must_use]
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self.key {
+            SignerKey::Hmac(_) => SignatureAlgorithm::HmacSha256,
+            SignerKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
+        }
+    }
+
+    /// The signature header value for `identity` published on `subject`
+    #[must_use]
+    pub fn sign(&self, subject: &str, identity: &MessageIdentity) -> String {
+        let input = signing_input(subject, identity);
+        let signature = match &self.key {
+            SignerKey::Hmac(secret) => {
+                let mut mac = hmac(secret);
+                mac.update(&input);
+                mac.finalize().into_bytes().to_vec()
+            },
+            SignerKey::Ed25519(key) => key.sign(&input).to_bytes().to_vec(),
+        };
+        format!("{}:{}", self.algorithm(), BASE64.encode(signature))
+    }
+
+    /// Sign the identity in a message's headers, replacing any previous
+    /// signature
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the message's identity headers are
+    /// missing or malformed
+    pub fn sign_message(&self, message: &mut NatsMessage) -> Result<()> {
+        let identity = message_identity(message)?;
+        let signature = self.sign(&message.subject, &identity);
+        message.headers.insert(SIGNATURE_HEADER, signature)
+    }
+}
+
+impl fmt::Debug for IdentitySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentitySigner")
+            .field("algorithm", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Checks identity signatures
+#[derive(Clone)]
+pub struct IdentityVerifier {
+    key: VerifierKey,
+}
+
+#[derive(Clone)]
+enum VerifierKey {
+    Hmac(Vec<u8>),
+    Ed25519(VerifyingKey),
+}
+
+impl IdentityVerifier {
+    /// Verify HMAC-SHA256 signatures under a shared secret
+    #[must_use]
+    pub fn hmac_sha256(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: VerifierKey::Hmac(secret.into()),
+        }
+    }
+
+    /// Verify Ed25519 signatures with a public key
+    #[must_use]
+    pub fn ed25519(key: VerifyingKey) -> Self {
+        Self {
+            key: VerifierKey::Ed25519(key),
+        }
+    }
+
+    /// The algorithm this verifier accepts
+    #[must_use]
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self.key {
+            VerifierKey::Hmac(_) => SignatureAlgorithm::HmacSha256,
+            VerifierKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
+        }
+    }
+
+    /// Check a signature header value for `identity` published on `subject`
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the signature is malformed or uses
+    /// another algorithm, or a permission denied error if it does not match
+    pub fn verify(&self, subject: &str, identity: &MessageIdentity, signature: &str) -> Result<()> {
+        let (algorithm, encoded) = signature.split_once(':').ok_or_else(|| {
+            SubjectError::validation_error(format!("Malformed identity signature '{signature}'"))
+        })?;
+        if algorithm != self.algorithm().name() {
+            return Err(SubjectError::validation_error(format!(
+                "Expected a {} identity signature, found {algorithm}",
+                self.algorithm()
+            )));
+        }
+        let signature = BASE64.decode(encoded).map_err(|e| {
+            SubjectError::validation_error(format!("Malformed identity signature: {e}"))
+        })?;
+
+        let input = signing_input(subject, identity);
+        let valid = match &self.key {
+            VerifierKey::Hmac(secret) => {
+                let mut mac = hmac(secret);
+                mac.update(&input);
+                mac.verify_slice(&signature).is_ok()
+            },
+            VerifierKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(&signature)
+                .is_ok_and(|signature| key.verify(&input, &signature).is_ok()),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(SubjectError::permission_denied(format!(
+                "Identity signature does not match message {} on '{subject}'",
+                identity.message_id
+            )))
+        }
+    }
+
+    /// Check a message's signature header, returning its identity if the
+    /// signature matches
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the identity or signature headers are
+    /// missing or malformed, or a permission denied error if the signature
+    /// does not match
+    pub fn verify_message(&self, message: &NatsMessage) -> Result<MessageIdentity> {
+        let identity = message_identity(message)?;
+        let signature = message.headers.get(SIGNATURE_HEADER).ok_or_else(|| {
+            SubjectError::validation_error(format!("Missing '{SIGNATURE_HEADER}' header"))
+        })?;
+        self.verify(&message.subject, &identity, signature)?;
+        Ok(identity)
+    }
+}
+
+impl fmt::Debug for IdentityVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentityVerifier")
+            .field("algorithm", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+fn hmac(secret: &[u8]) -> Hmac<Sha256> {
+    Hmac::new_from_slice(secret).expect("HMAC accepts keys of any length")
+}
+
+fn message_identity(message: &NatsMessage) -> Result<MessageIdentity> {
+    message
+        .headers
+        .identity()
+        .map_err(|e| SubjectError::validation_error(format!("Invalid identity headers: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::IdType;
+
+    fn message(identity: &MessageIdentity) -> NatsMessage {
+        NatsMessage::with_correlation(
+            "orders.order.placed.v1".to_string(),
+            serde_json::json!({"order_id": 7}),
+            identity,
+        )
+    }
+
+    #[test]
+    fn test_ed25519_signatures() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let signer = IdentitySigner::ed25519(key.clone());
+        let verifier = IdentityVerifier::ed25519(key.verifying_key());
+
+        let root = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let child = MessageIdentity::caused_by(
+            IdType::Uuid(Uuid::new_v4()),
+            root.correlation_id.clone(),
+            root.message_id.clone(),
+        );
+        let mut sent = message(&child);
+        signer.sign_message(&mut sent).unwrap();
+        assert!(sent
+            .headers
+            .get(SIGNATURE_HEADER)
+            .unwrap()
+            .starts_with("ed25519:"));
+        assert_eq!(verifier.verify_message(&sent).unwrap(), child);
+
+        // An intermediary re-parenting the message is caught
+        let mut forged = sent.clone();
+        let other = IdType::Uuid(Uuid::new_v4()).to_string();
+        forged.headers = sent
+            .headers
+            .iter()
+            .map(|(name, value)| match name {
+                "X-Causation-ID" => (name, other.as_str()),
+                _ => (name, value),
+            })
+            .collect();
+        assert!(matches!(
+            verifier.verify_message(&forged),
+            Err(SubjectError::PermissionDenied(_))
+        ));
+
+        // Another key or algorithm does not verify
+        let stranger = IdentityVerifier::ed25519(SigningKey::from_bytes(&[8; 32]).verifying_key());
+        assert!(stranger.verify_message(&sent).is_err());
+        let hmac = IdentityVerifier::hmac_sha256(b"secret".to_vec());
+        assert!(matches!(
+            hmac.verify_message(&sent),
+            Err(SubjectError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsigned_messages_are_rejected() {
+        let identity = MessageIdentity::root(IdType::Uuid(Uuid::new_v4()));
+        let verifier = IdentityVerifier::hmac_sha256(b"secret".to_vec());
+        assert!(verifier.verify_message(&message(&identity)).is_err());
+
+        let signer = IdentitySigner::hmac_sha256(b"secret".to_vec());
+        let signature = signer.sign("orders.order.placed.v1", &identity);
+        assert!(verifier
+            .verify("orders.order.placed.v1", &identity, &signature)
+            .is_ok());
+        assert!(verifier
+            .verify(
+                "orders.order.placed.v1",
+                &identity,
+                "hmac-sha256:not base64!"
+            )
+            .is_err());
+        assert!(!format!("{signer:?}").contains("secret"));
+    }
+}
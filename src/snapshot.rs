@@ -0,0 +1,246 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Deterministic text renderings for snapshot tests and code review
+//!
+//! Correlation chains, permission sets and translators hold hash maps, so
+//! their `Debug` output changes from run to run. The renderings here sort
+//! everything without a meaningful order, start with a header naming the
+//! format version, and change only when the value does, which suits
+//! `insta`-style snapshot tests and diffs in review. The version in the
+//! header is bumped whenever a rendering changes shape.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::correlation::{
+    IdType,
+    MessageIdentity,
+};
+use crate::message_algebra::CorrelationChain;
+use crate::permission_audit::describe_rule;
+use crate::permissions::{
+    Permissions,
+    SubscriptionCheck,
+};
+use crate::translator::Translator;
+
+/// Version of the snapshot formats, written in every header
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+impl CorrelationChain {
+    /// Render the causation tree, children sorted by message ID
+    ///
+    /// ```text
+    /// cim-subject chain v1
+    /// correlation 5f0c…
+    /// - 5f0c… command
+    ///   - 8a41… command
+    ///   - c3d2… event
+    /// ```
+    #[must_use]
+    pub fn to_snapshot(&self) -> String {
+        self.snapshot(true)
+    }
+
+    /// Render the causation tree without message IDs, for chains whose IDs
+    /// are generated afresh on every run
+    ///
+    /// Siblings are sorted by their rendered subtrees, so the output only
+    /// depends on the chain's shape and message kinds.
+    #[must_use]
+    pub fn to_redacted_snapshot(&self) -> String {
+        self.snapshot(false)
+    }
+
+    fn snapshot(&self, with_ids: bool) -> String {
+        let mut children: HashMap<&IdType, Vec<&MessageIdentity>> = HashMap::new();
+        for (child, parent) in &self.causation_graph {
+            if let Some(message) = self.messages.get(child) {
+                children.entry(parent).or_default().push(message);
+            }
+        }
+
+        let mut out = format!("cim-subject chain v{SNAPSHOT_FORMAT_VERSION}\n");
+        if with_ids {
+            let _ = writeln!(out, "correlation {}", self.root.correlation_id.0);
+        }
+        out.push_str(&render_message(&self.root, &children, 0, with_ids));
+        out
+    }
+}
+
+/// A message and its effects, one line each, siblings sorted by rendering
+fn render_message(
+    message: &MessageIdentity,
+    children: &HashMap<&IdType, Vec<&MessageIdentity>>,
+    depth: usize,
+    with_ids: bool,
+) -> String {
+    let kind = message
+        .kind
+        .map_or_else(|| "unknown".to_string(), |kind| kind.to_string());
+    let mut out = format!("{:indent$}- ", "", indent = depth * 2);
+    if with_ids {
+        let _ = write!(out, "{} ", message.message_id);
+    }
+    let _ = writeln!(out, "{kind}");
+
+    let mut rendered: Vec<String> = children
+        .get(&message.message_id)
+        .into_iter()
+        .flatten()
+        .map(|child| render_message(child, children, depth + 1, with_ids))
+        .collect();
+    rendered.sort();
+    for child in rendered {
+        out.push_str(&child);
+    }
+    out
+}
+
+impl Permissions {
+    /// Render the default policy, conflict resolution, a non-strict
+    /// subscription check and the rules
+    ///
+    /// Rules keep their registration order, which breaks ties between
+    /// equally specific rules; operations and queue groups are sorted.
+    ///
+    /// ```text
+    /// cim-subject permissions v1
+    /// default Deny
+    /// resolution MostSpecific
+    /// Allow orders.> [Publish, Subscribe]
+    /// Deny orders.*.internal.> [Publish] (internal events)
+    /// Allow work.> [QueueSubscribe] queues [billing, shipping]
+    /// ```
+    #[must_use]
+    pub fn to_snapshot(&self) -> String {
+        let mut out = format!("cim-subject permissions v{SNAPSHOT_FORMAT_VERSION}\n");
+        let _ = writeln!(out, "default {:?}", self.default_policy());
+        let _ = writeln!(out, "resolution {:?}", self.conflict_resolution());
+        // Only non-default, so existing snapshots stay valid
+        if self.subscription_check() != SubscriptionCheck::Strict {
+            let _ = writeln!(out, "subscriptions {:?}", self.subscription_check());
+        }
+        for rule in self.rules() {
+            out.push_str(&describe_rule(rule));
+            if let Some(groups) = &rule.queue_groups {
+                let mut groups: Vec<&str> = groups.iter().map(String::as_str).collect();
+                groups.sort_unstable();
+                let _ = write!(out, " queues [{}]", groups.join(", "));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Translator {
+    /// Render the rules sorted by name, with their source and target
+    /// patterns and whether they can be reversed
+    ///
+    /// ```text
+    /// cim-subject translator v1
+    /// billing_to_finance: billing.> -> finance.> reversible
+    /// legacy: orders.*.*.v1 -> *
+    /// ```
+    #[must_use]
+    pub fn to_snapshot(&self) -> String {
+        let mut rules: Vec<String> = self
+            .named_rules()
+            .into_iter()
+            .map(|(name, rule)| {
+                let target = rule
+                    .target_pattern
+                    .as_ref()
+                    .map_or_else(|| "*".to_string(), ToString::to_string);
+                let reversible = if rule.reverse_fn.is_some() {
+                    " reversible"
+                } else {
+                    ""
+                };
+                format!("{name}: {} -> {target}{reversible}\n", rule.source_pattern)
+            })
+            .collect();
+        rules.sort();
+
+        let mut out = format!("cim-subject translator v{SNAPSHOT_FORMAT_VERSION}\n");
+        out.extend(rules);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::correlation::MessageFactory;
+    use crate::permissions::{
+        Operation,
+        PermissionsBuilder,
+    };
+    use crate::translator::TranslatorBuilder;
+
+    #[test]
+    fn test_chain_snapshots() {
+        let root = MessageFactory::create_root_command(Uuid::from_u128(1));
+        let query = MessageFactory::query_from_command(Uuid::from_u128(3), &root);
+        let command = MessageFactory::command_from_command(Uuid::from_u128(2), &root);
+        let nested = MessageFactory::command_from_query(Uuid::from_u128(4), &query);
+        let mut chain = CorrelationChain::new(root).unwrap();
+        for message in [query, command, nested] {
+            chain.add_message(message).unwrap();
+        }
+
+        assert_eq!(
+            chain.to_snapshot(),
+            "cim-subject chain v1\n\
+             correlation 00000000-0000-0000-0000-000000000001\n\
+             - 00000000-0000-0000-0000-000000000001 command\n  \
+               - 00000000-0000-0000-0000-000000000002 command\n  \
+               - 00000000-0000-0000-0000-000000000003 query\n    \
+                 - 00000000-0000-0000-0000-000000000004 command\n"
+        );
+        assert_eq!(
+            chain.to_redacted_snapshot(),
+            "cim-subject chain v1\n- command\n  - command\n  - query\n    - command\n"
+        );
+    }
+
+    #[test]
+    fn test_permissions_and_translator_snapshots() {
+        let permissions = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Subscribe, Operation::Publish])
+            .unwrap()
+            .allow_queue("work.>", &["shipping", "billing"])
+            .unwrap()
+            .build();
+        assert_eq!(
+            permissions.to_snapshot(),
+            "cim-subject permissions v1\n\
+             default Deny\n\
+             resolution MostSpecific\n\
+             Allow orders.> [Publish, Subscribe]\n\
+             Allow work.> [QueueSubscribe] queues [billing, shipping]\n"
+        );
+
+        let translator = TranslatorBuilder::new()
+            .translate_context("orders", "sales")
+            .unwrap()
+            .map("billing.*.*.v1", "finance.{aggregate}.{event}.v1")
+            .unwrap()
+            .build();
+        let snapshot = translator.to_snapshot();
+        assert!(snapshot.starts_with("cim-subject translator v1\n"));
+        let names: Vec<&str> = snapshot
+            .lines()
+            .skip(1)
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names, sorted);
+    }
+}
@@ -2,11 +2,17 @@
 
 //! Core subject types and operations
 
-use std::fmt::{
+use alloc::format;
+use alloc::string::{
+    String,
+    ToString,
+};
+use alloc::vec::Vec;
+use core::fmt::{
     self,
     Display,
 };
-use std::str::FromStr;
+use core::str::FromStr;
 
 use serde::{
     Deserialize,
@@ -14,9 +20,11 @@ use serde::{
 };
 
 use crate::error::{
+    ErrorCode,
     Result,
     SubjectError,
 };
+use crate::limits::SubjectLimits;
 
 /// A NATS subject representing a hierarchical address
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -30,6 +38,9 @@ pub struct Subject {
 impl Subject {
     /// Create a new subject from a string
     ///
+    /// The first token may start with `$`, as NATS system subjects such as
+    /// `$JS.API.STREAM.INFO` do.
+    ///
     /// # Arguments
     ///
     /// * `subject` - The subject string to parse
@@ -44,8 +55,18 @@ impl Subject {
     ///
     /// # Examples
     pub fn new(subject: impl Into<String>) -> Result<Self> {
+        Self::new_with_limits(subject, &SubjectLimits::DEFAULT)
+    }
+
+    /// Create a new subject, enforcing `limits` instead of the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubjectError::LimitExceeded`] if the subject is over
+    /// `limits`, and otherwise the same errors as [`Subject::new`]
+    pub fn new_with_limits(subject: impl Into<String>, limits: &SubjectLimits) -> Result<Self> {
         let raw = subject.into();
-        let parts = SubjectParts::parse(&raw)?;
+        let parts = SubjectPartsRef::parse_with_limits(&raw, limits)?.into_owned();
         Ok(Self { raw, parts })
     }
 
@@ -98,7 +119,25 @@ impl Subject {
         &self.parts.version
     }
 
+    /// Edit a copy of this subject; see [`SubjectMut`]
+    #[must_use]
+    pub fn edit(&self) -> SubjectMut {
+        self.clone().into_mut()
+    }
+
+    /// Edit this subject in place, reusing its buffers
+    #[must_use]
+    pub fn into_mut(self) -> SubjectMut {
+        SubjectMut {
+            subject: self,
+            dirty: false,
+        }
+    }
+
     /// Create a new subject with a different event type
+    ///
+    /// The event type is not validated; [`SubjectMut`] validates edits and
+    /// allocates less when changing several tokens.
     #[must_use]
     pub fn with_event_type(&self, event_type: impl Into<String>) -> Self {
         let mut parts = self.parts.clone();
@@ -197,41 +236,18 @@ impl SubjectParts {
     /// assert_eq!(parts.version, "version");
     /// ```
     pub fn parse(subject: &str) -> Result<Self> {
-        let parts: Vec<&str> = subject.split('.').collect();
-
-        if parts.len() != 4 {
-            return Err(SubjectError::invalid_format(format!(
-                "Subject must have exactly 4 parts separated by dots, got {}: '{}'",
-                parts.len(),
-                subject
-            )));
-        }
+        SubjectPartsRef::parse(subject).map(SubjectPartsRef::into_owned)
+    }
 
-        // Validate each part
-        for (i, part) in parts.iter().enumerate() {
-            if part.is_empty() {
-                return Err(SubjectError::invalid_format(format!(
-                    "Subject part {} cannot be empty in '{}'",
-                    i + 1,
-                    subject
-                )));
-            }
-            if !part
-                .chars()
-                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-            {
-                return Err(SubjectError::invalid_format(format!(
-                    "Subject part '{part}' contains invalid characters in '{subject}'"
-                )));
-            }
+    /// Borrow these parts without copying
+    #[must_use]
+    pub fn as_parts_ref(&self) -> SubjectPartsRef<'_> {
+        SubjectPartsRef {
+            context: &self.context,
+            aggregate: &self.aggregate,
+            event_type: &self.event_type,
+            version: &self.version,
         }
-
-        Ok(Self {
-            context: parts[0].to_string(),
-            aggregate: parts[1].to_string(),
-            event_type: parts[2].to_string(),
-            version: parts[3].to_string(),
-        })
     }
 
     /// Convert back to a subject string
@@ -258,6 +274,203 @@ impl FromStr for SubjectParts {
     }
 }
 
+/// A borrowed, allocation-free view of a subject
+///
+/// Parsing validates the subject exactly like [`Subject::new`] but only keeps
+/// slices into the input, which suits routers that inspect far more subjects
+/// than they store. Convert with [`SubjectRef::to_subject`] when ownership is
+/// needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubjectRef<'a> {
+    /// The raw subject string
+    raw: &'a str,
+    /// Parsed components
+    parts: SubjectPartsRef<'a>,
+}
+
+impl<'a> SubjectRef<'a> {
+    /// Parse a subject string without allocating
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Subject::new`]
+    pub fn new(subject: &'a str) -> Result<Self> {
+        let parts = SubjectPartsRef::parse(subject)?;
+        Ok(Self {
+            raw: subject,
+            parts,
+        })
+    }
+
+    /// Get the raw subject string
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Get the parsed parts
+    #[must_use]
+    pub fn parts(&self) -> SubjectPartsRef<'a> {
+        self.parts
+    }
+
+    /// Get the context component
+    #[must_use]
+    pub fn context(&self) -> &'a str {
+        self.parts.context
+    }
+
+    /// Get the aggregate component
+    #[must_use]
+    pub fn aggregate(&self) -> &'a str {
+        self.parts.aggregate
+    }
+
+    /// Get the event type component
+    #[must_use]
+    pub fn event_type(&self) -> &'a str {
+        self.parts.event_type
+    }
+
+    /// Get the version component
+    #[must_use]
+    pub fn version(&self) -> &'a str {
+        self.parts.version
+    }
+
+    /// Copy into an owned [`Subject`]
+    #[must_use]
+    pub fn to_subject(&self) -> Subject {
+        Subject {
+            raw: self.raw.to_string(),
+            parts: self.parts.into_owned(),
+        }
+    }
+}
+
+impl Display for SubjectRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl<'a> From<&'a Subject> for SubjectRef<'a> {
+    fn from(subject: &'a Subject) -> Self {
+        Self {
+            raw: &subject.raw,
+            parts: subject.parts.as_parts_ref(),
+        }
+    }
+}
+
+impl AsRef<str> for SubjectRef<'_> {
+    fn as_ref(&self) -> &str {
+        self.raw
+    }
+}
+
+/// Borrowed components of a parsed subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubjectPartsRef<'a> {
+    /// Bounded context name
+    pub context: &'a str,
+    /// Aggregate root type
+    pub aggregate: &'a str,
+    /// Event type
+    pub event_type: &'a str,
+    /// Schema version
+    pub version: &'a str,
+}
+
+impl<'a> SubjectPartsRef<'a> {
+    /// Parse a subject string into borrowed parts
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The subject does not have exactly 4 parts
+    /// - A part is empty
+    /// - A part contains invalid characters
+    /// - The subject is over [`SubjectLimits::DEFAULT`]
+    pub fn parse(subject: &'a str) -> Result<Self> {
+        Self::parse_with_limits(subject, &SubjectLimits::DEFAULT)
+    }
+
+    /// Parse a subject string into borrowed parts, enforcing `limits`
+    /// instead of the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubjectError::LimitExceeded`] if the subject is over
+    /// `limits`, and otherwise the same errors as [`SubjectPartsRef::parse`]
+    pub fn parse_with_limits(subject: &'a str, limits: &SubjectLimits) -> Result<Self> {
+        limits.check_limits(subject)?;
+        let invalid = |span, reason| {
+            SubjectError::invalid_token(ErrorCode::InvalidFormat, subject, span, reason)
+        };
+
+        let mut parts = [""; 4];
+        let mut count = 0;
+        let mut start = 0;
+        for part in subject.split('.') {
+            if count < parts.len() {
+                parts[count] = part;
+            } else if count == parts.len() {
+                // Everything from the dot before the fifth token is surplus
+                return Err(invalid(
+                    start - 1..subject.len(),
+                    format!("more than {} tokens", parts.len()),
+                ));
+            }
+            if part.is_empty() {
+                return Err(invalid(
+                    start..start,
+                    format!("token {} is empty", count + 1),
+                ));
+            }
+            // A system subject such as `$JS.API.STREAM.INFO` keeps its
+            // leading '$'
+            let name = system_name(part, count);
+            if let Some((offset, c)) = name
+                .char_indices()
+                .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            {
+                let at = start + part.len() - name.len() + offset;
+                return Err(invalid(at..at + c.len_utf8(), format!("character '{c}'")));
+            }
+            start += part.len() + 1;
+            count += 1;
+        }
+
+        if count != parts.len() {
+            return Err(invalid(
+                subject.len()..subject.len(),
+                format!("expected {} tokens, found {count}", parts.len()),
+            ));
+        }
+
+        let [context, aggregate, event_type, version] = parts;
+        Ok(Self {
+            context,
+            aggregate,
+            event_type,
+            version,
+        })
+    }
+
+    /// The parts as tokens in subject order
+    #[must_use]
+    pub fn tokens(&self) -> [&'a str; 4] {
+        [self.context, self.aggregate, self.event_type, self.version]
+    }
+
+    /// Copy into owned [`SubjectParts`]
+    #[must_use]
+    pub fn into_owned(self) -> SubjectParts {
+        SubjectParts::new(self.context, self.aggregate, self.event_type, self.version)
+    }
+}
+
 /// Builder for constructing subjects
 #[derive(Debug, Clone, Default)]
 pub struct SubjectBuilder {
@@ -265,6 +478,7 @@ pub struct SubjectBuilder {
     aggregate: Option<String>,
     event_type: Option<String>,
     version: Option<String>,
+    default_version: Option<String>,
 }
 
 impl SubjectBuilder {
@@ -302,15 +516,51 @@ impl SubjectBuilder {
         self
     }
 
+    /// Set the version used when none is set explicitly
+    #[must_use]
+    pub fn default_version(mut self, version: impl Into<String>) -> Self {
+        self.default_version = Some(version.into());
+        self
+    }
+
+    /// Set the event type from the name of `T`, in snake case
+    ///
+    /// ```
+    /// use cim_subject::SubjectBuilder;
+    ///
+    /// struct OrderPlaced;
+    ///
+    /// let subject = SubjectBuilder::new()
+    ///     .context("orders")
+    ///     .aggregate("order")
+    ///     .event_from_type::<OrderPlaced>()
+    ///     .default_version("v1")
+    ///     .build()?;
+    /// assert_eq!(subject.as_str(), "orders.order.order_placed.v1");
+    /// # Ok::<(), cim_subject::SubjectError>(())
+    /// ```
+    #[must_use]
+    pub fn event_from_type<T: ?Sized>(self) -> Self {
+        self.event_type(type_token::<T>())
+    }
+
     /// Build the subject
     ///
+    /// Without an explicit context, the context of the current
+    /// [`SubjectScope`](crate::scope::SubjectScope) is used.
+    ///
     /// # Errors
     ///
     /// Returns an error if any required component is missing
     pub fn build(self) -> Result<Subject> {
+        #[cfg(feature = "std")]
         let context = self
             .context
-            .ok_or_else(|| SubjectError::validation_error("Context is required"))?;
+            .or_else(crate::scope::SubjectScope::current_context);
+        #[cfg(not(feature = "std"))]
+        let context = self.context;
+        let context =
+            context.ok_or_else(|| SubjectError::validation_error("Context is required"))?;
         let aggregate = self
             .aggregate
             .ok_or_else(|| SubjectError::validation_error("Aggregate is required"))?;
@@ -319,6 +569,7 @@ impl SubjectBuilder {
             .ok_or_else(|| SubjectError::validation_error("Event type is required"))?;
         let version = self
             .version
+            .or(self.default_version)
             .ok_or_else(|| SubjectError::validation_error("Version is required"))?;
 
         let parts = SubjectParts::new(context, aggregate, event_type, version);
@@ -326,6 +577,172 @@ impl SubjectBuilder {
     }
 }
 
+/// Editor for the tokens of a [`Subject`]
+///
+/// Each edit validates only the token it changes and overwrites the old
+/// token's buffer; the subject string is rebuilt once, in
+/// [`finish`](Self::finish).
+///
+/// ```
+/// use cim_subject::Subject;
+///
+/// let placed = Subject::new("orders.order.placed.v1")?;
+/// let mut edit = placed.into_mut();
+/// edit.set_event_type("shipped")?.set_version("v2")?;
+/// assert!(edit.set_aggregate("or der").is_err());
+/// assert_eq!(edit.finish()?.as_str(), "orders.order.shipped.v2");
+/// # Ok::<(), cim_subject::SubjectError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubjectMut {
+    subject: Subject,
+    dirty: bool,
+}
+
+impl SubjectMut {
+    /// The parts as edited so far
+    #[must_use]
+    pub fn parts(&self) -> &SubjectParts {
+        &self.subject.parts
+    }
+
+    /// Replace the context
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `context` is not a valid token; the subject is
+    /// left unchanged
+    pub fn set_context(&mut self, context: &str) -> Result<&mut Self> {
+        self.set(0, context)
+    }
+
+    /// Replace the aggregate
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `aggregate` is not a valid token; the subject is
+    /// left unchanged
+    pub fn set_aggregate(&mut self, aggregate: &str) -> Result<&mut Self> {
+        self.set(1, aggregate)
+    }
+
+    /// Replace the event type
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `event_type` is not a valid token; the subject is
+    /// left unchanged
+    pub fn set_event_type(&mut self, event_type: &str) -> Result<&mut Self> {
+        self.set(2, event_type)
+    }
+
+    /// Replace the version
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `version` is not a valid token; the subject is
+    /// left unchanged
+    pub fn set_version(&mut self, version: &str) -> Result<&mut Self> {
+        self.set(3, version)
+    }
+
+    /// Rebuild the subject string if any token changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the edited subject is over
+    /// [`SubjectLimits::DEFAULT`]
+    pub fn finish(self) -> Result<Subject> {
+        let Self { mut subject, dirty } = self;
+        if dirty {
+            let parts = &subject.parts;
+            subject.raw.clear();
+            for (index, token) in [
+                &parts.context,
+                &parts.aggregate,
+                &parts.event_type,
+                &parts.version,
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                if index > 0 {
+                    subject.raw.push('.');
+                }
+                subject.raw.push_str(token);
+            }
+            SubjectLimits::DEFAULT.check_limits(&subject.raw)?;
+        }
+        Ok(subject)
+    }
+
+    fn set(&mut self, position: usize, token: &str) -> Result<&mut Self> {
+        check_token(token, position)?;
+        let parts = &mut self.subject.parts;
+        let slot = match position {
+            0 => &mut parts.context,
+            1 => &mut parts.aggregate,
+            2 => &mut parts.event_type,
+            _ => &mut parts.version,
+        };
+        if slot != token {
+            slot.clear();
+            slot.push_str(token);
+            self.dirty = true;
+        }
+        Ok(self)
+    }
+}
+
+/// Check a single token, reporting problems against the token itself
+fn check_token(token: &str, position: usize) -> Result<()> {
+    let invalid =
+        |span, reason| SubjectError::invalid_token(ErrorCode::InvalidFormat, token, span, reason);
+    if token.is_empty() {
+        return Err(invalid(0..0, format!("token {} is empty", position + 1)));
+    }
+    let name = system_name(token, position);
+    if let Some((offset, c)) = name
+        .char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '-'))
+    {
+        let at = token.len() - name.len() + offset;
+        return Err(invalid(at..at + c.len_utf8(), format!("character '{c}'")));
+    }
+    Ok(())
+}
+
+/// The token without the leading `$` a system subject's first token may
+/// carry
+fn system_name(token: &str, position: usize) -> &str {
+    match token.strip_prefix('$') {
+        Some(name) if position == 0 && !name.is_empty() => name,
+        _ => token,
+    }
+}
+
+/// The unqualified name of `T` in snake case, e.g. `order_placed` for
+/// `events::OrderPlaced<u8>`
+fn type_token<T: ?Sized>() -> String {
+    let name = core::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+
+    let chars: Vec<char> = name.chars().collect();
+    let mut token = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                token.push('_');
+            }
+        }
+        token.extend(c.to_lowercase());
+    }
+    token
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,6 +779,21 @@ mod tests {
 
         // Invalid characters
         assert!(Subject::new("people.per$on.created.v1").is_err());
+        assert!(Subject::new("$.API.STREAM.INFO").is_err());
+        assert!(Subject::new("$$JS.API.STREAM.INFO").is_err());
+        assert!(Subject::new("people.$person.created.v1").is_err());
+    }
+
+    #[test]
+    fn test_system_subjects() {
+        let info = Subject::new("$JS.API.STREAM.INFO").unwrap();
+        assert_eq!(info.context(), "$JS");
+        assert!(crate::Pattern::new("$JS.API.>").unwrap().matches(&info));
+
+        let mut edit = Subject::new("people.person.created.v1").unwrap().into_mut();
+        edit.set_context("$SYS").unwrap();
+        assert!(edit.set_aggregate("$person").is_err());
+        assert_eq!(edit.finish().unwrap().as_str(), "$SYS.person.created.v1");
     }
 
     #[test]
@@ -377,6 +809,49 @@ mod tests {
         assert_eq!(subject.as_str(), "inventory.product.restocked.v1");
     }
 
+    #[test]
+    fn test_subject_builder_defaults() {
+        struct HTTPRequestSent;
+
+        let subject = SubjectBuilder::new()
+            .context("gateway")
+            .aggregate("request")
+            .event_from_type::<HTTPRequestSent>()
+            .default_version("v1")
+            .build()
+            .unwrap();
+        assert_eq!(subject.as_str(), "gateway.request.http_request_sent.v1");
+
+        // An explicit version wins over the default
+        let subject = SubjectBuilder::new()
+            .context("gateway")
+            .aggregate("request")
+            .event_from_type::<Vec<u8>>()
+            .default_version("v1")
+            .version("v2")
+            .build()
+            .unwrap();
+        assert_eq!(subject.as_str(), "gateway.request.vec.v2");
+    }
+
+    #[test]
+    fn test_subject_mut() {
+        let subject = Subject::new("orders.order.placed.v1").unwrap();
+
+        let mut edit = subject.edit();
+        edit.set_version("v2").unwrap();
+        assert!(edit.set_context("").is_err());
+        assert!(edit.set_event_type("a.b").is_err());
+        assert_eq!(edit.parts().context, "orders");
+        let edited = edit.finish().unwrap();
+        assert_eq!(edited, Subject::new("orders.order.placed.v2").unwrap());
+
+        // Edits that change nothing keep the subject as is
+        let mut edit = subject.clone().into_mut();
+        edit.set_aggregate("order").unwrap();
+        assert_eq!(edit.finish().unwrap(), subject);
+    }
+
     #[test]
     fn test_subject_builder_missing_fields() {
         let result = SubjectBuilder::new()
@@ -388,6 +863,34 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_subject_ref() {
+        let raw = "orders.order.placed.v2";
+        let subject = SubjectRef::new(raw).unwrap();
+        assert_eq!(subject.context(), "orders");
+        assert_eq!(subject.aggregate(), "order");
+        assert_eq!(subject.event_type(), "placed");
+        assert_eq!(subject.version(), "v2");
+        // Components borrow from the input
+        assert!(std::ptr::eq(subject.as_str(), raw));
+
+        let owned = subject.to_subject();
+        assert_eq!(owned, Subject::new(raw).unwrap());
+        assert_eq!(SubjectRef::from(&owned), subject);
+
+        for invalid in [
+            "people.person",
+            "people.person.created.v1.extra",
+            "people..created.v1",
+            "people.per$on.created.v1",
+        ] {
+            assert_eq!(
+                SubjectRef::new(invalid).unwrap_err(),
+                Subject::new(invalid).unwrap_err()
+            );
+        }
+    }
+
     #[test]
     fn test_subject_modifications() {
         let subject = Subject::new("users.user.created.v1").unwrap();
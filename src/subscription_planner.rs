@@ -0,0 +1,230 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Planning compact subscription sets
+//!
+//! A service that must receive many concrete subjects can cover them with
+//! far fewer wildcard subscriptions. [`SubscriptionPlanner`] repeatedly
+//! merges patterns that differ in a single token into one with `*` there,
+//! as long as the merged pattern cannot match anything on its deny list.
+//! Merging is greedy, so the result is small but not guaranteed minimal,
+//! and a merged pattern can match subjects that were not required.
+//!
+//! ```
+//! use cim_subject::subscription_planner::SubscriptionPlanner;
+//! use cim_subject::Pattern;
+//!
+//! let required: Vec<Pattern> = [
+//!     "orders.order.placed.v1",
+//!     "orders.order.shipped.v1",
+//!     "orders.order.cancelled.v1",
+//!     "orders.payment.captured.v1",
+//! ]
+//! .into_iter()
+//! .map(|s| Pattern::new(s).unwrap())
+//! .collect();
+//!
+//! let planner = SubscriptionPlanner::new().deny(Pattern::new("orders.order.deleted.v1").unwrap());
+//! let plan = planner.plan(&required).unwrap();
+//! // `orders.order.*.v1` would also receive deleted orders
+//! assert_eq!(plan.len(), 4);
+//!
+//! let plan = SubscriptionPlanner::new().plan(&required).unwrap();
+//! let plan: Vec<&str> = plan.iter().map(Pattern::as_str).collect();
+//! assert_eq!(plan, vec![
+//!     "orders.order.*.v1",
+//!     "orders.payment.captured.v1"
+//! ]);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{
+    String,
+    ToString,
+};
+use alloc::vec::Vec;
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+
+/// Computes compact pattern sets covering required subjects
+#[derive(Debug, Clone)]
+pub struct SubscriptionPlanner {
+    deny: Vec<Pattern>,
+    min_siblings: usize,
+}
+
+impl Default for SubscriptionPlanner {
+    fn default() -> Self {
+        Self {
+            deny: Vec::new(),
+            min_siblings: 2,
+        }
+    }
+}
+
+impl SubscriptionPlanner {
+    /// A planner with an empty deny list that merges any two siblings
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Never produce a pattern that could match anything `pattern` matches
+    #[must_use]
+    pub fn deny(mut self, pattern: Pattern) -> Self {
+        self.deny.push(pattern);
+        self
+    }
+
+    /// Only merge patterns when at least `count` of them differ in the same
+    /// token, to avoid broad subscriptions for a handful of subjects
+    #[must_use]
+    pub fn min_siblings(mut self, count: usize) -> Self {
+        self.min_siblings = count.max(2);
+        self
+    }
+
+    /// Plan subscriptions covering concrete subjects
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required subject is denied
+    pub fn plan_subjects(&self, subjects: &[Subject]) -> Result<Vec<Pattern>> {
+        let required: Vec<Pattern> = subjects
+            .iter()
+            .map(|subject| Pattern::from_valid_tokens(&subject.parts().as_parts_ref().tokens()))
+            .collect();
+        self.plan(&required)
+    }
+
+    /// Plan subscriptions covering every required pattern, sorted by their
+    /// string form
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required pattern overlaps the deny list, since
+    /// no subscription could then cover it
+    pub fn plan(&self, required: &[Pattern]) -> Result<Vec<Pattern>> {
+        if let Some(conflict) = required.iter().find(|p| self.is_denied(p)) {
+            return Err(SubjectError::validation_error(format!(
+                "Required pattern '{conflict}' overlaps the deny list"
+            )));
+        }
+
+        let mut plan = Pattern::remove_subsumed(required);
+        while let Some(merged) = self.merge_once(&plan) {
+            plan = Pattern::remove_subsumed(&merged);
+        }
+        plan.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        Ok(plan)
+    }
+
+    /// Merge the largest group of patterns that differ in one position, or
+    /// return `None` if no allowed merge is left
+    fn merge_once(&self, patterns: &[Pattern]) -> Option<Vec<Pattern>> {
+        let max_len = patterns.iter().map(|p| p.tokens().len()).max()?;
+        let mut best: Option<(Pattern, Vec<&Pattern>)> = None;
+
+        for position in 0..max_len {
+            // Patterns keyed by all their tokens except the one at `position`
+            let mut groups: BTreeMap<Vec<String>, Vec<&Pattern>> = BTreeMap::new();
+            for pattern in patterns {
+                let tokens: Vec<String> = pattern.as_str().split('.').map(str::to_string).collect();
+                if position >= tokens.len() || matches!(tokens[position].as_str(), "*" | ">") {
+                    continue;
+                }
+                let mut key = tokens;
+                key[position] = "*".to_string();
+                groups.entry(key).or_default().push(pattern);
+            }
+
+            for (key, members) in groups {
+                if members.len() < self.min_siblings
+                    || best.as_ref().is_some_and(|(_, b)| b.len() >= members.len())
+                {
+                    continue;
+                }
+                let candidate = Pattern::from_valid_tokens(&key);
+                if !self.is_denied(&candidate) {
+                    best = Some((candidate, members));
+                }
+            }
+        }
+
+        let (merged, members) = best?;
+        let mut result: Vec<Pattern> = patterns
+            .iter()
+            .filter(|p| !members.contains(p))
+            .cloned()
+            .collect();
+        result.push(merged);
+        Some(result)
+    }
+
+    fn is_denied(&self, pattern: &Pattern) -> bool {
+        self.deny.iter().any(|deny| pattern.overlaps(deny))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(raw: &[&str]) -> Vec<Pattern> {
+        raw.iter().map(|s| Pattern::new(*s).unwrap()).collect()
+    }
+
+    fn plan_strs(plan: &[Pattern]) -> Vec<&str> {
+        plan.iter().map(Pattern::as_str).collect()
+    }
+
+    #[test]
+    fn test_plan_merges_siblings() {
+        let required = patterns(&[
+            "billing.invoice.sent.v1",
+            "billing.invoice.paid.v1",
+            "orders.order.placed.v1",
+            "orders.order.placed.v2",
+            "orders.order.>",
+        ]);
+        let plan = SubscriptionPlanner::new().plan(&required).unwrap();
+        assert_eq!(plan_strs(&plan), vec![
+            "billing.invoice.*.v1",
+            "orders.order.>"
+        ]);
+
+        // Every required subject stays covered
+        let subject = Subject::new("billing.invoice.paid.v1").unwrap();
+        assert!(plan.iter().any(|p| p.matches(&subject)));
+
+        let strict = SubscriptionPlanner::new().min_siblings(3);
+        assert_eq!(strict.plan(&required).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_deny_list() {
+        let planner = SubscriptionPlanner::new().deny(Pattern::new("*.invoice.voided.>").unwrap());
+        let subjects: Vec<Subject> = ["billing.invoice.sent.v1", "billing.invoice.paid.v1"]
+            .into_iter()
+            .map(|s| Subject::new(s).unwrap())
+            .collect();
+        // `billing.invoice.*.v1` would include voided invoices
+        assert_eq!(plan_strs(&planner.plan_subjects(&subjects).unwrap()), vec![
+            "billing.invoice.paid.v1",
+            "billing.invoice.sent.v1"
+        ]);
+
+        assert!(planner.plan(&patterns(&["billing.invoice.*.v1"])).is_err());
+        assert!(Pattern::new("a.>")
+            .unwrap()
+            .overlaps(&Pattern::new("*.b.c").unwrap()));
+        assert!(!Pattern::new("a.*")
+            .unwrap()
+            .overlaps(&Pattern::new("a.b.c").unwrap()));
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Metrics emitted through the `metrics` facade
+//!
+//! With the `metrics` feature enabled, the crate records the metrics named
+//! below to whatever recorder the application installs, such as a
+//! Prometheus exporter. Without the feature, or without a recorder, nothing
+//! is recorded and the instrumented calls cost nothing extra.
+//!
+//! | Metric | Kind | Labels |
+//! |--------|------|--------|
+//! | [`TRANSLATION_RULE_HITS`] | counter | `rule` |
+//! | [`PERMISSION_DECISIONS`] | counter | `decision`, `rule` |
+//! | [`PATTERN_MATCH_SECONDS`] | histogram | `component` |
+//! | [`CHAIN_DEPTH`] | histogram | none |
+
+/// Subjects translated by each
+/// [`TranslationRule`](crate::translator::TranslationRule), labeled by the
+/// rule's registered name
+pub const TRANSLATION_RULE_HITS: &str = "cim_subject_translation_rule_hits_total";
+
+/// Permission checks, labeled `allow` or `deny` and by the deciding rule's
+/// description, its pattern if it has none, or `default` if no rule matched
+pub const PERMISSION_DECISIONS: &str = "cim_subject_permission_decisions_total";
+
+/// Time spent matching a subject against a set of patterns, labeled by the
+/// component doing the matching: `router`, `permissions` or `translator`
+pub const PATTERN_MATCH_SECONDS: &str = "cim_subject_pattern_match_seconds";
+
+/// Depth of each message added to a
+/// [`CorrelationChain`](crate::message_algebra::CorrelationChain), where the
+/// root is at depth zero
+pub const CHAIN_DEPTH: &str = "cim_subject_chain_depth";
+
+/// Count a subject translated by the rule registered as `rule`
+#[cfg(feature = "metrics")]
+pub(crate) fn record_translation(rule: &str) {
+    metrics::counter!(TRANSLATION_RULE_HITS, "rule" => rule.to_string()).increment(1);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn record_translation(_rule: &str) {}
+
+/// Count a permission decision made by `rule`, or the default policy
+#[cfg(feature = "metrics")]
+pub(crate) fn record_permission_decision(allowed: bool, rule: Option<&str>) {
+    metrics::counter!(
+        PERMISSION_DECISIONS,
+        "decision" => if allowed { "allow" } else { "deny" },
+        "rule" => rule.unwrap_or("default").to_string()
+    )
+    .increment(1);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn record_permission_decision(_allowed: bool, _rule: Option<&str>) {}
+
+/// Run `matching` and record how long it took
+#[cfg(feature = "metrics")]
+pub(crate) fn time_pattern_match<T>(component: &'static str, matching: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let result = matching();
+    metrics::histogram!(PATTERN_MATCH_SECONDS, "component" => component)
+        .record(start.elapsed().as_secs_f64());
+    result
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn time_pattern_match<T>(_component: &'static str, matching: impl FnOnce() -> T) -> T {
+    matching()
+}
+
+/// Record the depth of a message added to a correlation chain, computing
+/// it only when metrics are enabled
+#[cfg(feature = "metrics")]
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn record_chain_depth(depth: impl FnOnce() -> usize) {
+    metrics::histogram!(CHAIN_DEPTH).record(depth() as f64);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn record_chain_depth(_depth: impl FnOnce() -> usize) {}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+
+    use metrics::{
+        Counter,
+        Gauge,
+        Histogram,
+        Key,
+        KeyName,
+        Metadata,
+        Recorder,
+        SharedString,
+        Unit,
+    };
+    use uuid::Uuid;
+
+    use crate::correlation::MessageFactory;
+    use crate::message_algebra::CorrelationChain;
+    use crate::pattern::Pattern;
+    use crate::permissions::{
+        Operation,
+        PermissionRule,
+        Permissions,
+        Policy,
+    };
+    use crate::subject::Subject;
+    use crate::translator::{
+        TranslationRule,
+        Translator,
+    };
+
+    /// Remembers every metric key registered with it
+    #[derive(Default)]
+    struct KeyRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl KeyRecorder {
+        fn remember(&self, key: &Key) {
+            let labels: Vec<String> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}{{{}}}", key.name(), labels.join(",")));
+        }
+    }
+
+    impl Recorder for KeyRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            self.remember(key);
+            Counter::noop()
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            self.remember(key);
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            self.remember(key);
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn test_instrumented_calls_record_metrics() {
+        let recorder = KeyRecorder::default();
+        let keys = Arc::clone(&recorder.0);
+
+        metrics::with_local_recorder(&recorder, || {
+            let subject = Subject::new("orders.order.placed.v1").unwrap();
+
+            let translator = Translator::new();
+            translator.register_rule(
+                "upgrade",
+                TranslationRule::new(
+                    "upgrade",
+                    Pattern::new("orders.*.*.v1").unwrap(),
+                    Arc::new(|_| Subject::new("orders.order.placed.v2")),
+                ),
+            );
+            translator.translate(&subject).unwrap();
+
+            let mut permissions = Permissions::new(Policy::Deny);
+            permissions.add_rule(
+                PermissionRule::new(
+                    Pattern::new("orders.>").unwrap(),
+                    [Operation::Publish].into_iter().collect(),
+                    Policy::Allow,
+                )
+                .with_description("orders publisher"),
+            );
+            assert!(permissions.is_allowed(&subject, Operation::Publish));
+            assert!(!permissions.is_allowed(&subject, Operation::Subscribe));
+
+            let root = MessageFactory::create_root_command(Uuid::new_v4());
+            let mut chain = CorrelationChain::new(root.clone()).unwrap();
+            chain
+                .add_message(MessageFactory::command_from_command(Uuid::new_v4(), &root))
+                .unwrap();
+        });
+
+        let keys: HashSet<String> = keys.lock().unwrap().iter().cloned().collect();
+        for expected in [
+            "cim_subject_translation_rule_hits_total{rule=upgrade}",
+            "cim_subject_permission_decisions_total{decision=allow,rule=orders publisher}",
+            "cim_subject_permission_decisions_total{decision=deny,rule=default}",
+            "cim_subject_pattern_match_seconds{component=permissions}",
+            "cim_subject_pattern_match_seconds{component=translator}",
+            "cim_subject_chain_depth{}",
+        ] {
+            assert!(keys.contains(expected), "missing {expected} in {keys:?}");
+        }
+    }
+}
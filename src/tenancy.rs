@@ -0,0 +1,334 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Tenant-scoped subjects and permissions
+//!
+//! Subjects always have four tokens, so a tenant cannot be added as an extra
+//! leading token. Instead it qualifies the context token:
+//! `orders.order.place.v1` becomes `tenant-123__orders.order.place.v1` for
+//! tenant `tenant-123`, with [`TENANT_SEPARATOR`] between the two.
+//!
+//! A [`TenantScope`] wraps a tenant-agnostic [`Permissions`] set and applies
+//! it to one tenant's subjects only: a subject is allowed when it carries
+//! the scope's tenant and the permissions allow it with the tenant removed.
+//! Subjects of other tenants, and subjects without one, are always denied.
+//!
+//! ```
+//! use cim_subject::permissions::{
+//!     Operation,
+//!     PermissionsBuilder,
+//! };
+//! use cim_subject::tenancy::TenantScope;
+//! use cim_subject::Subject;
+//!
+//! let permissions = PermissionsBuilder::new()
+//!     .allow("orders.>", &[Operation::Publish])?
+//!     .build();
+//! let scope = TenantScope::new("tenant-123", permissions)?;
+//!
+//! let subject = Subject::new("orders.order.place.v1")?.with_tenant("tenant-123")?;
+//! assert_eq!(subject.as_str(), "tenant-123__orders.order.place.v1");
+//! assert!(scope.is_allowed(&subject, Operation::Publish));
+//!
+//! let other = Subject::new("orders.order.place.v1")?.with_tenant("tenant-456")?;
+//! assert!(!scope.is_allowed(&other, Operation::Publish));
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::pattern::Pattern;
+use crate::permissions::{
+    EvaluationContext,
+    Operation,
+    Permissions,
+    Policy,
+};
+use crate::subject::{
+    Subject,
+    SubjectParts,
+};
+
+/// Separates the tenant from the context in a tenant-qualified context token
+pub const TENANT_SEPARATOR: &str = "__";
+
+/// Check that `tenant` can qualify a context token
+///
+/// # Errors
+///
+/// Returns an error if the tenant is empty, contains characters not allowed
+/// in a subject token, contains [`TENANT_SEPARATOR`] or ends with `_`, which
+/// would make the qualified token ambiguous
+pub fn validate_tenant(tenant: &str) -> Result<()> {
+    if tenant.is_empty() {
+        return Err(SubjectError::validation_error("Tenant cannot be empty"));
+    }
+    if !tenant
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(SubjectError::validation_error(format!(
+            "Tenant '{tenant}' contains invalid characters"
+        )));
+    }
+    if tenant.contains(TENANT_SEPARATOR) || tenant.ends_with('_') {
+        return Err(SubjectError::validation_error(format!(
+            "Tenant '{tenant}' cannot contain '{TENANT_SEPARATOR}' or end with '_'"
+        )));
+    }
+    Ok(())
+}
+
+impl Subject {
+    /// This subject with its context qualified by `tenant`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tenant is invalid or the subject already
+    /// carries a tenant
+    pub fn with_tenant(&self, tenant: &str) -> Result<Self> {
+        validate_tenant(tenant)?;
+        if let Some(existing) = self.tenant() {
+            return Err(SubjectError::validation_error(format!(
+                "Subject '{self}' already belongs to tenant '{existing}'"
+            )));
+        }
+        let mut parts = self.parts().clone();
+        parts.context = format!("{tenant}{TENANT_SEPARATOR}{}", parts.context);
+        Ok(Self::from_parts(parts))
+    }
+
+    /// The tenant qualifying this subject's context, if any
+    #[must_use]
+    pub fn tenant(&self) -> Option<&str> {
+        split_context(self.context()).map(|(tenant, _)| tenant)
+    }
+
+    /// This subject with the tenant removed from its context
+    #[must_use]
+    pub fn without_tenant(&self) -> Self {
+        match split_context(self.context()) {
+            Some((_, context)) => Self::from_parts(SubjectParts::new(
+                context,
+                self.aggregate(),
+                self.event_type(),
+                self.version(),
+            )),
+            None => self.clone(),
+        }
+    }
+}
+
+/// Split a tenant-qualified context token into tenant and context
+fn split_context(token: &str) -> Option<(&str, &str)> {
+    token
+        .split_once(TENANT_SEPARATOR)
+        .filter(|(tenant, context)| !tenant.is_empty() && !context.is_empty())
+}
+
+/// A permission set confined to one tenant's subjects
+#[derive(Debug, Clone)]
+pub struct TenantScope {
+    tenant: String,
+    permissions: Permissions,
+}
+
+impl TenantScope {
+    /// Confine tenant-agnostic `permissions` to `tenant`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tenant is invalid
+    pub fn new(tenant: impl Into<String>, permissions: Permissions) -> Result<Self> {
+        let tenant = tenant.into();
+        validate_tenant(&tenant)?;
+        Ok(Self {
+            tenant,
+            permissions,
+        })
+    }
+
+    /// The tenant this scope is confined to
+    #[must_use]
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// The tenant-agnostic permissions
+    #[must_use]
+    pub fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
+    /// Check if an operation on a tenant-qualified subject is allowed
+    #[must_use]
+    pub fn is_allowed(&self, subject: &Subject, operation: Operation) -> bool {
+        self.is_allowed_with(subject, operation, &EvaluationContext::new())
+    }
+
+    /// Check if an operation on a tenant-qualified subject is allowed in
+    /// `context`
+    #[must_use]
+    pub fn is_allowed_with(
+        &self,
+        subject: &Subject,
+        operation: Operation,
+        context: &EvaluationContext,
+    ) -> bool {
+        subject.tenant() == Some(self.tenant.as_str())
+            && self
+                .permissions
+                .is_allowed_with(&subject.without_tenant(), operation, context)
+    }
+
+    /// Check if subscribing to a tenant-qualified pattern is allowed
+    ///
+    /// The pattern's context token must be a literal carrying this scope's
+    /// tenant; a wildcard there could match other tenants' subjects.
+    #[must_use]
+    pub fn is_subscription_allowed(&self, pattern: &Pattern, operation: Operation) -> bool {
+        self.unscope_pattern(pattern)
+            .is_some_and(|inner| self.permissions.is_subscription_allowed(&inner, operation))
+    }
+
+    /// Qualify a subject with this scope's tenant
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subject already carries a tenant
+    pub fn scope_subject(&self, subject: &Subject) -> Result<Subject> {
+        subject.with_tenant(&self.tenant)
+    }
+
+    /// Qualify a tenant-agnostic pattern's context token with this scope's
+    /// tenant
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the context token is a wildcard, since NATS
+    /// wildcards match whole tokens and cannot be confined to a tenant
+    pub fn scope_pattern(&self, pattern: &Pattern) -> Result<Pattern> {
+        let (context, rest) = match pattern.as_str().split_once('.') {
+            Some((context, rest)) => (context, Some(rest)),
+            None => (pattern.as_str(), None),
+        };
+        if context == "*" || context == ">" {
+            return Err(SubjectError::invalid_pattern(format!(
+                "Pattern '{pattern}' has a wildcard context and cannot be scoped to tenant '{}'",
+                self.tenant
+            )));
+        }
+        let context = format!("{}{TENANT_SEPARATOR}{context}", self.tenant);
+        Pattern::new(match rest {
+            Some(rest) => format!("{context}.{rest}"),
+            None => context,
+        })
+    }
+
+    /// The permissions rewritten onto this tenant's subjects, e.g. for
+    /// exporting to NATS
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule's pattern cannot be scoped, or if the
+    /// default policy is allow, which would grant other tenants' subjects
+    pub fn scoped_permissions(&self) -> Result<Permissions> {
+        if self.permissions.default_policy() == Policy::Allow {
+            return Err(SubjectError::validation_error(format!(
+                "An allow-by-default permission set cannot be confined to tenant '{}'",
+                self.tenant
+            )));
+        }
+        let mut scoped = Permissions::new(Policy::Deny);
+        scoped.set_conflict_resolution(self.permissions.conflict_resolution());
+        scoped.set_subscription_check(self.permissions.subscription_check());
+        for rule in self.permissions.rules() {
+            let mut rule = rule.clone();
+            rule.pattern = self.scope_pattern(&rule.pattern)?;
+            scoped.add_rule(rule);
+        }
+        Ok(scoped)
+    }
+
+    /// The tenant-agnostic form of a pattern qualified with this scope's
+    /// tenant
+    fn unscope_pattern(&self, pattern: &Pattern) -> Option<Pattern> {
+        let (context, rest) = match pattern.as_str().split_once('.') {
+            Some((context, rest)) => (context, Some(rest)),
+            None => (pattern.as_str(), None),
+        };
+        let (tenant, context) = split_context(context)?;
+        if tenant != self.tenant {
+            return None;
+        }
+        Pattern::new(match rest {
+            Some(rest) => format!("{context}.{rest}"),
+            None => context.to_string(),
+        })
+        .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::PermissionsBuilder;
+
+    fn scope() -> TenantScope {
+        let permissions = PermissionsBuilder::new()
+            .allow("orders.>", &[Operation::Publish, Operation::Subscribe])
+            .unwrap()
+            .deny("orders.order.delete.*", &[Operation::Publish])
+            .unwrap()
+            .build();
+        TenantScope::new("acme", permissions).unwrap()
+    }
+
+    #[test]
+    fn test_tenant_subjects() {
+        let subject = Subject::new("orders.order.place.v1").unwrap();
+        let scoped = subject.with_tenant("acme").unwrap();
+        assert_eq!(scoped.as_str(), "acme__orders.order.place.v1");
+        assert_eq!(scoped.tenant(), Some("acme"));
+        assert_eq!(scoped.without_tenant(), subject);
+        assert_eq!(subject.tenant(), None);
+
+        assert!(scoped.with_tenant("other").is_err());
+        assert!(subject.with_tenant("a__b").is_err());
+        assert!(subject.with_tenant("acme_").is_err());
+        assert!(subject.with_tenant("").is_err());
+
+        // Contexts may start with '_' without confusing the split
+        let inbox = Subject::new("_inbox.order.reply.v1").unwrap();
+        let scoped = inbox.with_tenant("acme").unwrap();
+        assert_eq!(scoped.tenant(), Some("acme"));
+        assert_eq!(scoped.without_tenant(), inbox);
+    }
+
+    #[test]
+    fn test_scope_enforcement() {
+        let scope = scope();
+        let place = Subject::new("orders.order.place.v1").unwrap();
+        assert!(scope.is_allowed(&place.with_tenant("acme").unwrap(), Operation::Publish));
+        assert!(!scope.is_allowed(&place.with_tenant("globex").unwrap(), Operation::Publish));
+        assert!(!scope.is_allowed(&place, Operation::Publish));
+        let delete = Subject::new("orders.order.delete.v1").unwrap();
+        assert!(!scope.is_allowed(&delete.with_tenant("acme").unwrap(), Operation::Publish));
+
+        let own = Pattern::new("acme__orders.order.>").unwrap();
+        assert!(scope.is_subscription_allowed(&own, Operation::Subscribe));
+        let any_tenant = Pattern::new("*.order.>").unwrap();
+        assert!(!scope.is_subscription_allowed(&any_tenant, Operation::Subscribe));
+        let other = Pattern::new("globex__orders.>").unwrap();
+        assert!(!scope.is_subscription_allowed(&other, Operation::Subscribe));
+
+        let scoped = scope.scoped_permissions().unwrap();
+        assert_eq!(scoped.rules()[0].pattern.as_str(), "acme__orders.>");
+        assert!(scoped.is_allowed(&place.with_tenant("acme").unwrap(), Operation::Publish));
+        assert!(!scoped.is_allowed(&place.with_tenant("globex").unwrap(), Operation::Publish));
+        assert!(scope
+            .scope_pattern(&Pattern::new("*.order.>").unwrap())
+            .is_err());
+    }
+}
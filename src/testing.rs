@@ -0,0 +1,651 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Test helpers: generators for property-based tests and fuzzing, and
+//! assertions for routing behavior
+//!
+//! With the `testing` feature, [`Subject`], [`SubjectParts`], [`Pattern`]
+//! and [`MessageIdentity`] implement both `proptest`'s and `arbitrary`'s
+//! `Arbitrary`, so downstream crates can fuzz their routing logic. Random
+//! subjects rarely hit the interesting cases, so [`SubjectSchema`] narrows
+//! each token to a known vocabulary and generates subjects, patterns and
+//! subjects matching a given pattern from it.
+//!
+//! ```
+//! use cim_subject::testing::SubjectSchema;
+//! use proptest::strategy::{
+//!     Strategy,
+//!     ValueTree,
+//! };
+//! use proptest::test_runner::TestRunner;
+//!
+//! let schema = SubjectSchema::new()
+//!     .contexts(["orders", "billing"])
+//!     .versions(["v1", "v2"]);
+//! let mut runner = TestRunner::default();
+//! let subject = schema.subjects().new_tree(&mut runner).unwrap().current();
+//! assert!(["orders", "billing"].contains(&subject.context()));
+//! ```
+//!
+//! The assertion macros take subjects and patterns as strings or parsed
+//! values. [`assert_routes_to!`](crate::assert_routes_to) identifies
+//! handlers by the names given to [`named`], and [`chain!`](crate::chain)
+//! builds correlation chains from arrows, with brackets for branches:
+//!
+//! ```
+//! use cim_subject::testing::named;
+//! use cim_subject::{
+//!     assert_matches,
+//!     assert_routes_to,
+//!     chain,
+//!     Pattern,
+//!     Router,
+//! };
+//!
+//! assert_matches!("orders.*.placed.>", "orders.order.placed.v1");
+//!
+//! let router: Router<()> = Router::new()
+//!     .route(Pattern::new("orders.>")?, named("orders", |_, _| Ok(())))
+//!     .route(
+//!         Pattern::new("orders.*.placed.v1")?,
+//!         named("placed", |_, _| Ok(())),
+//!     );
+//! assert_routes_to!(router, "orders.order.placed.v1", "placed");
+//!
+//! let chain = chain! { root => a => [b, c] };
+//! assert_eq!(chain.chain().depth(), 2);
+//! assert_eq!(chain.message("b").causation_id.0, chain.id("a").clone());
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use arbitrary::Unstructured;
+use proptest::prelude::{
+    any,
+    BoxedStrategy,
+    Just,
+    Strategy,
+};
+use proptest::sample::select;
+use uuid::Uuid;
+
+use crate::correlation::{
+    CorrelationId,
+    IdType,
+    MessageFactory,
+    MessageIdentity,
+    MessageKind,
+};
+use crate::error::Result;
+use crate::message_algebra::CorrelationChain;
+use crate::pattern::Pattern;
+use crate::router::Router;
+use crate::subject::{
+    Subject,
+    SubjectParts,
+};
+
+/// Regex for tokens generated when a position has no vocabulary
+const TOKEN_REGEX: &str = "[a-z][a-z0-9_-]{0,7}";
+
+/// Characters of tokens generated from unstructured fuzzer input
+const TOKEN_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_-";
+
+/// Per-token vocabularies constraining generated subjects and patterns
+///
+/// Positions without a vocabulary take any short lowercase token.
+#[derive(Debug, Clone, Default)]
+pub struct SubjectSchema {
+    vocabularies: [Option<Vec<String>>; 4],
+}
+
+impl SubjectSchema {
+    /// A schema allowing any token in every position
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw contexts from `contexts`
+    #[must_use]
+    pub fn contexts<S: Into<String>>(self, contexts: impl IntoIterator<Item = S>) -> Self {
+        self.vocabulary(0, contexts)
+    }
+
+    /// Draw aggregates from `aggregates`
+    #[must_use]
+    pub fn aggregates<S: Into<String>>(self, aggregates: impl IntoIterator<Item = S>) -> Self {
+        self.vocabulary(1, aggregates)
+    }
+
+    /// Draw event types from `event_types`
+    #[must_use]
+    pub fn event_types<S: Into<String>>(self, event_types: impl IntoIterator<Item = S>) -> Self {
+        self.vocabulary(2, event_types)
+    }
+
+    /// Draw versions from `versions`
+    #[must_use]
+    pub fn versions<S: Into<String>>(self, versions: impl IntoIterator<Item = S>) -> Self {
+        self.vocabulary(3, versions)
+    }
+
+    fn vocabulary<S: Into<String>>(
+        mut self,
+        position: usize,
+        tokens: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let tokens: Vec<String> = tokens.into_iter().map(Into::into).collect();
+        self.vocabularies[position] = (!tokens.is_empty()).then_some(tokens);
+        self
+    }
+
+    /// Tokens for one position
+    fn tokens(&self, position: usize) -> BoxedStrategy<String> {
+        match &self.vocabularies[position] {
+            Some(tokens) => select(tokens.clone()).boxed(),
+            None => TOKEN_REGEX.boxed(),
+        }
+    }
+
+    /// Subjects built from the schema's vocabularies
+    ///
+    /// # Panics
+    ///
+    /// Panics when generating if a vocabulary holds an invalid token
+    pub fn subjects(&self) -> BoxedStrategy<Subject> {
+        (
+            self.tokens(0),
+            self.tokens(1),
+            self.tokens(2),
+            self.tokens(3),
+        )
+            .prop_map(|(context, aggregate, event_type, version)| {
+                Subject::new(format!("{context}.{aggregate}.{event_type}.{version}"))
+                    .expect("schema tokens must be valid subject tokens")
+            })
+            .boxed()
+    }
+
+    /// Patterns over the schema's vocabularies, with any position possibly
+    /// replaced by `*` and any suffix possibly replaced by `>`
+    ///
+    /// # Panics
+    ///
+    /// Panics when generating if a vocabulary holds an invalid token
+    pub fn patterns(&self) -> BoxedStrategy<Pattern> {
+        let wildcards = proptest::collection::vec(any::<bool>(), 4);
+        (self.subjects(), wildcards, 0..=4usize)
+            .prop_map(|(subject, wildcards, tail_from)| {
+                let mut tokens: Vec<&str> = subject
+                    .parts()
+                    .as_parts_ref()
+                    .tokens()
+                    .iter()
+                    .zip(&wildcards)
+                    .map(|(token, wildcard)| if *wildcard { "*" } else { *token })
+                    .collect();
+                // `>` needs at least one token before it has something to
+                // stand for, and at most replaces the whole subject
+                if tail_from < 4 {
+                    tokens.truncate(tail_from);
+                    tokens.push(">");
+                }
+                Pattern::new(tokens.join(".")).expect("generated patterns are valid")
+            })
+            .boxed()
+    }
+
+    /// Subjects matching `pattern`, drawing wildcard positions from the
+    /// schema's vocabularies
+    ///
+    /// # Panics
+    ///
+    /// Panics if no subject can match the pattern, i.e. it has more than
+    /// four tokens or fewer than four without ending in `>`
+    pub fn subjects_matching(&self, pattern: &Pattern) -> BoxedStrategy<Subject> {
+        let raw: Vec<String> = pattern.as_str().split('.').map(str::to_string).collect();
+        let tail = raw.last().is_some_and(|token| token == ">");
+        let fixed = if tail { raw.len() - 1 } else { raw.len() };
+        assert!(
+            fixed <= 4 && (tail || fixed == 4),
+            "no subject can match '{pattern}'"
+        );
+
+        let positions: Vec<BoxedStrategy<String>> = (0..4)
+            .map(|i| match raw.get(i).map(String::as_str) {
+                Some(literal) if i < fixed && literal != "*" => Just(literal.to_string()).boxed(),
+                _ => self.tokens(i),
+            })
+            .collect();
+        positions
+            .prop_map(|tokens| Subject::new(tokens.join(".")).expect("schema tokens are valid"))
+            .boxed()
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for Subject {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        SubjectSchema::new().subjects()
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for SubjectParts {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        any::<Subject>().prop_map(Subject::into_parts).boxed()
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for Pattern {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        SubjectSchema::new().patterns()
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for MessageIdentity {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// UUID-identified roots and caused messages; CID-identified events
+    /// are not generated
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        let kind = proptest::option::of(select(vec![MessageKind::Command, MessageKind::Query]));
+        (
+            any::<u128>(),
+            proptest::option::of((any::<u128>(), any::<u128>())),
+            kind,
+        )
+            .prop_map(|(id, parent, kind)| identity(id, parent, kind))
+            .boxed()
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Subject {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let tokens = [
+            arbitrary_token(u)?,
+            arbitrary_token(u)?,
+            arbitrary_token(u)?,
+            arbitrary_token(u)?,
+        ];
+        Subject::new(tokens.join(".")).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for SubjectParts {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Subject::arbitrary(u)?.into_parts())
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Pattern {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(1..=5)?;
+        let mut tokens = Vec::with_capacity(len);
+        for i in 0..len {
+            let token = match u.int_in_range(0..=3)? {
+                0 => "*".to_string(),
+                1 if i + 1 == len => ">".to_string(),
+                _ => arbitrary_token(u)?,
+            };
+            tokens.push(token);
+        }
+        Pattern::new(tokens.join(".")).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for MessageIdentity {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let kind = match u.int_in_range(0..=2)? {
+            0 => None,
+            1 => Some(MessageKind::Command),
+            _ => Some(MessageKind::Query),
+        };
+        let id = u.arbitrary()?;
+        let parent = u.arbitrary()?;
+        Ok(identity(id, parent, kind))
+    }
+}
+
+/// A UUID-identified identity, caused by `parent` (correlation and
+/// causation IDs) if given
+fn identity(id: u128, parent: Option<(u128, u128)>, kind: Option<MessageKind>) -> MessageIdentity {
+    let message_id = IdType::Uuid(Uuid::from_u128(id));
+    let identity = match parent {
+        None => MessageIdentity::root(message_id),
+        Some((correlation, causation)) => MessageIdentity::caused_by(
+            message_id,
+            CorrelationId::from_uuid(Uuid::from_u128(correlation)),
+            IdType::Uuid(Uuid::from_u128(causation)),
+        ),
+    };
+    match kind {
+        Some(kind) => identity.with_kind(kind),
+        None => identity,
+    }
+}
+
+/// A short non-empty token from fuzzer input
+fn arbitrary_token(u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+    let len = u.int_in_range(1..=8)?;
+    (0..len)
+        .map(|_| u.choose(TOKEN_CHARS).map(|&c| char::from(c)))
+        .collect()
+}
+
+thread_local! {
+    /// Names of the [`named`] handlers run on this thread
+    static DISPATCHED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Wrap a handler so [`dispatched_to`] and
+/// [`assert_routes_to!`](crate::assert_routes_to) can tell it apart
+pub fn named<M, F>(
+    name: impl Into<String>,
+    handler: F,
+) -> impl Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static
+where
+    F: Fn(&Subject, &M) -> Result<()> + Send + Sync + 'static,
+{
+    let name = name.into();
+    move |subject, message| {
+        DISPATCHED.with(|dispatched| dispatched.borrow_mut().push(name.clone()));
+        handler(subject, message)
+    }
+}
+
+/// Dispatch a message, returning the names of the [`named`] handlers that
+/// ran, once per attempt
+///
+/// # Errors
+///
+/// Returns the router's error if dispatch fails
+pub fn dispatched_to<M>(router: &Router<M>, subject: &Subject, message: &M) -> Result<Vec<String>> {
+    DISPATCHED.with(|dispatched| dispatched.borrow_mut().clear());
+    let result = router.dispatch(subject, message);
+    let names = DISPATCHED.with(RefCell::take);
+    result.map(|_| names)
+}
+
+/// Parse a subject for an assertion
+///
+/// # Panics
+///
+/// Panics if `subject` is not a valid subject
+#[doc(hidden)]
+#[must_use]
+pub fn subject(subject: &(impl Display + ?Sized)) -> Subject {
+    let subject = subject.to_string();
+    Subject::new(&subject).unwrap_or_else(|e| panic!("invalid subject '{subject}': {e}"))
+}
+
+/// Parse a pattern for an assertion
+///
+/// # Panics
+///
+/// Panics if `pattern` is not a valid pattern
+#[doc(hidden)]
+#[must_use]
+pub fn pattern(pattern: &(impl Display + ?Sized)) -> Pattern {
+    let pattern = pattern.to_string();
+    Pattern::new(&pattern).unwrap_or_else(|e| panic!("invalid pattern '{pattern}': {e}"))
+}
+
+/// Assert that a pattern matches a subject
+#[macro_export]
+macro_rules! assert_matches {
+    ($pattern:expr, $subject:expr $(,)?) => {{
+        let pattern = $crate::testing::pattern(&$pattern);
+        let subject = $crate::testing::subject(&$subject);
+        assert!(
+            pattern.matches(&subject),
+            "expected '{}' to match '{}'",
+            pattern,
+            subject
+        );
+    }};
+}
+
+/// Assert that a pattern does not match a subject
+#[macro_export]
+macro_rules! assert_not_matches {
+    ($pattern:expr, $subject:expr $(,)?) => {{
+        let pattern = $crate::testing::pattern(&$pattern);
+        let subject = $crate::testing::subject(&$subject);
+        assert!(
+            !pattern.matches(&subject),
+            "expected '{}' not to match '{}'",
+            pattern,
+            subject
+        );
+    }};
+}
+
+/// Assert that a [`Router`] dispatches a subject to the handler with the
+/// given [`named`] name
+///
+/// The message defaults to `Default::default()`; pass a reference to one as
+/// a fourth argument otherwise.
+#[macro_export]
+macro_rules! assert_routes_to {
+    ($router:expr, $subject:expr, $handler:expr $(,)?) => {
+        $crate::assert_routes_to!(
+            $router,
+            $subject,
+            $handler,
+            &::core::default::Default::default()
+        )
+    };
+    ($router:expr, $subject:expr, $handler:expr, $message:expr $(,)?) => {{
+        let subject = $crate::testing::subject(&$subject);
+        let handler: &str = $handler;
+        match $crate::testing::dispatched_to(&$router, &subject, $message) {
+            Ok(handlers) => assert!(
+                handlers.iter().any(|name| name == handler),
+                "expected '{}' to route to '{}', but it reached {:?}",
+                subject,
+                handler,
+                handlers
+            ),
+            Err(error) => panic!(
+                "expected '{}' to route to '{}', but dispatch failed: {}",
+                subject, handler, error
+            ),
+        }
+    }};
+}
+
+/// Build a [`TestChain`] of commands from arrows, where `a => b` means `a`
+/// caused `b` and brackets hold the branches of a fork
+///
+/// ```
+/// let chain = cim_subject::chain! { root => a => [b => d, c] };
+/// assert_eq!(chain.chain().leaves().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! chain {
+    (@link $chain:ident, $parent:expr, [$($branches:tt)*]) => {
+        $crate::chain!(@branches $chain, $parent, [] $($branches)*)
+    };
+    (@link $chain:ident, $parent:expr, $child:ident $(=> $($rest:tt)+)?) => {
+        $chain.caused_by($parent, stringify!($child));
+        $( $crate::chain!(@link $chain, stringify!($child), $($rest)+); )?
+    };
+    // Collect a branch's tokens up to the next top-level comma
+    (@branches $chain:ident, $parent:expr, [$($branch:tt)+] , $($rest:tt)*) => {
+        $crate::chain!(@link $chain, $parent, $($branch)+);
+        $crate::chain!(@branches $chain, $parent, [] $($rest)*);
+    };
+    (@branches $chain:ident, $parent:expr, [$($branch:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::chain!(@branches $chain, $parent, [$($branch)* $next] $($rest)*)
+    };
+    (@branches $chain:ident, $parent:expr, [$($branch:tt)+]) => {
+        $crate::chain!(@link $chain, $parent, $($branch)+);
+    };
+    (@branches $chain:ident, $parent:expr, []) => {};
+    ($root:ident $(=> $($rest:tt)+)?) => {{
+        let mut chain = $crate::testing::TestChain::new(stringify!($root));
+        $( $crate::chain!(@link chain, stringify!($root), $($rest)+); )?
+        chain
+    }};
+}
+
+/// A correlation chain whose messages are known by name, built by
+/// [`chain!`](crate::chain)
+#[derive(Debug, Clone)]
+pub struct TestChain {
+    chain: CorrelationChain,
+    messages: HashMap<&'static str, MessageIdentity>,
+}
+
+impl TestChain {
+    /// A chain holding a root command named `root`
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: a root command always starts a chain
+    #[must_use]
+    pub fn new(root: &'static str) -> Self {
+        let identity = MessageFactory::create_root_command(Uuid::new_v4());
+        let chain = CorrelationChain::new(identity.clone()).expect("root commands start chains");
+        Self {
+            chain,
+            messages: HashMap::from([(root, identity)]),
+        }
+    }
+
+    /// Add a command caused by the message named `parent`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is unknown or `name` is taken
+    pub fn caused_by(&mut self, parent: &str, name: &'static str) {
+        assert!(
+            !self.messages.contains_key(name),
+            "'{name}' appears twice in the chain"
+        );
+        let identity = MessageFactory::command_from_command(Uuid::new_v4(), self.message(parent));
+        self.chain
+            .add_message(identity.clone())
+            .expect("commands caused by chain members join the chain");
+        self.messages.insert(name, identity);
+    }
+
+    /// The message named `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if no message has that name
+    #[must_use]
+    pub fn message(&self, name: &str) -> &MessageIdentity {
+        self.messages
+            .get(name)
+            .unwrap_or_else(|| panic!("no message named '{name}' in the chain"))
+    }
+
+    /// The ID of the message named `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if no message has that name
+    #[must_use]
+    pub fn id(&self, name: &str) -> &IdType {
+        &self.message(name).message_id
+    }
+
+    /// The correlation chain
+    #[must_use]
+    pub fn chain(&self) -> &CorrelationChain {
+        &self.chain
+    }
+
+    /// Take the correlation chain
+    #[must_use]
+    pub fn into_chain(self) -> CorrelationChain {
+        self.chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_subjects_round_trip(subject in any::<Subject>()) {
+            prop_assert_eq!(Subject::new(subject.as_str()).unwrap(), subject);
+        }
+
+        #[test]
+        fn test_subjects_matching_match(
+            (pattern, subject) in SubjectSchema::new()
+                .contexts(["orders", "billing"])
+                .patterns()
+                .prop_flat_map(|pattern| {
+                    let subjects = SubjectSchema::new().subjects_matching(&pattern);
+                    (Just(pattern), subjects)
+                })
+        ) {
+            prop_assert!(pattern.matches(&subject), "{} !~ {}", pattern, subject);
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_from_bytes() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&bytes);
+        let subject: Subject = u.arbitrary().unwrap();
+        let pattern: Pattern = u.arbitrary().unwrap();
+        let identity: MessageIdentity = u.arbitrary().unwrap();
+        assert!(Subject::new(subject.as_str()).is_ok());
+        assert!(Pattern::new(pattern.as_str()).is_ok());
+        assert_eq!(identity.message_id.to_string().len(), 36);
+    }
+
+    #[test]
+    fn test_routing_assertions() {
+        let router: Router<u32> = Router::new()
+            .dispatch_mode(crate::router::DispatchMode::All)
+            .route(pattern("orders.>"), named("audit", |_, _| Ok(())))
+            .route(
+                pattern("orders.*.placed.v1"),
+                named("placed", |_, _| Ok(())),
+            );
+        assert_routes_to!(router, "orders.order.placed.v1", "placed");
+        assert_routes_to!(router, subject("orders.order.placed.v1"), "audit", &7);
+        assert_eq!(
+            dispatched_to(&router, &subject("orders.order.shipped.v1"), &0).unwrap(),
+            vec!["audit"]
+        );
+        assert_matches!("orders.*.placed.>", "orders.order.placed.v1");
+        assert_not_matches!(pattern("billing.>"), "orders.order.placed.v1");
+    }
+
+    #[test]
+    fn test_chain_macro() {
+        let chain = chain! { root => a => [b => [d, e], c] };
+        let leaves: Vec<&IdType> = chain
+            .chain()
+            .leaves()
+            .iter()
+            .map(|identity| &identity.message_id)
+            .collect();
+        assert_eq!(leaves.len(), 3);
+        assert!(leaves.contains(&chain.id("c")));
+        assert_eq!(chain.message("d").causation_id.0, chain.id("b").clone());
+        assert_eq!(chain.chain().depth(), 3);
+    }
+}
@@ -5,7 +5,9 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use bytes::Bytes;
 use dashmap::DashMap;
+use serde::de::DeserializeOwned;
 use serde::{
     Deserialize,
     Serialize,
@@ -16,11 +18,15 @@ use crate::error::{
     Result,
     SubjectError,
 };
+use crate::field_transform::FieldTransform;
+use crate::format::PayloadFormat;
+use crate::headers::Headers;
 use crate::pattern::Pattern;
 use crate::subject::{
     Subject,
     SubjectParts,
 };
+use crate::telemetry;
 
 // Type alias to simplify the complex function type
 type TranslateFn = Arc<dyn Fn(&Subject) -> Result<Subject> + Send + Sync>;
@@ -58,17 +64,38 @@ impl Translator {
         self.rules.insert(name.into(), rule);
     }
 
+    /// Registered rules with their names, in no particular order
+    pub(crate) fn named_rules(&self) -> Vec<(String, TranslationRule)> {
+        self.rules
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
     /// Translate a subject using registered rules
     ///
     /// # Errors
     ///
     /// Returns `SubjectError` if the translation function fails
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(subject = %subject, rule = tracing::field::Empty),
+            err
+        )
+    )]
     pub fn translate(&self, subject: &Subject) -> Result<Subject> {
         // Find matching rule
-        for rule in self.rules.iter() {
-            if rule.matches_source(subject) {
-                return rule.translate(subject);
-            }
+        let matching = telemetry::time_pattern_match("translator", || {
+            self.rules.iter().find(|rule| rule.matches_source(subject))
+        });
+        if let Some(rule) = matching {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("rule", rule.key().as_str());
+            telemetry::record_translation(rule.key());
+            return rule.translate(subject);
         }
 
         // No rule found, return original
@@ -123,6 +150,19 @@ impl Translator {
     /// Returns `SubjectError` if:
     /// - Subject creation fails
     /// - Translation fails
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                message_id = %identity.message_id,
+                correlation_id = %identity.correlation_id.0,
+                causation_id = %identity.causation_id.0,
+            ),
+            err
+        )
+    )]
     pub fn translate_with_correlation(
         &self,
         context: &str,
@@ -275,6 +315,18 @@ pub trait MessageTranslator<From, To> {
     fn reverse(&self, to: To) -> std::result::Result<From, Self::Error>;
 }
 
+/// Fill a subject template's `{context}`, `{aggregate}`, `{event}` and
+/// `{version}` placeholders from a subject
+pub(crate) fn render_template(template: &str, subject: &Subject) -> Result<Subject> {
+    Subject::new(
+        template
+            .replace("{context}", subject.context())
+            .replace("{aggregate}", subject.aggregate())
+            .replace("{event}", subject.event_type())
+            .replace("{version}", subject.version()),
+    )
+}
+
 /// Builder for creating translators
 #[derive(Default)]
 pub struct TranslatorBuilder {
@@ -300,15 +352,7 @@ impl TranslatorBuilder {
         let rule = TranslationRule::new(
             format!("map_{source_pattern}"),
             pattern,
-            Arc::new(move |subject| {
-                // Simple template replacement
-                let mut result = template.clone();
-                result = result.replace("{context}", subject.context());
-                result = result.replace("{aggregate}", subject.aggregate());
-                result = result.replace("{event}", subject.event_type());
-                result = result.replace("{version}", subject.version());
-                Subject::new(result)
-            }),
+            Arc::new(move |subject| render_template(&template, subject)),
         );
 
         self.rules.push((rule.name.clone(), rule));
@@ -382,42 +426,539 @@ pub struct FieldMapping {
     pub source_path: String,
     /// Target field path
     pub target_path: String,
-    /// Optional transformation
+    /// Optional transformation, a [`FieldTransform`] expression or the name
+    /// of a transform registered on a [`JsonMessageTranslator`]
     pub transform: Option<String>,
 }
 
+impl FieldMapping {
+    /// Parse the transform as a built-in [`FieldTransform`] expression
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transform is not a valid expression
+    pub fn parsed_transform(&self) -> Result<Option<FieldTransform>> {
+        self.transform
+            .as_deref()
+            .map(FieldTransform::parse)
+            .transpose()
+    }
+}
+
+impl SchemaMapping {
+    /// Produce the mapping for the opposite direction
+    ///
+    /// Source and target schemas and paths are swapped, and each transform is
+    /// replaced by its [`FieldTransform::inverse`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NonInvertibleMapping`] listing every field whose transform is
+    /// lossy, unknown, or whose target path is shared with another field
+    pub fn invert(&self) -> std::result::Result<SchemaMapping, NonInvertibleMapping> {
+        let mut field_mappings = Vec::with_capacity(self.field_mappings.len());
+        let mut fields = Vec::new();
+
+        for field in &self.field_mappings {
+            let non_invertible = |reason: String| NonInvertibleField {
+                source_path: field.source_path.clone(),
+                target_path: field.target_path.clone(),
+                reason,
+            };
+
+            if self
+                .field_mappings
+                .iter()
+                .filter(|other| other.target_path == field.target_path)
+                .count()
+                > 1
+            {
+                fields.push(non_invertible("target path is shared".to_string()));
+                continue;
+            }
+
+            let transform = match field.transform.as_deref().map(FieldTransform::parse) {
+                None => None,
+                Some(Ok(transform)) => {
+                    let Some(inverse) = transform.inverse() else {
+                        let step = transform
+                            .non_invertible_step()
+                            .map(ToString::to_string)
+                            .unwrap_or_default();
+                        fields.push(non_invertible(format!("'{step}' is not invertible")));
+                        continue;
+                    };
+                    Some(inverse.to_string())
+                },
+                Some(Err(_)) => {
+                    let name = field.transform.clone().unwrap_or_default();
+                    fields.push(non_invertible(format!(
+                        "custom transform '{name}' has no known inverse"
+                    )));
+                    continue;
+                },
+            };
+
+            field_mappings.push(FieldMapping {
+                source_path: field.target_path.clone(),
+                target_path: field.source_path.clone(),
+                transform,
+            });
+        }
+
+        if !fields.is_empty() {
+            return Err(NonInvertibleMapping {
+                mapping: self.name.clone(),
+                fields,
+            });
+        }
+
+        Ok(SchemaMapping {
+            name: format!("{}_inverse", self.name),
+            source_schema: self.target_schema.clone(),
+            target_schema: self.source_schema.clone(),
+            field_mappings,
+        })
+    }
+
+    /// Apply this mapping to a JSON payload using built-in transforms
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubjectError` if a source field is missing, a target path
+    /// conflicts with a non-object value, or a transform fails
+    pub fn apply(&self, payload: &serde_json::Value) -> Result<serde_json::Value> {
+        apply_mapping(self, payload, &HashMap::new())
+    }
+}
+
+/// A field that prevents a [`SchemaMapping`] from being inverted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonInvertibleField {
+    /// Source field path
+    pub source_path: String,
+    /// Target field path
+    pub target_path: String,
+    /// Why the field cannot be inverted
+    pub reason: String,
+}
+
+/// Error returned by [`SchemaMapping::invert`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Mapping '{mapping}' is not invertible: {}", describe_fields(.fields))]
+pub struct NonInvertibleMapping {
+    /// Name of the mapping
+    pub mapping: String,
+    /// Fields that cannot be inverted
+    pub fields: Vec<NonInvertibleField>,
+}
+
+impl From<NonInvertibleMapping> for SubjectError {
+    fn from(error: NonInvertibleMapping) -> Self {
+        SubjectError::translation_error(error.to_string())
+    }
+}
+
+fn describe_fields(fields: &[NonInvertibleField]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{} -> {} ({})",
+                field.source_path, field.target_path, field.reason
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Type alias for named payload field transforms
+pub type FieldTransformFn =
+    Arc<dyn Fn(serde_json::Value) -> Result<serde_json::Value> + Send + Sync>;
+
+/// Translator for whole NATS messages: subject and JSON payload together
+///
+/// The subject is translated with a [`Translator`] and the payload is
+/// reshaped with a [`SchemaMapping`]: each [`FieldMapping`] moves the value at
+/// its source path to its target path (dot-separated), applying its
+/// transform if one is declared. Unmapped fields are carried over unchanged.
+#[derive(Clone)]
+pub struct JsonMessageTranslator {
+    /// Subject translator
+    subjects: Translator,
+    /// Forward payload mapping
+    mapping: SchemaMapping,
+    /// Reverse payload mapping, if the translation is bidirectional
+    reverse_mapping: Option<SchemaMapping>,
+    /// Named field transforms referenced by mappings
+    transforms: HashMap<String, FieldTransformFn>,
+}
+
+impl JsonMessageTranslator {
+    /// Create a message translator from a subject translator and a mapping
+    #[must_use]
+    pub fn new(subjects: Translator, mapping: SchemaMapping) -> Self {
+        Self {
+            subjects,
+            mapping,
+            reverse_mapping: None,
+            transforms: HashMap::new(),
+        }
+    }
+
+    /// Create a bidirectional message translator
+    ///
+    /// The reverse payload mapping is derived with [`SchemaMapping::invert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubjectError` if the mapping is not invertible
+    pub fn bidirectional(subjects: Translator, mapping: SchemaMapping) -> Result<Self> {
+        let reverse = mapping.invert()?;
+        Ok(Self::new(subjects, mapping).with_reverse_mapping(reverse))
+    }
+
+    /// Add a reverse payload mapping for [`MessageTranslator::reverse`]
+    #[must_use]
+    pub fn with_reverse_mapping(mut self, mapping: SchemaMapping) -> Self {
+        self.reverse_mapping = Some(mapping);
+        self
+    }
+
+    /// Register a named field transform
+    #[must_use]
+    pub fn with_transform(mut self, name: impl Into<String>, transform: FieldTransformFn) -> Self {
+        self.transforms.insert(name.into(), transform);
+        self
+    }
+
+    /// Apply a schema mapping to a JSON payload
+    ///
+    /// Transforms registered with [`with_transform`](Self::with_transform)
+    /// take precedence over built-in [`FieldTransform`] expressions.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubjectError` if:
+    /// - A source field is missing
+    /// - A target path conflicts with a non-object value
+    /// - A declared transform is unknown or fails
+    pub fn map_payload(
+        &self,
+        mapping: &SchemaMapping,
+        payload: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        apply_mapping(mapping, payload, &self.transforms)
+    }
+
+    fn translate_message(
+        &self,
+        message: NatsMessage,
+        mapping: &SchemaMapping,
+        subject_fn: impl Fn(&Subject) -> Result<Subject>,
+    ) -> Result<NatsMessage> {
+        let subject = subject_fn(&Subject::new(&message.subject)?)?;
+        let payload = self.map_payload(mapping, &message.payload_json()?)?;
+
+        Ok(NatsMessage {
+            subject: subject.to_string(),
+            payload: Payload::Json(payload),
+            headers: message.headers,
+            payload_format: message.payload_format,
+        })
+    }
+}
+
+impl MessageTranslator<NatsMessage, NatsMessage> for JsonMessageTranslator {
+    type Error = SubjectError;
+
+    fn translate(&self, from: NatsMessage) -> Result<NatsMessage> {
+        self.translate_message(from, &self.mapping, |subject| {
+            self.subjects.translate(subject)
+        })
+    }
+
+    fn reverse(&self, to: NatsMessage) -> Result<NatsMessage> {
+        let mapping = self.reverse_mapping.as_ref().ok_or_else(|| {
+            SubjectError::translation_error(format!(
+                "No reverse mapping available for '{}'",
+                self.mapping.name
+            ))
+        })?;
+        self.translate_message(to, mapping, |subject| {
+            self.subjects.reverse_translate(subject)
+        })
+    }
+}
+
+/// Move every mapped field to its target path, applying transforms
+fn apply_mapping(
+    mapping: &SchemaMapping,
+    payload: &serde_json::Value,
+    named: &HashMap<String, FieldTransformFn>,
+) -> Result<serde_json::Value> {
+    let mut source = payload.clone();
+    let mut moved = Vec::with_capacity(mapping.field_mappings.len());
+
+    // Take all mapped values first so swaps and nested moves are safe
+    for field in &mapping.field_mappings {
+        let named_transform = field.transform.as_ref().and_then(|name| named.get(name));
+        let builtin = match named_transform {
+            Some(_) => None,
+            None => field.parsed_transform()?,
+        };
+
+        let value = match remove_path(&mut source, &field.source_path) {
+            Some(value) => value,
+            // Constants need no input
+            None if builtin.as_ref().is_some_and(FieldTransform::is_constant) => {
+                serde_json::Value::Null
+            },
+            None => {
+                return Err(SubjectError::translation_error(format!(
+                    "Field '{}' missing from payload for mapping '{}'",
+                    field.source_path, mapping.name
+                )))
+            },
+        };
+
+        let value = match (named_transform, &builtin) {
+            (Some(transform), _) => transform(value)?,
+            (None, Some(transform)) => transform.apply(value)?,
+            (None, None) => value,
+        };
+        moved.push((&field.target_path, value));
+    }
+
+    for (path, value) in moved {
+        insert_path(&mut source, path, value)?;
+    }
+
+    Ok(source)
+}
+
+/// Remove the value at a dot-separated path, along with any parent objects
+/// left empty by the removal
+fn remove_path(value: &mut serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    let object = value.as_object_mut()?;
+    let Some((key, rest)) = path.split_once('.') else {
+        return object.remove(path);
+    };
+    let child = object.get_mut(key)?;
+    let removed = remove_path(child, rest)?;
+    if child.as_object().is_some_and(serde_json::Map::is_empty) {
+        object.remove(key);
+    }
+    Some(removed)
+}
+
+/// Insert a value at a dot-separated path, creating intermediate objects
+fn insert_path(
+    value: &mut serde_json::Value,
+    path: &str,
+    new_value: serde_json::Value,
+) -> Result<()> {
+    let mut current = value;
+    let mut keys = path.split('.').peekable();
+
+    while let Some(key) = keys.next() {
+        let object = current.as_object_mut().ok_or_else(|| {
+            SubjectError::translation_error(format!(
+                "Cannot insert '{path}': '{key}' has a non-object parent"
+            ))
+        })?;
+
+        if keys.peek().is_none() {
+            object.insert(key.to_string(), new_value);
+            return Ok(());
+        }
+
+        current = object
+            .entry(key.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    }
+
+    Ok(())
+}
+
+/// Header naming the payload's MIME type
+pub const CONTENT_TYPE_HEADER: &str = "Content-Type";
+
+/// A message payload: a JSON value, or raw bytes in the message's
+/// [`PayloadFormat`] or an opaque binary format
+///
+/// Serialized untagged, so JSON payloads keep their plain shape. Formats
+/// without a byte string type, like JSON, serialize raw bytes as an array
+/// of numbers, which reads back as a JSON payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Payload {
+    /// A JSON value
+    Json(serde_json::Value),
+    /// Raw bytes
+    Raw(Bytes),
+}
+
+impl From<serde_json::Value> for Payload {
+    fn from(value: serde_json::Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<Bytes> for Payload {
+    fn from(bytes: Bytes) -> Self {
+        Self::Raw(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Payload {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Raw(bytes.into())
+    }
+}
+
 /// NATS message representation with headers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NatsMessage {
     /// Subject for the message
     pub subject: String,
     /// Message payload
-    pub payload: serde_json::Value,
+    pub payload: Payload,
     /// NATS headers including correlation
-    pub headers: HashMap<String, String>,
+    pub headers: Headers,
+    /// How the payload is encoded on the wire
+    #[serde(default)]
+    pub payload_format: PayloadFormat,
 }
 
 impl NatsMessage {
-    /// Create a new NATS message with correlation headers
+    /// Create a new NATS message with a JSON payload and correlation headers
     #[must_use]
     pub fn with_correlation(
         subject: String,
         payload: serde_json::Value,
         identity: &MessageIdentity,
     ) -> Self {
-        let mut headers = HashMap::new();
+        Self::new_correlated(subject, Payload::Json(payload), identity)
+    }
 
-        // Add correlation headers
-        for (key, value) in identity.to_nats_headers() {
-            headers.insert(key.to_string(), value);
-        }
+    /// Create a new NATS message with a raw payload and correlation headers
+    ///
+    /// The payload is taken to be in `format`, which is also recorded in
+    /// the `Content-Type` header.
+    #[must_use]
+    pub fn with_bytes(
+        subject: String,
+        payload: impl Into<Bytes>,
+        format: PayloadFormat,
+        identity: &MessageIdentity,
+    ) -> Self {
+        Self::new_correlated(subject, Payload::Raw(payload.into()), identity).with_format(format)
+    }
+
+    /// Create a message as received from NATS, taking the payload format
+    /// from the `Content-Type` header
+    ///
+    /// Without a recognised content type the format is JSON.
+    #[must_use]
+    pub fn from_wire(
+        subject: String,
+        payload: impl Into<Bytes>,
+        headers: impl Into<Headers>,
+    ) -> Self {
+        let mut message = Self {
+            subject,
+            payload: Payload::Raw(payload.into()),
+            headers: headers.into(),
+            payload_format: PayloadFormat::Json,
+        };
+        message.payload_format = message
+            .content_type()
+            .and_then(PayloadFormat::from_content_type)
+            .unwrap_or_default();
+        message
+    }
+
+    fn new_correlated(subject: String, payload: Payload, identity: &MessageIdentity) -> Self {
+        let mut headers = Headers::new();
+        headers.set_identity(identity);
 
         Self {
             subject,
             payload,
             headers,
+            payload_format: PayloadFormat::Json,
+        }
+    }
+
+    /// Encode the payload as `format` on the wire, recording it in the
+    /// `Content-Type` header
+    #[must_use]
+    pub fn with_format(mut self, format: PayloadFormat) -> Self {
+        self.payload_format = format;
+        self.headers.put(
+            CONTENT_TYPE_HEADER.to_string(),
+            format.content_type().to_string(),
+        );
+        self
+    }
+
+    /// The `Content-Type` header, matched case-insensitively
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(CONTENT_TYPE_HEADER)
+    }
+
+    /// The payload as a `T`, decoding raw bytes in the message's format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is not a valid `T`, or its format is
+    /// not available in this build
+    pub fn payload_json<T: DeserializeOwned>(&self) -> Result<T> {
+        match &self.payload {
+            Payload::Json(value) => T::deserialize(value).map_err(|e| {
+                SubjectError::parse_error(format!("Invalid payload for '{}': {e}", self.subject))
+            }),
+            Payload::Raw(bytes) => self.payload_format.decode(bytes),
+        }
+    }
+
+    /// The payload as bytes on the wire, encoding JSON payloads in the
+    /// message's format
+    ///
+    /// # Errors
+    ///
+    /// Returns a translation error if the format is not available in this
+    /// build
+    pub fn payload_raw(&self) -> Result<Bytes> {
+        match &self.payload {
+            Payload::Json(value) => self.payload_format.encode(value).map(Bytes::from),
+            Payload::Raw(bytes) => Ok(bytes.clone()),
         }
     }
+
+    /// The payload encoded in the message's format
+    ///
+    /// # Errors
+    ///
+    /// Returns a translation error if the format is not available in this
+    /// build
+    pub fn encode_payload(&self) -> Result<Vec<u8>> {
+        self.payload_raw().map(Vec::from)
+    }
+
+    /// Decode `bytes` in `format` into a JSON payload
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format is not available or the bytes are
+    /// not a valid encoding
+    pub fn decode_payload(&mut self, bytes: &[u8], format: PayloadFormat) -> Result<()> {
+        self.payload = Payload::Json(format.decode(bytes)?);
+        self.payload_format = format;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -516,6 +1057,210 @@ mod tests {
         assert_eq!(back.as_str(), internal.as_str());
     }
 
+    #[test]
+    fn test_json_message_translation() {
+        let subjects = TranslatorBuilder::new()
+            .translate_context("crm", "sales")
+            .unwrap()
+            .build();
+        let mapping = SchemaMapping {
+            name: "crm_to_sales".to_string(),
+            source_schema: "crm.customer.v1".to_string(),
+            target_schema: "sales.buyer.v1".to_string(),
+            field_mappings: vec![
+                FieldMapping {
+                    source_path: "customer.name".to_string(),
+                    target_path: "buyer.full_name".to_string(),
+                    transform: Some("upper".to_string()),
+                },
+                FieldMapping {
+                    source_path: "id".to_string(),
+                    target_path: "buyer.id".to_string(),
+                    transform: None,
+                },
+            ],
+        };
+        let translator = JsonMessageTranslator::new(subjects, mapping).with_transform(
+            "upper",
+            Arc::new(|value| {
+                Ok(serde_json::Value::String(
+                    value.as_str().unwrap_or_default().to_uppercase(),
+                ))
+            }),
+        );
+
+        let message = NatsMessage {
+            subject: "crm.customer.registered.v1".to_string(),
+            payload: serde_json::json!({
+                "id": 42,
+                "customer": { "name": "ada" },
+                "source": "web"
+            })
+            .into(),
+            headers: Headers::new(),
+            payload_format: PayloadFormat::Json,
+        };
+
+        let translated = MessageTranslator::translate(&translator, message.clone()).unwrap();
+        assert_eq!(translated.subject, "sales.customer.registered.v1");
+        assert_eq!(
+            translated.payload,
+            Payload::Json(serde_json::json!({
+                "buyer": { "id": 42, "full_name": "ADA" },
+                "source": "web"
+            }))
+        );
+
+        // No reverse mapping registered
+        assert!(translator.reverse(translated).is_err());
+    }
+
+    #[test]
+    fn test_remove_path_prunes_empty_parents() {
+        let mut value = serde_json::json!({
+            "a": { "b": { "c": 1 }, "keep": true },
+            "x": { "y": { "z": 2 } },
+            "empty": {}
+        });
+        assert_eq!(remove_path(&mut value, "a.b.c"), Some(serde_json::json!(1)));
+        assert_eq!(remove_path(&mut value, "x.y.z"), Some(serde_json::json!(2)));
+        assert_eq!(remove_path(&mut value, "x.missing"), None);
+        assert_eq!(
+            value,
+            serde_json::json!({ "a": { "keep": true }, "empty": {} })
+        );
+    }
+
+    #[test]
+    fn test_schema_mapping_builtin_transforms() {
+        let field = |source: &str, target: &str, transform: &str| FieldMapping {
+            source_path: source.to_string(),
+            target_path: target.to_string(),
+            transform: Some(transform.to_string()),
+        };
+        let mapping = SchemaMapping {
+            name: "legacy_to_v2".to_string(),
+            source_schema: "orders.order.v1".to_string(),
+            target_schema: "orders.order.v2".to_string(),
+            field_mappings: vec![
+                field("status", "status", "lowercase | rename(open=pending)"),
+                field("created", "created_at", "timestamp(seconds->rfc3339)"),
+                field("schema", "schema", "const(2)"),
+            ],
+        };
+
+        let mapped = mapping
+            .apply(&serde_json::json!({ "status": "OPEN", "created": 0 }))
+            .unwrap();
+        assert_eq!(
+            mapped,
+            serde_json::json!({
+                "status": "pending",
+                "created_at": "1970-01-01T00:00:00Z",
+                "schema": 2
+            })
+        );
+
+        // Case folding and constants cannot be inverted
+        let err = mapping.invert().unwrap_err();
+        assert_eq!(err.fields.len(), 2);
+        assert_eq!(err.fields[0].source_path, "status");
+        assert_eq!(err.fields[1].source_path, "schema");
+
+        // Unknown expressions are rejected rather than silently ignored
+        let mut broken = mapping.clone();
+        broken.field_mappings[0].transform = Some("upper".to_string());
+        assert!(broken
+            .apply(&serde_json::json!({ "status": "OPEN", "created": 0 }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_bidirectional_mapping_round_trip() {
+        let subjects = TranslatorBuilder::new()
+            .translate_context("crm", "sales")
+            .unwrap()
+            .build();
+        let mapping = SchemaMapping {
+            name: "crm_to_sales".to_string(),
+            source_schema: "crm.customer.v1".to_string(),
+            target_schema: "sales.buyer.v1".to_string(),
+            field_mappings: vec![
+                FieldMapping {
+                    source_path: "tier".to_string(),
+                    target_path: "buyer.level".to_string(),
+                    transform: Some("rename(gold=premium, silver=standard)".to_string()),
+                },
+                FieldMapping {
+                    source_path: "joined".to_string(),
+                    target_path: "buyer.since".to_string(),
+                    transform: Some("timestamp(seconds->rfc3339)".to_string()),
+                },
+            ],
+        };
+
+        let inverse = mapping.invert().unwrap();
+        assert_eq!(inverse.source_schema, "sales.buyer.v1");
+        assert_eq!(inverse.field_mappings[0].source_path, "buyer.level");
+
+        let translator = JsonMessageTranslator::bidirectional(subjects, mapping).unwrap();
+        let message = NatsMessage {
+            subject: "crm.customer.upgraded.v1".to_string(),
+            payload: serde_json::json!({ "tier": "gold", "joined": 1_737_540_000 }).into(),
+            headers: Headers::new(),
+            payload_format: PayloadFormat::Json,
+        };
+
+        let forward = MessageTranslator::translate(&translator, message.clone()).unwrap();
+        assert_eq!(
+            forward.payload,
+            Payload::Json(serde_json::json!({
+                "buyer": { "level": "premium", "since": "2025-01-22T10:00:00Z" }
+            }))
+        );
+
+        let back = translator.reverse(forward).unwrap();
+        let back: serde_json::Value = back.payload_json().unwrap();
+        assert_eq!(back["tier"], "gold");
+        assert_eq!(back["joined"], 1_737_540_000);
+    }
+
+    #[test]
+    fn test_raw_payloads() {
+        let identity =
+            MessageIdentity::root(crate::correlation::IdType::Uuid(uuid::Uuid::new_v4()));
+        let message = NatsMessage::with_bytes(
+            "orders.order.placed.v1".to_string(),
+            br#"{"order_id":7}"#.to_vec(),
+            PayloadFormat::Json,
+            &identity,
+        );
+        assert_eq!(message.content_type(), Some("application/json"));
+        let payload: serde_json::Value = message.payload_json().unwrap();
+        assert_eq!(payload["order_id"], 7);
+
+        // Opaque binary payloads are passed through untouched
+        let image = NatsMessage::from_wire(
+            "media.image.uploaded.v1".to_string(),
+            vec![0x89, 0x50],
+            [("content-type", "image/png")]
+                .into_iter()
+                .collect::<Headers>(),
+        );
+        assert_eq!(image.payload_raw().unwrap().as_ref(), [0x89, 0x50]);
+        assert!(image.payload_json::<serde_json::Value>().is_err());
+
+        let json = NatsMessage::with_correlation(
+            "orders.order.placed.v1".to_string(),
+            serde_json::json!({"order_id": 7}),
+            &identity,
+        );
+        assert_eq!(json.payload_raw().unwrap().as_ref(), br#"{"order_id":7}"#);
+        // The JSON shape of a message is unchanged
+        let serialized = serde_json::to_value(&json).unwrap();
+        assert_eq!(serialized["payload"]["order_id"], 7);
+    }
+
     #[test]
     fn test_no_matching_rule() {
         let translator = TranslatorBuilder::new()
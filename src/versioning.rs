@@ -0,0 +1,280 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Subject version negotiation and upgrade planning
+//!
+//! Subjects end in a version token following the `vN` convention. A
+//! [`VersionPolicy`] records which versions each event family (context,
+//! aggregate and event type) supports, answers which is the latest or which
+//! a consumer and producer both understand, and generates the
+//! [`TranslationRule`]s that upgrade older subjects to the latest version.
+//!
+//! ```
+//! use cim_subject::versioning::VersionPolicy;
+//! use cim_subject::Subject;
+//!
+//! let policy = VersionPolicy::new()
+//!     .support("orders", "order", "created", [1, 2, 3])
+//!     .support("orders", "order", "shipped", [1]);
+//! assert_eq!(policy.latest("orders", "order", "created"), Some(3));
+//! assert_eq!(
+//!     policy.negotiate("orders", "order", "created", &[1, 2]),
+//!     Some(2)
+//! );
+//!
+//! let translator = policy.upgrade_translator()?;
+//! let old = Subject::new("orders.order.created.v1")?;
+//! assert_eq!(
+//!     translator.translate(&old)?.as_str(),
+//!     "orders.order.created.v3"
+//! );
+//! # Ok::<(), cim_subject::SubjectError>(())
+//! ```
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::pattern::Pattern;
+use crate::subject::Subject;
+use crate::translator::{
+    TranslationRule,
+    Translator,
+};
+
+/// Parse a `vN` version token
+///
+/// ```
+/// use cim_subject::versioning::parse_version;
+///
+/// assert_eq!(parse_version("v12"), Some(12));
+/// assert_eq!(parse_version("beta"), None);
+/// ```
+#[must_use]
+pub fn parse_version(token: &str) -> Option<u32> {
+    let digits = token.strip_prefix('v')?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// The `vN` token for a version
+#[must_use]
+pub fn version_token(version: u32) -> String {
+    format!("v{version}")
+}
+
+/// An event family: subjects sharing context, aggregate and event type
+type Family = (String, String, String);
+
+/// Supported versions per event family
+#[derive(Debug, Clone, Default)]
+pub struct VersionPolicy {
+    families: BTreeMap<Family, BTreeSet<u32>>,
+}
+
+impl VersionPolicy {
+    /// Create an empty policy
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare versions an event family supports, adding to any declared
+    /// before
+    #[must_use]
+    pub fn support(
+        mut self,
+        context: impl Into<String>,
+        aggregate: impl Into<String>,
+        event_type: impl Into<String>,
+        versions: impl IntoIterator<Item = u32>,
+    ) -> Self {
+        self.families
+            .entry((context.into(), aggregate.into(), event_type.into()))
+            .or_default()
+            .extend(versions);
+        self
+    }
+
+    /// Versions an event family supports, oldest first
+    #[must_use]
+    pub fn versions(&self, context: &str, aggregate: &str, event_type: &str) -> Vec<u32> {
+        self.family(context, aggregate, event_type)
+            .map(|versions| versions.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The newest version of an event family
+    #[must_use]
+    pub fn latest(&self, context: &str, aggregate: &str, event_type: &str) -> Option<u32> {
+        self.family(context, aggregate, event_type)?.last().copied()
+    }
+
+    /// The newest version of an event family that a peer also accepts
+    #[must_use]
+    pub fn negotiate(
+        &self,
+        context: &str,
+        aggregate: &str,
+        event_type: &str,
+        accepted: &[u32],
+    ) -> Option<u32> {
+        self.family(context, aggregate, event_type)?
+            .iter()
+            .rev()
+            .find(|version| accepted.contains(version))
+            .copied()
+    }
+
+    /// Check if a subject's family is declared and its version supported
+    #[must_use]
+    pub fn is_supported(&self, subject: &Subject) -> bool {
+        let (Some(versions), Some(version)) = (
+            self.subject_family(subject),
+            parse_version(subject.version()),
+        ) else {
+            return false;
+        };
+        versions.contains(&version)
+    }
+
+    /// The subject at its family's latest version, if it is a supported
+    /// older version
+    #[must_use]
+    pub fn upgrade(&self, subject: &Subject) -> Option<Subject> {
+        let versions = self.subject_family(subject)?;
+        let version = parse_version(subject.version())?;
+        let latest = *versions.last()?;
+        (version < latest && versions.contains(&version))
+            .then(|| subject.with_version(version_token(latest)))
+    }
+
+    /// Rules upgrading every supported older version to its family's latest
+    ///
+    /// Rules are named `upgrade.<context>.<aggregate>.<event_type>.v<N>`.
+    /// They have no reverse, since every older version of a family maps
+    /// onto the same subject.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a declared family has tokens that cannot form a
+    /// subject
+    pub fn upgrade_rules(&self) -> Result<Vec<TranslationRule>> {
+        let mut rules = Vec::new();
+        for ((context, aggregate, event_type), versions) in &self.families {
+            let Some(&latest) = versions.last() else {
+                continue;
+            };
+            let family = format!("{context}.{aggregate}.{event_type}");
+            let latest_token = version_token(latest);
+            let target = Pattern::new(format!("{family}.{latest_token}"))?;
+
+            for &version in versions.range(..latest) {
+                let source = Pattern::new(format!("{family}.{}", version_token(version)))?;
+                let latest_token = latest_token.clone();
+                let name = format!("upgrade.{family}.{}", version_token(version));
+                rules.push(
+                    TranslationRule::new(
+                        name,
+                        source,
+                        Arc::new(move |subject: &Subject| {
+                            Ok(subject.with_version(latest_token.clone()))
+                        }),
+                    )
+                    .with_target_pattern(target.clone()),
+                );
+            }
+        }
+        Ok(rules)
+    }
+
+    /// A translator applying [`upgrade_rules`](Self::upgrade_rules)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a declared family has tokens that cannot form a
+    /// subject
+    pub fn upgrade_translator(&self) -> Result<Translator> {
+        let translator = Translator::new();
+        for rule in self.upgrade_rules()? {
+            translator.register_rule(rule.name.clone(), rule);
+        }
+        Ok(translator)
+    }
+
+    fn family(&self, context: &str, aggregate: &str, event_type: &str) -> Option<&BTreeSet<u32>> {
+        self.families
+            .get(&(
+                context.to_string(),
+                aggregate.to_string(),
+                event_type.to_string(),
+            ))
+            .filter(|versions| !versions.is_empty())
+    }
+
+    fn subject_family(&self, subject: &Subject) -> Option<&BTreeSet<u32>> {
+        self.family(subject.context(), subject.aggregate(), subject.event_type())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> VersionPolicy {
+        VersionPolicy::new()
+            .support("orders", "order", "created", [3, 1])
+            .support("orders", "order", "created", [2])
+            .support("orders", "order", "shipped", [1])
+    }
+
+    #[test]
+    fn test_versions_and_negotiation() {
+        let policy = policy();
+        assert_eq!(policy.versions("orders", "order", "created"), vec![1, 2, 3]);
+        assert_eq!(policy.latest("orders", "order", "shipped"), Some(1));
+        assert_eq!(policy.latest("orders", "order", "cancelled"), None);
+        assert_eq!(
+            policy.negotiate("orders", "order", "created", &[1, 2, 4]),
+            Some(2)
+        );
+        assert_eq!(policy.negotiate("orders", "order", "created", &[4]), None);
+
+        let v2 = Subject::new("orders.order.created.v2").unwrap();
+        assert!(policy.is_supported(&v2));
+        assert!(!policy.is_supported(&v2.with_version("v4")));
+        assert_eq!(policy.upgrade(&v2).unwrap().version(), "v3");
+        assert!(policy.upgrade(&v2.with_version("v3")).is_none());
+        assert_eq!(parse_version("v"), None);
+        assert_eq!(parse_version("v+1"), None);
+    }
+
+    #[test]
+    fn test_upgrade_translator() {
+        let policy = policy();
+        let mut names: Vec<String> = policy
+            .upgrade_rules()
+            .unwrap()
+            .into_iter()
+            .map(|rule| rule.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![
+            "upgrade.orders.order.created.v1",
+            "upgrade.orders.order.created.v2"
+        ]);
+
+        let translator = policy.upgrade_translator().unwrap();
+        for old in ["orders.order.created.v1", "orders.order.created.v2"] {
+            let upgraded = translator.translate(&Subject::new(old).unwrap()).unwrap();
+            assert_eq!(upgraded.as_str(), "orders.order.created.v3");
+        }
+        // Subjects already at their latest version pass through unchanged
+        let shipped = Subject::new("orders.order.shipped.v1").unwrap();
+        assert_eq!(translator.translate(&shipped).unwrap(), shipped);
+    }
+}
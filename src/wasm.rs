@@ -0,0 +1,243 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! JavaScript bindings for browsers
+//!
+//! With the `wasm` feature, `wasm-bindgen` exports `Subject`, `Pattern`
+//! and `Permissions` classes so admin UIs can validate subjects and preview
+//! permission decisions client-side with the same rules the services use.
+//! Permission sets cross the boundary as the JSON their serde form produces,
+//! and operations are named like their variants: `"Publish"`,
+//! `"Subscribe"`, `"Request"`, `"QueueSubscribe"` or `"All"`.
+//!
+//! ```js
+//! import { Subject, Pattern, Permissions } from "cim-subject";
+//!
+//! const subject = new Subject("orders.order.placed.v1");
+//! new Pattern("orders.*.placed.>").matches(subject); // true
+//!
+//! const permissions = Permissions.fromJson(json);
+//! permissions.isAllowed(subject, "Publish");
+//! ```
+
+use wasm_bindgen::prelude::{
+    wasm_bindgen,
+    JsError,
+};
+
+use crate::pattern::Pattern;
+use crate::permissions::{
+    Operation,
+    Permissions,
+};
+use crate::subject::Subject;
+
+/// A validated subject
+#[wasm_bindgen(js_name = Subject)]
+#[derive(Debug, Clone)]
+pub struct JsSubject {
+    inner: Subject,
+}
+
+#[wasm_bindgen(js_class = Subject)]
+impl JsSubject {
+    /// Parse and validate a subject
+    ///
+    /// # Errors
+    ///
+    /// Throws if the subject is invalid
+    #[wasm_bindgen(constructor)]
+    pub fn new(subject: &str) -> Result<JsSubject, JsError> {
+        Ok(Self {
+            inner: Subject::new(subject)?,
+        })
+    }
+
+    /// Whether a string is a valid subject, without throwing
+    #[wasm_bindgen(js_name = isValid)]
+    #[must_use]
+    pub fn is_valid(subject: &str) -> bool {
+        Subject::new(subject).is_ok()
+    }
+
+    /// The bounded context token
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn context(&self) -> String {
+        self.inner.context().to_string()
+    }
+
+    /// The aggregate token
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn aggregate(&self) -> String {
+        self.inner.aggregate().to_string()
+    }
+
+    /// The event type token
+    #[wasm_bindgen(getter, js_name = eventType)]
+    #[must_use]
+    pub fn event_type(&self) -> String {
+        self.inner.event_type().to_string()
+    }
+
+    /// The version token
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn version(&self) -> String {
+        self.inner.version().to_string()
+    }
+
+    /// The subject string
+    #[wasm_bindgen(js_name = toString)]
+    #[must_use]
+    pub fn to_js_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// A validated subject pattern
+#[wasm_bindgen(js_name = Pattern)]
+#[derive(Debug, Clone)]
+pub struct JsPattern {
+    inner: Pattern,
+}
+
+#[wasm_bindgen(js_class = Pattern)]
+impl JsPattern {
+    /// Parse and validate a pattern
+    ///
+    /// # Errors
+    ///
+    /// Throws if the pattern is invalid
+    #[wasm_bindgen(constructor)]
+    pub fn new(pattern: &str) -> Result<JsPattern, JsError> {
+        Ok(Self {
+            inner: Pattern::new(pattern)?,
+        })
+    }
+
+    /// Whether the pattern matches a subject
+    #[must_use]
+    pub fn matches(&self, subject: &JsSubject) -> bool {
+        self.inner.matches(&subject.inner)
+    }
+
+    /// Whether the pattern matches a subject string
+    ///
+    /// # Errors
+    ///
+    /// Throws if the string is not a valid subject
+    #[wasm_bindgen(js_name = matchesStr)]
+    pub fn matches_str(&self, subject: &str) -> Result<bool, JsError> {
+        Ok(self.inner.matches(&Subject::new(subject)?))
+    }
+
+    /// The pattern string
+    #[wasm_bindgen(js_name = toString)]
+    #[must_use]
+    pub fn to_js_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// A permission set loaded from JSON
+#[wasm_bindgen(js_name = Permissions)]
+#[derive(Debug, Clone)]
+pub struct JsPermissions {
+    inner: Permissions,
+}
+
+#[wasm_bindgen(js_class = Permissions)]
+impl JsPermissions {
+    /// Load a permission set from its JSON form
+    ///
+    /// # Errors
+    ///
+    /// Throws if the JSON does not describe a permission set
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<JsPermissions, JsError> {
+        Ok(Self {
+            inner: serde_json::from_str(json)?,
+        })
+    }
+
+    /// The permission set as JSON
+    ///
+    /// # Errors
+    ///
+    /// Throws if serialization fails
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.inner)?)
+    }
+
+    /// Whether an operation is allowed on a subject
+    ///
+    /// # Errors
+    ///
+    /// Throws if the operation name is unknown
+    #[wasm_bindgen(js_name = isAllowed)]
+    pub fn is_allowed(&self, subject: &JsSubject, operation: &str) -> Result<bool, JsError> {
+        Ok(self
+            .inner
+            .is_allowed(&subject.inner, parse_operation(operation)?))
+    }
+}
+
+/// Parse an operation by its variant name
+fn parse_operation(operation: &str) -> serde_json::Result<Operation> {
+    serde_json::from_value(serde_json::Value::String(operation.to_string()))
+}
+
+impl From<Subject> for JsSubject {
+    fn from(inner: Subject) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<Pattern> for JsPattern {
+    fn from(inner: Pattern) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<Permissions> for JsPermissions {
+    fn from(inner: Permissions) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{
+        PermissionRule,
+        Policy,
+    };
+
+    // Error paths build JS values, which only works on wasm targets
+
+    #[test]
+    fn test_bindings_match_native_types() {
+        let subject = JsSubject::new("orders.order.placed.v1").unwrap();
+        assert_eq!(subject.event_type(), "placed");
+        assert_eq!(subject.to_js_string(), "orders.order.placed.v1");
+        assert!(!JsSubject::is_valid("orders.order"));
+
+        let pattern = JsPattern::new("orders.*.placed.>").unwrap();
+        assert!(pattern.matches(&subject));
+        assert!(!pattern.matches_str("orders.order.shipped.v1").unwrap());
+
+        let mut permissions = Permissions::new(Policy::Deny);
+        permissions.add_rule(PermissionRule::new(
+            Pattern::new("orders.>").unwrap(),
+            [Operation::Publish].into_iter().collect(),
+            Policy::Allow,
+        ));
+        let json = JsPermissions::from(permissions).to_json().unwrap();
+        let permissions = JsPermissions::from_json(&json).unwrap();
+        assert!(permissions.is_allowed(&subject, "Publish").unwrap());
+        assert!(!permissions.is_allowed(&subject, "Subscribe").unwrap());
+        assert!(parse_operation("Delete").is_err());
+    }
+}
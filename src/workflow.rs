@@ -0,0 +1,287 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Workflow builder for saga-style orchestration
+//!
+//! A [`WorkflowBuilder`] describes an orchestration as steps, parallel
+//! branches and choices. Building it yields a [`Workflow`] holding both the
+//! [`AlgebraExpr`] for the whole composition and the concrete step subjects
+//! in the order they are declared, each with its optional compensation.
+//!
+//! ```
+//! use cim_subject::{
+//!     Subject,
+//!     WorkflowBuilder,
+//! };
+//!
+//! let workflow = WorkflowBuilder::new()
+//!     .step(Subject::new("orders.commands.order.validate").unwrap())
+//!     .step(Subject::new("inventory.commands.stock.reserve").unwrap())
+//!     .compensate(Subject::new("inventory.commands.stock.release").unwrap())
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(workflow.steps().len(), 2);
+//! assert!(workflow.steps()[1].compensation.is_some());
+//! ```
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::algebra::SubjectAlgebra;
+use crate::algebra_expr::AlgebraExpr;
+use crate::error::{
+    Result,
+    SubjectError,
+};
+use crate::subject::Subject;
+
+/// A concrete step of a workflow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Subject published for this step
+    pub subject: Subject,
+    /// Subject that undoes this step, if any
+    pub compensation: Option<Subject>,
+}
+
+/// A built workflow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    expr: AlgebraExpr,
+    steps: Vec<WorkflowStep>,
+}
+
+impl Workflow {
+    /// The composition of all steps
+    #[must_use]
+    pub fn expr(&self) -> &AlgebraExpr {
+        &self.expr
+    }
+
+    /// Steps in declaration order
+    #[must_use]
+    pub fn steps(&self) -> &[WorkflowStep] {
+        &self.steps
+    }
+
+    /// Step subjects in declaration order
+    #[must_use]
+    pub fn subjects(&self) -> Vec<&Subject> {
+        self.steps.iter().map(|step| &step.subject).collect()
+    }
+
+    /// The compensation registered for a step subject
+    #[must_use]
+    pub fn compensation_for(&self, subject: &Subject) -> Option<&Subject> {
+        self.steps
+            .iter()
+            .find(|step| &step.subject == subject)
+            .and_then(|step| step.compensation.as_ref())
+    }
+
+    /// Evaluate the workflow's composition
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any composition in the expression fails
+    pub fn evaluate(&self, algebra: &SubjectAlgebra) -> Result<Subject> {
+        self.expr.evaluate(algebra)
+    }
+
+    /// Split into the expression and the steps
+    #[must_use]
+    pub fn into_parts(self) -> (AlgebraExpr, Vec<WorkflowStep>) {
+        (self.expr, self.steps)
+    }
+}
+
+/// Builder for [`Workflow`]s
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowBuilder {
+    stages: Vec<AlgebraExpr>,
+    steps: Vec<WorkflowStep>,
+    error: Option<SubjectError>,
+}
+
+impl WorkflowBuilder {
+    /// Create an empty workflow builder
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step that runs after all previous stages
+    #[must_use]
+    pub fn step(mut self, subject: Subject) -> Self {
+        self.stages.push(AlgebraExpr::subject(subject.clone()));
+        self.push_step(subject);
+        self
+    }
+
+    /// Add a stage whose steps run concurrently
+    #[must_use]
+    pub fn parallel(mut self, subjects: impl IntoIterator<Item = Subject>) -> Self {
+        let branches: Vec<AlgebraExpr> = subjects
+            .into_iter()
+            .map(|subject| {
+                self.push_step(subject.clone());
+                AlgebraExpr::subject(subject)
+            })
+            .collect();
+        if branches.is_empty() {
+            self.fail("A parallel stage needs at least one step");
+        } else {
+            self.stages.push(AlgebraExpr::Parallel(branches));
+        }
+        self
+    }
+
+    /// Add a stage that runs `when_true` if the condition holds, otherwise
+    /// `otherwise`
+    ///
+    /// Both branches are listed as steps, `when_true` first.
+    #[must_use]
+    pub fn choice(
+        mut self,
+        condition: impl Into<String>,
+        when_true: Subject,
+        otherwise: Subject,
+    ) -> Self {
+        self.stages.push(
+            AlgebraExpr::subject(when_true.clone())
+                .or_else(AlgebraExpr::subject(otherwise.clone()), condition),
+        );
+        self.push_step(when_true);
+        self.push_step(otherwise);
+        self
+    }
+
+    /// Register a compensation for the most recently added step
+    #[must_use]
+    pub fn compensate(mut self, compensation: Subject) -> Self {
+        match self.steps.last_mut() {
+            Some(step) if step.compensation.is_none() => step.compensation = Some(compensation),
+            Some(step) => {
+                let message = format!("Step '{}' already has a compensation", step.subject);
+                self.fail(message);
+            },
+            None => self.fail("Cannot compensate before adding a step"),
+        }
+        self
+    }
+
+    /// Build the workflow
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No steps were added
+    /// - A parallel stage was empty
+    /// - A compensation was added before any step, or twice for one step
+    pub fn build(self) -> Result<Workflow> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.steps.is_empty() {
+            return Err(SubjectError::validation_error(
+                "A workflow needs at least one step",
+            ));
+        }
+
+        let mut stages = self.stages;
+        let expr = if stages.len() == 1 {
+            stages.remove(0)
+        } else {
+            AlgebraExpr::Sequence(stages)
+        };
+        Ok(Workflow {
+            expr,
+            steps: self.steps,
+        })
+    }
+
+    fn push_step(&mut self, subject: Subject) {
+        self.steps.push(WorkflowStep {
+            subject,
+            compensation: None,
+        });
+    }
+
+    fn fail(&mut self, message: impl Into<String>) {
+        self.error
+            .get_or_insert_with(|| SubjectError::validation_error(message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(s: &str) -> Subject {
+        Subject::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_workflow_expr_and_steps() {
+        let workflow = WorkflowBuilder::new()
+            .step(subject("orders.commands.order.validate"))
+            .parallel([
+                subject("inventory.commands.stock.reserve"),
+                subject("payments.commands.payment.authorize"),
+            ])
+            .compensate(subject("payments.commands.payment.void"))
+            .choice(
+                "express",
+                subject("shipping.commands.parcel.express"),
+                subject("shipping.commands.parcel.standard"),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            workflow.expr().to_string(),
+            "(orders.commands.order.validate → (inventory.commands.stock.reserve ⊗ \
+             payments.commands.payment.authorize) → (shipping.commands.parcel.express \
+             +[express] shipping.commands.parcel.standard))"
+        );
+        let subjects: Vec<&str> = workflow
+            .subjects()
+            .into_iter()
+            .map(Subject::as_str)
+            .collect();
+        assert_eq!(subjects, vec![
+            "orders.commands.order.validate",
+            "inventory.commands.stock.reserve",
+            "payments.commands.payment.authorize",
+            "shipping.commands.parcel.express",
+            "shipping.commands.parcel.standard",
+        ]);
+        assert_eq!(
+            workflow.compensation_for(&subject("payments.commands.payment.authorize")),
+            Some(&subject("payments.commands.payment.void"))
+        );
+        assert!(workflow
+            .compensation_for(&subject("inventory.commands.stock.reserve"))
+            .is_none());
+        assert!(workflow.evaluate(&SubjectAlgebra::new()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_workflows() {
+        assert!(WorkflowBuilder::new().build().is_err());
+        assert!(WorkflowBuilder::new()
+            .compensate(subject("orders.commands.order.cancel"))
+            .step(subject("orders.commands.order.place"))
+            .build()
+            .is_err());
+        assert!(WorkflowBuilder::new()
+            .step(subject("orders.commands.order.place"))
+            .compensate(subject("orders.commands.order.cancel"))
+            .compensate(subject("orders.commands.order.void"))
+            .build()
+            .is_err());
+        assert!(WorkflowBuilder::new().parallel(Vec::new()).build().is_err());
+    }
+}
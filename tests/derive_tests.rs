@@ -0,0 +1,69 @@
+// Copyright 2025 Cowboy AI, LLC.
+
+//! Derive Tests for CIM Subject
+//!
+//! Tests for `#[derive(SubjectBound)]`
+//!
+//! ## Derive Flow
+//! ```mermaid
+//! graph TD
+//!     A[Struct + subject attribute] --> B[SubjectBound impl]
+//!     B --> C[Subject from fields]
+//!     B --> D[Pattern from template]
+//!     B --> E[Typed identities]
+//! ```
+
+#![cfg(feature = "derive")]
+
+use cim_subject::bound::SubjectBound;
+use cim_subject::{
+    MessageKind,
+    Subject,
+};
+use uuid::Uuid;
+
+#[derive(SubjectBound)]
+#[subject(template = "orders.{aggregate}.created.{version}", kind = "event")]
+struct OrderCreated {
+    aggregate: String,
+    version: &'static str,
+    #[allow(dead_code)]
+    total: u64,
+}
+
+#[derive(SubjectBound)]
+#[subject(template = "orders.order.cancel.v1", kind = "command")]
+struct CancelOrder;
+
+#[test]
+fn test_derived_subject_and_pattern() {
+    let event = OrderCreated {
+        aggregate: "order".to_string(),
+        version: "v2",
+        total: 10,
+    };
+    let subject = event.subject().unwrap();
+    assert_eq!(subject, Subject::new("orders.order.created.v2").unwrap());
+    assert_eq!(OrderCreated::pattern().as_str(), "orders.*.created.*");
+    assert!(OrderCreated::pattern().matches(&subject));
+
+    let invalid = OrderCreated {
+        aggregate: "two words".to_string(),
+        ..event
+    };
+    assert!(invalid.subject().is_err());
+}
+
+#[test]
+fn test_derived_identity_kind() {
+    assert_eq!(CancelOrder::TEMPLATE, "orders.order.cancel.v1");
+    assert_eq!(
+        CancelOrder.subject().unwrap().as_str(),
+        "orders.order.cancel.v1"
+    );
+
+    let root = CancelOrder::root_identity(Uuid::new_v4());
+    assert_eq!(root.kind, Some(MessageKind::Command));
+    let next = CancelOrder::caused_identity(Uuid::new_v4(), &root);
+    assert_eq!(next.correlation_id, root.correlation_id);
+}